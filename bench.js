@@ -24,7 +24,7 @@ async function run() {
   console.time('parcel-css');
   let res = css.transform(opts);
   console.timeEnd('parcel-css');
-  console.log(res.length + ' bytes');
+  console.log(res.code.length + ' bytes');
 }
 
 async function doCssNano() {
@@ -25,6 +25,10 @@ for (let prop in prefixes) {
       continue;
     }
     let prefix = browsers[name].prefix_exceptions?.[version] || browsers[name].prefix;
+    // There is no -ms-appearance, Edge and IE use -webkit-appearance (same as autoprefixer's hack).
+    if (prop === 'appearance' && prefix === 'ms') {
+      prefix = 'webkit';
+    }
     name = BROWSER_MAPPING[name] || name;
     let v = parseVersion(version);
     if (v == null) {
@@ -89,7 +93,7 @@ let s = `// This file is autogenerated by build-prefixes.js. DO NOT EDIT!
 use serde::{Deserialize, Serialize};
 use super::VendorPrefix;
 
-#[derive(Serialize, Debug, Deserialize, Clone, Copy, Default)]
+#[derive(Serialize, Debug, Deserialize, Clone, Copy, Default, Hash)]
 pub struct Browsers {
   pub ${Object.keys(browsers).filter(b => !(b in BROWSER_MAPPING)).sort().join(': Option<u32>,\n  pub ')}: Option<u32>
 }
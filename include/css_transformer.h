@@ -0,0 +1,55 @@
+#ifndef CSS_TRANSFORMER_H
+#define CSS_TRANSFORMER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Browser versions are encoded as major << 16 | minor << 8 | patch, or 0 to ignore the browser. */
+typedef struct {
+  uint32_t android;
+  uint32_t chrome;
+  uint32_t edge;
+  uint32_t firefox;
+  uint32_t ie;
+  uint32_t ios_saf;
+  uint32_t opera;
+  uint32_t safari;
+  uint32_t samsung;
+} CssTargets;
+
+typedef struct {
+  const char *filename;
+  bool minify;
+  bool source_map;
+  const CssTargets *targets;
+} CssTransformOptions;
+
+typedef struct {
+  char *message;
+  uint32_t line;
+  uint32_t column;
+} CssWarning;
+
+typedef struct {
+  char *code;
+  char *map;
+  CssWarning *warnings;
+  size_t warnings_len;
+} CssTransformResult;
+
+/* Returns NULL if code is NULL or not valid UTF-8, or if the stylesheet cannot be printed.
+   options may be NULL. */
+CssTransformResult *css_transform(const char *code, const CssTransformOptions *options);
+
+void css_transform_result_free(CssTransformResult *result);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
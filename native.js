@@ -15,15 +15,20 @@ if (process.platform === 'linux') {
 let name = `parcel-css.${parts.join('-')}.node`;
 if (process.env.PARCEL_BUILD_ENV === 'production') {
   module.exports = require(`./${name}`);
-} else if (process.env.PARCEL_SWC_WASM) {
-  const {transform} = require('./wasm/dist-node/parcel_js_swc_wasm.js');
+} else if (process.env.PARCEL_CSS_WASM) {
+  const {transform} = require('./pkg/parcel_css.js');
 
   module.exports.transform = function(config) {
-    let result = transform(config);
+    let result = transform({
+      ...config,
+      code: config.code.toString(),
+    });
+    // Return Buffers like the native module
     return {
-      ...result,
-      // Hydrate Uint8Array into Buffer
-      code: Buffer.from(result.code.buffer),
+      code: Buffer.from(result.code),
+      map: null,
+      exports: result.exports || null,
+      warnings: [],
     };
   };
 } else if (require('fs').existsSync(require('path').join(__dirname, name))) {
@@ -0,0 +1,121 @@
+use crate::bundler::SourceProvider;
+use crate::limits::{self, Limits, LimitError};
+use crate::printer::PrinterOptions;
+use crate::properties::prefixes::Browsers;
+use crate::stylesheet::{StyleSheet, MinifyOptions};
+use crate::warnings::{self, Warning};
+use std::path::{Path, PathBuf};
+
+#[derive(Default)]
+pub struct BatchOptions {
+  pub targets: Option<Browsers>,
+  pub minify: bool,
+  pub limits: Limits
+}
+
+/// The reason a file in a batch could not be processed.
+#[derive(Debug, PartialEq)]
+pub enum BatchError {
+  /// The file could not be read, with the message of the underlying error.
+  Read(String),
+  /// The file exceeded one of the limits.
+  Limit(LimitError),
+  /// The stylesheet could not be printed.
+  Print
+}
+
+#[derive(Debug, PartialEq)]
+pub struct FileResult {
+  pub file: PathBuf,
+  /// The compiled code, or `None` if the file could not be processed.
+  pub code: Option<String>,
+  pub error: Option<BatchError>,
+  /// The warnings reported while validating the file against the targets.
+  pub warnings: Vec<Warning>
+}
+
+/// The results of processing a batch of files, in the order the files were given.
+#[derive(Debug, PartialEq)]
+pub struct BatchSummary {
+  pub files: Vec<FileResult>
+}
+
+impl BatchSummary {
+  /// Returns the number of files that could not be processed.
+  pub fn error_count(&self) -> usize {
+    self.files.iter().filter(|file| file.error.is_some()).count()
+  }
+
+  /// Returns the total number of warnings across all files.
+  pub fn warning_count(&self) -> usize {
+    self.files.iter().map(|file| file.warnings.len()).sum()
+  }
+
+  /// Returns the exit code for a command line tool: 1 if any file could not be processed,
+  /// and 0 otherwise. Warnings do not affect the exit code.
+  pub fn exit_code(&self) -> i32 {
+    if self.error_count() > 0 { 1 } else { 0 }
+  }
+}
+
+/// Reads, validates, minifies, and prints each of the given files. Unlike the other APIs,
+/// this does not stop at the first file that fails, so that all problems across a project
+/// can be reported at once.
+pub fn process_batch<P: SourceProvider>(provider: &P, files: &[PathBuf], options: &BatchOptions) -> BatchSummary {
+  BatchSummary {
+    files: files.iter().map(|file| process_file(provider, file, options)).collect()
+  }
+}
+
+fn process_file<P: SourceProvider>(provider: &P, file: &Path, options: &BatchOptions) -> FileResult {
+  let source = match provider.read(file) {
+    Ok(source) => source,
+    Err(err) => return FileResult {
+      file: file.to_path_buf(),
+      code: None,
+      error: Some(BatchError::Read(err.to_string())),
+      warnings: vec![]
+    }
+  };
+
+  // Invalid declarations are dropped, as browsers would ignore them.
+  let ((stylesheet, error), warnings) = warnings::recover(options.targets, || {
+    limits::with_limits(options.limits, || StyleSheet::parse_rules(&source))
+  });
+
+  if let Some(error) = error {
+    return FileResult {
+      file: file.to_path_buf(),
+      code: None,
+      error: Some(BatchError::Limit(error)),
+      warnings
+    }
+  }
+
+  let mut stylesheet = stylesheet;
+  stylesheet.minify(MinifyOptions {
+    targets: options.targets,
+    ..MinifyOptions::default()
+  });
+
+  let res = stylesheet.to_css(PrinterOptions {
+    minify: options.minify,
+    targets: options.targets,
+    ..PrinterOptions::default()
+  });
+
+  match res {
+    Ok(res) => FileResult {
+      file: file.to_path_buf(),
+      code: Some(res.code),
+      error: None,
+      warnings
+    },
+    Err(_) => FileResult {
+      file: file.to_path_buf(),
+      code: None,
+      error: Some(BatchError::Print),
+      warnings
+    }
+  }
+}
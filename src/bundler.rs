@@ -0,0 +1,174 @@
+use crate::parser::{CssRule, MediaRule};
+use crate::media_query::MediaList;
+use crate::stylesheet::{StyleSheet, ParserOptions};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Reads the source code for a file referenced by an `@import` rule.
+/// Implementations may be called from multiple threads at once.
+pub trait SourceProvider: Sync {
+  fn read(&self, file: &Path) -> std::io::Result<String>;
+}
+
+/// Reads files from the file system.
+pub struct FileProvider;
+
+impl SourceProvider for FileProvider {
+  fn read(&self, file: &Path) -> std::io::Result<String> {
+    std::fs::read_to_string(file)
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BundleError {
+  pub file: PathBuf,
+  pub message: String
+}
+
+/// Combines a stylesheet and the files it references via `@import` into a single stylesheet.
+///
+/// The imports of each file are read in parallel, but they are resolved depth first in source
+/// order once read, so the output only depends on the order of the `@import` rules in the
+/// source, never on the order in which reads complete. Each file is included at the position
+/// of the first `@import` that references it in that traversal, and again if a later `@import`
+/// references it under weaker media conditions, e.g. without the media query of the first one.
+/// Imports with a media query are wrapped in an `@media` rule.
+pub struct Bundler<'a, P: SourceProvider> {
+  provider: &'a P,
+  /// The media conditions that each file was included under.
+  loaded: HashMap<PathBuf, Vec<Vec<MediaList>>>,
+  /// The media lists of the imports that enclose the file being bundled.
+  conditions: Vec<MediaList>,
+  /// The files that were read ahead of being bundled, or the error message if a read failed.
+  sources: HashMap<PathBuf, Result<String, String>>
+}
+
+impl<'a, P: SourceProvider> Bundler<'a, P> {
+  pub fn new(provider: &'a P) -> Bundler<'a, P> {
+    Bundler {
+      provider,
+      loaded: HashMap::new(),
+      conditions: Vec::new(),
+      sources: HashMap::new()
+    }
+  }
+
+  pub fn bundle(&mut self, entry: &Path) -> Result<StyleSheet, BundleError> {
+    let entry = normalize(entry);
+    let code = self.provider.read(&entry).map_err(|err| BundleError {
+      file: entry.clone(),
+      message: err.to_string()
+    })?;
+
+    self.loaded.clear();
+    self.conditions.clear();
+    self.sources.clear();
+    self.loaded.insert(entry.clone(), vec![vec![]]);
+    let mut rules = vec![];
+    self.bundle_file(&entry, &code, &mut rules)?;
+    Ok(StyleSheet { rules, ..StyleSheet::default() })
+  }
+
+  fn bundle_file(&mut self, file: &Path, code: &str, dest: &mut Vec<CssRule>) -> Result<(), BundleError> {
+    let stylesheet = StyleSheet::parse(code, ParserOptions::default());
+    let dir = file.parent().unwrap_or_else(|| Path::new(""));
+    self.read_imports(&stylesheet.rules, dir);
+
+    for rule in stylesheet.rules {
+      let import = match rule {
+        CssRule::Import(import) => import,
+        rule => {
+          dest.push(rule);
+          continue
+        }
+      };
+
+      let path = normalize(&dir.join(&import.url));
+      let has_media = !import.media.media_queries.is_empty();
+      if has_media {
+        self.conditions.push(import.media.clone());
+      }
+
+      if self.is_loaded(&path) {
+        if has_media {
+          self.conditions.pop();
+        }
+        continue
+      }
+      self.loaded.entry(path.clone()).or_default().push(self.conditions.clone());
+
+      let source = match self.sources.get(&path) {
+        Some(source) => source.clone(),
+        None => self.provider.read(&path).map_err(|err| err.to_string())
+      };
+      let source = source.map_err(|message| BundleError {
+        file: path.clone(),
+        message
+      })?;
+
+      if has_media {
+        let mut media_rules = vec![];
+        self.bundle_file(&path, &source, &mut media_rules)?;
+        self.conditions.pop();
+        dest.push(CssRule::Media(MediaRule {
+          query: import.media,
+          rules: media_rules
+        }));
+      } else {
+        self.bundle_file(&path, &source, dest)?;
+      }
+    }
+
+    Ok(())
+  }
+
+  /// Reads the files imported by a stylesheet that were not read yet, in parallel. The results
+  /// are stored by path rather than in the order the reads complete.
+  fn read_imports(&mut self, rules: &[CssRule], dir: &Path) {
+    let mut paths = vec![];
+    for rule in rules {
+      if let CssRule::Import(import) = rule {
+        let path = normalize(&dir.join(&import.url));
+        if !self.loaded.contains_key(&path) && !self.sources.contains_key(&path) && !paths.contains(&path) {
+          paths.push(path);
+        }
+      }
+    }
+
+    let provider = self.provider;
+    let sources: Vec<Result<String, String>> = std::thread::scope(|scope| {
+      let handles: Vec<_> = paths.iter()
+        .map(|path| scope.spawn(move || provider.read(path).map_err(|err| err.to_string())))
+        .collect();
+      handles.into_iter().map(|handle| handle.join().unwrap()).collect()
+    });
+    self.sources.extend(paths.into_iter().zip(sources));
+  }
+
+  /// Returns whether the file was already included under conditions that also apply to the
+  /// current import, i.e. each of their media lists encloses the current import as well.
+  /// This is always the case for a file that imports itself, directly or indirectly.
+  fn is_loaded(&self, file: &Path) -> bool {
+    self.loaded.get(file).map_or(false, |loads| {
+      loads.iter().any(|conditions| conditions.iter().all(|media| self.conditions.contains(media)))
+    })
+  }
+}
+
+/// Lexically resolves `.` and `..` components so that the same file imported
+/// via different relative paths is only included once.
+fn normalize(path: &Path) -> PathBuf {
+  let mut result = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir => {
+        if !result.pop() {
+          result.push("..");
+        }
+      }
+      c => result.push(c.as_os_str())
+    }
+  }
+  result
+}
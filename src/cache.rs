@@ -0,0 +1,112 @@
+use crate::parser::CssRule;
+use crate::printer::{Printer, PrinterOptions};
+use crate::stylesheet::ContentHasher;
+use crate::traits::ToCss;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::hash::Hash;
+
+/// Caches the serialized output of top-level rules between builds, e.g. in watch mode,
+/// so that only the rules that changed since the previous build are printed again.
+///
+/// Rules are keyed by a hash of their structure, and each entry stores a copy of the rule,
+/// so that a hit is only used if the rule is equal to the cached one. Two different rules
+/// with the same hash are printed separately. Entries that are not used by a build are
+/// evicted, so the cache only holds the output of the latest build.
+#[derive(Default)]
+pub struct SerializationCache {
+  options: Option<u64>,
+  entries: HashMap<u64, (CssRule, String)>,
+  /// The number of rules whose output was reused by the latest build.
+  pub hits: usize,
+  /// The number of rules that were printed by the latest build.
+  pub misses: usize
+}
+
+impl SerializationCache {
+  pub fn new() -> SerializationCache {
+    SerializationCache::default()
+  }
+
+  /// Returns the number of cached rules.
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  pub fn clear(&mut self) {
+    self.options = None;
+    self.entries.clear();
+  }
+
+  /// Prints the rules in the same way as `StyleSheet::to_css`, reusing cached output where possible.
+  pub(crate) fn print_rules(&mut self, rules: &[CssRule], options: &PrinterOptions, dest: &mut String) -> std::fmt::Result {
+    // The output of every rule depends on the options, so the cache is only valid for one set of them.
+    let options_hash = hash_options(options);
+    if self.options != Some(options_hash) {
+      self.clear();
+      self.options = Some(options_hash);
+    }
+
+    let mut previous = std::mem::take(&mut self.entries);
+    self.hits = 0;
+    self.misses = 0;
+
+    let mut first = true;
+    for rule in rules {
+      if rule.is_omitted_comment(options.preserve_comments) {
+        continue
+      }
+
+      if first {
+        first = false;
+      } else if !options.minify {
+        dest.push('\n');
+      }
+
+      let key = rule_key(rule);
+      if let Some((_, output)) = self.entries.get(&key).filter(|(cached, _)| cached == rule) {
+        self.hits += 1;
+        dest.push_str(output);
+        continue
+      }
+
+      let entry = match previous.remove(&key).filter(|(cached, _)| cached == rule) {
+        Some(entry) => {
+          self.hits += 1;
+          entry
+        }
+        None => {
+          self.misses += 1;
+          let mut output = String::new();
+          let mut printer = Printer::with_options(&mut output, options);
+          rule.to_css(&mut printer)?;
+          printer.newline()?;
+          (rule.clone(), output)
+        }
+      };
+
+      dest.push_str(&entry.1);
+      self.entries.insert(key, entry);
+    }
+
+    Ok(())
+  }
+}
+
+fn hash_options(options: &PrinterOptions) -> u64 {
+  let mut hasher = ContentHasher::new();
+  options.hash(&mut hasher);
+  hasher.finish()
+}
+
+/// Hashes the debug representation of the rule, which reflects its structure
+/// without printing it as CSS. It is written to the hasher without being stored.
+fn rule_key(rule: &CssRule) -> u64 {
+  let mut hasher = ContentHasher::new();
+  write!(hasher, "{:?}", rule).unwrap();
+  hasher.finish()
+}
@@ -0,0 +1,1359 @@
+use crate::properties::prefixes::Browsers;
+
+/// Features that are either supported or unsupported by a browser version,
+/// as opposed to the vendor prefixed features in `properties::prefixes`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Feature {
+  CssNesting,
+  MediaRangeSyntax,
+  ClampFunction,
+  EnvFunction,
+  XResolutionUnit,
+  LogicalInset,
+  LogicalProperties,
+  LogicalTextAlign,
+  DirSelector,
+  OverflowShorthand,
+  FileSelectorButton,
+  FontSizeAdjust,
+  FontSizeAdjustTwoValues,
+  FontOpticalSizing,
+  InitialLetter,
+  PlaceContent,
+  PlaceItems,
+  PlaceSelf,
+  NotSelectorList,
+  Gap,
+  IndividualTransforms,
+  LogicalFloat,
+  LogicalResize,
+  BackgroundAttachmentFixed,
+  OffsetProperties,
+  LabColors,
+  OklabColors,
+  P3Colors,
+  ColorFunction,
+  HexAlphaColors,
+  RgbaColors
+}
+
+impl Feature {
+  /// Returns whether all of the given target browsers support the feature.
+  pub fn is_compatible(&self, browsers: Browsers) -> bool {
+    match self {
+      Feature::CssNesting => {
+        if let Some(version) = browsers.android {
+          if version < 7340032 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 7340032 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 7340032 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 7667712 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 1049856 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 6422528 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 1049856 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1507328 {
+            return false;
+          }
+        }
+      }
+      Feature::MediaRangeSyntax => {
+        if let Some(version) = browsers.android {
+          if version < 6815744 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 6815744 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 6815744 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4128768 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 1049600 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 5963776 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 1049600 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1310720 {
+            return false;
+          }
+        }
+      }
+      Feature::ClampFunction => {
+        if let Some(version) = browsers.android {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4915200 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 852992 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 4325376 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 852224 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 786432 {
+            return false;
+          }
+        }
+      }
+      Feature::EnvFunction => {
+        if let Some(version) = browsers.android {
+          if version < 4521984 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 4521984 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4259840 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 721408 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 3670016 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 721152 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 655360 {
+            return false;
+          }
+        }
+      }
+      Feature::XResolutionUnit => {
+        if let Some(version) = browsers.android {
+          if version < 4456448 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 4456448 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4063232 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 1048576 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 3604480 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 1048576 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 655360 {
+            return false;
+          }
+        }
+      }
+      Feature::LogicalInset => {
+        if let Some(version) = browsers.android {
+          if version < 5701632 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 5701632 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5701632 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4325376 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 918784 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 4784128 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 917760 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 917504 {
+            return false;
+          }
+        }
+      }
+      Feature::LogicalProperties => {
+        if let Some(version) = browsers.android {
+          if version < 5832704 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 5832704 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5832704 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4325376 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 983040 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 4915200 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 983040 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 983040 {
+            return false;
+          }
+        }
+      }
+      Feature::LogicalTextAlign => {
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+      }
+      Feature::DirSelector => {
+        if let Some(version) = browsers.android {
+          if version < 7864320 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 7864320 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 7864320 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 3211264 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 1049600 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 6946816 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 1049600 {
+            return false;
+          }
+        }
+        if browsers.samsung.is_some() {
+          return false;
+        }
+      }
+      Feature::OverflowShorthand => {
+        if let Some(version) = browsers.android {
+          if version < 4456448 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 4456448 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 3997696 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 852992 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 3604480 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 852224 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 655360 {
+            return false;
+          }
+        }
+      }
+      Feature::FileSelectorButton => {
+        if let Some(version) = browsers.android {
+          if version < 5832704 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 5832704 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5832704 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 5373952 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 918784 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 4915200 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 917760 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 983040 {
+            return false;
+          }
+        }
+      }
+      Feature::FontSizeAdjust => {
+        if let Some(version) = browsers.android {
+          if version < 8323072 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 8323072 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 8323072 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 196608 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 1114112 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 7405568 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 1114112 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1835008 {
+            return false;
+          }
+        }
+      }
+      Feature::FontSizeAdjustTwoValues => {
+        if let Some(version) = browsers.android {
+          if version < 8323072 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 8323072 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 8323072 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 7733248 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 1114112 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 7405568 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 1114112 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1835008 {
+            return false;
+          }
+        }
+      }
+      Feature::FontOpticalSizing => {
+        if let Some(version) = browsers.android {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 1114112 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4063232 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 720896 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 4325376 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 720896 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 786432 {
+            return false;
+          }
+        }
+      }
+      Feature::InitialLetter => {
+        if let Some(version) = browsers.android {
+          if version < 7208960 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 7208960 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 7208960 {
+            return false;
+          }
+        }
+        if browsers.firefox.is_some() {
+          return false;
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if browsers.ios_saf.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.opera {
+          if version < 6291456 {
+            return false;
+          }
+        }
+        if browsers.safari.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1376256 {
+            return false;
+          }
+        }
+      }
+      Feature::PlaceContent => {
+        if let Some(version) = browsers.android {
+          if version < 3866624 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 3866624 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 2949120 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 589824 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 3014656 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 589824 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 458752 {
+            return false;
+          }
+        }
+      }
+      Feature::PlaceItems => {
+        if let Some(version) = browsers.android {
+          if version < 3866624 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 3866624 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 2949120 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 720896 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 3014656 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 720896 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 458752 {
+            return false;
+          }
+        }
+      }
+      Feature::PlaceSelf => {
+        if let Some(version) = browsers.android {
+          if version < 3866624 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 3866624 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 2949120 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 720896 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 3014656 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 720896 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 458752 {
+            return false;
+          }
+        }
+      }
+      Feature::NotSelectorList => {
+        if let Some(version) = browsers.android {
+          if version < 5767168 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 5767168 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5767168 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 5505024 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 589824 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 4849664 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 589824 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 983040 {
+            return false;
+          }
+        }
+      }
+      Feature::Gap => {
+        if let Some(version) = browsers.android {
+          if version < 4325376 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 4325376 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 1048576 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 3997696 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 786432 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 3473408 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 786432 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 589824 {
+            return false;
+          }
+        }
+      }
+      Feature::IndividualTransforms => {
+        if let Some(version) = browsers.android {
+          if version < 6815744 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 6815744 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 6815744 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4718592 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 917760 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 5898240 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 917760 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1310720 {
+            return false;
+          }
+        }
+      }
+      Feature::LogicalFloat => {
+        if let Some(version) = browsers.android {
+          if version < 7733248 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 7733248 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 7733248 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 3604480 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 983040 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 6815744 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 983040 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1638400 {
+            return false;
+          }
+        }
+      }
+      Feature::LogicalResize => {
+        if let Some(version) = browsers.android {
+          if version < 7733248 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 7733248 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 7733248 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4128768 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if browsers.ios_saf.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.opera {
+          if version < 6815744 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 1048576 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1638400 {
+            return false;
+          }
+        }
+      }
+      Feature::BackgroundAttachmentFixed => {
+        if browsers.ios_saf.is_some() {
+          return false;
+        }
+      }
+      Feature::OffsetProperties => {
+        if let Some(version) = browsers.android {
+          if version < 3604480 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 3604480 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 4718592 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 1048576 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 2752512 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 1048576 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 393216 {
+            return false;
+          }
+        }
+      }
+      Feature::LabColors => {
+        if let Some(version) = browsers.android {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 7405568 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 983040 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 6356992 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 983040 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1441792 {
+            return false;
+          }
+        }
+      }
+      Feature::OklabColors => {
+        if let Some(version) = browsers.android {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 7405568 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 984064 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 6356992 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 984064 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1441792 {
+            return false;
+          }
+        }
+      }
+      Feature::P3Colors => {
+        if let Some(version) = browsers.android {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 7405568 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 655360 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 6356992 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 655360 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1441792 {
+            return false;
+          }
+        }
+      }
+      Feature::ColorFunction => {
+        if let Some(version) = browsers.android {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 7274496 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 7405568 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 983040 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 6356992 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 983040 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 1441792 {
+            return false;
+          }
+        }
+      }
+      Feature::HexAlphaColors => {
+        if let Some(version) = browsers.android {
+          if version < 4063232 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 4063232 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 5177344 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 3211264 {
+            return false;
+          }
+        }
+        if browsers.ie.is_some() {
+          return false;
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 590592 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 3211264 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 655360 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 524800 {
+            return false;
+          }
+        }
+      }
+      Feature::RgbaColors => {
+        if let Some(version) = browsers.android {
+          if version < 131328 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.chrome {
+          if version < 262144 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.edge {
+          if version < 786432 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.firefox {
+          if version < 196608 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.ie {
+          if version < 589824 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.ios_saf {
+          if version < 197120 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.opera {
+          if version < 655360 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.safari {
+          if version < 196864 {
+            return false;
+          }
+        }
+        if let Some(version) = browsers.samsung {
+          if version < 262144 {
+            return false;
+          }
+        }
+      }
+    }
+    true
+  }
+}
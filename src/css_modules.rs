@@ -0,0 +1,203 @@
+use selectors::parser::Component;
+use serde::Serialize;
+use std::collections::HashMap;
+use crate::parser::{CssRule, StyleRule};
+use crate::properties::Property;
+use crate::properties::animation::rename_animations;
+use crate::properties::css_modules::ComposesFrom;
+use crate::properties::list::rename_counter_styles;
+use crate::rename;
+use crate::selector::Namespaces;
+use crate::warnings::{self, WarningKind};
+
+/// The name generated for a class, id, `@keyframes` rule or `@counter-style` rule
+/// when compiling a stylesheet as a CSS module.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CssModuleExport {
+  pub name: String,
+  /// The classes composed into a class via `composes`, in order, which should be
+  /// applied to elements along with the class.
+  pub composes: Vec<CssModuleReference>
+}
+
+/// A class referenced by a `composes` declaration.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum CssModuleReference {
+  /// A class in the same file, with its generated name.
+  Local { name: String },
+  /// A class that is not scoped, referenced via `from global`.
+  Global { name: String },
+  /// A class in another CSS module, with its original name, and the specifier of the file
+  /// as written, e.g. `./button.css`, which is resolved by the caller.
+  Dependency { name: String, specifier: String }
+}
+
+/// A map from the original names in a CSS module to the generated names, which serializes
+/// to JSON as `{"button": {"name": "button_3f2a1c", "composes": []}}`.
+pub type CssModuleExports = HashMap<String, CssModuleExport>;
+
+/// The generated names of a CSS module.
+struct Scope {
+  hash: String,
+  exports: CssModuleExports
+}
+
+impl Scope {
+  fn name(&mut self, name: &str) -> String {
+    let hash = &self.hash;
+    self.exports.entry(name.to_owned()).or_insert_with(|| CssModuleExport {
+      name: format!("{}_{}", name, hash),
+      composes: vec![]
+    }).name.clone()
+  }
+}
+
+/// Scopes the classes, ids, `@keyframes` names and `@counter-style` names defined in a
+/// stylesheet to the file, by appending a hash of the filename to them, and renames the
+/// references to them in `animation`, `list-style` and `content`. Classes and ids within
+/// `:global()` are left as is. `composes` declarations are removed, and recorded in the
+/// exports of the classes they apply to. Rules left empty are removed, and `composes` in rules
+/// whose selectors are not single classes are kept, with a warning. Returns the generated names.
+pub(crate) fn scope_names(rules: &mut Vec<CssRule>, filename: &str, namespaces: &Namespaces) -> CssModuleExports {
+  let mut scope = Scope {
+    hash: hash(filename),
+    exports: CssModuleExports::new()
+  };
+
+  // Composition is resolved before renaming, since it refers to the original class names.
+  resolve_composes(rules, &mut scope);
+  rename::scope_with(rules, namespaces, &mut |_, name| Some(scope.name(name)));
+
+  let mut keyframes = HashMap::new();
+  let mut counter_styles = HashMap::new();
+  rename_definitions(rules, &mut keyframes, &mut counter_styles, &mut scope);
+  rename_references(rules, &keyframes, &counter_styles);
+  scope.exports
+}
+
+fn resolve_composes(rules: &mut Vec<CssRule>, scope: &mut Scope) {
+  rules.retain_mut(|rule| {
+    match rule {
+      CssRule::Style(style) => {
+        let has_composes = style.declarations.declarations.iter().any(|decl| matches!(decl.property, Property::Composes(_)));
+        if !has_composes {
+          return true
+        }
+
+        // Composition only applies to rules with a single class as each selector, e.g. `.a, .b`.
+        // Otherwise, the declarations are kept so that browsers ignore them.
+        let classes = match single_classes(style) {
+          Some(classes) => classes,
+          None => {
+            if warnings::is_validating() {
+              warnings::warn(style.loc, WarningKind::InvalidComposes);
+            }
+            return true
+          }
+        };
+
+        let mut composes = vec![];
+        style.declarations.declarations.retain(|decl| {
+          match &decl.property {
+            Property::Composes(value) => {
+              composes.push(value.clone());
+              false
+            }
+            _ => true
+          }
+        });
+
+        let mut references = vec![];
+        for value in &composes {
+          for name in &value.names {
+            references.push(match &value.from {
+              None => CssModuleReference::Local { name: scope.name(&name.0) },
+              Some(ComposesFrom::Global) => CssModuleReference::Global { name: name.0.clone() },
+              Some(ComposesFrom::File(specifier)) => CssModuleReference::Dependency {
+                name: name.0.clone(),
+                specifier: specifier.clone()
+              }
+            });
+          }
+        }
+
+        for class in classes {
+          scope.name(&class);
+          scope.exports.get_mut(&class).unwrap().composes.extend(references.iter().cloned());
+        }
+
+        // A rule that only composed other classes would otherwise be printed empty.
+        !style.declarations.declarations.is_empty() || !style.rules.is_empty()
+      }
+      CssRule::Media(media) => {
+        resolve_composes(&mut media.rules, scope);
+        true
+      }
+      CssRule::Supports(supports) => {
+        resolve_composes(&mut supports.rules, scope);
+        true
+      }
+      _ => true
+    }
+  });
+}
+
+/// Returns the classes of a style rule if each of its selectors consists of a single class.
+fn single_classes(style: &StyleRule) -> Option<Vec<String>> {
+  let mut classes = vec![];
+  for selector in style.selectors.0.iter() {
+    let mut components = selector.iter_raw_match_order();
+    match (components.next(), components.next()) {
+      (Some(Component::Class(name)), None) => classes.push(cssparser::ToCss::to_css_string(name)),
+      _ => return None
+    }
+  }
+  Some(classes)
+}
+
+fn rename_definitions(rules: &mut Vec<CssRule>, keyframes: &mut HashMap<String, String>, counter_styles: &mut HashMap<String, String>, scope: &mut Scope) {
+  for rule in rules.iter_mut() {
+    match rule {
+      CssRule::Keyframes(rule) => {
+        let name = scope.name(&rule.name);
+        keyframes.insert(std::mem::replace(&mut rule.name, name.clone()), name);
+      }
+      CssRule::CounterStyle(rule) => {
+        let name = scope.name(&rule.name.0);
+        counter_styles.insert(std::mem::replace(&mut rule.name.0, name.clone()), name);
+      }
+      CssRule::Media(media) => rename_definitions(&mut media.rules, keyframes, counter_styles, scope),
+      CssRule::Supports(supports) => rename_definitions(&mut supports.rules, keyframes, counter_styles, scope),
+      _ => {}
+    }
+  }
+}
+
+fn rename_references(rules: &mut Vec<CssRule>, keyframes: &HashMap<String, String>, counter_styles: &HashMap<String, String>) {
+  for rule in rules.iter_mut() {
+    match rule {
+      CssRule::Style(style) => {
+        for decl in style.declarations.declarations.iter_mut() {
+          rename_animations(&mut decl.property, keyframes);
+          rename_counter_styles(&mut decl.property, counter_styles);
+        }
+        rename_references(&mut style.rules, keyframes, counter_styles);
+      }
+      CssRule::Media(media) => rename_references(&mut media.rules, keyframes, counter_styles),
+      CssRule::Supports(supports) => rename_references(&mut supports.rules, keyframes, counter_styles),
+      _ => {}
+    }
+  }
+}
+
+/// A 32-bit FNV-1a hash of the filename, truncated to six hex digits. Unlike the hasher in
+/// the standard library, it is stable across Rust versions, so the names don't change.
+fn hash(filename: &str) -> String {
+  let mut hash: u32 = 0x811c9dc5;
+  for byte in filename.bytes() {
+    hash ^= byte as u32;
+    hash = hash.wrapping_mul(0x01000193);
+  }
+  format!("{:06x}", hash & 0xffffff)
+}
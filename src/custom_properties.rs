@@ -0,0 +1,144 @@
+use cssparser::*;
+use crate::parser::CssRule;
+use crate::declaration::Declaration;
+use crate::properties::Property;
+use crate::traits::ToCss;
+use std::collections::{HashMap, HashSet};
+use crate::error::ParserError;
+
+/// Removes declarations of custom properties that are not referenced by a `var()` in any
+/// declaration that is kept, including the values of other custom properties that are used,
+/// or in the given set of names used outside the stylesheet. Style rules and `@media` rules
+/// that become empty are removed.
+pub fn remove_unused_custom_properties(rules: &mut Vec<CssRule>, used: &HashSet<String>) {
+  // References from custom properties only count if the referencing property is used itself.
+  let mut references = HashSet::new();
+  let mut dependencies = HashMap::new();
+  collect_rules(rules, &mut references, &mut dependencies);
+
+  let mut queue: Vec<String> = references.into_iter().chain(used.iter().cloned()).collect();
+  let mut used = HashSet::new();
+  while let Some(name) = queue.pop() {
+    if let Some(deps) = dependencies.remove(&name) {
+      queue.extend(deps);
+    }
+    used.insert(name);
+  }
+
+  remove_rules(rules, &used);
+}
+
+fn collect_rules(rules: &[CssRule], references: &mut HashSet<String>, dependencies: &mut HashMap<String, HashSet<String>>) {
+  for rule in rules {
+    match rule {
+      CssRule::Media(media) => collect_rules(&media.rules, references, dependencies),
+      CssRule::Supports(supports) => collect_rules(&supports.rules, references, dependencies),
+      CssRule::Style(style) => {
+        collect_declarations(&style.declarations.declarations, references, dependencies);
+        collect_rules(&style.rules, references, dependencies);
+      }
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &keyframes.keyframes {
+          collect_declarations(&keyframe.declarations.declarations, references, dependencies);
+        }
+      }
+      CssRule::Page(page) => collect_declarations(&page.declarations.declarations, references, dependencies),
+      _ => {}
+    }
+  }
+}
+
+fn collect_declarations(declarations: &[Declaration], references: &mut HashSet<String>, dependencies: &mut HashMap<String, HashSet<String>>) {
+  for declaration in declarations {
+    match &declaration.property {
+      Property::Custom(custom) if custom.name.starts_with("--") => {
+        let deps = dependencies.entry(custom.name.clone()).or_insert_with(HashSet::new);
+        collect_references(&custom.value, deps);
+      }
+      Property::Custom(custom) => collect_references(&custom.value, references),
+      Property::Unparsed(unparsed) => collect_references(&unparsed.value.to_css_string(), references),
+      _ => {}
+    }
+  }
+}
+
+/// Adds the names of the custom properties referenced by `var()`s in the given value,
+/// including those nested within fallback values and other functions.
+fn collect_references(value: &str, references: &mut HashSet<String>) {
+  let mut input = ParserInput::new(value);
+  let mut parser = Parser::new(&mut input);
+  collect_tokens(&mut parser, references);
+}
+
+fn collect_tokens<'i, 't>(input: &mut Parser<'i, 't>, references: &mut HashSet<String>) {
+  loop {
+    let is_var = match input.next() {
+      Ok(Token::Function(f)) => f.eq_ignore_ascii_case("var"),
+      Ok(Token::ParenthesisBlock) | Ok(Token::SquareBracketBlock) | Ok(Token::CurlyBracketBlock) => false,
+      Ok(_) => continue,
+      Err(_) => return
+    };
+
+    let _ = input.parse_nested_block(|input| -> Result<(), ParseError<'i, ParserError<'i>>> {
+      if is_var {
+        if let Ok(name) = input.try_parse(|input| input.expect_ident_cloned()) {
+          if name.starts_with("--") {
+            references.insert(name.as_ref().into());
+          }
+        }
+      }
+      collect_tokens(input, references);
+      Ok(())
+    });
+  }
+}
+
+fn remove_rules(rules: &mut Vec<CssRule>, used: &HashSet<String>) {
+  *rules = std::mem::take(rules).into_iter().filter_map(|rule| {
+    match rule {
+      CssRule::Media(mut media) => {
+        let was_empty = media.rules.is_empty();
+        remove_rules(&mut media.rules, used);
+        if !was_empty && media.rules.is_empty() {
+          return None
+        }
+        Some(CssRule::Media(media))
+      }
+      CssRule::Supports(mut supports) => {
+        let was_empty = supports.rules.is_empty();
+        remove_rules(&mut supports.rules, used);
+        if !was_empty && supports.rules.is_empty() {
+          return None
+        }
+        Some(CssRule::Supports(supports))
+      }
+      CssRule::Style(mut style) => {
+        let was_empty = style.declarations.declarations.is_empty() && style.rules.is_empty();
+        remove_declarations(&mut style.declarations.declarations, used);
+        remove_rules(&mut style.rules, used);
+        if !was_empty && style.declarations.declarations.is_empty() && style.rules.is_empty() {
+          return None
+        }
+        Some(CssRule::Style(style))
+      }
+      CssRule::Keyframes(mut keyframes) => {
+        for keyframe in keyframes.keyframes.iter_mut() {
+          remove_declarations(&mut keyframe.declarations.declarations, used);
+        }
+        Some(CssRule::Keyframes(keyframes))
+      }
+      CssRule::Page(mut page) => {
+        remove_declarations(&mut page.declarations.declarations, used);
+        Some(CssRule::Page(page))
+      }
+      rule => Some(rule)
+    }
+  }).collect();
+}
+
+fn remove_declarations(declarations: &mut Vec<Declaration>, used: &HashSet<String>) {
+  declarations.retain(|declaration| match &declaration.property {
+    Property::Custom(custom) if custom.name.starts_with("--") => used.contains(&custom.name),
+    _ => true
+  });
+}
@@ -6,15 +6,33 @@ use crate::properties::{
   align::AlignHandler,
   background::BackgroundHandler,
   flex::FlexHandler,
-  font::FontHandler,
+  font::{FontHandler, GenericFontFamily},
   margin_padding::*,
   outline::OutlineHandler,
   border::BorderHandler,
   transition::TransitionHandler,
   animation::AnimationHandler,
+  masking::MaskHandler,
+  position::PositionHandler,
+  overflow::OverflowHandler,
+  text::{TextDecorationHandler, TextEmphasisHandler},
+  box_shadow::ShadowHandler,
+  list::ListStyleHandler,
+  scroll_snap::ScrollSnapHandler,
+  columns::ColumnsHandler,
+  grid::{self, GridHandler},
+  inline::InitialLetterHandler,
+  transform::TransformHandler,
+  motion::OffsetHandler,
+  logical::LogicalPropertiesHandler,
   prefix_handler::PrefixHandler,
 };
 use crate::properties::prefixes::Browsers;
+use crate::value_hooks;
+use crate::warnings::{self, WarningKind};
+use crate::macros::trace;
+use crate::error::ParserError;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Declaration {
@@ -23,12 +41,38 @@ pub struct Declaration {
 }
 
 impl Declaration {
-  pub fn parse<'i, 't>(name: CowRcStr<'i>, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
-    let property = input.parse_until_before(Delimiter::Bang, |input| Property::parse(name, input))?;
+  pub fn parse<'i, 't>(name: CowRcStr<'i>, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let state = input.state();
+    input.skip_whitespace();
+    let location = input.current_source_location();
+    input.reset(&state);
+
+    let property = input.parse_until_before(Delimiter::Bang, |input| {
+      // Custom units and functions are replaced first, so that the result is parsed as usual.
+      if let Some(value) = value_hooks::rewrite(input) {
+        let mut value_input = ParserInput::new(&value);
+        let mut parser = Parser::new(&mut value_input);
+        return Property::parse(name, &mut parser).map_err(|_| input.new_custom_error(ParserError::InvalidValue))
+      }
+
+      Property::parse(name, input)
+    })?;
+    if warnings::is_validating() {
+      grid::check_ms_grid(&property, location);
+    }
+    input.skip_whitespace();
+    let important_location = input.current_source_location();
     let important = input.try_parse(|input| {
       input.expect_delim('!')?;
       input.expect_ident_matching("important")
     }).is_ok();
+    // Anything else after the value, e.g. `!importantx`, makes the whole declaration invalid.
+    if !input.is_exhausted() {
+      if warnings::is_validating() {
+        warnings::warn(important_location, WarningKind::InvalidDeclaration);
+      }
+      return Err(important_location.new_custom_error(ParserError::InvalidDeclaration))
+    }
     Ok(Declaration { property, important })
   }
 }
@@ -39,85 +83,255 @@ impl ToCss for Declaration {
   }
 }
 
-#[derive(Default)]
-pub struct DeclarationHandler {
-  important: bool,
+/// Creates a custom property handler. It is called once for normal declarations
+/// and once for `!important` declarations, since these are handled separately.
+pub type HandlerFactory = Box<dyn Fn() -> Box<dyn PropertyHandler>>;
+
+macro_rules! define_handlers {
+  (
+    $( $field: ident: $handler: ident, )+
+  ) => {
+    /// Owns the property handlers used when minifying declaration blocks, and defines
+    /// how they interact.
+    ///
+    /// Each property is offered to the handlers in order, and the first handler that returns
+    /// `true` from `handle_property` takes it. Later handlers never see it, so the catch-all
+    /// `PrefixHandler` comes last, and custom handlers come before all of the built-in ones so
+    /// that they can take over properties which would otherwise be handled by them.
+    /// When finalized, the output of each handler is emitted in the same order.
+    ///
+    /// Properties that are not taken by any handler are passed through unchanged, in their
+    /// original order, before the output of the handlers.
+    #[derive(Default)]
+    pub struct Handlers {
+      custom: Vec<Box<dyn PropertyHandler>>,
+      $(
+        $field: $handler,
+      )+
+    }
+
+    impl PropertyHandler for Handlers {
+      fn handle_property(&mut self, property: &Property) -> bool {
+        if self.custom.iter_mut().any(|handler| handler.handle_property(property)) {
+          trace!(property = property.name(), handler = "custom", "handled property");
+          return true
+        }
+        $(
+          if self.$field.handle_property(property) {
+            trace!(property = property.name(), handler = stringify!($field), "handled property");
+            return true
+          }
+        )+
+        false
+      }
+
+      fn finalize(&mut self) -> Vec<Property> {
+        let mut decls = vec![];
+        for handler in self.custom.iter_mut() {
+          decls.extend(handler.finalize());
+        }
+        $(
+          let output = self.$field.finalize();
+          #[cfg(feature = "trace")]
+          if !output.is_empty() {
+            tracing::trace!(handler = stringify!($field), output = %describe(&output), "flushed handler");
+          }
+          decls.extend(output);
+        )+
+        decls
+      }
+    }
+  };
+}
+
+/// Serializes the output of a handler for the trace log.
+#[cfg(feature = "trace")]
+fn describe(properties: &[Property]) -> String {
+  let mut s = String::new();
+  let mut printer = Printer::new(&mut s, false);
+  for (i, property) in properties.iter().enumerate() {
+    if i > 0 {
+      printer.write_str("; ").unwrap();
+    }
+    property.to_css(&mut printer, false).unwrap();
+  }
+  s
+}
+
+// Handlers are listed in the order in which they are consulted, and in which their output
+// is emitted. Shorthand handlers that also handle prefixed properties (e.g. transition)
+// must come before the generic prefix handler.
+define_handlers! {
   background: BackgroundHandler,
   border: BorderHandler,
   outline: OutlineHandler,
   flex: FlexHandler,
   align: AlignHandler,
+  position: PositionHandler,
+  overflow: OverflowHandler,
+  inset: InsetHandler,
   margin: MarginHandler,
   padding: PaddingHandler,
   scroll_margin: ScrollMarginHandler,
   scroll_padding: ScrollPaddingHandler,
+  scroll_snap: ScrollSnapHandler,
   font: FontHandler,
+  list_style: ListStyleHandler,
+  columns: ColumnsHandler,
+  grid: GridHandler,
+  initial_letter: InitialLetterHandler,
+  text_decoration: TextDecorationHandler,
+  text_emphasis: TextEmphasisHandler,
   transition: TransitionHandler,
   animation: AnimationHandler,
-  prefix: PrefixHandler
+  mask: MaskHandler,
+  shadow: ShadowHandler,
+  transform: TransformHandler,
+  offset: OffsetHandler,
+  prefix: PrefixHandler,
 }
 
-impl DeclarationHandler {
-  pub fn new(important: bool, targets: Option<Browsers>) -> Self {
-    DeclarationHandler {
-      important,
+impl Handlers {
+  pub fn new(targets: Option<Browsers>) -> Handlers {
+    Handlers {
+      background: BackgroundHandler::new(targets),
       border: BorderHandler::new(targets),
       flex: FlexHandler::new(targets),
       align: AlignHandler::new(targets),
+      position: PositionHandler::new(targets),
+      overflow: OverflowHandler::new(targets),
+      inset: InsetHandler::new(targets),
+      scroll_snap: ScrollSnapHandler::new(targets),
+      columns: ColumnsHandler::new(targets),
+      grid: GridHandler::new(targets),
+      initial_letter: InitialLetterHandler::new(targets),
+      text_decoration: TextDecorationHandler::new(targets),
+      text_emphasis: TextEmphasisHandler::new(targets),
       transition: TransitionHandler::new(targets),
       animation: AnimationHandler::new(targets),
+      mask: MaskHandler::new(targets),
+      shadow: ShadowHandler::new(targets),
+      transform: TransformHandler::new(targets),
+      offset: OffsetHandler::new(targets),
       prefix: PrefixHandler::new(targets),
-      ..DeclarationHandler::default()
+      ..Handlers::default()
     }
   }
 
+  /// Registers a custom handler. Custom handlers are consulted in the order they
+  /// were registered.
+  pub fn register(&mut self, handler: Box<dyn PropertyHandler>) {
+    self.custom.push(handler);
+  }
+}
+
+pub struct DeclarationHandler {
+  important: bool,
+  pub targets: Option<Browsers>,
+  /// Whether to add fallbacks with opaque colors for targets that don't support alpha in colors.
+  pub opaque_color_fallbacks: bool,
+  /// Whether to add `opacity: 1` to the `::-moz-placeholder` rules generated for older versions of Firefox.
+  pub placeholder_opacity: bool,
+  handlers: Handlers,
+  logical: LogicalPropertiesHandler
+}
+
+impl DeclarationHandler {
+  pub fn new(important: bool, targets: Option<Browsers>) -> Self {
+    DeclarationHandler {
+      important,
+      targets,
+      opaque_color_fallbacks: false,
+      placeholder_opacity: false,
+      handlers: Handlers::new(targets),
+      logical: LogicalPropertiesHandler::new(targets)
+    }
+  }
+
+  /// Registers a custom handler, which takes precedence over the built-in handlers.
+  pub fn register(&mut self, handler: Box<dyn PropertyHandler>) {
+    self.handlers.register(handler);
+  }
+
+  /// Sets the generic font families to append to `font-family` lists that do not contain one.
+  pub fn set_font_fallbacks(&mut self, fallbacks: Vec<GenericFontFamily>) {
+    self.handlers.font = FontHandler::with_fallbacks(fallbacks);
+  }
+
   pub fn handle_property(&mut self, decl: &Declaration) -> bool {
-    let property = &decl.property;
-    self.background.handle_property(property) ||
-    self.border.handle_property(property) ||
-    self.outline.handle_property(property) ||
-    self.flex.handle_property(property) ||
-    self.align.handle_property(property) ||
-    self.margin.handle_property(property) ||
-    self.padding.handle_property(property) ||
-    self.scroll_margin.handle_property(property) ||
-    self.scroll_padding.handle_property(property) ||
-    self.font.handle_property(property) ||
-    self.transition.handle_property(property) ||
-    self.animation.handle_property(property) ||
-    self.prefix.handle_property(property)
+    self.handlers.handle_property(&decl.property)
+  }
+
+  /// Compiles a logical property for targets that don't support it, and returns the
+  /// declarations to handle in its place. See `LogicalPropertiesHandler::compile`.
+  pub fn compile_logical(&mut self, decl: &Declaration, directional: bool) -> Option<Vec<Declaration>> {
+    let important = decl.important;
+    self.logical.compile(&decl.property, directional).map(|properties| {
+      properties.into_iter()
+        .map(|property| Declaration { property, important })
+        .collect()
+    })
+  }
+
+  /// Takes the declarations compiled from logical properties for left-to-right
+  /// and right-to-left content.
+  pub fn take_directional(&mut self) -> (Vec<Declaration>, Vec<Declaration>) {
+    let important = self.important;
+    let (ltr, rtl) = self.logical.take_directional();
+    let to_declarations = |properties: Vec<Property>| -> Vec<Declaration> {
+      properties.into_iter()
+        .map(|property| Declaration { property, important })
+        .collect()
+    };
+    (to_declarations(ltr), to_declarations(rtl))
   }
 
   pub fn finalize(&mut self) -> Vec<Declaration> {
     let important = self.important;
-    let mut background = self.background.finalize();
-    let mut border = self.border.finalize();
-    let mut outline = self.outline.finalize();
-    let mut flex = self.flex.finalize();
-    let mut align = self.align.finalize();
-    let mut margin = self.margin.finalize();
-    let mut padding = self.padding.finalize();
-    let mut scroll_margin = self.scroll_margin.finalize();
-    let mut scroll_padding = self.scroll_padding.finalize();
-    let mut font = self.font.finalize();
-    let mut transition = self.transition.finalize();
-    let mut animation = self.animation.finalize();
-    let mut prefixed = self.prefix.finalize();
-
-    let mut decls = Vec::with_capacity(background.len() + border.len() + outline.len() + flex.len() + align.len() + margin.len() + padding.len() + scroll_margin.len() + scroll_padding.len() + font.len() + transition.len() + animation.len() + prefixed.len());
-    decls.extend(background.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(border.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(outline.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(flex.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(align.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(margin.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(padding.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(scroll_margin.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(scroll_padding.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(font.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(transition.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(animation.drain(..).map(|property| Declaration { property, important }));
-    decls.extend(prefixed.drain(..).map(|property| Declaration { property, important }));
-    decls
+    self.handlers.finalize()
+      .into_iter()
+      .map(|property| Declaration { property, important })
+      .collect()
   }
 }
+
+/// Removes declarations that are overridden by a later declaration of the same property with the
+/// same vendor prefixes and importance, or by an `!important` declaration of it. Properties that
+/// are combined by a handler have already been merged. The others are only removed if both values
+/// were fully parsed, or both are the same, since a value that could not be parsed, e.g. one using
+/// a newer function, commonly follows another as a fallback for older browsers. Custom properties
+/// accept any value, so these are always overridden.
+pub(crate) fn remove_shadowed_declarations(decls: &mut Vec<Declaration>) {
+  let is_typed = |property: &Property| !matches!(property, Property::Unparsed(..) | Property::Custom(..));
+
+  let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+  for (i, decl) in decls.iter().enumerate() {
+    by_name.entry(decl.property.name()).or_default().push(i);
+  }
+
+  let mut keep = vec![true; decls.len()];
+  for (name, indices) in &by_name {
+    for (a, &i) in indices.iter().enumerate() {
+      let decl = &decls[i];
+      keep[i] = !indices.iter().enumerate().any(|(b, &j)| {
+        let other = &decls[j];
+        let overrides = (other.important && !decl.important) || (other.important == decl.important && b > a);
+        overrides && (
+          name.starts_with("--") ||
+          other.property == decl.property ||
+          (is_typed(&decl.property) && is_typed(&other.property) && other.property.vendor_prefix().contains(decl.property.vendor_prefix()))
+        )
+      });
+    }
+  }
+
+  #[cfg(feature = "trace")]
+  for (decl, keep) in decls.iter().zip(keep.iter()) {
+    if !keep {
+      tracing::trace!(property = decl.property.name(), important = decl.important, "removed overridden declaration");
+    }
+  }
+
+  let mut keep = keep.into_iter();
+  decls.retain(|_| keep.next().unwrap());
+}
@@ -0,0 +1,94 @@
+use crate::parser::CssRule;
+use crate::declaration::Declaration;
+use crate::properties::Property;
+use crate::properties::content::{Content, ContentItem};
+use crate::rules::font_face::{FontFaceProperty, Source};
+use crate::values::url::Url;
+
+/// A reference from a stylesheet to another file, which a build tool may need to resolve.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dependency {
+  /// An `@import` rule.
+  Import(String),
+  /// A `url()`, e.g. in a `background-image` or `@font-face` source. The URL has any
+  /// escapes in the source resolved.
+  Url(String)
+}
+
+/// Returns the dependencies of the given rules, in source order.
+pub fn collect_dependencies(rules: &[CssRule]) -> Vec<Dependency> {
+  let mut dependencies = vec![];
+  collect_rules(rules, &mut dependencies);
+  dependencies
+}
+
+/// Returns the dependencies of the given declarations, e.g. those of a `style` attribute, in source order.
+pub fn collect_declaration_dependencies(declarations: &[Declaration]) -> Vec<Dependency> {
+  let mut dependencies = vec![];
+  collect_declarations(declarations, &mut dependencies);
+  dependencies
+}
+
+fn collect_rules(rules: &[CssRule], dependencies: &mut Vec<Dependency>) {
+  for rule in rules {
+    match rule {
+      CssRule::Import(import) => dependencies.push(Dependency::Import(import.url.clone())),
+      CssRule::Media(media) => collect_rules(&media.rules, dependencies),
+      CssRule::Supports(supports) => collect_rules(&supports.rules, dependencies),
+      CssRule::Style(style) => {
+        collect_declarations(&style.declarations.declarations, dependencies);
+        collect_rules(&style.rules, dependencies);
+      }
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in &keyframes.keyframes {
+          collect_declarations(&keyframe.declarations.declarations, dependencies);
+        }
+      }
+      CssRule::Page(page) => collect_declarations(&page.declarations.declarations, dependencies),
+      CssRule::FontFace(font_face) => {
+        for property in &font_face.properties {
+          if let FontFaceProperty::Source(sources) = property {
+            for source in sources {
+              if let Source::Url(source) = source {
+                dependencies.push(Dependency::Url(source.url.url.clone()));
+              }
+            }
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+}
+
+fn collect_declarations(declarations: &[Declaration], dependencies: &mut Vec<Dependency>) {
+  let mut urls: Vec<&Url> = vec![];
+  for declaration in declarations {
+    match &declaration.property {
+      Property::BackgroundImage(images) => {
+        for image in images {
+          image.collect_urls(&mut urls);
+        }
+      }
+      Property::Background(backgrounds) => {
+        for background in backgrounds {
+          background.image.collect_urls(&mut urls);
+        }
+      }
+      Property::BorderImageSource(image) => image.collect_urls(&mut urls),
+      Property::BorderImage(border_image, _) => border_image.source.collect_urls(&mut urls),
+      Property::ListStyleImage(image) => image.collect_urls(&mut urls),
+      Property::ListStyle(list_style) => list_style.image.collect_urls(&mut urls),
+      Property::Content(Content::List(items, _)) => {
+        for item in items {
+          if let ContentItem::Image(image) = item {
+            image.collect_urls(&mut urls);
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+
+  dependencies.extend(urls.into_iter().map(|url| Dependency::Url(url.url.clone())));
+}
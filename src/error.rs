@@ -0,0 +1,48 @@
+use cssparser::*;
+use crate::limits::LimitKind;
+use std::fmt;
+
+/// The custom error type of the `ParseError`s returned while parsing, which also carry
+/// the location of the error. Errors from the tokenizer, e.g. unexpected tokens, are
+/// reported as basic errors by cssparser instead.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParserError<'i> {
+  /// A value is invalid, e.g. it is out of range or not allowed in the context it is used in.
+  InvalidValue,
+  /// The value of the given property could not be parsed.
+  InvalidPropertyValue(CowRcStr<'i>),
+  /// A declaration is invalid, e.g. its value contains a `{}` block.
+  InvalidDeclaration,
+  /// A selector could not be parsed.
+  InvalidSelector,
+  /// A media query could not be parsed, e.g. it references an unknown media feature.
+  InvalidMediaQuery,
+  /// The prelude of an at-rule is invalid.
+  AtRulePreludeInvalid,
+  /// The body of an at-rule is invalid, e.g. a required descriptor is missing.
+  AtRuleBodyInvalid,
+  /// An `@import` rule appears after rules other than `@charset` and other `@import` rules.
+  UnexpectedImportRule,
+  /// A `@namespace` rule appears after rules other than `@charset`, `@import`, and other
+  /// `@namespace` rules.
+  UnexpectedNamespaceRule,
+  /// One of the limits set when parsing was exceeded.
+  LimitExceeded(LimitKind)
+}
+
+impl<'i> fmt::Display for ParserError<'i> {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      ParserError::InvalidValue => f.write_str("Invalid value"),
+      ParserError::InvalidPropertyValue(name) => write!(f, "Invalid value for property `{}`", name),
+      ParserError::InvalidDeclaration => f.write_str("Invalid declaration"),
+      ParserError::InvalidSelector => f.write_str("Invalid selector"),
+      ParserError::InvalidMediaQuery => f.write_str("Invalid media query"),
+      ParserError::AtRulePreludeInvalid => f.write_str("Invalid at-rule prelude"),
+      ParserError::AtRuleBodyInvalid => f.write_str("Invalid at-rule body"),
+      ParserError::UnexpectedImportRule => f.write_str("@import rules must precede all rules aside from @charset"),
+      ParserError::UnexpectedNamespaceRule => f.write_str("@namespace rules must precede all rules aside from @charset and @import"),
+      ParserError::LimitExceeded(kind) => write!(f, "Limit exceeded: {:?}", kind)
+    }
+  }
+}
@@ -0,0 +1,164 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+use crate::{Browsers, StyleSheet, ParserOptions, MinifyOptions, PrinterOptions};
+
+/// The minimum version of each browser to generate output for, encoded as
+/// `major << 16 | minor << 8 | patch`, or 0 to ignore the browser.
+#[repr(C)]
+#[derive(Clone, Copy, Default)]
+pub struct CssTargets {
+  pub android: u32,
+  pub chrome: u32,
+  pub edge: u32,
+  pub firefox: u32,
+  pub ie: u32,
+  pub ios_saf: u32,
+  pub opera: u32,
+  pub safari: u32,
+  pub samsung: u32
+}
+
+impl From<&CssTargets> for Browsers {
+  fn from(targets: &CssTargets) -> Browsers {
+    let version = |v: u32| if v == 0 { None } else { Some(v) };
+    Browsers {
+      android: version(targets.android),
+      chrome: version(targets.chrome),
+      edge: version(targets.edge),
+      firefox: version(targets.firefox),
+      ie: version(targets.ie),
+      ios_saf: version(targets.ios_saf),
+      opera: version(targets.opera),
+      safari: version(targets.safari),
+      samsung: version(targets.samsung)
+    }
+  }
+}
+
+#[repr(C)]
+pub struct CssTransformOptions {
+  /// The name of the file, used as the source in source maps. May be null.
+  pub filename: *const c_char,
+  pub minify: bool,
+  pub source_map: bool,
+  /// The browsers to generate output for. May be null to leave the output untranspiled.
+  pub targets: *const CssTargets
+}
+
+#[repr(C)]
+pub struct CssWarning {
+  pub message: *mut c_char,
+  pub line: u32,
+  pub column: u32
+}
+
+#[repr(C)]
+pub struct CssTransformResult {
+  pub code: *mut c_char,
+  /// The source map as JSON, or null if `source_map` was not set.
+  pub map: *mut c_char,
+  pub warnings: *mut CssWarning,
+  pub warnings_len: usize
+}
+
+/// Transforms a stylesheet, for embedding the transformer in other languages. Strings are
+/// null terminated UTF-8, and the result must be released with `css_transform_result_free`.
+/// See `include/css_transformer.h` for the C declarations. Returns null if `code` is null or
+/// not valid UTF-8, or if the stylesheet cannot be printed. `options` may be null to minify
+/// without targets.
+///
+/// # Safety
+///
+/// `code` must be a null terminated string, and `options` must be null or point to valid
+/// options whose strings are null terminated.
+#[no_mangle]
+pub unsafe extern "C" fn css_transform(code: *const c_char, options: *const CssTransformOptions) -> *mut CssTransformResult {
+  if code.is_null() {
+    return ptr::null_mut()
+  }
+
+  let code = match CStr::from_ptr(code).to_str() {
+    Ok(code) => code,
+    Err(_) => return ptr::null_mut()
+  };
+
+  let (filename, minify, source_map, targets) = match options.as_ref() {
+    Some(options) => (
+      if options.filename.is_null() { String::new() } else { CStr::from_ptr(options.filename).to_string_lossy().into_owned() },
+      options.minify,
+      options.source_map,
+      options.targets.as_ref().map(Browsers::from)
+    ),
+    None => (String::new(), true, false, None)
+  };
+
+  let mut stylesheet = StyleSheet::parse(code, ParserOptions {
+    filename,
+    ..ParserOptions::default()
+  });
+  stylesheet.minify(MinifyOptions {
+    targets,
+    ..MinifyOptions::default()
+  });
+  let res = match stylesheet.to_css(PrinterOptions {
+    minify,
+    targets,
+    source_map,
+    ..PrinterOptions::default()
+  }) {
+    Ok(res) => res,
+    Err(_) => return ptr::null_mut()
+  };
+
+  let warnings: Box<[CssWarning]> = stylesheet.warnings.iter().map(|warning| CssWarning {
+    message: into_c_string(warning.message()),
+    line: warning.line,
+    column: warning.column
+  }).collect();
+  let warnings_len = warnings.len();
+
+  Box::into_raw(Box::new(CssTransformResult {
+    code: into_c_string(res.code),
+    map: res.map.map_or(ptr::null_mut(), |map| into_c_string(map.to_json(None))),
+    warnings: Box::into_raw(warnings) as *mut CssWarning,
+    warnings_len
+  }))
+}
+
+/// Releases a result returned by `css_transform`, including its strings and warnings.
+///
+/// # Safety
+///
+/// `result` must be null or a result returned by `css_transform` that was not freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn css_transform_result_free(result: *mut CssTransformResult) {
+  if result.is_null() {
+    return
+  }
+
+  let result = Box::from_raw(result);
+  free_c_string(result.code);
+  free_c_string(result.map);
+  let warnings = Box::from_raw(ptr::slice_from_raw_parts_mut(result.warnings, result.warnings_len));
+  for warning in warnings.iter() {
+    free_c_string(warning.message);
+  }
+}
+
+/// Converts a string to a C string, dropping any interior null bytes, which can
+/// only come from escapes in the source and would otherwise truncate the string.
+fn into_c_string(s: String) -> *mut c_char {
+  let s = CString::new(s).unwrap_or_else(|err| {
+    let mut bytes = err.into_vec();
+    bytes.retain(|b| *b != 0);
+    CString::new(bytes).unwrap()
+  });
+  s.into_raw()
+}
+
+unsafe fn free_c_string(s: *mut c_char) {
+  if !s.is_null() {
+    drop(CString::from_raw(s));
+  }
+}
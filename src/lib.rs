@@ -1,8 +1,11 @@
+#[cfg(feature = "node")]
 extern crate napi;
+#[cfg(feature = "node")]
 #[macro_use]
 extern crate napi_derive;
 extern crate serde;
 extern crate serde_bytes;
+extern crate serde_json;
 extern crate cssparser;
 extern crate selectors;
 extern crate itertools;
@@ -13,176 +16,53 @@ extern crate bitflags;
 #[global_allocator]
 static GLOBAL: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
-mod parser;
-mod rules;
-mod declaration;
-mod media_query;
-mod selector;
-mod properties;
-mod values;
-mod printer;
-mod traits;
+pub mod error;
+pub mod parser;
+pub mod rules;
+pub mod declaration;
+pub mod media_query;
+pub mod selector;
+pub mod properties;
+pub mod values;
+pub mod printer;
+pub mod traits;
 mod macros;
+mod compat;
+mod nesting;
+pub mod stylesheet;
+pub mod bundler;
+pub mod batch;
+pub mod limits;
+pub mod warnings;
+pub mod dependencies;
+pub mod stats;
+mod rename;
+pub mod css_modules;
+pub mod cache;
+pub mod value_hooks;
+pub mod source_map;
+pub mod visitor;
+#[cfg(feature = "browserslist")]
+pub mod targets;
+mod custom_properties;
+mod unused_symbols;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(feature = "node")]
+mod node;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+pub use printer::{PrinterOptions, PreserveComments};
+pub use properties::prefixes::Browsers;
+pub use stylesheet::{StyleSheet, StyleAttribute, ParserOptions, MinifyOptions, ToCssResult};
+pub use visitor::{Visitor, Visit};
 
-use napi::{CallContext, JsObject, JsBuffer};
-use serde::{Deserialize, Serialize};
-use cssparser::{Parser, ParserInput, RuleListParser};
-use crate::traits::ToCss;
-use printer::Printer;
-use properties::VendorPrefix;
-use properties::prefixes::{Browsers, Feature};
-use declaration::DeclarationHandler;
-use std::collections::HashMap;
-
-use parser::TopLevelRuleParser;
-
-#[derive(Serialize, Debug, Deserialize)]
-struct Config {
-  filename: String,
-  #[serde(with = "serde_bytes")]
-  code: Vec<u8>,
-  targets: Option<Browsers>
-}
-
-#[js_function(1)]
-fn transform(ctx: CallContext) -> napi::Result<JsBuffer> {
-  let opts = ctx.get::<JsObject>(0)?;
-  let config: Config = ctx.env.from_js_value(opts)?;
-
-  let code = unsafe { std::str::from_utf8_unchecked(&config.code) };
-  let res = compile(code, true, config.targets);
-
-  Ok(ctx.env.create_buffer_with_data(res.into_bytes())?.into_raw())
-}
-
-fn compile(code: &str, minify: bool, targets: Option<Browsers>) -> String {
-  let mut input = ParserInput::new(&code);
-  let mut parser = Parser::new(&mut input);
-  let rule_list = RuleListParser::new_for_stylesheet(&mut parser, TopLevelRuleParser {});
-
-  let mut dest = String::new();
-  let mut printer = Printer::new(&mut dest, minify);
-  let mut first = true;
-  let mut rules = vec![];
-
-  let mut handler = DeclarationHandler::new(false, targets);
-  let mut important_handler = DeclarationHandler::new(true, targets);
-  let mut keyframe_rules = HashMap::new();
-
-  for rule in rule_list {
-    let rule = if let Ok((_, rule)) = rule {
-      rule
-    } else {
-      continue
-    };
-    // println!("{:?}", rule);
-    let rule = match rule {
-      parser::CssRule::Import(import) => {
-        parser::CssRule::Import(parser::ImportRule {
-          media: import.media,
-          url: "test".into()
-        })
-      },
-      parser::CssRule::Keyframes(mut keyframes) => {
-        for keyframe in keyframes.keyframes.iter_mut() {
-          keyframe.declarations.minify(&mut handler, &mut important_handler);
-        }
-
-        macro_rules! set_prefix {
-          ($keyframes: ident) => {
-            if $keyframes.vendor_prefix.contains(VendorPrefix::None) {
-              if let Some(targets) = targets {
-                $keyframes.vendor_prefix = Feature::AtKeyframes.prefixes_for(targets)
-              }
-            }
-          };
-        }
-
-        // If there is an existing rule with the same name and identical keyframes,
-        // merge the vendor prefixes from this rule into it.
-        if let Some(existing_idx) = keyframe_rules.get(&keyframes.name) {
-          if let Some(parser::CssRule::Keyframes(existing)) = &mut rules.get_mut(*existing_idx) {
-            if existing.keyframes == keyframes.keyframes {
-              existing.vendor_prefix |= keyframes.vendor_prefix;
-              set_prefix!(existing);
-              continue;
-            }
-          }
-        }
-
-        set_prefix!(keyframes);
-        keyframe_rules.insert(keyframes.name.clone(), rules.len());
-        parser::CssRule::Keyframes(keyframes)
-      }
-      parser::CssRule::Media(mut media) => {
-        for rule in media.rules.iter_mut() {
-          match rule {
-            parser::CssRule::Style(style) => {
-              style.declarations.minify(&mut handler, &mut important_handler)
-            }
-            _ => {}
-          }
-        }
-        parser::CssRule::Media(media)
-      }
-      parser::CssRule::Style(mut style) => {
-        for selector in style.selectors.0.iter() {
-          for x in selector.iter() {
-            match x {
-              selectors::parser::Component::Class(c) => {
-                // if c == "hi" {
-                //   selectors::parser::Component::Class("RENAMED".into())
-                // } else {
-                //   selectors::parser::Component::Class(c.clone())
-                // }
-                if c == "hi" {
-                  c.replace("RENAMED".into());
-                }
-              },
-              _ => {}
-            }
-          }
-        }
-
-        style.declarations.minify(&mut handler, &mut important_handler);
-
-        if let Some(parser::CssRule::Style(last_style_rule)) = rules.last_mut() {
-          if style.selectors == last_style_rule.selectors {
-            last_style_rule.declarations.declarations.extend(style.declarations.declarations);
-            last_style_rule.declarations.minify(&mut handler, &mut important_handler);
-            continue
-          } else if style.declarations == last_style_rule.declarations {
-            last_style_rule.selectors.0.extend(style.selectors.0);
-            continue
-          }
-        }
-
-        parser::CssRule::Style(style)
-      },
-      r => r
-    };
-    rules.push(rule);
-  }
-
-  for rule in rules {
-    if first {
-      first = false;
-    } else {
-      printer.newline();
-    }
-
-    rule.to_css(&mut printer);
-    printer.newline();
-  }
-
-  dest
-}
-
-#[module_exports]
-fn init(mut exports: JsObject) -> napi::Result<()> {
-  exports.create_named_method("transform", transform)?;
-
-  Ok(())
+#[cfg(test)]
+fn compile(code: &str, parser_options: ParserOptions, minify_options: MinifyOptions, printer_options: PrinterOptions) -> String {
+  let mut stylesheet = StyleSheet::parse(code, parser_options);
+  stylesheet.minify(minify_options);
+  stylesheet.to_css(printer_options).unwrap().code
 }
 
 #[cfg(test)]
@@ -192,17 +72,49 @@ mod tests {
   use self::indoc::indoc;
 
   fn test(source: &str, expected: &str) {
-    let res = compile(source, false, None);
+    let res = compile(source, ParserOptions::default(), MinifyOptions::default(), PrinterOptions::default());
     assert_eq!(res, expected);
   }
 
   fn minify_test(source: &str, expected: &str) {
-    let res = compile(source, true, None);
+    let res = compile(source, ParserOptions::default(), MinifyOptions::default(), PrinterOptions {
+      minify: true,
+      ..PrinterOptions::default()
+    });
     assert_eq!(res, expected);
   }
 
   fn prefix_test(source: &str, expected: &str, targets: Browsers) {
-    let res = compile(source, false, Some(targets));
+    let res = compile(source, ParserOptions::default(), MinifyOptions {
+      targets: Some(targets),
+      ..MinifyOptions::default()
+    }, PrinterOptions {
+      targets: Some(targets),
+      ..PrinterOptions::default()
+    });
+    assert_eq!(res, expected);
+  }
+
+  fn nested_test(source: &str, expected: &str, targets: Option<Browsers>) {
+    let res = compile(source, ParserOptions::default(), MinifyOptions {
+      targets,
+      ..MinifyOptions::default()
+    }, PrinterOptions {
+      nested: true,
+      targets,
+      ..PrinterOptions::default()
+    });
+    assert_eq!(res, expected);
+  }
+
+  fn custom_media_test(source: &str, expected: &str) {
+    let res = compile(source, ParserOptions {
+      custom_media: true,
+      ..ParserOptions::default()
+    }, MinifyOptions::default(), PrinterOptions {
+      minify: true,
+      ..PrinterOptions::default()
+    });
     assert_eq!(res, expected);
   }
 
@@ -563,7 +475,7 @@ mod tests {
       }
     "#, indoc! {r#"
       .foo {
-        border-radius: 10px 100px / 120px;
+        border-radius: 10px 100px 100px 10px / 120px;
       }
     "#
     });
@@ -764,6 +676,173 @@ mod tests {
     })
   }
 
+  #[test]
+  fn test_border_radius_syntax() {
+    use crate::warnings::{Warning, WarningKind};
+
+    let cases = [
+      // Mixed lengths and percentages.
+      ("border-radius: 10px 50%", "border-radius:10px 50%"),
+      ("border-radius: 10px 50% / 50% 10px", "border-radius:10px 50%/50% 10px"),
+      ("border-radius: 50% / 50%", "border-radius:50%"),
+      ("border-radius: 10% 10% 10% 10% / 5px 5px 5px 5px", "border-radius:10%/5px"),
+      ("border-radius: 0 0% 0 0%", "border-radius:0"),
+      ("border-radius: 10px 0% / 0 10px", "border-radius:10px 0/0 10px"),
+      ("border-radius: 1px 2px 3px 4px", "border-radius:1px 2px 3px 4px"),
+      ("border-radius: 1px 2px 3px 2px", "border-radius:1px 2px 3px"),
+      ("border-radius: 1px 2px 1px 2px / 3px 4px 3px 4px", "border-radius:1px 2px/3px 4px"),
+      ("border-radius: 1px 2px 3px 4px / 1px 2px 3px 4px", "border-radius:1px 2px 3px 4px"),
+      // Whitespace around the slash.
+      ("border-radius: 10px/20px", "border-radius:10px/20px"),
+      ("border-radius: 10px /20px", "border-radius:10px/20px"),
+      // Invalid values are kept as written.
+      ("border-radius: / 10px", "border-radius:/ 10px"),
+      ("border-radius: 10px /", "border-radius:10px /"),
+      ("border-radius: -10px", "border-radius:-10px"),
+      ("border-radius: 10px -10px / 5px", "border-radius:10px -10px / 5px"),
+      ("border-radius: 10px / -5%", "border-radius:10px / -5%"),
+      ("border-top-left-radius: -1px 2px", "border-top-left-radius:-1px 2px"),
+      ("border-top-left-radius: 0% 50%", "border-top-left-radius:0 50%"),
+    ];
+
+    for (source, expected) in cases.iter() {
+      minify_test(&format!(".foo {{ {} }}", source), &format!(".foo{{{}}}", expected));
+    }
+
+    // The longhands map to the corners in clockwise order, starting at the top left.
+    minify_test(".foo { border-radius: 1px 2px 3px 4px; border-bottom-left-radius: 5px }", ".foo{border-radius:1px 2px 3px 5px}");
+    minify_test(".foo { border-radius: 1px 2px 3px 4px; border-bottom-right-radius: 5px }", ".foo{border-radius:1px 2px 5px 4px}");
+
+    // Negative calc() results are clamped rather than invalid.
+    minify_test(".foo { border-radius: calc(10px - 1em) }", ".foo{border-radius:calc(10px - 1em)}");
+
+    let (_, warnings) = StyleSheet::parse_with_warnings(".foo {\n  border-radius: 10px -10px;\n  border-top-left-radius: 1px;\n  border-top-right-radius: 2px -5%;\n}");
+    assert_eq!(warnings, vec![
+      Warning { kind: WarningKind::NegativeBorderRadius, line: 2, column: 23 },
+      Warning { kind: WarningKind::NegativeBorderRadius, line: 4, column: 32 }
+    ]);
+
+    let (_, warnings) = StyleSheet::parse_with_warnings(".foo { border-radius: 10px 50% / 0 }");
+    assert_eq!(warnings, vec![]);
+  }
+
+  #[test]
+  fn test_validate() {
+    use crate::warnings::{Warning, WarningKind};
+
+    let warnings = StyleSheet::validate(".foo {\n  color: red;\n  width: foo;\n  border-radius: -1px;\n  height: var(--h);\n  margin: inherit;\n  --custom: foo;\n}", None);
+    assert_eq!(warnings, vec![
+      Warning { kind: WarningKind::InvalidValue, line: 3, column: 10 },
+      Warning { kind: WarningKind::NegativeBorderRadius, line: 4, column: 18 }
+    ]);
+
+    let warnings = StyleSheet::validate("..foo { color: red }\n.bar { color: red }", None);
+    assert_eq!(warnings.iter().map(|w| w.kind).collect::<Vec<_>>(), vec![WarningKind::InvalidRule]);
+
+    let warnings = StyleSheet::validate(".foo { padding-top: env(safe-area-inset-top) }", Some(Browsers {
+      chrome: Some(60 << 16),
+      ..Browsers::default()
+    }));
+    assert_eq!(warnings, vec![
+      Warning { kind: WarningKind::UnsupportedFeature(compat::Feature::EnvFunction), line: 1, column: 21 }
+    ]);
+
+    let warnings = StyleSheet::validate(".foo { padding-top: env(safe-area-inset-top) }", Some(Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    }));
+    assert_eq!(warnings, vec![]);
+
+    let warnings = StyleSheet::validate(".foo { color: red }\n@media print, (min-width) {\n  .bar { color: red }\n}", None);
+    assert_eq!(warnings, vec![
+      Warning { kind: WarningKind::InvalidMediaQuery, line: 2, column: 16 }
+    ]);
+
+    // Invalid values are only reported when validating.
+    let (_, warnings) = StyleSheet::parse_with_warnings(".foo { width: foo }");
+    assert_eq!(warnings, vec![]);
+  }
+
+  #[cfg(feature = "node")]
+  #[test]
+  fn test_node_warnings() {
+    use crate::warnings::{Warning, WarningKind};
+
+    // The warnings returned by the Node API are collected regardless of error recovery.
+    let (stylesheet, warnings) = node::parse(".foo {\n  width: foo;\n}\n..bar { color: red }", ParserOptions::default(), None);
+    assert_eq!(warnings[0], Warning { kind: WarningKind::InvalidValue, line: 2, column: 10 });
+    assert_eq!(warnings[1].kind, WarningKind::InvalidRule);
+    assert_eq!(warnings.len(), 2);
+    assert_eq!(stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap().code, ".foo{width:foo}");
+
+    let (_, warnings) = node::parse(".foo {\n  width: foo;\n}", ParserOptions {
+      error_recovery: true,
+      ..ParserOptions::default()
+    }, None);
+    assert_eq!(warnings, vec![Warning { kind: WarningKind::InvalidValue, line: 2, column: 10 }]);
+
+    let (_, warnings) = node::parse(".foo { color: red }", ParserOptions::default(), None);
+    assert_eq!(warnings, vec![]);
+  }
+
+  #[test]
+  fn test_error_recovery() {
+    use crate::warnings::{Warning, WarningKind};
+
+    let code = ".foo {\n  color: red;\n  width: foo;\n  height: var(--h);\n  border-radius: -1px;\n}\n..bar { color: red }\n.baz { color: green }";
+    let print = |stylesheet: &StyleSheet| stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap().code;
+
+    let stylesheet = StyleSheet::parse(code, ParserOptions {
+      error_recovery: true,
+      ..ParserOptions::default()
+    });
+    assert_eq!(print(&stylesheet), ".foo{color:red;height:var(--h)}.baz{color:green}");
+    assert_eq!(stylesheet.warnings[..2], [
+      Warning { kind: WarningKind::InvalidValue, line: 3, column: 10 },
+      Warning { kind: WarningKind::NegativeBorderRadius, line: 5, column: 18 }
+    ]);
+    assert_eq!(stylesheet.warnings[2].kind, WarningKind::InvalidRule);
+    assert_eq!(stylesheet.warnings[2].line, 7);
+    assert_eq!(stylesheet.warnings.len(), 3);
+    assert_eq!(stylesheet.warnings[0].to_string(), "Invalid property value at 3:10");
+
+    let stylesheet = StyleSheet::parse(".foo {\n  color: red;\n  color: blue !importantx;\n}", ParserOptions {
+      error_recovery: true,
+      ..ParserOptions::default()
+    });
+    assert_eq!(print(&stylesheet), ".foo{color:red}");
+    assert_eq!(stylesheet.warnings, vec![
+      Warning { kind: WarningKind::InvalidDeclaration, line: 3, column: 15 }
+    ]);
+
+    // Otherwise, invalid values are kept as is.
+    let stylesheet = StyleSheet::parse(code, ParserOptions::default());
+    assert_eq!(print(&stylesheet), ".foo{color:red;width:foo;height:var(--h);border-radius:-1px}.baz{color:green}");
+    assert_eq!(stylesheet.warnings, vec![]);
+  }
+
+  #[test]
+  fn test_parser_errors() {
+    use cssparser::{Parser, ParserInput, ParseErrorKind};
+    use crate::error::ParserError;
+    use crate::rules::custom_media::CustomMediaRule;
+    use crate::properties::Property;
+    use crate::warnings;
+
+    let mut input = ParserInput::new("narrow (max-width: 30em)");
+    let mut parser = Parser::new(&mut input);
+    let err = CustomMediaRule::parse(&mut parser).unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::Custom(ParserError::AtRulePreludeInvalid));
+    assert_eq!((err.location.line, err.location.column), (0, 7));
+
+    let mut input = ParserInput::new("foo");
+    let mut parser = Parser::new(&mut input);
+    let (res, _) = warnings::recover(None, || Property::parse("width".into(), &mut parser));
+    let err = res.unwrap_err();
+    assert_eq!(err.kind, ParseErrorKind::Custom(ParserError::InvalidPropertyValue("width".into())));
+    assert_eq!(ParserError::InvalidPropertyValue("width".into()).to_string(), "Invalid value for property `width`");
+  }
+
   #[test]
   pub fn test_outline() {
     test(r#"
@@ -904,2113 +983,4513 @@ mod tests {
   }
 
   #[test]
-  pub fn test_background() {
-    test(r#"
+  fn test_inset() {
+    minify_test(".foo { top: 0; right: 10px; bottom: 0; left: 10px }", ".foo{inset:0 10px}");
+    minify_test(".foo { inset: 0 10px; left: 20px }", ".foo{inset:0 10px 0 20px}");
+    minify_test(".foo { inset-block-start: 5px; inset-block-end: 5px }", ".foo{inset-block:5px}");
+    minify_test(".foo { inset-inline: 5px 10px }", ".foo{inset-inline:5px 10px}");
+    minify_test(".foo { top: 0; inset-inline-start: 5px }", ".foo{top:0;inset-inline-start:5px}");
+
+    prefix_test(r#"
       .foo {
-        background: url(img.png);
-        background-position-x: 20px;
-        background-position-y: 10px;
-        background-size: 50px 100px;
-        background-repeat: repeat no-repeat;
+        inset: 0 10px;
       }
     "#, indoc! {r#"
       .foo {
-        background: url(img.png) 20px 10px / 50px 100px repeat-x;
+        top: 0;
+        bottom: 0;
+        left: 10px;
+        right: 10px;
       }
     "#
+    }, Browsers {
+      chrome: Some(80 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        background-color: red;
-        background-position: 0% 0%;
-        background-size: auto;
-        background-repeat: repeat;
-        background-clip: border-box;
-        background-origin: padding-box;
-        background-attachment: scroll;
-        background-image: none
+        inset-block: 5px 10px;
+        inset-inline-start: 20px;
       }
     "#, indoc! {r#"
       .foo {
-        background: red;
+        top: 5px;
+        bottom: 10px;
       }
-    "#
-    });
 
-    test(r#"
-      .foo {
-        background-color: gray;
-        background-position: 40% 50%;
-        background-size: 10em auto;
-        background-repeat: round;
-        background-clip: border-box;
-        background-origin: border-box;
-        background-attachment: fixed;
-        background-image: url('chess.png');
+      [dir="ltr"] .foo {
+        left: 20px;
       }
-    "#, indoc! {r#"
-      .foo {
-        background: gray url(chess.png) 40% / 10em round fixed border-box;
+
+      [dir="rtl"] .foo {
+        right: 20px;
       }
     "#
+    }, Browsers {
+      safari: Some(13 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        background: url(img.png), url(test.jpg) gray;
-        background-position-x: right 20px, 10px;
-        background-position-y: top 20px, 15px;
-        background-size: 50px 50px, auto;
-        background-repeat: repeat no-repeat, no-repeat;      
+        inset-block: 5px 10px;
       }
     "#, indoc! {r#"
       .foo {
-        background: url(img.png) right 20px top 20px / 50px 50px repeat-x, gray url(test.jpg) 10px 15px no-repeat;
+        inset-block: 5px 10px;
       }
     "#
+    }, Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
     });
+  }
 
-    minify_test(r#"
-      .foo {
-        background-position: center center;
-      }
-    "#, indoc! {".foo{background-position:50% 50%}"
-    });
+  #[test]
+  fn test_logical_properties() {
+    minify_test(".foo { float: inline-start }", ".foo{float:inline-start}");
+    minify_test(".foo { clear: inline-end }", ".foo{clear:inline-end}");
+    minify_test(".foo { resize: block }", ".foo{resize:block}");
+    minify_test(".foo { caption-side: block-start }", ".foo{caption-side:block-start}");
+
+    let targets = Browsers {
+      safari: Some(13 << 16),
+      ..Browsers::default()
+    };
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        background: url(img.png) gray;
-        background-clip: content-box;
-        -webkit-background-clip: text;
+        margin-block-start: 10px;
+        padding-block: 5px 10px;
+        margin-inline: 5px;
       }
     "#, indoc! {r#"
       .foo {
-        background: gray url(img.png) padding-box content-box;
-        -webkit-background-clip: text;
+        margin-top: 10px;
+        margin-left: 5px;
+        margin-right: 5px;
+        padding-top: 5px;
+        padding-bottom: 10px;
       }
     "#
-    });
+    }, targets);
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        background: url(img.png) gray;
-        -webkit-background-clip: text;
-        background-clip: content-box;
+        margin-inline-start: 10px;
+        padding-inline: 5px 10px;
+        border-start-end-radius: 2px;
+        color: red;
       }
     "#, indoc! {r#"
       .foo {
-        background: gray url(img.png);
-        -webkit-background-clip: text;
-        background-clip: content-box;
+        color: red;
+      }
+
+      [dir="ltr"] .foo {
+        margin-left: 10px;
+        padding-left: 5px;
+        padding-right: 10px;
+        border-top-right-radius: 2px;
+      }
+
+      [dir="rtl"] .foo {
+        margin-right: 10px;
+        padding-right: 5px;
+        padding-left: 10px;
+        border-top-left-radius: 2px;
       }
     "#
-    });
-  }
+    }, targets);
 
-  #[test]
-  pub fn test_flex() {
-    test(r#"
+    prefix_test(r#"
       .foo {
-        flex-direction: column;
-        flex-wrap: wrap;
+        text-align: end;
       }
     "#, indoc! {r#"
-      .foo {
-        flex-flow: column wrap;
+      [dir="ltr"] .foo {
+        text-align: right;
+      }
+
+      [dir="rtl"] .foo {
+        text-align: left;
       }
     "#
+    }, Browsers {
+      ie: Some(11 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        flex-direction: row;
-        flex-wrap: wrap;
+        float: inline-start;
+        clear: inline-end;
+        resize: block;
+        caption-side: block-end;
       }
     "#, indoc! {r#"
       .foo {
-        flex-flow: wrap;
+        resize: vertical;
+        caption-side: bottom;
+      }
+
+      [dir="ltr"] .foo {
+        float: left;
+        clear: right;
+      }
+
+      [dir="rtl"] .foo {
+        float: right;
+        clear: left;
       }
     "#
+    }, Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        flex-direction: row;
-        flex-wrap: nowrap;
+        float: inline-start;
+        resize: inline;
       }
     "#, indoc! {r#"
       .foo {
-        flex-flow: row;
+        float: inline-start;
+        resize: inline;
       }
     "#
+    }, Browsers {
+      firefox: Some(70 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
-      .foo {
-        flex-direction: column;
-        flex-wrap: nowrap;
+    // :dir() is used when supported, except after pseudo elements.
+    prefix_test(r#"
+      .foo, .bar:before {
+        margin-inline-end: 10px !important;
       }
     "#, indoc! {r#"
-      .foo {
-        flex-flow: column;
+      .foo:dir(ltr), [dir="ltr"] .bar:before {
+        margin-right: 10px !important;
+      }
+
+      .foo:dir(rtl), [dir="rtl"] .bar:before {
+        margin-left: 10px !important;
       }
     "#
+    }, Browsers {
+      firefox: Some(60 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    // text-align: start is only compiled for browsers that don't support it.
+    prefix_test(r#"
       .foo {
-        flex-grow: 1;
-        flex-shrink: 1;
-        flex-basis: 0%;
+        text-align: start;
+        margin-inline-start: 10px;
       }
     "#, indoc! {r#"
       .foo {
-        flex: 1;
+        text-align: start;
+        margin-inline-start: 10px;
       }
     "#
+    }, Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
-      .foo {
-        flex-grow: 1;
-        flex-shrink: 0;
-        flex-basis: 0%;
+    // Rules for each direction can't be used in keyframes.
+    prefix_test(r#"
+      @keyframes test {
+        from {
+          margin-inline-start: 0;
+          margin-block-start: 0;
+        }
       }
     "#, indoc! {r#"
-      .foo {
-        flex: 1 0;
+      @keyframes test {
+        from {
+          margin-inline-start: 0;
+          margin-top: 0;
+        }
       }
     "#
-    });
+    }, targets);
+  }
 
-    test(r#"
+  #[test]
+  fn test_position() {
+    minify_test(".foo { position: absolute }", ".foo{position:absolute}");
+    minify_test(".foo { position: relative; position: fixed }", ".foo{position:fixed}");
+    minify_test(".foo { position: -webkit-sticky; position: sticky }", ".foo{position:-webkit-sticky;position:sticky}");
+
+    prefix_test(r#"
       .foo {
-        flex-grow: 1;
-        flex-shrink: 0;
-        flex-basis: auto;
+        position: sticky;
       }
     "#, indoc! {r#"
       .foo {
-        flex: 1 0 auto;
+        position: -webkit-sticky;
+        position: sticky;
       }
     "#
+    }, Browsers {
+      safari: Some(12 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        flex-grow: 1;
-        flex-shrink: 1;
-        flex-basis: auto;
+        position: -webkit-sticky;
+        position: sticky;
       }
     "#, indoc! {r#"
       .foo {
-        flex: auto;
-      }
-    "#
-    });
-
-    test(r#"
-      .foo {
-        flex: 0 0;
-        flex-grow: 1;
-      }
-    "#, indoc! {r#"
-      .foo {
-        flex: 1 0;
+        position: sticky;
       }
     "#
+    }, Browsers {
+      safari: Some(14 << 16),
+      ..Browsers::default()
     });
+  }
 
-    test(r#"
+  #[test]
+  fn test_overflow() {
+    minify_test(".foo { overflow: hidden }", ".foo{overflow:hidden}");
+    minify_test(".foo { overflow: hidden hidden }", ".foo{overflow:hidden}");
+    minify_test(".foo { overflow: hidden auto }", ".foo{overflow:hidden auto}");
+    minify_test(".foo { overflow-x: hidden; overflow-y: hidden }", ".foo{overflow:hidden}");
+    minify_test(".foo { overflow-x: hidden; overflow-y: auto }", ".foo{overflow:hidden auto}");
+    minify_test(".foo { overflow: hidden; overflow-y: scroll }", ".foo{overflow:hidden scroll}");
+    minify_test(".foo { overflow-x: clip }", ".foo{overflow-x:clip}");
+
+    prefix_test(r#"
       .foo {
-        align-content: center;
-        justify-content: center;
+        overflow: hidden auto;
       }
     "#, indoc! {r#"
       .foo {
-        place-content: center;
+        overflow-x: hidden;
+        overflow-y: auto;
       }
     "#
+    }, Browsers {
+      safari: Some(12 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        align-content: first baseline;
-        justify-content: safe right;
+        overflow-x: hidden;
+        overflow-y: hidden;
       }
     "#, indoc! {r#"
       .foo {
-        place-content: baseline safe right;
+        overflow: hidden;
       }
     "#
+    }, Browsers {
+      safari: Some(12 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    minify_test(".foo { text-overflow: ellipsis }", ".foo{text-overflow:ellipsis}");
+    minify_test(".foo { text-overflow: clip ellipsis }", ".foo{text-overflow:ellipsis}");
+    minify_test(".foo { text-overflow: ellipsis clip }", ".foo{text-overflow:ellipsis clip}");
+    minify_test(".foo { text-overflow: ellipsis '-' }", ".foo{text-overflow:ellipsis \"-\"}");
+  }
+
+  #[test]
+  fn test_text_decoration() {
+    minify_test(".foo { text-decoration: underline }", ".foo{text-decoration:underline}");
+    minify_test(".foo { text-decoration: none }", ".foo{text-decoration:none}");
+    minify_test(".foo { text-decoration: red dotted 2px overline underline }", ".foo{text-decoration:underline overline 2px dotted red}");
+    minify_test(".foo { text-decoration: underline solid currentColor auto }", ".foo{text-decoration:underline}");
+    minify_test(".foo { text-decoration-line: underline; text-decoration-style: wavy; text-decoration-color: red; text-decoration-thickness: auto }", ".foo{text-decoration:underline wavy red}");
+    minify_test(".foo { text-decoration-line: underline; text-decoration-style: wavy; text-decoration-color: red }", ".foo{text-decoration-line:underline;text-decoration-style:wavy;text-decoration-color:red}");
+    minify_test(".foo { text-decoration: underline; text-decoration-color: red }", ".foo{text-decoration:underline red}");
+    minify_test(".foo { text-decoration: underline; text-decoration-thickness: from-font }", ".foo{text-decoration:underline from-font}");
+    minify_test(".foo { text-underline-offset: 0.2em }", ".foo{text-underline-offset:.2em}");
+    minify_test(".foo { text-underline-position: left under }", ".foo{text-underline-position:under left}");
+    minify_test(".foo { text-underline-position: from-font }", ".foo{text-underline-position:from-font}");
+
+    prefix_test(r#"
       .foo {
-        place-content: first baseline unsafe left;
+        text-decoration: underline wavy red;
       }
     "#, indoc! {r#"
       .foo {
-        place-content: baseline unsafe left;
+        -webkit-text-decoration: underline wavy red;
+        text-decoration: underline wavy red;
       }
     "#
+    }, Browsers {
+      safari: Some(8 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        place-content: center center;
+        text-decoration: underline 2px;
       }
     "#, indoc! {r#"
       .foo {
-        place-content: center;
+        -webkit-text-decoration: underline;
+        text-decoration: underline 2px;
       }
     "#
+    }, Browsers {
+      safari: Some(8 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        align-self: center;
-        justify-self: center;
+        text-decoration-style: dotted;
       }
     "#, indoc! {r#"
       .foo {
-        place-self: center;
+        -webkit-text-decoration-style: dotted;
+        text-decoration-style: dotted;
       }
     "#
+    }, Browsers {
+      safari: Some(8 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        align-self: center;
-        justify-self: unsafe left;
+        -webkit-text-decoration: underline;
+        text-decoration: underline;
       }
     "#, indoc! {r#"
       .foo {
-        place-self: center unsafe left;
+        text-decoration: underline;
       }
     "#
+    }, Browsers {
+      safari: Some(14 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        align-items: center;
-        justify-items: center;
+        -webkit-text-decoration-color: red;
+        text-decoration: underline wavy red;
       }
     "#, indoc! {r#"
       .foo {
-        place-items: center;
+        -webkit-text-decoration: underline wavy red;
+        text-decoration: underline wavy red;
       }
     "#
+    }, Browsers {
+      safari: Some(8 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        align-items: center;
-        justify-items: legacy left;
+        -webkit-text-decoration-line: underline;
+        text-decoration: underline 2px wavy red;
       }
     "#, indoc! {r#"
       .foo {
-        place-items: center legacy left;
+        -webkit-text-decoration: underline wavy red;
+        text-decoration: underline 2px wavy red;
       }
     "#
+    }, Browsers {
+      safari: Some(8 << 16),
+      ..Browsers::default()
     });
+  }
 
-    test(r#"
+  #[test]
+  fn test_text_emphasis() {
+    minify_test(".foo { text-emphasis-style: filled dot }", ".foo{text-emphasis-style:dot}");
+    minify_test(".foo { text-emphasis-style: sesame open }", ".foo{text-emphasis-style:open sesame}");
+    minify_test(".foo { text-emphasis-style: filled }", ".foo{text-emphasis-style:filled}");
+    minify_test(".foo { text-emphasis-style: 'x' }", ".foo{text-emphasis-style:\"x\"}");
+    minify_test(".foo { text-emphasis: red double-circle }", ".foo{text-emphasis:double-circle red}");
+    minify_test(".foo { text-emphasis: none }", ".foo{text-emphasis:none}");
+    minify_test(".foo { text-emphasis-style: triangle; text-emphasis-color: red }", ".foo{text-emphasis:triangle red}");
+    minify_test(".foo { text-emphasis: circle; text-emphasis-color: red }", ".foo{text-emphasis:circle red}");
+    minify_test(".foo { text-emphasis-position: left under }", ".foo{text-emphasis-position:under left}");
+    minify_test(".foo { text-emphasis-position: over }", ".foo{text-emphasis-position:over right}");
+
+    prefix_test(r#"
       .foo {
-        row-gap: 10px;
-        column-gap: 20px;
+        text-emphasis: dot red;
+        text-emphasis-position: under left;
       }
     "#, indoc! {r#"
       .foo {
-        gap: 10px 20px;
+        -webkit-text-emphasis: dot red;
+        text-emphasis: dot red;
+        -webkit-text-emphasis-position: under left;
+        text-emphasis-position: under left;
       }
     "#
+    }, Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        row-gap: 10px;
-        column-gap: 10px;
+        -webkit-text-emphasis: dot red;
+        text-emphasis: dot red;
       }
     "#, indoc! {r#"
       .foo {
-        gap: 10px;
+        text-emphasis: dot red;
       }
     "#
+    }, Browsers {
+      chrome: Some(100 << 16),
+      ..Browsers::default()
     });
+  }
 
-    test(r#"
+  #[test]
+  fn test_initial_letter() {
+    minify_test(".foo { initial-letter: normal }", ".foo{initial-letter:normal}");
+    minify_test(".foo { initial-letter: 3 }", ".foo{initial-letter:3}");
+    minify_test(".foo { initial-letter: 3 drop }", ".foo{initial-letter:3}");
+    minify_test(".foo { initial-letter: raise 1.5 }", ".foo{initial-letter:1.5 raise}");
+    minify_test(".foo { initial-letter: 3 2 }", ".foo{initial-letter:3 2}");
+    minify_test(".foo { -webkit-initial-letter: 3 2 }", ".foo{-webkit-initial-letter:3 2}");
+    minify_test(".foo { -webkit-initial-letter: 2; initial-letter: 2 }", ".foo{-webkit-initial-letter:2;initial-letter:2}");
+
+    prefix_test(r#"
       .foo {
-        gap: 10px;
-        column-gap: 20px;
+        initial-letter: 3 2;
       }
     "#, indoc! {r#"
       .foo {
-        gap: 10px 20px;
+        -webkit-initial-letter: 3 2;
+        initial-letter: 3 2;
       }
-    "#
+    "#}, Browsers {
+      safari: Some(15 << 16),
+      chrome: Some(110 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        column-gap: 20px;
-        gap: 10px;
+        -webkit-initial-letter: 3;
+        initial-letter: 3;
       }
     "#, indoc! {r#"
       .foo {
-        gap: 10px;
+        initial-letter: 3;
       }
-    "#
+    "#}, Browsers {
+      chrome: Some(110 << 16),
+      ..Browsers::default()
     });
+  }
 
-    test(r#"
+  #[test]
+  fn test_offset() {
+    minify_test(".foo { offset-path: none }", ".foo{offset-path:none}");
+    minify_test(".foo { offset-path: path('M 0 0 L 100 100') }", ".foo{offset-path:path(\"M 0 0 L 100 100\")}");
+    minify_test(".foo { offset-path: ray(45deg closest-side) }", ".foo{offset-path:ray(45deg)}");
+    minify_test(".foo { offset-path: ray(farthest-corner 90deg contain) }", ".foo{offset-path:ray(90deg farthest-corner contain)}");
+    minify_test(".foo { offset-path: url(#path) }", ".foo{offset-path:url(#path)}");
+    minify_test(".foo { offset-path: circle(50px at 10px 20px) }", ".foo{offset-path:circle(50px at 10px 20px)}");
+    minify_test(".foo { offset-path: circle(closest-side at 50% 50%) }", ".foo{offset-path:circle()}");
+    minify_test(".foo { offset-path: ellipse(10px 20px) }", ".foo{offset-path:ellipse(10px 20px)}");
+    minify_test(".foo { offset-path: inset(10px 20px round 5px) }", ".foo{offset-path:inset(10px 20px round 5px)}");
+    minify_test(".foo { offset-path: polygon(evenodd, 0 0, 100% 0, 50% 100%) }", ".foo{offset-path:polygon(evenodd,0 0,100% 0,50% 100%)}");
+    minify_test(".foo { offset-path: polygon(nonzero, 0 0, 100% 0) }", ".foo{offset-path:polygon(0 0,100% 0)}");
+    minify_test(".foo { offset-distance: 50% }", ".foo{offset-distance:50%}");
+    minify_test(".foo { offset-rotate: auto }", ".foo{offset-rotate:auto}");
+    minify_test(".foo { offset-rotate: auto 0deg }", ".foo{offset-rotate:auto}");
+    minify_test(".foo { offset-rotate: 30deg reverse }", ".foo{offset-rotate:reverse 30deg}");
+    minify_test(".foo { offset-rotate: 30deg }", ".foo{offset-rotate:30deg}");
+    minify_test(".foo { offset-anchor: 10px 20px }", ".foo{offset-anchor:10px 20px}");
+    minify_test(".foo { offset-position: auto }", ".foo{offset-position:auto}");
+    minify_test(".foo { offset: path('M 0 0 H 100') 50% auto 30deg }", ".foo{offset:path(\"M 0 0 H 100\") 50% auto 30deg}");
+    minify_test(".foo { offset: 10px 20px }", ".foo{offset:10px 20px}");
+    minify_test(".foo { offset: none }", ".foo{offset:none}");
+    minify_test(".foo { offset: auto none 10px }", ".foo{offset:auto none 10px}");
+    minify_test(".foo { offset: ray(45deg) / 10px 20px }", ".foo{offset:ray(45deg)/10px 20px}");
+    minify_test(
+      ".foo { offset-position: normal; offset-path: ray(45deg); offset-distance: 10px; offset-rotate: auto; offset-anchor: auto }",
+      ".foo{offset:ray(45deg) 10px}"
+    );
+    minify_test(".foo { offset-path: ray(45deg); offset-distance: 10px }", ".foo{offset-path:ray(45deg);offset-distance:10px}");
+    minify_test(".foo { offset: ray(45deg); offset-distance: 10px }", ".foo{offset:ray(45deg) 10px}");
+    minify_test(".foo { motion-path: none }", ".foo{motion-path:none}");
+
+    prefix_test(r#"
       .foo {
-        row-gap: normal;
-        column-gap: 20px;
+        offset-path: path('M 0 0 H 100');
+        offset-distance: 50%;
       }
     "#, indoc! {r#"
       .foo {
-        gap: normal 20px;
+        motion-path: path("M 0 0 H 100");
+        motion-offset: 50%;
+        offset-path: path("M 0 0 H 100");
+        offset-distance: 50%;
       }
-    "#
+    "#}, Browsers {
+      chrome: Some(50 << 16),
+      ..Browsers::default()
     });
 
-    test(r#"
+    prefix_test(r#"
       .foo {
-        -webkit-flex-grow: 1;
-        -webkit-flex-shrink: 1;
-        -webkit-flex-basis: auto;
+        offset-path: ray(45deg);
+        offset-rotate: reverse;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-flex: auto;
+        motion-rotation: reverse;
+        offset-path: ray(45deg);
+        offset-rotate: reverse;
       }
-    "#
+    "#}, Browsers {
+      chrome: Some(50 << 16),
+      ..Browsers::default()
     });
-    test(r#"
+
+    prefix_test(r#"
       .foo {
-        -webkit-flex-grow: 1;
-        -webkit-flex-shrink: 1;
-        -webkit-flex-basis: auto;
-        flex-grow: 1;
-        flex-shrink: 1;
-        flex-basis: auto;
+        motion-path: path('M 0 0 H 100');
+        offset-path: path('M 0 0 H 100');
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-flex: auto;
-        flex: auto;
+        offset-path: path("M 0 0 H 100");
       }
-    "#
+    "#}, Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
     });
+  }
+
+  #[test]
+  fn test_list_style() {
+    minify_test(".foo { list-style-type: square }", ".foo{list-style-type:square}");
+    minify_test(".foo { list-style-type: '-' }", ".foo{list-style-type:\"-\"}");
+    minify_test(".foo { list-style-type: symbols(cyclic '*' '+') }", ".foo{list-style-type:symbols(cyclic \"*\"\"+\")}");
+    minify_test(".foo { list-style-type: symbols(symbolic url(star.png)) }", ".foo{list-style-type:symbols(url(star.png))}");
+    minify_test(".foo { list-style-image: url(star.png) }", ".foo{list-style-image:url(star.png)}");
+    minify_test(".foo { list-style-position: inside }", ".foo{list-style-position:inside}");
+    minify_test(".foo { list-style: none }", ".foo{list-style:none}");
+    minify_test(".foo { list-style: inside square }", ".foo{list-style:inside square}");
+    minify_test(".foo { list-style: square outside url(star.png) }", ".foo{list-style:url(star.png) square}");
+    minify_test(".foo { list-style: disc outside none }", ".foo{list-style:disc}");
+    minify_test(".foo { list-style: none url(star.png) }", ".foo{list-style:url(star.png) none}");
+    minify_test(".foo { list-style-type: square; list-style-position: inside; list-style-image: none }", ".foo{list-style:inside square}");
+    minify_test(".foo { list-style-type: square; list-style-position: inside }", ".foo{list-style-type:square;list-style-position:inside}");
+    minify_test(".foo { list-style: inside; list-style-type: decimal }", ".foo{list-style:inside decimal}");
+  }
+
+  #[test]
+  fn test_content() {
+    minify_test(".foo { content: normal }", ".foo{content:normal}");
+    minify_test(".foo { content: none }", ".foo{content:none}");
+    minify_test(".foo { content: 'foo' }", ".foo{content:\"foo\"}");
+    minify_test(".foo { content: 'foo' 'bar' }", ".foo{content:\"foobar\"}");
+    minify_test(".foo { content: 'a\"b' }", ".foo{content:\"a\\\"b\"}");
+    minify_test(".foo { content: '(' counter(item) ')' }", ".foo{content:\"(\"counter(item)\")\"}");
+    minify_test(".foo { content: counter(item, decimal) }", ".foo{content:counter(item)}");
+    minify_test(".foo { content: counter(item, upper-roman) }", ".foo{content:counter(item,upper-roman)}");
+    minify_test(".foo { content: counters(item, '.', lower-alpha) ' ' }", ".foo{content:counters(item,\".\",lower-alpha)\" \"}");
+    minify_test(".foo { content: open-quote attr(title) close-quote }", ".foo{content:open-quote attr(title) close-quote}");
+    minify_test(".foo { content: no-open-quote }", ".foo{content:no-open-quote}");
+    minify_test(".foo { content: url(icon.png) / 'Icon' }", ".foo{content:url(icon.png)/\"Icon\"}");
+    minify_test(".foo { content: '→' / 'Next' ' ' 'page' }", ".foo{content:\"→\"/\"Next page\"}");
+    minify_test(".foo { content: counter(item, none) }", ".foo{content:counter(item,none)}");
+    test(".foo { content: 'a' 'b' counter(x) / 'c' }", indoc! {r#"
+      .foo {
+        content: "a" "b" counter(x) / "c";
+      }
+    "#});
+  }
+
+  #[test]
+  fn test_will_change() {
+    use crate::warnings::{Warning, WarningKind};
+
+    minify_test(".foo { will-change: auto }", ".foo{will-change:auto}");
+    minify_test(".foo { will-change: transform }", ".foo{will-change:transform}");
+    minify_test(".foo { will-change: Transform, opacity }", ".foo{will-change:transform,opacity}");
+    minify_test(".foo { will-change: scroll-position, contents }", ".foo{will-change:scroll-position,contents}");
+    minify_test(".foo { will-change: transform, opacity, transform }", ".foo{will-change:transform,opacity}");
+    minify_test(".foo { will-change: --Foo, --Foo }", ".foo{will-change:--Foo}");
+
+    let warnings = StyleSheet::validate(".foo { will-change: transform, colour }", None);
+    assert_eq!(warnings, vec![
+      Warning { kind: WarningKind::UnknownProperty, line: 1, column: 32 }
+    ]);
+    assert_eq!(StyleSheet::validate(".foo { will-change: -webkit-transform, --foo }", None), vec![]);
+  }
+
+  #[test]
+  fn test_appearance() {
+    minify_test(".foo { appearance: none }", ".foo{appearance:none}");
+    minify_test(".foo { appearance: MENULIST-BUTTON }", ".foo{appearance:menulist-button}");
+    minify_test(".foo { -webkit-appearance: textfield }", ".foo{-webkit-appearance:textfield}");
+    minify_test(".foo { -webkit-appearance: caret }", ".foo{-webkit-appearance:caret}");
+    minify_test(".foo { -webkit-appearance: none; -moz-appearance: none; appearance: none }", ".foo{-webkit-appearance:none;-moz-appearance:none;appearance:none}");
+
     prefix_test(r#"
       .foo {
-        -webkit-box-orient: horizontal;
-        -webkit-box-direction: normal;
-        flex-direction: row;
+        appearance: none;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-box-orient: horizontal;
-        -webkit-box-direction: normal;
-        -webkit-flex-direction: row;
-        flex-direction: row;
+        -webkit-appearance: none;
+        -moz-appearance: none;
+        appearance: none;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
+    "#}, Browsers {
+      chrome: Some(80 << 16),
+      firefox: Some(70 << 16),
       ..Browsers::default()
     });
+
     prefix_test(r#"
       .foo {
-        flex-direction: row;
+        -webkit-appearance: none;
+        -moz-appearance: none;
+        appearance: none;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-box-orient: horizontal;
-        -moz-box-orient: horizontal;
-        -webkit-box-direction: normal;
-        -moz-box-direction: normal;
-        -webkit-flex-direction: row;
-        -ms-flex-direction: row;
-        flex-direction: row;
+        appearance: none;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
+    "#}, Browsers {
+      chrome: Some(90 << 16),
+      firefox: Some(90 << 16),
       ..Browsers::default()
     });
+
     prefix_test(r#"
       .foo {
-        -webkit-box-orient: horizontal;
-        -webkit-box-direction: normal;
-        -moz-box-orient: horizontal;
-        -moz-box-direction: normal;
-        -webkit-flex-direction: row;
-        -ms-flex-direction: row;
-        flex-direction: row;
+        -webkit-appearance: none;
+        appearance: auto;
       }
     "#, indoc! {r#"
       .foo {
-        flex-direction: row;
+        -webkit-appearance: auto;
+        appearance: auto;
       }
-    "#},
-    Browsers {
+    "#}, Browsers {
       safari: Some(14 << 16),
       ..Browsers::default()
     });
+
     prefix_test(r#"
       .foo {
-        flex-wrap: wrap;
+        -webkit-appearance: none;
+        appearance: auto;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-box-lines: multiple;
-        -moz-box-lines: multiple;
-        -webkit-flex-wrap: wrap;
-        -ms-flex-wrap: wrap;
-        flex-wrap: wrap;
+        -webkit-appearance: auto;
+        appearance: auto;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
+    "#}, Browsers {
+      edge: Some(18 << 16),
+      ie: Some(11 << 16),
       ..Browsers::default()
     });
+  }
+
+  #[test]
+  fn test_columns() {
+    minify_test(".foo { columns: 100px 3 }", ".foo{columns:75pt 3}");
+    minify_test(".foo { columns: 3 100px }", ".foo{columns:75pt 3}");
+    minify_test(".foo { columns: 3 auto }", ".foo{columns:3}");
+    minify_test(".foo { columns: auto auto }", ".foo{columns:auto}");
+    minify_test(".foo { column-width: 10em; column-count: 2 }", ".foo{columns:10em 2}");
+    minify_test(".foo { columns: 3; column-width: 10em }", ".foo{columns:10em 3}");
+    minify_test(".foo { column-count: 2 }", ".foo{column-count:2}");
+    minify_test(".foo { column-rule: 1px solid red }", ".foo{column-rule:1px solid red}");
+    minify_test(".foo { column-rule-width: 2px; column-rule-style: dashed; column-rule-color: blue }", ".foo{column-rule:2px dashed #00f}");
+    minify_test(".foo { column-rule-style: dotted }", ".foo{column-rule-style:dotted}");
+    minify_test(".foo { column-span: all }", ".foo{column-span:all}");
+    minify_test(".foo { column-fill: balance-all }", ".foo{column-fill:balance-all}");
+    minify_test(".foo { -webkit-columns: 2; columns: 2 }", ".foo{-webkit-columns:2;columns:2}");
+
     prefix_test(r#"
       .foo {
-        -webkit-box-lines: multiple;
-        -moz-box-lines: multiple;
-        -webkit-flex-wrap: wrap;
-        -ms-flex-wrap: wrap;
-        flex-wrap: wrap;
+        columns: 200px 2;
+        column-gap: 20px;
       }
     "#, indoc! {r#"
       .foo {
-        flex-wrap: wrap;
+        -webkit-column-gap: 20px;
+        -moz-column-gap: 20px;
+        grid-column-gap: 20px;
+        column-gap: 20px;
+        -webkit-columns: 200px 2;
+        -moz-columns: 200px 2;
+        columns: 200px 2;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
+    "#}, Browsers {
+      chrome: Some(40 << 16),
+      firefox: Some(40 << 16),
       ..Browsers::default()
     });
+
     prefix_test(r#"
       .foo {
-        flex-flow: row wrap;
+        -webkit-columns: 2;
+        -moz-columns: 2;
+        columns: 2;
+        -webkit-column-gap: 1em;
+        column-gap: 1em;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-box-orient: horizontal;
-        -moz-box-orient: horizontal;
-        -webkit-box-direction: normal;
-        -moz-box-direction: normal;
-        -webkit-flex-flow: wrap;
-        -ms-flex-flow: wrap;
-        flex-flow: wrap;
+        column-gap: 1em;
+        columns: 2;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
+    "#}, Browsers {
+      chrome: Some(90 << 16),
+      firefox: Some(90 << 16),
       ..Browsers::default()
     });
+  }
+
+  #[test]
+  fn test_scroll_snap() {
+    minify_test(".foo { scroll-snap-type: none }", ".foo{scroll-snap-type:none}");
+    minify_test(".foo { scroll-snap-type: x mandatory }", ".foo{scroll-snap-type:x mandatory}");
+    minify_test(".foo { scroll-snap-type: y proximity }", ".foo{scroll-snap-type:y}");
+    minify_test(".foo { scroll-snap-align: start }", ".foo{scroll-snap-align:start}");
+    minify_test(".foo { scroll-snap-align: center center }", ".foo{scroll-snap-align:center}");
+    minify_test(".foo { scroll-snap-align: none end }", ".foo{scroll-snap-align:none end}");
+    minify_test(".foo { scroll-snap-stop: always }", ".foo{scroll-snap-stop:always}");
+    minify_test(".foo { scroll-behavior: smooth }", ".foo{scroll-behavior:smooth}");
+    minify_test(".foo { scroll-padding: 10px 10px }", ".foo{scroll-padding:10px}");
+    minify_test(".foo { -ms-scroll-snap-type: mandatory }", ".foo{-ms-scroll-snap-type:mandatory}");
+
     prefix_test(r#"
       .foo {
-        -webkit-box-orient: horizontal;
-        -moz-box-orient: horizontal;
-        -webkit-box-direction: normal;
-        -moz-box-direction: normal;
-        -webkit-flex-flow: wrap;
-        -ms-flex-flow: wrap;
-        flex-flow: wrap;
+        scroll-snap-type: x mandatory;
       }
     "#, indoc! {r#"
       .foo {
-        flex-flow: wrap;
+        -ms-scroll-snap-type: mandatory;
+        scroll-snap-type: x mandatory;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
+    "#}, Browsers {
+      edge: Some(16 << 16),
       ..Browsers::default()
     });
+
     prefix_test(r#"
       .foo {
-        flex-grow: 1;
+        -ms-scroll-snap-type: proximity;
+        scroll-snap-type: both;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-box-flex: 1;
-        -moz-box-flex: 1;
-        -ms-flex-positive: 1;
-        -webkit-flex-grow: 1;
-        flex-grow: 1;
+        scroll-snap-type: both;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
+    "#}, Browsers {
+      edge: Some(90 << 16),
       ..Browsers::default()
     });
+  }
+
+  #[test]
+  fn test_grid() {
+    use crate::warnings::{Warning, WarningKind};
+
+    minify_test(".foo { grid-template-columns: 100px 1fr 2fr }", ".foo{grid-template-columns:75pt 1fr 2fr}");
+    minify_test(
+      ".foo { grid-template-columns: [full-start] minmax(1em, 1fr) [main-start] minmax(10px, 40em) [main-end full-end] }",
+      ".foo{grid-template-columns:[full-start] minmax(1em,1fr) [main-start] minmax(10px,40em) [main-end full-end]}"
+    );
+    minify_test(".foo { grid-template-columns: repeat(auto-fill, minmax(100px, 1fr)) }", ".foo{grid-template-columns:repeat(auto-fill,minmax(75pt,1fr))}");
+    minify_test(".foo { grid-template-columns: repeat(3, [col] 1fr) }", ".foo{grid-template-columns:repeat(3,[col] 1fr)}");
+    minify_test(".foo { grid-template-rows: none }", ".foo{grid-template-rows:none}");
+    minify_test(".foo { grid-template-rows: fit-content(50%) min-content auto }", ".foo{grid-template-rows:fit-content(50%) min-content auto}");
+    minify_test(".foo { grid-row: 1 / 3 }", ".foo{grid-row:1/3}");
+    minify_test(".foo { grid-row: span 2 / 5 }", ".foo{grid-row:span 2/5}");
+    minify_test(".foo { grid-row: 2 / auto }", ".foo{grid-row:2}");
+    minify_test(".foo { grid-column: header / header }", ".foo{grid-column:header}");
+    minify_test(".foo { grid-column: header / auto }", ".foo{grid-column:header/auto}");
+    minify_test(".foo { grid-column-start: span header }", ".foo{grid-column-start:span header}");
+    minify_test(".foo { grid-column-start: header span 2 }", ".foo{grid-column-start:span 2 header}");
+    minify_test(".foo { grid-column-end: -1 }", ".foo{grid-column-end:-1}");
+    minify_test(".foo { grid-area: 1 / 2 / 3 / 4 }", ".foo{grid-area:1/2/3/4}");
+    minify_test(".foo { grid-area: 1 / 2 / auto / auto }", ".foo{grid-area:1/2}");
+    minify_test(".foo { grid-area: header / header / header / header }", ".foo{grid-area:header}");
+    minify_test(".foo { grid-area: header / main / header }", ".foo{grid-area:header/main}");
+    minify_test(".foo { grid-area: header / auto }", ".foo{grid-area:header/auto}");
+    minify_test(".foo { grid-area: span 2 / 3 }", ".foo{grid-area:span 2/3}");
+    minify_test(".foo { grid-auto-flow: row dense }", ".foo{grid-auto-flow:dense}");
+    minify_test(".foo { grid-auto-flow: dense column }", ".foo{grid-auto-flow:column dense}");
+    minify_test(".foo { -ms-grid-columns: 100px (1fr 10px)[3] }", ".foo{-ms-grid-columns:75pt (1fr 10px)[3]}");
+    minify_test(".foo { -ms-grid-row: 2; -ms-grid-row-span: 3 }", ".foo{-ms-grid-row:2;-ms-grid-row-span:3}");
+
+    let ie = Browsers {
+      ie: Some(11 << 16),
+      ..Browsers::default()
+    };
+
     prefix_test(r#"
       .foo {
-        -webkit-box-flex: 1;
-        -moz-box-flex: 1;
-        -ms-flex-positive: 1;
-        -webkit-flex-grow: 1;
-        flex-grow: 1;
+        display: grid;
+        grid-template-columns: 100px repeat(2, [col] 1fr 10px);
+        grid-template-rows: auto;
+      }
+      .bar {
+        grid-row: 1 / 3;
+        grid-column: 2;
       }
     "#, indoc! {r#"
       .foo {
-        flex-grow: 1;
+        display: -ms-grid;
+        display: grid;
+        -ms-grid-columns: 100px (1fr 10px)[2];
+        grid-template-columns: 100px repeat(2, [col] 1fr 10px);
+        -ms-grid-rows: auto;
+        grid-template-rows: auto;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
-      ..Browsers::default()
-    });
+
+      .bar {
+        -ms-grid-row: 1;
+        -ms-grid-row-span: 2;
+        grid-row: 1 / 3;
+        -ms-grid-column: 2;
+        grid-column: 2;
+      }
+    "#}, ie);
+
     prefix_test(r#"
       .foo {
-        flex-shrink: 1;
+        grid-row-start: span 2;
+        grid-row-end: 4;
+        grid-column-start: 3;
+        grid-column-end: span 2;
       }
     "#, indoc! {r#"
       .foo {
-        -ms-flex-negative: 1;
-        -webkit-flex-shrink: 1;
-        flex-shrink: 1;
+        -ms-grid-row: 2;
+        -ms-grid-row-span: 2;
+        grid-row: span 2 / 4;
+        -ms-grid-column: 3;
+        -ms-grid-column-span: 2;
+        grid-column: 3 / span 2;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
-      ..Browsers::default()
-    });
+    "#}, ie);
+
+    prefix_test(".a { grid-area: 1 / 2 / 3 / 4 }", indoc! {r#"
+      .a {
+        -ms-grid-row: 1;
+        -ms-grid-row-span: 2;
+        -ms-grid-column: 2;
+        -ms-grid-column-span: 2;
+        grid-area: 1 / 2 / 3 / 4;
+      }
+    "#}, ie);
+
+    prefix_test(".a { grid-area: 2 / 3; grid-row-end: span 2 }", indoc! {r#"
+      .a {
+        -ms-grid-row: 2;
+        -ms-grid-row-span: 2;
+        -ms-grid-column: 3;
+        grid-area: 2 / 3 / span 2;
+      }
+    "#}, ie);
+
+    // Auto-placement, named lines and automatic repetitions cannot be translated.
     prefix_test(r#"
       .foo {
-        -ms-flex-negative: 1;
-        -webkit-flex-shrink: 1;
-        flex-shrink: 1;
+        display: inline-grid;
+        grid-template-columns: repeat(auto-fill, 100px);
+        grid-row: span 2;
+        grid-column: header;
       }
     "#, indoc! {r#"
       .foo {
-        flex-shrink: 1;
+        display: -ms-inline-grid;
+        display: inline-grid;
+        grid-template-columns: repeat(auto-fill, 100px);
+        grid-row: span 2;
+        grid-column: header;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
-      ..Browsers::default()
-    });
+    "#}, ie);
+
+    // Legacy properties that are declared explicitly are kept.
     prefix_test(r#"
       .foo {
-        flex-basis: 1px;
+        display: -ms-grid;
+        display: grid;
+        -ms-grid-columns: 1fr;
+        grid-template-columns: 1fr 1fr;
       }
     "#, indoc! {r#"
       .foo {
-        -ms-flex-preferred-size: 1px;
-        -webkit-flex-basis: 1px;
-        flex-basis: 1px;
+        display: -ms-grid;
+        display: grid;
+        -ms-grid-columns: 1fr;
+        grid-template-columns: 1fr 1fr;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
-      ..Browsers::default()
-    });
+    "#}, ie);
+
     prefix_test(r#"
       .foo {
-        -ms-flex-preferred-size: 1px;
-        -webkit-flex-basis: 1px;
-        flex-basis: 1px;
+        display: grid;
+        grid-template-columns: 1fr 1fr;
+        grid-row: 1 / 3;
       }
     "#, indoc! {r#"
       .foo {
-        flex-basis: 1px;
+        display: grid;
+        grid-template-columns: 1fr 1fr;
+        grid-row: 1 / 3;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
+    "#}, Browsers {
+      chrome: Some(90 << 16),
       ..Browsers::default()
     });
-    prefix_test(r#"
+
+    let warnings = StyleSheet::validate(".foo { grid-gap: 10px; grid-row: 1 / 3; grid-column: auto; grid-auto-flow: dense }", Some(ie));
+    assert_eq!(warnings, vec![
+      Warning { kind: WarningKind::UnsupportedMsGrid, line: 1, column: 18 },
+      Warning { kind: WarningKind::UnsupportedMsGrid, line: 1, column: 54 },
+      Warning { kind: WarningKind::UnsupportedMsGrid, line: 1, column: 76 }
+    ]);
+
+    let warnings = StyleSheet::validate(".foo { grid-template-areas: \"a b\"; grid-area: a; grid-area: 1 / 2 / 3 / 4 }", Some(ie));
+    assert_eq!(warnings, vec![
+      Warning { kind: WarningKind::UnsupportedMsGrid, line: 1, column: 29 },
+      Warning { kind: WarningKind::UnsupportedMsGrid, line: 1, column: 47 }
+    ]);
+
+    let warnings = StyleSheet::validate(".foo { grid-gap: 10px; grid-column: auto }", Some(Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    }));
+    assert_eq!(warnings, vec![]);
+  }
+
+  #[test]
+  pub fn test_background() {
+    use crate::warnings::{Warning, WarningKind};
+
+    test(r#"
       .foo {
-        flex: 1;
+        background: url(img.png);
+        background-position-x: 20px;
+        background-position-y: 10px;
+        background-size: 50px 100px;
+        background-repeat: repeat no-repeat;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-box-flex: 1;
-        -moz-box-flex: 1;
-        -webkit-flex: 1;
-        -ms-flex: 1;
-        flex: 1;
+        background: url(img.png) 20px 10px / 50px 100px repeat-x;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    test(r#"
       .foo {
-        -webkit-box-flex: 1;
-        -moz-box-flex: 1;
-        -webkit-flex: 1;
-        -ms-flex: 1;
-        flex: 1;
+        background-color: red;
+        background-position: 0% 0%;
+        background-size: auto;
+        background-repeat: repeat;
+        background-clip: border-box;
+        background-origin: padding-box;
+        background-attachment: scroll;
+        background-image: none
       }
     "#, indoc! {r#"
       .foo {
-        flex: 1;
+        background: red;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    test(r#"
       .foo {
-        align-content: space-between;
+        background-color: gray;
+        background-position: 40% 50%;
+        background-size: 10em auto;
+        background-repeat: round;
+        background-clip: border-box;
+        background-origin: border-box;
+        background-attachment: fixed;
+        background-image: url('chess.png');
       }
     "#, indoc! {r#"
       .foo {
-        -ms-flex-line-pack: justify;
-        -webkit-align-content: space-between;
-        align-content: space-between;
+        background: gray url(chess.png) 40% / 10em round fixed border-box;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    test(r#"
       .foo {
-        -ms-flex-line-pack: justify;
-        -webkit-align-content: space-between;
-        align-content: space-between;
+        background: url(img.png), url(test.jpg) gray;
+        background-position-x: right 20px, 10px;
+        background-position-y: top 20px, 15px;
+        background-size: 50px 50px, auto;
+        background-repeat: repeat no-repeat, no-repeat;      
       }
     "#, indoc! {r#"
       .foo {
-        align-content: space-between;
+        background: url(img.png) right 20px top 20px / 50px 50px repeat-x, gray url(test.jpg) 10px 15px no-repeat;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    minify_test(r#"
       .foo {
-        justify-content: space-between;
+        background-position: center center;
+      }
+    "#, indoc! {".foo{background-position:50% 50%}"
+    });
+
+    test(r#"
+      .foo {
+        background: url(img.png) gray;
+        background-clip: content-box;
+        -webkit-background-clip: text;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-box-pack: justify;
-        -moz-box-pack: justify;
-        -ms-flex-pack: justify;
-        -webkit-justify-content: space-between;
-        justify-content: space-between;
+        background: gray url(img.png) padding-box content-box;
+        -webkit-background-clip: text;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    test(r#"
       .foo {
-        -webkit-box-pack: justify;
-        -moz-box-pack: justify;
-        -ms-flex-pack: justify;
-        -webkit-justify-content: space-between;
-        justify-content: space-between;
+        background: url(img.png) gray;
+        -webkit-background-clip: text;
+        background-clip: content-box;
       }
     "#, indoc! {r#"
       .foo {
-        justify-content: space-between;
+        background: gray url(img.png);
+        -webkit-background-clip: text;
+        background-clip: content-box;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    test(r#"
       .foo {
-        place-content: space-between flex-end;
+        background: url(img.png), url(test.jpg) gray;
+        background-repeat: no-repeat;
+        background-attachment: fixed, scroll, local;
       }
     "#, indoc! {r#"
       .foo {
-        -ms-flex-line-pack: justify;
-        -webkit-box-pack: end;
-        -moz-box-pack: end;
-        -ms-flex-pack: end;
-        -webkit-align-content: space-between;
-        -webkit-justify-content: flex-end;
-        place-content: space-between flex-end;
+        background: url(img.png) no-repeat fixed, gray url(test.jpg) no-repeat;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
+    "#
+    });
+
+    minify_test(".foo { background-attachment: fixed, fixed }", ".foo{background-attachment:fixed}");
+    minify_test(".foo { background-attachment: fixed, local, fixed, local }", ".foo{background-attachment:fixed,local}");
+    minify_test(".foo { background-attachment: fixed, local, fixed }", ".foo{background-attachment:fixed,local,fixed}");
+    minify_test(".foo { background-image: url(a.png), url(b.png); background-attachment: fixed, scroll, local }", ".foo{background-image:url(a.png),url(b.png);background-attachment:fixed,scroll,local}");
+    // Another rule may declare more layers, so the values are not truncated to the number of images.
+    minify_test(
+      ".a { background-image: url(a.png); background-attachment: fixed, local } .a.b { background-image: url(a.png), url(b.png) }",
+      ".a{background-image:url(a.png);background-attachment:fixed,local}.a.b{background-image:url(a.png),url(b.png)}"
+    );
+
+    let targets = Some(Browsers {
+      ios_saf: Some(15 << 16),
       ..Browsers::default()
     });
+    assert_eq!(StyleSheet::validate(".foo { background-attachment: fixed }", targets), vec![
+      Warning { kind: WarningKind::UnsupportedFeature(compat::Feature::BackgroundAttachmentFixed), line: 1, column: 31 }
+    ]);
+    assert_eq!(StyleSheet::validate(".foo { background: url(a.png) fixed }", targets), vec![
+      Warning { kind: WarningKind::UnsupportedFeature(compat::Feature::BackgroundAttachmentFixed), line: 1, column: 31 }
+    ]);
+    assert_eq!(StyleSheet::validate(".foo { background-attachment: local }", targets), vec![]);
+  }
+
+  #[test]
+  fn test_image_set() {
+    minify_test(".foo { background-image: image-set(url(foo.png) 192dpi, url(bar.png) 1dppx) }", ".foo{background-image:image-set(url(foo.png) 2x,url(bar.png) 1x)}");
+    minify_test(".foo { background-image: image-set(url(foo.png) 0.5x, url(bar.png) 1dpi) }", ".foo{background-image:image-set(url(foo.png) .5x,url(bar.png) 1dpi)}");
+    minify_test(".foo { background-image: image-set(\"foo.png\" 2x, \"bar.png\") }", ".foo{background-image:image-set(url(foo.png) 2x,url(bar.png) 1x)}");
+    minify_test(".foo { background-image: image-set(\"foo.avif\" type(\"image/avif\"), \"foo.png\" type(\"image/png\") 2x) }", ".foo{background-image:image-set(url(foo.avif) 1x type(\"image/avif\"),url(foo.png) 2x type(\"image/png\"))}");
+    minify_test(".foo { background-image: image-set(linear-gradient(red, blue) 1x, url(foo.png) 2x) }", ".foo{background-image:image-set(linear-gradient(red,#00f) 1x,url(foo.png) 2x)}");
+    minify_test(".foo { background-image: -webkit-image-set(url(foo.png) 1x, url(bar.png) 2x) }", ".foo{background-image:-webkit-image-set(url(foo.png) 1x,url(bar.png) 2x)}");
+    minify_test(".foo { background: image-set(url(foo.png) 96dpi) no-repeat }", ".foo{background:image-set(url(foo.png) 1x) no-repeat}");
+
+    // A fallback declared before an image-set() is kept.
+    minify_test(".foo { background-image: url(foo.png); background-image: image-set(url(foo.png) 1x, url(bar.png) 2x) }", ".foo{background-image:url(foo.png);background-image:image-set(url(foo.png) 1x,url(bar.png) 2x)}");
+
     prefix_test(r#"
       .foo {
-        -ms-flex-line-pack: justify;
-        -webkit-box-pack: end;
-        -moz-box-pack: end;
-        -ms-flex-pack: end;
-        -webkit-align-content: space-between;
-        -webkit-justify-content: flex-end;
-        place-content: space-between flex-end;
+        background-image: image-set(url(foo.png) 1x, url(bar.png) 192dpi);
       }
     "#, indoc! {r#"
       .foo {
-        place-content: space-between flex-end;
+        background-image: -webkit-image-set(url(foo.png) 1x, url(bar.png) 2x);
+        background-image: image-set(url(foo.png) 1x, url(bar.png) 192dpi);
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
+    "#
+    }, Browsers {
+      chrome: Some(90 << 16),
       ..Browsers::default()
     });
+
     prefix_test(r#"
       .foo {
-        align-self: flex-end;
+        background: url(foo.png) no-repeat;
+        background: image-set(url(foo.png) 1x, url(bar.png) 2x) no-repeat;
       }
     "#, indoc! {r#"
       .foo {
-        -ms-flex-item-align: end;
-        -webkit-align-self: flex-end;
-        align-self: flex-end;
+        background: url(foo.png) no-repeat;
+        background: -webkit-image-set(url(foo.png) 1x, url(bar.png) 2x) no-repeat;
+        background: image-set(url(foo.png) 1x, url(bar.png) 2x) no-repeat;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
+    "#
+    }, Browsers {
+      chrome: Some(90 << 16),
       ..Browsers::default()
     });
+
     prefix_test(r#"
       .foo {
-        -ms-flex-item-align: end;
-        -webkit-align-self: flex-end;
-        align-self: flex-end;
+        background-image: -webkit-image-set(url(foo.png) 1x, url(bar.png) 2x);
+        background-image: image-set(url(foo.png) 1x, url(bar.png) 2x);
       }
     "#, indoc! {r#"
       .foo {
-        align-self: flex-end;
+        background-image: -webkit-image-set(url(foo.png) 1x, url(bar.png) 2x);
+        background-image: image-set(url(foo.png) 1x, url(bar.png) 2x);
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
+    "#
+    }, Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    });
+
+    // Existing prefixed fallbacks are not duplicated, so minifying again doesn't change the result.
+    let targets = Browsers {
+      chrome: Some(90 << 16),
       ..Browsers::default()
+    };
+    let minify_prefix = |source: &str| compile(source, ParserOptions::default(), MinifyOptions {
+      targets: Some(targets),
+      ..MinifyOptions::default()
+    }, PrinterOptions {
+      minify: true,
+      targets: Some(targets),
+      ..PrinterOptions::default()
     });
+    for source in [
+      ".foo { background-image: -webkit-image-set(url(foo.png) 1x, url(bar.png) 2x); background-size: 10px; background-image: image-set(url(foo.png) 1x, url(bar.png) 2x) }",
+      ".foo { background: -webkit-image-set(url(foo.png) 1x, url(bar.png) 2x); background-image: image-set(url(foo.png) 1x, url(bar.png) 2x) }"
+    ] {
+      let first = minify_prefix(source);
+      assert_eq!(first.matches("-webkit-image-set").count(), 1);
+      assert_eq!(minify_prefix(&first), first);
+    }
+
+    // Options with a type() are not supported by the prefixed syntax.
     prefix_test(r#"
       .foo {
-        place-self: center flex-end;
+        background-image: image-set("foo.avif" type("image/avif"), "foo.png" type("image/png"));
       }
     "#, indoc! {r#"
       .foo {
-        -ms-flex-item-align: center;
-        -webkit-align-self: center;
-        place-self: center flex-end;
+        background-image: image-set(url(foo.avif) 1x type("image/avif"), url(foo.png) 1x type("image/png"));
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
+    "#
+    }, Browsers {
+      chrome: Some(90 << 16),
       ..Browsers::default()
     });
+
     prefix_test(r#"
       .foo {
-        -ms-flex-item-align: center;
-        -webkit-align-self: center;
-        place-self: center flex-end;
+        background-image: image-set(url(foo.png) 1x, url(bar.png) 2x);
       }
     "#, indoc! {r#"
       .foo {
-        place-self: center flex-end;
+        background-image: image-set(url(foo.png) 1x, url(bar.png) 2x);
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
+    "#
+    }, Browsers {
+      firefox: Some(90 << 16),
       ..Browsers::default()
     });
-    prefix_test(r#"
+  }
+
+  #[test]
+  fn test_url() {
+    minify_test(".foo { background-image: url(\"foo.png\") }", ".foo{background-image:url(foo.png)}");
+    minify_test(".foo { background-image: url('foo.png') }", ".foo{background-image:url(foo.png)}");
+    minify_test(".foo { background-image: url(fo\\6f.png) }", ".foo{background-image:url(foo.png)}");
+    minify_test(".foo { background-image: url(\"foo bar.png\") }", ".foo{background-image:url(\"foo bar.png\")}");
+    minify_test(".foo { background-image: url(foo\\ bar.png) }", ".foo{background-image:url(\"foo bar.png\")}");
+    minify_test(".foo { background-image: url(\"foo(1).png\") }", ".foo{background-image:url(foo\\(1\\).png)}");
+    minify_test("@font-face {src: url(\"my font.woff\") format(woff);}", "@font-face{src:url(\"my font.woff\")format(woff)}");
+
+    use crate::dependencies::Dependency;
+    let stylesheet = StyleSheet::parse(r#"
+      @import "foo.css";
+      .foo {
+        background: url(fo\6f.png), image-set("a.png" 1x, url(b.png) 2x);
+        border-image: url(border.png) 30;
+      }
+      @media print {
+        .bar { background-image: url("print.png") }
+      }
+      @font-face {
+        src: url(font.woff) format(woff), local(Test);
+      }
+    "#, ParserOptions::default());
+    assert_eq!(stylesheet.dependencies(), vec![
+      Dependency::Import("foo.css".into()),
+      Dependency::Url("foo.png".into()),
+      Dependency::Url("a.png".into()),
+      Dependency::Url("b.png".into()),
+      Dependency::Url("border.png".into()),
+      Dependency::Url("print.png".into()),
+      Dependency::Url("font.woff".into())
+    ]);
+  }
+
+  #[test]
+  pub fn test_flex() {
+    test(r#"
       .foo {
-        align-items: flex-end;
+        flex-direction: column;
+        flex-wrap: wrap;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-box-align: end;
-        -moz-box-align: end;
-        -ms-flex-align: end;
-        -webkit-align-items: flex-end;
-        align-items: flex-end;
+        flex-flow: column wrap;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    test(r#"
       .foo {
-        -webkit-box-align: end;
-        -moz-box-align: end;
-        -ms-flex-align: end;
-        -webkit-align-items: flex-end;
-        align-items: flex-end;
+        flex-direction: row;
+        flex-wrap: wrap;
       }
     "#, indoc! {r#"
       .foo {
-        align-items: flex-end;
+        flex-flow: wrap;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    test(r#"
       .foo {
-        place-items: flex-end center;
+        flex-direction: row;
+        flex-wrap: nowrap;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-box-align: end;
-        -moz-box-align: end;
-        -ms-flex-align: end;
-        -webkit-align-items: flex-end;
-        place-items: flex-end center;
+        flex-flow: row;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    test(r#"
       .foo {
-        -webkit-box-align: end;
-        -moz-box-align: end;
-        -ms-flex-align: end;
-        -webkit-align-items: flex-end;
-        place-items: flex-end center;
+        flex-direction: column;
+        flex-wrap: nowrap;
       }
     "#, indoc! {r#"
       .foo {
-        place-items: flex-end center;
+        flex-flow: column;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    test(r#"
       .foo {
-        order: 1;
+        flex-grow: 1;
+        flex-shrink: 1;
+        flex-basis: 0%;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-box-ordinal-group: 1;
-        -moz-box-ordinal-group: 1;
-        -ms-flex-order: 1;
-        -webkit-order: 1;
-        order: 1;
+        flex: 1;
       }
-    "#},
-    Browsers {
-      safari: Some(4 << 16),
-      firefox: Some(4 << 16),
-      ie: Some(10 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
+
+    test(r#"
       .foo {
-        -webkit-box-ordinal-group: 1;
-        -moz-box-ordinal-group: 1;
-        -ms-flex-order: 1;
-        -webkit-order: 1;
-        order: 1;
+        flex-grow: 1;
+        flex-shrink: 0;
+        flex-basis: 0%;
       }
     "#, indoc! {r#"
       .foo {
-        order: 1;
+        flex: 1 0;
       }
-    "#},
-    Browsers {
-      safari: Some(11 << 16),
-      ..Browsers::default()
+    "#
     });
-  }
 
-  #[test]
-  fn test_font() {
     test(r#"
       .foo {
-        font-family: "Helvetica", "Times New Roman", sans-serif;
-        font-size: 12px;
-        font-weight: bold;
-        font-style: italic;
-        font-stretch: expanded;
-        font-variant-caps: small-caps;
-        line-height: 1.2em;
+        flex-grow: 1;
+        flex-shrink: 0;
+        flex-basis: auto;
       }
     "#, indoc! {r#"
       .foo {
-        font: italic small-caps bold expanded 12px / 1.2em Helvetica, Times New Roman, sans-serif;
+        flex: 1 0 auto;
       }
     "#
     });
 
-    minify_test(r#"
+    test(r#"
       .foo {
-        font-family: "Helvetica", "Times New Roman", sans-serif;
-        font-size: 12px;
-        font-weight: bold;
-        font-style: italic;
-        font-stretch: expanded;
-        font-variant-caps: small-caps;
-        line-height: 1.2em;
+        flex-grow: 1;
+        flex-shrink: 1;
+        flex-basis: auto;
+      }
+    "#, indoc! {r#"
+      .foo {
+        flex: auto;
       }
-    "#, indoc! {".foo{font:italic small-caps 700 50% 12px/1.2em Helvetica,Times New Roman,sans-serif}"
+    "#
     });
 
     test(r#"
       .foo {
-        font: 12px "Helvetica", "Times New Roman", sans-serif;
-        line-height: 1.2em;
+        flex: 0 0;
+        flex-grow: 1;
       }
     "#, indoc! {r#"
       .foo {
-        font: 12px / 1.2em Helvetica, Times New Roman, sans-serif;
+        flex: 1 0;
       }
     "#
     });
 
-    minify_test(r#"
+    test(r#"
       .foo {
-        font-family: "Helvetica", "Times New Roman", sans-serif;
-        font-size: 12px;
-        font-stretch: expanded;
+        align-content: center;
+        justify-content: center;
+      }
+    "#, indoc! {r#"
+      .foo {
+        place-content: center;
       }
-    "#, indoc! {".foo{font-family:Helvetica,Times New Roman,sans-serif;font-size:12px;font-stretch:50%}"
+    "#
     });
 
     test(r#"
       .foo {
-        font-family: "Helvetica", "Times New Roman", sans-serif;
-        font-size: 12px;
-        font-weight: bold;
-        font-style: italic;
-        font-stretch: expanded;
-        font-variant-caps: all-small-caps;
-        line-height: 1.2em;
+        align-content: first baseline;
+        justify-content: safe right;
       }
     "#, indoc! {r#"
       .foo {
-        font: italic bold expanded 12px / 1.2em Helvetica, Times New Roman, sans-serif;
-        font-variant-caps: all-small-caps;
+        place-content: baseline safe right;
       }
     "#
     });
-  }
-
-  #[test]
-  fn test_selectors() {
-    minify_test("[foo=\"baz\"] {}", "[foo=baz]{}");
-    minify_test("[foo=\"foo bar\"] {}", "[foo=foo\\ bar]{}");
-    minify_test("[foo=\"foo bar baz\"] {}", "[foo=\"foo bar baz\"]{}");
-    minify_test(".test:not([foo=\"bar\"]) {}", ".test:not([foo=bar]){}");
-    minify_test(".test + .foo {}", ".test+.foo{}");
-    minify_test(".test ~ .foo {}", ".test~.foo{}");
-    minify_test(".test .foo {}", ".test .foo{}");
-  }
 
-  #[test]
-  fn test_keyframes() {
-    minify_test(r#"
-      @keyframes test {
-        from {
-          background: green;
-        }
-
-        50% {
-          background: red;
-        }
-
-        100% {
-          background: blue
-        }
+    test(r#"
+      .foo {
+        place-content: first baseline unsafe left;
       }
-    "#, "@keyframes test{0%{background:green}50%{background:red}to{background:#00f}}");
-    minify_test(r#"
-      @keyframes test {
-        from {
-          background: green;
-          background-color: red;
-        }
-
-        100% {
-          background: blue
-        }
+    "#, indoc! {r#"
+      .foo {
+        place-content: baseline unsafe left;
       }
-    "#, "@keyframes test{0%{background:red}to{background:#00f}}");
-    minify_test(r#"
-      @-webkit-keyframes test {
-        from {
-          background: green;
-          background-color: red;
-        }
+    "#
+    });
 
-        100% {
-          background: blue
-        }
+    test(r#"
+      .foo {
+        place-content: center center;
       }
-    "#, "@-webkit-keyframes test{0%{background:red}to{background:#00f}}");
-    minify_test(r#"
-      @-moz-keyframes test {
-        from {
-          background: green;
-          background-color: red;
-        }
-
-        100% {
-          background: blue
-        }
+    "#, indoc! {r#"
+      .foo {
+        place-content: center;
       }
-    "#, "@-moz-keyframes test{0%{background:red}to{background:#00f}}");
-    minify_test(r#"
-      @-webkit-keyframes test {
-        from {
-          background: green;
-          background-color: red;
-        }
+    "#
+    });
 
-        100% {
-          background: blue
-        }
+    test(r#"
+      .foo {
+        align-self: center;
+        justify-self: center;
       }
-      @-moz-keyframes test {
-        from {
-          background: green;
-          background-color: red;
-        }
-
-        100% {
-          background: blue
-        }
+    "#, indoc! {r#"
+      .foo {
+        place-self: center;
       }
-    "#, "@-webkit-keyframes test{0%{background:red}to{background:#00f}}@-moz-keyframes test{0%{background:red}to{background:#00f}}");
+    "#
+    });
 
-    prefix_test(r#"
-      @keyframes test {
-        from {
-          background: green;
-        }
-        to {
-          background: blue
-        }
+    test(r#"
+      .foo {
+        align-self: center;
+        justify-self: unsafe left;
       }
-    "#, indoc! { r#"
-      @-webkit-keyframes test {
-        from {
-          background: green;
-        }
-
-        to {
-          background: #00f;
-        }
+    "#, indoc! {r#"
+      .foo {
+        place-self: center unsafe left;
       }
+    "#
+    });
 
-      @-moz-keyframes test {
-        from {
-          background: green;
-        }
-
-        to {
-          background: #00f;
-        }
+    test(r#"
+      .foo {
+        align-items: center;
+        justify-items: center;
       }
-
-      @keyframes test {
-        from {
-          background: green;
-        }
-
-        to {
-          background: #00f;
-        }
+    "#, indoc! {r#"
+      .foo {
+        place-items: center;
       }
-    "#}, Browsers {
-      safari: Some(5 << 16),
-      firefox: Some(6 << 16),
-      ..Browsers::default()
+    "#
     });
-    prefix_test(r#"
-      @-webkit-keyframes test {
-        from {
-          background: green;
-        }
 
-        to {
-          background: blue;
-        }
+    test(r#"
+      .foo {
+        align-items: center;
+        justify-items: legacy left;
       }
-      @-moz-keyframes test {
-        from {
-          background: green;
-        }
+    "#, indoc! {r#"
+      .foo {
+        place-items: center legacy left;
+      }
+    "#
+    });
 
-        to {
-          background: blue;
-        }
+    test(r#"
+      .foo {
+        row-gap: 10px;
+        column-gap: 20px;
       }
-      @keyframes test {
-        from {
-          background: green;
-        }
-        to {
-          background: blue
-        }
+    "#, indoc! {r#"
+      .foo {
+        gap: 10px 20px;
       }
-    "#, indoc! { r#"
-      @keyframes test {
-        from {
-          background: green;
-        }
+    "#
+    });
 
-        to {
-          background: #00f;
-        }
+    test(r#"
+      .foo {
+        row-gap: 10px;
+        column-gap: 10px;
       }
-    "#}, Browsers {
-      safari: Some(10 << 16),
-      firefox: Some(17 << 16),
-      ..Browsers::default()
+    "#, indoc! {r#"
+      .foo {
+        gap: 10px;
+      }
+    "#
     });
-    prefix_test(r#"
-      @-webkit-keyframes test1 {
-        from {
-          background: green;
-        }
 
-        to {
-          background: blue;
-        }
+    test(r#"
+      .foo {
+        gap: 10px;
+        column-gap: 20px;
       }
-
-      @-moz-keyframes test2 {
-        from {
-          background: green;
-        }
-
-        to {
-          background: blue;
-        }
+    "#, indoc! {r#"
+      .foo {
+        gap: 10px 20px;
       }
+    "#
+    });
 
-      @keyframes test3 {
-        from {
-          background: green;
-        }
-        to {
-          background: blue
-        }
+    test(r#"
+      .foo {
+        column-gap: 20px;
+        gap: 10px;
       }
-    "#, indoc! { r#"
-      @-webkit-keyframes test1 {
-        from {
-          background: green;
-        }
-
-        to {
-          background: #00f;
-        }
+    "#, indoc! {r#"
+      .foo {
+        gap: 10px;
       }
+    "#
+    });
 
-      @-moz-keyframes test2 {
-        from {
-          background: green;
-        }
-
-        to {
-          background: #00f;
-        }
-      }
-
-      @keyframes test3 {
-        from {
-          background: green;
-        }
-
-        to {
-          background: #00f;
-        }
-      }
-    "#}, Browsers {
-      safari: Some(10 << 16),
-      firefox: Some(17 << 16),
-      ..Browsers::default()
-    });
-    prefix_test(r#"
-      @-webkit-keyframes test {
-        from {
-          background: green;
-        }
-
-        to {
-          background: red;
-        }
-      }
-      @-moz-keyframes test {
-        from {
-          background: green;
-        }
-
-        to {
-          background: pink;
-        }
-      }
-      @keyframes test {
-        from {
-          background: green;
-        }
-        to {
-          background: blue
-        }
-      }
-    "#, indoc! { r#"
-      @-webkit-keyframes test {
-        from {
-          background: green;
-        }
-
-        to {
-          background: red;
-        }
-      }
-
-      @-moz-keyframes test {
-        from {
-          background: green;
-        }
-
-        to {
-          background: pink;
-        }
+    test(r#"
+      .foo {
+        row-gap: normal;
+        column-gap: 20px;
       }
-
-      @keyframes test {
-        from {
-          background: green;
-        }
-
-        to {
-          background: #00f;
-        }
+    "#, indoc! {r#"
+      .foo {
+        gap: normal 20px;
       }
-    "#}, Browsers {
-      safari: Some(10 << 16),
-      firefox: Some(17 << 16),
-      ..Browsers::default()
+    "#
     });
-  }
 
-  #[test]
-  fn test_important() {
     test(r#"
       .foo {
-        align-items: center;
-        justify-items: center !important;
+        grid-row-gap: 10px;
+        grid-column-gap: 20px;
       }
     "#, indoc! {r#"
       .foo {
-        align-items: center;
-        justify-items: center !important;
+        gap: 10px 20px;
       }
-    "#});
+    "#
+    });
 
     test(r#"
       .foo {
-        justify-items: center !important;
-        align-items: center;
+        grid-gap: 10px;
       }
     "#, indoc! {r#"
       .foo {
-        align-items: center;
-        justify-items: center !important;
+        gap: 10px;
       }
-    "#});
+    "#
+    });
 
-    minify_test(r#"
+    prefix_test(r#"
       .foo {
-        font-family: SFMono-Regular, Menlo, Monaco, Consolas, "Liberation Mono", "Courier New", monospace !important;
+        gap: 10px 20px;
       }
-    "#, ".foo{font-family:SFMono-Regular,Menlo,Monaco,Consolas,Liberation Mono,Courier New,monospace!important}");
-  }
-
-  #[test]
-  fn test_calc() {
-    minify_test(".foo { width: calc(20px * 2) }", ".foo{width:40px}");
-    minify_test(".foo { font-size: calc(100vw / 35) }", ".foo{font-size:2.85714vw}");
-    minify_test(".foo { width: calc(20px * 2 * 3) }", ".foo{width:120px}");
-    minify_test(".foo { width: calc(20px + 30px) }", ".foo{width:50px}");
-    minify_test(".foo { width: calc(20px + 30px + 40px) }", ".foo{width:90px}");
-    minify_test(".foo { width: calc(100% - 30px) }", ".foo{width:calc(100% - 30px)}");
-    minify_test(".foo { width: calc(100% - 30px + 20px) }", ".foo{width:calc(100% - 10px)}");
-    minify_test(".foo { width: calc(20px + 100% - 30px) }", ".foo{width:calc(100% - 10px)}");
-    minify_test(".foo { width: calc(20px + 100% + 10vw - 30px) }", ".foo{width:calc(100% - 10px + 10vw)}");
-    minify_test(".foo { width: calc(20px + 100% - 30px) }", ".foo{width:calc(100% - 10px)}");
-    minify_test(".foo { width: calc(2 * (100% - 20px)) }", ".foo{width:calc(200% - 40px)}");
-    minify_test(".foo { width: calc((100% - 20px) * 2) }", ".foo{width:calc(200% - 40px)}");
-    minify_test(".foo { width: calc(100% - 20px * 2) }", ".foo{width:calc(100% - 40px)}");
-    minify_test(".foo { width: calc(1px + 1px) }", ".foo{width:2px}");
-    minify_test(".foo { width: calc(100vw / 2) }", ".foo{width:50vw}");
-    minify_test(".foo { width: calc(50px - (20px - 30px)) }", ".foo{width:60px}");
-    minify_test(".foo { width: calc(100px - (100px - 100%)) }", ".foo{width:100%}");
-    minify_test(".foo { width: calc(100px + (100px - 100%)) }", ".foo{width:calc(200px - 100%)}");
-    minify_test(".foo { width: calc(1px - (2em + 3%)) }", ".foo{width:calc(1px + -2em - 3%)}"); // TODO: fix sign
-    minify_test(".foo { width: calc((100vw - 50em) / 2) }", ".foo{width:calc(50vw - 25em)}");
-    minify_test(".foo { width: calc(1px - (2em + 4vh + 3%)) }", ".foo{width:calc(1px + -2em - 4vh - 3%)}"); // TODO
-    minify_test(".foo { width: calc(1px + (2em + (3vh + 4px))) }", ".foo{width:calc(2em + 3vh + 5px)}");
-    minify_test(".foo { width: calc(1px - (2em + 4px - 6vh) / 2) }", ".foo{width:calc(-1em - 1px + 3vh)}");
-    minify_test(".foo { width: calc(100% - calc(50% + 25px)) }", ".foo{width:calc(50% - 25px)}");
-    minify_test(".foo { width: calc(1px/100) }", ".foo{width:.01px}");
-    minify_test(".foo { width: calc(100vw / 2 - 6px + 0px) }", ".foo{width:calc(50vw - 6px)}");
-    minify_test(".foo { width: calc(1px + 1) }", ".foo{width:calc(1px + 1)}");
-    minify_test(".foo { width: calc( (1em - calc( 10px + 1em)) / 2) }", ".foo{width:-5px}");
-    minify_test(".foo { width: calc((100px - 1em) + (-50px + 1em)) }", ".foo{width:50px}");
-    minify_test(".foo { width: calc(100% + (2 * 100px) - ((75.37% - 63.5px) - 900px)) }", ".foo{width:calc(24.63% + 1163.5px)}");
-    minify_test(".foo { width: calc(((((100% + (2 * 30px) + 63.5px) / 0.7537) - (100vw - 60px)) / 2) + 30px) }", ".foo{width:calc(66.3394% + 141.929px - 50vw)}");
-    minify_test(".foo { width: calc(((75.37% - 63.5px) - 900px) + (2 * 100px)) }", ".foo{width:calc(75.37% - 763.5px)}");
-    minify_test(".foo { width: calc((900px - (10% - 63.5px)) + (2 * 100px)) }", ".foo{width:calc(1163.5px - 10%)}");
-    minify_test(".foo { width: calc(500px/0) }", ".foo{width:calc(500px/0)}");
-    minify_test(".foo { width: calc(500px/2px) }", ".foo{width:calc(500px/2px)}");
-    minify_test(".foo { width: calc(100% / 3 * 3) }", ".foo{width:100%}");
-    minify_test(".foo { width: calc(+100px + +100px) }", ".foo{width:200px}");
-    minify_test(".foo { width: calc(+100px - +100px) }", ".foo{width:0}");
-    minify_test(".foo { width: calc(200px * +1) }", ".foo{width:200px}");
-    minify_test(".foo { width: calc(200px / +1) }", ".foo{width:200px}");
-    minify_test(".foo { width: calc(1.1e+1px + 1.1e+1px) }", ".foo{width:22px}");
-    minify_test(".foo { border-width: calc(1px + 2px) }", ".foo{border-width:3px}");
-    minify_test(".foo { border-width: calc(1em + 2px + 2em + 3px) }", ".foo{border-width:calc(3em + 5px)}");
-    
-    minify_test(".foo { border-width: min(1em, 2px) }", ".foo{border-width:min(1em,2px)}");
-    minify_test(".foo { border-width: min(1em + 2em, 2px + 2px) }", ".foo{border-width:min(3em,4px)}");
-    minify_test(".foo { border-width: min(1em + 2px, 2px + 1em) }", ".foo{border-width:min(1em + 2px,2px + 1em)}");
-    minify_test(".foo { border-width: min(1em + 2px + 2px, 2px + 1em + 1px) }", ".foo{border-width:min(1em + 4px,3px + 1em)}");
-    minify_test(".foo { border-width: min(2px + 1px, 3px + 4px) }", ".foo{border-width:3px}");
-    minify_test(".foo { border-width: min(1px, 1em, 2px, 3in) }", ".foo{border-width:min(1px,1em)}");
-
-    minify_test(".foo { border-width: max(1em, 2px) }", ".foo{border-width:max(1em,2px)}");
-    minify_test(".foo { border-width: max(1em + 2em, 2px + 2px) }", ".foo{border-width:max(3em,4px)}");
-    minify_test(".foo { border-width: max(1em + 2px, 2px + 1em) }", ".foo{border-width:max(1em + 2px,2px + 1em)}");
-    minify_test(".foo { border-width: max(1em + 2px + 2px, 2px + 1em + 1px) }", ".foo{border-width:max(1em + 4px,3px + 1em)}");
-    minify_test(".foo { border-width: max(2px + 1px, 3px + 4px) }", ".foo{border-width:7px}");
-    minify_test(".foo { border-width: max(1px, 1em, 2px, 3in) }", ".foo{border-width:max(3in,1em)}");
-
-    minify_test(".foo { border-width: clamp(1px, 2px, 3px) }", ".foo{border-width:2px}");
-    minify_test(".foo { border-width: clamp(1px, 10px, 3px) }", ".foo{border-width:3px}");
-    minify_test(".foo { border-width: clamp(5px, 2px, 10px) }", ".foo{border-width:5px}");
-    minify_test(".foo { border-width: clamp(100px, 2px, 10px) }", ".foo{border-width:100px}");
-    minify_test(".foo { border-width: clamp(5px + 5px, 5px + 7px, 10px + 20px) }", ".foo{border-width:12px}");
-
-    minify_test(".foo { border-width: clamp(1em, 2px, 4vh) }", ".foo{border-width:clamp(1em,2px,4vh)}");
-    minify_test(".foo { border-width: clamp(1em, 2em, 4vh) }", ".foo{border-width:min(2em,4vh)}");
-    minify_test(".foo { border-width: clamp(1em, 2vh, 4vh) }", ".foo{border-width:max(1em,2vh)}");
-    minify_test(".foo { border-width: clamp(1px, 1px + 2em, 4px) }", ".foo{border-width:clamp(1px,1px + 2em,4px)}");
-    minify_test(".foo { border-width: clamp(1px, 2pt, 1in) }", ".foo{border-width:2pt}");
-  }
-
-  #[test]
-  fn test_box_shadow() {
-    minify_test(".foo { box-shadow: 64px 64px 12px 40px rgba(0,0,0,0.4) }", ".foo{box-shadow:64px 64px 12px 40px #0006}");
-    minify_test(".foo { box-shadow: 12px 12px 0px 8px rgba(0,0,0,0.4) inset }", ".foo{box-shadow:inset 12px 12px 0 8px #0006}");
-    minify_test(".foo { box-shadow: inset 12px 12px 0px 8px rgba(0,0,0,0.4) }", ".foo{box-shadow:inset 12px 12px 0 8px #0006}");
-    minify_test(".foo { box-shadow: 12px 12px 8px 0px rgba(0,0,0,0.4) }", ".foo{box-shadow:12px 12px 8px #0006}");
-    minify_test(".foo { box-shadow: 12px 12px 0px 0px rgba(0,0,0,0.4) }", ".foo{box-shadow:12px 12px #0006}");
-    minify_test(".foo { box-shadow: 64px 64px 12px 40px rgba(0,0,0,0.4), 12px 12px 0px 8px rgba(0,0,0,0.4) inset }", ".foo{box-shadow:64px 64px 12px 40px #0006,inset 12px 12px 0 8px #0006}");
-  }
-
-  #[test]
-  fn test_media() {
-    minify_test("@media (min-width: 240px) { .foo { color: chartreuse }}", "@media (min-width:240px){.foo{color:#7fff00}}")
-  }
-
-  #[test]
-  fn test_merge_rules() {
-    test(r#"
+    "#, indoc! {r#"
       .foo {
-        color: red;
+        grid-gap: 10px 20px;
+        gap: 10px 20px;
       }
-      .bar {
-        color: red;
+    "#
+    }, Browsers {
+      chrome: Some(60 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .foo {
+        row-gap: normal;
+        grid-column-gap: 20px;
       }
     "#, indoc! {r#"
-      .foo, .bar {
-        color: red;
-      }
-    "#});
-    test(r#"
       .foo {
-        color: red;
+        grid-column-gap: 20px;
+        gap: normal 20px;
       }
+    "#
+    }, Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
       .foo {
-        background: green;
+        grid-gap: 10px;
       }
     "#, indoc! {r#"
       .foo {
-        color: red;
-        background: green;
+        gap: 10px;
       }
-    "#});
+    "#
+    }, Browsers {
+      chrome: Some(70 << 16),
+      ..Browsers::default()
+    });
+
     test(r#"
       .foo {
-        background: red;
-      }
-      .foo {
-        background: green;
+        -webkit-flex-grow: 1;
+        -webkit-flex-shrink: 1;
+        -webkit-flex-basis: auto;
       }
     "#, indoc! {r#"
       .foo {
-        background: green;
+        -webkit-flex: auto;
       }
-    "#});
+    "#
+    });
     test(r#"
       .foo {
-        color: red;
-      }
-
-      .bar {
-        background: green;
+        -webkit-flex-grow: 1;
+        -webkit-flex-shrink: 1;
+        -webkit-flex-basis: auto;
+        flex-grow: 1;
+        flex-shrink: 1;
+        flex-basis: auto;
       }
     "#, indoc! {r#"
       .foo {
-        color: red;
-      }
-
-      .bar {
-        background: green;
+        -webkit-flex: auto;
+        flex: auto;
       }
-    "#});
-    test(r#"
+    "#
+    });
+    prefix_test(r#"
       .foo {
-        color: red;
-      }
-
-      .baz {
-        color: blue;
-      }
-
-      .bar {
-        color: red;
+        -webkit-box-orient: horizontal;
+        -webkit-box-direction: normal;
+        flex-direction: row;
       }
     "#, indoc! {r#"
       .foo {
-        color: red;
-      }
-
-      .baz {
-        color: #00f;
-      }
-      
-      .bar {
-        color: red;
+        -webkit-box-orient: horizontal;
+        -webkit-box-direction: normal;
+        -webkit-flex-direction: row;
+        flex-direction: row;
       }
-    "#});
-  }
-
-  #[test]
-  fn test_opacity() {
-    minify_test(".foo { opacity: 0 }", ".foo{opacity:0}");
-    minify_test(".foo { opacity: 0% }", ".foo{opacity:0}");
-    minify_test(".foo { opacity: 0.5 }", ".foo{opacity:.5}");
-    minify_test(".foo { opacity: 50% }", ".foo{opacity:.5}");
-    minify_test(".foo { opacity: 1 }", ".foo{opacity:1}");
-    minify_test(".foo { opacity: 100% }", ".foo{opacity:1}");
-  }
-
-  #[test]
-  fn test_transitions() {
-    minify_test(".foo { transition-duration: 500ms }", ".foo{transition-duration:.5s}");
-    minify_test(".foo { transition-duration: .5s }", ".foo{transition-duration:.5s}");
-    minify_test(".foo { transition-duration: 99ms }", ".foo{transition-duration:99ms}");
-    minify_test(".foo { transition-duration: .099s }", ".foo{transition-duration:99ms}");
-    minify_test(".foo { transition-duration: 2000ms }", ".foo{transition-duration:2s}");
-    minify_test(".foo { transition-duration: 2s }", ".foo{transition-duration:2s}");
-    minify_test(".foo { transition-duration: calc(1s - 50ms) }", ".foo{transition-duration:.95s}");
-    minify_test(".foo { transition-duration: calc(1s - 50ms + 2s) }", ".foo{transition-duration:2.95s}");
-    minify_test(".foo { transition-duration: calc((1s - 50ms) * 2) }", ".foo{transition-duration:1.9s}");
-    minify_test(".foo { transition-duration: calc(2 * (1s - 50ms)) }", ".foo{transition-duration:1.9s}");
-    minify_test(".foo { transition-duration: calc((2s + 50ms) - (1s - 50ms)) }", ".foo{transition-duration:1.1s}");
-    minify_test(".foo { transition-duration: 500ms, 50ms }", ".foo{transition-duration:.5s,50ms}");
-    minify_test(".foo { transition-delay: 500ms }", ".foo{transition-delay:.5s}");
-    minify_test(".foo { transition-property: background }", ".foo{transition-property:background}");
-    minify_test(".foo { transition-property: background, opacity }", ".foo{transition-property:background,opacity}");
-    minify_test(".foo { transition-timing-function: linear }", ".foo{transition-timing-function:linear}");
-    minify_test(".foo { transition-timing-function: ease }", ".foo{transition-timing-function:ease}");
-    minify_test(".foo { transition-timing-function: ease-in }", ".foo{transition-timing-function:ease-in}");
-    minify_test(".foo { transition-timing-function: ease-out }", ".foo{transition-timing-function:ease-out}");
-    minify_test(".foo { transition-timing-function: ease-in-out }", ".foo{transition-timing-function:ease-in-out}");
-    minify_test(".foo { transition-timing-function: cubic-bezier(0.25, 0.1, 0.25, 1) }", ".foo{transition-timing-function:ease}");
-    minify_test(".foo { transition-timing-function: cubic-bezier(0.42, 0, 1, 1) }", ".foo{transition-timing-function:ease-in}");
-    minify_test(".foo { transition-timing-function: cubic-bezier(0, 0, 0.58, 1) }", ".foo{transition-timing-function:ease-out}");
-    minify_test(".foo { transition-timing-function: cubic-bezier(0.42, 0, 0.58, 1) }", ".foo{transition-timing-function:ease-in-out}");
-    minify_test(".foo { transition-timing-function: cubic-bezier(0.58, 0.2, 0.11, 1.2) }", ".foo{transition-timing-function:cubic-bezier(.58,.2,.11,1.2)}");
-    minify_test(".foo { transition-timing-function: step-start }", ".foo{transition-timing-function:step-start}");
-    minify_test(".foo { transition-timing-function: step-end }", ".foo{transition-timing-function:step-end}");
-    minify_test(".foo { transition-timing-function: steps(1, start) }", ".foo{transition-timing-function:step-start}");
-    minify_test(".foo { transition-timing-function: steps(1, jump-start) }", ".foo{transition-timing-function:step-start}");
-    minify_test(".foo { transition-timing-function: steps(1, end) }", ".foo{transition-timing-function:step-end}");
-    minify_test(".foo { transition-timing-function: steps(1, jump-end) }", ".foo{transition-timing-function:step-end}");
-    minify_test(".foo { transition-timing-function: steps(5, jump-start) }", ".foo{transition-timing-function:steps(5,start)}");
-    minify_test(".foo { transition-timing-function: steps(5, jump-end) }", ".foo{transition-timing-function:steps(5,end)}");
-    minify_test(".foo { transition-timing-function: steps(5, jump-both) }", ".foo{transition-timing-function:steps(5,jump-both)}");
-    minify_test(".foo { transition-timing-function: ease-in-out, cubic-bezier(0.42, 0, 1, 1) }", ".foo{transition-timing-function:ease-in-out,ease-in}");
-    minify_test(".foo { transition-timing-function: cubic-bezier(0.42, 0, 1, 1), cubic-bezier(0.58, 0.2, 0.11, 1.2) }", ".foo{transition-timing-function:ease-in,cubic-bezier(.58,.2,.11,1.2)}");
-    minify_test(".foo { transition-timing-function: step-start, steps(5, jump-start) }", ".foo{transition-timing-function:step-start,steps(5,start)}");
-    minify_test(".foo { transition: width 2s ease }", ".foo{transition:width 2s}");
-    minify_test(".foo { transition: width 2s ease, height 1000ms cubic-bezier(0.25, 0.1, 0.25, 1) }", ".foo{transition:width 2s,height 1s}");
-    minify_test(".foo { transition: width 2s 1s }", ".foo{transition:width 2s 1s}");
-    minify_test(".foo { transition: width 2s ease 1s }", ".foo{transition:width 2s 1s}");
-    minify_test(".foo { transition: ease-in 1s width 4s }", ".foo{transition:width 1s ease-in 4s}");
-    minify_test(".foo { transition: opacity 0s .6s }", ".foo{transition:opacity 0s .6s}");
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        transition-property: opacity;
-        transition-duration: 0.09s;
-        transition-timing-function: ease-in-out;
-        transition-delay: 500ms;
+        flex-direction: row;
       }
     "#, indoc! {r#"
       .foo {
-        transition: opacity 90ms ease-in-out .5s;
+        -webkit-box-orient: horizontal;
+        -moz-box-orient: horizontal;
+        -webkit-box-direction: normal;
+        -moz-box-direction: normal;
+        -webkit-flex-direction: row;
+        -ms-flex-direction: row;
+        flex-direction: row;
       }
-    "#});
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        transition: opacity 2s;
-        transition-timing-function: ease;
-        transition-delay: 500ms;
+        -webkit-box-orient: horizontal;
+        -webkit-box-direction: normal;
+        -moz-box-orient: horizontal;
+        -moz-box-direction: normal;
+        -webkit-flex-direction: row;
+        -ms-flex-direction: row;
+        flex-direction: row;
       }
     "#, indoc! {r#"
       .foo {
-        transition: opacity 2s .5s;
+        flex-direction: row;
       }
-    "#});
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(14 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        transition-property: opacity;
-        transition-duration: 0.09s;
-        transition-timing-function: ease-in-out;
-        transition-delay: 500ms;
-        transition: color 2s;
+        flex-wrap: wrap;
       }
     "#, indoc! {r#"
       .foo {
-        transition: color 2s;
+        -webkit-box-lines: multiple;
+        -moz-box-lines: multiple;
+        -webkit-flex-wrap: wrap;
+        -ms-flex-wrap: wrap;
+        flex-wrap: wrap;
       }
-    "#});
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        transition-property: opacity, color;
-        transition-duration: 2s, 4s;
-        transition-timing-function: ease-in-out, ease-in;
-        transition-delay: 500ms, 0s;
+        -webkit-box-lines: multiple;
+        -moz-box-lines: multiple;
+        -webkit-flex-wrap: wrap;
+        -ms-flex-wrap: wrap;
+        flex-wrap: wrap;
       }
     "#, indoc! {r#"
       .foo {
-        transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+        flex-wrap: wrap;
       }
-    "#});
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        transition-property: opacity, color;
-        transition-duration: 2s;
-        transition-timing-function: ease-in-out;
-        transition-delay: 500ms;
+        flex-flow: row wrap;
       }
     "#, indoc! {r#"
       .foo {
-        transition-property: opacity, color;
-        transition-duration: 2s;
-        transition-delay: .5s;
-        transition-timing-function: ease-in-out;
+        -webkit-box-orient: horizontal;
+        -moz-box-orient: horizontal;
+        -webkit-box-direction: normal;
+        -moz-box-direction: normal;
+        -webkit-flex-flow: wrap;
+        -ms-flex-flow: wrap;
+        flex-flow: wrap;
       }
-    "#});
-
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        -webkit-transition-property: opacity, color;
-        -webkit-transition-duration: 2s, 4s;
-        -webkit-transition-timing-function: ease-in-out, ease-in;
-        -webkit-transition-delay: 500ms, 0s;
+        -webkit-box-orient: horizontal;
+        -moz-box-orient: horizontal;
+        -webkit-box-direction: normal;
+        -moz-box-direction: normal;
+        -webkit-flex-flow: wrap;
+        -ms-flex-flow: wrap;
+        flex-flow: wrap;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+        flex-flow: wrap;
       }
-    "#});
-
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        -webkit-transition-property: opacity, color;
-        -webkit-transition-duration: 2s, 4s;
-        -webkit-transition-timing-function: ease-in-out, ease-in;
-        -webkit-transition-delay: 500ms, 0s;
-        -moz-transition-property: opacity, color;
-        -moz-transition-duration: 2s, 4s;
-        -moz-transition-timing-function: ease-in-out, ease-in;
-        -moz-transition-delay: 500ms, 0s;
-        transition-property: opacity, color;
-        transition-duration: 2s, 4s;
-        transition-timing-function: ease-in-out, ease-in;
-        transition-delay: 500ms, 0s;
+        flex-grow: 1;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-transition: opacity 2s ease-in-out .5s, color 4s ease-in;
-        -moz-transition: opacity 2s ease-in-out .5s, color 4s ease-in;
-        transition: opacity 2s ease-in-out .5s, color 4s ease-in;
-      }
-    "#});
-
-    test(r#"
+        -webkit-box-flex: 1;
+        -moz-box-flex: 1;
+        -ms-flex-positive: 1;
+        -webkit-flex-grow: 1;
+        flex-grow: 1;
+      }
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        -webkit-transition-property: opacity, color;
-        -moz-transition-property: opacity, color;
-        transition-property: opacity, color;
-        -webkit-transition-duration: 2s, 4s;
-        -moz-transition-duration: 2s, 4s;
-        transition-duration: 2s, 4s;
-        -webkit-transition-timing-function: ease-in-out, ease-in;
-        transition-timing-function: ease-in-out, ease-in;
-        -moz-transition-timing-function: ease-in-out, ease-in;
-        -webkit-transition-delay: 500ms, 0s;
-        -moz-transition-delay: 500ms, 0s;
-        transition-delay: 500ms, 0s;
+        -webkit-box-flex: 1;
+        -moz-box-flex: 1;
+        -ms-flex-positive: 1;
+        -webkit-flex-grow: 1;
+        flex-grow: 1;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-transition: opacity 2s ease-in-out .5s, color 4s ease-in;
-        -moz-transition: opacity 2s ease-in-out .5s, color 4s ease-in;
-        transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+        flex-grow: 1;
       }
-    "#});
-
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        -webkit-transition-property: opacity;
-        -moz-transition-property: color;
-        transition-property: opacity, color;
-        -webkit-transition-duration: 2s;
-        -moz-transition-duration: 4s;
-        transition-duration: 2s, 4s;
-        -webkit-transition-timing-function: ease-in-out;
-        -moz-transition-timing-function: ease-in-out;
-        transition-timing-function: ease-in-out, ease-in;
-        -webkit-transition-delay: 500ms;
-        -moz-transition-delay: 0s;
-        transition-delay: 500ms, 0s;
+        flex-shrink: 1;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-transition-property: opacity;
-        -moz-transition-property: color;
-        transition-property: opacity, color;
-        -webkit-transition-duration: 2s;
-        -moz-transition-duration: 4s;
-        transition-duration: 2s, 4s;
-        -webkit-transition-timing-function: ease-in-out;
-        -moz-transition-timing-function: ease-in-out;
-        -webkit-transition-delay: .5s;
-        transition-timing-function: ease-in-out, ease-in;
-        -moz-transition-delay: 0s;
-        transition-delay: .5s, 0s;
+        -ms-flex-negative: 1;
+        -webkit-flex-shrink: 1;
+        flex-shrink: 1;
       }
-    "#});
-
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        -webkit-transition-property: opacity;
-        transition-property: opacity, color;
-        -moz-transition-property: color;
-        -webkit-transition-duration: 2s;
-        transition-duration: 2s, 4s;
-        -moz-transition-duration: 4s;
-        -webkit-transition-timing-function: ease-in-out;
-        transition-timing-function: ease-in-out, ease-in;
-        -moz-transition-timing-function: ease-in-out;
-        -webkit-transition-delay: 500ms;
-        transition-delay: 500ms, 0s;
-        -moz-transition-delay: 0s;
+        -ms-flex-negative: 1;
+        -webkit-flex-shrink: 1;
+        flex-shrink: 1;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-transition-property: opacity;
-        transition-property: opacity, color;
-        -moz-transition-property: color;
-        -webkit-transition-duration: 2s;
-        transition-duration: 2s, 4s;
-        -moz-transition-duration: 4s;
-        -webkit-transition-timing-function: ease-in-out;
-        transition-timing-function: ease-in-out, ease-in;
-        -webkit-transition-delay: .5s;
-        -moz-transition-timing-function: ease-in-out;
-        transition-delay: .5s, 0s;
-        -moz-transition-delay: 0s;
+        flex-shrink: 1;
       }
-    "#});
-
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        transition: opacity 2s;
-        -webkit-transition-duration: 2s;
+        flex-basis: 1px;
       }
     "#, indoc! {r#"
       .foo {
-        transition: opacity 2s;
-        -webkit-transition-duration: 2s;
+        -ms-flex-preferred-size: 1px;
+        -webkit-flex-basis: 1px;
+        flex-basis: 1px;
       }
-    "#});
-  }
-
-  #[test]
-  fn test_animation() {
-    minify_test(".foo { animation-name: test }", ".foo{animation-name:test}");
-    minify_test(".foo { animation-name: \"test\" }", ".foo{animation-name:test}");
-    minify_test(".foo { animation-name: foo, bar }", ".foo{animation-name:foo,bar}");
-    minify_test(".foo { animation-duration: 100ms }", ".foo{animation-duration:.1s}");
-    minify_test(".foo { animation-duration: 100ms, 2000ms }", ".foo{animation-duration:.1s,2s}");
-    minify_test(".foo { animation-timing-function: ease }", ".foo{animation-timing-function:ease}");
-    minify_test(".foo { animation-timing-function: cubic-bezier(0.42, 0, 1, 1) }", ".foo{animation-timing-function:ease-in}");
-    minify_test(".foo { animation-timing-function: ease, cubic-bezier(0.42, 0, 1, 1) }", ".foo{animation-timing-function:ease,ease-in}");
-    minify_test(".foo { animation-iteration-count: 5 }", ".foo{animation-iteration-count:5}");
-    minify_test(".foo { animation-iteration-count: 2.5 }", ".foo{animation-iteration-count:2.5}");
-    minify_test(".foo { animation-iteration-count: 2.0 }", ".foo{animation-iteration-count:2}");
-    minify_test(".foo { animation-iteration-count: infinite }", ".foo{animation-iteration-count:infinite}");
-    minify_test(".foo { animation-iteration-count: 1, infinite }", ".foo{animation-iteration-count:1,infinite}");
-    minify_test(".foo { animation-direction: reverse }", ".foo{animation-direction:reverse}");
-    minify_test(".foo { animation-direction: alternate, reverse }", ".foo{animation-direction:alternate,reverse}");
-    minify_test(".foo { animation-play-state: paused }", ".foo{animation-play-state:paused}");
-    minify_test(".foo { animation-play-state: running, paused }", ".foo{animation-play-state:running,paused}");
-    minify_test(".foo { animation-delay: 100ms }", ".foo{animation-delay:.1s}");
-    minify_test(".foo { animation-delay: 100ms, 2000ms }", ".foo{animation-delay:.1s,2s}");
-    minify_test(".foo { animation-fill-mode: forwards }", ".foo{animation-fill-mode:forwards}");
-    minify_test(".foo { animation-fill-mode: Backwards,forwards }", ".foo{animation-fill-mode:backwards,forwards}");
-    minify_test(".foo { animation: 3s ease-in 1s infinite reverse both running slidein }", ".foo{animation:slidein 3s ease-in 1s infinite reverse both}");
-    minify_test(".foo { animation: 3s slidein paused ease 1s 1 reverse both }", ".foo{animation:slidein 3s 1s reverse both paused}");
-    minify_test(".foo { animation: 3s ease ease }", ".foo{animation:ease 3s ease}");
-    minify_test(".foo { animation: 3s cubic-bezier(0.25, 0.1, 0.25, 1) foo }", ".foo{animation:foo 3s}");
-    minify_test(".foo { animation: foo 0s 3s infinite }", ".foo{animation:foo 0s 3s infinite}");
-    minify_test(".foo { animation: none }", ".foo{animation:none}");
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        animation-name: foo;
-        animation-duration: 0.09s;
-        animation-timing-function: ease-in-out;
-        animation-iteration-count: 2;
-        animation-direction: alternate;
-        animation-play-state: running;
-        animation-delay: 100ms;
-        animation-fill-mode: forwards;
+        -ms-flex-preferred-size: 1px;
+        -webkit-flex-basis: 1px;
+        flex-basis: 1px;
       }
     "#, indoc! {r#"
       .foo {
-        animation: foo 90ms ease-in-out .1s 2 alternate forwards;
+        flex-basis: 1px;
       }
-    "#});
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        animation-name: foo, bar;
-        animation-duration: 0.09s, 200ms;
-        animation-timing-function: ease-in-out, ease;
-        animation-iteration-count: 2, 1;
-        animation-direction: alternate, normal;
-        animation-play-state: running, paused;
-        animation-delay: 100ms, 0s;
-        animation-fill-mode: forwards, none;
+        flex: 1;
       }
     "#, indoc! {r#"
       .foo {
-        animation: foo 90ms ease-in-out .1s 2 alternate forwards, bar .2s paused;
+        -webkit-box-flex: 1;
+        -moz-box-flex: 1;
+        -webkit-flex: 1;
+        -ms-flex: 1;
+        flex: 1;
       }
-    "#});
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        animation: bar 200ms;
-        animation-timing-function: ease-in-out;
+        -webkit-box-flex: 1;
+        -moz-box-flex: 1;
+        -webkit-flex: 1;
+        -ms-flex: 1;
+        flex: 1;
       }
     "#, indoc! {r#"
       .foo {
-        animation: bar .2s ease-in-out;
+        flex: 1;
       }
-    "#});
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        animation-name: foo, bar;
-        animation-duration: 0.09s;
-        animation-timing-function: ease-in-out;
-        animation-iteration-count: 2;
-        animation-direction: alternate;
-        animation-play-state: running;
-        animation-delay: 100ms;
-        animation-fill-mode: forwards;
+        align-content: space-between;
       }
     "#, indoc! {r#"
       .foo {
-        animation-name: foo, bar;
-        animation-duration: 90ms;
-        animation-timing-function: ease-in-out;
-        animation-iteration-count: 2;
-        animation-direction: alternate;
-        animation-play-state: running;
-        animation-delay: .1s;
-        animation-fill-mode: forwards;
+        -ms-flex-line-pack: justify;
+        -webkit-align-content: space-between;
+        align-content: space-between;
       }
-    "#});
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        -webkit-animation-name: foo;
-        -webkit-animation-duration: 0.09s;
-        -webkit-animation-timing-function: ease-in-out;
-        -webkit-animation-iteration-count: 2;
-        -webkit-animation-direction: alternate;
-        -webkit-animation-play-state: running;
-        -webkit-animation-delay: 100ms;
-        -webkit-animation-fill-mode: forwards;
+        -ms-flex-line-pack: justify;
+        -webkit-align-content: space-between;
+        align-content: space-between;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-animation: foo 90ms ease-in-out .1s 2 alternate forwards;
+        align-content: space-between;
       }
-    "#});
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        -moz-animation: bar 200ms;
-        -moz-animation-timing-function: ease-in-out;
+        justify-content: space-between;
       }
     "#, indoc! {r#"
       .foo {
-        -moz-animation: bar .2s ease-in-out;
+        -webkit-box-pack: justify;
+        -moz-box-pack: justify;
+        -ms-flex-pack: justify;
+        -webkit-justify-content: space-between;
+        justify-content: space-between;
       }
-    "#});
-    test(r#"
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
       .foo {
-        -webkit-animation: bar 200ms;
-        -webkit-animation-timing-function: ease-in-out;
-        -moz-animation: bar 200ms;
-        -moz-animation-timing-function: ease-in-out;
+        -webkit-box-pack: justify;
+        -moz-box-pack: justify;
+        -ms-flex-pack: justify;
+        -webkit-justify-content: space-between;
+        justify-content: space-between;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-animation: bar .2s ease-in-out;
-        -moz-animation: bar .2s ease-in-out;
+        justify-content: space-between;
       }
-    "#});
-
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
     prefix_test(r#"
       .foo {
-        animation: bar .2s ease-in-out;
+        place-content: space-between flex-end;
       }
     "#, indoc! {r#"
       .foo {
-        -webkit-animation: bar .2s ease-in-out;
-        -moz-animation: bar .2s ease-in-out;
-        animation: bar .2s ease-in-out;
+        -ms-flex-line-pack: justify;
+        -webkit-box-pack: end;
+        -moz-box-pack: end;
+        -ms-flex-pack: end;
+        -webkit-align-content: space-between;
+        align-content: space-between;
+        -webkit-justify-content: flex-end;
+        justify-content: flex-end;
       }
-    "#}, Browsers {
-      firefox: Some(6 << 16),
-      safari: Some(6 << 16),
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
       ..Browsers::default()
     });
-
     prefix_test(r#"
       .foo {
-        -webkit-animation: bar .2s ease-in-out;
-        -moz-animation: bar .2s ease-in-out;
-        animation: bar .2s ease-in-out;
+        -ms-flex-line-pack: justify;
+        -webkit-box-pack: end;
+        -moz-box-pack: end;
+        -ms-flex-pack: end;
+        -webkit-align-content: space-between;
+        -webkit-justify-content: flex-end;
+        place-content: space-between flex-end;
       }
     "#, indoc! {r#"
       .foo {
-        animation: bar .2s ease-in-out;
+        place-content: space-between flex-end;
       }
-    "#}, Browsers {
-      firefox: Some(20 << 16),
-      safari: Some(14 << 16),
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
       ..Browsers::default()
     });
-  }
-
-  #[test]
-  fn test_transform() {
-    minify_test(".foo { transform: translate(2px, 3px)", ".foo{transform:translate(2px,3px)}");
-    minify_test(".foo { transform: translate(2px, 0px)", ".foo{transform:translate(2px)}");
-    minify_test(".foo { transform: translate(0px, 2px)", ".foo{transform:translateY(2px)}");
-    minify_test(".foo { transform: translateX(2px)", ".foo{transform:translate(2px)}");
-    minify_test(".foo { transform: translateY(2px)", ".foo{transform:translateY(2px)}");
-    minify_test(".foo { transform: translateZ(2px)", ".foo{transform:translateZ(2px)}");
-    minify_test(".foo { transform: translate3d(2px, 3px, 4px)", ".foo{transform:translate3d(2px,3px,4px)}");
-    minify_test(".foo { transform: translate3d(10%, 20%, 4px)", ".foo{transform:translate3d(10%,20%,4px)}");
-    minify_test(".foo { transform: translate3d(2px, 0px, 0px)", ".foo{transform:translate(2px)}");
-    minify_test(".foo { transform: translate3d(0px, 2px, 0px)", ".foo{transform:translateY(2px)}");
-    minify_test(".foo { transform: translate3d(0px, 0px, 2px)", ".foo{transform:translateZ(2px)}");
-    minify_test(".foo { transform: translate3d(2px, 3px, 0px)", ".foo{transform:translate(2px,3px)}");
-    minify_test(".foo { transform: scale(2, 3)", ".foo{transform:scale(2,3)}");
-    minify_test(".foo { transform: scale(10%, 20%)", ".foo{transform:scale(.1,.2)}");
-    minify_test(".foo { transform: scale(2, 2)", ".foo{transform:scale(2)}");
-    minify_test(".foo { transform: scale(2, 1)", ".foo{transform:scaleX(2)}");
-    minify_test(".foo { transform: scale(1, 2)", ".foo{transform:scaleY(2)}");
-    minify_test(".foo { transform: scaleX(2)", ".foo{transform:scaleX(2)}");
-    minify_test(".foo { transform: scaleY(2)", ".foo{transform:scaleY(2)}");
-    minify_test(".foo { transform: scaleZ(2)", ".foo{transform:scaleZ(2)}");
-    minify_test(".foo { transform: scale3d(2, 3, 4)", ".foo{transform:scale3d(2,3,4)}");
-    minify_test(".foo { transform: scale3d(2, 1, 1)", ".foo{transform:scaleX(2)}");
-    minify_test(".foo { transform: scale3d(1, 2, 1)", ".foo{transform:scaleY(2)}");
-    minify_test(".foo { transform: scale3d(1, 1, 2)", ".foo{transform:scaleZ(2)}");
-    minify_test(".foo { transform: scale3d(2, 2, 1)", ".foo{transform:scale(2)}");
-    minify_test(".foo { transform: rotate(20deg)", ".foo{transform:rotate(20deg)}");
-    minify_test(".foo { transform: rotateX(20deg)", ".foo{transform:rotateX(20deg)}");
-    minify_test(".foo { transform: rotateY(20deg)", ".foo{transform:rotateY(20deg)}");
-    minify_test(".foo { transform: rotateZ(20deg)", ".foo{transform:rotate(20deg)}");
-    minify_test(".foo { transform: rotate(360deg)", ".foo{transform:rotate(360deg)}");
-    minify_test(".foo { transform: rotate3d(2, 3, 4, 20deg)", ".foo{transform:rotate3d(2,3,4,20deg)}");
-    minify_test(".foo { transform: rotate3d(1, 0, 0, 20deg)", ".foo{transform:rotateX(20deg)}");
-    minify_test(".foo { transform: rotate3d(0, 1, 0, 20deg)", ".foo{transform:rotateY(20deg)}");
-    minify_test(".foo { transform: rotate3d(0, 0, 1, 20deg)", ".foo{transform:rotate(20deg)}");
-    minify_test(".foo { transform: skew(20deg)", ".foo{transform:skew(20deg)}");
-    minify_test(".foo { transform: skew(20deg, 0deg)", ".foo{transform:skew(20deg)}");
-    minify_test(".foo { transform: skew(0deg, 20deg)", ".foo{transform:skewY(20deg)}");
-    minify_test(".foo { transform: skewX(20deg)", ".foo{transform:skew(20deg)}");
-    minify_test(".foo { transform: skewY(20deg)", ".foo{transform:skewY(20deg)}");
-    minify_test(".foo { transform: perspective(10px)", ".foo{transform:perspective(10px)}");
-    minify_test(".foo { transform: matrix(1, 2, -1, 1, 80, 80)", ".foo{transform:matrix(1,2,-1,1,80,80)}");
-    minify_test(".foo { transform: matrix3d(1, 0, 0, 0, 0, 1, 6, 0, 0, 0, 1, 0, 50, 100, 0, 1.1)", ".foo{transform:matrix3d(1,0,0,0,0,1,6,0,0,0,1,0,50,100,0,1.1)}");
-    minify_test(
-      ".foo{transform:translate(100px,200px) rotate(45deg) skew(10deg) scale(2)}",
-      ".foo{transform:matrix(1.41421,1.41421,-1.16485,1.66358,100,200)}"
-    );
-    minify_test(
-      ".foo{transform:translate(200px,300px) translate(100px,200px) scale(2)}",
-      ".foo{transform:matrix(2,0,0,2,300,500)}"
-    );
-    minify_test(
-      ".foo{transform:translate(100px,200px) rotate(45deg)}",
-      ".foo{transform:translate(100px,200px)rotate(45deg)}"
-    );
-    minify_test(
-      ".foo{transform:rotate3d(1, 1, 1, 45deg) translate3d(100px, 100px, 10px)}",
-      ".foo{transform:rotate3d(1,1,1,45deg)translate3d(100px,100px,10px)}"
-    );
-    minify_test(
-      ".foo{transform:translate3d(100px, 100px, 10px) skew(10deg) scale3d(2, 3, 4)}",
-      ".foo{transform:matrix3d(2,0,0,0,.528981,3,0,0,0,0,4,0,100,100,10,1)}"
-    );
-    minify_test(
-      ".foo{transform:matrix3d(0.804737854124365, 0.5058793634016805, -0.31061721752604554, 0, -0.31061721752604554, 0.804737854124365, 0.5058793634016805, 0, 0.5058793634016805, -0.31061721752604554, 0.804737854124365, 0, 100, 100, 10, 1)}",
-      ".foo{transform:translate3d(100px,100px,10px)rotate3d(1,1,1,45deg)}"
-    );
-    minify_test(
-      ".foo{transform:matrix3d(1, 0, 0, 0, 0, 0.7071067811865476, 0.7071067811865475, 0, 0, -0.7071067811865475, 0.7071067811865476, 0, 100, 100, 10, 1)}",
-      ".foo{transform:translate3d(100px,100px,10px)rotateX(45deg)}"
-    );
-    minify_test(
-      ".foo{transform:translate3d(100px, 200px, 10px) translate(100px, 100px)}",
-      ".foo{transform:translate3d(200px,300px,10px)}"
-    );
-    minify_test(
-      ".foo{transform:rotate(45deg) rotate(45deg)}",
-      ".foo{transform:rotate(90deg)}"
-    );
-    minify_test(
-      ".foo{transform:matrix(0.7071067811865476, 0.7071067811865475, -0.7071067811865475, 0.7071067811865476, 100, 100)}",
-      ".foo{transform:translate(100px,100px)rotate(45deg)}"
-    );
-    minify_test(
-      ".foo{transform:translateX(2in) translateX(50px)}",
-      ".foo{transform:translate(242px)}"
-    );
-    minify_test(
-      ".foo{transform:translateX(calc(2in + 50px))}",
-      ".foo{transform:translate(242px)}"
-    );
-    minify_test(
-      ".foo{transform:translateX(50%)}",
-      ".foo{transform:translate(50%)}"
-    );
-    minify_test(
-      ".foo{transform:translateX(calc(50% - 100px + 20px))}",
-      ".foo{transform:translate(calc(50% - 80px))}"
-    );
-    minify_test(
-      ".foo{transform:rotate(calc(10deg + 20deg))}",
-      ".foo{transform:rotate(30deg)}"
-    );
-    minify_test(
-      ".foo{transform:rotate(calc(10deg + 0.349066rad))}",
-      ".foo{transform:rotate(30deg)}"
-    );
-    minify_test(
-      ".foo{transform:rotate(calc(10deg + 1.5turn))}",
-      ".foo{transform:rotate(550deg)}"
-    );
-    minify_test(
-      ".foo{transform:rotate(calc(10deg * 2))}",
-      ".foo{transform:rotate(20deg)}"
-    );
-    minify_test(
-      ".foo{transform:rotate(calc(-10deg * 2))}",
-      ".foo{transform:rotate(-20deg)}"
-    );
-    minify_test(
-      ".foo{transform:rotate(calc(10deg + var(--test)))}",
-      ".foo{transform:rotate(calc(10deg + var(--test)))}"
-    );
-    minify_test(".foo { transform: scale(calc(10% + 20%))", ".foo{transform:scale(.3)}");
-    minify_test(".foo { transform: scale(calc(.1 + .2))", ".foo{transform:scale(.3)}");
-
-    minify_test(".foo { -webkit-transform: scale(calc(10% + 20%))", ".foo{-webkit-transform:scale(.3)}");
-  }
-
-  #[test]
-  pub fn test_gradients() {
-    minify_test(
-      ".foo { background: linear-gradient(yellow, blue) }",
-      ".foo{background:linear-gradient(#ff0,#00f)}"
-    );
-    minify_test(
-      ".foo { background: linear-gradient(to bottom, yellow, blue); }",
-      ".foo{background:linear-gradient(#ff0,#00f)}"
-    );
-    minify_test(
-      ".foo { background: linear-gradient(180deg, yellow, blue); }",
-      ".foo{background:linear-gradient(#ff0,#00f)}"
-    );
-    minify_test(
-      ".foo { background: linear-gradient(0.5turn, yellow, blue); }",
-      ".foo{background:linear-gradient(#ff0,#00f)}"
-    );
-    minify_test(
-      ".foo { background: linear-gradient(yellow 10%, blue 20%) }",
-      ".foo{background:linear-gradient(#ff0 10%,#00f 20%)}"
-    );
-    minify_test(
-      ".foo { background: linear-gradient(to top, blue, yellow); }",
-      ".foo{background:linear-gradient(#ff0,#00f)}"
-    );
+    prefix_test(r#"
+      .foo {
+        align-self: flex-end;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -ms-flex-item-align: end;
+        -webkit-align-self: flex-end;
+        align-self: flex-end;
+      }
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        -ms-flex-item-align: end;
+        -webkit-align-self: flex-end;
+        align-self: flex-end;
+      }
+    "#, indoc! {r#"
+      .foo {
+        align-self: flex-end;
+      }
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        place-self: center flex-end;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -ms-flex-item-align: center;
+        -webkit-align-self: center;
+        align-self: center;
+        justify-self: flex-end;
+      }
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        -ms-flex-item-align: center;
+        -webkit-align-self: center;
+        place-self: center flex-end;
+      }
+    "#, indoc! {r#"
+      .foo {
+        place-self: center flex-end;
+      }
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        align-items: flex-end;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-box-align: end;
+        -moz-box-align: end;
+        -ms-flex-align: end;
+        -webkit-align-items: flex-end;
+        align-items: flex-end;
+      }
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        -webkit-box-align: end;
+        -moz-box-align: end;
+        -ms-flex-align: end;
+        -webkit-align-items: flex-end;
+        align-items: flex-end;
+      }
+    "#, indoc! {r#"
+      .foo {
+        align-items: flex-end;
+      }
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        place-items: flex-end center;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-box-align: end;
+        -moz-box-align: end;
+        -ms-flex-align: end;
+        -webkit-align-items: flex-end;
+        align-items: flex-end;
+        justify-items: center;
+      }
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        -webkit-box-align: end;
+        -moz-box-align: end;
+        -ms-flex-align: end;
+        -webkit-align-items: flex-end;
+        place-items: flex-end center;
+      }
+    "#, indoc! {r#"
+      .foo {
+        place-items: flex-end center;
+      }
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        align-content: center;
+        justify-content: space-between;
+        align-items: center;
+        justify-items: start;
+      }
+    "#, indoc! {r#"
+      .foo {
+        align-content: center;
+        justify-content: space-between;
+        align-items: center;
+        justify-items: start;
+      }
+    "#},
+    Browsers {
+      chrome: Some(50 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        align-content: center;
+        justify-content: space-between;
+        align-items: center;
+        justify-items: start;
+      }
+    "#, indoc! {r#"
+      .foo {
+        place-content: center space-between;
+        place-items: center start;
+      }
+    "#},
+    Browsers {
+      chrome: Some(60 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        order: 1;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-box-ordinal-group: 1;
+        -moz-box-ordinal-group: 1;
+        -ms-flex-order: 1;
+        -webkit-order: 1;
+        order: 1;
+      }
+    "#},
+    Browsers {
+      safari: Some(4 << 16),
+      firefox: Some(4 << 16),
+      ie: Some(10 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      .foo {
+        -webkit-box-ordinal-group: 1;
+        -moz-box-ordinal-group: 1;
+        -ms-flex-order: 1;
+        -webkit-order: 1;
+        order: 1;
+      }
+    "#, indoc! {r#"
+      .foo {
+        order: 1;
+      }
+    "#},
+    Browsers {
+      safari: Some(11 << 16),
+      ..Browsers::default()
+    });
+  }
+
+  #[test]
+  fn test_font() {
+    test(r#"
+      .foo {
+        font-family: "Helvetica", "Times New Roman", sans-serif;
+        font-size: 12px;
+        font-weight: bold;
+        font-style: italic;
+        font-stretch: expanded;
+        font-variant-caps: small-caps;
+        line-height: 1.2em;
+      }
+    "#, indoc! {r#"
+      .foo {
+        font: italic small-caps bold expanded 12px / 1.2em Helvetica, Times New Roman, sans-serif;
+      }
+    "#
+    });
+
+    minify_test(r#"
+      .foo {
+        font-family: "Helvetica", "Times New Roman", sans-serif;
+        font-size: 12px;
+        font-weight: bold;
+        font-style: italic;
+        font-stretch: expanded;
+        font-variant-caps: small-caps;
+        line-height: 1.2em;
+      }
+    "#, indoc! {".foo{font:italic small-caps 700 50% 9pt/1.2em Helvetica,Times New Roman,sans-serif}"
+    });
+
+    test(r#"
+      .foo {
+        font: 12px "Helvetica", "Times New Roman", sans-serif;
+        line-height: 1.2em;
+      }
+    "#, indoc! {r#"
+      .foo {
+        font: 12px / 1.2em Helvetica, Times New Roman, sans-serif;
+      }
+    "#
+    });
+
+    minify_test(r#"
+      .foo {
+        font-family: "Helvetica", "Times New Roman", sans-serif;
+        font-size: 12px;
+        font-stretch: expanded;
+      }
+    "#, indoc! {".foo{font-family:Helvetica,Times New Roman,sans-serif;font-size:9pt;font-stretch:50%}"
+    });
+
+    test(r#"
+      .foo {
+        font-family: "Helvetica", "Times New Roman", sans-serif;
+        font-size: 12px;
+        font-weight: bold;
+        font-style: italic;
+        font-stretch: expanded;
+        font-variant-caps: all-small-caps;
+        line-height: 1.2em;
+      }
+    "#, indoc! {r#"
+      .foo {
+        font: italic bold expanded 12px / 1.2em Helvetica, Times New Roman, sans-serif;
+        font-variant-caps: all-small-caps;
+      }
+    "#
+    });
+  }
+
+  #[test]
+  fn test_font_fallbacks() {
+    use crate::properties::font::GenericFontFamily;
+
+    let minify = |code: &str, font_fallbacks: Vec<GenericFontFamily>| compile(code, ParserOptions::default(), MinifyOptions {
+      font_fallbacks,
+      ..MinifyOptions::default()
+    }, PrinterOptions {
+      minify: true,
+      ..PrinterOptions::default()
+    });
+
+    let fallbacks = vec![GenericFontFamily::SansSerif];
+    assert_eq!(minify(".foo { font-family: Helvetica, Arial }", fallbacks.clone()), ".foo{font-family:Helvetica,Arial,sans-serif}");
+    assert_eq!(minify(".foo { font: 12px Helvetica }", fallbacks.clone()), ".foo{font:9pt Helvetica,sans-serif}");
+    assert_eq!(minify(".foo { font-family: Helvetica !important }", fallbacks.clone()), ".foo{font-family:Helvetica,sans-serif!important}");
+    assert_eq!(minify(".foo { font-family: Georgia, serif }", fallbacks.clone()), ".foo{font-family:Georgia,serif}");
+    assert_eq!(minify(".foo { font-family: inherit }", fallbacks.clone()), ".foo{font-family:inherit}");
+    assert_eq!(minify(".foo { font-family: Helvetica }", vec![]), ".foo{font-family:Helvetica}");
+
+    // Fallbacks that are already in the list are not added again.
+    assert_eq!(
+      minify(".foo { font-family: Menlo }", vec![GenericFontFamily::UIMonospace, GenericFontFamily::Monospace, GenericFontFamily::Monospace]),
+      ".foo{font-family:Menlo,ui-monospace,monospace}"
+    );
+  }
+
+  #[test]
+  fn test_font_size_adjust() {
+    use crate::warnings::{Warning, WarningKind};
+
+    minify_test(".foo { font-size-adjust: none }", ".foo{font-size-adjust:none}");
+    minify_test(".foo { font-size-adjust: 0.5 }", ".foo{font-size-adjust:.5}");
+    minify_test(".foo { font-size-adjust: ex-height 0.5 }", ".foo{font-size-adjust:.5}");
+    minify_test(".foo { font-size-adjust: cap-height 0.7 }", ".foo{font-size-adjust:cap-height .7}");
+    minify_test(".foo { font-size-adjust: from-font }", ".foo{font-size-adjust:from-font}");
+    minify_test(".foo { font-size-adjust: ic-width from-font }", ".foo{font-size-adjust:ic-width from-font}");
+    minify_test(".foo { font-optical-sizing: none }", ".foo{font-optical-sizing:none}");
+    minify_test(".foo { font-size-adjust: 0.5; font: 12px serif }", ".foo{font:9pt serif}");
+    minify_test(".foo { font: 12px serif; font-size-adjust: 0.5 }", ".foo{font:9pt serif;font-size-adjust:.5}");
+
+    let targets = Some(Browsers {
+      firefox: Some(100 << 16),
+      ..Browsers::default()
+    });
+    assert_eq!(StyleSheet::validate(".foo { font-size-adjust: 0.5 }", targets), vec![]);
+    assert_eq!(StyleSheet::validate(".foo { font-size-adjust: cap-height 0.5 }", targets), vec![
+      Warning { kind: WarningKind::UnsupportedFeature(compat::Feature::FontSizeAdjustTwoValues), line: 1, column: 26 }
+    ]);
+
+    let targets = Some(Browsers {
+      chrome: Some(70 << 16),
+      ..Browsers::default()
+    });
+    assert_eq!(StyleSheet::validate(".foo { font-optical-sizing: auto }", targets), vec![
+      Warning { kind: WarningKind::UnsupportedFeature(compat::Feature::FontOpticalSizing), line: 1, column: 29 }
+    ]);
+  }
+
+  #[test]
+  fn test_selectors() {
+    minify_test("[foo=\"baz\"] {}", "[foo=baz]{}");
+    minify_test("[foo=\"foo bar\"] {}", "[foo=foo\\ bar]{}");
+    minify_test("[foo=\"foo bar baz\"] {}", "[foo=\"foo bar baz\"]{}");
+    minify_test(".test:not([foo=\"bar\"]) {}", ".test:not([foo=bar]){}");
+    minify_test(".test + .foo {}", ".test+.foo{}");
+    minify_test(".test ~ .foo {}", ".test~.foo{}");
+    minify_test(".test .foo {}", ".test .foo{}");
+    minify_test(".test:not(.a, .b) {}", ".test:not(.a,.b){}");
+
+    prefix_test(r#"
+      .test:not(.a, .b > .c) {
+        color: red;
+      }
+    "#, indoc! {r#"
+      .test:not(.a):not(.b > .c) {
+        color: red;
+      }
+    "#
+    }, Browsers {
+      safari: Some(8 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .test:not(.a, .b) {
+        color: red;
+      }
+    "#, indoc! {r#"
+      .test:not(.a, .b) {
+        color: red;
+      }
+    "#
+    }, Browsers {
+      chrome: Some(88 << 16),
+      firefox: Some(84 << 16),
+      ..Browsers::default()
+    });
+  }
+
+  #[test]
+  fn test_pseudo_elements() {
+    minify_test("li::marker { color: red }", "li::marker{color:red}");
+    minify_test("dialog::backdrop { color: red }", "dialog::backdrop{color:red}");
+    minify_test("dialog::-ms-backdrop { color: red }", "dialog::-ms-backdrop{color:red}");
+    minify_test("input::file-selector-button { color: red }", "input::file-selector-button{color:red}");
+    minify_test("input::-webkit-file-upload-button { color: red }", "input::-webkit-file-upload-button{color:red}");
+    minify_test("input::-ms-browse { color: red }", "input::-ms-browse{color:red}");
+    minify_test("input::-webkit-file-upload-button { color: red } input::file-selector-button { color: red }", "input::-webkit-file-upload-button{color:red}input::file-selector-button{color:red}");
+
+    prefix_test(r#"
+      input::file-selector-button {
+        color: red;
+      }
+    "#, indoc! {r#"
+      input::-webkit-file-upload-button {
+        color: red;
+      }
+
+      input::file-selector-button {
+        color: red;
+      }
+    "#
+    }, Browsers {
+      safari: Some(13 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .a::file-selector-button, .b {
+        color: red;
+      }
+    "#, indoc! {r#"
+      .a::-webkit-file-upload-button {
+        color: red;
+      }
+
+      .a::-ms-browse {
+        color: red;
+      }
+
+      .a::file-selector-button, .b {
+        color: red;
+      }
+    "#
+    }, Browsers {
+      ie: Some(11 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      input::file-selector-button {
+        color: red;
+      }
+    "#, indoc! {r#"
+      input::file-selector-button {
+        color: red;
+      }
+    "#
+    }, Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      dialog::backdrop {
+        color: red;
+      }
+    "#, indoc! {r#"
+      dialog::-webkit-backdrop {
+        color: red;
+      }
+
+      dialog::backdrop {
+        color: red;
+      }
+    "#
+    }, Browsers {
+      chrome: Some(30 << 16),
+      ..Browsers::default()
+    });
+
+    minify_test("input::placeholder { color: red }", "input::placeholder{color:red}");
+    minify_test("input::-webkit-input-placeholder { color: red }", "input::-webkit-input-placeholder{color:red}");
+    minify_test("input::-moz-placeholder { color: red }", "input::-moz-placeholder{color:red}");
+    minify_test("input::-ms-input-placeholder { color: red }", "input::-ms-input-placeholder{color:red}");
+
+    prefix_test(r#"
+      input::placeholder {
+        color: red;
+      }
+    "#, indoc! {r#"
+      input::-webkit-input-placeholder {
+        color: red;
+      }
+
+      input::-moz-placeholder {
+        color: red;
+      }
+
+      input::placeholder {
+        color: red;
+      }
+    "#
+    }, Browsers {
+      chrome: Some(50 << 16),
+      firefox: Some(40 << 16),
+      ..Browsers::default()
+    });
+
+    let compile_placeholder = |source: &str, placeholder_opacity: bool| {
+      let targets = Some(Browsers {
+        firefox: Some(40 << 16),
+        ..Browsers::default()
+      });
+      compile(source, ParserOptions::default(), MinifyOptions {
+        targets,
+        placeholder_opacity,
+        ..MinifyOptions::default()
+      }, PrinterOptions {
+        minify: true,
+        targets,
+        ..PrinterOptions::default()
+      })
+    };
+
+    assert_eq!(
+      compile_placeholder("input::placeholder { color: red }", true),
+      "input::-moz-placeholder{color:red;opacity:1}input::placeholder{color:red}"
+    );
+    assert_eq!(
+      compile_placeholder("input::placeholder { color: red }", false),
+      "input::-moz-placeholder{color:red}input::placeholder{color:red}"
+    );
+    assert_eq!(
+      compile_placeholder("input::placeholder { color: red; opacity: .8 }", true),
+      "input::-moz-placeholder{color:red;opacity:.8}input::placeholder{color:red;opacity:.8}"
+    );
+    assert_eq!(
+      compile_placeholder(".a { color: red }", true),
+      ".a{color:red}"
+    );
+  }
+
+  #[test]
+  fn test_keyframes() {
+    minify_test(r#"
+      @keyframes test {
+        from {
+          background: green;
+        }
+
+        50% {
+          background: red;
+        }
+
+        100% {
+          background: blue
+        }
+      }
+    "#, "@keyframes test{0%{background:green}50%{background:red}to{background:#00f}}");
+    minify_test(r#"
+      @keyframes test {
+        from {
+          background: green;
+          background-color: red;
+        }
+
+        100% {
+          background: blue
+        }
+      }
+    "#, "@keyframes test{0%{background:red}to{background:#00f}}");
+    minify_test(r#"
+      @-webkit-keyframes test {
+        from {
+          background: green;
+          background-color: red;
+        }
+
+        100% {
+          background: blue
+        }
+      }
+    "#, "@-webkit-keyframes test{0%{background:red}to{background:#00f}}");
+    minify_test(r#"
+      @-moz-keyframes test {
+        from {
+          background: green;
+          background-color: red;
+        }
+
+        100% {
+          background: blue
+        }
+      }
+    "#, "@-moz-keyframes test{0%{background:red}to{background:#00f}}");
+    minify_test(r#"
+      @-webkit-keyframes test {
+        from {
+          background: green;
+          background-color: red;
+        }
+
+        100% {
+          background: blue
+        }
+      }
+      @-moz-keyframes test {
+        from {
+          background: green;
+          background-color: red;
+        }
+
+        100% {
+          background: blue
+        }
+      }
+    "#, "@-webkit-keyframes test{0%{background:red}to{background:#00f}}@-moz-keyframes test{0%{background:red}to{background:#00f}}");
+
+    prefix_test(r#"
+      @keyframes test {
+        from {
+          background: green;
+        }
+        to {
+          background: blue
+        }
+      }
+    "#, indoc! { r#"
+      @-webkit-keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: #00f;
+        }
+      }
+
+      @-moz-keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: #00f;
+        }
+      }
+
+      @keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: #00f;
+        }
+      }
+    "#}, Browsers {
+      safari: Some(5 << 16),
+      firefox: Some(6 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      @-webkit-keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: blue;
+        }
+      }
+      @-moz-keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: blue;
+        }
+      }
+      @keyframes test {
+        from {
+          background: green;
+        }
+        to {
+          background: blue
+        }
+      }
+    "#, indoc! { r#"
+      @keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: #00f;
+        }
+      }
+    "#}, Browsers {
+      safari: Some(10 << 16),
+      firefox: Some(17 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      @-webkit-keyframes test1 {
+        from {
+          background: green;
+        }
+
+        to {
+          background: blue;
+        }
+      }
+
+      @-moz-keyframes test2 {
+        from {
+          background: green;
+        }
+
+        to {
+          background: blue;
+        }
+      }
+
+      @keyframes test3 {
+        from {
+          background: green;
+        }
+        to {
+          background: blue
+        }
+      }
+    "#, indoc! { r#"
+      @-webkit-keyframes test1 {
+        from {
+          background: green;
+        }
+
+        to {
+          background: #00f;
+        }
+      }
+
+      @-moz-keyframes test2 {
+        from {
+          background: green;
+        }
+
+        to {
+          background: #00f;
+        }
+      }
+
+      @keyframes test3 {
+        from {
+          background: green;
+        }
+
+        to {
+          background: #00f;
+        }
+      }
+    "#}, Browsers {
+      safari: Some(10 << 16),
+      firefox: Some(17 << 16),
+      ..Browsers::default()
+    });
+    prefix_test(r#"
+      @-webkit-keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: red;
+        }
+      }
+      @-moz-keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: pink;
+        }
+      }
+      @keyframes test {
+        from {
+          background: green;
+        }
+        to {
+          background: blue
+        }
+      }
+    "#, indoc! { r#"
+      @-webkit-keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: red;
+        }
+      }
+
+      @-moz-keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: pink;
+        }
+      }
+
+      @keyframes test {
+        from {
+          background: green;
+        }
+
+        to {
+          background: #00f;
+        }
+      }
+    "#}, Browsers {
+      safari: Some(10 << 16),
+      firefox: Some(17 << 16),
+      ..Browsers::default()
+    });
+
+    // from and to are equivalent to 0% and 100% when deduplicating and merging keyframes.
+    minify_test(r#"
+      @keyframes test {
+        from, 0% {
+          background: red;
+        }
+        to, 100% {
+          background: blue
+        }
+      }
+    "#, "@keyframes test{0%{background:red}to{background:#00f}}");
+    minify_test(r#"
+      @keyframes test {
+        from {
+          background: red;
+        }
+        0% {
+          background: red;
+        }
+        50% {
+          background: red;
+        }
+        100% {
+          background: blue
+        }
+        to {
+          background: blue
+        }
+      }
+    "#, "@keyframes test{0%,50%{background:red}to{background:#00f}}");
+    minify_test(r#"
+      @-webkit-keyframes test {
+        from {
+          background: red;
+        }
+        to {
+          background: blue
+        }
+      }
+      @keyframes test {
+        0% {
+          background: red;
+        }
+        100% {
+          background: blue
+        }
+      }
+    "#, "@-webkit-keyframes test{0%{background:red}to{background:#00f}}@keyframes test{0%{background:red}to{background:#00f}}");
+
+    // Properties are prefixed within @-webkit-keyframes, which old versions of WebKit require.
+    prefix_test(r#"
+      @keyframes test {
+        from {
+          opacity: 0;
+          transform: rotate(0deg);
+        }
+        to {
+          opacity: 1;
+          transform: rotate(360deg);
+        }
+      }
+    "#, indoc! { r#"
+      @-webkit-keyframes test {
+        from {
+          opacity: 0;
+          -webkit-transform: rotate(0deg);
+        }
+
+        to {
+          opacity: 1;
+          -webkit-transform: rotate(360deg);
+        }
+      }
+
+      @keyframes test {
+        from {
+          opacity: 0;
+          transform: rotate(0deg);
+        }
+
+        to {
+          opacity: 1;
+          transform: rotate(360deg);
+        }
+      }
+    "#}, Browsers {
+      chrome: Some(40 << 16),
+      ..Browsers::default()
+    });
+  }
+
+  #[test]
+  fn test_duplicate_keyframes() {
+    use crate::rules::keyframes::DuplicateKeyframes;
+
+    let minify = |code: &str, duplicate_keyframes: DuplicateKeyframes| compile(code, ParserOptions::default(), MinifyOptions {
+      duplicate_keyframes,
+      ..MinifyOptions::default()
+    }, PrinterOptions {
+      minify: true,
+      ..PrinterOptions::default()
+    });
+
+    let source = r#"
+      @keyframes spin { from { opacity: 0 } to { opacity: 1 } }
+      .a { animation-name: spin }
+      @keyframes spin { from { color: red } to { color: green } }
+      .b { animation: spin 1s }
+    "#;
+    assert_eq!(
+      minify(source, DuplicateKeyframes::Keep),
+      "@keyframes spin{0%{opacity:0}to{opacity:1}}.a{animation-name:spin}@keyframes spin{0%{color:red}to{color:green}}.b{animation:spin 1s}"
+    );
+    assert_eq!(
+      minify(source, DuplicateKeyframes::RemoveEarlier),
+      ".a{animation-name:spin}@keyframes spin{0%{color:red}to{color:green}}.b{animation:spin 1s}"
+    );
+    assert_eq!(
+      minify(source, DuplicateKeyframes::Rename),
+      "@keyframes spin-1{0%{opacity:0}to{opacity:1}}.a{animation-name:spin-1}@keyframes spin{0%{color:red}to{color:green}}.b{animation:spin 1s}"
+    );
+
+    // Animations declared before the first rule refer to it, and existing names are not reused.
+    assert_eq!(
+      minify(r#"
+        .a { animation-name: spin }
+        @keyframes spin-1 { to { opacity: 1 } }
+        @keyframes spin { to { opacity: 0 } }
+        @media print { .b { animation-name: spin } }
+        @keyframes spin { to { color: red } }
+      "#, DuplicateKeyframes::Rename),
+      ".a{animation-name:spin-2}@keyframes spin-1{to{opacity:1}}@keyframes spin-2{to{opacity:0}}@media print{.b{animation-name:spin-2}}@keyframes spin{to{color:red}}"
+    );
+
+    // Rules with different vendor prefixes are not duplicates.
+    assert_eq!(
+      minify(r#"
+        @-webkit-keyframes fade { from { opacity: 0 } }
+        @keyframes fade { from { opacity: 0 } }
+      "#, DuplicateKeyframes::RemoveEarlier),
+      "@-webkit-keyframes fade{0%{opacity:0}}@keyframes fade{0%{opacity:0}}"
+    );
+  }
+
+  #[test]
+  fn test_important() {
+    test(r#"
+      .foo {
+        align-items: center;
+        justify-items: center !important;
+      }
+    "#, indoc! {r#"
+      .foo {
+        align-items: center;
+        justify-items: center !important;
+      }
+    "#});
+
+    test(r#"
+      .foo {
+        justify-items: center !important;
+        align-items: center;
+      }
+    "#, indoc! {r#"
+      .foo {
+        align-items: center;
+        justify-items: center !important;
+      }
+    "#});
+
+    minify_test(r#"
+      .foo {
+        font-family: SFMono-Regular, Menlo, Monaco, Consolas, "Liberation Mono", "Courier New", monospace !important;
+      }
+    "#, ".foo{font-family:SFMono-Regular,Menlo,Monaco,Consolas,Liberation Mono,Courier New,monospace!important}");
+
+    minify_test(r#"
+      .foo {
+        outline-width: 2px;
+        outline-style: solid;
+        outline-color: blue !important;
+      }
+    "#, ".foo{outline-style:solid;outline-width:2px;outline-color:#00f!important}");
+
+    minify_test(r#"
+      .foo {
+        border-radius: 10px;
+        border-top-left-radius: 5px !important;
+      }
+    "#, ".foo{border-radius:10px;border-top-left-radius:5px!important}");
+
+    minify_test(r#"
+      .foo {
+        margin: 10px !important;
+        margin-top: 20px;
+      }
+    "#, ".foo{margin-top:20px;margin:10px!important}");
+
+    minify_test(r#"
+      @keyframes test {
+        from {
+          opacity: 0 !important;
+          color: red;
+        }
+        to {
+          opacity: 1;
+        }
+      }
+    "#, "@keyframes test{0%{color:red}to{opacity:1}}");
+  }
+
+  #[test]
+  fn test_calc() {
+    minify_test(".foo { width: calc(20px * 2) }", ".foo{width:40px}");
+    minify_test(".foo { font-size: calc(100vw / 35) }", ".foo{font-size:2.85714vw}");
+    minify_test(".foo { width: calc(20px * 2 * 3) }", ".foo{width:90pt}");
+    minify_test(".foo { width: calc(20px + 30px) }", ".foo{width:50px}");
+    minify_test(".foo { width: calc(20px + 30px + 40px) }", ".foo{width:90px}");
+    minify_test(".foo { width: calc(100% - 30px) }", ".foo{width:calc(100% - 30px)}");
+    minify_test(".foo { width: calc(100% - 30px + 20px) }", ".foo{width:calc(100% - 10px)}");
+    minify_test(".foo { width: calc(20px + 100% - 30px) }", ".foo{width:calc(100% - 10px)}");
+    minify_test(".foo { width: calc(20px + 100% + 10vw - 30px) }", ".foo{width:calc(100% - 10px + 10vw)}");
+    minify_test(".foo { width: calc(20px + 100% - 30px) }", ".foo{width:calc(100% - 10px)}");
+    minify_test(".foo { width: calc(2 * (100% - 20px)) }", ".foo{width:calc(200% - 40px)}");
+    minify_test(".foo { width: calc((100% - 20px) * 2) }", ".foo{width:calc(200% - 40px)}");
+    minify_test(".foo { width: calc(100% - 20px * 2) }", ".foo{width:calc(100% - 40px)}");
+    minify_test(".foo { width: calc(1px + 1px) }", ".foo{width:2px}");
+    minify_test(".foo { width: calc(100vw / 2) }", ".foo{width:50vw}");
+    minify_test(".foo { width: calc(50px - (20px - 30px)) }", ".foo{width:60px}");
+    minify_test(".foo { width: calc(100px - (100px - 100%)) }", ".foo{width:100%}");
+    minify_test(".foo { width: calc(100px + (100px - 100%)) }", ".foo{width:calc(200px - 100%)}");
+    minify_test(".foo { width: calc(1px - (2em + 3%)) }", ".foo{width:calc(1px + -2em - 3%)}"); // TODO: fix sign
+    minify_test(".foo { width: calc((100vw - 50em) / 2) }", ".foo{width:calc(50vw - 25em)}");
+    minify_test(".foo { width: calc(1px - (2em + 4vh + 3%)) }", ".foo{width:calc(1px + -2em - 4vh - 3%)}"); // TODO
+    minify_test(".foo { width: calc(1px + (2em + (3vh + 4px))) }", ".foo{width:calc(2em + 3vh + 5px)}");
+    minify_test(".foo { width: calc(1px - (2em + 4px - 6vh) / 2) }", ".foo{width:calc(-1em - 1px + 3vh)}");
+    minify_test(".foo { width: calc(100% - calc(50% + 25px)) }", ".foo{width:calc(50% - 25px)}");
+    minify_test(".foo { width: calc(1px/100) }", ".foo{width:.01px}");
+    minify_test(".foo { width: calc(100vw / 2 - 6px + 0px) }", ".foo{width:calc(50vw - 6px)}");
+    minify_test(".foo { width: calc(1px + 1) }", ".foo{width:calc(1px + 1)}");
+    minify_test(".foo { width: calc( (1em - calc( 10px + 1em)) / 2) }", ".foo{width:-5px}");
+    minify_test(".foo { width: calc((100px - 1em) + (-50px + 1em)) }", ".foo{width:50px}");
+    minify_test(".foo { width: calc(100% + (2 * 100px) - ((75.37% - 63.5px) - 900px)) }", ".foo{width:calc(24.63% + 1163.5px)}");
+    minify_test(".foo { width: calc(((((100% + (2 * 30px) + 63.5px) / 0.7537) - (100vw - 60px)) / 2) + 30px) }", ".foo{width:calc(66.3394% + 141.929px - 50vw)}");
+    minify_test(".foo { width: calc(((75.37% - 63.5px) - 900px) + (2 * 100px)) }", ".foo{width:calc(75.37% - 763.5px)}");
+    minify_test(".foo { width: calc((900px - (10% - 63.5px)) + (2 * 100px)) }", ".foo{width:calc(1163.5px - 10%)}");
+    minify_test(".foo { width: calc(500px/0) }", ".foo{width:calc(500px/0)}");
+    minify_test(".foo { width: calc(500px/2px) }", ".foo{width:calc(500px/2px)}");
+    minify_test(".foo { width: calc(100% / 3 * 3) }", ".foo{width:100%}");
+    minify_test(".foo { width: calc(+100px + +100px) }", ".foo{width:200px}");
+    minify_test(".foo { width: calc(+100px - +100px) }", ".foo{width:0}");
+    minify_test(".foo { width: calc(200px * +1) }", ".foo{width:200px}");
+    minify_test(".foo { width: calc(200px / +1) }", ".foo{width:200px}");
+    minify_test(".foo { width: calc(1.1e+1px + 1.1e+1px) }", ".foo{width:22px}");
+    minify_test(".foo { border-width: calc(1px + 2px) }", ".foo{border-width:3px}");
+    minify_test(".foo { border-width: calc(1em + 2px + 2em + 3px) }", ".foo{border-width:calc(3em + 5px)}");
+    
+    minify_test(".foo { border-width: min(1em, 2px) }", ".foo{border-width:min(1em,2px)}");
+    minify_test(".foo { border-width: min(1em + 2em, 2px + 2px) }", ".foo{border-width:min(3em,4px)}");
+    minify_test(".foo { border-width: min(1em + 2px, 2px + 1em) }", ".foo{border-width:min(1em + 2px,2px + 1em)}");
+    minify_test(".foo { border-width: min(1em + 2px + 2px, 2px + 1em + 1px) }", ".foo{border-width:min(1em + 4px,3px + 1em)}");
+    minify_test(".foo { border-width: min(2px + 1px, 3px + 4px) }", ".foo{border-width:3px}");
+    minify_test(".foo { border-width: min(1px, 1em, 2px, 3in) }", ".foo{border-width:min(1px,1em)}");
+
+    minify_test(".foo { border-width: max(1em, 2px) }", ".foo{border-width:max(1em,2px)}");
+    minify_test(".foo { border-width: max(1em + 2em, 2px + 2px) }", ".foo{border-width:max(3em,4px)}");
+    minify_test(".foo { border-width: max(1em + 2px, 2px + 1em) }", ".foo{border-width:max(1em + 2px,2px + 1em)}");
+    minify_test(".foo { border-width: max(1em + 2px + 2px, 2px + 1em + 1px) }", ".foo{border-width:max(1em + 4px,3px + 1em)}");
+    minify_test(".foo { border-width: max(2px + 1px, 3px + 4px) }", ".foo{border-width:7px}");
+    minify_test(".foo { border-width: max(1px, 1em, 2px, 3in) }", ".foo{border-width:max(3in,1em)}");
+
+    minify_test(".foo { border-width: clamp(1px, 2px, 3px) }", ".foo{border-width:2px}");
+    minify_test(".foo { border-width: clamp(1px, 10px, 3px) }", ".foo{border-width:3px}");
+    minify_test(".foo { border-width: clamp(5px, 2px, 10px) }", ".foo{border-width:5px}");
+    minify_test(".foo { border-width: clamp(100px, 2px, 10px) }", ".foo{border-width:75pt}");
+    minify_test(".foo { border-width: clamp(5px + 5px, 5px + 7px, 10px + 20px) }", ".foo{border-width:9pt}");
+
+    minify_test(".foo { border-width: clamp(1em, 2px, 4vh) }", ".foo{border-width:clamp(1em,2px,4vh)}");
+    minify_test(".foo { border-width: clamp(1em, 2em, 4vh) }", ".foo{border-width:min(2em,4vh)}");
+    minify_test(".foo { border-width: clamp(1em, 2vh, 4vh) }", ".foo{border-width:max(1em,2vh)}");
+    minify_test(".foo { border-width: clamp(1px, 1px + 2em, 4px) }", ".foo{border-width:clamp(1px,1px + 2em,4px)}");
+    minify_test(".foo { border-width: clamp(1px, 2pt, 1in) }", ".foo{border-width:2pt}");
+
+    minify_test(".foo { width: calc(min(1em, 2vh)) }", ".foo{width:min(1em,2vh)}");
+    minify_test(".foo { width: calc(2 * min(1em, 2vh)) }", ".foo{width:min(2em,4vh)}");
+    minify_test(".foo { width: calc(-1 * min(1em, 2vh)) }", ".foo{width:max(-1em,-2vh)}");
+    minify_test(".foo { width: calc(max(1em, 2vh) / -2) }", ".foo{width:min(-.5em,-1vh)}");
+    minify_test(".foo { width: calc(2 * clamp(1em, 2vh, 4vw)) }", ".foo{width:clamp(2em,4vh,8vw)}");
+    minify_test(".foo { width: calc(-1 * clamp(1em, 2vh, 4vw)) }", ".foo{width:min(-1em,max(-2vh,-4vw))}");
+
+    prefix_test(r#"
+      .foo {
+        width: clamp(1em, 2vh, 4vw);
+      }
+    "#, indoc! {r#"
+      .foo {
+        width: max(1em, min(2vh, 4vw));
+      }
+    "#
+    }, Browsers {
+      safari: Some(12 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .foo {
+        width: clamp(1em, 2vh, 4vw);
+      }
+    "#, indoc! {r#"
+      .foo {
+        width: clamp(1em, 2vh, 4vw);
+      }
+    "#
+    }, Browsers {
+      safari: Some(14 << 16),
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    });
+  }
+
+  #[test]
+  fn test_env() {
+    minify_test(".foo { padding-top: env(safe-area-inset-top) }", ".foo{padding-top:env(safe-area-inset-top)}");
+    minify_test(".foo { padding-top: env(safe-area-inset-top, 12px) }", ".foo{padding-top:env(safe-area-inset-top,12px)}");
+    minify_test(".foo { padding-top: env( safe-area-inset-top , 12px ) }", ".foo{padding-top:env(safe-area-inset-top,12px)}");
+    minify_test(".foo { padding-top: env(safe-area-inset-top) !important }", ".foo{padding-top:env(safe-area-inset-top)!important}");
+    minify_test(".foo { padding-top: constant(safe-area-inset-top) }", ".foo{padding-top:constant(safe-area-inset-top)}");
+    minify_test(".foo { padding-top: calc(env(safe-area-inset-top) + 10px) }", ".foo{padding-top:calc(env(safe-area-inset-top) + 10px)}");
+    minify_test(".foo { margin: 0 env(safe-area-inset-right, 1em) 0 env(safe-area-inset-left, 1em) }", ".foo{margin:0 env(safe-area-inset-right,1em) 0 env(safe-area-inset-left,1em)}");
+    minify_test(".foo { padding: 0 env(safe-area-inset-right) }", ".foo{padding:0 env(safe-area-inset-right)}");
+    minify_test(".foo { border: 1px solid env(my-color) }", ".foo{border:1px solid env(my-color)}");
+    minify_test(".foo { --inset: env(safe-area-inset-top) }", ".foo{--inset:env(safe-area-inset-top)}");
+
+    prefix_test(r#"
+      .foo {
+        padding-top: env(safe-area-inset-top, 12px);
+      }
+    "#, indoc! {r#"
+      .foo {
+        padding-top: constant(safe-area-inset-top, 12px);
+        padding-top: env(safe-area-inset-top, 12px);
+      }
+    "#
+    }, Browsers {
+      ios_saf: Some(11 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .foo {
+        padding-top: constant(safe-area-inset-top, 12px);
+        padding-top: env(safe-area-inset-top, 12px);
+      }
+    "#, indoc! {r#"
+      .foo {
+        padding-top: constant(safe-area-inset-top, 12px);
+        padding-top: env(safe-area-inset-top, 12px);
+      }
+    "#
+    }, Browsers {
+      ios_saf: Some(11 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .foo {
+        padding-top: constant(safe-area-inset-top, 12px);
+        padding-top: env(safe-area-inset-top, 12px);
+      }
+    "#, indoc! {r#"
+      .foo {
+        padding-top: env(safe-area-inset-top, 12px);
+      }
+    "#
+    }, Browsers {
+      ios_saf: Some(13 << 16),
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .foo {
+        padding-top: env(safe-area-inset-top, 12px);
+      }
+    "#, indoc! {r#"
+      .foo {
+        padding-top: env(safe-area-inset-top, 12px);
+      }
+    "#
+    }, Browsers {
+      chrome: Some(60 << 16),
+      ..Browsers::default()
+    });
+  }
+
+  #[test]
+  fn test_unused_custom_properties() {
+    use std::collections::HashSet;
+
+    let minify = |code: &str, used: &[&str]| compile(code, ParserOptions::default(), MinifyOptions {
+      remove_unused_custom_properties: true,
+      used_custom_properties: used.iter().map(|name| name.to_string()).collect::<HashSet<String>>(),
+      ..MinifyOptions::default()
+    }, PrinterOptions {
+      minify: true,
+      ..PrinterOptions::default()
+    });
+
+    assert_eq!(minify(".foo { --a: red; --b: blue; color: var(--a) }", &[]), ".foo{--a:red;color:var(--a)}");
+    assert_eq!(minify(":root { --a: red } .foo { color: var(--a) }", &[]), ":root{--a:red}.foo{color:var(--a)}");
+    assert_eq!(minify(":root { --unused: 1px } .foo { color: red }", &[]), ".foo{color:red}");
+    assert_eq!(minify("@media print { :root { --unused: 1px } } .foo { color: red }", &[]), ".foo{color:red}");
+    assert_eq!(minify(".foo { --a: 1px; --b: 2px; width: var(--x, var(--a)) }", &[]), ".foo{--a:1px;width:var(--x, var(--a))}");
+    assert_eq!(minify(".foo { --a: 1px; width: calc(var(--a) * 2) }", &[]), ".foo{--a:1px;width:calc(var(--a) * 2)}");
+    assert_eq!(minify(".foo { --a: 1px; padding-top: env(safe-area-inset-top, var(--a)) }", &[]), ".foo{--a:1px;padding-top:env(safe-area-inset-top,var(--a))}");
+    assert_eq!(minify("@keyframes foo { from { --a: 1px; width: var(--a) } }", &[]), "@keyframes foo{0%{--a:1px;width:var(--a)}}");
+    assert_eq!(minify(".foo {}", &[]), ".foo{}");
+
+    // References from custom properties only count if those are used.
+    assert_eq!(
+      minify(".foo { --a: var(--b); --b: red; --c: var(--d); --d: blue; color: var(--a) }", &[]),
+      ".foo{--a:var(--b);--b:red;color:var(--a)}"
+    );
+    assert_eq!(minify(".foo { --a: var(--b); --b: var(--a) }", &[]), "");
+
+    // Custom properties used outside the stylesheet are kept.
+    assert_eq!(minify(":root { --a: var(--b); --b: red; --c: blue }", &["--a"]), ":root{--a:var(--b);--b:red}");
+
+    // The option must be enabled explicitly.
+    minify_test(":root { --unused: 1px }", ":root{--unused:1px}");
+  }
+
+  #[test]
+  fn test_unused_symbols() {
+    let minify = |code: &str, unused: &[&str]| compile(code, ParserOptions::default(), MinifyOptions {
+      unused_symbols: unused.iter().map(|name| name.to_string()).collect(),
+      ..MinifyOptions::default()
+    }, PrinterOptions {
+      minify: true,
+      ..PrinterOptions::default()
+    });
+
+    assert_eq!(minify(".foo { color: red } .bar { color: green }", &["foo"]), ".bar{color:green}");
+    assert_eq!(minify(".foo, .bar { color: red }", &["foo"]), ".bar{color:red}");
+    assert_eq!(minify(".bar .foo:hover, #main > a { color: red }", &["foo", "main"]), "");
+    assert_eq!(minify(".bar:not(.foo) { color: red }", &["foo"]), ".bar:not(.foo){color:red}");
+    assert_eq!(minify("[class~=foo] { color: red }", &["foo"]), "[class~=foo]{color:red}");
+    assert_eq!(minify("@media print { .foo { color: red } } .bar { color: green }", &["foo"]), ".bar{color:green}");
+    assert_eq!(minify(".bar { color: red; & .foo { color: green } }", &["foo"]), ".bar{color:red}");
+
+    // Keyframes and font faces are removed once the rules referencing them are.
+    assert_eq!(
+      minify("@keyframes fade { from { opacity: 0 } } .foo { animation: fade 1s } .bar { color: red }", &["foo"]),
+      ".bar{color:red}"
+    );
+    assert_eq!(
+      minify("@keyframes fade { from { opacity: 0 } } .foo { animation: fade 1s } .bar { animation-name: fade }", &["foo"]),
+      "@keyframes fade{0%{opacity:0}}.bar{animation-name:fade}"
+    );
+    assert_eq!(
+      minify("@font-face { font-family: Test; src: url(test.woff) } .foo { font-family: Test, serif }", &["foo"]),
+      ""
+    );
+
+    // Those that are not referenced at all may be used outside the stylesheet, unless listed.
+    assert_eq!(minify("@keyframes fade { from { opacity: 0 } }", &["foo"]), "@keyframes fade{0%{opacity:0}}");
+    assert_eq!(minify("@keyframes fade { from { opacity: 0 } }", &["fade"]), "");
+  }
+
+  #[test]
+  fn test_box_shadow() {
+    minify_test(".foo { box-shadow: 64px 64px 12px 40px rgba(0,0,0,0.4) }", ".foo{box-shadow:4pc 4pc 9pt 40px #0006}");
+    minify_test(".foo { box-shadow: 12px 12px 0px 8px rgba(0,0,0,0.4) inset }", ".foo{box-shadow:inset 9pt 9pt 0 8px #0006}");
+    minify_test(".foo { box-shadow: inset 12px 12px 0px 8px rgba(0,0,0,0.4) }", ".foo{box-shadow:inset 9pt 9pt 0 8px #0006}");
+    minify_test(".foo { box-shadow: 12px 12px 8px 0px rgba(0,0,0,0.4) }", ".foo{box-shadow:9pt 9pt 8px #0006}");
+    minify_test(".foo { box-shadow: 12px 12px 0px 0px rgba(0,0,0,0.4) }", ".foo{box-shadow:9pt 9pt #0006}");
+    minify_test(".foo { box-shadow: 64px 64px 12px 40px rgba(0,0,0,0.4), 12px 12px 0px 8px rgba(0,0,0,0.4) inset }", ".foo{box-shadow:4pc 4pc 9pt 40px #0006,inset 9pt 9pt 0 8px #0006}");
+
+    // Duplicates are only removed if they are hidden beneath the earlier shadow.
+    minify_test(".foo { box-shadow: 2px 2px red, 4px 4px blue, 2px 2px red }", ".foo{box-shadow:2px 2px red,4px 4px #00f}");
+    minify_test(".foo { box-shadow: 2px 2px 4px red, 2px 2px 4px red }", ".foo{box-shadow:2px 2px 4px red,2px 2px 4px red}");
+    minify_test(".foo { box-shadow: 2px 2px #f008, 2px 2px #f008 }", ".foo{box-shadow:2px 2px #f008,2px 2px #f008}");
+    minify_test(".foo { box-shadow: 2px 2px, 2px 2px }", ".foo{box-shadow:2px 2px,2px 2px}");
+    minify_test(".foo { box-shadow: 2px 2px red, inset 2px 2px red }", ".foo{box-shadow:2px 2px red,inset 2px 2px red}");
+
+    minify_test(".foo { text-shadow: 1px 1px 2px rgba(0,0,0,0.4) }", ".foo{text-shadow:1px 1px 2px #0006}");
+    minify_test(".foo { text-shadow: red 1px 1px 0px }", ".foo{text-shadow:1px 1px red}");
+    minify_test(".foo { text-shadow: 1px 1px red, 2px 2px blue, 1px 1px red }", ".foo{text-shadow:1px 1px red,2px 2px #00f}");
+    minify_test(".foo { text-shadow: 1px 1px 2px red, 1px 1px 2px red }", ".foo{text-shadow:1px 1px 2px red,1px 1px 2px red}");
+  }
+
+  #[test]
+  fn test_color_spaces() {
+    minify_test(".foo { color: lab(29.2345% 39.3825 20.0664) }", ".foo{color:lab(29.2345% 39.3825 20.0664)}");
+    minify_test(".foo { color: lch(52.2345% 72.2 56.2deg / 50%) }", ".foo{color:lch(52.2345% 72.2 56.2/.5)}");
+    minify_test(".foo { color: oklab(0.5 0.1 -0.1) }", ".foo{color:oklab(50% .1 -.1)}");
+    minify_test(".foo { color: oklch(40.1% 0.123 21.57 / 1) }", ".foo{color:oklch(40.1% .123 21.57)}");
+    minify_test(".foo { color: color(display-p3 1 0.5 0) }", ".foo{color:color(display-p3 1 .5 0)}");
+    minify_test(".foo { color: color(xyz-d65 0.2 0.3 0.4) }", ".foo{color:color(xyz .2 .3 .4)}");
+    minify_test(".foo { color: color(foo 1 0.5 0) }", ".foo{color:color(foo 1 0.5 0)}");
+
+    let safari = Browsers {
+      safari: Some(14 << 16),
+      ..Browsers::default()
+    };
+
+    prefix_test(r#"
+      .foo {
+        color: lab(29.2345% 39.3825 20.0664);
+        background-color: lch(52.2345% 72.2 56.2 / .5);
+        border: 1px solid oklch(40.1% 0.123 21.57);
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: #7d2329;
+        color: lab(29.2345% 39.3825 20.0664);
+        background-color: #c65d0680;
+        background-color: lch(52.2345% 72.2 56.2 / .5);
+        border: 1px solid #7d2429;
+        border: 1px solid oklch(40.1% .123 21.57);
+      }
+    "#}, safari);
+
+    // Safari 14 supports Display P3 colors, but Chrome 90 does not. Colors outside of the
+    // sRGB gamut are clipped.
+    prefix_test(r#"
+      .foo {
+        color: color(display-p3 .5 .25 .75);
+        background-color: color(display-p3 1 0 0);
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: color(display-p3 .5 .25 .75);
+        background-color: color(display-p3 1 0 0);
+      }
+    "#}, safari);
+
+    prefix_test(r#"
+      .foo {
+        color: color(display-p3 .5 .25 .75);
+        background-color: color(display-p3 1 0 0);
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: #893bc6;
+        color: color(display-p3 .5 .25 .75);
+        background-color: red;
+        background-color: color(display-p3 1 0 0);
+      }
+    "#}, Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    });
+
+    // A fallback written by the author is overridden, so it is replaced by the computed one.
+    prefix_test(r#"
+      .foo {
+        color: red;
+        color: lab(29.2345% 39.3825 20.0664);
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: #7d2329;
+        color: lab(29.2345% 39.3825 20.0664);
+      }
+    "#}, safari);
+
+    prefix_test(r#"
+      .foo {
+        color: lab(29.2345% 39.3825 20.0664);
+        color: red;
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: red;
+      }
+    "#}, safari);
+
+    prefix_test(r#"
+      .foo {
+        color: lab(29.2345% 39.3825 20.0664);
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: lab(29.2345% 39.3825 20.0664);
+      }
+    "#}, Browsers {
+      chrome: Some(120 << 16),
+      safari: Some(16 << 16),
+      ..Browsers::default()
+    });
+  }
+
+  #[test]
+  fn test_color_fallbacks() {
+    prefix_test(r#"
+      .foo {
+        color: #ff000080;
+        background-color: rgba(0, 0, 0, .4);
+        border-color: transparent;
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: rgba(255, 0, 0, .5);
+        background-color: rgba(0, 0, 0, .4);
+        border-color: transparent;
+      }
+    "#}, Browsers {
+      ie: Some(11 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .foo {
+        color: #ff000080;
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: #ff000080;
+      }
+    "#}, Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    });
+
+    let compile_opaque = |source: &str, opaque_color_fallbacks: bool| {
+      let targets = Some(Browsers {
+        ie: Some(8 << 16),
+        ..Browsers::default()
+      });
+      compile(source, ParserOptions::default(), MinifyOptions {
+        targets,
+        opaque_color_fallbacks,
+        ..MinifyOptions::default()
+      }, PrinterOptions {
+        minify: true,
+        targets,
+        ..PrinterOptions::default()
+      })
+    };
+
+    let source = ".foo { color: rgba(255, 0, 0, .5); background-color: lab(29.2345% 39.3825 20.0664 / .5) }";
+    assert_eq!(
+      compile_opaque(source, true),
+      ".foo{color:red;color:rgba(255,0,0,.5);background-color:#7d2329;background-color:rgba(125,35,41,.5);background-color:lab(29.2345% 39.3825 20.0664/.5)}"
+    );
+    assert_eq!(
+      compile_opaque(source, false),
+      ".foo{color:rgba(255,0,0,.5);background-color:rgba(125,35,41,.5);background-color:lab(29.2345% 39.3825 20.0664/.5)}"
+    );
+    assert_eq!(compile_opaque(".foo { color: transparent; background-color: red }", true), ".foo{color:transparent;background-color:red}");
+  }
+
+  #[test]
+  fn test_media() {
+    minify_test("@media (min-width: 240px) { .foo { color: chartreuse }}", "@media (min-width:240px){.foo{color:#7fff00}}")
+  }
+
+  #[test]
+  fn test_merge_rules() {
+    test(r#"
+      .foo {
+        color: red;
+      }
+      .bar {
+        color: red;
+      }
+    "#, indoc! {r#"
+      .foo, .bar {
+        color: red;
+      }
+    "#});
+    test(r#"
+      .foo {
+        color: red;
+      }
+      .foo {
+        background: green;
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: red;
+        background: green;
+      }
+    "#});
+    test(r#"
+      .foo {
+        background: red;
+      }
+      .foo {
+        background: green;
+      }
+    "#, indoc! {r#"
+      .foo {
+        background: green;
+      }
+    "#});
+    test(r#"
+      .foo {
+        color: red;
+      }
+
+      .bar {
+        background: green;
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: red;
+      }
+
+      .bar {
+        background: green;
+      }
+    "#});
+    test(r#"
+      .foo {
+        color: red;
+      }
+
+      .baz {
+        color: blue;
+      }
+
+      .bar {
+        color: red;
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: red;
+      }
+
+      .baz {
+        color: #00f;
+      }
+      
+      .bar {
+        color: red;
+      }
+    "#});
+  }
+
+  #[test]
+  fn test_opacity() {
+    minify_test(".foo { opacity: 0 }", ".foo{opacity:0}");
+    minify_test(".foo { opacity: 0% }", ".foo{opacity:0}");
+    minify_test(".foo { opacity: 0.5 }", ".foo{opacity:.5}");
+    minify_test(".foo { opacity: 50% }", ".foo{opacity:.5}");
+    minify_test(".foo { opacity: 1 }", ".foo{opacity:1}");
+    minify_test(".foo { opacity: 100% }", ".foo{opacity:1}");
+    minify_test(".foo { opacity: 1.5 }", ".foo{opacity:1}");
+    minify_test(".foo { opacity: 150% }", ".foo{opacity:1}");
+    minify_test(".foo { opacity: -0.5 }", ".foo{opacity:0}");
+  }
+
+  #[test]
+  fn test_aspect_ratio() {
+    minify_test(".foo { aspect-ratio: auto }", ".foo{aspect-ratio:auto}");
+    minify_test(".foo { aspect-ratio: 16 / 9 }", ".foo{aspect-ratio:16/9}");
+    minify_test(".foo { aspect-ratio: 1 / 1 }", ".foo{aspect-ratio:1}");
+    minify_test(".foo { aspect-ratio: 2 }", ".foo{aspect-ratio:2}");
+    minify_test(".foo { aspect-ratio: 32 / 18 }", ".foo{aspect-ratio:16/9}");
+    minify_test(".foo { aspect-ratio: 1.5 / 3 }", ".foo{aspect-ratio:1.5/3}");
+    minify_test(".foo { aspect-ratio: auto 4 / 3 }", ".foo{aspect-ratio:auto 4/3}");
+    minify_test(".foo { aspect-ratio: 4 / 3 auto }", ".foo{aspect-ratio:auto 4/3}");
+    minify_test(".foo { aspect-ratio: -1 / 2 }", ".foo{aspect-ratio:-1 / 2}");
+    test(".foo { aspect-ratio: 32 / 18 }", indoc! {r#"
+      .foo {
+        aspect-ratio: 32 / 18;
+      }
+    "#});
+  }
+
+  #[test]
+  fn test_transitions() {
+    minify_test(".foo { transition-duration: 500ms }", ".foo{transition-duration:.5s}");
+    minify_test(".foo { transition-duration: .5s }", ".foo{transition-duration:.5s}");
+    minify_test(".foo { transition-duration: 99ms }", ".foo{transition-duration:99ms}");
+    minify_test(".foo { transition-duration: .099s }", ".foo{transition-duration:99ms}");
+    minify_test(".foo { transition-duration: 2000ms }", ".foo{transition-duration:2s}");
+    minify_test(".foo { transition-duration: 2s }", ".foo{transition-duration:2s}");
+    minify_test(".foo { transition-duration: calc(1s - 50ms) }", ".foo{transition-duration:.95s}");
+    minify_test(".foo { transition-duration: calc(1s - 50ms + 2s) }", ".foo{transition-duration:2.95s}");
+    minify_test(".foo { transition-duration: calc((1s - 50ms) * 2) }", ".foo{transition-duration:1.9s}");
+    minify_test(".foo { transition-duration: calc(2 * (1s - 50ms)) }", ".foo{transition-duration:1.9s}");
+    minify_test(".foo { transition-duration: calc((2s + 50ms) - (1s - 50ms)) }", ".foo{transition-duration:1.1s}");
+    minify_test(".foo { transition-duration: calc(50ms + 1s) }", ".foo{transition-duration:1.05s}");
+    minify_test(".foo { transition-duration: 500ms, 50ms }", ".foo{transition-duration:.5s,50ms}");
+    minify_test(".foo { transition-delay: 500ms }", ".foo{transition-delay:.5s}");
+    minify_test(".foo { transition-property: background }", ".foo{transition-property:background}");
+    minify_test(".foo { transition-property: background, opacity }", ".foo{transition-property:background,opacity}");
+    minify_test(".foo { transition-timing-function: linear }", ".foo{transition-timing-function:linear}");
+    minify_test(".foo { transition-timing-function: ease }", ".foo{transition-timing-function:ease}");
+    minify_test(".foo { transition-timing-function: ease-in }", ".foo{transition-timing-function:ease-in}");
+    minify_test(".foo { transition-timing-function: ease-out }", ".foo{transition-timing-function:ease-out}");
+    minify_test(".foo { transition-timing-function: ease-in-out }", ".foo{transition-timing-function:ease-in-out}");
+    minify_test(".foo { transition-timing-function: cubic-bezier(0.25, 0.1, 0.25, 1) }", ".foo{transition-timing-function:ease}");
+    minify_test(".foo { transition-timing-function: cubic-bezier(0.42, 0, 1, 1) }", ".foo{transition-timing-function:ease-in}");
+    minify_test(".foo { transition-timing-function: cubic-bezier(0, 0, 0.58, 1) }", ".foo{transition-timing-function:ease-out}");
+    minify_test(".foo { transition-timing-function: cubic-bezier(0.42, 0, 0.58, 1) }", ".foo{transition-timing-function:ease-in-out}");
+    minify_test(".foo { transition-timing-function: cubic-bezier(0.58, 0.2, 0.11, 1.2) }", ".foo{transition-timing-function:cubic-bezier(.58,.2,.11,1.2)}");
+    minify_test(".foo { transition-timing-function: step-start }", ".foo{transition-timing-function:step-start}");
+    minify_test(".foo { transition-timing-function: step-end }", ".foo{transition-timing-function:step-end}");
+    minify_test(".foo { transition-timing-function: steps(1, start) }", ".foo{transition-timing-function:step-start}");
+    minify_test(".foo { transition-timing-function: steps(1, jump-start) }", ".foo{transition-timing-function:step-start}");
+    minify_test(".foo { transition-timing-function: steps(1, end) }", ".foo{transition-timing-function:step-end}");
+    minify_test(".foo { transition-timing-function: steps(1, jump-end) }", ".foo{transition-timing-function:step-end}");
+    minify_test(".foo { transition-timing-function: steps(5, jump-start) }", ".foo{transition-timing-function:steps(5,start)}");
+    minify_test(".foo { transition-timing-function: steps(5, jump-end) }", ".foo{transition-timing-function:steps(5,end)}");
+    minify_test(".foo { transition-timing-function: steps(5, jump-both) }", ".foo{transition-timing-function:steps(5,jump-both)}");
+    minify_test(".foo { transition-timing-function: ease-in-out, cubic-bezier(0.42, 0, 1, 1) }", ".foo{transition-timing-function:ease-in-out,ease-in}");
+    minify_test(".foo { transition-timing-function: cubic-bezier(0.42, 0, 1, 1), cubic-bezier(0.58, 0.2, 0.11, 1.2) }", ".foo{transition-timing-function:ease-in,cubic-bezier(.58,.2,.11,1.2)}");
+    minify_test(".foo { transition-timing-function: step-start, steps(5, jump-start) }", ".foo{transition-timing-function:step-start,steps(5,start)}");
+    minify_test(".foo { transition: width 2s ease }", ".foo{transition:width 2s}");
+    minify_test(".foo { transition: width 2s ease, height 1000ms cubic-bezier(0.25, 0.1, 0.25, 1) }", ".foo{transition:width 2s,height 1s}");
+    minify_test(".foo { transition: width 2s 1s }", ".foo{transition:width 2s 1s}");
+    minify_test(".foo { transition: width 2s ease 1s }", ".foo{transition:width 2s 1s}");
+    minify_test(".foo { transition: ease-in 1s width 4s }", ".foo{transition:width 1s ease-in 4s}");
+    minify_test(".foo { transition: opacity 0s .6s }", ".foo{transition:opacity 0s .6s}");
+    test(r#"
+      .foo {
+        transition-property: opacity;
+        transition-duration: 0.09s;
+        transition-timing-function: ease-in-out;
+        transition-delay: 500ms;
+      }
+    "#, indoc! {r#"
+      .foo {
+        transition: opacity 90ms ease-in-out .5s;
+      }
+    "#});
+    test(r#"
+      .foo {
+        transition: opacity 2s;
+        transition-timing-function: ease;
+        transition-delay: 500ms;
+      }
+    "#, indoc! {r#"
+      .foo {
+        transition: opacity 2s .5s;
+      }
+    "#});
+    test(r#"
+      .foo {
+        transition-property: opacity;
+        transition-duration: 0.09s;
+        transition-timing-function: ease-in-out;
+        transition-delay: 500ms;
+        transition: color 2s;
+      }
+    "#, indoc! {r#"
+      .foo {
+        transition: color 2s;
+      }
+    "#});
+    test(r#"
+      .foo {
+        transition-property: opacity, color;
+        transition-duration: 2s, 4s;
+        transition-timing-function: ease-in-out, ease-in;
+        transition-delay: 500ms, 0s;
+      }
+    "#, indoc! {r#"
+      .foo {
+        transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+      }
+    "#});
+    test(r#"
+      .foo {
+        transition-property: opacity, color;
+        transition-duration: 2s;
+        transition-timing-function: ease-in-out;
+        transition-delay: 500ms;
+      }
+    "#, indoc! {r#"
+      .foo {
+        transition-property: opacity, color;
+        transition-duration: 2s;
+        transition-delay: .5s;
+        transition-timing-function: ease-in-out;
+      }
+    "#});
+
+    test(r#"
+      .foo {
+        -webkit-transition-property: opacity, color;
+        -webkit-transition-duration: 2s, 4s;
+        -webkit-transition-timing-function: ease-in-out, ease-in;
+        -webkit-transition-delay: 500ms, 0s;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+      }
+    "#});
+
+    test(r#"
+      .foo {
+        -webkit-transition-property: opacity, color;
+        -webkit-transition-duration: 2s, 4s;
+        -webkit-transition-timing-function: ease-in-out, ease-in;
+        -webkit-transition-delay: 500ms, 0s;
+        -moz-transition-property: opacity, color;
+        -moz-transition-duration: 2s, 4s;
+        -moz-transition-timing-function: ease-in-out, ease-in;
+        -moz-transition-delay: 500ms, 0s;
+        transition-property: opacity, color;
+        transition-duration: 2s, 4s;
+        transition-timing-function: ease-in-out, ease-in;
+        transition-delay: 500ms, 0s;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+        -moz-transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+        transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+      }
+    "#});
+
+    test(r#"
+      .foo {
+        -webkit-transition-property: opacity, color;
+        -moz-transition-property: opacity, color;
+        transition-property: opacity, color;
+        -webkit-transition-duration: 2s, 4s;
+        -moz-transition-duration: 2s, 4s;
+        transition-duration: 2s, 4s;
+        -webkit-transition-timing-function: ease-in-out, ease-in;
+        transition-timing-function: ease-in-out, ease-in;
+        -moz-transition-timing-function: ease-in-out, ease-in;
+        -webkit-transition-delay: 500ms, 0s;
+        -moz-transition-delay: 500ms, 0s;
+        transition-delay: 500ms, 0s;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+        -moz-transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+        transition: opacity 2s ease-in-out .5s, color 4s ease-in;
+      }
+    "#});
+
+    test(r#"
+      .foo {
+        -webkit-transition-property: opacity;
+        -moz-transition-property: color;
+        transition-property: opacity, color;
+        -webkit-transition-duration: 2s;
+        -moz-transition-duration: 4s;
+        transition-duration: 2s, 4s;
+        -webkit-transition-timing-function: ease-in-out;
+        -moz-transition-timing-function: ease-in-out;
+        transition-timing-function: ease-in-out, ease-in;
+        -webkit-transition-delay: 500ms;
+        -moz-transition-delay: 0s;
+        transition-delay: 500ms, 0s;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-transition-property: opacity;
+        -moz-transition-property: color;
+        transition-property: opacity, color;
+        -webkit-transition-duration: 2s;
+        -moz-transition-duration: 4s;
+        transition-duration: 2s, 4s;
+        -webkit-transition-timing-function: ease-in-out;
+        -moz-transition-timing-function: ease-in-out;
+        -webkit-transition-delay: .5s;
+        transition-timing-function: ease-in-out, ease-in;
+        -moz-transition-delay: 0s;
+        transition-delay: .5s, 0s;
+      }
+    "#});
+
+    test(r#"
+      .foo {
+        -webkit-transition-property: opacity;
+        transition-property: opacity, color;
+        -moz-transition-property: color;
+        -webkit-transition-duration: 2s;
+        transition-duration: 2s, 4s;
+        -moz-transition-duration: 4s;
+        -webkit-transition-timing-function: ease-in-out;
+        transition-timing-function: ease-in-out, ease-in;
+        -moz-transition-timing-function: ease-in-out;
+        -webkit-transition-delay: 500ms;
+        transition-delay: 500ms, 0s;
+        -moz-transition-delay: 0s;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-transition-property: opacity;
+        transition-property: opacity, color;
+        -moz-transition-property: color;
+        -webkit-transition-duration: 2s;
+        transition-duration: 2s, 4s;
+        -moz-transition-duration: 4s;
+        -webkit-transition-timing-function: ease-in-out;
+        transition-timing-function: ease-in-out, ease-in;
+        -webkit-transition-delay: .5s;
+        -moz-transition-timing-function: ease-in-out;
+        transition-delay: .5s, 0s;
+        -moz-transition-delay: 0s;
+      }
+    "#});
+
+    test(r#"
+      .foo {
+        transition: opacity 2s;
+        -webkit-transition-duration: 2s;
+      }
+    "#, indoc! {r#"
+      .foo {
+        transition: opacity 2s;
+        -webkit-transition-duration: 2s;
+      }
+    "#});
+  }
+
+  #[test]
+  fn test_animation() {
+    minify_test(".foo { animation-name: test }", ".foo{animation-name:test}");
+    minify_test(".foo { animation-name: \"test\" }", ".foo{animation-name:test}");
+    minify_test(".foo { animation-name: foo, bar }", ".foo{animation-name:foo,bar}");
+    minify_test(".foo { animation-duration: 100ms }", ".foo{animation-duration:.1s}");
+    minify_test(".foo { animation-duration: 100ms, 2000ms }", ".foo{animation-duration:.1s,2s}");
+    minify_test(".foo { animation-timing-function: ease }", ".foo{animation-timing-function:ease}");
+    minify_test(".foo { animation-timing-function: cubic-bezier(0.42, 0, 1, 1) }", ".foo{animation-timing-function:ease-in}");
+    minify_test(".foo { animation-timing-function: ease, cubic-bezier(0.42, 0, 1, 1) }", ".foo{animation-timing-function:ease,ease-in}");
+    minify_test(".foo { animation-iteration-count: 5 }", ".foo{animation-iteration-count:5}");
+    minify_test(".foo { animation-iteration-count: 2.5 }", ".foo{animation-iteration-count:2.5}");
+    minify_test(".foo { animation-iteration-count: 2.0 }", ".foo{animation-iteration-count:2}");
+    minify_test(".foo { animation-iteration-count: infinite }", ".foo{animation-iteration-count:infinite}");
+    minify_test(".foo { animation-iteration-count: 1, infinite }", ".foo{animation-iteration-count:1,infinite}");
+    minify_test(".foo { animation-direction: reverse }", ".foo{animation-direction:reverse}");
+    minify_test(".foo { animation-direction: alternate, reverse }", ".foo{animation-direction:alternate,reverse}");
+    minify_test(".foo { animation-play-state: paused }", ".foo{animation-play-state:paused}");
+    minify_test(".foo { animation-play-state: running, paused }", ".foo{animation-play-state:running,paused}");
+    minify_test(".foo { animation-delay: 100ms }", ".foo{animation-delay:.1s}");
+    minify_test(".foo { animation-delay: 100ms, 2000ms }", ".foo{animation-delay:.1s,2s}");
+    minify_test(".foo { animation-fill-mode: forwards }", ".foo{animation-fill-mode:forwards}");
+    minify_test(".foo { animation-fill-mode: Backwards,forwards }", ".foo{animation-fill-mode:backwards,forwards}");
+    minify_test(".foo { animation: 3s ease-in 1s infinite reverse both running slidein }", ".foo{animation:slidein 3s ease-in 1s infinite reverse both}");
+    minify_test(".foo { animation: 3s slidein paused ease 1s 1 reverse both }", ".foo{animation:slidein 3s 1s reverse both paused}");
+    minify_test(".foo { animation: 3s ease ease }", ".foo{animation:ease 3s ease}");
+    minify_test(".foo { animation: 3s cubic-bezier(0.25, 0.1, 0.25, 1) foo }", ".foo{animation:foo 3s}");
+    minify_test(".foo { animation: foo 0s 3s infinite }", ".foo{animation:foo 0s 3s infinite}");
+    minify_test(".foo { animation: none }", ".foo{animation:none}");
+    test(r#"
+      .foo {
+        animation-name: foo;
+        animation-duration: 0.09s;
+        animation-timing-function: ease-in-out;
+        animation-iteration-count: 2;
+        animation-direction: alternate;
+        animation-play-state: running;
+        animation-delay: 100ms;
+        animation-fill-mode: forwards;
+      }
+    "#, indoc! {r#"
+      .foo {
+        animation: foo 90ms ease-in-out .1s 2 alternate forwards;
+      }
+    "#});
+    test(r#"
+      .foo {
+        animation-name: foo, bar;
+        animation-duration: 0.09s, 200ms;
+        animation-timing-function: ease-in-out, ease;
+        animation-iteration-count: 2, 1;
+        animation-direction: alternate, normal;
+        animation-play-state: running, paused;
+        animation-delay: 100ms, 0s;
+        animation-fill-mode: forwards, none;
+      }
+    "#, indoc! {r#"
+      .foo {
+        animation: foo 90ms ease-in-out .1s 2 alternate forwards, bar .2s paused;
+      }
+    "#});
+    test(r#"
+      .foo {
+        animation: bar 200ms;
+        animation-timing-function: ease-in-out;
+      }
+    "#, indoc! {r#"
+      .foo {
+        animation: bar .2s ease-in-out;
+      }
+    "#});
+    test(r#"
+      .foo {
+        animation-name: foo, bar;
+        animation-duration: 0.09s;
+        animation-timing-function: ease-in-out;
+        animation-iteration-count: 2;
+        animation-direction: alternate;
+        animation-play-state: running;
+        animation-delay: 100ms;
+        animation-fill-mode: forwards;
+      }
+    "#, indoc! {r#"
+      .foo {
+        animation-name: foo, bar;
+        animation-duration: 90ms;
+        animation-timing-function: ease-in-out;
+        animation-iteration-count: 2;
+        animation-direction: alternate;
+        animation-play-state: running;
+        animation-delay: .1s;
+        animation-fill-mode: forwards;
+      }
+    "#});
+    test(r#"
+      .foo {
+        -webkit-animation-name: foo;
+        -webkit-animation-duration: 0.09s;
+        -webkit-animation-timing-function: ease-in-out;
+        -webkit-animation-iteration-count: 2;
+        -webkit-animation-direction: alternate;
+        -webkit-animation-play-state: running;
+        -webkit-animation-delay: 100ms;
+        -webkit-animation-fill-mode: forwards;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-animation: foo 90ms ease-in-out .1s 2 alternate forwards;
+      }
+    "#});
+    test(r#"
+      .foo {
+        -moz-animation: bar 200ms;
+        -moz-animation-timing-function: ease-in-out;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -moz-animation: bar .2s ease-in-out;
+      }
+    "#});
+    test(r#"
+      .foo {
+        -webkit-animation: bar 200ms;
+        -webkit-animation-timing-function: ease-in-out;
+        -moz-animation: bar 200ms;
+        -moz-animation-timing-function: ease-in-out;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-animation: bar .2s ease-in-out;
+        -moz-animation: bar .2s ease-in-out;
+      }
+    "#});
+
+    prefix_test(r#"
+      .foo {
+        animation: bar .2s ease-in-out;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-animation: bar .2s ease-in-out;
+        -moz-animation: bar .2s ease-in-out;
+        animation: bar .2s ease-in-out;
+      }
+    "#}, Browsers {
+      firefox: Some(6 << 16),
+      safari: Some(6 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .foo {
+        -webkit-animation: bar .2s ease-in-out;
+        -moz-animation: bar .2s ease-in-out;
+        animation: bar .2s ease-in-out;
+      }
+    "#, indoc! {r#"
+      .foo {
+        animation: bar .2s ease-in-out;
+      }
+    "#}, Browsers {
+      firefox: Some(20 << 16),
+      safari: Some(14 << 16),
+      ..Browsers::default()
+    });
+  }
+
+  #[test]
+  fn test_transform() {
+    minify_test(".foo { transform: translate(2px, 3px)", ".foo{transform:translate(2px,3px)}");
+    minify_test(".foo { transform: translate(2px, 0px)", ".foo{transform:translate(2px)}");
+    minify_test(".foo { transform: translate(0px, 2px)", ".foo{transform:translateY(2px)}");
+    minify_test(".foo { transform: translateX(2px)", ".foo{transform:translate(2px)}");
+    minify_test(".foo { transform: translateY(2px)", ".foo{transform:translateY(2px)}");
+    minify_test(".foo { transform: translateZ(2px)", ".foo{transform:translateZ(2px)}");
+    minify_test(".foo { transform: translate3d(2px, 3px, 4px)", ".foo{transform:translate3d(2px,3px,4px)}");
+    minify_test(".foo { transform: translate3d(10%, 20%, 4px)", ".foo{transform:translate3d(10%,20%,4px)}");
+    minify_test(".foo { transform: translate3d(2px, 0px, 0px)", ".foo{transform:translate(2px)}");
+    minify_test(".foo { transform: translate3d(0px, 2px, 0px)", ".foo{transform:translateY(2px)}");
+    minify_test(".foo { transform: translate3d(0px, 0px, 2px)", ".foo{transform:translateZ(2px)}");
+    minify_test(".foo { transform: translate3d(2px, 3px, 0px)", ".foo{transform:translate(2px,3px)}");
+    minify_test(".foo { transform: scale(2, 3)", ".foo{transform:scale(2,3)}");
+    minify_test(".foo { transform: scale(10%, 20%)", ".foo{transform:scale(.1,.2)}");
+    minify_test(".foo { transform: scale(2, 2)", ".foo{transform:scale(2)}");
+    minify_test(".foo { transform: scale(2, 1)", ".foo{transform:scaleX(2)}");
+    minify_test(".foo { transform: scale(1, 2)", ".foo{transform:scaleY(2)}");
+    minify_test(".foo { transform: scaleX(2)", ".foo{transform:scaleX(2)}");
+    minify_test(".foo { transform: scaleY(2)", ".foo{transform:scaleY(2)}");
+    minify_test(".foo { transform: scaleZ(2)", ".foo{transform:scaleZ(2)}");
+    minify_test(".foo { transform: scale3d(2, 3, 4)", ".foo{transform:scale3d(2,3,4)}");
+    minify_test(".foo { transform: scale3d(2, 1, 1)", ".foo{transform:scaleX(2)}");
+    minify_test(".foo { transform: scale3d(1, 2, 1)", ".foo{transform:scaleY(2)}");
+    minify_test(".foo { transform: scale3d(1, 1, 2)", ".foo{transform:scaleZ(2)}");
+    minify_test(".foo { transform: scale3d(2, 2, 1)", ".foo{transform:scale(2)}");
+    minify_test(".foo { transform: rotate(20deg)", ".foo{transform:rotate(20deg)}");
+    minify_test(".foo { transform: rotateX(20deg)", ".foo{transform:rotateX(20deg)}");
+    minify_test(".foo { transform: rotateY(20deg)", ".foo{transform:rotateY(20deg)}");
+    minify_test(".foo { transform: rotateZ(20deg)", ".foo{transform:rotate(20deg)}");
+    minify_test(".foo { transform: rotate(360deg)", ".foo{transform:translate(0)}");
+    minify_test(".foo { transform: rotate3d(2, 3, 4, 20deg)", ".foo{transform:rotate3d(2,3,4,20deg)}");
+    minify_test(".foo { transform: rotate3d(1, 0, 0, 20deg)", ".foo{transform:rotateX(20deg)}");
+    minify_test(".foo { transform: rotate3d(0, 1, 0, 20deg)", ".foo{transform:rotateY(20deg)}");
+    minify_test(".foo { transform: rotate3d(0, 0, 1, 20deg)", ".foo{transform:rotate(20deg)}");
+    minify_test(".foo { transform: skew(20deg)", ".foo{transform:skew(20deg)}");
+    minify_test(".foo { transform: skew(20deg, 0deg)", ".foo{transform:skew(20deg)}");
+    minify_test(".foo { transform: skew(0deg, 20deg)", ".foo{transform:skewY(20deg)}");
+    minify_test(".foo { transform: skewX(20deg)", ".foo{transform:skew(20deg)}");
+    minify_test(".foo { transform: skewY(20deg)", ".foo{transform:skewY(20deg)}");
+    minify_test(".foo { transform: perspective(10px)", ".foo{transform:perspective(10px)}");
+    minify_test(".foo { transform: matrix(1, 2, -1, 1, 80, 80)", ".foo{transform:matrix(1,2,-1,1,80,80)}");
+    minify_test(".foo { transform: matrix3d(1, 0, 0, 0, 0, 1, 6, 0, 0, 0, 1, 0, 50, 100, 0, 1.1)", ".foo{transform:matrix3d(1,0,0,0,0,1,6,0,0,0,1,0,50,100,0,1.1)}");
+    minify_test(
+      ".foo{transform:translate(100px,200px) rotate(45deg) skew(10deg) scale(2)}",
+      ".foo{transform:matrix(1.41421,1.41421,-1.16485,1.66358,100,200)}"
+    );
+    minify_test(
+      ".foo{transform:translate(200px,300px) translate(100px,200px) scale(2)}",
+      ".foo{transform:matrix(2,0,0,2,300,500)}"
+    );
+    minify_test(
+      ".foo{transform:translate(100px,200px) rotate(45deg)}",
+      ".foo{transform:translate(75pt,200px)rotate(45deg)}"
+    );
+    minify_test(
+      ".foo{transform:rotate3d(1, 1, 1, 45deg) translate3d(100px, 100px, 10px)}",
+      ".foo{transform:rotate3d(1,1,1,45deg)translate3d(75pt,75pt,10px)}"
+    );
+    minify_test(
+      ".foo{transform:translate3d(100px, 100px, 10px) skew(10deg) scale3d(2, 3, 4)}",
+      ".foo{transform:translate3d(75pt,75pt,10px)skew(10deg)scale3d(2,3,4)}"
+    );
+    minify_test(
+      ".foo{transform:matrix3d(0.804737854124365, 0.5058793634016805, -0.31061721752604554, 0, -0.31061721752604554, 0.804737854124365, 0.5058793634016805, 0, 0.5058793634016805, -0.31061721752604554, 0.804737854124365, 0, 100, 100, 10, 1)}",
+      ".foo{transform:translate3d(75pt,75pt,10px)rotate3d(1,1,1,45deg)}"
+    );
+    minify_test(
+      ".foo{transform:matrix3d(1, 0, 0, 0, 0, 0.7071067811865476, 0.7071067811865475, 0, 0, -0.7071067811865475, 0.7071067811865476, 0, 100, 100, 10, 1)}",
+      ".foo{transform:translate3d(75pt,75pt,10px)rotateX(45deg)}"
+    );
+    minify_test(
+      ".foo{transform:translate3d(100px, 200px, 10px) translate(100px, 100px)}",
+      ".foo{transform:translate3d(200px,300px,10px)}"
+    );
+    minify_test(
+      ".foo{transform:rotate(45deg) rotate(45deg)}",
+      ".foo{transform:rotate(90deg)}"
+    );
+    minify_test(
+      ".foo{transform:matrix(0.7071067811865476, 0.7071067811865475, -0.7071067811865475, 0.7071067811865476, 100, 100)}",
+      ".foo{transform:translate(75pt,75pt)rotate(45deg)}"
+    );
+    minify_test(
+      ".foo{transform:matrix(0.7071067811865476, 0.7071067811865475, -0.7071067811865475, 0.7071067811865476, 0, 0)}",
+      ".foo{transform:rotate(45deg)}"
+    );
+    minify_test(
+      ".foo{transform:matrix(1.7320508, 1, -1, 1.7320508, 0, 0)}",
+      ".foo{transform:rotate(30deg)scale(2)}"
+    );
+    minify_test(
+      ".foo{transform:matrix(1, 0, 1, 1, 0, 0)}",
+      ".foo{transform:skew(45deg)}"
+    );
+    minify_test(
+      ".foo{transform:translateX(2in) translateX(50px)}",
+      ".foo{transform:translate(242px)}"
+    );
+    minify_test(
+      ".foo{transform:translateX(calc(2in + 50px))}",
+      ".foo{transform:translate(242px)}"
+    );
+    minify_test(
+      ".foo{transform:translateX(50%)}",
+      ".foo{transform:translate(50%)}"
+    );
+    minify_test(
+      ".foo{transform:translateX(calc(50% - 100px + 20px))}",
+      ".foo{transform:translate(calc(50% - 5pc))}"
+    );
+    minify_test(
+      ".foo{transform:rotate(calc(10deg + 20deg))}",
+      ".foo{transform:rotate(30deg)}"
+    );
+    minify_test(
+      ".foo{transform:rotate(calc(10deg + 0.349066rad))}",
+      ".foo{transform:rotate(30deg)}"
+    );
+    minify_test(
+      ".foo{transform:rotate(calc(10deg + 1.5turn))}",
+      ".foo{transform:rotate(550deg)}"
+    );
+    minify_test(
+      ".foo{transform:rotate(calc(10deg * 2))}",
+      ".foo{transform:rotate(20deg)}"
+    );
+    minify_test(
+      ".foo{transform:rotate(calc(-10deg * 2))}",
+      ".foo{transform:rotate(-20deg)}"
+    );
+    minify_test(
+      ".foo{transform:rotate(calc(10deg + var(--test)))}",
+      ".foo{transform:rotate(calc(10deg + var(--test)))}"
+    );
+    minify_test(".foo { transform: scale(calc(10% + 20%))", ".foo{transform:scale(.3)}");
+    minify_test(".foo { transform: scale(calc(.1 + .2))", ".foo{transform:scale(.3)}");
+
+    minify_test(".foo { -webkit-transform: scale(calc(10% + 20%))", ".foo{-webkit-transform:scale(.3)}");
+
+    // Transforms that cannot be converted to a matrix are folded with their neighbours instead.
+    minify_test(".foo { transform: translate(10%) translate(20%, 5%)", ".foo{transform:translate(30%,5%)}");
+    minify_test(".foo { transform: translateX(1em) translateY(2em)", ".foo{transform:translate(1em,2em)}");
+    minify_test(".foo { transform: translate(10%) translateZ(5px)", ".foo{transform:translate3d(10%,0,5px)}");
+    minify_test(".foo { transform: translate(10%) translate(10px)", ".foo{transform:translate(10%)translate(10px)}");
+    minify_test(".foo { transform: translate(10%) scale(2) scale(1.5, 3)", ".foo{transform:translate(10%)scale(3,6)}");
+    minify_test(".foo { transform: translate(10%) rotate(10deg) rotate(.5turn)", ".foo{transform:translate(10%)rotate(190deg)}");
+    minify_test(".foo { transform: translate(10%) rotateX(10deg) rotateY(10deg)", ".foo{transform:translate(10%)rotateX(10deg)rotateY(10deg)}");
+    minify_test(".foo { transform: translate(0) translate(10%) scale(1) rotate(0deg)", ".foo{transform:translate(10%)}");
+    minify_test(".foo { transform: translate(10%) rotate(10deg) rotate(-10deg) translate(5%)", ".foo{transform:translate(15%)}");
+    minify_test(".foo { transform: translate(0%) scale(1)", ".foo{transform:translate(0)}");
+  }
+
+  #[test]
+  fn test_transform_origin() {
+    minify_test(".foo { transform-origin: left top }", ".foo{transform-origin:0 0}");
+    minify_test(".foo { transform-origin: center }", ".foo{transform-origin:50%}");
+    minify_test(".foo { transform-origin: top }", ".foo{transform-origin:top}");
+    minify_test(".foo { transform-origin: center left }", ".foo{transform-origin:0}");
+    minify_test(".foo { transform-origin: bottom right }", ".foo{transform-origin:100% 100%}");
+    minify_test(".foo { transform-origin: right bottom 10px }", ".foo{transform-origin:100% 100% 10px}");
+    minify_test(".foo { transform-origin: 10px 20px 0px }", ".foo{transform-origin:10px 20px}");
+    minify_test(".foo { transform-origin: 10px center 5px }", ".foo{transform-origin:10px 50% 5px}");
+    minify_test(".foo { perspective-origin: left top }", ".foo{perspective-origin:0 0}");
+    minify_test(".foo { perspective-origin: right 10px top }", ".foo{perspective-origin:right 10px top}");
+    test(".foo { transform-origin: left top 10px }", indoc! {r#"
+      .foo {
+        transform-origin: left top 10px;
+      }
+    "#});
+
+    prefix_test(r#"
+      .foo {
+        transform-origin: left top;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-transform-origin: left top;
+        transform-origin: left top;
+      }
+    "#
+    }, Browsers {
+      safari: Some(6 << 16),
+      ..Browsers::default()
+    });
+  }
+
+  #[test]
+  fn test_individual_transforms() {
+    minify_test(".foo { translate: 10px 0px }", ".foo{translate:10px}");
+    minify_test(".foo { translate: 10px 20px 30px }", ".foo{translate:10px 20px 30px}");
+    minify_test(".foo { translate: none }", ".foo{translate:none}");
+    minify_test(".foo { rotate: 45deg }", ".foo{rotate:45deg}");
+    minify_test(".foo { rotate: z 45deg }", ".foo{rotate:45deg}");
+    minify_test(".foo { rotate: 45deg x }", ".foo{rotate:x 45deg}");
+    minify_test(".foo { rotate: 1 1 0 45deg }", ".foo{rotate:1 1 0 45deg}");
+    minify_test(".foo { scale: 2 2 }", ".foo{scale:2}");
+    minify_test(".foo { scale: 2 3 1 }", ".foo{scale:2 3}");
+    minify_test(".foo { scale: 1 1 2 }", ".foo{scale:1 1 2}");
+
+    prefix_test(r#"
+      .foo {
+        translate: 10px 20px;
+        rotate: 45deg;
+        scale: 2;
+        transform: skewX(10deg);
+      }
+    "#, indoc! {r#"
+      .foo {
+        transform: translate(10px, 20px) rotate(45deg) scale(2) skewX(10deg);
+      }
+    "#
+    }, Browsers {
+      chrome: Some(90 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .foo {
+        rotate: x 45deg;
+      }
+    "#, indoc! {r#"
+      .foo {
+        transform: rotateX(45deg);
+      }
+    "#
+    }, Browsers {
+      safari: Some(14 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .foo {
+        translate: 10px 20px;
+        transform: skewX(10deg);
+      }
+    "#, indoc! {r#"
+      .foo {
+        transform: skewX(10deg);
+        translate: 10px 20px;
+      }
+    "#
+    }, Browsers {
+      chrome: Some(110 << 16),
+      ..Browsers::default()
+    });
+  }
+
+  #[test]
+  fn test_animated_transform() {
+    minify_test(
+      ".foo{transition:transform 1s;transform:translate(200px,300px) translate(100px,200px) scale(2)}",
+      ".foo{transition:transform 1s;transform:translate(200px,300px)translate(75pt,200px)scale(2)}"
+    );
+    minify_test(
+      ".foo{transition-property:all;transform:translate(200px,300px) translate(100px,200px) scale(2)}",
+      ".foo{transition-property:all;transform:translate(200px,300px)translate(75pt,200px)scale(2)}"
+    );
+    minify_test(
+      ".foo{transition:opacity 1s;transform:translate(200px,300px) translate(100px,200px) scale(2)}",
+      ".foo{transition:opacity 1s;transform:matrix(2,0,0,2,300,500)}"
+    );
+    minify_test(
+      "@keyframes foo{from{transform:rotate(10deg)}to{transform:translate(200px,300px) translate(100px,200px) scale(2)}}",
+      "@keyframes foo{0%{transform:rotate(10deg)}to{transform:translate(200px,300px)translate(75pt,200px)scale(2)}}"
+    );
+
+    let res = compile("@keyframes foo{to{transform:translate(200px,300px) translate(100px,200px) scale(2)}}", ParserOptions::default(), MinifyOptions::default(), PrinterOptions {
+      minify: true,
+      collapse_animated_transforms: true,
+      ..PrinterOptions::default()
+    });
+    assert_eq!(res, "@keyframes foo{to{transform:matrix(2,0,0,2,300,500)}}");
+  }
+
+  #[test]
+  fn test_transform_interpolation() {
+    use crate::properties::transform::TransformList;
+    use crate::traits::{Parse, ToCss};
+    use cssparser::{Parser, ParserInput};
+
+    fn parse(source: &str) -> TransformList {
+      let mut input = ParserInput::new(source);
+      let mut parser = Parser::new(&mut input);
+      TransformList::parse(&mut parser).unwrap()
+    }
+
+    fn interpolate_test(from: &str, to: &str, t: f32, expected: &str) {
+      assert_eq!(parse(from).interpolate(&parse(to), t).to_css_string(), expected);
+    }
+
+    interpolate_test("translate(0) rotate(0deg)", "translate(100px) rotate(90deg)", 0.5, "translate(50px) rotate(45deg)");
+    interpolate_test("translateX(100px)", "translateY(100px)", 0.5, "translate(50px, 50px)");
+    interpolate_test("scale(1)", "scaleX(3)", 0.5, "scale(2, 1)");
+    interpolate_test("skewX(10deg)", "skewY(10deg)", 0.5, "skew(5deg, 5deg)");
+    interpolate_test("rotate(0deg)", "rotate(90deg)", 0.0, "rotate(0deg)");
+    interpolate_test("rotate(0deg)", "rotate(90deg)", 1.0, "rotate(90deg)");
+
+    // `none` is interpolated as identity transforms of the same kind as the other list.
+    interpolate_test("none", "scale(2)", 0.5, "scale(1.5)");
+    interpolate_test("rotate(90deg)", "none", 0.75, "rotate(22.5deg)");
+    interpolate_test("none", "none", 0.5, "none");
+
+    // Lists that do not match are interpolated as matrices.
+    interpolate_test("scale(2)", "translate(100px, 50px)", 0.5, "matrix(1.5, 0, 0, 1.5, 50, 25)");
+    interpolate_test("scale(2)", "translate(100px, 50px)", 0.0, "matrix(2, 0, 0, 2, 0, 0)");
+    interpolate_test("scale(2) translate(10px)", "translate(100px, 50px)", 1.0, "matrix(1, 0, 0, 1, 100, 50)");
+
+    // Matrices cannot be computed with percentages, so the lists are swapped halfway through.
+    interpolate_test("translate(50%)", "scale(2)", 0.25, "translate(50%)");
+    interpolate_test("translate(50%)", "scale(2)", 0.75, "scale(2)");
+  }
+
+  #[test]
+  fn test_transform_builder() {
+    use crate::properties::transform::{TransformList, Matrix3d};
+    use crate::values::{angle::Angle, length::LengthPercentage};
+    use crate::traits::{Parse, ToCss};
+    use cssparser::{Parser, ParserInput};
+
+    fn parse(source: &str) -> TransformList {
+      let mut input = ParserInput::new(source);
+      let mut parser = Parser::new(&mut input);
+      TransformList::parse(&mut parser).unwrap()
+    }
+
+    let list = TransformList::new()
+      .then_translate(LengthPercentage::px(10.0), LengthPercentage::px(20.0))
+      .then_rotate(Angle::Deg(45.0))
+      .then_scale(2.0, 2.0);
+    assert_eq!(list.to_css_string(), "translate(10px, 20px) rotate(45deg) scale(2)");
+    assert_eq!(TransformList::new().to_css_string(), "none");
+    assert_eq!((parse("translate(10px)") * parse("scale(2)")).to_css_string(), "translate(10px) scale(2)");
+
+    let matrix = Matrix3d::translate(10.0, 0.0, 0.0) * Matrix3d::scale(2.0, 2.0, 1.0);
+    assert_eq!(Some(matrix.clone()), parse("scale(2) translate(10px)").to_matrix());
+    assert_eq!(TransformList::from(matrix).to_css_string(), "matrix(2, 0, 0, 2, 20, 0)");
+    assert_eq!(TransformList::from(Matrix3d::translate(0.0, 0.0, 5.0)).to_css_string(), "matrix3d(1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 5, 1)");
+  }
+
+  #[test]
+  fn test_transform_context() {
+    use crate::properties::transform::TransformContext;
+
+    let minify = |code: &str, transform_context: TransformContext| compile(code, ParserOptions::default(), MinifyOptions::default(), PrinterOptions {
+      minify: true,
+      transform_context,
+      ..PrinterOptions::default()
+    });
+
+    let context = TransformContext {
+      font_size: Some(16.0),
+      root_font_size: Some(10.0),
+      viewport: Some((1000.0, 500.0)),
+      ..TransformContext::default()
+    };
+
+    assert_eq!(minify(".foo{transform:translate(1em, 2rem) scale(2)}", context), ".foo{transform:matrix(2,0,0,2,16,20)}");
+    assert_eq!(minify(".foo{transform:translateX(10vw) translateX(10px)}", context), ".foo{transform:translate(110px)}");
+    assert_eq!(minify(".foo{transform:translateX(10vmin) translateX(10px)}", context), ".foo{transform:translate(60px)}");
+    assert_eq!(minify(".foo{transform:translate(1em, 2rem) scale(2)}", TransformContext::default()), ".foo{transform:translate(1em,2rem)scale(2)}");
+
+    // Percentages are relative to the size of the element's bounding box.
+    let context = TransformContext {
+      bounding_box: Some((200.0, 100.0)),
+      ..context
+    };
+    assert_eq!(minify(".foo{transform:translate(50%, 50%) scale(2)}", context), ".foo{transform:matrix(2,0,0,2,100,50)}");
+    assert_eq!(minify(".foo{transform:translateX(50%) translateX(10px)}", context), ".foo{transform:translate(110px)}");
+    assert_eq!(minify(".foo{transform:translate(50%, 50%) scale(2)}", TransformContext::default()), ".foo{transform:translate(50%,50%)scale(2)}");
+  }
+
+  #[test]
+  fn test_scale_percentage() {
+    minify_test(
+      ".foo{transition:transform 1s;transform:scale(50%)}",
+      ".foo{transition:transform 1s;transform:scale(.5)}"
+    );
+    minify_test(
+      ".foo{transition:transform 1s;transform:scale(5%, 200%)}",
+      ".foo{transition:transform 1s;transform:scale(5%,2)}"
+    );
+    minify_test(
+      ".foo{transition:transform 1s;transform:scale(-.5)}",
+      ".foo{transition:transform 1s;transform:scale(-.5)}"
+    );
+  }
+
+  #[test]
+  fn test_svg_transform() {
+    use crate::properties::transform::TransformList;
+    use crate::traits::{Parse, ToCss};
+    use cssparser::{Parser, ParserInput};
+
+    fn parse_svg(source: &str) -> Option<TransformList> {
+      let mut input = ParserInput::new(source);
+      let mut parser = Parser::new(&mut input);
+      TransformList::parse_svg(&mut parser).ok()
+    }
+
+    fn svg_test(source: &str, expected: &str) {
+      assert_eq!(parse_svg(source).unwrap().to_css_string(), expected);
+    }
+
+    svg_test("translate(10)", "translate(10px)");
+    svg_test("translate(10 20)", "translate(10px, 20px)");
+    svg_test("translate(10,20) scale(2)", "translate(10px, 20px) scale(2)");
+    svg_test("translate(10-20),scale(2 3)", "translate(10px, -20px) scale(2, 3)");
+    svg_test("rotate(45)", "rotate(45deg)");
+    svg_test("rotate(45 10 20)", "translate(10px, 20px) rotate(45deg) translate(-10px, -20px)");
+    svg_test("skewX(30) skewY(-10)", "skewX(30deg) skewY(-10deg)");
+    svg_test("", "none");
+    assert_eq!(
+      parse_svg("matrix(1 0 0 1 10 20)").unwrap().to_matrix(),
+      parse_svg("translate(10, 20)").unwrap().to_matrix()
+    );
+
+    assert_eq!(parse_svg("translate(10px)"), None);
+    assert_eq!(parse_svg("translate()"), None);
+    assert_eq!(parse_svg("rotate(45 10)"), None);
+    assert_eq!(parse_svg("matrix(1 0 0 1)"), None);
+    assert_eq!(parse_svg("translate(10),"), None);
+    assert_eq!(parse_svg("translateX(10)"), None);
+
+    fn to_svg(source: &str) -> Option<String> {
+      let mut input = ParserInput::new(source);
+      let mut parser = Parser::new(&mut input);
+      TransformList::parse(&mut parser).unwrap().to_svg_string()
+    }
+
+    assert_eq!(to_svg("translate(10px, 20px) scale(2)"), Some("translate(10 20) scale(2)".into()));
+    assert_eq!(to_svg("translateX(1in) translateY(5px)"), Some("translate(96) translate(0 5)".into()));
+    assert_eq!(to_svg("scale(50%, 2) scaleY(3)"), Some("scale(.5 2) scale(1 3)".into()));
+    assert_eq!(to_svg("rotate(0.25turn) skewX(30deg)"), Some("rotate(90) skewX(30)".into()));
+    assert_eq!(to_svg("matrix(1, 2, 3, 4, 5, 6)"), Some("matrix(1 2 3 4 5 6)".into()));
+    assert_eq!(to_svg("skew(45deg)"), Some("matrix(1 0 1 1 0 0)".into()));
+    assert_eq!(to_svg("none"), Some("".into()));
+    assert_eq!(to_svg("translate(50%)"), None);
+    assert_eq!(to_svg("translate(1em)"), None);
+    assert_eq!(to_svg("rotateX(45deg)"), None);
+
+    let svg = "translate(10 -20) rotate(45) skewY(-10)";
+    assert_eq!(parse_svg(svg).unwrap().to_svg_string(), Some(svg.into()));
+  }
+
+  #[test]
+  pub fn test_gradients() {
+    minify_test(
+      ".foo { background: linear-gradient(yellow, blue) }",
+      ".foo{background:linear-gradient(#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: linear-gradient(to bottom, yellow, blue); }",
+      ".foo{background:linear-gradient(#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: linear-gradient(180deg, yellow, blue); }",
+      ".foo{background:linear-gradient(#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: linear-gradient(0.5turn, yellow, blue); }",
+      ".foo{background:linear-gradient(#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: linear-gradient(yellow 10%, blue 20%) }",
+      ".foo{background:linear-gradient(#ff0 10%,#00f 20%)}"
+    );
+    minify_test(
+      ".foo { background: linear-gradient(to top, blue, yellow); }",
+      ".foo{background:linear-gradient(#ff0,#00f)}"
+    );
     minify_test(
       ".foo { background: linear-gradient(to top, blue 10%, yellow 20%); }",
       ".foo{background:linear-gradient(#ff0 80%,#00f 90%)}"
@@ -3099,147 +5578,2401 @@ mod tests {
       ".foo { background: radial-gradient(20px, yellow, blue) }",
       ".foo{background:radial-gradient(20px,#ff0,#00f)}"
     );
-    minify_test(
-      ".foo { background: radial-gradient(circle 20px, yellow, blue) }",
-      ".foo{background:radial-gradient(20px,#ff0,#00f)}"
+    minify_test(
+      ".foo { background: radial-gradient(circle 20px, yellow, blue) }",
+      ".foo{background:radial-gradient(20px,#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: radial-gradient(20px 40px, yellow, blue) }",
+      ".foo{background:radial-gradient(20px 40px,#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: radial-gradient(ellipse 20px 40px, yellow, blue) }",
+      ".foo{background:radial-gradient(20px 40px,#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: radial-gradient(ellipse calc(20px + 10px) 40px, yellow, blue) }",
+      ".foo{background:radial-gradient(30px 40px,#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: radial-gradient(circle farthest-side, yellow, blue) }",
+      ".foo{background:radial-gradient(circle farthest-side,#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: radial-gradient(farthest-side circle, yellow, blue) }",
+      ".foo{background:radial-gradient(circle farthest-side,#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: radial-gradient(ellipse farthest-side, yellow, blue) }",
+      ".foo{background:radial-gradient(farthest-side,#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: radial-gradient(farthest-side ellipse, yellow, blue) }",
+      ".foo{background:radial-gradient(farthest-side,#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: repeating-radial-gradient(circle 20px, yellow, blue) }",
+      ".foo{background:repeating-radial-gradient(20px,#ff0,#00f)}"
+    );
+    minify_test(
+      ".foo { background: conic-gradient(#f06, gold) }",
+      ".foo{background:conic-gradient(#f06,gold)}"
+    );
+    minify_test(
+      ".foo { background: conic-gradient(at 50% 50%, #f06, gold) }",
+      ".foo{background:conic-gradient(#f06,gold)}"
+    );
+    minify_test(
+      ".foo { background: conic-gradient(from 0deg, #f06, gold) }",
+      ".foo{background:conic-gradient(#f06,gold)}"
+    );
+    minify_test(
+      ".foo { background: conic-gradient(from 0deg at center, #f06, gold) }",
+      ".foo{background:conic-gradient(#f06,gold)}"
+    );
+    minify_test(
+      ".foo { background: conic-gradient(white -50%, black 150%) }",
+      ".foo{background:conic-gradient(#fff -50%,#000 150%)}"
+    );
+    minify_test(
+      ".foo { background: conic-gradient(white -180deg, black 540deg) }",
+      ".foo{background:conic-gradient(#fff -180deg,#000 540deg)}"
+    );
+    minify_test(
+      ".foo { background: conic-gradient(from 45deg, white, black, white) }",
+      ".foo{background:conic-gradient(from 45deg,#fff,#000,#fff)}"
+    );
+    minify_test(
+      ".foo { background: repeating-conic-gradient(from 45deg, white, black, white) }",
+      ".foo{background:repeating-conic-gradient(from 45deg,#fff,#000,#fff)}"
+    );
+    minify_test(
+      ".foo { background: repeating-conic-gradient(black 0deg 25%, white 0deg 50%) }",
+      ".foo{background:repeating-conic-gradient(#000 0deg 25%,#fff 0deg 50%)}"
+    );
+  }
+
+  #[test]
+  fn test_font_face() {
+    minify_test(r#"@font-face {
+      src: url("test.woff");
+      font-family: "Helvetica";
+      font-weight: bold;
+      font-style: italic;
+    }"#, "@font-face{src:url(test.woff);font-family:Helvetica;font-weight:700;font-style:italic}");
+    minify_test("@font-face {src: url(test.woff);}", "@font-face{src:url(test.woff)}");
+    minify_test("@font-face {src: local(\"Test\");}", "@font-face{src:local(Test)}");
+    minify_test("@font-face {src: local(\"Foo Bar\");}", "@font-face{src:local(Foo Bar)}");
+    minify_test("@font-face {src: local(Test);}", "@font-face{src:local(Test)}");
+    minify_test("@font-face {src: local(Foo Bar);}", "@font-face{src:local(Foo Bar)}");
+    minify_test("@font-face {src: url(\"test.woff\") format(woff);}", "@font-face{src:url(test.woff)format(woff)}");
+    minify_test("@font-face {src: url(\"test.woff\") format(woff), url(test.ttf) format(truetype);}", "@font-face{src:url(test.woff)format(woff),url(test.ttf)format(truetype)}");
+    minify_test("@font-face {src: url(\"test.woff\") format(woff supports features(opentype));}", "@font-face{src:url(test.woff)format(woff supports features(opentype))}");
+    minify_test("@font-face {src: url(\"test.woff\") format(woff supports color(COLRv1));}", "@font-face{src:url(test.woff)format(woff supports color(colrv1))}");
+    minify_test("@font-face {src: url(\"test.woff\") format(woff supports variations);}", "@font-face{src:url(test.woff)format(woff supports variations)}");
+    minify_test("@font-face {src: url(\"test.woff\") format(woff supports palettes);}", "@font-face{src:url(test.woff)format(woff supports palettes)}");
+    minify_test("@font-face {src: url(\"test.woff\") format(woff supports features(opentype) color(sbix));}", "@font-face{src:url(test.woff)format(woff supports features(opentype) color(sbix))}");
+  }
+
+  #[test]
+  fn test_page_rule() {
+    minify_test("@page {margin: 0.5cm}", "@page{margin:5mm}");
+    minify_test("@page :left {margin: 0.5cm}", "@page:left{margin:5mm}");
+    minify_test("@page :right {margin: 0.5cm}", "@page:right{margin:5mm}");
+    minify_test("@page LandscapeTable {margin: 0.5cm}", "@page LandscapeTable{margin:5mm}");
+    minify_test("@page CompanyLetterHead:first {margin: 0.5cm}", "@page CompanyLetterHead:first{margin:5mm}");
+    minify_test("@page:first {margin: 0.5cm}", "@page:first{margin:5mm}");
+    minify_test("@page :blank:first {margin: 0.5cm}", "@page:blank:first{margin:5mm}");
+    minify_test("@page toc, index {margin: 0.5cm}", "@page toc,index{margin:5mm}");
+  }
+
+  #[test]
+  fn test_import() {
+    minify_test("@import url(foo.css);", "@import \"foo.css\";");
+    minify_test("@import \"foo.css\" print;", "@import \"foo.css\" print;");
+    minify_test("@import \"foo.css\" screen and (min-width: 100px), print;", "@import \"foo.css\" screen and (min-width:100px),print;");
+    test(r#"
+      @import "foo.css" screen and (orientation: landscape);
+    "#, indoc! {r#"
+      @import "foo.css" screen and (orientation: landscape);
+    "#});
+  }
+
+  #[test]
+  fn test_namespaces() {
+    minify_test("@namespace \"http://toto.example.org\";", "@namespace\"http://toto.example.org\";");
+    minify_test("@namespace url(http://toto.example.org);", "@namespace\"http://toto.example.org\";");
+    minify_test(
+      "@namespace svg url(http://www.w3.org/2000/svg); svg|a { color: red }",
+      "@namespace svg\"http://www.w3.org/2000/svg\";svg|a{color:red}"
+    );
+    minify_test(
+      "@namespace svg url(http://www.w3.org/2000/svg); [svg|href] { color: red }",
+      "@namespace svg\"http://www.w3.org/2000/svg\";[svg|href]{color:red}"
+    );
+    minify_test(
+      "@namespace url(http://www.w3.org/1999/xhtml); a, *|b, |c { color: red }",
+      "@namespace\"http://www.w3.org/1999/xhtml\";a,*|b,|c{color:red}"
+    );
+
+    // Only the last declaration for each prefix is kept.
+    minify_test(
+      "@namespace url(http://a.example); @namespace svg url(http://b.example); @namespace url(http://c.example);",
+      "@namespace svg\"http://b.example\";@namespace\"http://c.example\";"
+    );
+
+    // Selectors with undeclared prefixes are invalid.
+    minify_test("foo|a { color: red }", "");
+    minify_test("@namespace svg url(http://www.w3.org/2000/svg); foo|a { color: red }", "@namespace svg\"http://www.w3.org/2000/svg\";");
+
+    // @namespace rules must come before all other rules except @import.
+    minify_test(".foo { color: red } @namespace svg url(http://www.w3.org/2000/svg);", ".foo{color:red}");
+    minify_test("@namespace svg url(http://www.w3.org/2000/svg); @import \"foo.css\";", "@namespace svg\"http://www.w3.org/2000/svg\";");
+  }
+
+  #[test]
+  fn test_counter_style() {
+    minify_test(r#"
+      @counter-style circled-alpha {
+        system: fixed;
+        symbols: Ⓐ Ⓑ Ⓒ;
+        suffix: " ";
+      }
+    "#, "@counter-style circled-alpha{system:fixed;symbols:Ⓐ Ⓑ Ⓒ;suffix:\" \"}");
+    minify_test(r#"
+      @counter-style thumbs {
+        system: cyclic;
+        symbols: "👍" "👎";
+        suffix: " ";
+      }
+    "#, "@counter-style thumbs{system:cyclic;symbols:\"👍\"\"👎\";suffix:\" \"}");
+    minify_test(r#"
+      @counter-style upper-roman {
+        system: additive;
+        range: 1 3999;
+        additive-symbols: 1000 M, 900 CM, 500 D, 400 CD, 100 C, 90 XC, 50 L, 40 XL, 10 X, 9 IX, 5 V, 4 IV, 1 I;
+      }
+    "#, "@counter-style upper-roman{system:additive;range:1 3999;additive-symbols:1000 M,900 CM,500 D,400 CD,100 C,90 XC,50 L,40 XL,10 X,9 IX,5 V,4 IV,1 I}");
+    minify_test(r#"
+      @counter-style foo {
+        system: fixed 1;
+        symbols: a b;
+        negative: "(" ")";
+        pad: "0" 3;
+        range: infinite -1, 5 infinite;
+        fallback: lower-alpha;
+        speak-as: spell-out;
+      }
+    "#, "@counter-style foo{system:fixed;symbols:a b;negative:\"(\"\")\";pad:3\"0\";range:infinite -1,5 infinite;fallback:lower-alpha;speak-as:spell-out}");
+    test(r#"
+      @counter-style foo {
+        system: extends decimal;
+        prefix: "~";
+        system: extends disc;
+      }
+    "#, indoc! {r#"
+      @counter-style foo {
+        prefix: "~";
+        system: extends disc;
+      }
+    "#});
+
+    // Invalid rules are dropped.
+    minify_test("@counter-style none { system: cyclic; symbols: a }", "");
+    minify_test("@counter-style foo { system: alphabetic; symbols: a }", "");
+    minify_test("@counter-style foo { system: additive; additive-symbols: 1 I, 5 V }", "");
+    minify_test("@counter-style foo { system: extends decimal; symbols: a }", "");
+    minify_test("@counter-style foo { system: extends none }", "");
+    minify_test("@counter-style foo { system: extends decimal; unknown: 1; suffix: \")\" }", "@counter-style foo{system:extends decimal;suffix:\")\"}");
+    minify_test("@counter-style foo { system: cyclic; symbols: a; range: 5 1 }", "@counter-style foo{system:cyclic;symbols:a}");
+  }
+
+  #[test]
+  fn test_property_rule() {
+    minify_test(r#"
+      @property --property-name {
+        syntax: '<length>';
+        inherits: false;
+        initial-value: 0px;
+      }
+    "#, "@property --property-name{syntax:\"<length>\";inherits:false;initial-value:0}");
+    minify_test(r#"
+      @property --property-name {
+        syntax: '<color>';
+        inherits: true;
+        initial-value: rgb(255, 0, 0);
+      }
+    "#, "@property --property-name{syntax:\"<color>\";inherits:true;initial-value:red}");
+    minify_test(r#"
+      @property --property-name {
+        syntax: '<length> | <percentage>+ | auto';
+        inherits: false;
+        initial-value: 10% 20%;
+      }
+    "#, "@property --property-name{syntax:\"<length>|<percentage>+|auto\";inherits:false;initial-value:10% 20%}");
+    minify_test(r#"
+      @property --property-name {
+        syntax: '<length>#';
+        initial-value: 1in, 2px;
+        inherits: false;
+      }
+    "#, "@property --property-name{syntax:\"<length>#\";inherits:false;initial-value:1in,2px}");
+    minify_test(r#"
+      @property --property-name {
+        syntax: '*';
+        inherits: false;
+      }
+    "#, "@property --property-name{syntax:\"*\";inherits:false}");
+    minify_test(r#"
+      @property --property-name {
+        syntax: '<resolution>';
+        inherits: false;
+        initial-value: 192dpi;
+      }
+    "#, "@property --property-name{syntax:\"<resolution>\";inherits:false;initial-value:2x}");
+    test(r#"
+      @property --property-name {
+        syntax: '<transform-list>';
+        inherits: false;
+        initial-value: translateX(10px) rotate(45deg);
+      }
+    "#, indoc! {r#"
+      @property --property-name {
+        syntax: "<transform-list>";
+        inherits: false;
+        initial-value: translateX(10px) rotate(45deg);
+      }
+    "#});
+
+    // Invalid rules are dropped.
+    minify_test("@property --property-name { syntax: '<length>'; inherits: false; initial-value: red }", "");
+    minify_test("@property --property-name { syntax: '<length>'; inherits: false; initial-value: 2em }", "");
+    minify_test("@property --property-name { syntax: '<length>'; inherits: false }", "");
+    minify_test("@property --property-name { syntax: '<foo>'; inherits: false; initial-value: 0px }", "");
+    minify_test("@property --property-name { syntax: '<length>'; initial-value: 0px }", "");
+    minify_test("@property property-name { syntax: '<length>'; inherits: false; initial-value: 0px }", "");
+  }
+
+  #[test]
+  fn test_nested_output() {
+    nested_test(r#"
+      .foo {
+        color: red;
+      }
+      .foo .bar {
+        color: green;
+      }
+      .foo > .baz:hover {
+        color: blue;
+      }
+      .qux {
+        color: yellow;
+      }
+    "#, indoc! {r#"
+      .foo {
+        & {
+          color: red;
+        }
+        & .bar {
+          color: green;
+        }
+        & > .baz:hover {
+          color: #00f;
+        }
+      }
+
+      .qux {
+        color: #ff0;
+      }
+    "#}, None);
+
+    nested_test(r#"
+      @media (min-width: 240px) {
+        .foo .bar {
+          color: red;
+        }
+        .foo .baz {
+          color: green;
+        }
+      }
+    "#, indoc! {r#"
+      @media (min-width: 240px) {
+        .foo {
+          & .bar {
+            color: red;
+          }
+          & .baz {
+            color: green;
+          }
+        }
+      }
+    "#}, None);
+
+    // Pseudo elements and selector lists cannot be represented with the nesting selector.
+    nested_test(r#"
+      .foo::before {
+        color: red;
+      }
+      .foo::after {
+        color: green;
+      }
+      .foo .bar, .foo .baz {
+        color: blue;
+      }
+    "#, indoc! {r#"
+      .foo:before {
+        color: red;
+      }
+
+      .foo:after {
+        color: green;
+      }
+
+      .foo .bar, .foo .baz {
+        color: #00f;
+      }
+    "#}, None);
+
+    nested_test(r#"
+      .foo .bar {
+        color: red;
+      }
+      .foo .baz {
+        color: green;
+      }
+    "#, indoc! {r#"
+      .foo {
+        & .bar {
+          color: red;
+        }
+        & .baz {
+          color: green;
+        }
+      }
+    "#}, Some(Browsers {
+      chrome: Some(112 << 16),
+      ..Browsers::default()
+    }));
+
+    // Targets that do not support nesting get the flat output.
+    nested_test(r#"
+      .foo .bar {
+        color: red;
+      }
+      .foo .baz {
+        color: green;
+      }
+    "#, indoc! {r#"
+      .foo .bar {
+        color: red;
+      }
+
+      .foo .baz {
+        color: green;
+      }
+    "#}, Some(Browsers {
+      chrome: Some(95 << 16),
+      ..Browsers::default()
+    }));
+  }
+
+  #[test]
+  fn test_content_hash() {
+    let hash = |code: &str| {
+      let mut stylesheet = StyleSheet::parse(code, ParserOptions::default());
+      stylesheet.minify(MinifyOptions::default());
+      stylesheet.content_hash().unwrap()
+    };
+
+    let a = hash(".foo { color: red; background: green }");
+    assert_eq!(a, hash(".foo{color:red;background:green}"));
+    assert_eq!(a, hash("/* comment */\n.foo {\n  color: #ff0000;\n  background: green;\n}\n"));
+    assert_ne!(a, hash(".foo { color: blue; background: green }"));
+    assert_ne!(a, hash(".foo { color: red } .bar { background: green }"));
+    assert_ne!(hash(".foo { color: red } .bar { color: green }"), hash(".foo { color: red; } .bar { color: green; } .baz {}"));
+  }
+
+  #[test]
+  fn test_serialization_cache() {
+    use crate::cache::SerializationCache;
+
+    let build = |code: &str, cache: &mut SerializationCache, minify: bool| {
+      let mut stylesheet = StyleSheet::parse(code, ParserOptions::default());
+      stylesheet.minify(MinifyOptions::default());
+      let options = || PrinterOptions { minify, ..PrinterOptions::default() };
+      let res = stylesheet.to_css_with_cache(options(), cache).unwrap();
+      assert_eq!(res, stylesheet.to_css(options()).unwrap().code);
+      res
+    };
+
+    let mut cache = SerializationCache::new();
+    build(".foo { color: red } .bar { color: green } @media print { .baz { color: blue } }", &mut cache, false);
+    assert_eq!((cache.hits, cache.misses), (0, 3));
+
+    let res = build(".foo { color: red } .bar { color: yellow } @media print { .baz { color: blue } }", &mut cache, false);
+    assert_eq!(res, ".foo {\n  color: red;\n}\n\n.bar {\n  color: #ff0;\n}\n\n@media print {\n  .baz {\n    color: #00f;\n  }\n}\n");
+    assert_eq!((cache.hits, cache.misses), (2, 1));
+    assert_eq!(cache.len(), 3);
+
+    build(".foo { color: red }", &mut cache, false);
+    assert_eq!((cache.hits, cache.misses), (1, 0));
+    assert_eq!(cache.len(), 1);
+
+    // Changing the options invalidates the cache.
+    let res = build(".foo { color: red }", &mut cache, true);
+    assert_eq!(res, ".foo{color:red}");
+    assert_eq!((cache.hits, cache.misses), (0, 1));
+
+    let stylesheet = StyleSheet::parse("/* a */ .foo { color: red }", ParserOptions::default());
+    stylesheet.to_css_with_cache(PrinterOptions::default(), &mut cache).unwrap();
+    let res = stylesheet.to_css_with_cache(PrinterOptions {
+      preserve_comments: PreserveComments::All,
+      ..PrinterOptions::default()
+    }, &mut cache).unwrap();
+    assert_eq!(res, "/* a */
+
+.foo {
+  color: red;
+}
+");
+    assert_eq!((cache.hits, cache.misses), (0, 2));
+
+    // Rules that only moved within the source are reused.
+    build(".foo { color: red }", &mut cache, false);
+    build("\n\n  .foo { color: red }", &mut cache, false);
+    assert_eq!((cache.hits, cache.misses), (1, 0));
+
+    // A rule that appears twice is only printed once.
+    let stylesheet = StyleSheet::parse(".foo { color: red } .bar { color: green } .foo { color: red }", ParserOptions::default());
+    let mut cache = SerializationCache::new();
+    let res = stylesheet.to_css_with_cache(PrinterOptions { minify: true, ..PrinterOptions::default() }, &mut cache).unwrap();
+    assert_eq!(res, ".foo{color:red}.bar{color:green}.foo{color:red}");
+    assert_eq!((cache.hits, cache.misses), (1, 2));
+    assert_eq!(cache.len(), 2);
+  }
+
+  #[test]
+  fn test_banner_footer() {
+    use crate::cache::SerializationCache;
+
+    let print = |code: &str, minify: bool| {
+      let stylesheet = StyleSheet::parse(code, ParserOptions::default());
+      let options = || PrinterOptions {
+        minify,
+        banner: Some("/*! v1.0.0 */".into()),
+        footer: Some("/* built 2021-01-01 */".into()),
+        ..PrinterOptions::default()
+      };
+      let res = stylesheet.to_css(options()).unwrap().code;
+      assert_eq!(res, stylesheet.to_css_with_cache(options(), &mut SerializationCache::new()).unwrap());
+      res
+    };
+
+    assert_eq!(print(".foo { color: red }", true), "/*! v1.0.0 */\n.foo{color:red}\n/* built 2021-01-01 */");
+    assert_eq!(print(".foo { color: red }", false), "/*! v1.0.0 */\n.foo {\n  color: red;\n}\n/* built 2021-01-01 */\n");
+    assert_eq!(print("", true), "/*! v1.0.0 */\n/* built 2021-01-01 */");
+  }
+
+  #[test]
+  fn test_charset() {
+    use crate::warnings::WarningKind;
+
+    let print = |code: &str, minify: bool, emit_charset: bool| {
+      let stylesheet = StyleSheet::parse(code, ParserOptions::default());
+      stylesheet.to_css(PrinterOptions {
+        minify,
+        emit_charset,
+        ..PrinterOptions::default()
+      }).unwrap().code
+    };
+
+    assert_eq!(print("\u{feff}.foo { color: red }", true, false), ".foo{color:red}");
+    assert_eq!(print("@charset \"UTF-8\";\n.foo { color: red }", true, false), ".foo{color:red}");
+    assert_eq!(print("\u{feff}@charset \"utf-8\"; .foo { content: \"\u{2192}\" }", true, false), ".foo{content:\"\u{2192}\"}");
+    assert_eq!(print("@CHARSET  \"utf-8\" ;\n.foo { color: red }", true, false), ".foo{color:red}");
+    assert_eq!(print(".foo { color: red }", true, true), "@charset \"UTF-8\";.foo{color:red}");
+    assert_eq!(print("@charset \"UTF-8\"; .foo { color: red }", false, true), "@charset \"UTF-8\";\n.foo {\n  color: red;\n}\n");
+
+    let warnings = |code: &str| {
+      StyleSheet::parse(code, ParserOptions { error_recovery: true, ..ParserOptions::default() }).warnings
+        .into_iter()
+        .map(|warning| warning.kind)
+        .collect::<Vec<_>>()
+    };
+    assert_eq!(warnings("@charset \"UTF-8\"; .foo { color: red }"), vec![]);
+    assert_eq!(warnings("@charset \"iso-8859-15\"; .foo { color: red }"), vec![WarningKind::UnsupportedCharset]);
+  }
+
+  #[test]
+  fn test_unknown_rules() {
+    minify_test("@tailwind base;", "@tailwind base;");
+    minify_test("@tailwind  base ; .foo { color: red }", "@tailwind base;.foo{color:red}");
+    minify_test("@supports (display: grid) { .foo { display: grid } }", "@supports (display: grid){.foo{display:grid}}");
+    minify_test("@media print { @page-margin top { content: none } }", "@media print{@page-margin top{content: none}}");
+    minify_test(".btn { @apply font-bold py-2; color: red }", ".btn{@apply font-bold py-2;color:red}");
+    minify_test(".btn { color: red; @apply font-bold py-2 }", ".btn{color:red;@apply font-bold py-2}");
+    minify_test(".btn { color: green; @apply font-bold; color: red; width: 1px }", ".btn{color:green;@apply font-bold;color:red;width:1px}");
+    minify_test(".foo { foo-bar: 1px solid {x} }", ".foo{foo-bar:1px solid {x}}");
+    minify_test(".foo { future-property: 1px 2px }", ".foo{future-property:1px 2px}");
+    test("@tailwind base;\n@tailwind utilities;", "@tailwind base;\n\n@tailwind utilities;\n");
+    test(r#"
+      @font-feature-values Font One {
+        @styleset { nice-style: 12; }
+      }
+    "#, indoc! {r#"
+      @font-feature-values Font One {
+        @styleset { nice-style: 12; }
+      }
+    "#});
+
+    let targets = Browsers {
+      chrome: Some(95 << 16),
+      ..Browsers::default()
+    };
+    prefix_test(r#"
+      .foo {
+        @apply px-2;
+        .bar {
+          color: blue;
+        }
+      }
+    "#, indoc! {r#"
+      .foo {
+        @apply px-2;
+      }
+
+      .foo .bar {
+        color: #00f;
+      }
+    "#}, targets);
+
+    test(r#"
+      .btn {
+        color: green;
+        @apply font-bold;
+        color: red;
+      }
+    "#, indoc! {r#"
+      .btn {
+        color: green;
+        @apply font-bold;
+        color: red;
+      }
+    "#});
+
+    // When nesting is compiled, the declarations after the at-rule are output in a later rule.
+    prefix_test(r#"
+      .btn {
+        color: green;
+        @apply font-bold;
+        color: red;
+      }
+    "#, indoc! {r#"
+      .btn {
+        color: green;
+        @apply font-bold;
+      }
+
+      .btn {
+        color: red;
+      }
+    "#}, targets);
+  }
+
+  #[test]
+  fn test_value_hooks() {
+    use crate::value_hooks::ValueHooks;
+
+    let mut hooks = ValueHooks::new();
+    hooks.register_unit("rpx", |value| Some(format!("{}px", value / 2.0)));
+    hooks.register_function("theme", |args| match args {
+      "colors.red" => Some("#ff0000".into()),
+      _ => None
+    });
+
+    let transform = |code: &str| {
+      let mut stylesheet = StyleSheet::parse_with_hooks(code, &hooks);
+      stylesheet.minify(MinifyOptions::default());
+      stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap().code
+    };
+
+    assert_eq!(transform(".foo { width: 20rpx; height: calc(10RPX + 1em) }"), ".foo{width:10px;height:calc(5px + 1em)}");
+    assert_eq!(transform(".foo { color: theme( colors.red ) !important }"), ".foo{color:red!important}");
+    assert_eq!(transform(".foo { border: 2rpx solid theme(colors.red) }"), ".foo{border:1px solid red}");
+    assert_eq!(transform(".foo { color: theme(colors.unknown) }"), ".foo{color:theme(colors.unknown)}");
+    assert_eq!(transform(".foo { width: 20px }"), ".foo{width:20px}");
+  }
+
+  #[test]
+  fn test_api() {
+    use crate::dependencies::Dependency;
+    use crate::value_hooks::ValueHooks;
+
+    let mut hooks = ValueHooks::new();
+    hooks.register_unit("rpx", |value| Some(format!("{}px", value / 2.0)));
+
+    let mut stylesheet = StyleSheet::parse(r#"
+      @import "foo.css";
+      @custom-media --small (max-width: 30em);
+      @media (--small) {
+        .foo { width: 20rpx; background: url(a.png) }
+      }
+    "#, ParserOptions {
+      hooks: Some(hooks),
+      custom_media: true,
+      ..ParserOptions::default()
+    });
+    stylesheet.minify(MinifyOptions {
+      targets: Some(Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      }),
+      ..MinifyOptions::default()
+    });
+
+    let res = stylesheet.to_css(PrinterOptions {
+      minify: true,
+      analyze_dependencies: true,
+      ..PrinterOptions::default()
+    }).unwrap();
+    assert_eq!(res.code, "@import \"foo.css\";@media (max-width:30em){.foo{width:10px;background:url(a.png)}}");
+    assert_eq!(res.dependencies, Some(vec![
+      Dependency::Import("foo.css".into()),
+      Dependency::Url("a.png".into())
+    ]));
+
+    let res = stylesheet.to_css(PrinterOptions::default()).unwrap();
+    assert_eq!(res.dependencies, None);
+  }
+
+  #[test]
+  fn test_source_maps() {
+    use crate::source_map::{SourceMap, SourceMapError, Mapping, OriginalLocation};
+
+    let mapping = |generated_line, generated_column, line, column| Mapping {
+      generated_line,
+      generated_column,
+      original: Some(OriginalLocation { source: 0, line, column })
+    };
+
+    let print = |code: &str, input_source_map: Option<SourceMap>, banner: Option<String>| {
+      let stylesheet = StyleSheet::parse(code, ParserOptions {
+        filename: "a.css".into(),
+        input_source_map,
+        ..ParserOptions::default()
+      });
+      stylesheet.to_css(PrinterOptions {
+        minify: true,
+        source_map: true,
+        banner,
+        ..PrinterOptions::default()
+      }).unwrap()
+    };
+
+    let code = ".foo { color: red }\n  .bar { color: blue }";
+    let res = print(code, None, None);
+    assert_eq!(res.code, ".foo{color:red}.bar{color:#00f}");
+    let map = res.map.unwrap();
+    assert_eq!(map.sources, vec!["a.css".to_string()]);
+    assert_eq!(map.mappings, vec![mapping(0, 0, 0, 0), mapping(0, 15, 1, 2)]);
+    assert_eq!(map.to_json(Some("a.min.css")), r#"{"version":3,"file":"a.min.css","sources":["a.css"],"names":[],"mappings":"AAAA,eACE"}"#);
+
+    let res = print(code, None, Some("/*! v1.0.0 */".into()));
+    assert_eq!(res.map.unwrap().mappings, vec![mapping(1, 0, 0, 0), mapping(1, 15, 1, 2)]);
+
+    // The positions are remapped to the authored source through the input map.
+    let input = SourceMap::from_json(r#"{"version":3,"sourceRoot":"src","sources":["a.scss"],"sourcesContent":["..."],"names":[],"mappings":"AAEE;EAGA"}"#).unwrap();
+    let map = print(code, Some(input), None).map.unwrap();
+    assert_eq!(map.sources, vec!["src/a.scss".to_string()]);
+    assert_eq!(map.sources_content, vec![Some("...".to_string())]);
+    assert_eq!(map.mappings, vec![mapping(0, 0, 2, 2), mapping(0, 15, 5, 2)]);
+
+    let stylesheet = StyleSheet::parse(code, ParserOptions::default());
+    assert_eq!(stylesheet.to_css(PrinterOptions::default()).unwrap().map, None);
+
+    assert_eq!(SourceMap::from_json(r#"{"version":2,"sources":[],"mappings":""}"#), Err(SourceMapError::UnsupportedVersion));
+    assert_eq!(SourceMap::from_json(r#"{"version":3,"sources":["a.scss"],"mappings":"AA"}"#), Err(SourceMapError::InvalidMappings));
+    assert!(matches!(SourceMap::from_json("{}"), Err(SourceMapError::Json(_))));
+  }
+
+  #[test]
+  fn test_style_attribute() {
+    use crate::dependencies::Dependency;
+
+    let attr_test = |source: &str, expected: &str, minify: bool| {
+      let mut attr = StyleAttribute::parse(source);
+      attr.minify(MinifyOptions::default());
+      let res = attr.to_css(PrinterOptions { minify, ..PrinterOptions::default() }).unwrap();
+      assert_eq!(res.code, expected);
+    };
+
+    attr_test("color: #ff0000; width: calc(20px * 2)", "color:red;width:40px", true);
+    attr_test("color: #ff0000; width: calc(20px * 2)", "color: red; width: 40px", false);
+    attr_test("color:red;transform:translate(10px)", "color:red;transform:translate(10px)", true);
+    attr_test("margin-top: 1px; margin-right: 1px; margin-bottom: 1px; margin-left: 1px;", "margin:1px", true);
+    attr_test("color: red !important; color: green", "color:red!important", true);
+    attr_test("color: red; width: ; height: 10px", "color:red;height:10px", true);
+    attr_test("", "", true);
+
+    let attr = StyleAttribute::parse("background: url(a.png); color: red");
+    let res = attr.to_css(PrinterOptions { minify: true, analyze_dependencies: true, ..PrinterOptions::default() }).unwrap();
+    assert_eq!(res.dependencies, Some(vec![Dependency::Url("a.png".into())]));
+  }
+
+  #[test]
+  fn test_visitor() {
+    use crate::values::length::LengthValue;
+    use crate::values::url::Url;
+    use crate::values::color::CssColor;
+    use crate::declaration::Declaration;
+    use crate::parser::CssRule;
+
+    struct PxToRem;
+    impl Visitor for PxToRem {
+      fn visit_length(&mut self, length: &mut LengthValue) {
+        if let LengthValue::Px(px) = length {
+          *length = LengthValue::Rem(*px / 16.0);
+        }
+      }
+    }
+
+    struct Cdn;
+    impl Visitor for Cdn {
+      fn visit_url(&mut self, url: &mut Url) {
+        url.url = format!("https://cdn.example.com/{}", url.url);
+      }
+    }
+
+    #[derive(Default)]
+    struct Counter {
+      rules: usize,
+      declarations: usize,
+      colors: usize
+    }
+    impl Visitor for Counter {
+      fn visit_rule(&mut self, _rule: &mut CssRule) {
+        self.rules += 1;
+      }
+      fn visit_declaration(&mut self, _declaration: &mut Declaration) {
+        self.declarations += 1;
+      }
+      fn visit_color(&mut self, _color: &mut CssColor) {
+        self.colors += 1;
+      }
+    }
+
+    let visit_test = |source: &str, expected: &str, visitor: &mut dyn FnMut(&mut StyleSheet)| {
+      let mut stylesheet = StyleSheet::parse(source, ParserOptions::default());
+      visitor(&mut stylesheet);
+      stylesheet.minify(MinifyOptions::default());
+      let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+      assert_eq!(res.code, expected);
+    };
+
+    visit_test(".foo { width: 32px; margin: 8px 16px; font-size: calc(100% + 4px) }", ".foo{width:2rem;margin:.5rem 1rem;font-size:calc(100% + .25rem)}", &mut |s| s.visit(&mut PxToRem));
+    visit_test(".foo { box-shadow: 16px 16px red }", ".foo{box-shadow:1rem 1rem red}", &mut |s| s.visit(&mut PxToRem));
+    visit_test("@media (min-width: 100px) { .foo { transform: translate(16px, 50%) } }", "@media (min-width:100px){.foo{transform:translate(1rem,50%)}}", &mut |s| s.visit(&mut PxToRem));
+    visit_test(".foo { width: 2em }", ".foo{width:2em}", &mut |s| s.visit(&mut PxToRem));
+    visit_test(".foo { background: url(a.png) }", ".foo{background:url(https://cdn.example.com/a.png)}", &mut |s| s.visit(&mut Cdn));
+    visit_test(".foo { background-image: image-set(url(a.png) 1x, url(b.png) 2x) }", ".foo{background-image:image-set(url(https://cdn.example.com/a.png) 1x,url(https://cdn.example.com/b.png) 2x)}", &mut |s| s.visit(&mut Cdn));
+    visit_test("@font-face { font-family: Foo; src: url(foo.woff2) format(woff2) }", "@font-face{font-family:Foo;src:url(https://cdn.example.com/foo.woff2)format(woff2)}", &mut |s| s.visit(&mut Cdn));
+
+    let mut stylesheet = StyleSheet::parse(r#"
+      .foo { color: red; background: linear-gradient(yellow, blue) }
+      @media print {
+        .bar { border: 1px solid green }
+      }
+      @keyframes fade {
+        from { opacity: 0 }
+        to { opacity: 1 }
+      }
+    "#, ParserOptions::default());
+    let mut counter = Counter::default();
+    stylesheet.visit(&mut counter);
+    assert_eq!(counter.rules, 4);
+    assert_eq!(counter.declarations, 5);
+    // Includes the default transparent color of the background shorthand.
+    assert_eq!(counter.colors, 5);
+
+    let mut attr = StyleAttribute::parse("padding: 4px; color: red");
+    attr.visit(&mut PxToRem);
+    let res = attr.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+    assert_eq!(res.code, "padding:.25rem;color:red");
+  }
+
+  #[test]
+  #[cfg(feature = "browserslist")]
+  fn test_browserslist() {
+    let browsers = Browsers::from_browserslist(&["chrome 95", "and_chr 90", "safari 14.1", "ios_saf 15.2-15.3"]).unwrap().unwrap();
+    assert_eq!(browsers.chrome, Some(90 << 16));
+    assert_eq!(browsers.safari, Some(14 << 16 | 1 << 8));
+    assert_eq!(browsers.ios_saf, Some(15 << 16 | 2 << 8));
+    assert_eq!(browsers.firefox, None);
+    assert!(Browsers::from_browserslist(&["op_mini all"]).unwrap().is_none());
+    assert!(Browsers::from_browserslist(&["not a query"]).is_err());
+
+    let dir = std::env::temp_dir().join("parcel-css-browserslist-test");
+    let nested = dir.join("src");
+    std::fs::create_dir_all(&nested).unwrap();
+    std::fs::write(dir.join("package.json"), r#"{"browserslist": {"production": ["firefox 90"], "development": "chrome 95"}}"#).unwrap();
+    let browsers = Browsers::load_from_config(nested.join("index.css")).unwrap().unwrap();
+    assert_eq!(browsers.firefox, Some(90 << 16));
+    assert_eq!(browsers.chrome, None);
+
+    std::fs::write(dir.join(".browserslistrc"), "# comment\nie 11\n\n[production staging]\nedge 90\n").unwrap();
+    let browsers = Browsers::load_from_config(&nested).unwrap().unwrap();
+    assert_eq!(browsers.edge, Some(90 << 16));
+    assert_eq!(browsers.ie, None);
+    std::fs::remove_dir_all(&dir).unwrap();
+  }
+
+  #[test]
+  fn test_nesting() {
+    test(r#"
+      .foo {
+        color: red;
+        .bar {
+          color: blue;
+        }
+        &:hover {
+          color: green;
+        }
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: red;
+        & .bar {
+          color: #00f;
+        }
+        &:hover {
+          color: green;
+        }
+      }
+    "#});
+    minify_test(".foo { color: red; > .bar { color: blue } }", ".foo{color:red;&>.bar{color:#00f}}");
+    minify_test(".foo { a:hover { color: blue } }", ".foo{& a:hover{color:#00f}}");
+
+    let targets = Browsers {
+      chrome: Some(95 << 16),
+      ..Browsers::default()
+    };
+    prefix_test(r#"
+      .foo {
+        color: red;
+        .bar {
+          color: blue;
+        }
+        &:hover {
+          color: green;
+        }
+        .baz & {
+          color: blue;
+        }
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: red;
+      }
+
+      .foo .bar {
+        color: #00f;
+      }
+
+      .foo:hover {
+        color: green;
+      }
+
+      .baz .foo {
+        color: #00f;
+      }
+    "#}, targets);
+    prefix_test(r#"
+      .a, .b {
+        > .c {
+          color: red;
+          .d & {
+            color: green;
+          }
+        }
+      }
+    "#, indoc! {r#"
+      .a > .c, .b > .c {
+        color: red;
+      }
+
+      .d .a > .c, .d .b > .c {
+        color: green;
+      }
+    "#}, targets);
+    prefix_test(r#"
+      .a .b {
+        .c& {
+          color: red;
+        }
+      }
+    "#, indoc! {r#"
+      .c:is(.a .b) {
+        color: red;
+      }
+    "#}, targets);
+    prefix_test(r#"
+      .a, .b {
+        & + & {
+          color: red;
+        }
+      }
+    "#, indoc! {r#"
+      .a + .a, .a + .b, .b + .a, .b + .b {
+        color: red;
+      }
+    "#}, targets);
+    prefix_test(r#"
+      .foo {
+        color: red;
+        @media (min-width: 100px) {
+          color: blue;
+          .bar {
+            color: green;
+          }
+        }
+        &:hover {
+          color: green;
+        }
+      }
+    "#, indoc! {r#"
+      .foo {
+        color: red;
+      }
+
+      @media (min-width: 100px) {
+        .foo {
+          color: #00f;
+        }
+        .foo .bar {
+          color: green;
+        }
+      }
+
+      .foo:hover {
+        color: green;
+      }
+    "#}, targets);
+    prefix_test(r#"
+      @media print {
+        .foo {
+          @media (min-width: 100px) {
+            color: blue;
+          }
+        }
+      }
+    "#, indoc! {r#"
+      @media print and (min-width: 100px) {
+        .foo {
+          color: #00f;
+        }
+      }
+    "#}, targets);
+    minify_test(".foo { color: red; @media (min-width: 100px) { color: blue; .bar { color: green } } }", ".foo{color:red;@media (min-width:100px){color:#00f;& .bar{color:green}}}");
+  }
+
+  /// Serves files from memory for the bundler and batch tests.
+  struct TestProvider {
+    files: std::collections::HashMap<std::path::PathBuf, String>
+  }
+
+  impl TestProvider {
+    fn new(files: &[(&str, &str)]) -> TestProvider {
+      TestProvider {
+        files: files.iter().map(|(path, code)| (std::path::PathBuf::from(path), code.to_string())).collect()
+      }
+    }
+  }
+
+  impl crate::bundler::SourceProvider for TestProvider {
+    fn read(&self, file: &std::path::Path) -> std::io::Result<String> {
+      match self.files.get(file) {
+        Some(code) => Ok(code.clone()),
+        None => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "not found"))
+      }
+    }
+  }
+
+  #[test]
+  fn test_bundler() {
+    use crate::bundler::{Bundler, SourceProvider, BundleError};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Condvar, Mutex};
+    use std::time::Duration;
+
+    let provider = TestProvider::new(&[
+      ("/a.css", r#"@import "b.css"; @import "./dir/c.css" print; @import "d.css"; .a { color: red }"#),
+      ("/b.css", r#"@import "d.css"; .b { color: green }"#),
+      ("/dir/c.css", r#"@import "../b.css"; .c { color: blue }"#),
+      ("/d.css", ".d { width: 10px }")
+    ]);
+
+    let stylesheet = Bundler::new(&provider).bundle(Path::new("/a.css")).unwrap();
+    assert_eq!(stylesheet.to_css(PrinterOptions::default()).unwrap().code, indoc! {r#"
+      .d {
+        width: 10px;
+      }
+
+      .b {
+        color: green;
+      }
+
+      @media print {
+        .c {
+          color: #00f;
+        }
+      }
+
+      .a {
+        color: red;
+      }
+    "#});
+
+    // A file that was first imported under a media query is included again by a later import
+    // without one, including when the first import was nested within another file.
+    let provider = TestProvider::new(&[
+      ("/a.css", r#"@import "b.css" print; @import "c.css" screen; @import "b.css" print; @import "b.css"; @import "d.css";"#),
+      ("/b.css", ".b { color: green }"),
+      ("/c.css", r#"@import "d.css"; @import "c.css"; .c { color: blue }"#),
+      ("/d.css", r#"@import "c.css"; .d { width: 10px }"#)
+    ]);
+
+    let stylesheet = Bundler::new(&provider).bundle(Path::new("/a.css")).unwrap();
+    assert_eq!(stylesheet.to_css(PrinterOptions::default()).unwrap().code, indoc! {r#"
+      @media print {
+        .b {
+          color: green;
+        }
+      }
+
+      @media screen {
+        .d {
+          width: 10px;
+        }
+        .c {
+          color: #00f;
+        }
+      }
+
+      .b {
+        color: green;
+      }
+
+      .c {
+        color: #00f;
+      }
+
+      .d {
+        width: 10px;
+      }
+    "#});
+
+    let provider = TestProvider::new(&[
+      ("/a.css", r#"@import "missing.css";"#)
+    ]);
+    assert_eq!(Bundler::new(&provider).bundle(Path::new("/a.css")), Err(BundleError {
+      file: PathBuf::from("/missing.css"),
+      message: "not found".into()
+    }));
+
+    // Imports are read in parallel, so a read may complete before those of earlier imports.
+    // Each read waits until the reads before it in `order` have completed, which would never
+    // happen if the files were read one at a time.
+    struct OutOfOrderProvider {
+      files: TestProvider,
+      order: Vec<PathBuf>,
+      completed: Mutex<Vec<PathBuf>>,
+      condvar: Condvar
+    }
+
+    impl SourceProvider for OutOfOrderProvider {
+      fn read(&self, file: &Path) -> std::io::Result<String> {
+        if let Some(position) = self.order.iter().position(|path| path == file) {
+          let completed = self.completed.lock().unwrap();
+          let (mut completed, timeout) = self.condvar.wait_timeout_while(completed, Duration::from_secs(10), |completed| completed.len() < position).unwrap();
+          assert!(!timeout.timed_out(), "imports were not read in parallel");
+          completed.push(file.to_owned());
+          self.condvar.notify_all();
+        }
+        self.files.read(file)
+      }
+    }
+
+    let files = [
+      ("/a.css", r#"@import "b.css"; @import "c.css" print; @import "d.css"; .a { color: red }"#),
+      ("/b.css", ".b { color: green }"),
+      ("/c.css", ".c { color: blue }"),
+      ("/d.css", ".d { width: 10px }")
+    ];
+    let provider = OutOfOrderProvider {
+      files: TestProvider::new(&files),
+      order: vec![PathBuf::from("/d.css"), PathBuf::from("/c.css"), PathBuf::from("/b.css")],
+      completed: Mutex::new(vec![]),
+      condvar: Condvar::new()
+    };
+    let stylesheet = Bundler::new(&provider).bundle(Path::new("/a.css")).unwrap();
+    assert_eq!(*provider.completed.lock().unwrap(), provider.order);
+    let expected = Bundler::new(&TestProvider::new(&files)).bundle(Path::new("/a.css")).unwrap();
+    assert_eq!(stylesheet.to_css(PrinterOptions::default()).unwrap().code, expected.to_css(PrinterOptions::default()).unwrap().code);
+    assert_eq!(stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap().code, ".b{color:green}@media print{.c{color:#00f}}.d{width:10px}.a{color:red}");
+  }
+
+  #[test]
+  fn test_batch() {
+    use crate::batch::{process_batch, BatchOptions, BatchError, FileResult};
+    use crate::limits::{Limits, LimitKind};
+    use crate::warnings::{Warning, WarningKind};
+    use std::path::PathBuf;
+
+    let provider = TestProvider::new(&[
+      ("/a.css", ".a { color: red }"),
+      ("/b.css", ".b { width: foo; color: red }"),
+      ("/c.css", "@media print { @media print { .c { color: red } } }")
+    ]);
+
+    let files: Vec<PathBuf> = vec!["/a.css", "/missing.css", "/b.css", "/c.css"].into_iter().map(PathBuf::from).collect();
+    let summary = process_batch(&provider, &files, &BatchOptions {
+      minify: true,
+      limits: Limits {
+        max_block_depth: 2,
+        ..Limits::default()
+      },
+      ..BatchOptions::default()
+    });
+
+    assert_eq!(summary.files[0], FileResult {
+      file: PathBuf::from("/a.css"),
+      code: Some(".a{color:red}".into()),
+      error: None,
+      warnings: vec![]
+    });
+    assert_eq!(summary.files[1], FileResult {
+      file: PathBuf::from("/missing.css"),
+      code: None,
+      error: Some(BatchError::Read("not found".into())),
+      warnings: vec![]
+    });
+    assert_eq!(summary.files[2], FileResult {
+      file: PathBuf::from("/b.css"),
+      code: Some(".b{color:red}".into()),
+      error: None,
+      warnings: vec![Warning { kind: WarningKind::InvalidValue, line: 1, column: 13 }]
+    });
+    assert_eq!(summary.files[3].code, None);
+    assert!(matches!(&summary.files[3].error, Some(BatchError::Limit(err)) if err.kind == LimitKind::BlockDepth));
+    // The rule that exceeded the limit is also reported as skipped.
+    assert_eq!(summary.files[3].warnings, vec![Warning { kind: WarningKind::InvalidRule, line: 1, column: 35 }]);
+
+    assert_eq!(summary.error_count(), 2);
+    assert_eq!(summary.warning_count(), 2);
+    assert_eq!(summary.exit_code(), 1);
+
+    let summary = process_batch(&provider, &files[..1], &BatchOptions::default());
+    assert_eq!(summary.exit_code(), 0);
+  }
+
+  #[test]
+  fn test_limits() {
+    use crate::limits::{Limits, LimitKind};
+
+    let limits = Limits {
+      max_block_depth: 3,
+      max_calc_depth: 3,
+      max_selector_depth: 3,
+      ..Limits::default()
+    };
+
+    let nested = |depth: usize| format!("{}.foo {{ color: red }}{}", "@media print { ".repeat(depth), " }".repeat(depth));
+    assert!(StyleSheet::parse_with_limits(&nested(2), limits).is_ok());
+    assert_eq!(StyleSheet::parse_with_limits(&nested(3), limits).map(|_| ()).map_err(|e| (e.kind, e.line)), Err((LimitKind::BlockDepth, 1)));
+
+    let calc = |depth: usize| format!(".foo {{ width: {}1px{} }}", "calc(2 * ".repeat(depth), ")".repeat(depth));
+    assert!(StyleSheet::parse_with_limits(&calc(3), limits).is_ok());
+    assert_eq!(StyleSheet::parse_with_limits(&calc(4), limits).map(|_| ()).map_err(|e| e.kind), Err(LimitKind::CalcDepth));
+
+    let selector = |depth: usize| format!("{}.foo{} {{ color: red }}", ":not(".repeat(depth), ")".repeat(depth));
+    assert!(StyleSheet::parse_with_limits(&selector(3), limits).is_ok());
+    assert_eq!(StyleSheet::parse_with_limits(&selector(4), limits).map(|_| ()).map_err(|e| e.kind), Err(LimitKind::SelectorDepth));
+
+    // With the default limits, deeply nested constructs are dropped rather than overflowing the stack.
+    assert_eq!(StyleSheet::parse(&selector(100000), ParserOptions::default()).rules.len(), 0);
+    // Blocks left empty by dropping the nested constructs are dropped too.
+    assert_eq!(StyleSheet::parse(&nested(100000), ParserOptions::default()).rules.len(), 0);
+    minify_test(&format!("{}.foo {{ color: red }}{}", "@media print { ".repeat(100), " }".repeat(100)), "");
+    minify_test(&format!("{}@media print {{ .foo {{ color: red }} }}{}", "@supports (display: grid) { .bar { color: red } ".repeat(70), " }".repeat(70)), &format!("{}{}", "@supports (display: grid){.bar{color:red}".repeat(63), "}".repeat(63)));
+    minify_test(&calc(100000), ".foo{}");
+    minify_test(&format!(".foo {{ --foo: {}1px{} }}", "(".repeat(100000), ")".repeat(100000)), ".foo{}");
+    minify_test(&format!(".foo {{ width: {}env(a){} }}", "calc(".repeat(100000), ")".repeat(100000)), ".foo{}");
+    assert_eq!(StyleSheet::parse_with_limits(&format!(".foo {{ --foo: {}1px{} }}", "(".repeat(100), ")".repeat(100)), limits).map(|_| ()).map_err(|e| e.kind), Err(LimitKind::BlockDepth));
+  }
+
+  #[test]
+  fn test_custom_media() {
+    minify_test("@custom-media --narrow (max-width: 30em);", "@custom-media --narrow (max-width:30em);");
+    minify_test("@custom-media --print print and (color), screen;", "@custom-media --print print and (color),screen;");
+    minify_test("@custom-media narrow (max-width: 30em);", "");
+    custom_media_test(r#"
+      @custom-media --narrow (max-width: 30em);
+      @media (--narrow) {
+        .foo { color: red }
+      }
+    "#, "@media (max-width:30em){.foo{color:red}}");
+    custom_media_test(r#"
+      @custom-media --narrow (max-width: 30em);
+      @custom-media --color (color) and (--narrow);
+      @media screen and (--color) {
+        .foo { color: red }
+      }
+    "#, "@media screen and ((color) and (max-width:30em)){.foo{color:red}}");
+    custom_media_test(r#"
+      @custom-media --print print, (monochrome);
+      @media (--print), (--unknown) {
+        .foo { color: red }
+      }
+    "#, "@media print,(monochrome),(--unknown){.foo{color:red}}");
+    custom_media_test(r#"
+      @custom-media --a (--b);
+      @custom-media --b (--a);
+      @media not (--a) {
+        .foo { color: red }
+      }
+    "#, "@custom-media --a (--b);@custom-media --b (--a);@media not (--b){.foo{color:red}}");
+    // Definitions that can't be inlined into the referencing condition are kept.
+    custom_media_test(r#"
+      @custom-media --print print, (monochrome);
+      @custom-media --narrow (max-width: 30em);
+      @media (hover) and (--print) {
+        .foo { color: red }
+      }
+    "#, "@custom-media --print print,(monochrome);@media (hover) and (--print){.foo{color:red}}");
+  }
+
+  #[test]
+  fn test_media_minify() {
+    minify_test("@media print { .a { color: red } } @media print { .b { color: green } }", "@media print{.a{color:red}.b{color:green}}");
+    minify_test("@media print { .a { color: red } } .c {} @media print { .b { color: green } }", "@media print{.a{color:red}}.c{}@media print{.b{color:green}}");
+    minify_test("@media (width >= 600px) { .a { color: red } }", "@media (width>=600px){.a{color:red}}");
+    minify_test("@media (min-width: 600px) { .a { color: red } }", "@media (min-width:600px){.a{color:red}}");
+    minify_test("@media (600px <= width) { .a { color: red } }", "@media (width>=600px){.a{color:red}}");
+    minify_test("@media (600px < width <= 900px) { .a { color: red } }", "@media (width>600px) and (width<=900px){.a{color:red}}");
+    minify_test("@media (900px > width > 600px) { .a { color: red } }", "@media (width<900px) and (width>600px){.a{color:red}}");
+    minify_test("@media screen and (600px < width < 900px) { .a { color: red } }", "@media screen and (width>600px) and (width<900px){.a{color:red}}");
+    minify_test("@media not (600px < width < 900px) { .a { color: red } }", "@media not ((width>600px) and (width<900px)){.a{color:red}}");
+    minify_test("@media (600px < width < 900px) or (hover) { .a { color: red } }", "@media ((width>600px) and (width<900px)) or (hover){.a{color:red}}");
+    minify_test("@media screen { @media (min-width: 600px) { .a { color: red } } }", "@media screen and (min-width:600px){.a{color:red}}");
+    minify_test("@media (min-width: 600px) { @media (max-width: 900px) { .a { color: red } } }", "@media (min-width:600px) and (max-width:900px){.a{color:red}}");
+    minify_test("@media screen { @media print { .a { color: red } } }", "@media screen{@media print{.a{color:red}}}");
+    minify_test("@media not print { @media (color) { .a { color: red } } }", "@media not print{@media (color){.a{color:red}}}");
+    minify_test("@media screen { @media (color) or (hover) { .a { color: red } } }", "@media screen and ((color) or (hover)){.a{color:red}}");
+    minify_test("@media print, screen { @media (color) { .a { color: red } } }", "@media print and (color),screen and (color){.a{color:red}}");
+    minify_test("@media print { @media (color) { @media (hover) { .a { color: red } } } }", "@media print and (color) and (hover){.a{color:red}}");
+    minify_test("@media print { .a { color: red } @media (color) { .b { color: green } } .c { color: blue } }", "@media print{.a{color:red}}@media print and (color){.b{color:green}}@media print{.c{color:#00f}}");
+    minify_test("@media (hover) { .a { color: red } }", "@media (hover){.a{color:red}}");
+    minify_test("@media (hover: hover) { .a { color: red } }", "@media (hover){.a{color:red}}");
+    minify_test("@media (hover: none) { .a { color: red } }", "@media (hover:none){.a{color:red}}");
+    minify_test("@media (any-hover: hover) { .a { color: red } }", "@media (any-hover){.a{color:red}}");
+    minify_test("@media (grid: 1) { .a { color: red } }", "@media (grid){.a{color:red}}");
+    minify_test("@media (min-color: 1) { .a { color: red } }", "@media (color){.a{color:red}}");
+    minify_test("@media (min-color: 8) { .a { color: red } }", "@media (min-color:8){.a{color:red}}");
+    minify_test("@media (min-monochrome: 1) { .a { color: red } }", "@media (monochrome){.a{color:red}}");
+    minify_test("@media (color-index > 0) { .a { color: red } }", "@media (color-index){.a{color:red}}");
+    minify_test("@media (MIN-WIDTH: 600px) { .a { color: red } }", "@media (min-width:600px){.a{color:red}}");
+    minify_test("@media (max-width: 600px) { .a { color: red } }", "@media (max-width:600px){.a{color:red}}");
+    // Invalid queries never match, rather than making the rule unconditional.
+    minify_test("@media print, (min-width) { .a { color: red } }", "@media print,not all{.a{color:red}}");
+    minify_test("@media (600px < width > 900px) { .a { color: red } }", "@media not all{.a{color:red}}");
+    test("@media (hover: hover) { .a { color: red } }", indoc! {r#"
+      @media (hover: hover) {
+        .a {
+          color: red;
+        }
+      }
+    "#});
+
+    let old = Browsers {
+      chrome: Some(95 << 16),
+      ..Browsers::default()
+    };
+    let new = Browsers {
+      chrome: Some(110 << 16),
+      ..Browsers::default()
+    };
+
+    prefix_test("@media (width >= 600px) and (height <= 400px) { .a { color: red } }", indoc! {r#"
+      @media (min-width: 600px) and (max-height: 400px) {
+        .a {
+          color: red;
+        }
+      }
+    "#}, old);
+    prefix_test("@media (600px <= width < 900px) { .a { color: red } }", indoc! {r#"
+      @media (min-width: 600px) and (max-width: 899.999px) {
+        .a {
+          color: red;
+        }
+      }
+    "#}, old);
+    prefix_test("@media (width > 600px), (width < 40em), (width = 50px) { .a { color: red } }", indoc! {r#"
+      @media (min-width: 600.001px), (max-width: 39.999em), (width: 50px) {
+        .a {
+          color: red;
+        }
+      }
+    "#}, old);
+    prefix_test("@media (min-width: 600px) and (max-resolution: 2dppx) and (min-grid: 1) { .a { color: red } }", indoc! {r#"
+      @media (width >= 600px) and (resolution <= 2dppx) and (min-grid: 1) {
+        .a {
+          color: red;
+        }
+      }
+    "#}, new);
+
+    // Resolutions are only converted to the x unit if all targets support it.
+    let minify_prefix_test = |source: &str, expected: &str, targets: Browsers| {
+      let res = compile(source, ParserOptions::default(), MinifyOptions {
+        targets: Some(targets),
+        ..MinifyOptions::default()
+      }, PrinterOptions {
+        minify: true,
+        targets: Some(targets),
+        ..PrinterOptions::default()
+      });
+      assert_eq!(res, expected);
+    };
+    minify_prefix_test("@media (min-resolution: 192dpi) { .a { color: red } }", "@media (min-resolution:2x){.a{color:red}}", old);
+    minify_prefix_test("@media (resolution >= 1dppx) { .a { color: red } }", "@media (resolution>=1x){.a{color:red}}", new);
+    minify_prefix_test("@media (min-resolution: 192dpi) { .a { color: red } }", "@media (min-resolution:192dpi){.a{color:red}}", Browsers {
+      ie: Some(11 << 16),
+      ..Browsers::default()
+    });
+    minify_test("@media (min-resolution: 192dpi) { .a { color: red } }", "@media (min-resolution:192dpi){.a{color:red}}");
+  }
+
+  #[test]
+  fn test_size_limits() {
+    use crate::limits::{Limits, LimitKind};
+
+    let limits = Limits {
+      max_declarations: 2,
+      max_rules: 3,
+      max_selector_expansion: 4,
+      ..Limits::default()
+    };
+
+    let parse = |code: &str| StyleSheet::parse_with_limits(code, limits).map(|_| ()).map_err(|e| e.kind);
+    assert_eq!(parse(".a { color: red; width: 10px }"), Ok(()));
+    assert_eq!(parse(".a { color: red; width: 10px; height: 10px }"), Err(LimitKind::Declarations));
+    assert_eq!(parse(".a {} @media print { .b {} }"), Ok(()));
+    assert_eq!(parse(".a {} .b {} @media print { .c {} }"), Err(LimitKind::Rules));
+    assert_eq!(parse(".a {} .b { .c {} }"), Ok(()));
+    assert_eq!(parse(".a {} .b { .c {} .d {} }"), Err(LimitKind::Rules));
+    assert_eq!(parse(".a, .b { .c, .d { color: red } }"), Ok(()));
+    assert_eq!(parse(".a, .b { .c, .d { .e, .f { color: red } } }"), Err(LimitKind::SelectorExpansion));
+
+    let stylesheet = StyleSheet::parse(&".a { color: red }".repeat(5), ParserOptions::default());
+    assert_eq!(stylesheet.rules.len(), 5);
+  }
+
+  #[test]
+  fn test_duplicate_rules() {
+    minify_test(".a { color: red } .b { color: red }", ".a,.b{color:red}");
+    minify_test(".a { color: red } .a { background: green }", ".a{color:red;background:green}");
+    minify_test(".a { color: red } .b { color: green } .a { color: red }", ".b{color:green}.a{color:red}");
+    minify_test(".a { color: red } @media print { .b { color: green } } .a { color: red }", "@media print{.b{color:green}}.a{color:red}");
+    minify_test("@media print { .a { color: red } .b { color: green } .a { color: red } }", "@media print{.b{color:green}.a{color:red}}");
+    minify_test(".a { color: red; .b { color: green } } .a { color: red; .b { color: green } }", ".a{color:red;& .b{color:green}}.a{color:red;& .b{color:green}}");
+    minify_test(".a::-moz-selection { color: red } .a::selection { color: red }", ".a::-moz-selection{color:red}.a::selection{color:red}");
+    minify_test(".a:-moz-focusring { color: red } .a:focus { color: red }", ".a:-moz-focusring{color:red}.a:focus{color:red}");
+    minify_test(".a:not(:-moz-focusring) { color: red } .a:focus { color: red }", ".a:not(:-moz-focusring){color:red}.a:focus{color:red}");
+    minify_test(".a:hover { color: red } .a:focus { color: red }", ".a:hover,.a:focus{color:red}");
+
+    let unsafe_merge_test = |source: &str, expected: &str| {
+      let res = compile(source, ParserOptions::default(), MinifyOptions {
+        unsafe_merges: true,
+        ..MinifyOptions::default()
+      }, PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      });
+      assert_eq!(res, expected);
+    };
+    unsafe_merge_test(".a::-moz-selection { color: red } .a::selection { color: red }", ".a::-moz-selection,.a::selection{color:red}");
+    unsafe_merge_test(".a:-moz-focusring { color: red } .a:focus { color: red }", ".a:-moz-focusring,.a:focus{color:red}");
+  }
+
+  #[test]
+  fn test_shadowed_declarations() {
+    minify_test(".a { color: red; color: blue }", ".a{color:#00f}");
+    minify_test(".foo { color: red; color: green }", ".foo{color:green}");
+    minify_test(".foo { color: green; color: green }", ".foo{color:green}");
+    minify_test(".foo { color: red !important; color: green }", ".foo{color:red!important}");
+    minify_test(".foo { color: green; color: red !important }", ".foo{color:red!important}");
+    minify_test(".foo { color: red !important; color: green !important }", ".foo{color:green!important}");
+    minify_test(".foo { color: red; width: 10px; color: green }", ".foo{width:10px;color:green}");
+    minify_test(".foo { width: 10px; width: max(10px, 5vw) }", ".foo{width:max(10px,5vw)}");
+    minify_test(".foo { --x: 1px; --x: var(--y) }", ".foo{--x:var(--y)}");
+    // Values that are not fully parsed are kept, since these are commonly fallbacks.
+    minify_test(".foo { width: 10px; width: env(safe-area-inset-left) }", ".foo{width:10px;width:env(safe-area-inset-left)}");
+    minify_test(".foo { display: -webkit-box; display: flex }", ".foo{display:-webkit-box;display:flex}");
+    minify_test(".foo { display: grid; display: grid }", ".foo{display:grid}");
+    minify_test(".foo { position: relative; position: sticky }", ".foo{position:relative;position:sticky}");
+  }
+
+  #[test]
+  fn test_mask_composite() {
+    minify_test(".foo { mask-composite: subtract, add }", ".foo{mask-composite:subtract,add}");
+    minify_test(".foo { -webkit-mask-composite: source-out }", ".foo{-webkit-mask-composite:source-out}");
+    prefix_test(
+      ".foo { mask-composite: subtract, intersect }",
+      indoc! {r#"
+      .foo {
+        -webkit-mask-composite: source-out, source-in;
+        mask-composite: subtract, intersect;
+      }
+      "#},
+      Browsers {
+        chrome: Some(90 << 16),
+        ..Browsers::default()
+      }
+    );
+    prefix_test(
+      ".foo { -webkit-mask-composite: xor }",
+      indoc! {r#"
+      .foo {
+        -webkit-mask-composite: xor;
+        mask-composite: exclude;
+      }
+      "#},
+      Browsers {
+        safari: Some(14 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: radial-gradient(20px 40px, yellow, blue) }",
-      ".foo{background:radial-gradient(20px 40px,#ff0,#00f)}"
+    prefix_test(
+      ".foo { -webkit-mask-composite: source-in }",
+      indoc! {r#"
+      .foo {
+        mask-composite: intersect;
+      }
+      "#},
+      Browsers {
+        chrome: Some(120 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: radial-gradient(ellipse 20px 40px, yellow, blue) }",
-      ".foo{background:radial-gradient(20px 40px,#ff0,#00f)}"
+    prefix_test(
+      ".foo { -webkit-mask-composite: copy }",
+      indoc! {r#"
+      .foo {
+        -webkit-mask-composite: copy;
+      }
+      "#},
+      Browsers {
+        chrome: Some(120 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: radial-gradient(ellipse calc(20px + 10px) 40px, yellow, blue) }",
-      ".foo{background:radial-gradient(30px 40px,#ff0,#00f)}"
+  }
+
+  #[test]
+  fn test_number_serialization() {
+    use crate::values::number::serialize_number;
+    use crate::printer::Printer;
+
+    let serialize = |number: f32| {
+      let mut s = String::new();
+      serialize_number(number, &mut Printer::new(&mut s, true)).unwrap();
+      s
+    };
+
+    assert_eq!(serialize(0.0), "0");
+    assert_eq!(serialize(-0.0), "0");
+    assert_eq!(serialize(12.0), "12");
+    assert_eq!(serialize(-2147483520.0), "-2147483520");
+    assert_eq!(serialize(0.5), ".5");
+    assert_eq!(serialize(-0.5), "-.5");
+    assert_eq!(serialize(1.41421356), "1.41421");
+    assert_eq!(serialize(0.0123456789), ".0123457");
+    assert_eq!(serialize(123456.7), "123457");
+    assert_eq!(serialize(0.99999999), "1");
+    assert_eq!(serialize(1e-7), ".0000001");
+    assert_eq!(serialize(3e10), "30000000000");
+
+    minify_test(".foo { width: -0px; margin-left: -0.5em; transform: rotate(-0deg) }", ".foo{width:0;margin-left:-.5em;transform:rotate(0deg)}");
+    minify_test(".foo { width: 33.3333333% }", ".foo{width:33.3333%}");
+  }
+
+  #[test]
+  fn test_precision() {
+    // Rounding errors in matrices do not prevent them from being simplified.
+    minify_test(".foo{transform:matrix3d(1,0,0,0,0,1,0,0,0,0,1.0000001,0,0,0,0,1)}", ".foo{transform:translate(0)}");
+    minify_test(".foo{transform:matrix(1,.0000001,0,1,10,0)}", ".foo{transform:translate(10px)}");
+    minify_test(".foo{transform:matrix(1,0,.0000001,1,0,0)}", ".foo{transform:translate(0)}");
+    minify_test(".foo{transform:translate(10px) rotate(45deg) rotate(-45deg)}", ".foo{transform:translate(10px)}");
+
+    let minify = |code: &str, precision: u8| compile(code, ParserOptions::default(), MinifyOptions::default(), PrinterOptions {
+      minify: true,
+      precision: Some(precision),
+      ..PrinterOptions::default()
+    });
+
+    assert_eq!(minify(".foo { width: 33.3333333% }", 3), ".foo{width:33.3%}");
+    assert_eq!(minify(".foo { width: 1.23456789px }", 8), ".foo{width:1.2345679px}");
+    assert_eq!(minify(".foo { transform: rotate(12.3456deg) }", 3), ".foo{transform:rotate(12.3deg)}");
+  }
+
+  #[test]
+  fn test_length_units() {
+    use crate::values::length::{Length, LengthValue, LengthUnit};
+
+    fn convert_test(source: &str, expected: &str) {
+      let res = compile(source, ParserOptions::default(), MinifyOptions::default(), PrinterOptions {
+        minify: true,
+        ..PrinterOptions::default()
+      });
+      assert_eq!(res, expected);
+    }
+
+    convert_test(".foo { width: 96px }", ".foo{width:1in}");
+    convert_test(".foo { width: 0.5in }", ".foo{width:3pc}");
+    convert_test(".foo { width: 10mm }", ".foo{width:1cm}");
+    convert_test(".foo { width: 12px }", ".foo{width:9pt}");
+    convert_test(".foo { width: 10px }", ".foo{width:10px}");
+    convert_test(".foo { width: 3.75pt }", ".foo{width:5px}");
+    convert_test(".foo { width: 1.5em }", ".foo{width:1.5em}");
+    minify_test(".foo { width: 96px }", ".foo{width:1in}");
+    test(".foo { width: 96px }", ".foo {\n  width: 96px;\n}\n");
+
+    assert_eq!(LengthValue::Px(96.0).to_unit(LengthUnit::In), Some(LengthValue::In(1.0)));
+    assert_eq!(LengthValue::In(0.5).to_unit(LengthUnit::Pt), Some(LengthValue::Pt(36.0)));
+    assert_eq!(LengthValue::Pc(1.0).to_unit(LengthUnit::Px), Some(LengthValue::Px(16.0)));
+    assert_eq!(LengthValue::Em(1.0).to_unit(LengthUnit::Px), None);
+    assert_eq!(Length::px(48.0).to_unit(LengthUnit::In), Some(Length::Value(LengthValue::In(0.5))));
+  }
+
+  #[test]
+  fn test_stats() {
+    use crate::stats::{SelectorCounts, Specificity};
+
+    let stylesheet = StyleSheet::parse(r#"
+      .foo, #bar > a:hover { color: red; background: blue }
+      div::before, [type=text], * { color: green }
+      @media (min-width: 600px) {
+        .foo .bar:not(.baz) { color: red; -webkit-transition: opacity 1s }
+      }
+      @media (min-width: 600px) {
+        .qux { margin: 0 }
+      }
+      @media print {
+        .foo { color: black }
+      }
+    "#, ParserOptions::default());
+
+    let stats = stylesheet.stats();
+    assert_eq!(stats.style_rules, 5);
+    assert_eq!(stats.declarations, 7);
+    assert_eq!(stats.selectors, SelectorCounts {
+      selectors: 8,
+      type_selectors: 2,
+      universal: 1,
+      id: 1,
+      class: 6,
+      attribute: 1,
+      pseudo_class: 2,
+      pseudo_element: 1
+    });
+    assert_eq!(stats.properties, vec![
+      ("color".into(), 4),
+      ("background".into(), 1),
+      ("margin".into(), 1),
+      ("transition".into(), 1)
+    ]);
+    let specificity = |ids, classes, types| Specificity { ids, classes, types };
+    assert_eq!(stats.specificity, vec![
+      (specificity(0, 0, 0), 1),
+      (specificity(0, 0, 2), 1),
+      (specificity(0, 1, 0), 4),
+      (specificity(0, 3, 0), 1),
+      (specificity(1, 1, 1), 1)
+    ]);
+    assert_eq!(stats.media_queries, vec![
+      ("(min-width: 600px)".into(), 2),
+      ("print".into(), 1)
+    ]);
+  }
+
+  #[test]
+  fn test_rules_matching() {
+    use crate::traits::ToCss;
+
+    let stylesheet = StyleSheet::parse(r#"
+      .btn { color: red }
+      .nav > .btn.primary:hover, .link { color: green }
+      .btn-group, #btn { color: blue }
+      @media (min-width: 600px) {
+        a.btn::before { color: yellow }
+      }
+      .card {
+        & .btn { color: purple }
+      }
+      @supports (display: grid) {
+        .grid .btn { display: grid }
+      }
+    "#, ParserOptions::default());
+
+    let selectors = |query: &str| -> Option<Vec<String>> {
+      stylesheet.rules_matching(query).map(|rules| {
+        rules.iter().map(|rule| rule.selectors.to_css_string()).collect()
+      })
+    };
+
+    assert_eq!(selectors(".btn"), Some(vec![
+      ".btn".into(),
+      ".nav > .btn.primary:hover, .link".into(),
+      "a.btn:before".into(),
+      "& .btn".into(),
+      ".grid .btn".into()
+    ]));
+    assert_eq!(selectors(".primary.btn"), Some(vec![".nav > .btn.primary:hover, .link".into()]));
+    assert_eq!(selectors("a.btn"), Some(vec!["a.btn:before".into()]));
+    assert_eq!(selectors("#btn"), Some(vec![".btn-group, #btn".into()]));
+    assert_eq!(selectors(".missing"), Some(vec![]));
+    assert_eq!(selectors(".nav .btn"), None);
+    assert_eq!(selectors(".a, .b"), None);
+  }
+
+  #[test]
+  fn test_rename() {
+    use crate::traits::ToCss;
+
+    let mut stylesheet = StyleSheet::parse(r#"
+      .btn, .btn-group .btn:hover { color: red }
+      .nav:not(.btn) > #main.btn::before { color: green }
+      [class~=btn] { color: blue }
+      @media (min-width: 600px) {
+        .card .btn { color: yellow }
+      }
+      .card {
+        & .btn { color: purple }
+      }
+    "#, ParserOptions::default());
+
+    assert_eq!(stylesheet.rename_class("btn", "button"), 6);
+    assert_eq!(stylesheet.rename_id("main", "content"), 1);
+    assert_eq!(stylesheet.rename_class("missing", "other"), 0);
+
+    let rules = stylesheet.rules_matching(".button").unwrap();
+    assert_eq!(rules.iter().map(|rule| rule.selectors.to_css_string()).collect::<Vec<_>>(), vec![
+      ".button, .btn-group .button:hover",
+      ".nav:not(.button) > #content.button:before",
+      ".card .button",
+      "& .button"
+    ]);
+    assert_eq!(stylesheet.rules_matching(".btn").unwrap().len(), 0);
+
+    let mut stylesheet = StyleSheet::parse(r#"
+      @supports selector(.btn:has(> #icon)) and (display: grid) {
+        .btn { display: grid }
+        @media print {
+          .btn { color: red }
+        }
+      }
+    "#, ParserOptions::default());
+    assert_eq!(stylesheet.rename_class("btn", "button"), 3);
+    assert_eq!(stylesheet.rename_id("icon", "glyph"), 1);
+    assert_eq!(stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap().code,
+      "@supports selector(.button:has(> #glyph)) and (display: grid){.button{display:grid}@media print{.button{color:red}}}");
+  }
+
+  #[test]
+  fn test_preserve_comments() {
+    let print = |code: &str, minify: bool, preserve_comments: PreserveComments| {
+      let mut stylesheet = StyleSheet::parse(code, ParserOptions::default());
+      if minify {
+        stylesheet.minify(MinifyOptions::default());
+      }
+      stylesheet.to_css(PrinterOptions {
+        minify,
+        preserve_comments,
+        ..PrinterOptions::default()
+      }).unwrap().code
+    };
+
+    let code = "/*! License */\n.foo { color: red }\n/* Buttons */\n.bar { color: red } /* end */";
+    assert_eq!(print(code, true, PreserveComments::None), ".foo,.bar{color:red}");
+    assert_eq!(print(code, true, PreserveComments::License), "/*! License */.foo,.bar{color:red}");
+    assert_eq!(print(code, true, PreserveComments::All), "/*! License */.foo,.bar{color:red}");
+    assert_eq!(print(code, false, PreserveComments::All), indoc! {r#"
+      /*! License */
+
+      .foo {
+        color: red;
+      }
+
+      /* Buttons */
+
+      .bar {
+        color: red;
+      }
+
+      /* end */
+    "#});
+    assert_eq!(print(code, false, PreserveComments::License), indoc! {r#"
+      /*! License */
+
+      .foo {
+        color: red;
+      }
+
+      .bar {
+        color: red;
+      }
+    "#});
+
+    // Comments within rules are not kept, with or without minifying.
+    assert_eq!(print(".foo { /*! a */ color: red }", true, PreserveComments::All), ".foo{color:red}");
+    assert_eq!(print(".foo { color: red; /* a */ width: 1px }", false, PreserveComments::All), ".foo {\n  color: red;\n  width: 1px;\n}\n");
+    assert_eq!(print("@media print { /* a */ .foo { color: red } }", false, PreserveComments::All), "@media print {\n  .foo {\n    color: red;\n  }\n}\n");
+  }
+
+  #[test]
+  fn test_css_modules() {
+    let mut stylesheet = StyleSheet::parse(r#"
+      .foo { color: red; animation: fade 1s }
+      #main .foo:hover { list-style-type: stars }
+      @keyframes fade { from { opacity: 0 } }
+      @counter-style stars { system: cyclic; symbols: a }
+      [class~=foo] { color: blue; animation-name: other }
+    "#, ParserOptions {
+      filename: "test.css".into(),
+      css_modules: true,
+      ..ParserOptions::default()
+    });
+    stylesheet.minify(MinifyOptions::default());
+    let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+    assert_eq!(res.code, concat!(
+      ".foo_0e1a44{color:red;animation:fade_0e1a44 1s}#main_0e1a44 .foo_0e1a44:hover{list-style-type:stars_0e1a44}",
+      "@keyframes fade_0e1a44{0%{opacity:0}}@counter-style stars_0e1a44{system:cyclic;symbols:a}",
+      "[class~=foo]{color:#00f;animation-name:other}"
+    ));
+
+    let exports = res.exports.unwrap();
+    let mut names: Vec<_> = exports.iter().map(|(k, v)| (k.as_str(), v.name.as_str())).collect();
+    names.sort();
+    assert_eq!(names, vec![
+      ("fade", "fade_0e1a44"),
+      ("foo", "foo_0e1a44"),
+      ("main", "main_0e1a44"),
+      ("stars", "stars_0e1a44")
+    ]);
+    assert_eq!(serde_json::to_string(&exports["foo"]).unwrap(), r#"{"name":"foo_0e1a44","composes":[]}"#);
+
+    // The names only depend on the filename.
+    let other = StyleSheet::parse(".foo {}", ParserOptions {
+      filename: "other.css".into(),
+      css_modules: true,
+      ..ParserOptions::default()
+    });
+    assert_ne!(other.exports.unwrap()["foo"].name, "foo_0e1a44");
+
+
+    // Names within `:global()`, or after `:global` up to the next selector, are not scoped.
+    let modules = |code: &str| {
+      let stylesheet = StyleSheet::parse(code, ParserOptions {
+        filename: "test.css".into(),
+        css_modules: true,
+        ..ParserOptions::default()
+      });
+      let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+      let mut exports: Vec<_> = res.exports.unwrap().into_iter().map(|(k, v)| (k, v.name)).collect();
+      exports.sort();
+      (res.code, exports)
+    };
+    assert_eq!(modules(":global(.a) .b { color: red }"), (".a .b_0e1a44{color:red}".into(), vec![("b".into(), "b_0e1a44".into())]));
+    assert_eq!(modules(":global(.a) { color: red }"), (".a{color:red}".into(), vec![]));
+    assert_eq!(modules(".a :global(#b.c:hover) { color: red }"), (".a_0e1a44 #b.c:hover{color:red}".into(), vec![("a".into(), "a_0e1a44".into())]));
+    assert_eq!(modules(":global .a .b, .c { color: red }"), (".a .b,.c_0e1a44{color:red}".into(), vec![("c".into(), "c_0e1a44".into())]));
+    assert_eq!(modules(".a:global .b :local(.c) { color: red }"), (".a_0e1a44 .b .c_0e1a44{color:red}".into(), vec![("a".into(), "a_0e1a44".into()), ("c".into(), "c_0e1a44".into())]));
+    assert_eq!(modules(":local(.a) { color: red }"), (".a_0e1a44{color:red}".into(), vec![("a".into(), "a_0e1a44".into())]));
+    assert_eq!(modules(".a:not(:global(.b)) { color: red }"), (".a_0e1a44:not(.b){color:red}".into(), vec![("a".into(), "a_0e1a44".into())]));
+    // Outside of CSS modules, they are kept as written.
+    minify_test(":global(.a) .b { color: red }", ":global(.a) .b{color:red}");
+  }
+
+  #[test]
+  fn test_css_modules_composes() {
+    use crate::css_modules::CssModuleReference;
+    use crate::warnings::{Warning, WarningKind};
+
+    let mut stylesheet = StyleSheet::parse(r#"
+      .base { color: red }
+      .button, .link {
+        composes: base;
+        composes: reset from global;
+        composes: primary large from "./theme.css";
+        background: blue;
+      }
+      .card:hover { composes: base; color: green }
+    "#, ParserOptions {
+      filename: "test.css".into(),
+      css_modules: true,
+      ..ParserOptions::default()
+    });
+    stylesheet.minify(MinifyOptions::default());
+    let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+    assert_eq!(res.code, ".base_0e1a44{color:red}.button_0e1a44,.link_0e1a44{background:#00f}.card_0e1a44:hover{composes:base;color:green}");
+
+    let exports = res.exports.unwrap();
+    let composes = vec![
+      CssModuleReference::Local { name: "base_0e1a44".into() },
+      CssModuleReference::Global { name: "reset".into() },
+      CssModuleReference::Dependency { name: "primary".into(), specifier: "./theme.css".into() },
+      CssModuleReference::Dependency { name: "large".into(), specifier: "./theme.css".into() }
+    ];
+    assert_eq!(exports["button"].composes, composes);
+    assert_eq!(exports["link"].composes, composes);
+    assert_eq!(exports["base"].composes, vec![]);
+    // Composition is rejected unless each selector is a single class.
+    assert_eq!(exports["card"].composes, vec![]);
+    assert_eq!(
+      serde_json::to_string(&exports["link"].composes[2]).unwrap(),
+      r#"{"type":"dependency","name":"primary","specifier":"./theme.css"}"#
     );
-    minify_test(
-      ".foo { background: radial-gradient(circle farthest-side, yellow, blue) }",
-      ".foo{background:radial-gradient(circle farthest-side,#ff0,#00f)}"
+
+    let compile_module = |code: &str| {
+      let mut stylesheet = StyleSheet::parse(code, ParserOptions {
+        filename: "test.css".into(),
+        css_modules: true,
+        error_recovery: true,
+        ..ParserOptions::default()
+      });
+      let warnings = std::mem::take(&mut stylesheet.warnings);
+      stylesheet.minify(MinifyOptions::default());
+      let res = stylesheet.to_css(PrinterOptions { minify: true, ..PrinterOptions::default() }).unwrap();
+      (res.code, res.exports.unwrap(), warnings)
+    };
+
+    // Rules that only compose other classes are removed.
+    let (code, exports, warnings) = compile_module(".a { composes: b c }");
+    assert_eq!(code, "");
+    assert_eq!(exports["a"].composes, vec![
+      CssModuleReference::Local { name: "b_0e1a44".into() },
+      CssModuleReference::Local { name: "c_0e1a44".into() }
+    ]);
+    assert_eq!(warnings, vec![]);
+
+    let (code, exports, warnings) = compile_module(".a .b { composes: c }");
+    assert_eq!(code, ".a_0e1a44 .b_0e1a44{composes:c}");
+    assert!(!exports.contains_key("c"));
+    assert_eq!(warnings, vec![Warning { kind: WarningKind::InvalidComposes, line: 1, column: 1 }]);
+
+    // Outside of CSS modules, the declaration is kept.
+    minify_test(".foo { composes: a b from './a.css' }", ".foo{composes:a b from \"./a.css\"}");
+    minify_test(".foo { composes: a from global }", ".foo{composes:a from global}");
+  }
+
+  #[test]
+  fn test_custom_handlers() {
+    use crate::properties::Property;
+    use crate::traits::PropertyHandler;
+
+    // Keeps the first color in each block rather than the last.
+    #[derive(Default)]
+    struct FirstColorHandler {
+      color: Option<Property>
+    }
+
+    impl PropertyHandler for FirstColorHandler {
+      fn handle_property(&mut self, property: &Property) -> bool {
+        match property {
+          Property::Color(_) => {
+            if self.color.is_none() {
+              self.color = Some(property.clone());
+            }
+            true
+          }
+          _ => false
+        }
+      }
+
+      fn finalize(&mut self) -> Vec<Property> {
+        self.color.take().into_iter().collect()
+      }
+    }
+
+    let minify = |source: &str| compile(source, ParserOptions::default(), MinifyOptions {
+      custom_handlers: vec![Box::new(|| Box::new(FirstColorHandler::default()))],
+      ..MinifyOptions::default()
+    }, PrinterOptions {
+      minify: true,
+      ..PrinterOptions::default()
+    });
+
+    // Unhandled properties come first in their original order, followed by the output of the
+    // custom handlers, and then the built-in handlers.
+    assert_eq!(
+      minify(".foo { color: red; width: 10px; margin-left: 2px; color: blue; height: 10px }"),
+      ".foo{width:10px;height:10px;color:red;margin-left:2px}"
     );
-    minify_test(
-      ".foo { background: radial-gradient(farthest-side circle, yellow, blue) }",
-      ".foo{background:radial-gradient(circle farthest-side,#ff0,#00f)}"
+    assert_eq!(
+      minify(".foo { color: red; color: blue !important }"),
+      ".foo{color:red;color:#00f!important}"
     );
-    minify_test(
-      ".foo { background: radial-gradient(ellipse farthest-side, yellow, blue) }",
-      ".foo{background:radial-gradient(farthest-side,#ff0,#00f)}"
+    assert_eq!(
+      minify(".foo { color: red } .foo { color: blue }"),
+      ".foo{color:red}"
     );
-    minify_test(
-      ".foo { background: radial-gradient(farthest-side ellipse, yellow, blue) }",
-      ".foo{background:radial-gradient(farthest-side,#ff0,#00f)}"
+  }
+
+  #[test]
+  fn test_prefixed_property_ordering() {
+    // Prefixed variants with the same value are combined.
+    test(r#"
+      .foo {
+        -webkit-transform: rotate(10deg);
+        transform: rotate(10deg);
+        -webkit-transform: rotate(10deg);
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-transform: rotate(10deg);
+        transform: rotate(10deg);
+      }
+    "#});
+
+    // A later declaration with the same prefix overrides the value.
+    test(r#"
+      .foo {
+        transform: rotate(10deg);
+        transform: rotate(20deg);
+      }
+    "#, indoc! {r#"
+      .foo {
+        transform: rotate(20deg);
+      }
+    "#});
+
+    test(r#"
+      .foo {
+        -webkit-transform: rotate(10deg);
+        transform: rotate(10deg);
+        transform: rotate(20deg);
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-transform: rotate(10deg);
+        transform: rotate(20deg);
+      }
+    "#});
+
+    // A different value for a new prefix flushes what came before, to preserve the order.
+    test(r#"
+      .foo {
+        -webkit-box-shadow: 0 0 2px red;
+        box-shadow: 0 0 4px red;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-box-shadow: 0 0 2px red;
+        box-shadow: 0 0 4px red;
+      }
+    "#});
+
+    test(r#"
+      .foo {
+        -moz-border-radius: 10px;
+        -webkit-border-top-left-radius: 20px;
+        border-radius: 10px;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -moz-border-radius: 10px;
+        -webkit-border-top-left-radius: 20px;
+        border-radius: 10px;
+      }
+    "#});
+
+    prefix_test(r#"
+      .foo {
+        transition: opacity 200ms;
+        -webkit-transition-duration: 300ms;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-transition: opacity .2s;
+        transition: opacity .2s;
+        -webkit-transition-duration: .3s;
+      }
+    "#}, Browsers {
+      safari: Some(5 << 16),
+      ..Browsers::default()
+    });
+
+    prefix_test(r#"
+      .foo {
+        animation: foo 1s;
+        -webkit-animation-name: bar;
+      }
+    "#, indoc! {r#"
+      .foo {
+        -webkit-animation: foo 1s;
+        animation: foo 1s;
+        -webkit-animation-name: bar;
+      }
+    "#}, Browsers {
+      safari: Some(5 << 16),
+      ..Browsers::default()
+    });
+  }
+
+  #[test]
+  fn test_prefixes() {
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-transition: opacity 200ms;
+        -moz-transition: opacity 200ms;
+        transition: opacity 200ms;
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        transition: opacity .2s;
+      }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: repeating-radial-gradient(circle 20px, yellow, blue) }",
-      ".foo{background:repeating-radial-gradient(20px,#ff0,#00f)}"
+
+    prefix_test(
+      ".foo{transition:opacity 200ms}",
+      indoc! {r#"
+      .foo {
+        -webkit-transition: opacity .2s;
+        -moz-transition: opacity .2s;
+        transition: opacity .2s;
+      }
+      "#},
+      Browsers {
+        safari: Some(5 << 16),
+        firefox: Some(14 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: conic-gradient(#f06, gold) }",
-      ".foo{background:conic-gradient(#f06,gold)}"
+
+    // Prefixed declarations with a different value are removed when the unprefixed property
+    // overrides them, and none of the targets need the prefix.
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-border-radius: 20px;
+        -moz-border-radius: 20px;
+        border-radius: 30px;
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        border-radius: 30px;
+      }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        firefox: Some(90 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: conic-gradient(at 50% 50%, #f06, gold) }",
-      ".foo{background:conic-gradient(#f06,gold)}"
+
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-box-shadow: 0 0 2px red;
+        box-shadow: 0 0 4px red;
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        box-shadow: 0 0 4px red;
+      }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        firefox: Some(90 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: conic-gradient(from 0deg, #f06, gold) }",
-      ".foo{background:conic-gradient(#f06,gold)}"
+
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-transition: opacity 200ms;
+        transition: opacity 200ms, color 200ms;
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        transition: opacity .2s, color .2s;
+      }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        firefox: Some(90 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: conic-gradient(from 0deg at center, #f06, gold) }",
-      ".foo{background:conic-gradient(#f06,gold)}"
+
+    prefix_test(
+      r#"
+      .foo {
+        -moz-column-width: 100px;
+        column-width: 200px;
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        column-width: 200px;
+      }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        firefox: Some(90 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: conic-gradient(white -50%, black 150%) }",
-      ".foo{background:conic-gradient(#fff -50%,#000 150%)}"
+
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-border-top-left-radius: 20px;
+        border-radius: 30px;
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        border-radius: 30px;
+      }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: conic-gradient(white -180deg, black 540deg) }",
-      ".foo{background:conic-gradient(#fff -180deg,#000 540deg)}"
+
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-border-top-left-radius: 20px;
+        -webkit-border-bottom-right-radius: 10px;
+        border-radius: 30px;
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        border-radius: 30px;
+      }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: conic-gradient(from 45deg, white, black, white) }",
-      ".foo{background:conic-gradient(from 45deg,#fff,#000,#fff)}"
+
+    // The prefixes that a target still needs are output with the unprefixed value instead.
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-border-radius: 20px;
+        -moz-border-radius: 20px;
+        border-radius: 30px;
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        -webkit-border-radius: 30px;
+        border-radius: 30px;
+      }
+      "#},
+      Browsers {
+        safari: Some(4 << 16),
+        firefox: Some(90 << 16),
+        ..Browsers::default()
+      }
     );
-    minify_test(
-      ".foo { background: repeating-conic-gradient(from 45deg, white, black, white) }",
-      ".foo{background:repeating-conic-gradient(from 45deg,#fff,#000,#fff)}"
+
+    // Prefixed shorthands output before a flush are also removed, so minifying again doesn't change the result.
+    let targets = Browsers {
+      chrome: Some(95 << 16),
+      firefox: Some(90 << 16),
+      ..Browsers::default()
+    };
+    let minify_twice = |source: &str| {
+      let options = || (MinifyOptions {
+        targets: Some(targets),
+        ..MinifyOptions::default()
+      }, PrinterOptions {
+        minify: true,
+        targets: Some(targets),
+        ..PrinterOptions::default()
+      });
+      let (minify_options, printer_options) = options();
+      let first = compile(source, ParserOptions::default(), minify_options, printer_options);
+      let (minify_options, printer_options) = options();
+      let second = compile(&first, ParserOptions::default(), minify_options, printer_options);
+      assert_eq!(first, second);
+      first
+    };
+    assert_eq!(
+      minify_twice(".foo { -moz-border-radius: 10px; -webkit-border-top-left-radius: 20px; border-radius: 10px }"),
+      ".foo{border-radius:10px}"
     );
-    minify_test(
-      ".foo { background: repeating-conic-gradient(black 0deg 25%, white 0deg 50%) }",
-      ".foo{background:repeating-conic-gradient(#000 0deg 25%,#fff 0deg 50%)}"
+    assert_eq!(
+      minify_twice(".foo { -moz-border-top-left-radius: 10px; -webkit-border-top-left-radius: 20px; border-top-left-radius: 10px }"),
+      ".foo{border-top-left-radius:10px}"
     );
-  }
 
-  #[test]
-  fn test_font_face() {
-    minify_test(r#"@font-face {
-      src: url("test.woff");
-      font-family: "Helvetica";
-      font-weight: bold;
-      font-style: italic;
-    }"#, "@font-face{src:url(test.woff);font-family:Helvetica;font-weight:700;font-style:italic}");
-    minify_test("@font-face {src: url(test.woff);}", "@font-face{src:url(test.woff)}");
-    minify_test("@font-face {src: local(\"Test\");}", "@font-face{src:local(Test)}");
-    minify_test("@font-face {src: local(\"Foo Bar\");}", "@font-face{src:local(Foo Bar)}");
-    minify_test("@font-face {src: local(Test);}", "@font-face{src:local(Test)}");
-    minify_test("@font-face {src: local(Foo Bar);}", "@font-face{src:local(Foo Bar)}");
-    minify_test("@font-face {src: url(\"test.woff\") format(woff);}", "@font-face{src:url(test.woff)format(woff)}");
-    minify_test("@font-face {src: url(\"test.woff\") format(woff), url(test.ttf) format(truetype);}", "@font-face{src:url(test.woff)format(woff),url(test.ttf)format(truetype)}");
-    minify_test("@font-face {src: url(\"test.woff\") format(woff supports features(opentype));}", "@font-face{src:url(test.woff)format(woff supports features(opentype))}");
-    minify_test("@font-face {src: url(\"test.woff\") format(woff supports color(COLRv1));}", "@font-face{src:url(test.woff)format(woff supports color(colrv1))}");
-    minify_test("@font-face {src: url(\"test.woff\") format(woff supports variations);}", "@font-face{src:url(test.woff)format(woff supports variations)}");
-    minify_test("@font-face {src: url(\"test.woff\") format(woff supports palettes);}", "@font-face{src:url(test.woff)format(woff supports palettes)}");
-    minify_test("@font-face {src: url(\"test.woff\") format(woff supports features(opentype) color(sbix));}", "@font-face{src:url(test.woff)format(woff supports features(opentype) color(sbix))}");
+    // A prefixed declaration after the unprefixed one takes precedence, so it is kept.
+    prefix_test(
+      r#"
+      .foo {
+        border-radius: 30px;
+        -webkit-border-radius: 20px;
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        border-radius: 30px;
+        -webkit-border-radius: 20px;
+      }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
+        ..Browsers::default()
+      }
+    );
   }
 
   #[test]
-  fn test_page_rule() {
-    minify_test("@page {margin: 0.5cm}", "@page{margin:.5cm}");
-    minify_test("@page :left {margin: 0.5cm}", "@page:left{margin:.5cm}");
-    minify_test("@page :right {margin: 0.5cm}", "@page:right{margin:.5cm}");
-    minify_test("@page LandscapeTable {margin: 0.5cm}", "@page LandscapeTable{margin:.5cm}");
-    minify_test("@page CompanyLetterHead:first {margin: 0.5cm}", "@page CompanyLetterHead:first{margin:.5cm}");
-    minify_test("@page:first {margin: 0.5cm}", "@page:first{margin:.5cm}");
-    minify_test("@page :blank:first {margin: 0.5cm}", "@page:blank:first{margin:.5cm}");
-    minify_test("@page toc, index {margin: 0.5cm}", "@page toc,index{margin:.5cm}");
-  }
+  fn test_transform_prefixes() {
+    prefix_test(
+      r#"
+      .foo {
+        transform: rotate(10deg);
+        transform-style: preserve-3d;
+        backface-visibility: hidden;
+        perspective: 100px;
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        -webkit-transform: rotate(10deg);
+        -ms-transform: rotate(10deg);
+        transform: rotate(10deg);
+        -webkit-transform-style: preserve-3d;
+        transform-style: preserve-3d;
+        -webkit-backface-visibility: hidden;
+        backface-visibility: hidden;
+        -webkit-perspective: 100px;
+        perspective: 100px;
+      }
+      "#},
+      Browsers {
+        safari: Some(8 << 16),
+        ie: Some(9 << 16),
+        ..Browsers::default()
+      }
+    );
 
-  #[test]
-  fn test_prefixes() {
+    // Prefixed declarations with the same value are combined, and removed when not needed by the targets.
     prefix_test(
       r#"
       .foo {
-        -webkit-transition: opacity 200ms;
-        -moz-transition: opacity 200ms;
-        transition: opacity 200ms;
+        -webkit-transform: rotate(10deg);
+        -moz-transform: rotate(10deg);
+        -ms-transform: rotate(10deg);
+        -o-transform: rotate(10deg);
+        transform: rotate(10deg);
+        -webkit-backface-visibility: hidden;
+        backface-visibility: hidden;
+        -webkit-perspective: 100px;
+        -moz-perspective: 100px;
+        perspective: 100px;
       }
       "#,
       indoc! {r#"
       .foo {
-        transition: opacity .2s;
+        transform: rotate(10deg);
+        backface-visibility: hidden;
+        perspective: 100px;
       }
       "#},
       Browsers {
         chrome: Some(95 << 16),
+        firefox: Some(90 << 16),
         ..Browsers::default()
       }
     );
 
     prefix_test(
-      ".foo{transition:opacity 200ms}",
+      r#"
+      .foo {
+        -webkit-transform: rotate(10deg);
+        transform: rotate(10deg);
+      }
+      "#,
       indoc! {r#"
       .foo {
-        -webkit-transition: opacity .2s;
-        -moz-transition: opacity .2s;
-        transition: opacity .2s;
+        -webkit-transform: rotate(10deg);
+        -moz-transform: rotate(10deg);
+        transform: rotate(10deg);
       }
       "#},
       Browsers {
-        safari: Some(5 << 16),
-        firefox: Some(14 << 16),
+        firefox: Some(15 << 16),
+        safari: Some(8 << 16),
+        ..Browsers::default()
+      }
+    );
+
+    // A prefixed declaration with a different value is overridden by the unprefixed one, which is
+    // output with the prefixes the targets need.
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-transform: rotate(10deg);
+        transform: rotate(20deg);
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        -webkit-transform: rotate(20deg);
+        transform: rotate(20deg);
+      }
+      "#},
+      Browsers {
+        safari: Some(8 << 16),
+        ..Browsers::default()
+      }
+    );
+
+    prefix_test(
+      r#"
+      .foo {
+        -webkit-transform: rotate(10deg);
+        transform: rotate(20deg);
+      }
+      "#,
+      indoc! {r#"
+      .foo {
+        transform: rotate(20deg);
+      }
+      "#},
+      Browsers {
+        chrome: Some(95 << 16),
         ..Browsers::default()
       }
     );
@@ -0,0 +1,204 @@
+use cssparser::*;
+use std::cell::RefCell;
+use crate::error::ParserError;
+
+/// Limits on how deeply constructs may be nested within a stylesheet, and on its size.
+/// The parser is recursive, so without these, adversarial inputs could overflow the stack
+/// or use an unbounded amount of memory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Limits {
+  /// The maximum depth of nested rule blocks, e.g. style rules within `@media` rules
+  /// or nested style rules.
+  pub max_block_depth: usize,
+  /// The maximum depth of nested math functions and parentheses within `calc()`.
+  pub max_calc_depth: usize,
+  /// The maximum depth of nested functions such as `:not()` within a selector.
+  pub max_selector_depth: usize,
+  /// The maximum number of declarations within a single style rule.
+  pub max_declarations: usize,
+  /// The maximum number of rules in the stylesheet, including nested rules.
+  pub max_rules: usize,
+  /// The maximum number of selectors a nested style rule may expand to when combined
+  /// with its parents, e.g. when the nesting selector is replaced with `:is()` or the
+  /// parent selector list for older browsers.
+  pub max_selector_expansion: usize
+}
+
+impl Default for Limits {
+  fn default() -> Limits {
+    Limits {
+      max_block_depth: 64,
+      max_calc_depth: 32,
+      max_selector_depth: 32,
+      max_declarations: 10000,
+      max_rules: 1000000,
+      max_selector_expansion: 10000
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LimitKind {
+  BlockDepth,
+  CalcDepth,
+  SelectorDepth,
+  Declarations,
+  Rules,
+  SelectorExpansion
+}
+
+/// An error returned when a stylesheet exceeds one of the configured limits.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LimitError {
+  pub kind: LimitKind,
+  /// The line where the limit was exceeded, starting at 1.
+  pub line: u32,
+  /// The column where the limit was exceeded, starting at 1.
+  pub column: u32
+}
+
+#[derive(Default)]
+struct State {
+  limits: Limits,
+  depths: [usize; 3],
+  /// Whether a limit was exceeded and the parser is still unwinding out of the construct.
+  /// While set, entering the same kind of construct fails immediately, so that parsers
+  /// which try several alternatives don't retry the rest of the input at each level.
+  unwinding: [bool; 3],
+  /// The number of times a limit was exceeded.
+  exceeded: usize,
+  rules: usize,
+  error: Option<LimitError>
+}
+
+impl State {
+  fn max(&self, kind: LimitKind) -> usize {
+    match kind {
+      LimitKind::BlockDepth => self.limits.max_block_depth,
+      LimitKind::CalcDepth => self.limits.max_calc_depth,
+      LimitKind::SelectorDepth => self.limits.max_selector_depth,
+      LimitKind::Declarations => self.limits.max_declarations,
+      LimitKind::Rules => self.limits.max_rules,
+      LimitKind::SelectorExpansion => self.limits.max_selector_expansion
+    }
+  }
+
+  fn exceeded<'i, 't>(&mut self, input: &Parser<'i, 't>, kind: LimitKind) -> ParseError<'i, ParserError<'i>> {
+    self.exceeded += 1;
+    if self.error.is_none() {
+      let location = input.current_source_location();
+      self.error = Some(LimitError {
+        kind,
+        line: location.line + 1,
+        column: location.column
+      });
+    }
+    input.new_custom_error(ParserError::LimitExceeded(kind))
+  }
+}
+
+// The limits are tracked per thread rather than passed through the parser, since values
+// are parsed through the `Parse` trait, which has no context parameter.
+thread_local! {
+  static STATE: RefCell<State> = RefCell::new(State::default());
+}
+
+/// Runs the given function with the given limits, and returns its result along with
+/// the first limit that was exceeded, if any.
+pub fn with_limits<T, F: FnOnce() -> T>(limits: Limits, f: F) -> (T, Option<LimitError>) {
+  let prev = STATE.with(|state| std::mem::replace(&mut *state.borrow_mut(), State {
+    limits,
+    ..State::default()
+  }));
+  let res = f();
+  let state = STATE.with(|state| std::mem::replace(&mut *state.borrow_mut(), prev));
+  (res, state.error)
+}
+
+/// Keeps the depth for a kind of construct incremented until dropped.
+pub struct DepthGuard(LimitKind);
+
+impl Drop for DepthGuard {
+  fn drop(&mut self) {
+    STATE.with(|state| {
+      let mut state = state.borrow_mut();
+      let kind = self.0 as usize;
+      state.depths[kind] -= 1;
+      if state.depths[kind] == 0 {
+        state.unwinding[kind] = false;
+      }
+    })
+  }
+}
+
+/// Enters a nested construct. Returns an error if this exceeds the depth limit for its kind,
+/// in which case the construct should be treated as invalid.
+pub fn enter<'i, 't>(input: &Parser<'i, 't>, kind: LimitKind) -> Result<DepthGuard, ParseError<'i, ParserError<'i>>> {
+  STATE.with(|state| {
+    let mut state = state.borrow_mut();
+    let index = kind as usize;
+    if state.unwinding[index] || state.depths[index] >= state.max(kind) {
+      state.unwinding[index] = state.depths[index] > 0;
+      return Err(state.exceeded(input, kind))
+    }
+
+    state.depths[index] += 1;
+    Ok(DepthGuard(kind))
+  })
+}
+
+/// Returns an error if the given count exceeds the size limit for its kind.
+pub fn check<'i, 't>(input: &Parser<'i, 't>, kind: LimitKind, count: usize) -> Result<(), ParseError<'i, ParserError<'i>>> {
+  STATE.with(|state| {
+    let mut state = state.borrow_mut();
+    if count > state.max(kind) {
+      return Err(state.exceeded(input, kind))
+    }
+    Ok(())
+  })
+}
+
+/// Returns the number of times a limit was exceeded so far. Parsers that fall back to another
+/// representation when a value is invalid compare this before and after, so that values which
+/// exceeded a limit are dropped rather than kept in the fallback.
+pub fn exceeded_count() -> usize {
+  STATE.with(|state| state.borrow().exceeded)
+}
+
+/// Returns whether a limit was exceeded since `exceeded_count` returned the given count.
+pub fn exceeded_since(exceeded_count: usize) -> bool {
+  self::exceeded_count() != exceeded_count
+}
+
+/// Counts a parsed rule towards the maximum number of rules in the stylesheet.
+pub fn count_rule<'i, 't>(input: &Parser<'i, 't>) -> Result<(), ParseError<'i, ParserError<'i>>> {
+  let count = STATE.with(|state| {
+    let mut state = state.borrow_mut();
+    state.rules += 1;
+    state.rules
+  });
+  check(input, LimitKind::Rules, count)
+}
+
+/// Checks that the functions and blocks within a selector are not nested too deeply
+/// before it is parsed by the (recursive) selector parser.
+pub fn check_selector_depth<'i, 't>(input: &mut Parser<'i, 't>) -> Result<(), ParseError<'i, ParserError<'i>>> {
+  check_depth(input, LimitKind::SelectorDepth)
+}
+
+/// Checks that the functions and blocks within the input are not nested deeper than the
+/// limit for the given kind of construct.
+pub fn check_depth<'i, 't>(input: &mut Parser<'i, 't>, kind: LimitKind) -> Result<(), ParseError<'i, ParserError<'i>>> {
+  loop {
+    let is_block = match input.next_including_whitespace() {
+      Ok(Token::Function(_)) | Ok(Token::ParenthesisBlock) | Ok(Token::SquareBracketBlock) => true,
+      Ok(_) => false,
+      Err(_) => return Ok(())
+    };
+
+    if is_block {
+      let _guard = enter(input, kind)?;
+      input.parse_nested_block(|input| check_depth(input, kind))?;
+    }
+  }
+}
@@ -8,7 +8,7 @@ macro_rules! enum_property {
     }
 
     impl Parse for $name {
-      fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+      fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, $crate::error::ParserError<'i>>> {
         let ident = input.expect_ident()?;
         match &ident[..] {
           $(
@@ -50,7 +50,7 @@ macro_rules! enum_property {
     }
 
     impl Parse for $name {
-      fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+      fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, $crate::error::ParserError<'i>>> {
         let ident = input.expect_ident()?;
         match &ident[..] {
           $(
@@ -86,3 +86,16 @@ macro_rules! enum_property {
 }
 
 pub(crate) use enum_property;
+
+/// Logs a decision made while minifying, e.g. that a declaration was merged into a shorthand
+/// or dropped, when the `trace` feature is enabled. Otherwise, the arguments are not evaluated.
+macro_rules! trace {
+  ($($arg: tt)*) => {
+    {
+      #[cfg(feature = "trace")]
+      tracing::trace!($($arg)*);
+    }
+  };
+}
+
+pub(crate) use trace;
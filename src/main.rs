@@ -0,0 +1,148 @@
+use clap::Parser;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use parcel_css::bundler::{Bundler, FileProvider, SourceProvider};
+use parcel_css::{Browsers, MinifyOptions, ParserOptions, PreserveComments, PrinterOptions, StyleSheet};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Minifies and transforms CSS files for the given browser targets.
+#[derive(Parser, Debug)]
+#[clap(name = "css-transformer", version)]
+struct Cli {
+  /// The file to read, or `-` to read from stdin.
+  input_file: Option<PathBuf>,
+  /// The file to write the output to. Defaults to stdout.
+  #[clap(short, long)]
+  output_file: Option<PathBuf>,
+  /// Minify the output.
+  #[clap(short, long)]
+  minify: bool,
+  /// A browserslist query for the browsers to generate output for, e.g. `last 2 versions`.
+  /// Defaults to the browserslist config that applies to the input file, if any.
+  #[clap(short, long)]
+  targets: Option<String>,
+  /// Write a source map next to the output file, and reference it from the output.
+  #[clap(long, requires = "output-file")]
+  sourcemap: bool,
+  /// Inline the files referenced by `@import` rules.
+  #[clap(short, long)]
+  bundle: bool,
+  /// Don't print the input and output sizes.
+  #[clap(short, long)]
+  quiet: bool
+}
+
+/// Reads files from the file system, and counts the number of bytes read
+/// so that the input size of a bundle can be reported.
+struct CountingProvider {
+  bytes: AtomicUsize
+}
+
+impl SourceProvider for CountingProvider {
+  fn read(&self, file: &Path) -> std::io::Result<String> {
+    let code = FileProvider.read(file)?;
+    self.bytes.fetch_add(code.len(), Ordering::Relaxed);
+    Ok(code)
+  }
+}
+
+fn main() {
+  let cli = Cli::parse();
+  if let Err(err) = run(cli) {
+    eprintln!("error: {}", err);
+    std::process::exit(1);
+  }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+  let input_file = cli.input_file.filter(|file| file.as_os_str() != "-");
+  let targets = match (&cli.targets, &input_file) {
+    (Some(query), _) => Browsers::from_browserslist([query]).map_err(|err| err.to_string())?,
+    (None, Some(file)) => Browsers::load_from_config(file).map_err(|err| err.to_string())?,
+    (None, None) => Browsers::load_from_config(".").map_err(|err| err.to_string())?
+  };
+
+  let filename = input_file.as_ref().map_or("stdin".into(), |file| file.to_string_lossy().into_owned());
+  let (mut stylesheet, input_size) = if cli.bundle {
+    let file = input_file.as_ref().ok_or("--bundle requires an input file")?;
+    let provider = CountingProvider { bytes: AtomicUsize::new(0) };
+    let stylesheet = Bundler::new(&provider).bundle(file)
+      .map_err(|err| format!("{}: {}", err.file.display(), err.message))?;
+    (stylesheet, provider.bytes.into_inner())
+  } else {
+    let code = match &input_file {
+      Some(file) => std::fs::read_to_string(file).map_err(|err| format!("{}: {}", file.display(), err))?,
+      None => {
+        let mut code = String::new();
+        std::io::stdin().read_to_string(&mut code).map_err(|err| err.to_string())?;
+        code
+      }
+    };
+    let stylesheet = StyleSheet::parse(&code, ParserOptions {
+      filename,
+      ..ParserOptions::default()
+    });
+    (stylesheet, code.len())
+  };
+
+  for warning in &stylesheet.warnings {
+    eprintln!("warning: {}", warning);
+  }
+
+  stylesheet.minify(MinifyOptions {
+    targets,
+    ..MinifyOptions::default()
+  });
+  let res = stylesheet.to_css(PrinterOptions {
+    minify: cli.minify,
+    targets,
+    source_map: cli.sourcemap,
+    preserve_comments: PreserveComments::License,
+    ..PrinterOptions::default()
+  }).map_err(|_| "the stylesheet could not be printed".to_string())?;
+
+  let mut code = res.code;
+  match &cli.output_file {
+    Some(output_file) => {
+      if let Some(map) = res.map {
+        let name = output_file.file_name().unwrap().to_string_lossy();
+        let map_file = output_file.with_file_name(format!("{}.map", name));
+        std::fs::write(&map_file, map.to_json(Some(&name)))
+          .map_err(|err| format!("{}: {}", map_file.display(), err))?;
+        code.push_str(&format!("\n/*# sourceMappingURL={}.map */\n", name));
+      }
+      std::fs::write(output_file, &code).map_err(|err| format!("{}: {}", output_file.display(), err))?;
+    }
+    None => {
+      let mut stdout = std::io::stdout();
+      stdout.write_all(code.as_bytes()).and_then(|_| stdout.write_all(b"\n")).map_err(|err| err.to_string())?;
+    }
+  }
+
+  if !cli.quiet {
+    print_stats(input_size, &code);
+  }
+
+  Ok(())
+}
+
+/// Prints the input and output sizes to stderr, so they don't mix with the output
+/// when it is written to stdout.
+fn print_stats(input_size: usize, code: &str) {
+  let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+  let gzip_size = encoder.write_all(code.as_bytes())
+    .and_then(|_| encoder.finish())
+    .map(|gzipped| gzipped.len())
+    .unwrap_or(0);
+
+  let saved = if input_size > 0 {
+    100.0 - code.len() as f64 / input_size as f64 * 100.0
+  } else {
+    0.0
+  };
+
+  eprintln!("input:  {} bytes", input_size);
+  eprintln!("output: {} bytes ({:.1}% smaller), {} bytes gzipped", code.len(), saved, gzip_size);
+}
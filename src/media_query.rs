@@ -2,7 +2,12 @@ use cssparser::*;
 use crate::traits::{ToCss, Parse};
 use crate::printer::Printer;
 use crate::macros::enum_property;
+use crate::values::resolution::Resolution;
+use crate::compat;
 use std::fmt::Write;
+use std::collections::{HashMap, HashSet};
+use crate::error::ParserError;
+use crate::warnings::{self, WarningKind};
 
 /// A type that encapsulates a media query list.
 #[derive(Clone, Debug, PartialEq)]
@@ -20,26 +25,21 @@ impl MediaList {
   ///
   /// <https://drafts.csswg.org/mediaqueries/#error-handling>
   pub fn parse(input: &mut Parser) -> Self {
-      // if input.is_exhausted() {
-      //     return Self::empty();
-      // }
+      if input.is_exhausted() {
+          return MediaList { media_queries: vec![] };
+      }
 
       let mut media_queries = vec![];
       loop {
-          let start_position = input.position();
           match input.parse_until_before(Delimiter::Comma, |i| MediaQuery::parse(i)) {
               Ok(mq) => {
                   media_queries.push(mq);
               },
               Err(err) => {
-                println!("{:?}", err);
-                  // media_queries.push(MediaQuery::never_matching());
-                  // let location = err.location;
-                  // let error = ContextualParseError::InvalidMediaRule(
-                  //     input.slice_from(start_position),
-                  //     err,
-                  // );
-                  // context.log_css_error(location, error);
+                  if warnings::is_validating() {
+                    warnings::warn(err.location, WarningKind::InvalidMediaQuery);
+                  }
+                  media_queries.push(MediaQuery::never_matching());
               },
           }
 
@@ -52,6 +52,89 @@ impl MediaList {
 
       MediaList { media_queries }
   }
+
+  /// Replaces references to custom media queries, e.g. `(--narrow)`, with the queries
+  /// they were defined as. References to undefined custom media queries, or that cannot
+  /// be inlined into the surrounding condition, are left as is.
+  /// https://drafts.csswg.org/mediaqueries-5/#custom-mq
+  pub fn substitute_custom_media(&mut self, custom_media: &HashMap<String, MediaList>) {
+    self.media_queries = substitute_queries(std::mem::take(&mut self.media_queries), custom_media, 0);
+  }
+
+  /// Adds the names of the custom media queries referenced by this list to the given set.
+  pub fn custom_media_references(&self, names: &mut HashSet<String>) {
+    for query in &self.media_queries {
+      if let Some(condition) = &query.condition {
+        condition.custom_media_references(names);
+      }
+    }
+  }
+
+  /// Converts media features between the range syntax, e.g. `(width >= 600px)`, and the
+  /// legacy `min-` and `max-` prefixed syntax, e.g. `(min-width: 600px)`.
+  /// https://drafts.csswg.org/mediaqueries-4/#mq-range-context
+  pub fn transform_range_syntax(&mut self, use_range_syntax: bool) {
+    for query in self.media_queries.iter_mut() {
+      if let Some(condition) = &mut query.condition {
+        condition.transform_range_syntax(use_range_syntax);
+      }
+    }
+  }
+}
+
+impl MediaList {
+  /// Combines this media query list with one nested inside a rule that it applies to, so that
+  /// the result matches when both lists match. Returns `None` if the queries cannot be combined
+  /// into a single list, e.g. if either is negated with `not`, or has a different media type.
+  pub fn and(&self, other: &MediaList) -> Option<MediaList> {
+    if self.media_queries.is_empty() {
+      return Some(other.clone())
+    }
+
+    if other.media_queries.is_empty() {
+      return Some(self.clone())
+    }
+
+    // Combining two lists produces every pair of queries, which could grow
+    // quickly, so this is only done when at least one side has a single query.
+    if self.media_queries.len() > 1 && other.media_queries.len() > 1 {
+      return None
+    }
+
+    let mut media_queries = vec![];
+    for a in &self.media_queries {
+      for b in &other.media_queries {
+        media_queries.push(a.and(b)?);
+      }
+    }
+
+    Some(MediaList { media_queries })
+  }
+}
+
+fn substitute_queries(queries: Vec<MediaQuery>, custom_media: &HashMap<String, MediaList>, depth: usize) -> Vec<MediaQuery> {
+  let mut result = vec![];
+  for mut query in queries {
+    // Stop once every definition has been expanded, in case they reference each other in a cycle.
+    if depth <= custom_media.len() {
+      // A query that consists of only a reference can be replaced with a list of queries.
+      if query.qualifier.is_none() && query.media_type == MediaType::All {
+        if let Some(MediaCondition::Feature(feature)) = &query.condition {
+          if let Some(list) = feature.custom_media_name().and_then(|name| custom_media.get(name)) {
+            result.extend(substitute_queries(list.media_queries.clone(), custom_media, depth + 1));
+            continue
+          }
+        }
+      }
+
+      if let Some(condition) = &mut query.condition {
+        condition.substitute_custom_media(custom_media, depth);
+      }
+    }
+
+    result.push(query);
+  }
+  result
 }
 
 impl ToCss for MediaList {
@@ -116,7 +199,7 @@ impl MediaQuery {
   /// Returns an error if any of the expressions is unknown.
   pub fn parse<'i, 't>(
     input: &mut Parser<'i, 't>,
-  ) -> Result<Self, ParseError<'i, ()>> {
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let (qualifier, explicit_media_type) = input
         .try_parse(|input| -> Result<_, ()> {
             let qualifier = input.try_parse(Qualifier::parse).ok();
@@ -143,6 +226,55 @@ impl MediaQuery {
   }
 }
 
+impl MediaQuery {
+  /// Returns a query that never matches, i.e. `not all`, which replaces invalid queries.
+  fn never_matching() -> Self {
+    MediaQuery {
+      qualifier: Some(Qualifier::Not),
+      media_type: MediaType::All,
+      condition: None
+    }
+  }
+
+  fn and(&self, other: &MediaQuery) -> Option<MediaQuery> {
+    // `not` negates the whole query, including the media type, so it cannot be combined.
+    if self.qualifier == Some(Qualifier::Not) || other.qualifier == Some(Qualifier::Not) {
+      return None
+    }
+
+    // Different media types can never match at the same time, but the
+    // rule is left as is rather than removing it.
+    let media_type = match (&self.media_type, &other.media_type) {
+      (MediaType::All, media_type) | (media_type, MediaType::All) => media_type.clone(),
+      (a, b) if a == b => a.clone(),
+      _ => return None
+    };
+
+    // `only` hides the query from legacy browsers, so it is kept if either query had it.
+    let qualifier = self.qualifier.or(other.qualifier);
+
+    let condition = match (&self.condition, &other.condition) {
+      (None, None) => None,
+      (Some(condition), None) | (None, Some(condition)) => Some(condition.clone()),
+      (Some(a), Some(b)) => Some(MediaCondition::and(a, b))
+    };
+
+    // After a media type, the condition cannot contain `or` at the top level.
+    let condition = match condition {
+      Some(condition @ MediaCondition::Operation(_, Operator::Or)) if media_type != MediaType::All || qualifier.is_some() => {
+        Some(MediaCondition::InParens(Box::new(condition)))
+      }
+      condition => condition
+    };
+
+    Some(MediaQuery {
+      qualifier,
+      media_type,
+      condition
+    })
+  }
+}
+
 impl ToCss for MediaQuery {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
     if let Some(qual) = self.qualifier {
@@ -203,7 +335,7 @@ impl MediaCondition {
   pub fn parse<'i, 't>(
       input: &mut Parser<'i, 't>,
       allow_or: bool
-  ) -> Result<Self, ParseError<'i, ()>> {
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let location = input.current_source_location();
 
     // FIXME(emilio): This can be cleaner with nll.
@@ -222,7 +354,13 @@ impl MediaCondition {
     let first_condition = Self::parse_paren_block(input)?;
     let operator = match input.try_parse(Operator::parse) {
         Ok(op) => op,
-        Err(..) => return Ok(first_condition),
+        Err(..) => {
+          // A two-sided range on its own doesn't need the parentheses around its bounds.
+          return Ok(match first_condition {
+            MediaCondition::InParens(condition) if matches!(*condition, MediaCondition::Operation(_, Operator::And)) => *condition,
+            condition => condition
+          })
+        },
     };
 
     if allow_or && operator == Operator::Or {
@@ -253,25 +391,174 @@ impl MediaCondition {
   /// Parse a media condition in parentheses.
   pub fn parse_in_parens<'i, 't>(
       input: &mut Parser<'i, 't>,
-  ) -> Result<Self, ParseError<'i, ()>> {
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
       input.expect_parenthesis_block()?;
       Self::parse_paren_block(input)
   }
 
+  /// Combines two conditions with `and`, flattening existing `and` operations
+  /// and wrapping conditions that cannot appear as an operand in parentheses.
+  fn and(a: &MediaCondition, b: &MediaCondition) -> MediaCondition {
+    let mut conditions = vec![];
+    for condition in vec![a, b] {
+      match condition {
+        MediaCondition::Operation(list, Operator::And) => conditions.extend(list.iter().cloned()),
+        MediaCondition::Feature(_) | MediaCondition::InParens(_) => conditions.push(condition.clone()),
+        MediaCondition::Not(_) | MediaCondition::Operation(_, Operator::Or) => {
+          conditions.push(MediaCondition::InParens(Box::new(condition.clone())))
+        }
+      }
+    }
+
+    MediaCondition::Operation(conditions.into_boxed_slice(), Operator::And)
+  }
+
+  /// References within a condition can only be replaced with a single query that has no media type.
+  fn substitute_custom_media(&mut self, custom_media: &HashMap<String, MediaList>, depth: usize) {
+    match self {
+      MediaCondition::Feature(feature) => {
+        let list = match feature.custom_media_name().and_then(|name| custom_media.get(name)) {
+          Some(list) => list,
+          None => return
+        };
+
+        let mut queries = substitute_queries(list.media_queries.clone(), custom_media, depth + 1);
+        if queries.len() == 1 && queries[0].qualifier.is_none() && queries[0].media_type == MediaType::All {
+          if let Some(condition) = queries.remove(0).condition {
+            *self = match condition {
+              MediaCondition::Feature(_) | MediaCondition::InParens(_) => condition,
+              condition => MediaCondition::InParens(Box::new(condition))
+            };
+          }
+        }
+      }
+      MediaCondition::Not(condition) | MediaCondition::InParens(condition) => {
+        condition.substitute_custom_media(custom_media, depth)
+      }
+      MediaCondition::Operation(conditions, _) => {
+        for condition in conditions.iter_mut() {
+          condition.substitute_custom_media(custom_media, depth)
+        }
+      }
+    }
+  }
+
+  fn custom_media_references(&self, names: &mut HashSet<String>) {
+    match self {
+      MediaCondition::Feature(feature) => {
+        if let Some(name) = feature.custom_media_name() {
+          names.insert(name.into());
+        }
+      }
+      MediaCondition::Not(condition) | MediaCondition::InParens(condition) => condition.custom_media_references(names),
+      MediaCondition::Operation(conditions, _) => {
+        for condition in conditions.iter() {
+          condition.custom_media_references(names)
+        }
+      }
+    }
+  }
+
+  fn transform_range_syntax(&mut self, use_range_syntax: bool) {
+    match self {
+      MediaCondition::Feature(feature) => feature.transform_range_syntax(use_range_syntax),
+      MediaCondition::Not(condition) | MediaCondition::InParens(condition) => {
+        condition.transform_range_syntax(use_range_syntax)
+      }
+      MediaCondition::Operation(conditions, _) => {
+        for condition in conditions.iter_mut() {
+          condition.transform_range_syntax(use_range_syntax)
+        }
+      }
+    }
+  }
+
   fn parse_paren_block<'i, 't>(
       input: &mut Parser<'i, 't>,
-  ) -> Result<Self, ParseError<'i, ()>> {
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
       input.parse_nested_block(|input| {
           // Base case.
           if let Ok(inner) = input.try_parse(|i| Self::parse(i, true)) {
               return Ok(MediaCondition::InParens(Box::new(inner)));
           }
+          if let Ok(range) = input.try_parse(Self::parse_value_first_range) {
+              return Ok(range);
+          }
           let expr = MediaFeatureExpression::parse_in_parenthesis_block(input)?;
           Ok(MediaCondition::Feature(expr))
       })
   }
 }
 
+impl MediaCondition {
+  /// Parses a range that starts with a value, where we've already consumed the parenthesis,
+  /// e.g. `600px <= width` or `600px < width <= 900px`. Each bound is stored as a separate
+  /// feature with the name first, so two-sided ranges become two features combined with `and`.
+  /// https://drafts.csswg.org/mediaqueries-4/#mq-range-context
+  fn parse_value_first_range<'i, 't>(
+      input: &mut Parser<'i, 't>,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let start = input.position();
+    loop {
+      let state = input.state();
+      match input.next() {
+        Ok(Token::Delim('<')) | Ok(Token::Delim('>')) | Ok(Token::Delim('=')) => {
+          input.reset(&state);
+          break
+        }
+        Ok(_) => {}
+        Err(_) => return Err(location.new_custom_error(ParserError::InvalidMediaQuery))
+      }
+    }
+    let min_value = input.slice_from(start).trim();
+
+    let operator = match input.try_parse(consume_operation_or_colon) {
+      Ok(Some(operator)) if !min_value.is_empty() => operator,
+      _ => return Err(location.new_custom_error(ParserError::InvalidMediaQuery))
+    };
+
+    let name = input.expect_ident()?.to_ascii_lowercase();
+    if !is_range_feature(&name) {
+      return Err(location.new_custom_error(ParserError::InvalidMediaQuery))
+    }
+
+    let first = MediaFeatureExpression {
+      name: name.clone(),
+      range_or_operator: Some(RangeOrOperator::Operator(operator.reverse())),
+      value: Some(min_value.into())
+    };
+
+    if input.is_exhausted() {
+      return Ok(MediaCondition::Feature(first))
+    }
+
+    // Both comparisons in a two-sided range must point in the same direction.
+    let second_operator = match input.try_parse(consume_operation_or_colon) {
+      Ok(Some(second)) if operator.is_less_than() && second.is_less_than() => second,
+      Ok(Some(second)) if operator.is_greater_than() && second.is_greater_than() => second,
+      _ => return Err(location.new_custom_error(ParserError::InvalidMediaQuery))
+    };
+
+    input.skip_whitespace();
+    let max_value = exhaust(input);
+    if max_value.is_empty() {
+      return Err(location.new_custom_error(ParserError::InvalidMediaQuery))
+    }
+
+    let second = MediaFeatureExpression {
+      name,
+      range_or_operator: Some(RangeOrOperator::Operator(second_operator)),
+      value: Some(max_value.into())
+    };
+
+    Ok(MediaCondition::InParens(Box::new(MediaCondition::Operation(
+      vec![MediaCondition::Feature(first), MediaCondition::Feature(second)].into_boxed_slice(),
+      Operator::And
+    ))))
+  }
+}
+
 impl ToCss for MediaCondition {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
     match *self {
@@ -315,16 +602,55 @@ pub enum MediaQueryOperator {
     LessThanEqual,
 }
 
+impl MediaQueryOperator {
+  /// Returns the operator with its operands swapped, e.g. `<` for `>`.
+  fn reverse(self) -> Self {
+    match self {
+      MediaQueryOperator::Equal => MediaQueryOperator::Equal,
+      MediaQueryOperator::GreaterThan => MediaQueryOperator::LessThan,
+      MediaQueryOperator::GreaterThanEqual => MediaQueryOperator::LessThanEqual,
+      MediaQueryOperator::LessThan => MediaQueryOperator::GreaterThan,
+      MediaQueryOperator::LessThanEqual => MediaQueryOperator::GreaterThanEqual,
+    }
+  }
+
+  fn is_less_than(self) -> bool {
+    matches!(self, MediaQueryOperator::LessThan | MediaQueryOperator::LessThanEqual)
+  }
+
+  fn is_greater_than(self) -> bool {
+    matches!(self, MediaQueryOperator::GreaterThan | MediaQueryOperator::GreaterThanEqual)
+  }
+}
+
+/// The `min-` or `max-` prefix of a range feature in the legacy syntax.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MediaFeatureRange {
+    /// min-
+    Min,
+    /// max-
+    Max,
+}
+
+/// How a media feature is compared to its value.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RangeOrOperator {
+    /// A `min-` or `max-` prefixed feature, e.g. `(min-width: 600px)`.
+    Range(MediaFeatureRange),
+    /// A feature in the range syntax, e.g. `(width >= 600px)`.
+    Operator(MediaQueryOperator),
+}
+
 /// A feature expression contains a reference to the media feature, the value
 /// the media query contained, and the range to evaluate.
+///
+/// The name of range features is stored without the `min-` or `max-` prefix.
+/// A feature without a value is evaluated in a boolean context, e.g. `(hover)`.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct MediaFeatureExpression {
   name: String,
-  operator: Option<MediaQueryOperator>,
+  range_or_operator: Option<RangeOrOperator>,
   value: Option<String>
-    // feature_index: usize,
-    // value: Option<MediaExpressionValue>,
-    // range_or_operator: Option<RangeOrOperator>,
 }
 
 impl MediaFeatureExpression {
@@ -332,151 +658,228 @@ impl MediaFeatureExpression {
   /// parenthesis.
   pub fn parse_in_parenthesis_block<'i, 't>(
       input: &mut Parser<'i, 't>,
-  ) -> Result<Self, ParseError<'i, ()>> {
-      // let mut requirements = ParsingRequirements::empty();
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
       let location = input.current_source_location();
       let ident = input.expect_ident()?;
 
-      // if context.in_ua_or_chrome_sheet() {
-      //     requirements.insert(ParsingRequirements::CHROME_AND_UA_ONLY);
-      // }
-
-      let feature_name = String::from(&**ident);
-
-      // if starts_with_ignore_ascii_case(feature_name, "-webkit-") {
-      //     feature_name = &feature_name[8..];
-      //     requirements.insert(ParsingRequirements::WEBKIT_PREFIX);
-      // }
-
-      // let range = if starts_with_ignore_ascii_case(feature_name, "min-") {
-      //     feature_name = &feature_name[4..];
-      //     Some(Range::Min)
-      // } else if starts_with_ignore_ascii_case(feature_name, "max-") {
-      //     feature_name = &feature_name[4..];
-      //     Some(Range::Max)
-      // } else {
-      //     None
-      // };
-
-      // let atom = Atom::from(string_as_ascii_lowercase(feature_name));
-
-      // let (feature_index, feature) = match MEDIA_FEATURES
-      //     .iter()
-      //     .enumerate()
-      //     .find(|(_, f)| f.name == atom)
-      // {
-      //     Some((i, f)) => (i, f),
-      //     None => {
-      //         return Err(location.new_custom_error(
-      //             StyleParseErrorKind::MediaQueryExpectedFeatureName(ident.clone()),
-      //         ))
-      //     },
-      // };
-
-      // if disabled_by_pref(&feature.name, context) ||
-      //     !requirements.contains(feature.requirements) ||
-      //     (range.is_some() && !feature.allows_ranges())
-      // {
-      //     return Err(location.new_custom_error(
-      //         StyleParseErrorKind::MediaQueryExpectedFeatureName(ident.clone()),
-      //     ));
-      // }
-
-      let operator = input.try_parse(consume_operation_or_colon);
-      let operator = match operator {
+      let mut name: &str = &ident;
+      let range = if starts_with_ignore_ascii_case(name, "min-") && is_range_feature(&name[4..]) {
+          name = &name[4..];
+          Some(MediaFeatureRange::Min)
+      } else if starts_with_ignore_ascii_case(name, "max-") && is_range_feature(&name[4..]) {
+          name = &name[4..];
+          Some(MediaFeatureRange::Max)
+      } else {
+          None
+      };
+      // Custom media names are case sensitive, but media features are not.
+      let name = if name.starts_with("--") { String::from(name) } else { name.to_ascii_lowercase() };
+
+      let range_or_operator = match input.try_parse(consume_operation_or_colon) {
+          // If there's no colon, this is a media query of the form '(<feature>)',
+          // that is, the feature is evaluated in a boolean context. Ranged
+          // expressions require a value, so they are rejected here.
           Err(..) => {
-              // If there's no colon, this is a media query of the
-              // form '(<feature>)', that is, there's no value
-              // specified.
-              //
-              // Gecko doesn't allow ranged expressions without a
-              // value, so just reject them here too.
-              // if range.is_some() {
-              //     return Err(
-              //         input.new_custom_error(StyleParseErrorKind::RangedExpressionWithNoValue)
-              //     );
-              // }
-
-              // return Ok(Self::new(feature_index, None, None));
+              if range.is_some() {
+                  return Err(location.new_custom_error(ParserError::InvalidMediaQuery))
+              }
+
               return Ok(MediaFeatureExpression {
-                name: feature_name,
-                operator: None,
+                name,
+                range_or_operator: None,
                 value: None
               })
           },
-          Ok(operator) => operator,
+          Ok(None) => range.map(RangeOrOperator::Range),
+          Ok(Some(operator)) => {
+              // A prefixed feature cannot also be used with an operator, e.g. `(min-width >= 600px)`.
+              if range.is_some() {
+                  return Err(location.new_custom_error(ParserError::InvalidMediaQuery))
+              }
+              Some(RangeOrOperator::Operator(operator))
+          }
       };
 
-      // let range_or_operator = match range {
-      //     Some(range) => {
-      //         if operator.is_some() {
-      //             return Err(
-      //                 input.new_custom_error(StyleParseErrorKind::MediaQueryUnexpectedOperator)
-      //             );
-      //         }
-      //         Some(RangeOrOperator::Range(range))
-      //     },
-      //     None => match operator {
-      //         Some(operator) => {
-      //             if !feature.allows_ranges() {
-      //                 return Err(input
-      //                     .new_custom_error(StyleParseErrorKind::MediaQueryUnexpectedOperator));
-      //             }
-      //             Some(RangeOrOperator::Operator(operator))
-      //         },
-      //         None => None,
-      //     },
-      // };
-
-      // let value = MediaExpressionValue::parse(feature, context, input).map_err(|err| {
-      //     err.location
-      //         .new_custom_error(StyleParseErrorKind::MediaQueryExpectedFeatureValue)
-      // })?;
       input.skip_whitespace();
       let value = exhaust(input);
 
       Ok(MediaFeatureExpression {
-        name: feature_name.into(),
-        operator,
+        name,
+        range_or_operator,
         value: Some(value.into())
       })
   }
 }
 
+impl MediaFeatureExpression {
+  /// Returns the name of the custom media query if this is a reference to one, e.g. `(--narrow)`.
+  fn custom_media_name(&self) -> Option<&str> {
+    if self.name.starts_with("--") && self.range_or_operator.is_none() && self.value.is_none() {
+      Some(&self.name)
+    } else {
+      None
+    }
+  }
+
+  fn transform_range_syntax(&mut self, use_range_syntax: bool) {
+    if use_range_syntax {
+      self.range_or_operator = match self.range_or_operator {
+        Some(RangeOrOperator::Range(MediaFeatureRange::Min)) => Some(RangeOrOperator::Operator(MediaQueryOperator::GreaterThanEqual)),
+        Some(RangeOrOperator::Range(MediaFeatureRange::Max)) => Some(RangeOrOperator::Operator(MediaQueryOperator::LessThanEqual)),
+        range_or_operator => range_or_operator
+      };
+    } else if let Some(RangeOrOperator::Operator(operator)) = self.range_or_operator {
+      if !is_range_feature(&self.name) {
+        return
+      }
+
+      // Strict comparisons have no legacy equivalent, so the value is adjusted slightly
+      // if it is a dimension. Otherwise, the range syntax is kept.
+      let (range, value) = match (operator, &self.value) {
+        (MediaQueryOperator::Equal, _) => (None, None),
+        (MediaQueryOperator::GreaterThanEqual, _) => (Some(MediaFeatureRange::Min), None),
+        (MediaQueryOperator::LessThanEqual, _) => (Some(MediaFeatureRange::Max), None),
+        (MediaQueryOperator::GreaterThan, Some(value)) => match adjust_dimension(value, 0.001) {
+          Some(value) => (Some(MediaFeatureRange::Min), Some(value)),
+          None => return
+        },
+        (MediaQueryOperator::LessThan, Some(value)) => match adjust_dimension(value, -0.001) {
+          Some(value) => (Some(MediaFeatureRange::Max), Some(value)),
+          None => return
+        },
+        _ => return
+      };
+
+      self.range_or_operator = range.map(RangeOrOperator::Range);
+      if value.is_some() {
+        self.value = value;
+      }
+    }
+  }
+
+  /// Returns whether the expression is equivalent to evaluating the feature in a boolean
+  /// context, which is true whenever the value would be anything other than zero or `none`.
+  /// For example, `(min-color: 1)` and `(hover: hover)` can be written as `(color)` and `(hover)`.
+  fn is_boolean_equivalent(&self) -> bool {
+    let value = match &self.value {
+      Some(value) => value.trim(),
+      None => return true
+    };
+
+    match self.range_or_operator {
+      Some(RangeOrOperator::Range(MediaFeatureRange::Min)) |
+      Some(RangeOrOperator::Operator(MediaQueryOperator::GreaterThanEqual)) => {
+        is_integer_feature(&self.name) && value == "1"
+      }
+      Some(RangeOrOperator::Operator(MediaQueryOperator::GreaterThan)) => {
+        is_integer_feature(&self.name) && value == "0"
+      }
+      Some(_) => false,
+      None => match_ignore_ascii_case! { self.name.as_str(),
+        // These features only have one value other than `none`.
+        "hover" | "any-hover" => value.eq_ignore_ascii_case("hover"),
+        "grid" => value == "1",
+        _ => false
+      }
+    }
+  }
+}
+
+/// Range features whose value is a non-negative integer, and are false in a boolean context when zero.
+fn is_integer_feature(name: &str) -> bool {
+  match_ignore_ascii_case! { name,
+    "color" | "color-index" | "monochrome" => true,
+    _ => false
+  }
+}
+
+/// Features that can be used in a range context.
+/// https://drafts.csswg.org/mediaqueries-4/#mq-features
+fn is_range_feature(name: &str) -> bool {
+  match_ignore_ascii_case! { name,
+    "width" | "height" | "aspect-ratio" | "resolution" | "color" | "color-index" | "monochrome" |
+    "device-width" | "device-height" | "device-aspect-ratio" => true,
+    _ => false
+  }
+}
+
+fn adjust_dimension(value: &str, delta: f32) -> Option<String> {
+  let mut input = ParserInput::new(value);
+  let mut parser = Parser::new(&mut input);
+  let res = match parser.next() {
+    Ok(Token::Dimension { value, unit, .. }) => format!("{}{}", value + delta, unit),
+    _ => return None
+  };
+
+  if !parser.is_exhausted() {
+    return None
+  }
+
+  Some(res)
+}
+
+fn parse_resolution(value: &str) -> Option<Resolution> {
+  let mut input = ParserInput::new(value);
+  let mut parser = Parser::new(&mut input);
+  let res = Resolution::parse(&mut parser).ok()?;
+  if !parser.is_exhausted() {
+    return None
+  }
+
+  Some(res)
+}
+
+fn starts_with_ignore_ascii_case(string: &str, prefix: &str) -> bool {
+  string.len() >= prefix.len() &&
+    string.as_bytes()[0..prefix.len()].eq_ignore_ascii_case(prefix.as_bytes())
+}
+
 impl ToCss for MediaFeatureExpression {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
       dest.write_str("(")?;
 
-      // let feature = self.feature();
-
-      // if feature
-      //     .requirements
-      //     .contains(ParsingRequirements::WEBKIT_PREFIX)
-      // {
-      //     dest.write_str("-webkit-")?;
-      // }
+      // Use the shorter boolean context form when it is equivalent.
+      if dest.minify && self.is_boolean_equivalent() {
+        dest.write_str(&self.name)?;
+        return dest.write_str(")")
+      }
 
-      // if let Some(RangeOrOperator::Range(range)) = self.range_or_operator {
-      //     match range {
-      //         Range::Min => dest.write_str("min-")?,
-      //         Range::Max => dest.write_str("max-")?,
-      //     }
-      // }
+      if let Some(RangeOrOperator::Range(range)) = self.range_or_operator {
+        match range {
+          MediaFeatureRange::Min => dest.write_str("min-")?,
+          MediaFeatureRange::Max => dest.write_str("max-")?,
+        }
+      }
 
       dest.write_str(&self.name)?;
 
-      // if let Some(RangeOrOperator::Operator(op)) = self.range_or_operator {
-      //     dest.write_char(' ')?;
-      //     op.to_css(dest)?;
-      //     dest.write_char(' ')?;
-      // } else if self.value.is_some() {
-      //     dest.write_str(": ")?;
-      // }
-
       if let Some(ref val) = self.value {
-        // val.to_css(dest, self)?;
-        dest.delim(':', false)?;
-        dest.write_str(val)?;
+        match self.range_or_operator {
+          Some(RangeOrOperator::Operator(operator)) => {
+            dest.whitespace()?;
+            dest.write_str(match operator {
+              MediaQueryOperator::Equal => "=",
+              MediaQueryOperator::GreaterThan => ">",
+              MediaQueryOperator::GreaterThanEqual => ">=",
+              MediaQueryOperator::LessThan => "<",
+              MediaQueryOperator::LessThanEqual => "<=",
+            })?;
+            dest.whitespace()?;
+          }
+          _ => dest.delim(':', false)?
+        }
+
+        // Resolutions are minified to the shortest unit, which is usually the x unit.
+        // Older browsers only support dpi in media queries, so this requires targets.
+        let resolution = match dest.targets {
+          Some(targets) if dest.minify && self.name.eq_ignore_ascii_case("resolution") && compat::Feature::XResolutionUnit.is_compatible(targets) => parse_resolution(val),
+          _ => None
+        };
+
+        match resolution {
+          Some(resolution) => resolution.to_css(dest)?,
+          None => dest.write_str(val)?
+        }
       }
 
       dest.write_str(")")
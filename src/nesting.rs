@@ -0,0 +1,345 @@
+use cssparser::*;
+use selectors::SelectorList;
+use selectors::parser::{Selector, Component};
+use crate::parser::{CssRule, StyleRule};
+use crate::selector::{Selectors, Namespaces, parse_selector_list};
+use crate::printer::Printer;
+use crate::limits;
+use crate::traits::ToCss;
+use std::fmt::{self, Write};
+use crate::error::ParserError;
+
+/// The selectors crate does not support the nesting selector, so `&` is replaced
+/// with this internal pseudo class before parsing. It serializes back to `&`.
+const NESTING_PLACEHOLDER: &str = ":-internal-nesting()";
+
+/// Parses the selector list of a style rule nested within another style rule.
+/// Selectors that do not contain a nesting selector are relative to the parent,
+/// e.g. `.bar` and `> .bar` are equivalent to `& .bar` and `& > .bar`.
+/// https://drafts.csswg.org/css-nesting-1/#syntax
+pub fn parse_nested_selectors<'i, 't>(input: &mut Parser<'i, 't>, namespaces: &Namespaces) -> Result<SelectorList<Selectors>, ParseError<'i, ParserError<'i>>> {
+  let start = input.state();
+  limits::check_selector_depth(input)?;
+  input.reset(&start);
+
+  let selectors = input.parse_comma_separated(|input| {
+    let mut selector = String::new();
+    let has_nesting = replace_nesting(input, &mut selector, &mut |_| NESTING_PLACEHOLDER.into())?;
+    let selector = selector.trim();
+    if has_nesting {
+      Ok(selector.to_owned())
+    } else {
+      Ok(format!("{} {}", NESTING_PLACEHOLDER, selector))
+    }
+  })?;
+
+  match parse_selector_list(&selectors.join(","), namespaces) {
+    Some(selectors) => Ok(selectors),
+    None => Err(input.new_custom_error(ParserError::InvalidSelector))
+  }
+}
+
+/// Flattens nested style rules into standalone rules by replacing the nesting selector
+/// with the parent selector, for targets that don't support nesting.
+pub fn flatten(rules: Vec<CssRule>, namespaces: &Namespaces) -> Vec<CssRule> {
+  let mut result = vec![];
+  for rule in rules {
+    match rule {
+      CssRule::Style(style) => flatten_style_rule(style, namespaces, &mut result),
+      CssRule::Media(mut media) => {
+        media.rules = flatten(media.rules, namespaces);
+        result.push(CssRule::Media(media))
+      }
+      CssRule::Supports(mut supports) => {
+        supports.rules = flatten(supports.rules, namespaces);
+        result.push(CssRule::Supports(supports))
+      }
+      rule => result.push(rule)
+    }
+  }
+  result
+}
+
+fn flatten_style_rule(mut style: StyleRule, namespaces: &Namespaces, dest: &mut Vec<CssRule>) {
+  // Unknown at-rules such as `@apply` stay within the parent rule, since they
+  // cannot be moved out of it.
+  let (nested, rules): (Vec<CssRule>, Vec<CssRule>) = std::mem::take(&mut style.rules)
+    .into_iter()
+    .partition(|rule| matches!(rule, CssRule::Style(_) | CssRule::Media(_)));
+  style.rules = rules;
+  let parent = style.selectors.clone();
+
+  // The parent rule is omitted if it only existed to contain nested rules.
+  if !style.declarations.declarations.is_empty() || !style.rules.is_empty() || nested.is_empty() {
+    dest.push(CssRule::Style(style));
+  }
+
+  flatten_nested_rules(nested, &parent, namespaces, dest);
+}
+
+/// Hoists rules nested within a style rule after it, in source order. Nested conditional
+/// rules such as `@media` are moved out of the parent with their contents resolved against
+/// it, e.g. `.foo { @media print { color: red } }` becomes `@media print { .foo { color: red } }`.
+fn flatten_nested_rules(rules: Vec<CssRule>, parent: &SelectorList<Selectors>, namespaces: &Namespaces, dest: &mut Vec<CssRule>) {
+  for rule in rules {
+    match rule {
+      CssRule::Style(mut child) => {
+        if let Some(selectors) = resolve_nesting(&child.selectors, parent, namespaces) {
+          child.selectors = selectors;
+          flatten_style_rule(child, namespaces, dest);
+        }
+      }
+      CssRule::Media(mut media) => {
+        let mut rules = vec![];
+        flatten_nested_rules(std::mem::take(&mut media.rules), parent, namespaces, &mut rules);
+        if !rules.is_empty() {
+          media.rules = rules;
+          dest.push(CssRule::Media(media));
+        }
+      }
+      _ => {}
+    }
+  }
+}
+
+/// Returns a selector list containing only the nesting selector, which is used for the
+/// declarations of conditional rules nested directly within a style rule.
+pub fn nesting_selector(namespaces: &Namespaces) -> SelectorList<Selectors> {
+  parse_selector_list(NESTING_PLACEHOLDER, namespaces).unwrap()
+}
+
+/// Returns whether the selector list only contains the nesting selector.
+pub fn is_nesting_selector(selectors: &SelectorList<Selectors>) -> bool {
+  selectors.0.len() == 1 && selectors.0[0].to_css_string() == "&"
+}
+
+/// The maximum number of selectors a single nested selector may expand to when each of its
+/// nesting selectors is replaced with each of the parent selectors. Beyond this, the parent
+/// is inserted using `:is()` instead.
+const MAX_NESTING_COMBINATIONS: usize = 256;
+
+/// Replaces the nesting selectors in a selector list with the parent selectors. Each
+/// selector is combined with each of the parent selectors rather than using `:is()`,
+/// which is not supported by browsers that lack nesting support. When a selector contains
+/// multiple nesting selectors, every combination of parent selectors is generated, e.g.
+/// `& + &` within `.a, .b` becomes `.a + .a, .a + .b, .b + .a, .b + .b`. `:is()` is only used
+/// when the parent cannot be inserted directly, e.g. for `.foo&` with a complex parent selector.
+fn resolve_nesting(selectors: &SelectorList<Selectors>, parent: &SelectorList<Selectors>, namespaces: &Namespaces) -> Option<SelectorList<Selectors>> {
+  let parents: Vec<(String, bool)> = parent.0.iter()
+    .map(|parent| (parent.to_css_string(), is_compound_without_type(parent)))
+    .collect();
+  let mut result = vec![];
+  for selector in selectors.0.iter() {
+    let source = selector.to_css_string();
+    let count = substitute_nesting(&source, &mut |_| String::new())?.1;
+    match parents.len().checked_pow(count as u32) {
+      Some(combinations) if combinations <= MAX_NESTING_COMBINATIONS => {
+        for combination in 0..combinations {
+          // The last nesting selector varies fastest, so the output is in lexicographic order.
+          let mut index = 0;
+          let (resolved, _) = substitute_nesting(&source, &mut |compound_start| {
+            let (parent, is_compound) = &parents[combination / parents.len().pow((count - index - 1) as u32) % parents.len()];
+            index += 1;
+            if compound_start || *is_compound {
+              parent.clone()
+            } else {
+              format!(":is({})", parent)
+            }
+          })?;
+          result.push(resolved);
+        }
+      }
+      _ => {
+        let list = parents.iter().map(|(parent, _)| parent.as_str()).collect::<Vec<_>>().join(",");
+        result.push(substitute_nesting(&source, &mut |_| format!(":is({})", list))?.0);
+      }
+    }
+  }
+
+  parse_selector_list(&result.join(","), namespaces)
+}
+
+/// Replaces each nesting selector in the source using the callback, and returns
+/// the result along with the number of nesting selectors that were replaced.
+fn substitute_nesting<F>(source: &str, replace: &mut F) -> Option<(String, usize)>
+where F: FnMut(bool) -> String {
+  let mut input = ParserInput::new(source);
+  let mut parser = Parser::new(&mut input);
+  let mut resolved = String::new();
+  let mut count = 0;
+  replace_nesting(&mut parser, &mut resolved, &mut |compound_start| {
+    count += 1;
+    replace(compound_start)
+  }).ok()?;
+  Some((resolved, count))
+}
+
+fn is_compound_without_type(selector: &Selector<Selectors>) -> bool {
+  selector.iter_raw_match_order().all(|component| {
+    !matches!(component, Component::Combinator(_) | Component::LocalName(_) | Component::ExplicitUniversalType)
+  })
+}
+
+/// Serializes the tokens in the input, replacing each nesting selector with the result
+/// of the callback, which receives whether the nesting selector starts a compound selector.
+/// Returns whether any nesting selectors were found.
+fn replace_nesting<'i, 't, F>(input: &mut Parser<'i, 't>, dest: &mut String, replace: &mut F) -> Result<bool, ParseError<'i, ParserError<'i>>>
+where F: FnMut(bool) -> String {
+  let mut found = false;
+  let mut compound_start = true;
+  loop {
+    let token = match input.next_including_whitespace() {
+      Ok(token) => token.clone(),
+      Err(_) => break
+    };
+
+    match token {
+      Token::Delim('&') => {
+        dest.push_str(&replace(compound_start));
+        found = true;
+        compound_start = false;
+      }
+      Token::Function(_) | Token::ParenthesisBlock | Token::SquareBracketBlock => {
+        cssparser::ToCss::to_css(&token, dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?;
+        found |= input.parse_nested_block(|input| replace_nesting(input, dest, replace))?;
+        dest.push(if token == Token::SquareBracketBlock { ']' } else { ')' });
+        compound_start = false;
+      }
+      _ => {
+        cssparser::ToCss::to_css(&token, dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?;
+        compound_start = matches!(token, Token::WhiteSpace(_) | Token::Comma | Token::Delim('>') | Token::Delim('+') | Token::Delim('~'));
+      }
+    }
+  }
+
+  Ok(found)
+}
+
+/// Prints a list of rules in the expanded, nested style. Consecutive style rules
+/// that share the same leading compound selector are grouped under a parent rule
+/// using nesting selectors, e.g. `.foo .bar {}` and `.foo > .baz {}` become
+/// `.foo { & .bar {} & > .baz {} }`. This only affects presentation: each nested
+/// rule is equivalent to the rule it was created from, and the relative order
+/// of all rules is preserved.
+/// https://drafts.csswg.org/css-nesting-1/
+pub fn print_nested<W>(rules: &[CssRule], dest: &mut Printer<W>) -> fmt::Result where W: fmt::Write {
+  print_rule_list(rules, dest, true)
+}
+
+fn print_rule_list<W>(rules: &[CssRule], dest: &mut Printer<W>, top_level: bool) -> fmt::Result where W: fmt::Write {
+  let keys: Vec<Option<(String, String)>> = rules.iter().map(nesting_key).collect();
+  let mut first = true;
+  let mut i = 0;
+  while i < rules.len() {
+    if rules[i].is_omitted_comment(dest.preserve_comments) {
+      i += 1;
+      continue
+    }
+
+    // Find the end of the group of rules sharing the same prefix as this one.
+    let mut end = i + 1;
+    if let Some((prefix, _)) = &keys[i] {
+      while end < rules.len() && matches!(&keys[end], Some((p, _)) if p == prefix) {
+        end += 1;
+      }
+    }
+
+    if top_level {
+      if first {
+        first = false;
+      } else {
+        dest.newline()?;
+      }
+    } else {
+      dest.newline()?;
+    }
+
+    if end - i > 1 {
+      let (prefix, _) = keys[i].as_ref().unwrap();
+      dest.write_str(prefix)?;
+      dest.whitespace()?;
+      dest.write_char('{')?;
+      dest.indent();
+      for idx in i..end {
+        let (_, rest) = keys[idx].as_ref().unwrap();
+        if let CssRule::Style(style) = &rules[idx] {
+          dest.newline()?;
+          dest.write_char('&')?;
+          dest.write_str(rest)?;
+          style.declarations.to_css(dest)?;
+        }
+      }
+      dest.dedent();
+      dest.newline()?;
+      dest.write_char('}')?;
+    } else {
+      match &rules[i] {
+        CssRule::Media(media) => {
+          dest.write_str("@media ")?;
+          media.query.to_css(dest)?;
+          dest.whitespace()?;
+          dest.write_char('{')?;
+          dest.indent();
+          print_rule_list(&media.rules, dest, false)?;
+          dest.dedent();
+          dest.newline()?;
+          dest.write_char('}')?;
+        }
+        rule => rule.to_css(dest)?
+      }
+    }
+
+    if top_level {
+      dest.newline()?;
+    }
+
+    i = end;
+  }
+
+  Ok(())
+}
+
+/// Splits the selector of a style rule into its first compound selector, and the rest
+/// of the selector starting with a combinator. Only rules with a single selector and
+/// no pseudo elements can be nested.
+fn nesting_key(rule: &CssRule) -> Option<(String, String)> {
+  let style: &StyleRule = match rule {
+    CssRule::Style(style) => style,
+    _ => return None
+  };
+
+  if style.selectors.0.len() != 1 || !style.rules.is_empty() {
+    return None
+  }
+
+  let mut selector = String::new();
+  let mut printer = Printer::new(&mut selector, false);
+  style.selectors.to_css(&mut printer).ok()?;
+
+  if selector.contains("::") || [":before", ":after", ":first-line", ":first-letter"].iter().any(|p| selector.contains(p)) {
+    return None
+  }
+
+  // Find the first combinator outside of any brackets, parentheses, or strings.
+  let mut depth = 0;
+  let mut quote = None;
+  let mut split = selector.len();
+  for (idx, c) in selector.char_indices() {
+    match (c, quote) {
+      ('\\', _) => return None,
+      (c, Some(q)) if c == q => quote = None,
+      (_, Some(_)) => {},
+      ('"', None) | ('\'', None) => quote = Some(c),
+      ('(', None) | ('[', None) => depth += 1,
+      (')', None) | (']', None) => depth -= 1,
+      (' ', None) if depth == 0 => {
+        split = idx;
+        break
+      }
+      _ => {}
+    }
+  }
+
+  let rest = selector.split_off(split);
+  Some((selector, rest))
+}
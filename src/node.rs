@@ -0,0 +1,128 @@
+use napi::{CallContext, Env, JsObject, JsUnknown, Status};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use crate::bundler::{Bundler, FileProvider};
+use crate::warnings::{self, Warning};
+use crate::{Browsers, StyleSheet, ParserOptions, MinifyOptions, PrinterOptions, ToCssResult};
+
+#[derive(Serialize, Debug, Deserialize)]
+struct Config {
+  filename: String,
+  #[serde(with = "serde_bytes")]
+  code: Vec<u8>,
+  targets: Option<Browsers>,
+  minify: Option<bool>,
+  nested: Option<bool>,
+  custom_media: Option<bool>,
+  unsafe_merges: Option<bool>,
+  source_map: Option<bool>,
+  css_modules: Option<bool>
+}
+
+#[derive(Serialize, Debug, Deserialize)]
+struct BundleConfig {
+  filename: String,
+  targets: Option<Browsers>,
+  minify: Option<bool>,
+  nested: Option<bool>,
+  unsafe_merges: Option<bool>,
+  source_map: Option<bool>
+}
+
+/// Transforms the code of a stylesheet, given as a Buffer, and returns an object with the
+/// resulting `code` and source `map` as Buffers, along with the `warnings` found while parsing.
+#[js_function(1)]
+fn transform(ctx: CallContext) -> napi::Result<JsObject> {
+  let opts = ctx.get::<JsObject>(0)?;
+  let config: Config = ctx.env.from_js_value(opts)?;
+
+  let code = std::str::from_utf8(&config.code)
+    .map_err(|_| napi::Error::new(Status::InvalidArg, "code must be valid UTF-8".into()))?;
+  let (stylesheet, warnings) = parse(code, ParserOptions {
+    filename: config.filename,
+    custom_media: config.custom_media.unwrap_or(false),
+    css_modules: config.css_modules.unwrap_or(false),
+    ..ParserOptions::default()
+  }, config.targets);
+
+  let res = finish(stylesheet, config.targets, config.minify, config.nested, config.unsafe_merges, config.source_map)?;
+  create_result(ctx.env, res, &warnings)
+}
+
+/// Reads a stylesheet from the file system, inlines the files referenced by its `@import` rules,
+/// and returns the transformed code like `transform`. Throws if any of the files cannot be read.
+#[js_function(1)]
+fn bundle(ctx: CallContext) -> napi::Result<JsObject> {
+  let opts = ctx.get::<JsObject>(0)?;
+  let config: BundleConfig = ctx.env.from_js_value(opts)?;
+
+  let (stylesheet, warnings) = warnings::validate(config.targets, || Bundler::new(&FileProvider).bundle(Path::new(&config.filename)));
+  let stylesheet = stylesheet
+    .map_err(|err| napi::Error::new(Status::GenericFailure, format!("{}: {}", err.file.display(), err.message)))?;
+
+  let res = finish(stylesheet, config.targets, config.minify, config.nested, config.unsafe_merges, config.source_map)?;
+  create_result(ctx.env, res, &warnings)
+}
+
+/// Parses a stylesheet, and returns it along with the warnings about likely mistakes in it,
+/// e.g. invalid values, and features that are not supported by the targets.
+pub(crate) fn parse(code: &str, options: ParserOptions, targets: Option<Browsers>) -> (StyleSheet, Vec<Warning>) {
+  let (mut stylesheet, mut warnings) = warnings::validate(targets, || StyleSheet::parse(code, options));
+  warnings.append(&mut stylesheet.warnings);
+  (stylesheet, warnings)
+}
+
+fn finish(mut stylesheet: StyleSheet, targets: Option<Browsers>, minify: Option<bool>, nested: Option<bool>, unsafe_merges: Option<bool>, source_map: Option<bool>) -> napi::Result<ToCssResult> {
+  stylesheet.minify(MinifyOptions {
+    targets,
+    unsafe_merges: unsafe_merges.unwrap_or(false),
+    ..MinifyOptions::default()
+  });
+  stylesheet.to_css(PrinterOptions {
+    minify: minify.unwrap_or(true),
+    nested: nested.unwrap_or(false),
+    targets,
+    source_map: source_map.unwrap_or(false),
+    ..PrinterOptions::default()
+  }).map_err(|_| napi::Error::new(Status::GenericFailure, "the stylesheet could not be printed".into()))
+}
+
+/// Creates the `{ code, map, exports, warnings }` object returned to JavaScript. The map is `null`
+/// unless a source map was requested, the exports are `null` unless compiling a CSS module, and
+/// each warning is a `{ message, line, column }` object.
+fn create_result(env: &Env, res: ToCssResult, warnings: &[Warning]) -> napi::Result<JsObject> {
+  let mut result = env.create_object()?;
+  result.set_named_property("code", env.create_buffer_with_data(res.code.into_bytes())?.into_raw())?;
+
+  let map: JsUnknown = match res.map {
+    Some(map) => env.create_buffer_with_data(map.to_json(None).into_bytes())?.into_raw().into_unknown(),
+    None => env.get_null()?.into_unknown()
+  };
+  result.set_named_property("map", map)?;
+
+  let exports: JsUnknown = match res.exports {
+    Some(exports) => env.to_js_value(&exports)?,
+    None => env.get_null()?.into_unknown()
+  };
+  result.set_named_property("exports", exports)?;
+
+  let mut array = env.create_array_with_length(warnings.len())?;
+  for (i, warning) in warnings.iter().enumerate() {
+    let mut obj = env.create_object()?;
+    obj.set_named_property("message", env.create_string(&warning.message())?)?;
+    obj.set_named_property("line", env.create_uint32(warning.line)?)?;
+    obj.set_named_property("column", env.create_uint32(warning.column)?)?;
+    array.set_element(i as u32, obj)?;
+  }
+  result.set_named_property("warnings", array)?;
+
+  Ok(result)
+}
+
+#[module_exports]
+fn init(mut exports: JsObject) -> napi::Result<()> {
+  exports.create_named_method("transform", transform)?;
+  exports.create_named_method("bundle", bundle)?;
+
+  Ok(())
+}
@@ -3,15 +3,32 @@ use selectors::SelectorList;
 use std::fmt;
 use std::cell::RefCell;
 use crate::media_query::*;
-use crate::printer::Printer;
+use crate::printer::{Printer, PreserveComments};
 use crate::traits::{Parse, ToCss};
 use std::fmt::Write;
-use crate::selector::{Selectors, SelectorParser};
+use crate::selector::{Selectors, SelectorParser, Namespaces};
 use crate::rules::keyframes::{KeyframeListParser, KeyframesRule};
 use crate::rules::font_face::{FontFaceRule, FontFaceDeclarationParser};
 use crate::rules::page::{PageSelector, PageRule};
-use crate::declaration::{Declaration, DeclarationHandler};
-use crate::properties::VendorPrefix;
+use crate::rules::property::PropertyRule;
+use crate::rules::counter_style::CounterStyleRule;
+use crate::rules::custom_media::CustomMediaRule;
+use crate::rules::unknown::UnknownAtRule;
+use crate::rules::supports::SupportsRule;
+use crate::values::ident::CustomIdent;
+use crate::values::color::CssColor;
+use crate::visitor::{Visitor, Visit};
+use crate::declaration::{Declaration, DeclarationHandler, remove_shadowed_declarations};
+use crate::properties::{Property, VendorPrefix};
+use crate::properties::custom::UnparsedProperty;
+use crate::properties::prefixes::Browsers;
+use crate::values::env::EnvironmentFunction;
+use crate::compat;
+use crate::nesting;
+use crate::limits::{self, LimitKind};
+use crate::warnings::{self, WarningKind};
+use crate::macros::trace;
+use crate::error::ParserError;
 
 #[derive(Eq, PartialEq, Clone)]
 pub struct CssString(RefCell<String>);
@@ -64,11 +81,37 @@ impl std::cmp::PartialEq<str> for CssString {
 
 /// The parser for the top-level rules in a stylesheet.
 pub struct TopLevelRuleParser {
+  state: State,
+  namespaces: Namespaces
 }
 
-impl<'b> TopLevelRuleParser {
-  fn nested<'a: 'b>(&'a self) -> NestedRuleParser {
-      NestedRuleParser {}
+/// The order in which rules must appear at the top level of a stylesheet.
+/// https://drafts.csswg.org/css-namespaces/#syntax
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+enum State {
+  Start = 1,
+  Imports = 2,
+  Namespaces = 3,
+  Body = 4,
+}
+
+impl TopLevelRuleParser {
+  pub fn new() -> TopLevelRuleParser {
+    TopLevelRuleParser {
+      state: State::Start,
+      namespaces: Namespaces::default()
+    }
+  }
+
+  fn nested<'a>(&'a self) -> NestedRuleParser<'a> {
+      NestedRuleParser {
+        namespaces: &self.namespaces
+      }
+  }
+
+  /// Returns whether a rule that sets the given state is allowed at the current position.
+  fn check_state(&self, state: State) -> bool {
+    self.state <= state
   }
 }
 
@@ -80,11 +123,11 @@ pub enum AtRulePrelude {
   /// A @font-feature-values rule prelude, with its FamilyName list.
   FontFeatureValues,//(Vec<FamilyName>),
   /// A @counter-style rule prelude, with its counter style name.
-  CounterStyle,//(CustomIdent),
+  CounterStyle(CustomIdent),
   /// A @media rule prelude, with its media queries.
   Media(MediaList),//(Arc<Locked<MediaList>>),
-  /// An @supports rule, with its conditional
-  Supports,//(SupportsCondition),
+  /// An @supports rule prelude, with its condition as written.
+  Supports(String),
   /// A @viewport rule prelude.
   Viewport,
   /// A @keyframes rule, with its animation name and vendor prefix if exists.
@@ -96,14 +139,18 @@ pub enum AtRulePrelude {
   /// A @import rule prelude.
   Import(String, MediaList),//(CssUrl, Arc<Locked<MediaList>>),
   /// A @namespace rule prelude.
-  Namespace,//(Option<Prefix>, Namespace),
+  Namespace(Option<String>, String),
+  /// A @property rule prelude, with the name of the custom property.
+  Property(String),
+  /// A @custom-media rule.
+  CustomMedia(CustomMediaRule),
 }
 
 impl<'a, 'i> AtRuleParser<'i> for TopLevelRuleParser {
   type PreludeNoBlock = AtRulePrelude;
   type PreludeBlock = AtRulePrelude;
   type AtRule = (SourcePosition, CssRule);
-  type Error = ();
+  type Error = ParserError<'i>;
 
   fn parse_prelude<'t>(
       &mut self,
@@ -112,9 +159,9 @@ impl<'a, 'i> AtRuleParser<'i> for TopLevelRuleParser {
   ) -> Result<AtRuleType<AtRulePrelude, AtRulePrelude>, ParseError<'i, Self::Error>> {
       match_ignore_ascii_case! { &*name,
           "import" => {
-              // if !self.check_state(State::Imports) {
-              //     return Err(input.new_custom_error(StyleParseErrorKind::UnexpectedImportRule))
-              // }
+              if !self.check_state(State::Imports) {
+                  return Err(input.new_custom_error(ParserError::UnexpectedImportRule))
+              }
 
               // if let AllowImportRules::No = self.allow_import_rules {
               //     return Err(input.new_custom_error(StyleParseErrorKind::DisallowedImportRule))
@@ -138,24 +185,21 @@ impl<'a, 'i> AtRuleParser<'i> for TopLevelRuleParser {
 
               return Ok(AtRuleType::WithoutBlock(AtRulePrelude::Import(url_string, media)));
           },
-          // "namespace" => {
-          //     if !self.check_state(State::Namespaces) {
-          //         return Err(input.new_custom_error(StyleParseErrorKind::UnexpectedNamespaceRule))
-          //     }
+          "namespace" => {
+              if !self.check_state(State::Namespaces) {
+                  return Err(input.new_custom_error(ParserError::UnexpectedNamespaceRule))
+              }
 
-          //     let prefix = input.try_parse(|i| i.expect_ident_cloned())
-          //                       .map(|s| Prefix::from(s.as_ref())).ok();
-          //     let maybe_namespace = match input.expect_url_or_string() {
-          //         Ok(url_or_string) => url_or_string,
-          //         Err(BasicParseError { kind: BasicParseErrorKind::UnexpectedToken(t), location }) => {
-          //             return Err(location.new_custom_error(StyleParseErrorKind::UnexpectedTokenWithinNamespace(t)))
-          //         }
-          //         Err(e) => return Err(e.into()),
-          //     };
-          //     let url = Namespace::from(maybe_namespace.as_ref());
-          //     let prelude = AtRuleNonBlockPrelude::Namespace(prefix, url);
-          //     return Ok(AtRuleType::WithoutBlock(prelude));
-          // },
+              let prefix = input.try_parse(|i| i.expect_ident_cloned())
+                                .map(|s| s.as_ref().to_owned()).ok();
+              let url = input.expect_url_or_string()?.as_ref().to_owned();
+              let prelude = AtRulePrelude::Namespace(prefix, url);
+              return Ok(AtRuleType::WithoutBlock(prelude));
+          },
+          "custom-media" => {
+              let rule = CustomMediaRule::parse(input)?;
+              return Ok(AtRuleType::WithoutBlock(AtRulePrelude::CustomMedia(rule)));
+          },
           // // @charset is removed by rust-cssparser if it’s the first rule in the stylesheet
           // // anything left is invalid.
           // "charset" => {
@@ -180,7 +224,7 @@ impl<'a, 'i> AtRuleParser<'i> for TopLevelRuleParser {
       input: &mut Parser<'i, 't>,
   ) -> Result<Self::AtRule, ParseError<'i, Self::Error>> {
       let rule = AtRuleParser::parse_block(&mut self.nested(), prelude, start, input)?;
-      // self.state = State::Body;
+      self.state = State::Body;
       Ok((start.position(), rule))
   }
 
@@ -204,28 +248,29 @@ impl<'a, 'i> AtRuleParser<'i> for TopLevelRuleParser {
               //     media,
               // );
 
-              // self.state = State::Imports;
+              self.state = State::Imports;
               CssRule::Import(ImportRule {
                 url,
                 media
               })
           },
-          // AtRuleNonBlockPrelude::Namespace(prefix, url) => {
-          //     let prefix = if let Some(prefix) = prefix {
-          //         self.namespaces.prefixes.insert(prefix.clone(), url.clone());
-          //         Some(prefix)
-          //     } else {
-          //         self.namespaces.default = Some(url.clone());
-          //         None
-          //     };
-
-          //     self.state = State::Namespaces;
-          //     CssRule::Namespace(Arc::new(self.shared_lock.wrap(NamespaceRule {
-          //         prefix,
-          //         url,
-          //         source_location: start.source_location(),
-          //     })))
-          // },
+          AtRulePrelude::Namespace(prefix, url) => {
+              if let Some(prefix) = &prefix {
+                  self.namespaces.prefixes.insert(prefix.clone(), url.clone());
+              } else {
+                  self.namespaces.default = Some(url.clone());
+              }
+
+              self.state = State::Namespaces;
+              CssRule::Namespace(NamespaceRule {
+                prefix,
+                url
+              })
+          },
+          AtRulePrelude::CustomMedia(rule) => {
+              self.state = State::Body;
+              CssRule::CustomMedia(rule)
+          },
           _ => unreachable!()
       };
 
@@ -236,7 +281,7 @@ impl<'a, 'i> AtRuleParser<'i> for TopLevelRuleParser {
 impl<'a, 'i> QualifiedRuleParser<'i> for TopLevelRuleParser {
   type Prelude = SelectorList<Selectors>;
   type QualifiedRule = (SourcePosition, CssRule);
-  type Error = ();
+  type Error = ParserError<'i>;
 
   #[inline]
   fn parse_prelude<'t>(
@@ -258,17 +303,18 @@ impl<'a, 'i> QualifiedRuleParser<'i> for TopLevelRuleParser {
       input: &mut Parser<'i, 't>,
   ) -> Result<Self::QualifiedRule, ParseError<'i, Self::Error>> {
       let rule = QualifiedRuleParser::parse_block(&mut self.nested(), prelude, start, input)?;
-      // self.state = State::Body;
+      self.state = State::Body;
       // Ok((start.position(), rule))
       Ok((start.position(), rule))
   }
 }
 
 #[derive(Clone)] // shallow, relatively cheap .clone
-struct NestedRuleParser {
+struct NestedRuleParser<'a> {
+  namespaces: &'a Namespaces
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct MediaRule {
   pub query: MediaList,
   pub rules: Vec<CssRule>
@@ -281,6 +327,13 @@ impl ToCss for MediaRule {
     // serialize_string(&self.query, dest)?;
     // dest.write_str(";")
     // dest.write_str(" {")?;
+    // A rule nested within a style rule only contains declarations for the parent selector.
+    if let [CssRule::Style(style)] = self.rules.as_slice() {
+      if nesting::is_nesting_selector(&style.selectors) {
+        return style.block_to_css(dest)
+      }
+    }
+
     dest.whitespace()?;
     dest.write_char('{')?;
     dest.indent();
@@ -294,7 +347,7 @@ impl ToCss for MediaRule {
   }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ImportRule {
   pub url: String,
   pub media: MediaList
@@ -304,25 +357,127 @@ impl ToCss for ImportRule {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> fmt::Result where W: fmt::Write {
     dest.write_str("@import ")?;
     serialize_string(&self.url, dest)?;
-    // dest.write_str(&self.media)?;
+    if !self.media.media_queries.is_empty() {
+      dest.write_char(' ')?;
+      self.media.to_css(dest)?;
+    }
     dest.write_str(";")
   }
 }
 
-#[derive(Debug, PartialEq)]
+/// https://drafts.csswg.org/css-namespaces/#declaration
+#[derive(Debug, Clone, PartialEq)]
+pub struct NamespaceRule {
+  pub prefix: Option<String>,
+  pub url: String
+}
+
+impl ToCss for NamespaceRule {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> fmt::Result where W: fmt::Write {
+    dest.write_str("@namespace")?;
+    if let Some(prefix) = &self.prefix {
+      dest.write_char(' ')?;
+      serialize_identifier(prefix, dest)?;
+    }
+    dest.whitespace()?;
+    serialize_string(&self.url, dest)?;
+    dest.write_char(';')
+  }
+}
+
+#[derive(Clone)]
 pub struct StyleRule {
   pub selectors: SelectorList<Selectors>,
-  pub declarations: DeclarationBlock
+  pub declarations: DeclarationBlock,
+  pub rules: Vec<CssRule>,
+  /// The location of the rule in the source.
+  pub loc: SourceLocation
+}
+
+/// The location is not compared, so that a rule that only moved within the source is
+/// still equal to the original, e.g. when looking it up in the serialization cache.
+impl PartialEq for StyleRule {
+  fn eq(&self, other: &StyleRule) -> bool {
+    self.selectors == other.selectors && self.declarations == other.declarations && self.rules == other.rules
+  }
+}
+
+/// The location is omitted for the same reason as in `PartialEq`.
+impl fmt::Debug for StyleRule {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("StyleRule")
+      .field("selectors", &self.selectors)
+      .field("declarations", &self.declarations)
+      .field("rules", &self.rules)
+      .finish()
+  }
 }
 
 impl ToCss for StyleRule {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> fmt::Result where W: fmt::Write {
+    dest.add_mapping(self.loc);
     self.selectors.to_css(dest)?;
-    self.declarations.to_css(dest)
+    self.block_to_css(dest)
   }
 }
 
-#[derive(Debug, PartialEq)]
+impl StyleRule {
+  /// Prints the block of the rule, containing its declarations and nested rules.
+  fn block_to_css<W>(&self, dest: &mut Printer<W>) -> fmt::Result where W: fmt::Write {
+    let animated = self.declarations.has_transition("transform");
+    if self.rules.is_empty() {
+      return dest.with_animated(animated, |dest| self.declarations.to_css(dest))
+    }
+
+    // Declarations are always terminated with a semicolon when followed by nested rules.
+    dest.whitespace()?;
+    dest.write_char('{')?;
+    dest.indent();
+    dest.with_animated(animated, |dest| {
+      for decl in &self.declarations.declarations {
+        dest.newline()?;
+        decl.to_css(dest)?;
+        dest.write_char(';')?;
+      }
+      Ok(())
+    })?;
+    let len = self.rules.len();
+    for (i, rule) in self.rules.iter().enumerate() {
+      // The last item in the block doesn't need a semicolon when minifying.
+      let terminate = i != len - 1 || !dest.minify;
+      match rule {
+        // Declarations after an unknown at-rule, see `push_declaration`.
+        CssRule::Style(style) if style.rules.is_empty() && nesting::is_nesting_selector(&style.selectors) => {
+          let animated = style.declarations.has_transition("transform");
+          let count = style.declarations.declarations.len();
+          dest.with_animated(animated, |dest| {
+            for (j, decl) in style.declarations.declarations.iter().enumerate() {
+              dest.newline()?;
+              decl.to_css(dest)?;
+              if terminate || j != count - 1 {
+                dest.write_char(';')?;
+              }
+            }
+            Ok(())
+          })?;
+        }
+        CssRule::Unknown(unknown) => {
+          dest.newline()?;
+          unknown.to_css_terminated(dest, terminate)?;
+        }
+        rule => {
+          dest.newline()?;
+          rule.to_css(dest)?;
+        }
+      }
+    }
+    dest.dedent();
+    dest.newline()?;
+    dest.write_char('}')
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct DeclarationBlock {
   pub declarations: Vec<Declaration>
 }
@@ -347,48 +502,241 @@ impl ToCss for DeclarationBlock {
 }
 
 impl DeclarationBlock {
+  /// Returns whether the block contains a transition that may apply to the given property.
+  pub fn has_transition(&self, property: &str) -> bool {
+    let matches = |name: &CustomIdent| {
+      let name = ["-webkit-", "-moz-", "-ms-", "-o-"].iter()
+        .find_map(|prefix| name.0.strip_prefix(prefix))
+        .unwrap_or(name.0.as_str());
+      name.eq_ignore_ascii_case("all") || name.eq_ignore_ascii_case(property)
+    };
+
+    self.declarations.iter().any(|decl| match &decl.property {
+      Property::Transition(transitions, _) => transitions.iter().any(|transition| matches(&transition.property)),
+      Property::TransitionProperty(properties, _) => properties.iter().any(matches),
+      _ => false
+    })
+  }
+
   pub fn minify(&mut self, handler: &mut DeclarationHandler, important_handler: &mut DeclarationHandler) {
+    self.minify_declarations(handler, important_handler, false);
+  }
+
+  /// Like `minify`, but logical properties that depend on the direction are also compiled
+  /// for targets that don't support them. The resulting declarations for left-to-right and
+  /// right-to-left content are returned, to be output in rules for each direction.
+  pub fn minify_with_direction(&mut self, handler: &mut DeclarationHandler, important_handler: &mut DeclarationHandler) -> (DeclarationBlock, DeclarationBlock) {
+    self.minify_declarations(handler, important_handler, true);
+    let (mut ltr, mut rtl) = handler.take_directional();
+    let (important_ltr, important_rtl) = important_handler.take_directional();
+    ltr.extend(important_ltr);
+    rtl.extend(important_rtl);
+    (DeclarationBlock { declarations: ltr }, DeclarationBlock { declarations: rtl })
+  }
+
+  fn minify_declarations(&mut self, handler: &mut DeclarationHandler, important_handler: &mut DeclarationHandler, directional: bool) {
     let mut decls: Vec<Declaration> = vec![];
     for decl in self.declarations.iter() {
-      let handled = 
-        (decl.important && important_handler.handle_property(decl)) ||
-        (!decl.important && handler.handle_property(decl));
+      let handler = if decl.important { &mut *important_handler } else { &mut *handler };
+
+      // Logical properties are compiled first, so the resulting physical properties
+      // are combined with other declarations of the same properties.
+      if let Some(compiled) = handler.compile_logical(decl, directional) {
+        for decl in compiled {
+          if !handler.handle_property(&decl) {
+            decls.push(decl);
+          }
+        }
+        continue
+      }
 
-      if !handled {
+      if !handler.handle_property(decl) {
         decls.push(decl.clone());
       }
     }
 
+    remove_shadowed_declarations(&mut decls);
+    if let Some(targets) = handler.targets {
+      update_legacy_env_functions(&mut decls, targets);
+    }
     decls.extend(handler.finalize());
     decls.extend(important_handler.finalize());
+    if let Some(targets) = handler.targets {
+      add_color_fallbacks(&mut decls, targets, handler.opaque_color_fallbacks);
+    }
     self.declarations = decls;
   }
 }
 
-#[derive(Debug, PartialEq)]
+/// Safari 11.0 and iOS 11.0-11.1 only support environment variables using the legacy `constant()`
+/// function. If any targets need it, a declaration using `constant()` is added before each
+/// declaration using `env()`. Otherwise, such fallback declarations are removed.
+fn update_legacy_env_functions(decls: &mut Vec<Declaration>, targets: Browsers) {
+  let needs_constant = !compat::Feature::EnvFunction.is_compatible(Browsers {
+    safari: targets.safari,
+    ios_saf: targets.ios_saf,
+    ..Browsers::default()
+  });
+
+  let mut result: Vec<Declaration> = Vec::with_capacity(decls.len());
+  for decl in decls.drain(..) {
+    if let Property::Unparsed(unparsed) = &decl.property {
+      let legacy = Declaration {
+        property: Property::Unparsed(UnparsedProperty {
+          name: unparsed.name.clone(),
+          value: unparsed.value.with_env_function(EnvironmentFunction::Constant)
+        }),
+        important: decl.important
+      };
+
+      // Declarations that already use constant() are left as is.
+      if legacy != decl {
+        if result.last() == Some(&legacy) {
+          if !needs_constant {
+            trace!(property = %unparsed.name, "removed constant() fallback");
+            result.pop();
+          }
+        } else if needs_constant {
+          trace!(property = %unparsed.name, "added constant() fallback");
+          result.push(legacy);
+        }
+      }
+    }
+
+    result.push(decl);
+  }
+
+  *decls = result;
+}
+
+/// Adds a declaration before each declaration that uses colors which some of the targets don't
+/// support, e.g. `lab()` or `color(display-p3 …)`, with these colors converted to sRGB. Browsers
+/// that don't support the original declaration ignore it and use the fallback, while the others
+/// override the fallback. If `opaque` is set and some of the targets don't support alpha in colors,
+/// a declaration with opaque colors is added before that as well. Hex colors with alpha are printed
+/// using `rgba()` for targets that don't support them, so no fallback is needed for these.
+/// If the previous declaration is of the same property, it is assumed to be a fallback written by
+/// the author, and none are added.
+fn add_color_fallbacks(decls: &mut Vec<Declaration>, targets: Browsers, opaque: bool) {
+  struct ColorFallback {
+    targets: Browsers,
+    opaque: bool,
+    changed: bool
+  }
+
+  impl Visitor for ColorFallback {
+    fn visit_color(&mut self, color: &mut CssColor) {
+      if let Some(fallback) = color.get_fallback(self.targets) {
+        *color = fallback;
+        self.changed = true;
+      }
+
+      if self.opaque {
+        if let Some(fallback) = color.get_opaque_fallback() {
+          *color = fallback;
+          self.changed = true;
+        }
+      }
+    }
+  }
+
+  let opaque = opaque && !compat::Feature::RgbaColors.is_compatible(targets);
+  let mut result: Vec<Declaration> = Vec::with_capacity(decls.len());
+  for decl in decls.drain(..) {
+    let has_fallback = result.last().map_or(false, |prev| {
+      prev.important == decl.important && prev.property.name() == decl.property.name()
+    });
+
+    if !has_fallback {
+      let mut rgb = decl.clone();
+      let mut visitor = ColorFallback { targets, opaque: false, changed: false };
+      rgb.visit(&mut visitor);
+      let rgb_changed = visitor.changed;
+
+      if opaque {
+        let mut solid = rgb.clone();
+        let mut visitor = ColorFallback { targets, opaque: true, changed: false };
+        solid.visit(&mut visitor);
+        if visitor.changed {
+          trace!(property = decl.property.name(), "added opaque color fallback");
+          result.push(solid);
+        }
+      }
+
+      if rgb_changed {
+        trace!(property = decl.property.name(), "added sRGB color fallback");
+        result.push(rgb);
+      }
+    }
+
+    result.push(decl);
+  }
+
+  *decls = result;
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum CssRule {
   Media(MediaRule),
+  Supports(SupportsRule),
   Import(ImportRule),
+  Namespace(NamespaceRule),
   Style(StyleRule),
   Keyframes(KeyframesRule),
   FontFace(FontFaceRule),
-  Page(PageRule)
+  Page(PageRule),
+  Property(PropertyRule),
+  CounterStyle(CounterStyleRule),
+  CustomMedia(CustomMediaRule),
+  Unknown(UnknownAtRule),
+  /// A comment between top level rules, without the `/*` and `*/` delimiters.
+  Comment(String)
 }
 
 impl ToCss for CssRule {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> fmt::Result where W: fmt::Write {
     match self {
       CssRule::Media(media) => media.to_css(dest),
+      CssRule::Supports(supports) => supports.to_css(dest),
       CssRule::Import(import) => import.to_css(dest),
+      CssRule::Namespace(namespace) => namespace.to_css(dest),
       CssRule::Style(style) => style.to_css(dest),
       CssRule::Keyframes(keyframes) => keyframes.to_css(dest),
       CssRule::FontFace(font_face) => font_face.to_css(dest),
       CssRule::Page(font_face) => font_face.to_css(dest),
+      CssRule::Property(property) => property.to_css(dest),
+      CssRule::CounterStyle(counter_style) => counter_style.to_css(dest),
+      CssRule::CustomMedia(custom_media) => custom_media.to_css(dest),
+      CssRule::Unknown(unknown) => unknown.to_css(dest),
+      CssRule::Comment(comment) => {
+        dest.write_str("/*")?;
+        dest.write_str(comment)?;
+        dest.write_str("*/")
+      }
     }
   }
 }
 
-impl<'a, 'b> NestedRuleParser {
+impl CssRule {
+  /// Returns whether the rule is a comment that is not printed with the given option.
+  pub(crate) fn is_omitted_comment(&self, preserve_comments: PreserveComments) -> bool {
+    matches!(self, CssRule::Comment(comment) if !preserve_comments.keeps(comment))
+  }
+
+  /// Returns whether the rule is a conditional group rule left empty because its contents
+  /// exceeded a limit after `limits::exceeded_count` returned the given count. Such rules
+  /// are dropped along with their contents rather than kept as empty containers.
+  pub(crate) fn is_emptied_since(&self, exceeded_count: usize) -> bool {
+    let is_empty = match self {
+      CssRule::Media(media) => media.rules.is_empty(),
+      CssRule::Supports(supports) => supports.rules.is_empty(),
+      _ => false
+    };
+    is_empty && limits::exceeded_since(exceeded_count)
+  }
+}
+
+impl<'a> NestedRuleParser<'a> {
   fn parse_nested_rules(
       &mut self,
       input: &mut Parser,
@@ -396,15 +744,42 @@ impl<'a, 'b> NestedRuleParser {
   ) -> Vec<CssRule> {
       // let context = ParserContext::new_with_rule_type(self.context, rule_type, self.namespaces);
 
-      let nested_parser = NestedRuleParser {};
+      let nested_parser = NestedRuleParser {
+        namespaces: self.namespaces
+      };
 
       let mut iter = RuleListParser::new_for_nested_rule(input, nested_parser);
       let mut rules = Vec::new();
-      while let Some(result) = iter.next() {
+      loop {
+          if let Some(rule) = parse_unknown_at_rule(iter.input) {
+            if limits::count_rule(iter.input).is_err() {
+              break
+            }
+            rules.push(rule);
+            continue
+          }
+
+          let exceeded_count = limits::exceeded_count();
+          let result = match iter.next() {
+            Some(result) => result,
+            None => break
+          };
+
           match result {
-              Ok(rule) => rules.push(rule),
+              Ok(rule) => {
+                if limits::count_rule(iter.input).is_err() {
+                  break
+                }
+                if rule.is_emptied_since(exceeded_count) {
+                  continue
+                }
+                rules.push(rule)
+              },
               Err((error, slice)) => {
                   let location = error.location;
+                  if warnings::is_validating() {
+                    warnings::warn(location, WarningKind::InvalidRule);
+                  }
                   // let error = ContextualParseError::InvalidRule(slice, error);
                   // self.context.log_css_error(location, error);
               },
@@ -413,13 +788,241 @@ impl<'a, 'b> NestedRuleParser {
       // CssRules::new(rules, self.shared_lock)
       rules
   }
+
+  /// Parses the contents of a style rule, which may contain both declarations and
+  /// nested style rules. https://drafts.csswg.org/css-nesting-1/#syntax
+  /// `parent_selectors` is the number of selectors the rule expands to when flattened.
+  fn parse_style_block<'i, 't>(&self, input: &mut Parser<'i, 't>, parent_selectors: usize) -> (DeclarationBlock, Vec<CssRule>) {
+    let mut declarations = vec![];
+    let mut rules = vec![];
+    let mut count = 0;
+    loop {
+      let start = input.state();
+      let token = match input.next() {
+        Ok(token) => token.clone(),
+        Err(_) => break
+      };
+
+      match token {
+        Token::Semicolon => continue,
+        Token::AtKeyword(ref name) if name.eq_ignore_ascii_case("media") => {
+          if let Some(rule) = self.parse_nested_media_rule(input, &start, parent_selectors) {
+            if limits::count_rule(input).is_err() {
+              break
+            }
+            rules.push(rule);
+          }
+          continue
+        }
+        Token::AtKeyword(name) => {
+          // Other nested at rules are not parsed yet, so they are kept as written,
+          // like unknown at rules.
+          if let Ok(rule) = UnknownAtRule::parse(&name, input) {
+            if limits::count_rule(input).is_err() {
+              break
+            }
+            rules.push(CssRule::Unknown(rule));
+          }
+          continue
+        }
+        Token::Ident(name) => {
+          let decl = input.parse_until_after(Delimiter::Semicolon, |input| {
+            input.expect_colon()?;
+            // A declaration whose value contains a {} block is really a nested rule,
+            // e.g. `a:hover { ... }`. Custom properties may contain blocks.
+            if !name.starts_with("--") && has_top_level_block(input) {
+              return Err(input.new_custom_error(ParserError::InvalidDeclaration))
+            }
+            Declaration::parse(name, input)
+          });
+          if let Ok(decl) = decl {
+            count += 1;
+            if limits::check(input, LimitKind::Declarations, count).is_err() {
+              break
+            }
+            self.push_declaration(decl, &start, &mut declarations, &mut rules);
+            continue
+          }
+          input.reset(&start);
+        }
+        _ => input.reset(&start)
+      }
+
+      let selectors = input.parse_until_before(Delimiter::Semicolon | Delimiter::CurlyBracketBlock, |input| {
+        nesting::parse_nested_selectors(input, self.namespaces)
+      });
+      match input.next() {
+        Ok(Token::CurlyBracketBlock) => {},
+        _ => continue
+      }
+      let selectors = match selectors {
+        Ok(selectors) => selectors,
+        Err(_) => {
+          // A declaration of an unknown property whose value contains a {} block,
+          // e.g. `foo-bar: 1px {x}`, is kept as written.
+          let end = input.state();
+          input.reset(&start);
+          if let Ok(decl) = parse_declaration_with_block(input) {
+            count += 1;
+            if limits::check(input, LimitKind::Declarations, count).is_err() {
+              break
+            }
+            self.push_declaration(decl, &start, &mut declarations, &mut rules);
+            continue
+          }
+          input.reset(&end);
+          if warnings::is_validating() {
+            warnings::warn(start.source_location(), WarningKind::InvalidRule);
+          }
+          continue
+        }
+      };
+
+      // The number of selectors this rule expands to when combined with its parents.
+      let expansion = parent_selectors.saturating_mul(selectors.0.len());
+      if limits::check(input, LimitKind::SelectorExpansion, expansion).is_err() {
+        continue
+      }
+
+      let _guard = match limits::enter(input, LimitKind::BlockDepth) {
+        Ok(guard) => guard,
+        Err(_) => continue
+      };
+      let (block, nested) = input.parse_nested_block(|input| {
+        Ok::<_, ParseError<'i, ParserError<'i>>>(self.parse_style_block(input, expansion))
+      }).unwrap();
+      if limits::count_rule(input).is_err() {
+        break
+      }
+      rules.push(CssRule::Style(StyleRule {
+        selectors,
+        declarations: block,
+        rules: nested,
+        loc: start.source_location()
+      }));
+    }
+
+    (DeclarationBlock { declarations }, rules)
+  }
+
+  /// Adds a declaration parsed within a style block. Declarations after an unknown at-rule such
+  /// as `@apply` are stored in a nested style rule with only a nesting selector, which is printed
+  /// inline, so that they stay in source order relative to the at-rule.
+  fn push_declaration(&self, decl: Declaration, start: &ParserState, declarations: &mut Vec<Declaration>, rules: &mut Vec<CssRule>) {
+    if !rules.iter().any(|rule| matches!(rule, CssRule::Unknown(_))) {
+      declarations.push(decl);
+      return
+    }
+
+    if let Some(CssRule::Style(style)) = rules.last_mut() {
+      if style.rules.is_empty() && nesting::is_nesting_selector(&style.selectors) {
+        style.declarations.declarations.push(decl);
+        return
+      }
+    }
+
+    rules.push(CssRule::Style(StyleRule {
+      selectors: nesting::nesting_selector(self.namespaces),
+      declarations: DeclarationBlock { declarations: vec![decl] },
+      rules: vec![],
+      loc: start.source_location()
+    }));
+  }
+
+  /// Parses a `@media` rule nested directly within a style rule, after its name. The block
+  /// has the same contents as a style rule, which apply to the parent selector, so they are
+  /// stored in a nested style rule with only a nesting selector.
+  /// https://drafts.csswg.org/css-nesting-1/#conditionals
+  fn parse_nested_media_rule<'i, 't>(&self, input: &mut Parser<'i, 't>, start: &ParserState, parent_selectors: usize) -> Option<CssRule> {
+    let query = input.parse_until_before(Delimiter::Semicolon | Delimiter::CurlyBracketBlock, |input| {
+      Ok::<_, ParseError<'i, ParserError<'i>>>(MediaList::parse(input))
+    }).unwrap();
+    match input.next() {
+      Ok(Token::CurlyBracketBlock) => {},
+      _ => {
+        if warnings::is_validating() {
+          warnings::warn(start.source_location(), WarningKind::InvalidRule);
+        }
+        return None
+      }
+    }
+
+    let _guard = limits::enter(input, LimitKind::BlockDepth).ok()?;
+    let (declarations, rules) = input.parse_nested_block(|input| {
+      Ok::<_, ParseError<'i, ParserError<'i>>>(self.parse_style_block(input, parent_selectors))
+    }).unwrap();
+    Some(CssRule::Media(MediaRule {
+      query,
+      rules: vec![CssRule::Style(StyleRule {
+        selectors: nesting::nesting_selector(self.namespaces),
+        declarations,
+        rules,
+        loc: start.source_location()
+      })]
+    }))
+  }
 }
 
-impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser {
+/// Parses the next rule if it is an at-rule that is not otherwise supported, which is kept
+/// as written. Otherwise, returns `None` without consuming any input.
+pub(crate) fn parse_unknown_at_rule(input: &mut Parser) -> Option<CssRule> {
+  let start = input.state();
+  let name = match input.next() {
+    Ok(Token::AtKeyword(name)) if !is_supported_at_rule(name) => name.as_ref().to_owned(),
+    _ => {
+      input.reset(&start);
+      return None
+    }
+  };
+
+  match UnknownAtRule::parse(&name, input) {
+    Ok(rule) => Some(CssRule::Unknown(rule)),
+    Err(_) => {
+      input.reset(&start);
+      None
+    }
+  }
+}
+
+fn is_supported_at_rule(name: &str) -> bool {
+  match_ignore_ascii_case! { name,
+    "import" | "namespace" | "charset" | "custom-media" | "media" | "supports" | "font-face" | "counter-style" |
+    "keyframes" | "-webkit-keyframes" | "-moz-keyframes" | "-o-keyframes" | "property" | "page" => true,
+    _ => false
+  }
+}
+
+/// Parses a declaration of an unknown property whose value contains a {} block, up to and
+/// including the semicolon that ends it.
+fn parse_declaration_with_block<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Declaration, ParseError<'i, ParserError<'i>>> {
+  let name = input.expect_ident_cloned()?;
+  input.parse_until_after(Delimiter::Semicolon, |input| {
+    input.expect_colon()?;
+    if Property::is_known(&name) {
+      return Err(input.new_custom_error(ParserError::InvalidDeclaration))
+    }
+    Declaration::parse(name, input)
+  })
+}
+
+fn has_top_level_block(input: &mut Parser) -> bool {
+  let start = input.state();
+  let mut found = false;
+  while let Ok(token) = input.next() {
+    if let Token::CurlyBracketBlock = token {
+      found = true;
+      break
+    }
+  }
+  input.reset(&start);
+  found
+}
+
+impl<'a, 'i> AtRuleParser<'i> for NestedRuleParser<'a> {
   type PreludeNoBlock = AtRulePrelude;
   type PreludeBlock = AtRulePrelude;
   type AtRule = CssRule;
-  type Error = ();
+  type Error = ParserError<'i>;
 
   fn parse_prelude<'t>(
       &mut self,
@@ -435,10 +1038,10 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser {
               // Ok(AtRuleType::WithBlock(AtRulePrelude::Media(arc)))
               Ok(AtRuleType::WithBlock(AtRulePrelude::Media(media)))
           },
-          // "supports" => {
-          //     let cond = SupportsCondition::parse(input)?;
-          //     Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::Supports(cond)))
-          // },
+          "supports" => {
+            let condition = SupportsRule::parse_condition(input)?;
+            Ok(AtRuleType::WithBlock(AtRulePrelude::Supports(condition)))
+          },
           "font-face" => {
             Ok(AtRuleType::WithBlock(AtRulePrelude::FontFace))
           },
@@ -450,14 +1053,10 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser {
           //     let family_names = parse_family_name_list(self.context, input)?;
           //     Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::FontFeatureValues(family_names)))
           // },
-          // "counter-style" => {
-          //     if !cfg!(feature = "gecko") {
-          //         // Support for this rule is not fully implemented in Servo yet.
-          //         return Err(input.new_custom_error(StyleParseErrorKind::UnsupportedAtRule(name.clone())))
-          //     }
-          //     let name = parse_counter_style_name_definition(input)?;
-          //     Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::CounterStyle(name)))
-          // },
+          "counter-style" => {
+            let name = CounterStyleRule::parse_name(input)?;
+            Ok(AtRuleType::WithBlock(AtRulePrelude::CounterStyle(name)))
+          },
           // "viewport" => {
           //     if viewport_rule::enabled() {
           //         Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::Viewport))
@@ -485,6 +1084,14 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser {
 
               Ok(AtRuleType::WithBlock(AtRulePrelude::Keyframes(name.into(), prefix)))
           },
+          "property" => {
+            let location = input.current_source_location();
+            let name = input.expect_ident_cloned()?;
+            if !name.starts_with("--") {
+              return Err(location.new_unexpected_token_error(Token::Ident(name)))
+            }
+            Ok(AtRuleType::WithBlock(AtRulePrelude::Property(name.as_ref().into())))
+          },
           "page" => {
             let selectors = input.try_parse(|input| input.parse_comma_separated(PageSelector::parse)).unwrap_or_default();
             Ok(AtRuleType::WithBlock(AtRulePrelude::Page(selectors)))
@@ -499,11 +1106,8 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser {
           //     let cond = DocumentCondition::parse(self.context, input)?;
           //     Ok(AtRuleType::WithBlock(AtRuleBlockPrelude::Document(cond)))
           // },
-          // _ => Err(input.new_custom_error(StyleParseErrorKind::UnsupportedAtRule(name.clone())))
-          _ => {
-            print!("UNKNOWN AT RULE {}", name);
-            Ok(AtRuleType::WithBlock(AtRulePrelude::FontFeatureValues))
-          }
+          // Unknown at-rules are parsed by `parse_unknown_at_rule` before reaching the rule parser.
+          _ => Err(input.new_error(BasicParseErrorKind::AtRuleInvalid(name)))
       }
   }
 
@@ -542,48 +1146,28 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser {
           //         ),
           //     ))))
           // },
-          // AtRuleBlockPrelude::CounterStyle(name) => {
-          //     let context = ParserContext::new_with_rule_type(
-          //         self.context,
-          //         CssRuleType::CounterStyle,
-          //         self.namespaces,
-          //     );
-
-          //     Ok(CssRule::CounterStyle(Arc::new(
-          //         self.shared_lock.wrap(
-          //             parse_counter_style_body(name, &context, input, start.source_location())?
-          //                 .into(),
-          //         ),
-          //     )))
-          // },
+          AtRulePrelude::CounterStyle(name) => {
+            Ok(CssRule::CounterStyle(CounterStyleRule::parse(name, input)?))
+          },
           AtRulePrelude::Media(query) => {
               // Ok(CssRule::Media(Arc::new(self.shared_lock.wrap(MediaRule {
               //     media_queries,
               //     rules: self.parse_nested_rules(input, CssRuleType::Media),
               //     source_location: start.source_location(),
               // }))))
+              let _guard = limits::enter(input, LimitKind::BlockDepth)?;
               Ok(CssRule::Media(MediaRule {
                 query,
                 rules: self.parse_nested_rules(input)
               }))
           },
-          // AtRuleBlockPrelude::Supports(condition) => {
-          //     let eval_context = ParserContext::new_with_rule_type(
-          //         self.context,
-          //         CssRuleType::Style,
-          //         self.namespaces,
-          //     );
-
-          //     let enabled = condition.eval(&eval_context, self.namespaces);
-          //     Ok(CssRule::Supports(Arc::new(self.shared_lock.wrap(
-          //         SupportsRule {
-          //             condition,
-          //             rules: self.parse_nested_rules(input, CssRuleType::Supports),
-          //             enabled,
-          //             source_location: start.source_location(),
-          //         },
-          //     ))))
-          // },
+          AtRulePrelude::Supports(condition) => {
+            let _guard = limits::enter(input, LimitKind::BlockDepth)?;
+            Ok(CssRule::Supports(SupportsRule {
+              condition,
+              rules: self.parse_nested_rules(input)
+            }))
+          },
           // AtRuleBlockPrelude::Viewport => {
           //     let context = ParserContext::new_with_rule_type(
           //         self.context,
@@ -618,6 +1202,9 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser {
               }
             }))
           },
+          AtRulePrelude::Property(name) => {
+            Ok(CssRule::Property(PropertyRule::parse(name, input)?))
+          },
           // AtRuleBlockPrelude::Document(condition) => {
           //     if !cfg!(feature = "gecko") {
           //         unreachable!()
@@ -630,32 +1217,31 @@ impl<'a, 'b, 'i> AtRuleParser<'i> for NestedRuleParser {
           //         },
           //     ))))
           // },
-          // _ => Ok()
-          _ => {
-            println!("{:?}", prelude);
-            unreachable!()
-          }
+          // Other preludes are never returned for rules with a block.
+          _ => Err(input.new_custom_error(ParserError::AtRuleBodyInvalid))
       }
   }
 }
 
-impl<'a, 'b, 'i> QualifiedRuleParser<'i> for NestedRuleParser {
+impl<'a, 'i> QualifiedRuleParser<'i> for NestedRuleParser<'a> {
   type Prelude = SelectorList<Selectors>;
   type QualifiedRule = CssRule;
-  type Error = ();
+  type Error = ParserError<'i>;
 
   fn parse_prelude<'t>(
       &mut self,
       input: &mut Parser<'i, 't>,
   ) -> Result<Self::Prelude, ParseError<'i, Self::Error>> {
+      let start = input.state();
+      limits::check_selector_depth(input)?;
+      input.reset(&start);
+
       let selector_parser = SelectorParser {
-      //     stylesheet_origin: self.context.stylesheet_origin,
-      //     namespaces: self.namespaces,
-      //     url_data: Some(self.context.url_data),
+        namespaces: self.namespaces
       };
       match SelectorList::parse(&selector_parser, input) {
         Ok(x) => Ok(x),
-        Err(_) => Err(input.new_error(BasicParseErrorKind::QualifiedRuleInvalid))
+        Err(_) => Err(input.new_custom_error(ParserError::InvalidSelector))
       }
       // Ok(exhaust(input))
   }
@@ -673,20 +1259,13 @@ impl<'a, 'b, 'i> QualifiedRuleParser<'i> for NestedRuleParser {
       //     block,
       //     source_location: start.source_location(),
       // }))))
-      let mut parser = DeclarationListParser::new(input, PropertyDeclarationParser);
-      let mut declarations = vec![];
-      while let Some(decl) = parser.next() {
-        if let Ok(decl) = decl {
-          declarations.push(decl);
-        }
-      }
-
-      // Ok((prelude, declarations))
+      let _guard = limits::enter(input, LimitKind::BlockDepth)?;
+      let (declarations, rules) = self.parse_style_block(input, selectors.0.len());
       Ok(CssRule::Style(StyleRule {
         selectors,
-        declarations: DeclarationBlock {
-          declarations
-        }
+        declarations,
+        rules,
+        loc: start.source_location()
       }))
   }
 }
@@ -696,7 +1275,7 @@ pub struct PropertyDeclarationParser;
 /// Parse a declaration within {} block: `color: blue`
 impl<'i> cssparser::DeclarationParser<'i> for PropertyDeclarationParser {
   type Declaration = Declaration;
-  type Error = ();
+  type Error = ParserError<'i>;
 
   fn parse_value<'t>(
       &mut self,
@@ -712,7 +1291,7 @@ impl<'i> AtRuleParser<'i> for PropertyDeclarationParser {
   type PreludeNoBlock = ();
   type PreludeBlock = ();
   type AtRule = Declaration;
-  type Error = ();
+  type Error = ParserError<'i>;
 }
 
 fn starts_with_ignore_ascii_case(string: &str, prefix: &str) -> bool {
@@ -1,20 +1,146 @@
 use std::fmt::*;
+use crate::properties::prefixes::Browsers;
+use crate::properties::transform::TransformContext;
+use crate::source_map::{Mapping, OriginalLocation};
+use cssparser::SourceLocation;
+
+#[derive(Default, Hash)]
+pub struct PrinterOptions {
+  pub minify: bool,
+  /// Whether to group rules sharing a selector prefix using nesting. Only applies
+  /// when not minifying, and the targets support nesting.
+  pub nested: bool,
+  pub targets: Option<Browsers>,
+  /// Whether to collapse transforms into a matrix when minifying, even within `@keyframes`
+  /// or rules with a transition of `transform`. This often produces shorter output, but
+  /// changes how the transforms are interpolated, e.g. `rotate(360deg)` no longer animates.
+  pub collapse_animated_transforms: bool,
+  /// The sizes to resolve relative lengths and percentages against when collapsing
+  /// transforms into a matrix while minifying. Only set this when the sizes are known,
+  /// e.g. for static content, since the lengths are converted to pixels.
+  pub transform_context: TransformContext,
+  /// The maximum number of significant digits to print numbers with. Defaults to 6.
+  pub precision: Option<u8>,
+  /// Text to emit verbatim at the top of the output, e.g. a license comment or build metadata.
+  /// It is kept when minifying, and should be valid CSS such as a comment.
+  pub banner: Option<String>,
+  /// Text to emit verbatim at the bottom of the output, like `banner`.
+  pub footer: Option<String>,
+  /// Whether to return the files referenced by the stylesheet along with the code.
+  pub analyze_dependencies: bool,
+  /// Whether to generate a source map, which maps each style rule to its location in the source.
+  pub source_map: bool,
+  /// Whether to emit `@charset "UTF-8";` at the start of the output, for tools that require it.
+  /// The `@charset` rule of the source, if any, is always removed while parsing.
+  pub emit_charset: bool,
+  /// Which of the comments between top level rules to print. Minifying a stylesheet
+  /// removes all comments except license comments.
+  pub preserve_comments: PreserveComments
+}
+
+/// Which comments to keep in the output. Only comments between top level rules are kept,
+/// so comments within a rule, e.g. between declarations, are always removed.
+#[derive(Debug, Clone, Copy, PartialEq, Hash)]
+pub enum PreserveComments {
+  None,
+  /// Comments starting with `/*!`, which mark license comments by convention.
+  License,
+  /// All comments between top level rules. `StyleSheet::minify` removes all comments except
+  /// license comments, since they would prevent adjacent rules from being merged, so this
+  /// behaves like `License` for a minified stylesheet.
+  All
+}
+
+impl Default for PreserveComments {
+  fn default() -> PreserveComments {
+    PreserveComments::None
+  }
+}
+
+impl PreserveComments {
+  /// Returns whether to print a comment with the given text, excluding the delimiters.
+  pub fn keeps(&self, comment: &str) -> bool {
+    match self {
+      PreserveComments::None => false,
+      PreserveComments::License => comment.starts_with('!'),
+      PreserveComments::All => true
+    }
+  }
+}
 
 pub struct Printer<'a, W> {
   dest: &'a mut W,
   indent: u8,
-  pub minify: bool
+  pub minify: bool,
+  /// Whether the transforms being printed may be interpolated by an animation or transition.
+  pub animated: bool,
+  pub collapse_animated_transforms: bool,
+  pub transform_context: TransformContext,
+  /// The maximum number of significant digits to print numbers with.
+  pub precision: u8,
+  /// The browsers to generate output for, if any. Values with no equivalent in the minified
+  /// declaration tree, e.g. math functions nested within other values, are lowered at print time.
+  pub targets: Option<Browsers>,
+  /// The current position in the output, starting at 0, which is only tracked when generating
+  /// a source map. Columns are counted in UTF-16 code units, as browsers do when reading source maps.
+  pub line: u32,
+  pub column: u32,
+  /// The source map mappings, if a source map is being generated.
+  pub mappings: Option<Vec<Mapping>>,
+  pub preserve_comments: PreserveComments
 }
 
 impl<'a, W: Write + Sized> Printer<'a, W> {
   pub fn new(dest: &mut W, minify: bool) -> Printer<W> {
-    Printer { dest, indent: 0, minify }
+    Printer { dest, indent: 0, minify, animated: false, collapse_animated_transforms: false, transform_context: TransformContext::default(), precision: 6, targets: None, line: 0, column: 0, mappings: None, preserve_comments: PreserveComments::None }
+  }
+
+  pub fn with_options(dest: &'a mut W, options: &PrinterOptions) -> Printer<'a, W> {
+    let mut printer = Printer::new(dest, options.minify);
+    printer.collapse_animated_transforms = options.collapse_animated_transforms;
+    printer.transform_context = options.transform_context;
+    if let Some(precision) = options.precision {
+      printer.precision = precision;
+    }
+    printer.targets = options.targets;
+    printer.preserve_comments = options.preserve_comments;
+    if options.source_map {
+      printer.mappings = Some(vec![]);
+    }
+    printer
   }
 
   pub fn write_str(&mut self, s: &str) -> Result {
+    if self.mappings.is_some() {
+      match s.rfind('\n') {
+        Some(pos) => {
+          self.line += s.matches('\n').count() as u32;
+          self.column = s[pos + 1..].encode_utf16().count() as u32;
+        }
+        None => self.column += s.encode_utf16().count() as u32
+      }
+    }
     self.dest.write_str(s)
   }
 
+  /// Maps the current position in the output to the given location in the source,
+  /// if a source map is being generated.
+  pub fn add_mapping(&mut self, loc: SourceLocation) {
+    let (line, column) = (self.line, self.column);
+    if let Some(mappings) = &mut self.mappings {
+      mappings.push(Mapping {
+        generated_line: line,
+        generated_column: column,
+        original: Some(OriginalLocation {
+          source: 0,
+          line: loc.line,
+          // cssparser's columns start at 1.
+          column: loc.column.saturating_sub(1)
+        })
+      });
+    }
+  }
+
   pub fn whitespace(&mut self) -> Result {
     if self.minify {
       return Ok(())
@@ -51,10 +177,18 @@ impl<'a, W: Write + Sized> Printer<'a, W> {
   pub fn dedent(&mut self) {
     self.indent -= 2;
   }
+
+  /// Calls the function with `animated` set to the given value, and restores it afterward.
+  pub fn with_animated<F: FnOnce(&mut Self) -> Result>(&mut self, animated: bool, f: F) -> Result {
+    let prev = std::mem::replace(&mut self.animated, animated);
+    let res = f(self);
+    self.animated = prev;
+    res
+  }
 }
 
 impl<'a, W: Write + Sized> Write for Printer<'a, W> {
   fn write_str(&mut self, s: &str) -> Result {
-    self.dest.write_str(s)
+    Printer::write_str(self, s)
   }
 }
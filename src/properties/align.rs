@@ -5,7 +5,10 @@ use crate::traits::{Parse, ToCss, PropertyHandler, FromStandard};
 use super::{Property, VendorPrefix};
 use super::flex::{BoxAlign, FlexLinePack, BoxPack, FlexPack, FlexAlign, FlexItemAlign};
 use super::prefixes::{Browsers, Feature, is_flex_2009};
+use super::prefix_handler::expand_prefixes;
 use crate::printer::Printer;
+use crate::compat;
+use crate::error::ParserError;
 
 /// https://www.w3.org/TR/2020/WD-css-align-3-20200421/#typedef-baseline-position
 #[derive(Debug, Clone, PartialEq)]
@@ -15,7 +18,7 @@ pub enum BaselinePosition {
 }
 
 impl Parse for BaselinePosition {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let location = input.current_source_location();
     let ident = input.expect_ident()?;
     match_ignore_ascii_case! { &*ident,
@@ -77,7 +80,7 @@ pub enum AlignContent {
 }
 
 impl Parse for AlignContent {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
       return Ok(AlignContent::Normal)
     }
@@ -125,7 +128,7 @@ pub enum JustifyContent {
 }
 
 impl Parse for JustifyContent {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
       return Ok(JustifyContent::Normal)
     }
@@ -192,7 +195,7 @@ pub struct PlaceContent {
 }
 
 impl Parse for PlaceContent {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let align = AlignContent::parse(input)?;
     let justify = match input.try_parse(JustifyContent::parse) {
       Ok(j) => j,
@@ -254,7 +257,7 @@ pub enum AlignSelf {
 }
 
 impl Parse for AlignSelf {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
       return Ok(AlignSelf::Auto)
     }
@@ -309,7 +312,7 @@ pub enum JustifySelf {
 }
 
 impl Parse for JustifySelf {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
       return Ok(JustifySelf::Auto)
     }
@@ -386,7 +389,7 @@ pub struct PlaceSelf {
 }
 
 impl Parse for PlaceSelf {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let align = AlignSelf::parse(input)?;
     let justify = match input.try_parse(JustifySelf::parse) {
       Ok(j) => j,
@@ -437,7 +440,7 @@ pub enum AlignItems {
 }
 
 impl Parse for AlignItems {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
       return Ok(AlignItems::Normal)
     }
@@ -482,7 +485,7 @@ pub enum LegacyJustify {
 }
 
 impl Parse for LegacyJustify {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let location = input.current_source_location();
     let ident = input.expect_ident()?;
     match_ignore_ascii_case! { &*ident,
@@ -541,7 +544,7 @@ pub enum JustifyItems {
 }
 
 impl Parse for JustifyItems {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
       return Ok(JustifyItems::Normal)
     }
@@ -618,7 +621,7 @@ pub struct PlaceItems {
 }
 
 impl Parse for PlaceItems {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let align = AlignItems::parse(input)?;
     let justify = match input.try_parse(JustifyItems::parse) {
       Ok(j) => j,
@@ -665,7 +668,7 @@ pub enum GapValue {
 }
 
 impl Parse for GapValue {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
       return Ok(GapValue::Normal)
     }
@@ -687,12 +690,12 @@ impl ToCss for GapValue {
 /// https://www.w3.org/TR/2020/WD-css-align-3-20200421/#gap-shorthand
 #[derive(Debug, Clone, PartialEq)]
 pub struct Gap {
-  row: GapValue,
-  column: GapValue
+  pub row: GapValue,
+  pub column: GapValue
 }
 
 impl Parse for Gap {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let row = GapValue::parse(input)?;
     let column = input.try_parse(GapValue::parse).unwrap_or(row.clone());
     Ok(Gap { row, column })
@@ -726,7 +729,7 @@ pub struct AlignHandler {
   flex_align: Option<(FlexAlign, VendorPrefix)>,
   justify_items: Option<JustifyItems>,
   row_gap: Option<GapValue>,
-  column_gap: Option<GapValue>,
+  column_gap: Option<(GapValue, VendorPrefix)>,
   decls: Vec<Property>
 }
 
@@ -808,11 +811,19 @@ impl PropertyHandler for AlignHandler {
         property!(align_items, &val.align, &VendorPrefix::None);
         self.justify_items = Some(val.justify.clone());
       }
-      RowGap(val) => self.row_gap = Some(val.clone()),
-      ColumnGap(val) => self.column_gap = Some(val.clone()),
-      Gap(val) => {
+      RowGap(val) | GridRowGap(val) => self.row_gap = Some(val.clone()),
+      // The legacy grid properties are aliases of the standard ones.
+      GridColumnGap(val) => self.column_gap = Some((val.clone(), VendorPrefix::None)),
+      ColumnGap(val, vp) => {
+        // Prefixed variants with the same value are combined, otherwise the last value wins.
+        match &mut self.column_gap {
+          Some((cur, prefixes)) if *cur == *val => *prefixes |= *vp,
+          _ => self.column_gap = Some((val.clone(), *vp))
+        }
+      }
+      Gap(val) | GridGap(val) => {
         self.row_gap = Some(val.row.clone());
-        self.column_gap = Some(val.column.clone());
+        self.column_gap = Some((val.column.clone(), VendorPrefix::None));
       }
       _ => return false
     }
@@ -937,8 +948,8 @@ impl AlignHandler {
             $justify_prop
           })?;
 
-          // Only use shorthand if unprefixed.
-          if intersection.contains(VendorPrefix::None) {
+          // Only use shorthand if unprefixed, and supported by all targets.
+          if intersection.contains(VendorPrefix::None) && self.targets.map_or(true, |targets| compat::Feature::$prop.is_compatible(targets)) {
             // Add prefixed longhands if needed.
             *align_prefix = prefixes!($align_prop);
             align_prefix.remove(VendorPrefix::None);
@@ -995,6 +1006,45 @@ impl AlignHandler {
     standard_property!(AlignItems, align_items);
     unprefixed_property!(JustifyItems, justify_items);
 
+    // Prefixes are only needed for `column-gap` in multi-column layouts,
+    // and are output separately from the unprefixed property.
+    let column_gap = match column_gap {
+      Some((gap, prefixes)) => {
+        let mut prefixes = expand_prefixes(prefixes, Feature::ColumnGap, self.targets);
+        let unprefixed = prefixes.contains(VendorPrefix::None);
+        prefixes.remove(VendorPrefix::None);
+        if !prefixes.is_empty() {
+          self.decls.push(Property::ColumnGap(gap.clone(), prefixes))
+        }
+        if unprefixed {
+          Some(gap)
+        } else {
+          None
+        }
+      }
+      None => None
+    };
+
+    // Older browsers only support gaps in grid layouts with the legacy names, which also
+    // don't accept `normal`. These are output first so the standard properties take precedence.
+    if self.targets.map_or(false, |targets| !compat::Feature::Gap.is_compatible(targets)) {
+      let is_length = |gap: &Option<GapValue>| matches!(gap, Some(GapValue::LengthPercentage(_)));
+      if is_length(&row_gap) && is_length(&column_gap) {
+        self.decls.push(Property::GridGap(Gap {
+          row: row_gap.clone().unwrap(),
+          column: column_gap.clone().unwrap()
+        }))
+      } else {
+        if let Some(gap @ GapValue::LengthPercentage(_)) = &row_gap {
+          self.decls.push(Property::GridRowGap(gap.clone()))
+        }
+
+        if let Some(gap @ GapValue::LengthPercentage(_)) = &column_gap {
+          self.decls.push(Property::GridColumnGap(gap.clone()))
+        }
+      }
+    }
+
     if row_gap.is_some() && column_gap.is_some() {
       self.decls.push(Property::Gap(Gap {
         row: row_gap.unwrap(),
@@ -1006,7 +1056,7 @@ impl AlignHandler {
       }
 
       if let Some(gap) = column_gap {
-        self.decls.push(Property::ColumnGap(gap))
+        self.decls.push(Property::ColumnGap(gap, VendorPrefix::None))
       }
     }
   }
@@ -2,12 +2,15 @@ use cssparser::*;
 use crate::traits::{Parse, ToCss, PropertyHandler};
 use crate::values::{time::Time, easing::EasingFunction};
 use super::prefixes::{Feature, Browsers};
-use crate::properties::{Property, VendorPrefix};
+use crate::properties::Property;
+use super::prefix_handler::{PrefixedPropertyState, expand_prefixes};
 use crate::printer::Printer;
 use std::fmt::Write;
 use itertools::izip;
 use crate::macros::*;
 use smallvec::SmallVec;
+use std::collections::{HashMap, HashSet};
+use crate::error::ParserError;
 
 /// https://drafts.csswg.org/css-animations/#animation-name
 #[derive(Debug, Clone, PartialEq)]
@@ -17,7 +20,7 @@ pub enum AnimationName {
 }
 
 impl Parse for AnimationName {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
       return Ok(AnimationName::None)
     }
@@ -32,6 +35,17 @@ impl Parse for AnimationName {
   }
 }
 
+impl AnimationName {
+  /// Renames the animation if it refers to keyframes with a name in the given map.
+  fn rename(&mut self, names: &HashMap<String, String>) {
+    if let AnimationName::String(name) = self {
+      if let Some(new_name) = names.get(name) {
+        *name = new_name.clone();
+      }
+    }
+  }
+}
+
 impl ToCss for AnimationName {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
     match self {
@@ -49,7 +63,7 @@ pub enum AnimationIterationCount {
 }
 
 impl Parse for AnimationIterationCount {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("infinite")).is_ok() {
       return Ok(AnimationIterationCount::Infinite)
     }
@@ -104,7 +118,7 @@ pub struct Animation {
 }
 
 impl Parse for Animation {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut name = None;
     let mut duration = None;
     let mut timing_function = None;
@@ -200,14 +214,14 @@ impl ToCss for Animation {
 #[derive(Default)]
 pub struct AnimationHandler {
   targets: Option<Browsers>,
-  names: Option<(SmallVec<[AnimationName; 1]>, VendorPrefix)>,
-  durations: Option<(SmallVec<[Time; 1]>, VendorPrefix)>,
-  timing_functions: Option<(SmallVec<[EasingFunction; 1]>, VendorPrefix)>,
-  iteration_counts: Option<(SmallVec<[AnimationIterationCount; 1]>, VendorPrefix)>,
-  directions: Option<(SmallVec<[AnimationDirection; 1]>, VendorPrefix)>,
-  play_states: Option<(SmallVec<[AnimationPlayState; 1]>, VendorPrefix)>,
-  delays: Option<(SmallVec<[Time; 1]>, VendorPrefix)>,
-  fill_modes: Option<(SmallVec<[AnimationFillMode; 1]>, VendorPrefix)>,
+  names: PrefixedPropertyState<SmallVec<[AnimationName; 1]>>,
+  durations: PrefixedPropertyState<SmallVec<[Time; 1]>>,
+  timing_functions: PrefixedPropertyState<SmallVec<[EasingFunction; 1]>>,
+  iteration_counts: PrefixedPropertyState<SmallVec<[AnimationIterationCount; 1]>>,
+  directions: PrefixedPropertyState<SmallVec<[AnimationDirection; 1]>>,
+  play_states: PrefixedPropertyState<SmallVec<[AnimationPlayState; 1]>>,
+  delays: PrefixedPropertyState<SmallVec<[Time; 1]>>,
+  fill_modes: PrefixedPropertyState<SmallVec<[AnimationFillMode; 1]>>,
   decls: Vec<Property>
 }
 
@@ -224,74 +238,60 @@ impl PropertyHandler for AnimationHandler {
   fn handle_property(&mut self, property: &Property) -> bool {
     use Property::*;
 
-    macro_rules! maybe_flush {
-      ($prop: ident, $val: expr, $vp: ident) => {{
-        // If two vendor prefixes for the same property have different
-        // values, we need to flush what we have immediately to preserve order.
-        if let Some((val, prefixes)) = &self.$prop {
-          if val != $val && !prefixes.contains(*$vp) {
-            self.flush();
-          }
-        }
-      }};
-    }
-
     macro_rules! property {
-      ($prop: ident, $val: expr, $vp: ident) => {{
-        maybe_flush!($prop, $val, $vp);
-
-        // Otherwise, update the value and add the prefix.
-        if let Some((val, prefixes)) = &mut self.$prop {
-          *val = $val.clone();
-          *prefixes |= *$vp;
-        } else {
-          self.$prop = Some(($val.clone(), *$vp))
+      ($prop: ident, $feature: ident, $val: expr, $vp: ident) => {{
+        self.$prop.remove_overridden(*$vp, self.targets);
+        if self.$prop.needs_flush($val, *$vp) {
+          self.flush();
         }
+        self.$prop.set($val, *$vp);
       }};
     }
 
     match property {
-      AnimationName(val, vp) => property!(names, val, vp),
-      AnimationDuration(val, vp) => property!(durations, val, vp),
-      AnimationTimingFunction(val, vp) => property!(timing_functions, val, vp),
-      AnimationIterationCount(val, vp) => property!(iteration_counts, val, vp),
-      AnimationDirection(val, vp) => property!(directions, val, vp),
-      AnimationPlayState(val, vp) => property!(play_states, val, vp),
-      AnimationDelay(val, vp) => property!(delays, val, vp),
-      AnimationFillMode(val, vp) => property!(fill_modes, val, vp),
+      AnimationName(val, vp) => property!(names, AnimationName, val, vp),
+      AnimationDuration(val, vp) => property!(durations, AnimationDuration, val, vp),
+      AnimationTimingFunction(val, vp) => property!(timing_functions, AnimationTimingFunction, val, vp),
+      AnimationIterationCount(val, vp) => property!(iteration_counts, AnimationIterationCount, val, vp),
+      AnimationDirection(val, vp) => property!(directions, AnimationDirection, val, vp),
+      AnimationPlayState(val, vp) => property!(play_states, AnimationPlayState, val, vp),
+      AnimationDelay(val, vp) => property!(delays, AnimationDelay, val, vp),
+      AnimationFillMode(val, vp) => property!(fill_modes, AnimationFillMode, val, vp),
       Animation(val, vp) => {
         let names = val.iter().map(|b| b.name.clone()).collect();
-        maybe_flush!(names, &names, vp);
-
         let durations = val.iter().map(|b| b.duration.clone()).collect();
-        maybe_flush!(durations, &durations, vp);
-
         let timing_functions = val.iter().map(|b| b.timing_function.clone()).collect();
-        maybe_flush!(timing_functions, &timing_functions, vp);
-
         let iteration_counts = val.iter().map(|b| b.iteration_count.clone()).collect();
-        maybe_flush!(iteration_counts, &iteration_counts, vp);
-
         let directions = val.iter().map(|b| b.direction.clone()).collect();
-        maybe_flush!(directions, &directions, vp);
-
         let play_states = val.iter().map(|b| b.play_state.clone()).collect();
-        maybe_flush!(play_states, &play_states, vp);
-
         let delays = val.iter().map(|b| b.delay.clone()).collect();
-        maybe_flush!(delays, &delays, vp);
-
         let fill_modes = val.iter().map(|b| b.fill_mode.clone()).collect();
-        maybe_flush!(fill_modes, &fill_modes, vp);
-
-        property!(names, &names, vp);
-        property!(durations, &durations, vp);
-        property!(timing_functions, &timing_functions, vp);
-        property!(iteration_counts, &iteration_counts, vp);
-        property!(directions, &directions, vp);
-        property!(play_states, &play_states, vp);
-        property!(delays, &delays, vp);
-        property!(fill_modes, &fill_modes, vp);
+
+        self.names.remove_overridden(*vp, self.targets);
+        self.durations.remove_overridden(*vp, self.targets);
+        self.timing_functions.remove_overridden(*vp, self.targets);
+        self.iteration_counts.remove_overridden(*vp, self.targets);
+        self.directions.remove_overridden(*vp, self.targets);
+        self.play_states.remove_overridden(*vp, self.targets);
+        self.delays.remove_overridden(*vp, self.targets);
+        self.fill_modes.remove_overridden(*vp, self.targets);
+
+        // Flush before setting any of the longhands, so the shorthand isn't split.
+        if self.names.needs_flush(&names, *vp) || self.durations.needs_flush(&durations, *vp) ||
+          self.timing_functions.needs_flush(&timing_functions, *vp) || self.iteration_counts.needs_flush(&iteration_counts, *vp) ||
+          self.directions.needs_flush(&directions, *vp) || self.play_states.needs_flush(&play_states, *vp) ||
+          self.delays.needs_flush(&delays, *vp) || self.fill_modes.needs_flush(&fill_modes, *vp) {
+          self.flush();
+        }
+
+        self.names.set(&names, *vp);
+        self.durations.set(&durations, *vp);
+        self.timing_functions.set(&timing_functions, *vp);
+        self.iteration_counts.set(&iteration_counts, *vp);
+        self.directions.set(&directions, *vp);
+        self.play_states.set(&play_states, *vp);
+        self.delays.set(&delays, *vp);
+        self.fill_modes.set(&fill_modes, *vp);
       }
       _ => return false
     }
@@ -307,73 +307,85 @@ impl PropertyHandler for AnimationHandler {
 
 impl AnimationHandler {
   fn flush(&mut self) {
-    let mut names = std::mem::take(&mut self.names);
-    let mut durations = std::mem::take(&mut self.durations);
-    let mut timing_functions = std::mem::take(&mut self.timing_functions);
-    let mut iteration_counts = std::mem::take(&mut self.iteration_counts);
-    let mut directions = std::mem::take(&mut self.directions);
-    let mut play_states = std::mem::take(&mut self.play_states);
-    let mut delays = std::mem::take(&mut self.delays);
-    let mut fill_modes = std::mem::take(&mut self.fill_modes);
-
-    if let (Some((names, names_vp)), Some((durations, durations_vp)), Some((timing_functions, timing_functions_vp)), Some((iteration_counts, iteration_counts_vp)), Some((directions, directions_vp)), Some((play_states, play_states_vp)), Some((delays, delays_vp)), Some((fill_modes, fill_modes_vp))) = (&mut names, &mut durations, &mut timing_functions, &mut iteration_counts, &mut directions, &mut play_states, &mut delays, &mut fill_modes) {
+    if let (Some(names), Some(durations), Some(timing_functions), Some(iteration_counts), Some(directions), Some(play_states), Some(delays), Some(fill_modes)) = (self.names.value(), self.durations.value(), self.timing_functions.value(), self.iteration_counts.value(), self.directions.value(), self.play_states.value(), self.delays.value(), self.fill_modes.value()) {
       // Only use shorthand syntax if the number of animations matches on all properties.
       let len = names.len();
-      let intersection = *names_vp & *durations_vp & *timing_functions_vp & *iteration_counts_vp & *directions_vp & *play_states_vp & *delays_vp & *fill_modes_vp;
+      let intersection = self.names.prefixes() & self.durations.prefixes() & self.timing_functions.prefixes() & self.iteration_counts.prefixes() &
+        self.directions.prefixes() & self.play_states.prefixes() & self.delays.prefixes() & self.fill_modes.prefixes();
       if !intersection.is_empty() && durations.len() == len && timing_functions.len() == len && iteration_counts.len() == len && directions.len() == len && play_states.len() == len && delays.len() == len && fill_modes.len() == len {
-        let animations = izip!(names.drain(..), durations.drain(..), timing_functions.drain(..), iteration_counts.drain(..), directions.drain(..), play_states.drain(..), delays.drain(..), fill_modes.drain(..)).map(|(name, duration, timing_function, iteration_count, direction, play_state, delay, fill_mode)| {
+        let animations = izip!(names, durations, timing_functions, iteration_counts, directions, play_states, delays, fill_modes).map(|(name, duration, timing_function, iteration_count, direction, play_state, delay, fill_mode)| {
           Animation {
-            name,
-            duration,
-            timing_function,
-            iteration_count,
-            direction,
-            play_state,
-            delay,
-            fill_mode
+            name: name.clone(),
+            duration: duration.clone(),
+            timing_function: timing_function.clone(),
+            iteration_count: iteration_count.clone(),
+            direction: direction.clone(),
+            play_state: play_state.clone(),
+            delay: delay.clone(),
+            fill_mode: fill_mode.clone()
           }
         }).collect();
-        let mut prefix = intersection;
-        if prefix.contains(VendorPrefix::None) {
-          if let Some(targets) = self.targets {
-            prefix = Feature::Animation.prefixes_for(targets)
-          }
-        }
-        self.decls.push(Property::Animation(animations, prefix));
-        names_vp.remove(intersection);
-        durations_vp.remove(intersection);
-        timing_functions_vp.remove(intersection);
-        iteration_counts_vp.remove(intersection);
-        directions_vp.remove(intersection);
-        play_states_vp.remove(intersection);
-        delays_vp.remove(intersection);
-        fill_modes_vp.remove(intersection);
+
+        self.decls.push(Property::Animation(animations, expand_prefixes(intersection, Feature::Animation, self.targets)));
+        self.names.remove_prefixes(intersection);
+        self.durations.remove_prefixes(intersection);
+        self.timing_functions.remove_prefixes(intersection);
+        self.iteration_counts.remove_prefixes(intersection);
+        self.directions.remove_prefixes(intersection);
+        self.play_states.remove_prefixes(intersection);
+        self.delays.remove_prefixes(intersection);
+        self.fill_modes.remove_prefixes(intersection);
       }
     }
 
-    macro_rules! prop {
-      ($var: ident, $property: ident) => {
-        if let Some((val, vp)) = $var {
-          if !vp.is_empty() {
-            let mut prefix = vp;
-            if prefix.contains(VendorPrefix::None) {
-              if let Some(targets) = self.targets {
-                prefix = Feature::$property.prefixes_for(targets)
-              }
-            }
-            self.decls.push(Property::$property(val, prefix))
-          }
+    macro_rules! single_property {
+      ($prop: ident, $key: ident) => {
+        if let Some((val, vp)) = self.$key.take(Feature::$prop, self.targets) {
+          self.decls.push(Property::$prop(val, vp))
         }
       };
     }
 
-    prop!(names, AnimationName);
-    prop!(durations, AnimationDuration);
-    prop!(timing_functions, AnimationTimingFunction);
-    prop!(iteration_counts, AnimationIterationCount);
-    prop!(directions, AnimationDirection);
-    prop!(play_states, AnimationPlayState);
-    prop!(delays, AnimationDelay);
-    prop!(fill_modes, AnimationFillMode);
+    single_property!(AnimationName, names);
+    single_property!(AnimationDuration, durations);
+    single_property!(AnimationTimingFunction, timing_functions);
+    single_property!(AnimationIterationCount, iteration_counts);
+    single_property!(AnimationDirection, directions);
+    single_property!(AnimationPlayState, play_states);
+    single_property!(AnimationDelay, delays);
+    single_property!(AnimationFillMode, fill_modes);
+  }
+}
+
+/// Renames references to keyframes in `animation-name` and `animation` declarations,
+/// using a map from the current names to the new names.
+pub(crate) fn rename_animations(property: &mut Property, names: &HashMap<String, String>) {
+  match property {
+    Property::AnimationName(animation_names, _) => {
+      for name in animation_names.iter_mut() {
+        name.rename(names);
+      }
+    }
+    Property::Animation(animations, _) => {
+      for animation in animations.iter_mut() {
+        animation.name.rename(names);
+      }
+    }
+    _ => {}
+  }
+}
+
+/// Adds the names of the keyframes referenced by `animation-name` and `animation` declarations.
+pub(crate) fn collect_animation_names(property: &Property, names: &mut HashSet<String>) {
+  let animation_names: Vec<&AnimationName> = match property {
+    Property::AnimationName(animation_names, _) => animation_names.iter().collect(),
+    Property::Animation(animations, _) => animations.iter().map(|animation| &animation.name).collect(),
+    _ => return
+  };
+
+  for name in animation_names {
+    if let AnimationName::String(name) = name {
+      names.insert(name.clone());
+    }
   }
 }
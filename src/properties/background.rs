@@ -8,9 +8,12 @@ use crate::values::{
 use crate::traits::{Parse, ToCss, PropertyHandler};
 use crate::macros::*;
 use crate::properties::{Property, VendorPrefix};
+use crate::properties::prefixes::Browsers;
 use itertools::izip;
 use crate::printer::Printer;
+use crate::{compat, warnings};
 use smallvec::SmallVec;
+use crate::error::ParserError;
 
 /// https://www.w3.org/TR/css-backgrounds-3/#background-size
 #[derive(Debug, Clone, PartialEq)]
@@ -33,7 +36,7 @@ impl Default for BackgroundSize {
 }
 
 impl Parse for BackgroundSize {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(width) = input.try_parse(LengthPercentageOrAuto::parse) {
       let height = input.try_parse(LengthPercentageOrAuto::parse).unwrap_or(LengthPercentageOrAuto::Auto);
       return Ok(BackgroundSize::Explicit { width, height });
@@ -95,7 +98,7 @@ impl Default for BackgroundRepeat {
 }
 
 impl Parse for BackgroundRepeat {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     use BackgroundRepeatKeyword::*;
     let state = input.state();
     let ident = input.expect_ident()?;
@@ -132,12 +135,41 @@ impl ToCss for BackgroundRepeat {
   }
 }
 
-// https://www.w3.org/TR/css-backgrounds-3/#background-attachment
-enum_property!(BackgroundAttachment,
+/// https://www.w3.org/TR/css-backgrounds-3/#background-attachment
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BackgroundAttachment {
   Scroll,
   Fixed,
   Local
-);
+}
+
+impl Parse for BackgroundAttachment {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    input.skip_whitespace();
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    match_ignore_ascii_case! { &*ident,
+      "scroll" => Ok(BackgroundAttachment::Scroll),
+      "fixed" => {
+        // iOS Safari renders fixed backgrounds as if they scrolled with the element.
+        warnings::check_feature(location, compat::Feature::BackgroundAttachmentFixed);
+        Ok(BackgroundAttachment::Fixed)
+      },
+      "local" => Ok(BackgroundAttachment::Local),
+      _ => Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+    }
+  }
+}
+
+impl ToCss for BackgroundAttachment {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      BackgroundAttachment::Scroll => dest.write_str("scroll"),
+      BackgroundAttachment::Fixed => dest.write_str("fixed"),
+      BackgroundAttachment::Local => dest.write_str("local")
+    }
+  }
+}
 
 impl Default for BackgroundAttachment {
   fn default() -> BackgroundAttachment {
@@ -185,18 +217,18 @@ impl Into<BackgroundClip> for BackgroundBox {
 // https://www.w3.org/TR/css-backgrounds-3/#background
 #[derive(Debug, Clone, PartialEq)]
 pub struct Background {
-  image: Image,
-  color: CssColor,
-  position: Position,
-  repeat: BackgroundRepeat,
-  size: BackgroundSize,
-  attachment: BackgroundAttachment,
-  origin: BackgroundBox,
-  clip: BackgroundClip
+  pub image: Image,
+  pub color: CssColor,
+  pub position: Position,
+  pub repeat: BackgroundRepeat,
+  pub size: BackgroundSize,
+  pub attachment: BackgroundAttachment,
+  pub origin: BackgroundBox,
+  pub clip: BackgroundClip
 }
 
 impl Parse for Background {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut color: Option<CssColor> = None;
     let mut position: Option<Position> = None;
     let mut size: Option<BackgroundSize> = None;
@@ -353,6 +385,7 @@ impl ToCss for Background {
 
 #[derive(Default)]
 pub struct BackgroundHandler {
+  targets: Option<Browsers>,
   color: Option<CssColor>,
   images: Option<SmallVec<[Image; 1]>>,
   x_positions: Option<SmallVec<[HorizontalPosition; 1]>>,
@@ -369,7 +402,10 @@ impl PropertyHandler for BackgroundHandler {
   fn handle_property(&mut self, property: &Property) -> bool {
     match &property {
       Property::BackgroundColor(val) => self.color = Some(val.clone()),
-      Property::BackgroundImage(val) => self.images = Some(val.clone()),
+      Property::BackgroundImage(val) => {
+        self.flush_fallback(val);
+        self.images = Some(val.clone())
+      },
       Property::BackgroundPosition(val) => {
         self.x_positions = Some(val.iter().map(|p| p.x.clone()).collect());
         self.y_positions = Some(val.iter().map(|p| p.y.clone()).collect());
@@ -389,8 +425,10 @@ impl PropertyHandler for BackgroundHandler {
         }
       },
       Property::Background(val) => {
+        let images: SmallVec<[Image; 1]> = val.iter().map(|b| b.image.clone()).collect();
+        self.flush_fallback(&images);
         self.color = Some(val.last().unwrap().color.clone());
-        self.images = Some(val.iter().map(|b| b.image.clone()).collect());
+        self.images = Some(images);
         self.x_positions = Some(val.iter().map(|b| b.position.x.clone()).collect());
         self.y_positions = Some(val.iter().map(|b| b.position.y.clone()).collect());
         self.repeats = Some(val.iter().map(|b| b.repeat.clone()).collect());
@@ -412,6 +450,57 @@ impl PropertyHandler for BackgroundHandler {
 }
 
 impl BackgroundHandler {
+  pub fn new(targets: Option<Browsers>) -> BackgroundHandler {
+    BackgroundHandler {
+      targets,
+      ..BackgroundHandler::default()
+    }
+  }
+
+  /// An image-set() declared after another background image is usually paired with
+  /// a fallback for browsers that don't support it, so both declarations are kept.
+  fn flush_fallback(&mut self, images: &[Image]) {
+    if let Some(existing) = &self.images {
+      if existing.as_slice() != images && images.iter().any(|image| matches!(image, Image::ImageSet(_))) {
+        self.flush();
+      }
+    }
+  }
+
+  /// Returns the images with prefixed image-set() functions if the targets need them,
+  /// or `None` if no prefixed fallback is needed or possible.
+  fn get_prefixed_images(&self, images: &[Image]) -> Option<SmallVec<[Image; 1]>> {
+    let targets = self.targets?;
+    let prefixes = images.iter().fold(VendorPrefix::empty(), |prefixes, image| prefixes | image.get_necessary_prefixes(targets));
+    if !prefixes.contains(VendorPrefix::WebKit) {
+      return None
+    }
+
+    images.iter().map(|image| image.get_prefixed(VendorPrefix::WebKit)).collect()
+  }
+
+  /// Pushes a prefixed fallback declaration, unless an equivalent one was already written.
+  /// A `background-image` fallback is equivalent to any earlier declaration that set the
+  /// same prefixed images, as long as no other declaration changed the images since then.
+  fn push_fallback(&mut self, property: Property) {
+    if self.decls.last() == Some(&property) {
+      return
+    }
+
+    if let Property::BackgroundImage(prefixed) = &property {
+      let last_images = self.decls.iter().rev().find_map(|decl| match decl {
+        Property::BackgroundImage(images) => Some(images.clone()),
+        Property::Background(backgrounds) => Some(backgrounds.iter().map(|b| b.image.clone()).collect()),
+        _ => None
+      });
+      if last_images.as_ref() == Some(prefixed) {
+        return
+      }
+    }
+
+    self.decls.push(property)
+  }
+
   fn flush(&mut self) {    
     let color = std::mem::take(&mut self.color);
     let mut images = std::mem::take(&mut self.images);
@@ -424,9 +513,10 @@ impl BackgroundHandler {
     let mut clips = std::mem::take(&mut self.clips);
 
     if let (Some(color), Some(images), Some(x_positions), Some(y_positions), Some(repeats), Some(sizes), Some(attachments), Some(origins), Some(clips)) = (&color, &mut images, &mut x_positions, &mut y_positions, &mut repeats, &mut sizes, &mut attachments, &mut origins, &mut clips) {
-      // Only use shorthand syntax if the number of layers matches on all properties.
+      // The number of layers is determined by background-image. Other properties are
+      // repeated or truncated to match, as described in the spec.
       let len = images.len();
-      if x_positions.len() == len && y_positions.len() == len && repeats.len() == len && sizes.len() == len && attachments.len() == len && origins.len() == len && clips.len() == len {
+      if normalize_layers(x_positions, len) && normalize_layers(y_positions, len) && normalize_layers(repeats, len) && normalize_layers(sizes, len) && normalize_layers(attachments, len) && normalize_layers(origins, len) && normalize_layers(clips, len) {
         let backgrounds = izip!(images.drain(..), x_positions.drain(..), y_positions.drain(..), repeats.drain(..), sizes.drain(..), attachments.drain(..), origins.drain(..), clips.drain(..)).enumerate().map(|(i, (image, x_position, y_position, repeat, size, attachment, origin, clip))| {
           Background {
             color: if i == len - 1 {
@@ -445,7 +535,17 @@ impl BackgroundHandler {
             origin,
             clip
           }
-        }).collect();
+        }).collect::<SmallVec<[Background; 1]>>();
+
+        let images: SmallVec<[Image; 1]> = backgrounds.iter().map(|b| b.image.clone()).collect();
+        if let Some(prefixed) = self.get_prefixed_images(&images) {
+          let prefixed = backgrounds.iter().zip(prefixed).map(|(background, image)| Background {
+            image,
+            ..background.clone()
+          }).collect();
+          self.push_fallback(Property::Background(prefixed));
+        }
+
         self.decls.push(Property::Background(backgrounds));
         self.reset();
         return
@@ -457,6 +557,9 @@ impl BackgroundHandler {
     }
 
     if let Some(images) = images {
+      if let Some(prefixed) = self.get_prefixed_images(&images) {
+        self.push_fallback(Property::BackgroundImage(prefixed));
+      }
       self.decls.push(Property::BackgroundImage(images))
     }
 
@@ -484,7 +587,8 @@ impl BackgroundHandler {
       self.decls.push(Property::BackgroundSize(sizes))
     }
 
-    if let Some(attachments) = attachments {
+    if let Some(mut attachments) = attachments {
+      minify_layers(&mut attachments);
       self.decls.push(Property::BackgroundAttachment(attachments))
     }
 
@@ -511,3 +615,35 @@ impl BackgroundHandler {
     self.clips = None
   }
 }
+
+/// Repeats or truncates a list of layer values so that it has the given number of layers.
+/// Returns false if the list is empty, and thus cannot be repeated.
+/// https://drafts.csswg.org/css-backgrounds-3/#layering
+fn normalize_layers<T: Clone>(values: &mut SmallVec<[T; 1]>, len: usize) -> bool {
+  let count = values.len();
+  if count == 0 {
+    return false
+  }
+
+  if count > len {
+    values.truncate(len);
+  } else {
+    for i in count..len {
+      let value = values[i % count].clone();
+      values.push(value);
+    }
+  }
+
+  true
+}
+
+/// Removes values of a layered property that have no effect. A list that consists of a repeated
+/// sequence of values is equivalent to the sequence alone, since values are repeated to match the
+/// number of layers. Values beyond the number of images declared in the same rule are kept, since
+/// another rule may declare more images for the same element.
+fn minify_layers<T: PartialEq>(values: &mut SmallVec<[T; 1]>) {
+  let len = values.len();
+  if let Some(period) = (1..len).find(|period| len % period == 0 && (*period..len).all(|i| values[i] == values[i % period])) {
+    values.truncate(period);
+  }
+}
@@ -10,6 +10,7 @@ use super::border_radius::*;
 use super::prefixes::Browsers;
 use crate::printer::Printer;
 use std::fmt::Write;
+use crate::error::ParserError;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum BorderSideWidth {
@@ -30,7 +31,7 @@ impl Default for BorderSideWidth {
 }
 
 impl Parse for BorderSideWidth {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(length) = input.try_parse(|i| Length::parse(i)) {
       return Ok(BorderSideWidth::Length(length));
     }
@@ -93,7 +94,7 @@ impl<S: Default> Default for GenericBorder<S> {
 }
 
 impl<S: Parse + Default> Parse for GenericBorder<S> {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     // Order doesn't matter...
     let mut color = None;
     let mut style = None;
@@ -7,6 +7,7 @@ use crate::values::rect::Rect;
 use crate::values::image::Image;
 use crate::macros::*;
 use crate::printer::Printer;
+use crate::error::ParserError;
 
 // https://www.w3.org/TR/css-backgrounds-3/#border-image-repeat
 enum_property!(BorderImageRepeatKeyword,
@@ -26,7 +27,7 @@ impl Default for BorderImageRepeat {
 }
 
 impl Parse for BorderImageRepeat {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let horizontal = BorderImageRepeatKeyword::parse(input)?;
     let vertical = input.try_parse(BorderImageRepeatKeyword::parse).ok();
     Ok(BorderImageRepeat(horizontal, vertical.unwrap_or(horizontal)))
@@ -59,7 +60,7 @@ impl Default for BorderImageSideWidth {
 }
 
 impl Parse for BorderImageSideWidth {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|i| i.expect_ident_matching("auto")).is_ok() {
       return Ok(BorderImageSideWidth::Auto);
     }
@@ -104,7 +105,7 @@ impl Default for BorderImageSlice {
 }
 
 impl Parse for BorderImageSlice {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut fill = input.try_parse(|i| i.expect_ident_matching("fill")).is_ok();
     let offsets = Rect::parse(input)?;
     if !fill {
@@ -130,15 +131,15 @@ impl ToCss for BorderImageSlice {
 /// https://www.w3.org/TR/css-backgrounds-3/#border-image
 #[derive(Debug, Clone, PartialEq)]
 pub struct BorderImage {
-  source: Image,
-  slice: BorderImageSlice,
-  width: Rect<BorderImageSideWidth>,
-  outset: Rect<LengthOrNumber>,
-  repeat: BorderImageRepeat
+  pub source: Image,
+  pub slice: BorderImageSlice,
+  pub width: Rect<BorderImageSideWidth>,
+  pub outset: Rect<LengthOrNumber>,
+  pub repeat: BorderImageRepeat
 }
 
 impl Parse for BorderImage {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut source: Option<Image> = None;
     let mut slice: Option<BorderImageSlice> = None;
     let mut width: Option<Rect<BorderImageSideWidth>> = None;
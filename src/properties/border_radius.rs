@@ -3,40 +3,55 @@ use crate::values::size::Size2D;
 use cssparser::*;
 use crate::traits::{Parse, ToCss, PropertyHandler};
 use super::prefixes::{Feature, Browsers};
+use super::prefix_handler::{PrefixedPropertyState, expand_prefixes};
 use crate::properties::{Property, VendorPrefix};
+use crate::macros::trace;
 use crate::values::rect::Rect;
 use crate::printer::Printer;
+use crate::values::percentage::Percentage;
+use crate::warnings::{self, WarningKind};
+use std::cell::Cell;
+use crate::error::ParserError;
 
+/// https://drafts.csswg.org/css-backgrounds-3/#border-radius
 #[derive(Debug, Clone, PartialEq)]
 pub struct BorderRadius {
-  top_left: Size2D<LengthPercentage>,
-  top_right: Size2D<LengthPercentage>,
-  bottom_left: Size2D<LengthPercentage>,
-  bottom_right: Size2D<LengthPercentage>
+  pub top_left: Size2D<LengthPercentage>,
+  pub top_right: Size2D<LengthPercentage>,
+  pub bottom_right: Size2D<LengthPercentage>,
+  pub bottom_left: Size2D<LengthPercentage>
 }
 
 impl Parse for BorderRadius {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
-    let widths: Rect<LengthPercentage> = Rect::parse(input)?;
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let negative = Cell::new(None);
+    let parse = |input: &mut Parser<'i, 't>| parse_radius(input, &negative);
+    let widths: Rect<LengthPercentage> = Rect::parse_with(input, parse)?;
     let heights = if input.try_parse(|input| input.expect_delim('/')).is_ok() {
-      Rect::parse(input)?
+      Rect::parse_with(input, parse)?
     } else {
       widths.clone()
     };
 
+    if let Some(location) = negative.get() {
+      warnings::warn(location, WarningKind::NegativeBorderRadius);
+      return Err(location.new_custom_error(ParserError::InvalidValue))
+    }
+
+    // The corners are listed clockwise starting from the top left, like the sides of a `Rect`.
     Ok(BorderRadius {
       top_left: Size2D(widths.0, heights.0),
       top_right: Size2D(widths.1, heights.1),
-      bottom_left: Size2D(widths.2, heights.2),
-      bottom_right: Size2D(widths.3, heights.3)
+      bottom_right: Size2D(widths.2, heights.2),
+      bottom_left: Size2D(widths.3, heights.3)
     })
   }
 }
 
 impl ToCss for BorderRadius {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
-    let widths = Rect::new(&self.top_left.0, &self.top_right.0, &self.bottom_left.0, &self.bottom_right.0);
-    let heights = Rect::new(&self.top_left.1, &self.top_right.1, &self.bottom_left.1, &self.bottom_right.1);
+    let widths = Rect::new(&self.top_left.0, &self.top_right.0, &self.bottom_right.0, &self.bottom_left.0);
+    let heights = Rect::new(&self.top_left.1, &self.top_right.1, &self.bottom_right.1, &self.bottom_left.1);
 
     widths.to_css(dest)?;
     if widths != heights {
@@ -48,13 +63,58 @@ impl ToCss for BorderRadius {
   }
 }
 
+/// The horizontal and vertical radii of a single corner, i.e. the value
+/// of the `border-*-radius` longhand properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BorderCornerRadius(pub Size2D<LengthPercentage>);
+
+impl Parse for BorderCornerRadius {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let negative = Cell::new(None);
+    let first = parse_radius(input, &negative)?;
+    let second = input.try_parse(|input| parse_radius(input, &negative)).unwrap_or_else(|_| first.clone());
+    if let Some(location) = negative.get() {
+      warnings::warn(location, WarningKind::NegativeBorderRadius);
+      return Err(location.new_custom_error(ParserError::InvalidValue))
+    }
+
+    Ok(BorderCornerRadius(Size2D(first, second)))
+  }
+}
+
+impl ToCss for BorderCornerRadius {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    self.0.to_css(dest)
+  }
+}
+
+/// Parses a single radius. Negative radii are invalid, but they are still consumed so that the caller
+/// can reject the whole value rather than stopping before it, and the location of the first is recorded.
+/// A percentage of zero is equivalent to a length of zero, and is converted so the shorter form is used.
+fn parse_radius<'i, 't>(input: &mut Parser<'i, 't>, negative: &Cell<Option<SourceLocation>>) -> Result<LengthPercentage, ParseError<'i, ParserError<'i>>> {
+  input.skip_whitespace();
+  let location = input.current_source_location();
+  let radius = LengthPercentage::parse(input)?;
+  if radius < 0.0 && negative.get().is_none() {
+    negative.set(Some(location));
+  }
+
+  if let LengthPercentage::Percentage(Percentage(p)) = radius {
+    if p == 0.0 {
+      return Ok(LengthPercentage::zero())
+    }
+  }
+
+  Ok(radius)
+}
+
 #[derive(Default, Debug)]
 pub struct BorderRadiusHandler {
   targets: Option<Browsers>,
-  top_left: Option<(Size2D<LengthPercentage>, VendorPrefix)>,
-  top_right: Option<(Size2D<LengthPercentage>, VendorPrefix)>,
-  bottom_left: Option<(Size2D<LengthPercentage>, VendorPrefix)>,
-  bottom_right: Option<(Size2D<LengthPercentage>, VendorPrefix)>,
+  top_left: PrefixedPropertyState<Size2D<LengthPercentage>>,
+  top_right: PrefixedPropertyState<Size2D<LengthPercentage>>,
+  bottom_right: PrefixedPropertyState<Size2D<LengthPercentage>>,
+  bottom_left: PrefixedPropertyState<Size2D<LengthPercentage>>,
   logical: Vec<Property>,
   decls: Vec<Property>
 }
@@ -73,40 +133,42 @@ impl PropertyHandler for BorderRadiusHandler {
     use Property::*;
 
     macro_rules! property {
-      ($prop: ident, $val: expr, $vp: ident) => {{
-        // If two vendor prefixes for the same property have different
-        // values, we need to flush what we have immediately to preserve order.
-        if let Some((val, prefixes)) = &self.$prop {
-          if val != $val && !prefixes.contains(*$vp) {
-            self.flush();
-          }
-        }
-
-        // Otherwise, update the value and add the prefix.
-        if let Some((val, prefixes)) = &mut self.$prop {
-          *val = $val.clone();
-          *prefixes |= *$vp;
-        } else {
-          self.$prop = Some(($val.clone(), *$vp))
+      ($prop: ident, $feature: ident, $val: expr, $vp: ident) => {{
+        self.$prop.remove_overridden(*$vp, self.targets);
+        self.remove_overridden_decls(*$vp, |decl| matches!(decl, $feature(..)));
+        if self.$prop.needs_flush($val, *$vp) {
+          self.flush();
         }
+        self.$prop.set($val, *$vp);
       }};
     }
 
     match property {
-      BorderTopLeftRadius(val, vp) => property!(top_left, val, vp),
-      BorderTopRightRadius(val, vp) => property!(top_right, val, vp),
-      BorderBottomLeftRadius(val, vp) => property!(bottom_left, val, vp),
-      BorderBottomRightRadius(val, vp) => property!(bottom_right, val, vp),
+      BorderTopLeftRadius(val, vp) => property!(top_left, BorderTopLeftRadius, &val.0, vp),
+      BorderTopRightRadius(val, vp) => property!(top_right, BorderTopRightRadius, &val.0, vp),
+      BorderBottomRightRadius(val, vp) => property!(bottom_right, BorderBottomRightRadius, &val.0, vp),
+      BorderBottomLeftRadius(val, vp) => property!(bottom_left, BorderBottomLeftRadius, &val.0, vp),
       BorderStartStartRadius(_) | BorderStartEndRadius(_) | BorderEndStartRadius(_) | BorderEndEndRadius(_) => {
         self.flush();
         self.logical.push(property.clone());
       }
       BorderRadius(val, vp) => {
         self.logical.clear();
-        property!(top_left, &val.top_left, vp);
-        property!(top_right, &val.top_right, vp);
-        property!(bottom_left, &val.bottom_left, vp);
-        property!(bottom_right, &val.bottom_right, vp);
+        // Overridden prefixes are removed from all of the corners before any of them are set,
+        // so that setting one corner doesn't flush the others partway through the shorthand.
+        self.top_left.remove_overridden(*vp, self.targets);
+        self.top_right.remove_overridden(*vp, self.targets);
+        self.bottom_right.remove_overridden(*vp, self.targets);
+        self.bottom_left.remove_overridden(*vp, self.targets);
+        self.remove_overridden_decls(*vp, |_| true);
+        if self.top_left.needs_flush(&val.top_left, *vp) || self.top_right.needs_flush(&val.top_right, *vp) ||
+          self.bottom_right.needs_flush(&val.bottom_right, *vp) || self.bottom_left.needs_flush(&val.bottom_left, *vp) {
+          self.flush();
+        }
+        self.top_left.set(&val.top_left, *vp);
+        self.top_right.set(&val.top_right, *vp);
+        self.bottom_right.set(&val.bottom_right, *vp);
+        self.bottom_left.set(&val.bottom_left, *vp);
       }
       _ => return false
     }
@@ -121,47 +183,56 @@ impl PropertyHandler for BorderRadiusHandler {
 }
 
 impl BorderRadiusHandler {
-  fn flush(&mut self) {
-    let mut top_left = std::mem::take(&mut self.top_left);
-    let mut top_right = std::mem::take(&mut self.top_right);
-    let mut bottom_left = std::mem::take(&mut self.bottom_left);
-    let mut bottom_right = std::mem::take(&mut self.bottom_right);
+  /// Like `PrefixedPropertyState::remove_overridden`, but for the declarations that were
+  /// already output, e.g. `-moz-border-radius` when a prefixed corner caused a flush before
+  /// the unprefixed `border-radius`. Otherwise, these would only be removed when minifying
+  /// the output again.
+  fn remove_overridden_decls<F: Fn(&Property) -> bool>(&mut self, prefix: VendorPrefix, overrides: F) {
+    if self.targets.is_none() || !prefix.contains(VendorPrefix::None) {
+      return
+    }
 
+    self.decls.retain(|decl| {
+      let removed = overrides(decl) && matches!(decl,
+        Property::BorderRadius(..) |
+        Property::BorderTopLeftRadius(..) |
+        Property::BorderTopRightRadius(..) |
+        Property::BorderBottomRightRadius(..) |
+        Property::BorderBottomLeftRadius(..)
+      );
+      if removed {
+        trace!(?decl, "removed prefixed value overridden by the unprefixed property");
+      }
+      !removed
+    });
+  }
+
+  fn flush(&mut self) {
     self.decls.extend(self.logical.drain(..));
 
-    if let (Some((top_left, tl_prefix)), Some((top_right, tr_prefix)), Some((bottom_left, bl_prefix)), Some((bottom_right, br_prefix))) = (&mut top_left, &mut top_right, &mut bottom_left, &mut bottom_right) {
-      let intersection = *tl_prefix & *tr_prefix & *bl_prefix & *br_prefix;
+    // Output the shorthand for the prefixes that all of the corners were declared with.
+    // The remaining prefixes are output as individual properties below.
+    if let (Some(top_left), Some(top_right), Some(bottom_right), Some(bottom_left)) = (self.top_left.value(), self.top_right.value(), self.bottom_right.value(), self.bottom_left.value()) {
+      let intersection = self.top_left.prefixes() & self.top_right.prefixes() & self.bottom_right.prefixes() & self.bottom_left.prefixes();
       if !intersection.is_empty() {
-        let mut prefix = intersection;
-        if prefix.contains(VendorPrefix::None) {
-          if let Some(targets) = self.targets {
-            prefix = Feature::BorderRadius.prefixes_for(targets)
-          }
-        }
-        self.decls.push(Property::BorderRadius(BorderRadius {
+        let radius = BorderRadius {
           top_left: top_left.clone(),
           top_right: top_right.clone(),
-          bottom_left: bottom_left.clone(),
           bottom_right: bottom_right.clone(),
-        }, prefix));
-        tl_prefix.remove(intersection);
-        tr_prefix.remove(intersection);
-        bl_prefix.remove(intersection);
-        br_prefix.remove(intersection);
+          bottom_left: bottom_left.clone()
+        };
+        self.decls.push(Property::BorderRadius(radius, expand_prefixes(intersection, Feature::BorderRadius, self.targets)));
+        self.top_left.remove_prefixes(intersection);
+        self.top_right.remove_prefixes(intersection);
+        self.bottom_right.remove_prefixes(intersection);
+        self.bottom_left.remove_prefixes(intersection);
       }
     }
 
     macro_rules! single_property {
       ($prop: ident, $key: ident) => {
-        if let Some((val, mut vp)) = $key {
-          if !vp.is_empty() {
-            if vp.contains(VendorPrefix::None) {
-              if let Some(targets) = self.targets {
-                vp = Feature::$prop.prefixes_for(targets)
-              }
-            }
-            self.decls.push(Property::$prop(val, vp))
-          }
+        if let Some((val, vp)) = self.$key.take(Feature::$prop, self.targets) {
+          self.decls.push(Property::$prop(BorderCornerRadius(val), vp))
         }
       };
     }
@@ -1,9 +1,15 @@
 use cssparser::*;
 use crate::values::length::Length;
-use crate::traits::{Parse, ToCss};
+use crate::traits::{Parse, ToCss, PropertyHandler};
 use crate::values::color::CssColor;
 use crate::printer::Printer;
+use super::Property;
+use super::prefixes::{Browsers, Feature};
+use super::prefix_handler::PrefixedPropertyState;
+use super::text::TextShadow;
+use smallvec::SmallVec;
 use std::fmt::Write;
+use crate::error::ParserError;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct BoxShadow {
@@ -16,7 +22,7 @@ pub struct BoxShadow {
 }
 
 impl Parse for BoxShadow {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut color = None;
     let mut lengths = None;
     let mut inset = false;
@@ -30,7 +36,7 @@ impl Parse for BoxShadow {
       }
 
       if lengths.is_none() {
-        let value = input.try_parse::<_, _, ParseError<()>>(|input| {
+        let value = input.try_parse::<_, _, ParseError<ParserError>>(|input| {
           let horizontal = Length::parse(input)?;
           let vertical = Length::parse(input)?;
           let blur = input.try_parse(Length::parse).unwrap_or(Length::zero());
@@ -76,11 +82,11 @@ impl ToCss for BoxShadow {
     dest.write_char(' ')?;
     self.y_offset.to_css(dest)?;
     
-    if self.blur != Length::zero() || self.spread != Length::zero() {
+    if !self.blur.is_zero() || !self.spread.is_zero() {
       dest.write_char(' ')?;
       self.blur.to_css(dest)?;
 
-      if self.spread != Length::zero() {
+      if !self.spread.is_zero() {
         dest.write_char(' ')?;
         self.spread.to_css(dest)?;
       }  
@@ -94,3 +100,74 @@ impl ToCss for BoxShadow {
     Ok(())
   }
 }
+
+/// Removes shadows that are exact duplicates of an earlier shadow in the list, keeping the
+/// order of the rest. The first shadow is painted on top, so a later duplicate is hidden beneath
+/// the earlier one, but only if it is solid: unblurred with an opaque color. Otherwise, the
+/// duplicates combine into a darker shadow, so they are kept.
+pub fn dedup_shadows<T: PartialEq>(shadows: &mut SmallVec<[T; 1]>, is_solid: fn(&T) -> bool) {
+  let mut i = 1;
+  while i < shadows.len() {
+    if is_solid(&shadows[i]) && shadows[..i].contains(&shadows[i]) {
+      shadows.remove(i);
+    } else {
+      i += 1;
+    }
+  }
+}
+
+/// Handles `box-shadow` and `text-shadow`, removing duplicate shadows and adding
+/// the prefixed `box-shadow` properties that the targets need.
+#[derive(Default)]
+pub struct ShadowHandler {
+  targets: Option<Browsers>,
+  box_shadow: PrefixedPropertyState<SmallVec<[BoxShadow; 1]>>,
+  text_shadow: Option<SmallVec<[TextShadow; 1]>>,
+  decls: Vec<Property>
+}
+
+impl ShadowHandler {
+  pub fn new(targets: Option<Browsers>) -> ShadowHandler {
+    ShadowHandler {
+      targets,
+      ..ShadowHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for ShadowHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    match property {
+      Property::BoxShadow(val, prefix) => {
+        self.box_shadow.remove_overridden(*prefix, self.targets);
+        if self.box_shadow.needs_flush(val, *prefix) {
+          self.flush();
+        }
+        self.box_shadow.set(val, *prefix);
+      }
+      Property::TextShadow(val) => self.text_shadow = Some(val.clone()),
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    self.flush();
+    std::mem::take(&mut self.decls)
+  }
+}
+
+impl ShadowHandler {
+  fn flush(&mut self) {
+    if let Some((mut shadows, prefixes)) = self.box_shadow.take(Feature::BoxShadow, self.targets) {
+      dedup_shadows(&mut shadows, |shadow| shadow.blur == Length::zero() && shadow.color.is_opaque());
+      self.decls.push(Property::BoxShadow(shadows, prefixes));
+    }
+
+    if let Some(mut shadows) = std::mem::take(&mut self.text_shadow) {
+      dedup_shadows(&mut shadows, |shadow| shadow.blur == Length::zero() && shadow.color.is_opaque());
+      self.decls.push(Property::TextShadow(shadows));
+    }
+  }
+}
@@ -0,0 +1,296 @@
+use cssparser::*;
+use crate::macros::enum_property;
+use crate::traits::{Parse, ToCss, PropertyHandler};
+use crate::values::length::Length;
+use crate::values::color::CssColor;
+use crate::printer::Printer;
+use super::Property;
+use super::border::{BorderSideWidth, BorderStyle, GenericBorder};
+use super::prefixes::{Browsers, Feature};
+use super::prefix_handler::PrefixedPropertyState;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// https://www.w3.org/TR/css-multicol-1/#cw
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnWidth {
+  Auto,
+  Length(Length)
+}
+
+impl Default for ColumnWidth {
+  fn default() -> ColumnWidth {
+    ColumnWidth::Auto
+  }
+}
+
+impl Parse for ColumnWidth {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(ColumnWidth::Auto)
+    }
+
+    Ok(ColumnWidth::Length(Length::parse(input)?))
+  }
+}
+
+impl ToCss for ColumnWidth {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      ColumnWidth::Auto => dest.write_str("auto"),
+      ColumnWidth::Length(len) => len.to_css(dest)
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-multicol-1/#cc
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnCount {
+  Auto,
+  Integer(i32)
+}
+
+impl Default for ColumnCount {
+  fn default() -> ColumnCount {
+    ColumnCount::Auto
+  }
+}
+
+impl Parse for ColumnCount {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(ColumnCount::Auto)
+    }
+
+    let location = input.current_source_location();
+    let count = input.expect_integer()?;
+    if count < 1 {
+      return Err(location.new_custom_error(ParserError::InvalidValue))
+    }
+    Ok(ColumnCount::Integer(count))
+  }
+}
+
+impl ToCss for ColumnCount {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      ColumnCount::Auto => dest.write_str("auto"),
+      ColumnCount::Integer(count) => write!(dest, "{}", count)
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-multicol-1/#columns
+#[derive(Debug, Clone, PartialEq)]
+pub struct Columns {
+  pub width: ColumnWidth,
+  pub count: ColumnCount
+}
+
+impl Parse for Columns {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut width = None;
+    let mut count = None;
+    // `auto` may apply to either value, so it only counts towards the number of values.
+    let mut autos = 0;
+
+    for _ in 0..2 {
+      if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+        autos += 1;
+        continue
+      }
+
+      // The count is tried first, so that a unitless number is not parsed as a length.
+      if count.is_none() {
+        if let Ok(value) = input.try_parse(ColumnCount::parse) {
+          count = Some(value);
+          continue
+        }
+      }
+
+      if width.is_none() {
+        if let Ok(value) = input.try_parse(Length::parse) {
+          width = Some(ColumnWidth::Length(value));
+          continue
+        }
+      }
+
+      break
+    }
+
+    if autos == 0 && width.is_none() && count.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+
+    Ok(Columns {
+      width: width.unwrap_or_default(),
+      count: count.unwrap_or_default()
+    })
+  }
+}
+
+impl ToCss for Columns {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    if self.width == ColumnWidth::Auto && self.count == ColumnCount::Auto {
+      return dest.write_str("auto")
+    }
+
+    if self.width != ColumnWidth::Auto {
+      self.width.to_css(dest)?;
+      if self.count != ColumnCount::Auto {
+        dest.write_char(' ')?;
+      }
+    }
+
+    if self.count != ColumnCount::Auto {
+      self.count.to_css(dest)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// https://www.w3.org/TR/css-multicol-1/#column-rule
+pub type ColumnRule = GenericBorder<BorderStyle>;
+
+// https://www.w3.org/TR/css-multicol-1/#column-span
+enum_property!(ColumnSpan,
+  None,
+  All
+);
+
+// https://www.w3.org/TR/css-multicol-1/#cf
+enum_property!(ColumnFill,
+  ("auto", Auto),
+  ("balance", Balance),
+  ("balance-all", BalanceAll)
+);
+
+/// Combines the `columns` and `column-rule` longhands into their shorthands where they are
+/// declared with the same vendor prefixes, and adds the prefixes needed by the targets.
+#[derive(Default)]
+pub struct ColumnsHandler {
+  targets: Option<Browsers>,
+  width: PrefixedPropertyState<ColumnWidth>,
+  count: PrefixedPropertyState<ColumnCount>,
+  rule_width: PrefixedPropertyState<BorderSideWidth>,
+  rule_style: PrefixedPropertyState<BorderStyle>,
+  rule_color: PrefixedPropertyState<CssColor>,
+  span: PrefixedPropertyState<ColumnSpan>,
+  fill: PrefixedPropertyState<ColumnFill>,
+  decls: Vec<Property>
+}
+
+impl ColumnsHandler {
+  pub fn new(targets: Option<Browsers>) -> ColumnsHandler {
+    ColumnsHandler {
+      targets,
+      ..ColumnsHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for ColumnsHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    macro_rules! property {
+      ($prop: ident, $feature: ident, $val: expr, $vp: expr) => {{
+        self.$prop.remove_overridden(*$vp, self.targets);
+        if self.$prop.needs_flush($val, *$vp) {
+          self.flush();
+        }
+        self.$prop.set($val, *$vp);
+      }};
+    }
+
+    match property {
+      Property::ColumnWidth(val, vp) => property!(width, ColumnWidth, val, vp),
+      Property::ColumnCount(val, vp) => property!(count, ColumnCount, val, vp),
+      Property::Columns(val, vp) => {
+        property!(width, ColumnWidth, &val.width, vp);
+        property!(count, ColumnCount, &val.count, vp);
+      }
+      Property::ColumnRuleWidth(val, vp) => property!(rule_width, ColumnRuleWidth, val, vp),
+      Property::ColumnRuleStyle(val, vp) => property!(rule_style, ColumnRuleStyle, val, vp),
+      Property::ColumnRuleColor(val, vp) => property!(rule_color, ColumnRuleColor, val, vp),
+      Property::ColumnRule(val, vp) => {
+        property!(rule_width, ColumnRuleWidth, &val.width, vp);
+        property!(rule_style, ColumnRuleStyle, &val.style, vp);
+        property!(rule_color, ColumnRuleColor, &val.color, vp);
+      }
+      Property::ColumnSpan(val, vp) => property!(span, ColumnSpan, val, vp),
+      Property::ColumnFill(val, vp) => property!(fill, ColumnFill, val, vp),
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    self.flush();
+    std::mem::take(&mut self.decls)
+  }
+}
+
+impl ColumnsHandler {
+  fn flush(&mut self) {
+    let targets = self.targets;
+    let width = self.width.take(Feature::ColumnWidth, targets);
+    let count = self.count.take(Feature::ColumnCount, targets);
+    let rule_width = self.rule_width.take(Feature::ColumnRuleWidth, targets);
+    let rule_style = self.rule_style.take(Feature::ColumnRuleStyle, targets);
+    let rule_color = self.rule_color.take(Feature::ColumnRuleColor, targets);
+    let span = self.span.take(Feature::ColumnSpan, targets);
+    let fill = self.fill.take(Feature::ColumnFill, targets);
+
+    // The shorthand is used for the prefixes shared by all of the longhands,
+    // and the longhands are kept for any remaining prefixes.
+    let mut width = width;
+    let mut count = count;
+    if let (Some((w, wp)), Some((c, cp))) = (&mut width, &mut count) {
+      let common = *wp & *cp;
+      if !common.is_empty() {
+        self.decls.push(Property::Columns(Columns {
+          width: w.clone(),
+          count: c.clone()
+        }, common));
+        wp.remove(common);
+        cp.remove(common);
+      }
+    }
+
+    let mut rule_width = rule_width;
+    let mut rule_style = rule_style;
+    let mut rule_color = rule_color;
+    if let (Some((w, wp)), Some((s, sp)), Some((c, cp))) = (&mut rule_width, &mut rule_style, &mut rule_color) {
+      let common = *wp & *sp & *cp;
+      if !common.is_empty() {
+        self.decls.push(Property::ColumnRule(ColumnRule {
+          width: w.clone(),
+          style: s.clone(),
+          color: c.clone()
+        }, common));
+        wp.remove(common);
+        sp.remove(common);
+        cp.remove(common);
+      }
+    }
+
+    macro_rules! longhand {
+      ($val: ident, $prop: ident) => {
+        if let Some((val, prefixes)) = $val {
+          if !prefixes.is_empty() {
+            self.decls.push(Property::$prop(val, prefixes))
+          }
+        }
+      };
+    }
+
+    longhand!(width, ColumnWidth);
+    longhand!(count, ColumnCount);
+    longhand!(rule_width, ColumnRuleWidth);
+    longhand!(rule_style, ColumnRuleStyle);
+    longhand!(rule_color, ColumnRuleColor);
+    longhand!(span, ColumnSpan);
+    longhand!(fill, ColumnFill);
+  }
+}
@@ -0,0 +1,219 @@
+use cssparser::*;
+use crate::macros::enum_property;
+use crate::traits::{Parse, ToCss};
+use crate::values::ident::CustomIdent;
+use crate::values::image::Image;
+use crate::printer::Printer;
+use super::list::CounterStyle;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+// https://www.w3.org/TR/css-content-3/#quote-values
+enum_property!(Quote,
+  ("open-quote", OpenQuote),
+  ("close-quote", CloseQuote),
+  ("no-open-quote", NoOpenQuote),
+  ("no-close-quote", NoCloseQuote)
+);
+
+/// https://www.w3.org/TR/css-content-3/#content-property
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentItem {
+  String(String),
+  Counter(CustomIdent, Option<CounterStyle>),
+  Counters(CustomIdent, String, Option<CounterStyle>),
+  Attr(String),
+  Quote(Quote),
+  Image(Image)
+}
+
+impl Parse for ContentItem {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(s) = input.try_parse(|input| input.expect_string_cloned()) {
+      return Ok(ContentItem::String(s.as_ref().into()))
+    }
+
+    if let Ok(quote) = input.try_parse(Quote::parse) {
+      return Ok(ContentItem::Quote(quote))
+    }
+
+    if let Ok(item) = input.try_parse(|input| {
+      let name = input.expect_function()?.clone();
+      input.parse_nested_block(|input| {
+        match_ignore_ascii_case! { &name,
+          "counter" => {
+            let counter = CustomIdent::parse(input)?;
+            let style = parse_counter_style(input)?;
+            Ok(ContentItem::Counter(counter, style))
+          },
+          "counters" => {
+            let counter = CustomIdent::parse(input)?;
+            input.expect_comma()?;
+            let separator = input.expect_string()?.as_ref().into();
+            let style = parse_counter_style(input)?;
+            Ok(ContentItem::Counters(counter, separator, style))
+          },
+          "attr" => {
+            let attr = input.expect_ident()?.as_ref().into();
+            Ok(ContentItem::Attr(attr))
+          },
+          // Other functions may be images, e.g. gradients.
+          _ => Err(input.new_custom_error(ParserError::InvalidValue))
+        }
+      })
+    }) {
+      return Ok(item)
+    }
+
+    match Image::parse(input)? {
+      Image::None => Err(input.new_custom_error(ParserError::InvalidValue)),
+      image => Ok(ContentItem::Image(image))
+    }
+  }
+}
+
+/// Parses the optional counter style after a comma. `decimal` is the default.
+fn parse_counter_style<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Option<CounterStyle>, ParseError<'i, ParserError<'i>>> {
+  if input.try_parse(|input| input.expect_comma()).is_err() {
+    return Ok(None)
+  }
+
+  // Unlike in `list-style-type`, `none` is a valid counter style here, and hides the counter.
+  if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+    return Ok(Some(CounterStyle::Name(CustomIdent("none".into()))))
+  }
+
+  Ok(Some(CounterStyle::parse(input)?))
+}
+
+impl ToCss for ContentItem {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      ContentItem::String(s) => serialize_string(&s, dest),
+      ContentItem::Counter(counter, style) => {
+        dest.write_str("counter(")?;
+        counter.to_css(dest)?;
+        write_counter_style(style, dest)?;
+        dest.write_char(')')
+      }
+      ContentItem::Counters(counter, separator, style) => {
+        dest.write_str("counters(")?;
+        counter.to_css(dest)?;
+        dest.delim(',', false)?;
+        serialize_string(&separator, dest)?;
+        write_counter_style(style, dest)?;
+        dest.write_char(')')
+      }
+      ContentItem::Attr(attr) => {
+        dest.write_str("attr(")?;
+        serialize_identifier(&attr, dest)?;
+        dest.write_char(')')
+      }
+      ContentItem::Quote(quote) => quote.to_css(dest),
+      ContentItem::Image(image) => image.to_css(dest)
+    }
+  }
+}
+
+fn write_counter_style<W>(style: &Option<CounterStyle>, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+  if let Some(style) = style {
+    // `decimal` is the default, so it can be omitted.
+    if dest.minify && matches!(style, CounterStyle::Name(name) if name.0.eq_ignore_ascii_case("decimal")) {
+      return Ok(())
+    }
+
+    dest.delim(',', false)?;
+    style.to_css(dest)?;
+  }
+  Ok(())
+}
+
+/// Serializes a list of content items. When minifying, adjacent strings are concatenated,
+/// and whitespace is omitted next to strings, since they are self delimiting.
+fn serialize_items<W>(items: &Vec<ContentItem>, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+  let mut i = 0;
+  let mut prev_string = false;
+  while i < items.len() {
+    let item = &items[i];
+    let is_string = matches!(item, ContentItem::String(_));
+    if i > 0 && (!dest.minify || !(prev_string || is_string)) {
+      dest.write_char(' ')?;
+    }
+
+    if let (true, ContentItem::String(s)) = (dest.minify, item) {
+      let mut value = s.clone();
+      while let Some(ContentItem::String(next)) = items.get(i + 1) {
+        value.push_str(next);
+        i += 1;
+      }
+      serialize_string(&value, dest)?;
+    } else {
+      item.to_css(dest)?;
+    }
+
+    prev_string = is_string;
+    i += 1;
+  }
+  Ok(())
+}
+
+/// https://www.w3.org/TR/css-content-3/#content-property
+#[derive(Debug, Clone, PartialEq)]
+pub enum Content {
+  Normal,
+  None,
+  /// The content, and the alternative text for speech output after a `/`, which may be empty.
+  List(Vec<ContentItem>, Vec<ContentItem>)
+}
+
+impl Parse for Content {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(Content::Normal)
+    }
+
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(Content::None)
+    }
+
+    let mut items = vec![ContentItem::parse(input)?];
+    while let Ok(item) = input.try_parse(ContentItem::parse) {
+      items.push(item);
+    }
+
+    // The alternative text may only contain strings, counters, and attributes.
+    let mut alt = vec![];
+    if input.try_parse(|input| input.expect_delim('/')).is_ok() {
+      loop {
+        match input.try_parse(ContentItem::parse) {
+          Ok(ContentItem::Quote(_)) | Ok(ContentItem::Image(_)) => return Err(input.new_custom_error(ParserError::InvalidValue)),
+          Ok(item) => alt.push(item),
+          Err(_) => break
+        }
+      }
+
+      if alt.is_empty() {
+        return Err(input.new_custom_error(ParserError::InvalidValue))
+      }
+    }
+
+    Ok(Content::List(items, alt))
+  }
+}
+
+impl ToCss for Content {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      Content::Normal => dest.write_str("normal"),
+      Content::None => dest.write_str("none"),
+      Content::List(items, alt) => {
+        serialize_items(items, dest)?;
+        if !alt.is_empty() {
+          dest.delim('/', true)?;
+          serialize_items(alt, dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
@@ -0,0 +1,78 @@
+use cssparser::*;
+use smallvec::SmallVec;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::values::ident::CustomIdent;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// https://github.com/css-modules/css-modules#composition
+#[derive(Debug, Clone, PartialEq)]
+pub struct Composes {
+  pub names: SmallVec<[CustomIdent; 1]>,
+  pub from: Option<ComposesFrom>
+}
+
+/// Where the composed classes are defined, if not in the same file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComposesFrom {
+  Global,
+  File(String)
+}
+
+impl Parse for Composes {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut names = SmallVec::new();
+    while let Ok(name) = input.try_parse(parse_name) {
+      names.push(name);
+    }
+
+    if names.is_empty() {
+      return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+
+    let from = if input.try_parse(|input| input.expect_ident_matching("from")).is_ok() {
+      if let Ok(file) = input.try_parse(|input| input.expect_string_cloned()) {
+        Some(ComposesFrom::File(file.as_ref().into()))
+      } else {
+        input.expect_ident_matching("global")?;
+        Some(ComposesFrom::Global)
+      }
+    } else {
+      None
+    };
+
+    Ok(Composes { names, from })
+  }
+}
+
+fn parse_name<'i, 't>(input: &mut Parser<'i, 't>) -> Result<CustomIdent, ParseError<'i, ParserError<'i>>> {
+  let name = CustomIdent::parse(input)?;
+  if name.0.eq_ignore_ascii_case("from") {
+    return Err(input.new_custom_error(ParserError::InvalidValue))
+  }
+  Ok(name)
+}
+
+impl ToCss for Composes {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    let mut first = true;
+    for name in &self.names {
+      if first {
+        first = false;
+      } else {
+        dest.write_char(' ')?;
+      }
+      name.to_css(dest)?;
+    }
+
+    match &self.from {
+      Some(ComposesFrom::Global) => dest.write_str(" from global"),
+      Some(ComposesFrom::File(file)) => {
+        dest.write_str(" from ")?;
+        serialize_string(&file, dest)
+      }
+      None => Ok(())
+    }
+  }
+}
@@ -1,4 +1,9 @@
 use cssparser::*;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::values::env::{EnvironmentVariable, EnvironmentFunction};
+use crate::limits::{self, LimitKind};
+use crate::error::ParserError;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct CustomProperty {
@@ -10,7 +15,7 @@ impl CustomProperty {
   pub fn parse<'i, 't>(
     name: CowRcStr<'i>,
     input: &mut Parser<'i, 't>,
-  ) -> Result<Self, ParseError<'i, ()>> {
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     input.skip_whitespace();
     input.parse_until_before(Delimiter::Bang | Delimiter::Semicolon, |input| {
       // Need at least one token
@@ -18,6 +23,11 @@ impl CustomProperty {
       input.next_including_whitespace()?;
       input.reset(&start);
 
+      // The value is kept as a string, but blocks are still limited to the same depth as
+      // in other values, so that it can be parsed again later.
+      limits::check_depth(input, LimitKind::BlockDepth)?;
+      input.reset(&start);
+
       // parse_declaration_value_block(input, references, missing_closing_characters)
       let start = input.position();
       loop {
@@ -34,3 +44,116 @@ impl CustomProperty {
     })
   }
 }
+
+/// A property whose value contains an environment variable, e.g. `env(safe-area-inset-top)`.
+/// Environment variables are substituted at computed-value time, so the value cannot be
+/// parsed according to the grammar of the property, and is stored as a list of tokens instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnparsedProperty {
+  pub name: String,
+  pub value: TokenList
+}
+
+impl UnparsedProperty {
+  pub fn parse<'i, 't>(
+    name: CowRcStr<'i>,
+    input: &mut Parser<'i, 't>,
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let value = input.parse_until_before(Delimiter::Bang | Delimiter::Semicolon, TokenList::parse)?;
+    Ok(UnparsedProperty {
+      name: name.as_ref().into(),
+      value
+    })
+  }
+}
+
+/// A list of tokens, with any environment variables parsed so that they can be transformed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenList(pub Vec<TokenOrValue>);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenOrValue {
+  /// The source text of a token, or of the start or end of a function or block.
+  Token(String),
+  Env(EnvironmentVariable)
+}
+
+impl Parse for TokenList {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut tokens = vec![];
+    TokenList::parse_into(input, &mut tokens)?;
+    if tokens.last() == Some(&TokenOrValue::Token(" ".into())) {
+      tokens.pop();
+    }
+    Ok(TokenList(tokens))
+  }
+}
+
+impl TokenList {
+  fn parse_into<'i, 't>(input: &mut Parser<'i, 't>, tokens: &mut Vec<TokenOrValue>) -> Result<(), ParseError<'i, ParserError<'i>>> {
+    loop {
+      let state = input.state();
+      let start = input.position();
+      let token = match input.next_including_whitespace_and_comments() {
+        Ok(token) => token.clone(),
+        Err(_) => return Ok(())
+      };
+
+      match token {
+        Token::Comment(_) => {}
+        Token::WhiteSpace(_) => {
+          // Whitespace is collapsed, and removed at the start of the list.
+          if !tokens.is_empty() && tokens.last() != Some(&TokenOrValue::Token(" ".into())) {
+            tokens.push(TokenOrValue::Token(" ".into()));
+          }
+        }
+        Token::Function(ref f) if f.eq_ignore_ascii_case("env") || f.eq_ignore_ascii_case("constant") => {
+          input.reset(&state);
+          tokens.push(TokenOrValue::Env(EnvironmentVariable::parse(input)?));
+        }
+        Token::Function(_) | Token::ParenthesisBlock | Token::SquareBracketBlock | Token::CurlyBracketBlock => {
+          tokens.push(TokenOrValue::Token(input.slice_from(start).into()));
+          let _guard = limits::enter(input, LimitKind::BlockDepth)?;
+          input.parse_nested_block(|input| TokenList::parse_into(input, tokens))?;
+          let end = match token {
+            Token::SquareBracketBlock => "]",
+            Token::CurlyBracketBlock => "}",
+            _ => ")"
+          };
+          tokens.push(TokenOrValue::Token(end.into()));
+        }
+        _ => tokens.push(TokenOrValue::Token(input.slice_from(start).into()))
+      }
+    }
+  }
+
+  /// Returns whether the list contains an environment variable.
+  pub fn has_env(&self) -> bool {
+    self.0.iter().any(|token| matches!(token, TokenOrValue::Env(_)))
+  }
+
+  /// Returns a copy of the list with all environment variables, including those
+  /// within fallback values, referenced using the given function.
+  pub fn with_env_function(&self, function: EnvironmentFunction) -> TokenList {
+    TokenList(self.0.iter().map(|token| match token {
+      TokenOrValue::Env(env) => TokenOrValue::Env(EnvironmentVariable {
+        function,
+        name: env.name.clone(),
+        fallback: env.fallback.as_ref().map(|fallback| fallback.with_env_function(function))
+      }),
+      token => token.clone()
+    }).collect())
+  }
+}
+
+impl ToCss for TokenList {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    for token in &self.0 {
+      match token {
+        TokenOrValue::Token(token) => dest.write_str(token)?,
+        TokenOrValue::Env(env) => env.to_css(dest)?
+      }
+    }
+    Ok(())
+  }
+}
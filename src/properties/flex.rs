@@ -9,6 +9,7 @@ use super::{Property, VendorPrefix};
 use super::align::{JustifyContent, ContentDistribution, ContentPosition, AlignItems, SelfPosition, AlignSelf, AlignContent};
 use crate::printer::Printer;
 use super::prefixes::{Browsers, Feature, is_flex_2009};
+use crate::error::ParserError;
 
 // https://www.w3.org/TR/2018/CR-css-flexbox-1-20181119/#propdef-flex-direction
 enum_property!(FlexDirection,
@@ -51,7 +52,7 @@ pub struct FlexFlow {
 }
 
 impl Parse for FlexFlow {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut direction = None;
     let mut wrap = None;
     loop {
@@ -104,7 +105,7 @@ pub struct Flex {
 }
 
 impl Parse for Flex {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
       return Ok(Flex {
         grow: 0.0,
@@ -8,7 +8,10 @@ use crate::values::{
 use crate::traits::{Parse, ToCss, PropertyHandler};
 use super::Property;
 use crate::printer::Printer;
+use crate::warnings;
+use crate::compat;
 use std::fmt::Write;
+use crate::error::ParserError;
 
 /// https://www.w3.org/TR/2021/WD-css-fonts-4-20210729/#font-weight-prop
 #[derive(Debug, Clone, PartialEq)]
@@ -25,7 +28,7 @@ impl Default for FontWeight {
 }
 
 impl Parse for FontWeight {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(val) = input.try_parse(AbsoluteFontWeight::parse) {
       return Ok(FontWeight::Absolute(val))
     }
@@ -68,7 +71,7 @@ impl Default for AbsoluteFontWeight {
 }
 
 impl Parse for AbsoluteFontWeight {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(val) = input.try_parse(f32::parse) {
       return Ok(AbsoluteFontWeight::Weight(val))
     }
@@ -120,7 +123,7 @@ pub enum FontSize {
 }
 
 impl Parse for FontSize {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(val) = input.try_parse(LengthPercentage::parse) {
       return Ok(FontSize::Length(val))
     }
@@ -194,7 +197,7 @@ impl Default for FontStretch {
 }
 
 impl Parse for FontStretch {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(val) = input.try_parse(Percentage::parse) {
       return Ok(FontStretch::Percentage(val))
     }
@@ -252,7 +255,7 @@ pub enum FontFamily {
 }
 
 impl Parse for FontFamily {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(value) = input.try_parse(|i| i.expect_string_cloned()) {
       return Ok(FontFamily::FamilyName(value.as_ref().into()))
     }
@@ -311,7 +314,7 @@ impl Default for FontStyle {
 }
 
 impl Parse for FontStyle {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let location = input.current_source_location();
     let ident = input.expect_ident()?;
     match_ignore_ascii_case! { &*ident,
@@ -402,7 +405,7 @@ impl Default for LineHeight {
 }
 
 impl Parse for LineHeight {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
       return Ok(LineHeight::Normal)
     }
@@ -428,17 +431,17 @@ impl ToCss for LineHeight {
 /// https://www.w3.org/TR/2021/WD-css-fonts-4-20210729/#font-prop
 #[derive(Debug, Clone, PartialEq)]
 pub struct Font {
-  family: Vec<FontFamily>,
-  size: FontSize,
-  style: FontStyle,
-  weight: FontWeight,
-  stretch: FontStretch,
-  line_height: LineHeight,
-  variant_caps: FontVariantCapsCSS2
+  pub family: Vec<FontFamily>,
+  pub size: FontSize,
+  pub style: FontStyle,
+  pub weight: FontWeight,
+  pub stretch: FontStretch,
+  pub line_height: LineHeight,
+  pub variant_caps: FontVariantCapsCSS2
 }
 
 impl Parse for Font {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut style = None;
     let mut weight = None;
     let mut stretch = None;
@@ -544,6 +547,116 @@ impl ToCss for Font {
   }
 }
 
+// https://www.w3.org/TR/css-fonts-5/#font-size-adjust-prop
+enum_property!(FontSizeAdjustMetric,
+  ("ex-height", ExHeight),
+  ("cap-height", CapHeight),
+  ("ch-width", ChWidth),
+  ("ic-width", IcWidth),
+  ("ic-height", IcHeight)
+);
+
+impl Default for FontSizeAdjustMetric {
+  fn default() -> FontSizeAdjustMetric {
+    FontSizeAdjustMetric::ExHeight
+  }
+}
+
+/// https://www.w3.org/TR/css-fonts-5/#font-size-adjust-prop
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontSizeAdjustValue {
+  FromFont,
+  Number(f32)
+}
+
+/// https://www.w3.org/TR/css-fonts-5/#font-size-adjust-prop
+#[derive(Debug, Clone, PartialEq)]
+pub enum FontSizeAdjust {
+  None,
+  Value(FontSizeAdjustMetric, FontSizeAdjustValue)
+}
+
+impl Parse for FontSizeAdjust {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(FontSizeAdjust::None)
+    }
+
+    input.skip_whitespace();
+    let location = input.current_source_location();
+    let metric = input.try_parse(FontSizeAdjustMetric::parse).ok();
+    let value = if input.try_parse(|input| input.expect_ident_matching("from-font")).is_ok() {
+      FontSizeAdjustValue::FromFont
+    } else {
+      let number = f32::parse(input)?;
+      if number < 0.0 {
+        return Err(input.new_custom_error(ParserError::InvalidValue))
+      }
+      FontSizeAdjustValue::Number(number)
+    };
+
+    // The metric and `from-font` were added later than the single number syntax.
+    if metric.is_some() || value == FontSizeAdjustValue::FromFont {
+      warnings::check_feature(location, compat::Feature::FontSizeAdjustTwoValues);
+    } else {
+      warnings::check_feature(location, compat::Feature::FontSizeAdjust);
+    }
+
+    Ok(FontSizeAdjust::Value(metric.unwrap_or_default(), value))
+  }
+}
+
+impl ToCss for FontSizeAdjust {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      FontSizeAdjust::None => dest.write_str("none"),
+      FontSizeAdjust::Value(metric, value) => {
+        if *metric != FontSizeAdjustMetric::default() {
+          metric.to_css(dest)?;
+          dest.write_char(' ')?;
+        }
+
+        match value {
+          FontSizeAdjustValue::FromFont => dest.write_str("from-font"),
+          FontSizeAdjustValue::Number(number) => number.to_css(dest)
+        }
+      }
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-fonts-4/#font-optical-sizing-def
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FontOpticalSizing {
+  Auto,
+  None
+}
+
+impl Parse for FontOpticalSizing {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    input.skip_whitespace();
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    let value = match_ignore_ascii_case! { &*ident,
+      "auto" => FontOpticalSizing::Auto,
+      "none" => FontOpticalSizing::None,
+      _ => return Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+    };
+
+    warnings::check_feature(location, compat::Feature::FontOpticalSizing);
+    Ok(value)
+  }
+}
+
+impl ToCss for FontOpticalSizing {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      FontOpticalSizing::Auto => dest.write_str("auto"),
+      FontOpticalSizing::None => dest.write_str("none")
+    }
+  }
+}
+
 #[derive(Default, Debug)]
 pub struct FontHandler {
   family: Option<Vec<FontFamily>>,
@@ -552,7 +665,42 @@ pub struct FontHandler {
   weight: Option<FontWeight>,
   stretch: Option<FontStretch>,
   line_height: Option<LineHeight>,
-  variant_caps: Option<FontVariantCaps>
+  variant_caps: Option<FontVariantCaps>,
+  size_adjust: Option<FontSizeAdjust>,
+  optical_sizing: Option<FontOpticalSizing>,
+  fallbacks: Vec<GenericFontFamily>
+}
+
+impl FontHandler {
+  /// Creates a handler that appends the given generic families to `font-family` lists,
+  /// including within the `font` shorthand, that do not contain a generic family.
+  pub fn with_fallbacks(fallbacks: Vec<GenericFontFamily>) -> FontHandler {
+    FontHandler {
+      fallbacks,
+      ..FontHandler::default()
+    }
+  }
+
+  fn add_fallbacks(&self, family: &mut Vec<FontFamily>) {
+    if family.iter().any(|f| matches!(f, FontFamily::Generic(_))) {
+      return
+    }
+
+    // CSS-wide keywords are parsed as family names, but cannot be combined with other families.
+    if let [FontFamily::FamilyName(name)] = family.as_slice() {
+      let name = name.to_ascii_lowercase();
+      if name == "inherit" || name == "initial" || name == "unset" || name == "revert" {
+        return
+      }
+    }
+
+    for fallback in &self.fallbacks {
+      let fallback = FontFamily::Generic(*fallback);
+      if !family.contains(&fallback) {
+        family.push(fallback);
+      }
+    }
+  }
 }
 
 impl PropertyHandler for FontHandler {
@@ -567,6 +715,8 @@ impl PropertyHandler for FontHandler {
       FontStretch(val) => self.stretch = Some(val.clone()),
       FontVariantCaps(val) => self.variant_caps = Some(val.clone()),
       LineHeight(val) => self.line_height = Some(val.clone()),
+      FontSizeAdjust(val) => self.size_adjust = Some(val.clone()),
+      FontOpticalSizing(val) => self.optical_sizing = Some(val.clone()),
       Font(val) => {
         self.family = Some(val.family.clone());
         self.size = Some(val.size.clone());
@@ -575,6 +725,9 @@ impl PropertyHandler for FontHandler {
         self.stretch = Some(val.stretch.clone());
         self.line_height = Some(val.line_height.clone());
         self.variant_caps = Some(val.variant_caps.to_font_variant_caps());
+        // These are reset to their initial values by the shorthand, so earlier declarations have no effect.
+        self.size_adjust = None;
+        self.optical_sizing = None;
         // TODO: reset other properties
       }
       _ => return false
@@ -585,13 +738,18 @@ impl PropertyHandler for FontHandler {
 
   fn finalize(&mut self) -> Vec<Property> {
     let mut decls = vec![];
-    let family = std::mem::take(&mut self.family);
+    let mut family = std::mem::take(&mut self.family);
+    if let Some(family) = &mut family {
+      self.add_fallbacks(family);
+    }
     let size = std::mem::take(&mut self.size);
     let style = std::mem::take(&mut self.style);
     let weight = std::mem::take(&mut self.weight);
     let stretch = std::mem::take(&mut self.stretch);
     let line_height = std::mem::take(&mut self.line_height);
     let variant_caps = std::mem::take(&mut self.variant_caps);
+    let size_adjust = std::mem::take(&mut self.size_adjust);
+    let optical_sizing = std::mem::take(&mut self.optical_sizing);
 
     if family.is_some() && size.is_some() && style.is_some() && weight.is_some() && stretch.is_some() && line_height.is_some() && variant_caps.is_some() {
       let caps = variant_caps.unwrap().to_css2();
@@ -640,6 +798,15 @@ impl PropertyHandler for FontHandler {
       }
     }
 
+    // These come after the shorthand, which would otherwise reset them.
+    if let Some(val) = size_adjust {
+      decls.push(Property::FontSizeAdjust(val))
+    }
+
+    if let Some(val) = optical_sizing {
+      decls.push(Property::FontOpticalSizing(val))
+    }
+
     decls
   }
 }
@@ -0,0 +1,934 @@
+use cssparser::*;
+use crate::macros::enum_property;
+use crate::traits::{Parse, ToCss, PropertyHandler, FromStandard};
+use crate::values::length::LengthPercentage;
+use crate::values::ident::CustomIdent;
+use crate::values::number::serialize_dimension;
+use crate::printer::Printer;
+use crate::warnings::{self, WarningKind};
+use super::{Property, VendorPrefix};
+use super::custom::CustomProperty;
+use super::prefixes::{Browsers, Feature};
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// https://www.w3.org/TR/css-grid-1/#typedef-track-breadth
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackBreadth {
+  Length(LengthPercentage),
+  /// A flexible length in `fr` units.
+  Flex(f32),
+  MinContent,
+  MaxContent,
+  Auto
+}
+
+impl Parse for TrackBreadth {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(length) = input.try_parse(LengthPercentage::parse) {
+      return Ok(TrackBreadth::Length(length))
+    }
+
+    if let Ok(flex) = input.try_parse(parse_flex) {
+      return Ok(TrackBreadth::Flex(flex))
+    }
+
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    match_ignore_ascii_case! { &*ident,
+      "min-content" => Ok(TrackBreadth::MinContent),
+      "max-content" => Ok(TrackBreadth::MaxContent),
+      "auto" => Ok(TrackBreadth::Auto),
+      _ => Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+    }
+  }
+}
+
+fn parse_flex<'i, 't>(input: &mut Parser<'i, 't>) -> Result<f32, ParseError<'i, ParserError<'i>>> {
+  let location = input.current_source_location();
+  match *input.next()? {
+    Token::Dimension { value, ref unit, .. } if unit.eq_ignore_ascii_case("fr") && value >= 0.0 => Ok(value),
+    ref t => Err(location.new_unexpected_token_error(t.clone()))
+  }
+}
+
+impl ToCss for TrackBreadth {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      TrackBreadth::Length(length) => length.to_css(dest),
+      TrackBreadth::Flex(flex) => serialize_dimension(*flex, "fr", dest),
+      TrackBreadth::MinContent => dest.write_str("min-content"),
+      TrackBreadth::MaxContent => dest.write_str("max-content"),
+      TrackBreadth::Auto => dest.write_str("auto")
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-grid-1/#typedef-track-size
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackSize {
+  TrackBreadth(TrackBreadth),
+  MinMax(TrackBreadth, TrackBreadth),
+  FitContent(LengthPercentage)
+}
+
+impl Parse for TrackSize {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(breadth) = input.try_parse(TrackBreadth::parse) {
+      return Ok(TrackSize::TrackBreadth(breadth))
+    }
+
+    let location = input.current_source_location();
+    let function = input.expect_function()?.clone();
+    input.parse_nested_block(|input| {
+      match_ignore_ascii_case! { &*function,
+        "minmax" => {
+          // The minimum cannot be a flexible length.
+          let min_location = input.current_source_location();
+          let min = TrackBreadth::parse(input)?;
+          if let TrackBreadth::Flex(_) = min {
+            return Err(min_location.new_custom_error(ParserError::InvalidValue))
+          }
+          input.expect_comma()?;
+          let max = TrackBreadth::parse(input)?;
+          Ok(TrackSize::MinMax(min, max))
+        },
+        "fit-content" => Ok(TrackSize::FitContent(LengthPercentage::parse(input)?)),
+        _ => Err(location.new_unexpected_token_error(Token::Ident(function.clone())))
+      }
+    })
+  }
+}
+
+impl ToCss for TrackSize {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      TrackSize::TrackBreadth(breadth) => breadth.to_css(dest),
+      TrackSize::MinMax(min, max) => {
+        dest.write_str("minmax(")?;
+        min.to_css(dest)?;
+        dest.delim(',', false)?;
+        max.to_css(dest)?;
+        dest.write_char(')')
+      }
+      TrackSize::FitContent(length) => {
+        dest.write_str("fit-content(")?;
+        length.to_css(dest)?;
+        dest.write_char(')')
+      }
+    }
+  }
+}
+
+/// Parses a list of line names in square brackets, e.g. `[header-start main-start]`.
+/// https://www.w3.org/TR/css-grid-1/#typedef-line-names
+fn parse_line_names<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Vec<CustomIdent>, ParseError<'i, ParserError<'i>>> {
+  input.expect_square_bracket_block()?;
+  input.parse_nested_block(|input| {
+    let mut names = vec![];
+    while let Ok(name) = input.try_parse(CustomIdent::parse) {
+      names.push(name);
+    }
+    Ok(names)
+  })
+}
+
+fn write_line_names<W>(names: &[CustomIdent], dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+  dest.write_char('[')?;
+  for (i, name) in names.iter().enumerate() {
+    if i > 0 {
+      dest.write_char(' ')?;
+    }
+    name.to_css(dest)?;
+  }
+  dest.write_char(']')
+}
+
+/// Parses track sizes interleaved with line names, of which there is one more list than
+/// there are track sizes. Empty lists are stored for lines without names.
+fn parse_tracks<'i, 't, T, F>(input: &mut Parser<'i, 't>, mut parse_track: F) -> Result<(Vec<Vec<CustomIdent>>, Vec<T>), ParseError<'i, ParserError<'i>>>
+  where F: FnMut(&mut Parser<'i, 't>) -> Result<T, ParseError<'i, ParserError<'i>>> {
+  let mut line_names = vec![];
+  let mut tracks = vec![];
+  loop {
+    line_names.push(input.try_parse(parse_line_names).unwrap_or_default());
+    match input.try_parse(&mut parse_track) {
+      Ok(track) => tracks.push(track),
+      Err(_) => break
+    }
+  }
+
+  if tracks.is_empty() {
+    return Err(input.new_error_for_next_token())
+  }
+
+  Ok((line_names, tracks))
+}
+
+fn write_tracks<T: ToCss, W>(line_names: &[Vec<CustomIdent>], tracks: &[T], dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+  let mut first = true;
+  for (i, names) in line_names.iter().enumerate() {
+    if !names.is_empty() {
+      if !first {
+        dest.write_char(' ')?;
+      }
+      write_line_names(names, dest)?;
+      first = false;
+    }
+
+    if let Some(track) = tracks.get(i) {
+      if !first {
+        dest.write_char(' ')?;
+      }
+      track.to_css(dest)?;
+      first = false;
+    }
+  }
+  Ok(())
+}
+
+/// https://www.w3.org/TR/css-grid-1/#typedef-track-repeat
+#[derive(Debug, Clone, PartialEq)]
+pub enum RepeatCount {
+  Number(i32),
+  AutoFill,
+  AutoFit
+}
+
+impl Parse for RepeatCount {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto-fill")).is_ok() {
+      return Ok(RepeatCount::AutoFill)
+    }
+
+    if input.try_parse(|input| input.expect_ident_matching("auto-fit")).is_ok() {
+      return Ok(RepeatCount::AutoFit)
+    }
+
+    let location = input.current_source_location();
+    let count = input.expect_integer()?;
+    if count < 1 {
+      return Err(location.new_custom_error(ParserError::InvalidValue))
+    }
+    Ok(RepeatCount::Number(count))
+  }
+}
+
+impl ToCss for RepeatCount {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      RepeatCount::Number(count) => write!(dest, "{}", count),
+      RepeatCount::AutoFill => dest.write_str("auto-fill"),
+      RepeatCount::AutoFit => dest.write_str("auto-fit")
+    }
+  }
+}
+
+/// The `repeat()` function within a track list, e.g. `repeat(3, [col] 1fr)`.
+/// https://www.w3.org/TR/css-grid-1/#repeat-notation
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackRepeat {
+  pub count: RepeatCount,
+  pub line_names: Vec<Vec<CustomIdent>>,
+  pub track_sizes: Vec<TrackSize>
+}
+
+impl Parse for TrackRepeat {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    input.expect_function_matching("repeat")?;
+    input.parse_nested_block(|input| {
+      let count = RepeatCount::parse(input)?;
+      input.expect_comma()?;
+      let (line_names, track_sizes) = parse_tracks(input, TrackSize::parse)?;
+      Ok(TrackRepeat { count, line_names, track_sizes })
+    })
+  }
+}
+
+impl ToCss for TrackRepeat {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    dest.write_str("repeat(")?;
+    self.count.to_css(dest)?;
+    dest.delim(',', false)?;
+    write_tracks(&self.line_names, &self.track_sizes, dest)?;
+    dest.write_char(')')
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackListItem {
+  TrackSize(TrackSize),
+  TrackRepeat(TrackRepeat)
+}
+
+impl Parse for TrackListItem {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(repeat) = input.try_parse(TrackRepeat::parse) {
+      return Ok(TrackListItem::TrackRepeat(repeat))
+    }
+
+    Ok(TrackListItem::TrackSize(TrackSize::parse(input)?))
+  }
+}
+
+impl ToCss for TrackListItem {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      TrackListItem::TrackSize(size) => size.to_css(dest),
+      TrackListItem::TrackRepeat(repeat) => repeat.to_css(dest)
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-grid-1/#typedef-track-list
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackList {
+  pub line_names: Vec<Vec<CustomIdent>>,
+  pub items: Vec<TrackListItem>
+}
+
+impl Parse for TrackList {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let (line_names, items) = parse_tracks(input, TrackListItem::parse)?;
+    Ok(TrackList { line_names, items })
+  }
+}
+
+impl ToCss for TrackList {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    write_tracks(&self.line_names, &self.items, dest)
+  }
+}
+
+/// The value of the `grid-template-columns` and `grid-template-rows` properties.
+/// https://www.w3.org/TR/css-grid-1/#track-sizing
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackSizing {
+  None,
+  TrackList(TrackList)
+}
+
+impl Parse for TrackSizing {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(TrackSizing::None)
+    }
+
+    Ok(TrackSizing::TrackList(TrackList::parse(input)?))
+  }
+}
+
+impl ToCss for TrackSizing {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      TrackSizing::None => dest.write_str("none"),
+      TrackSizing::TrackList(list) => list.to_css(dest)
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-grid-1/#typedef-grid-row-start-grid-line
+#[derive(Debug, Clone, PartialEq)]
+pub enum GridLine {
+  Auto,
+  /// A named line, e.g. `header`.
+  Ident(CustomIdent),
+  /// The nth line, optionally counting only lines with the given name.
+  /// Negative numbers count from the end of the explicit grid.
+  Line(i32, Option<CustomIdent>),
+  /// A span of the given number of lines, optionally counting only lines with the given name.
+  Span(i32, Option<CustomIdent>)
+}
+
+impl Default for GridLine {
+  fn default() -> GridLine {
+    GridLine::Auto
+  }
+}
+
+fn parse_line_name<'i, 't>(input: &mut Parser<'i, 't>) -> Result<CustomIdent, ParseError<'i, ParserError<'i>>> {
+  let location = input.current_source_location();
+  let name = CustomIdent::parse(input)?;
+  if name.0.eq_ignore_ascii_case("span") || name.0.eq_ignore_ascii_case("auto") {
+    return Err(location.new_custom_error(ParserError::InvalidValue))
+  }
+  Ok(name)
+}
+
+impl Parse for GridLine {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(GridLine::Auto)
+    }
+
+    // The parts may be in any order.
+    let location = input.current_source_location();
+    let mut span = false;
+    let mut count = None;
+    let mut name = None;
+    loop {
+      if !span && input.try_parse(|input| input.expect_ident_matching("span")).is_ok() {
+        span = true;
+        continue
+      }
+
+      if count.is_none() {
+        if let Ok(c) = input.try_parse(|input| input.expect_integer()) {
+          count = Some(c);
+          continue
+        }
+      }
+
+      if name.is_none() {
+        if let Ok(n) = input.try_parse(parse_line_name) {
+          name = Some(n);
+          continue
+        }
+      }
+
+      break
+    }
+
+    match (span, count, name) {
+      (true, Some(count), name) if count > 0 => Ok(GridLine::Span(count, name)),
+      (true, None, Some(name)) => Ok(GridLine::Span(1, Some(name))),
+      (false, Some(count), name) if count != 0 => Ok(GridLine::Line(count, name)),
+      (false, None, Some(name)) => Ok(GridLine::Ident(name)),
+      _ => Err(location.new_custom_error(ParserError::InvalidValue))
+    }
+  }
+}
+
+impl ToCss for GridLine {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      GridLine::Auto => dest.write_str("auto"),
+      GridLine::Ident(name) => name.to_css(dest),
+      GridLine::Line(count, name) => {
+        write!(dest, "{}", count)?;
+        if let Some(name) = name {
+          dest.write_char(' ')?;
+          name.to_css(dest)?;
+        }
+        Ok(())
+      }
+      GridLine::Span(count, name) => {
+        dest.write_str("span ")?;
+        // A span of one line is the default when a name is given.
+        if *count != 1 || name.is_none() {
+          write!(dest, "{}", count)?;
+          if name.is_some() {
+            dest.write_char(' ')?;
+          }
+        }
+        if let Some(name) = name {
+          name.to_css(dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+/// The value of the `grid-row` and `grid-column` shorthands.
+/// https://www.w3.org/TR/css-grid-1/#placement-shorthands
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridPlacement {
+  pub start: GridLine,
+  pub end: GridLine
+}
+
+impl GridPlacement {
+  /// Returns the end line that is used when it is omitted from the shorthand.
+  fn default_end(start: &GridLine) -> GridLine {
+    match start {
+      GridLine::Ident(name) => GridLine::Ident(name.clone()),
+      _ => GridLine::Auto
+    }
+  }
+}
+
+impl Parse for GridPlacement {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let start = GridLine::parse(input)?;
+    let end = if input.try_parse(|input| input.expect_delim('/')).is_ok() {
+      GridLine::parse(input)?
+    } else {
+      GridPlacement::default_end(&start)
+    };
+    Ok(GridPlacement { start, end })
+  }
+}
+
+impl ToCss for GridPlacement {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    self.start.to_css(dest)?;
+    if self.end != GridPlacement::default_end(&self.start) {
+      dest.delim('/', true)?;
+      self.end.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+/// The value of the `grid-area` shorthand.
+/// https://www.w3.org/TR/css-grid-1/#propdef-grid-area
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridArea {
+  pub row_start: GridLine,
+  pub column_start: GridLine,
+  pub row_end: GridLine,
+  pub column_end: GridLine
+}
+
+impl Parse for GridArea {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut lines = vec![GridLine::parse(input)?];
+    while lines.len() < 4 && input.try_parse(|input| input.expect_delim('/')).is_ok() {
+      lines.push(GridLine::parse(input)?);
+    }
+
+    // Omitted lines default to the row start or column start if it is a named line, or to `auto`.
+    let mut lines = lines.into_iter();
+    let row_start = lines.next().unwrap();
+    let column_start = lines.next().unwrap_or_else(|| GridPlacement::default_end(&row_start));
+    let row_end = lines.next().unwrap_or_else(|| GridPlacement::default_end(&row_start));
+    let column_end = lines.next().unwrap_or_else(|| GridPlacement::default_end(&column_start));
+    Ok(GridArea { row_start, column_start, row_end, column_end })
+  }
+}
+
+impl ToCss for GridArea {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    // Trailing lines are omitted if they are the same as their default.
+    let mut count = 4;
+    if self.column_end == GridPlacement::default_end(&self.column_start) {
+      count = 3;
+      if self.row_end == GridPlacement::default_end(&self.row_start) {
+        count = 2;
+        if self.column_start == GridPlacement::default_end(&self.row_start) {
+          count = 1;
+        }
+      }
+    }
+
+    let lines = [&self.row_start, &self.column_start, &self.row_end, &self.column_end];
+    for (i, line) in lines.iter().take(count).enumerate() {
+      if i > 0 {
+        dest.delim('/', true)?;
+      }
+      line.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+// https://www.w3.org/TR/css-grid-1/#grid-auto-flow-property
+enum_property!(GridAutoFlowDirection,
+  Row,
+  Column
+);
+
+/// https://www.w3.org/TR/css-grid-1/#grid-auto-flow-property
+#[derive(Debug, Clone, PartialEq)]
+pub struct GridAutoFlow {
+  pub direction: GridAutoFlowDirection,
+  pub dense: bool
+}
+
+impl Parse for GridAutoFlow {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut direction = input.try_parse(GridAutoFlowDirection::parse).ok();
+    let dense = input.try_parse(|input| input.expect_ident_matching("dense")).is_ok();
+    if direction.is_none() {
+      direction = input.try_parse(GridAutoFlowDirection::parse).ok();
+      if direction.is_none() && !dense {
+        return Err(input.new_error_for_next_token())
+      }
+    }
+
+    Ok(GridAutoFlow {
+      direction: direction.unwrap_or(GridAutoFlowDirection::Row),
+      dense
+    })
+  }
+}
+
+impl ToCss for GridAutoFlow {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match (self.direction, self.dense) {
+      (GridAutoFlowDirection::Row, true) => dest.write_str("dense"),
+      (direction, dense) => {
+        direction.to_css(dest)?;
+        if dense {
+          dest.write_str(" dense")?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+/// An item in the legacy `-ms-grid-columns` and `-ms-grid-rows` syntax of Edge and IE, which
+/// repeats tracks with e.g. `(10px 1fr)[3]` rather than `repeat()`, and has no line names.
+/// https://www.w3.org/TR/2011/WD-css3-grid-layout-20110407/#grid-columns-and-rows-properties
+#[derive(Debug, Clone, PartialEq)]
+pub enum MsTrackListItem {
+  TrackSize(TrackSize),
+  Repeat(Vec<TrackSize>, i32)
+}
+
+/// Parses a track size, excluding `fit-content()`, which the legacy syntax does not support.
+fn parse_ms_track_size<'i, 't>(input: &mut Parser<'i, 't>) -> Result<TrackSize, ParseError<'i, ParserError<'i>>> {
+  let location = input.current_source_location();
+  match TrackSize::parse(input)? {
+    TrackSize::FitContent(_) => Err(location.new_custom_error(ParserError::InvalidValue)),
+    size => Ok(size)
+  }
+}
+
+impl Parse for MsTrackListItem {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(size) = input.try_parse(parse_ms_track_size) {
+      return Ok(MsTrackListItem::TrackSize(size))
+    }
+
+    input.expect_parenthesis_block()?;
+    let sizes = input.parse_nested_block(|input| {
+      let mut sizes = vec![parse_ms_track_size(input)?];
+      while let Ok(size) = input.try_parse(parse_ms_track_size) {
+        sizes.push(size);
+      }
+      Ok(sizes)
+    })?;
+
+    input.expect_square_bracket_block()?;
+    let count = input.parse_nested_block(|input| {
+      let location = input.current_source_location();
+      let count = input.expect_integer()?;
+      if count < 1 {
+        return Err(location.new_custom_error(ParserError::InvalidValue))
+      }
+      Ok(count)
+    })?;
+
+    Ok(MsTrackListItem::Repeat(sizes, count))
+  }
+}
+
+impl ToCss for MsTrackListItem {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      MsTrackListItem::TrackSize(size) => size.to_css(dest),
+      MsTrackListItem::Repeat(sizes, count) => {
+        dest.write_char('(')?;
+        for (i, size) in sizes.iter().enumerate() {
+          if i > 0 {
+            dest.write_char(' ')?;
+          }
+          size.to_css(dest)?;
+        }
+        write!(dest, ")[{}]", count)
+      }
+    }
+  }
+}
+
+/// The value of the legacy `-ms-grid-columns` and `-ms-grid-rows` properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MsTrackList(pub Vec<MsTrackListItem>);
+
+impl Parse for MsTrackList {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut items = vec![MsTrackListItem::parse(input)?];
+    while let Ok(item) = input.try_parse(MsTrackListItem::parse) {
+      items.push(item);
+    }
+    Ok(MsTrackList(items))
+  }
+}
+
+impl ToCss for MsTrackList {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    for (i, item) in self.0.iter().enumerate() {
+      if i > 0 {
+        dest.write_char(' ')?;
+      }
+      item.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+/// Line names are dropped, since the legacy syntax cannot reference them. Track lists with
+/// `auto-fill` or `auto-fit` repetitions or `fit-content()` cannot be translated.
+impl FromStandard<TrackSizing> for MsTrackList {
+  fn from_standard(sizing: &TrackSizing) -> Option<MsTrackList> {
+    let list = match sizing {
+      TrackSizing::None => return None,
+      TrackSizing::TrackList(list) => list
+    };
+
+    let is_supported = |size: &TrackSize| !matches!(size, TrackSize::FitContent(_));
+    let mut items = vec![];
+    for item in &list.items {
+      match item {
+        TrackListItem::TrackSize(size) if is_supported(size) => items.push(MsTrackListItem::TrackSize(size.clone())),
+        TrackListItem::TrackRepeat(TrackRepeat { count: RepeatCount::Number(count), track_sizes, .. })
+          if track_sizes.iter().all(is_supported) => {
+          items.push(MsTrackListItem::Repeat(track_sizes.clone(), *count))
+        }
+        _ => return None
+      }
+    }
+
+    Some(MsTrackList(items))
+  }
+}
+
+/// Returns the line and the number of tracks spanned in the legacy syntax, i.e. the values of
+/// `-ms-grid-row` and `-ms-grid-row-span`, for the given start and end lines. The legacy syntax
+/// has no auto-placement, named lines, or lines counted from the end of the grid, so placements
+/// that rely on them cannot be translated.
+pub fn ms_grid_placement(start: &GridLine, end: &GridLine) -> Option<(i32, i32)> {
+  match (start, end) {
+    (GridLine::Line(start, None), GridLine::Auto) if *start > 0 => Some((*start, 1)),
+    (GridLine::Line(start, None), GridLine::Span(span, None)) if *start > 0 => Some((*start, *span)),
+    // The lines are swapped if the end is before the start, and the end is
+    // ignored if it is the same line.
+    (GridLine::Line(start, None), GridLine::Line(end, None)) if *start > 0 && *end > 0 => {
+      if end > start {
+        Some((*start, end - start))
+      } else if end < start {
+        Some((*end, start - end))
+      } else {
+        Some((*start, 1))
+      }
+    }
+    (GridLine::Span(span, None), GridLine::Line(end, None)) if *end - *span >= 1 => Some((end - span, *span)),
+    _ => None
+  }
+}
+
+/// Returns whether the targets need the legacy `-ms-` grid syntax.
+fn needs_ms_grid(targets: Browsers) -> bool {
+  Feature::DisplayGrid.prefixes_for(targets).contains(VendorPrefix::Ms)
+}
+
+/// Reports an `UnsupportedMsGrid` warning if validating against targets that need the legacy
+/// `-ms-` grid syntax, and the given property cannot be translated to it. Properties that
+/// rely on auto-placement, gaps, or named grid areas are reported, since the legacy syntax
+/// supports none of them.
+pub fn check_ms_grid(property: &Property, location: SourceLocation) {
+  if !warnings::validation_targets().map_or(false, needs_ms_grid) {
+    return
+  }
+
+  // Explicit lines are only translated in combination with the other side of the
+  // placement, so spans and lines at the end are checked as if the other side were
+  // a line as well.
+  let is_supported = match property {
+    Property::GridTemplateColumns(sizing) |
+    Property::GridTemplateRows(sizing) => *sizing == TrackSizing::None || MsTrackList::from_standard(sizing).is_some(),
+    Property::GridRowStart(line) |
+    Property::GridColumnStart(line) => ms_grid_placement(line, &GridLine::Line(i32::MAX, None)).is_some(),
+    Property::GridRowEnd(line) |
+    Property::GridColumnEnd(line) => *line == GridLine::Auto || ms_grid_placement(&GridLine::Line(1, None), line).is_some(),
+    Property::GridRow(placement) |
+    Property::GridColumn(placement) => ms_grid_placement(&placement.start, &placement.end).is_some(),
+    Property::GridArea(area) => {
+      ms_grid_placement(&area.row_start, &area.row_end).is_some() &&
+        ms_grid_placement(&area.column_start, &area.column_end).is_some()
+    }
+    Property::Custom(custom) => !custom.name.eq_ignore_ascii_case("grid-template-areas"),
+    Property::GridAutoFlow(_) |
+    Property::GridGap(_) |
+    Property::GridRowGap(_) |
+    Property::GridColumnGap(_) => false,
+    _ => true
+  };
+
+  if !is_supported {
+    warnings::warn(location, WarningKind::UnsupportedMsGrid)
+  }
+}
+
+/// Returns whether a `display` declaration is for a grid container, and if so, the legacy
+/// `-ms-` value for it.
+fn ms_display_value(display: &CustomProperty) -> Option<&'static str> {
+  match_ignore_ascii_case! { &display.value,
+    "grid" => Some("-ms-grid"),
+    "inline-grid" => Some("-ms-inline-grid"),
+    _ => None
+  }
+}
+
+fn is_ms_display(display: &CustomProperty) -> bool {
+  display.value.eq_ignore_ascii_case("-ms-grid") || display.value.eq_ignore_ascii_case("-ms-inline-grid")
+}
+
+/// Adds the legacy `-ms-grid-*` properties, and the `-ms-grid` display value, for targets that
+/// need them, e.g. IE 11, translated from the standard grid properties where possible. Legacy
+/// properties that are declared explicitly are kept rather than generated. The handler does
+/// nothing if none of the targets need the legacy syntax.
+#[derive(Default)]
+pub struct GridHandler {
+  targets: Option<Browsers>,
+  display: Option<CustomProperty>,
+  ms_display: Option<CustomProperty>,
+  template_columns: Option<TrackSizing>,
+  template_rows: Option<TrackSizing>,
+  ms_columns: Option<MsTrackList>,
+  ms_rows: Option<MsTrackList>,
+  row_start: Option<GridLine>,
+  row_end: Option<GridLine>,
+  column_start: Option<GridLine>,
+  column_end: Option<GridLine>,
+  /// Whether the lines were set by `grid-area`, so they are printed as `grid-area` as well.
+  has_area: bool,
+  ms_row: Option<i32>,
+  ms_row_span: Option<i32>,
+  ms_column: Option<i32>,
+  ms_column_span: Option<i32>
+}
+
+impl GridHandler {
+  pub fn new(targets: Option<Browsers>) -> GridHandler {
+    GridHandler {
+      targets: targets.filter(|targets| needs_ms_grid(*targets)),
+      ..GridHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for GridHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    if self.targets.is_none() {
+      return false
+    }
+
+    use Property::*;
+    match property {
+      Custom(custom) if custom.name.eq_ignore_ascii_case("display") => {
+        if is_ms_display(custom) {
+          self.ms_display = Some(custom.clone());
+        } else {
+          self.display = Some(custom.clone());
+        }
+      }
+      GridTemplateColumns(val) => self.template_columns = Some(val.clone()),
+      GridTemplateRows(val) => self.template_rows = Some(val.clone()),
+      MsGridColumns(val) => self.ms_columns = Some(val.clone()),
+      MsGridRows(val) => self.ms_rows = Some(val.clone()),
+      GridRowStart(val) => self.row_start = Some(val.clone()),
+      GridRowEnd(val) => self.row_end = Some(val.clone()),
+      GridColumnStart(val) => self.column_start = Some(val.clone()),
+      GridColumnEnd(val) => self.column_end = Some(val.clone()),
+      GridRow(val) => {
+        self.row_start = Some(val.start.clone());
+        self.row_end = Some(val.end.clone());
+      }
+      GridColumn(val) => {
+        self.column_start = Some(val.start.clone());
+        self.column_end = Some(val.end.clone());
+      }
+      GridArea(val) => {
+        self.row_start = Some(val.row_start.clone());
+        self.column_start = Some(val.column_start.clone());
+        self.row_end = Some(val.row_end.clone());
+        self.column_end = Some(val.column_end.clone());
+        self.has_area = true;
+      }
+      MsGridRow(val) => self.ms_row = Some(*val),
+      MsGridRowSpan(val) => self.ms_row_span = Some(*val),
+      MsGridColumn(val) => self.ms_column = Some(*val),
+      MsGridColumnSpan(val) => self.ms_column_span = Some(*val),
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    let mut decls = vec![];
+
+    let display = std::mem::take(&mut self.display);
+    let mut ms_display = std::mem::take(&mut self.ms_display);
+    if ms_display.is_none() {
+      ms_display = display.as_ref().and_then(|display| {
+        ms_display_value(display).map(|value| CustomProperty {
+          name: display.name.clone(),
+          value: value.into()
+        })
+      });
+    }
+    decls.extend(ms_display.map(Property::Custom));
+    decls.extend(display.map(Property::Custom));
+
+    macro_rules! template {
+      ($standard: ident, $ms: ident, $standard_prop: ident, $ms_prop: ident) => {
+        let standard = std::mem::take(&mut self.$standard);
+        let mut ms = std::mem::take(&mut self.$ms);
+        if ms.is_none() {
+          ms = standard.as_ref().and_then(MsTrackList::from_standard);
+        }
+        decls.extend(ms.map(Property::$ms_prop));
+        decls.extend(standard.map(Property::$standard_prop));
+      };
+    }
+
+    template!(template_columns, ms_columns, GridTemplateColumns, MsGridColumns);
+    template!(template_rows, ms_rows, GridTemplateRows, MsGridRows);
+
+    macro_rules! ms_placement {
+      ($start: ident, $end: ident, $ms: ident, $ms_span: ident, $ms_prop: ident, $ms_span_prop: ident) => {
+        let mut ms = std::mem::take(&mut self.$ms);
+        let mut ms_span = std::mem::take(&mut self.$ms_span);
+        if ms.is_none() && ms_span.is_none() && self.$start.is_some() {
+          let start = self.$start.clone().unwrap_or_default();
+          let end = self.$end.clone().unwrap_or_default();
+          if let Some((line, span)) = ms_grid_placement(&start, &end) {
+            ms = Some(line);
+            // A span of one track is the default.
+            ms_span = if span != 1 { Some(span) } else { None };
+          }
+        }
+
+        decls.extend(ms.map(Property::$ms_prop));
+        decls.extend(ms_span.map(Property::$ms_span_prop));
+      };
+    }
+
+    macro_rules! placement {
+      ($start: ident, $end: ident, $shorthand: ident, $start_prop: ident, $end_prop: ident) => {
+        match (std::mem::take(&mut self.$start), std::mem::take(&mut self.$end)) {
+          (Some(start), Some(end)) => decls.push(Property::$shorthand(GridPlacement { start, end })),
+          (start, end) => {
+            decls.extend(start.map(Property::$start_prop));
+            decls.extend(end.map(Property::$end_prop));
+          }
+        }
+      };
+    }
+
+    // The lines set by `grid-area` are printed as `grid-area` again, after the legacy
+    // properties for both axes.
+    if std::mem::take(&mut self.has_area) {
+      ms_placement!(row_start, row_end, ms_row, ms_row_span, MsGridRow, MsGridRowSpan);
+      ms_placement!(column_start, column_end, ms_column, ms_column_span, MsGridColumn, MsGridColumnSpan);
+      decls.push(Property::GridArea(GridArea {
+        row_start: std::mem::take(&mut self.row_start).unwrap(),
+        column_start: std::mem::take(&mut self.column_start).unwrap(),
+        row_end: std::mem::take(&mut self.row_end).unwrap(),
+        column_end: std::mem::take(&mut self.column_end).unwrap()
+      }));
+    } else {
+      ms_placement!(row_start, row_end, ms_row, ms_row_span, MsGridRow, MsGridRowSpan);
+      placement!(row_start, row_end, GridRow, GridRowStart, GridRowEnd);
+      ms_placement!(column_start, column_end, ms_column, ms_column_span, MsGridColumn, MsGridColumnSpan);
+      placement!(column_start, column_end, GridColumn, GridColumnStart, GridColumnEnd);
+    }
+
+    decls
+  }
+}
@@ -0,0 +1,147 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss, PropertyHandler};
+use crate::printer::Printer;
+use crate::compat;
+use super::{Property, VendorPrefix};
+use super::prefixes::Browsers;
+use super::prefix_handler::PrefixedPropertyState;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// https://www.w3.org/TR/css-inline-3/#sizing-drop-initials
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitialLetterSink {
+  /// The number of lines the letter sinks.
+  Integer(i32),
+  /// Sinks the letter by its size, rounded down. This is the default.
+  Drop,
+  /// Sinks the letter by one line, i.e. it is raised.
+  Raise
+}
+
+/// https://www.w3.org/TR/css-inline-3/#sizing-drop-initials
+#[derive(Debug, Clone, PartialEq)]
+pub enum InitialLetter {
+  Normal,
+  Value(f32, InitialLetterSink)
+}
+
+impl Parse for InitialLetter {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(InitialLetter::Normal)
+    }
+
+    // The keywords may come before or after the size, but an integer must come after it.
+    let mut sink = input.try_parse(parse_sink_keyword).ok();
+    let location = input.current_source_location();
+    let size = f32::parse(input)?;
+    if size < 1.0 {
+      return Err(location.new_custom_error(ParserError::InvalidValue))
+    }
+
+    if sink.is_none() {
+      sink = input.try_parse(parse_sink_keyword).ok();
+    }
+
+    if sink.is_none() {
+      let location = input.current_source_location();
+      if let Ok(integer) = input.try_parse(|input| input.expect_integer()) {
+        if integer < 1 {
+          return Err(location.new_custom_error(ParserError::InvalidValue))
+        }
+        sink = Some(InitialLetterSink::Integer(integer));
+      }
+    }
+
+    Ok(InitialLetter::Value(size, sink.unwrap_or(InitialLetterSink::Drop)))
+  }
+}
+
+fn parse_sink_keyword<'i, 't>(input: &mut Parser<'i, 't>) -> Result<InitialLetterSink, ParseError<'i, ParserError<'i>>> {
+  let location = input.current_source_location();
+  let ident = input.expect_ident()?;
+  match_ignore_ascii_case! { &*ident,
+    "drop" => Ok(InitialLetterSink::Drop),
+    "raise" => Ok(InitialLetterSink::Raise),
+    _ => Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+  }
+}
+
+impl ToCss for InitialLetter {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      InitialLetter::Normal => dest.write_str("normal"),
+      InitialLetter::Value(size, sink) => {
+        size.to_css(dest)?;
+        match sink {
+          InitialLetterSink::Drop => Ok(()),
+          InitialLetterSink::Raise => dest.write_str(" raise"),
+          InitialLetterSink::Integer(integer) => write!(dest, " {}", integer)
+        }
+      }
+    }
+  }
+}
+
+/// Adds `-webkit-initial-letter` for targets that only support the prefixed property, e.g. Safari,
+/// and removes it if none of the targets need it. This is based on the support for the unprefixed
+/// property, since every browser that supports the property only supports one or the other.
+#[derive(Default)]
+pub struct InitialLetterHandler {
+  targets: Option<Browsers>,
+  initial_letter: PrefixedPropertyState<InitialLetter>,
+  decls: Vec<Property>
+}
+
+impl InitialLetterHandler {
+  pub fn new(targets: Option<Browsers>) -> InitialLetterHandler {
+    InitialLetterHandler {
+      targets,
+      ..InitialLetterHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for InitialLetterHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    match property {
+      Property::InitialLetter(val, vp) => {
+        if self.initial_letter.needs_flush(val, *vp) {
+          self.flush();
+        }
+        self.initial_letter.set(val, *vp);
+      }
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    self.flush();
+    std::mem::take(&mut self.decls)
+  }
+}
+
+impl InitialLetterHandler {
+  fn flush(&mut self) {
+    let prefixes = self.initial_letter.prefixes();
+    let val = match self.initial_letter.value() {
+      Some(val) if !prefixes.is_empty() => val.clone(),
+      _ => return
+    };
+    self.initial_letter = PrefixedPropertyState::default();
+
+    let mut prefixes = prefixes;
+    if let (true, Some(targets)) = (prefixes.contains(VendorPrefix::None), self.targets) {
+      if compat::Feature::InitialLetter.is_compatible(targets) {
+        prefixes.remove(VendorPrefix::WebKit);
+      } else {
+        prefixes.insert(VendorPrefix::WebKit);
+      }
+    }
+
+    self.decls.push(Property::InitialLetter(val, prefixes));
+  }
+}
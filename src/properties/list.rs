@@ -0,0 +1,322 @@
+use cssparser::*;
+use crate::macros::enum_property;
+use crate::traits::{Parse, ToCss, PropertyHandler};
+use crate::values::ident::CustomIdent;
+use crate::values::image::Image;
+use crate::rules::counter_style::{Symbol, serialize_symbols};
+use crate::printer::Printer;
+use super::Property;
+use super::content::{Content, ContentItem};
+use std::fmt::Write;
+use std::collections::HashMap;
+use crate::error::ParserError;
+
+// https://www.w3.org/TR/css-counter-styles-3/#symbols-function
+enum_property!(SymbolsType,
+  Cyclic,
+  Numeric,
+  Alphabetic,
+  Symbolic,
+  Fixed
+);
+
+/// https://www.w3.org/TR/css-counter-styles-3/#typedef-counter-style
+#[derive(Debug, Clone, PartialEq)]
+pub enum CounterStyle {
+  Name(CustomIdent),
+  Symbols(SymbolsType, Vec<Symbol>)
+}
+
+impl Parse for CounterStyle {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_function_matching("symbols")).is_ok() {
+      return input.parse_nested_block(|input| {
+        let symbols_type = input.try_parse(SymbolsType::parse).unwrap_or(SymbolsType::Symbolic);
+        // Unlike in `@counter-style` rules, symbols cannot be identifiers, and `none` is not an image.
+        let mut symbols = vec![];
+        while let Ok(symbol) = input.try_parse(|input| {
+          match Symbol::parse(input)? {
+            Symbol::Ident(_) | Symbol::Image(Image::None) => Err(input.new_custom_error(ParserError::InvalidValue)),
+            symbol => Ok(symbol)
+          }
+        }) {
+          symbols.push(symbol);
+        }
+
+        // The alphabetic and numeric systems require at least two symbols.
+        let min = match symbols_type {
+          SymbolsType::Alphabetic | SymbolsType::Numeric => 2,
+          _ => 1
+        };
+        if symbols.len() < min {
+          return Err(input.new_custom_error(ParserError::InvalidValue))
+        }
+
+        Ok(CounterStyle::Symbols(symbols_type, symbols))
+      })
+    }
+
+    let name = CustomIdent::parse(input)?;
+    if name.0.eq_ignore_ascii_case("none") {
+      return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+    Ok(CounterStyle::Name(name))
+  }
+}
+
+impl ToCss for CounterStyle {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      CounterStyle::Name(name) => name.to_css(dest),
+      CounterStyle::Symbols(symbols_type, symbols) => {
+        dest.write_str("symbols(")?;
+        if *symbols_type != SymbolsType::Symbolic {
+          symbols_type.to_css(dest)?;
+          dest.write_char(' ')?;
+        }
+        serialize_symbols(symbols, dest)?;
+        dest.write_char(')')
+      }
+    }
+  }
+}
+
+impl CounterStyle {
+  /// Renames the counter style if it refers to a `@counter-style` rule with a name in the given map.
+  fn rename(&mut self, names: &HashMap<String, String>) {
+    if let CounterStyle::Name(name) = self {
+      if let Some(new_name) = names.get(&name.0) {
+        name.0 = new_name.clone();
+      }
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-lists-3/#text-markers
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListStyleType {
+  None,
+  String(String),
+  CounterStyle(CounterStyle)
+}
+
+impl Default for ListStyleType {
+  fn default() -> ListStyleType {
+    ListStyleType::CounterStyle(CounterStyle::Name(CustomIdent("disc".into())))
+  }
+}
+
+impl Parse for ListStyleType {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(ListStyleType::None)
+    }
+
+    if let Ok(s) = input.try_parse(|input| input.expect_string_cloned()) {
+      return Ok(ListStyleType::String(s.as_ref().into()))
+    }
+
+    let style = CounterStyle::parse(input)?;
+    Ok(ListStyleType::CounterStyle(style))
+  }
+}
+
+impl ToCss for ListStyleType {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      ListStyleType::None => dest.write_str("none"),
+      ListStyleType::String(s) => serialize_string(&s, dest),
+      ListStyleType::CounterStyle(style) => style.to_css(dest)
+    }
+  }
+}
+
+// https://www.w3.org/TR/css-lists-3/#list-style-position-property
+enum_property!(ListStylePosition,
+  Inside,
+  Outside
+);
+
+impl Default for ListStylePosition {
+  fn default() -> ListStylePosition {
+    ListStylePosition::Outside
+  }
+}
+
+/// https://www.w3.org/TR/css-lists-3/#list-style-property
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListStyle {
+  pub list_style_type: ListStyleType,
+  pub image: Image,
+  pub position: ListStylePosition
+}
+
+impl Parse for ListStyle {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut position = None;
+    let mut image = None;
+    let mut list_style_type = None;
+    // `none` may apply to either the image or the type, so it is resolved at the end.
+    let mut nones = 0;
+
+    loop {
+      if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+        nones += 1;
+        if nones > 2 {
+          return Err(input.new_custom_error(ParserError::InvalidValue))
+        }
+        continue
+      }
+
+      if position.is_none() {
+        if let Ok(value) = input.try_parse(ListStylePosition::parse) {
+          position = Some(value);
+          continue
+        }
+      }
+
+      if image.is_none() {
+        if let Ok(value) = input.try_parse(Image::parse) {
+          image = Some(value);
+          continue
+        }
+      }
+
+      if list_style_type.is_none() {
+        if let Ok(value) = input.try_parse(ListStyleType::parse) {
+          list_style_type = Some(value);
+          continue
+        }
+      }
+
+      break
+    }
+
+    if nones == 0 && position.is_none() && image.is_none() && list_style_type.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+
+    let (image, list_style_type) = match (nones, image, list_style_type) {
+      (0, image, list_style_type) => (image.unwrap_or_default(), list_style_type.unwrap_or_default()),
+      (1, None, None) | (2, None, None) => (Image::None, ListStyleType::None),
+      (1, Some(image), None) => (image, ListStyleType::None),
+      (1, None, Some(list_style_type)) => (Image::None, list_style_type),
+      _ => return Err(input.new_custom_error(ParserError::InvalidValue))
+    };
+
+    Ok(ListStyle {
+      list_style_type,
+      image,
+      position: position.unwrap_or_default()
+    })
+  }
+}
+
+impl ToCss for ListStyle {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    let mut needs_space = false;
+    if self.position != ListStylePosition::default() {
+      self.position.to_css(dest)?;
+      needs_space = true;
+    }
+
+    if self.image != Image::default() {
+      if needs_space {
+        dest.write_char(' ')?;
+      }
+      self.image.to_css(dest)?;
+      needs_space = true;
+    }
+
+    // The type is printed if nothing else was, so that the value isn't empty.
+    if self.list_style_type != ListStyleType::default() || !needs_space {
+      if needs_space {
+        dest.write_char(' ')?;
+      }
+      self.list_style_type.to_css(dest)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Combines the `list-style` longhands into the shorthand when all of them are declared.
+#[derive(Default)]
+pub struct ListStyleHandler {
+  list_style_type: Option<ListStyleType>,
+  image: Option<Image>,
+  position: Option<ListStylePosition>
+}
+
+impl PropertyHandler for ListStyleHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    use Property::*;
+
+    match property {
+      ListStyleType(val) => self.list_style_type = Some(val.clone()),
+      ListStyleImage(val) => self.image = Some(val.clone()),
+      ListStylePosition(val) => self.position = Some(val.clone()),
+      ListStyle(val) => {
+        self.list_style_type = Some(val.list_style_type.clone());
+        self.image = Some(val.image.clone());
+        self.position = Some(val.position.clone());
+      }
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    let list_style_type = std::mem::take(&mut self.list_style_type);
+    let image = std::mem::take(&mut self.image);
+    let position = std::mem::take(&mut self.position);
+
+    if let (Some(list_style_type), Some(image), Some(position)) = (&list_style_type, &image, &position) {
+      return vec![Property::ListStyle(ListStyle {
+        list_style_type: list_style_type.clone(),
+        image: image.clone(),
+        position: position.clone()
+      })]
+    }
+
+    let mut decls = vec![];
+    if let Some(list_style_type) = list_style_type {
+      decls.push(Property::ListStyleType(list_style_type));
+    }
+
+    if let Some(image) = image {
+      decls.push(Property::ListStyleImage(image));
+    }
+
+    if let Some(position) = position {
+      decls.push(Property::ListStylePosition(position));
+    }
+
+    decls
+  }
+}
+
+/// Renames references to `@counter-style` rules in `list-style-type`, `list-style`, and the
+/// `counter()` and `counters()` functions of `content`, using a map from the current names
+/// to the new names.
+pub(crate) fn rename_counter_styles(property: &mut Property, names: &HashMap<String, String>) {
+  let rename_items = |items: &mut Vec<ContentItem>| {
+    for item in items.iter_mut() {
+      match item {
+        ContentItem::Counter(_, Some(style)) | ContentItem::Counters(_, _, Some(style)) => style.rename(names),
+        _ => {}
+      }
+    }
+  };
+
+  match property {
+    Property::ListStyleType(ListStyleType::CounterStyle(style)) => style.rename(names),
+    Property::ListStyle(ListStyle { list_style_type: ListStyleType::CounterStyle(style), .. }) => style.rename(names),
+    Property::Content(Content::List(items, alt)) => {
+      rename_items(items);
+      rename_items(alt);
+    }
+    _ => {}
+  }
+}
@@ -0,0 +1,163 @@
+use super::{Property, VendorPrefix};
+use super::prefixes::Browsers;
+use super::{text, position, ui, table};
+use crate::compat;
+
+/// Compiles logical properties to physical properties for targets that don't support them.
+/// https://drafts.csswg.org/css-logical/
+///
+/// Properties in the block axis map directly to physical properties, assuming a horizontal
+/// writing mode. Properties in the inline axis depend on the direction, so they are compiled
+/// to separate properties for left-to-right and right-to-left content, which are output in
+/// rules that select elements with each direction.
+#[derive(Default)]
+pub struct LogicalPropertiesHandler {
+  targets: Option<Browsers>,
+  ltr: Vec<Property>,
+  rtl: Vec<Property>
+}
+
+impl LogicalPropertiesHandler {
+  pub fn new(targets: Option<Browsers>) -> LogicalPropertiesHandler {
+    LogicalPropertiesHandler {
+      targets,
+      ..LogicalPropertiesHandler::default()
+    }
+  }
+
+  /// Returns the physical properties to handle in place of the given property, or `None`
+  /// if it doesn't need to be compiled. If `directional` is true, properties that depend on
+  /// the direction are compiled to properties for each direction, which are taken with
+  /// `take_directional`, and an empty list is returned. Otherwise, they are left as is.
+  pub fn compile(&mut self, property: &Property, directional: bool) -> Option<Vec<Property>> {
+    use Property::*;
+
+    let targets = self.targets?;
+    let is_compatible = match property {
+      TextAlign(_) => compat::Feature::LogicalTextAlign.is_compatible(targets),
+      Float(_) | Clear(_) => compat::Feature::LogicalFloat.is_compatible(targets),
+      Resize(_) => compat::Feature::LogicalResize.is_compatible(targets),
+      // No browsers support the logical caption sides yet.
+      CaptionSide(_) => false,
+      _ => compat::Feature::LogicalProperties.is_compatible(targets)
+    };
+    if is_compatible {
+      return None
+    }
+
+    match property {
+      MarginBlockStart(val) => Some(vec![MarginTop(val.clone())]),
+      MarginBlockEnd(val) => Some(vec![MarginBottom(val.clone())]),
+      MarginBlock(val) => Some(vec![MarginTop(val.0.clone()), MarginBottom(val.1.clone())]),
+      MarginInlineStart(val) => self.inline_side(val, directional, MarginLeft, MarginRight),
+      MarginInlineEnd(val) => self.inline_side(val, directional, MarginRight, MarginLeft),
+      MarginInline(val) => self.inline_sides(&val.0, &val.1, directional, MarginLeft, MarginRight),
+
+      PaddingBlockStart(val) => Some(vec![PaddingTop(val.clone())]),
+      PaddingBlockEnd(val) => Some(vec![PaddingBottom(val.clone())]),
+      PaddingBlock(val) => Some(vec![PaddingTop(val.0.clone()), PaddingBottom(val.1.clone())]),
+      PaddingInlineStart(val) => self.inline_side(val, directional, PaddingLeft, PaddingRight),
+      PaddingInlineEnd(val) => self.inline_side(val, directional, PaddingRight, PaddingLeft),
+      PaddingInline(val) => self.inline_sides(&val.0, &val.1, directional, PaddingLeft, PaddingRight),
+
+      InsetBlockStart(val) => Some(vec![Top(val.clone())]),
+      InsetBlockEnd(val) => Some(vec![Bottom(val.clone())]),
+      InsetBlock(val) => Some(vec![Top(val.0.clone()), Bottom(val.1.clone())]),
+      InsetInlineStart(val) => self.inline_side(val, directional, Left, Right),
+      InsetInlineEnd(val) => self.inline_side(val, directional, Right, Left),
+      InsetInline(val) => self.inline_sides(&val.0, &val.1, directional, Left, Right),
+
+      BorderBlockStartColor(val) => Some(vec![BorderTopColor(val.clone())]),
+      BorderBlockEndColor(val) => Some(vec![BorderBottomColor(val.clone())]),
+      BorderBlockColor(val) => Some(vec![BorderTopColor(val.clone()), BorderBottomColor(val.clone())]),
+      BorderInlineStartColor(val) => self.inline_side(val, directional, BorderLeftColor, BorderRightColor),
+      BorderInlineEndColor(val) => self.inline_side(val, directional, BorderRightColor, BorderLeftColor),
+      BorderInlineColor(val) => Some(vec![BorderLeftColor(val.clone()), BorderRightColor(val.clone())]),
+
+      BorderBlockStartStyle(val) => Some(vec![BorderTopStyle(val.clone())]),
+      BorderBlockEndStyle(val) => Some(vec![BorderBottomStyle(val.clone())]),
+      BorderBlockStyle(val) => Some(vec![BorderTopStyle(val.clone()), BorderBottomStyle(val.clone())]),
+      BorderInlineStartStyle(val) => self.inline_side(val, directional, BorderLeftStyle, BorderRightStyle),
+      BorderInlineEndStyle(val) => self.inline_side(val, directional, BorderRightStyle, BorderLeftStyle),
+      BorderInlineStyle(val) => Some(vec![BorderLeftStyle(val.clone()), BorderRightStyle(val.clone())]),
+
+      BorderBlockStartWidth(val) => Some(vec![BorderTopWidth(val.clone())]),
+      BorderBlockEndWidth(val) => Some(vec![BorderBottomWidth(val.clone())]),
+      BorderBlockWidth(val) => Some(vec![BorderTopWidth(val.clone()), BorderBottomWidth(val.clone())]),
+      BorderInlineStartWidth(val) => self.inline_side(val, directional, BorderLeftWidth, BorderRightWidth),
+      BorderInlineEndWidth(val) => self.inline_side(val, directional, BorderRightWidth, BorderLeftWidth),
+      BorderInlineWidth(val) => Some(vec![BorderLeftWidth(val.clone()), BorderRightWidth(val.clone())]),
+
+      BorderBlockStart(val) => Some(vec![BorderTop(val.clone())]),
+      BorderBlockEnd(val) => Some(vec![BorderBottom(val.clone())]),
+      BorderBlock(val) => Some(vec![BorderTop(val.clone()), BorderBottom(val.clone())]),
+      BorderInlineStart(val) => self.inline_side(val, directional, BorderLeft, BorderRight),
+      BorderInlineEnd(val) => self.inline_side(val, directional, BorderRight, BorderLeft),
+      BorderInline(val) => Some(vec![BorderLeft(val.clone()), BorderRight(val.clone())]),
+
+      BorderStartStartRadius(val) => self.inline_side(val, directional,
+        |val| BorderTopLeftRadius(val, VendorPrefix::None),
+        |val| BorderTopRightRadius(val, VendorPrefix::None)
+      ),
+      BorderStartEndRadius(val) => self.inline_side(val, directional,
+        |val| BorderTopRightRadius(val, VendorPrefix::None),
+        |val| BorderTopLeftRadius(val, VendorPrefix::None)
+      ),
+      BorderEndStartRadius(val) => self.inline_side(val, directional,
+        |val| BorderBottomLeftRadius(val, VendorPrefix::None),
+        |val| BorderBottomRightRadius(val, VendorPrefix::None)
+      ),
+      BorderEndEndRadius(val) => self.inline_side(val, directional,
+        |val| BorderBottomRightRadius(val, VendorPrefix::None),
+        |val| BorderBottomLeftRadius(val, VendorPrefix::None)
+      ),
+
+      TextAlign(text::TextAlign::Start) => self.inline_side(&text::TextAlign::Left, directional, TextAlign, |_| TextAlign(text::TextAlign::Right)),
+      TextAlign(text::TextAlign::End) => self.inline_side(&text::TextAlign::Right, directional, TextAlign, |_| TextAlign(text::TextAlign::Left)),
+
+      Float(position::Float::InlineStart) => self.inline_side(&position::Float::Left, directional, Float, |_| Float(position::Float::Right)),
+      Float(position::Float::InlineEnd) => self.inline_side(&position::Float::Right, directional, Float, |_| Float(position::Float::Left)),
+      Clear(position::Clear::InlineStart) => self.inline_side(&position::Clear::Left, directional, Clear, |_| Clear(position::Clear::Right)),
+      Clear(position::Clear::InlineEnd) => self.inline_side(&position::Clear::Right, directional, Clear, |_| Clear(position::Clear::Left)),
+
+      Resize(ui::Resize::Block) => Some(vec![Resize(ui::Resize::Vertical)]),
+      Resize(ui::Resize::Inline) => Some(vec![Resize(ui::Resize::Horizontal)]),
+      CaptionSide(table::CaptionSide::BlockStart) => Some(vec![CaptionSide(table::CaptionSide::Top)]),
+      CaptionSide(table::CaptionSide::BlockEnd) => Some(vec![CaptionSide(table::CaptionSide::Bottom)]),
+      _ => None
+    }
+  }
+
+  /// Takes the properties compiled for left-to-right and right-to-left content.
+  pub fn take_directional(&mut self) -> (Vec<Property>, Vec<Property>) {
+    (std::mem::take(&mut self.ltr), std::mem::take(&mut self.rtl))
+  }
+
+  /// Compiles a property for one side of the inline axis, given the physical property for
+  /// that side in left-to-right content, and in right-to-left content.
+  fn inline_side<T: Clone>(&mut self, val: &T, directional: bool, ltr: fn(T) -> Property, rtl: fn(T) -> Property) -> Option<Vec<Property>> {
+    if !directional {
+      return None
+    }
+
+    self.ltr.push(ltr(val.clone()));
+    self.rtl.push(rtl(val.clone()));
+    Some(vec![])
+  }
+
+  /// Compiles a shorthand for both sides of the inline axis. If both sides are equal,
+  /// the result doesn't depend on the direction.
+  fn inline_sides<T: Clone + PartialEq>(&mut self, start: &T, end: &T, directional: bool, left: fn(T) -> Property, right: fn(T) -> Property) -> Option<Vec<Property>> {
+    if start == end {
+      return Some(vec![left(start.clone()), right(end.clone())])
+    }
+
+    if !directional {
+      return None
+    }
+
+    self.ltr.extend(vec![left(start.clone()), right(end.clone())]);
+    self.rtl.extend(vec![right(start.clone()), left(end.clone())]);
+    Some(vec![])
+  }
+}
@@ -4,7 +4,9 @@ use crate::values::{
   rect::Rect
 };
 use crate::properties::Property;
+use crate::properties::prefixes::Browsers;
 use crate::traits::PropertyHandler;
+use crate::compat;
 
 #[derive(Debug, PartialEq)]
 enum SideCategory {
@@ -18,10 +20,14 @@ impl Default for SideCategory {
   }
 }
 
+/// Defines a handler for a shorthand property with physical and logical sides. If a compat
+/// feature is given, the shorthands and logical properties are compiled to physical
+/// properties when the targets don't support it.
 macro_rules! side_handler {
-  ($name: ident, $top: ident, $bottom: ident, $left: ident, $right: ident, $block_start: ident, $block_end: ident, $inline_start: ident, $inline_end: ident, $shorthand: ident, $block_shorthand: ident, $inline_shorthand: ident) => {
+  ($name: ident, $top: ident, $bottom: ident, $left: ident, $right: ident, $block_start: ident, $block_end: ident, $inline_start: ident, $inline_end: ident, $shorthand: ident, $block_shorthand: ident, $inline_shorthand: ident $(, $feature: ident)?) => {
     #[derive(Debug, Default)]
     pub struct $name {
+      targets: Option<Browsers>,
       top: Option<LengthPercentageOrAuto>,
       bottom: Option<LengthPercentageOrAuto>,
       left: Option<LengthPercentageOrAuto>,
@@ -95,15 +101,38 @@ macro_rules! side_handler {
     }
 
     impl $name {
+      pub fn new(targets: Option<Browsers>) -> $name {
+        $name {
+          targets,
+          ..$name::default()
+        }
+      }
+
       fn flush(&mut self) {
         use Property::*;
 
+        let logical_supported = match self.targets {
+          $(Some(targets) => compat::Feature::$feature.is_compatible(targets),)?
+          _ => true
+        };
+
+        if !logical_supported {
+          // Block sides map to physical sides in horizontal writing modes. Inline sides
+          // depend on the direction, so they are left as is.
+          if let Some(val) = std::mem::take(&mut self.block_start) {
+            self.top = Some(val);
+          }
+          if let Some(val) = std::mem::take(&mut self.block_end) {
+            self.bottom = Some(val);
+          }
+        }
+
         let top = std::mem::take(&mut self.top);
         let bottom = std::mem::take(&mut self.bottom);
         let left = std::mem::take(&mut self.left);
         let right = std::mem::take(&mut self.right);
 
-        if top.is_some() && bottom.is_some() && left.is_some() && right.is_some() {
+        if logical_supported && top.is_some() && bottom.is_some() && left.is_some() && right.is_some() {
           let rect = Rect::new(top.unwrap(), right.unwrap(), bottom.unwrap(), left.unwrap());
           self.decls.push($shorthand(rect));
         } else {
@@ -212,3 +241,19 @@ side_handler!(
   ScrollPaddingBlock,
   ScrollPaddingInline
 );
+
+side_handler!(
+  InsetHandler,
+  Top,
+  Bottom,
+  Left,
+  Right,
+  InsetBlockStart,
+  InsetBlockEnd,
+  InsetInlineStart,
+  InsetInlineEnd,
+  Inset,
+  InsetBlock,
+  InsetInline,
+  LogicalInset
+);
@@ -0,0 +1,118 @@
+use cssparser::*;
+use crate::macros::enum_property;
+use crate::traits::{Parse, ToCss, PropertyHandler, FromStandard};
+use super::{Property, VendorPrefix};
+use super::prefixes::{Browsers, Feature};
+use crate::printer::Printer;
+use smallvec::SmallVec;
+
+// https://www.w3.org/TR/css-masking-1/#the-mask-composite
+enum_property!(MaskComposite,
+  Add,
+  Subtract,
+  Intersect,
+  Exclude
+);
+
+// https://developer.mozilla.org/en-US/docs/Web/CSS/-webkit-mask-composite
+enum_property!(WebKitMaskComposite,
+  ("clear", Clear),
+  ("copy", Copy),
+  ("source-over", SourceOver),
+  ("source-in", SourceIn),
+  ("source-out", SourceOut),
+  ("source-atop", SourceAtop),
+  ("destination-over", DestinationOver),
+  ("destination-in", DestinationIn),
+  ("destination-out", DestinationOut),
+  ("destination-atop", DestinationAtop),
+  ("xor", Xor)
+);
+
+impl FromStandard<MaskComposite> for WebKitMaskComposite {
+  fn from_standard(composite: &MaskComposite) -> Option<WebKitMaskComposite> {
+    match composite {
+      MaskComposite::Add => Some(WebKitMaskComposite::SourceOver),
+      MaskComposite::Subtract => Some(WebKitMaskComposite::SourceOut),
+      MaskComposite::Intersect => Some(WebKitMaskComposite::SourceIn),
+      MaskComposite::Exclude => Some(WebKitMaskComposite::Xor)
+    }
+  }
+}
+
+impl FromStandard<WebKitMaskComposite> for MaskComposite {
+  fn from_standard(composite: &WebKitMaskComposite) -> Option<MaskComposite> {
+    match composite {
+      WebKitMaskComposite::SourceOver => Some(MaskComposite::Add),
+      WebKitMaskComposite::SourceOut => Some(MaskComposite::Subtract),
+      WebKitMaskComposite::SourceIn => Some(MaskComposite::Intersect),
+      WebKitMaskComposite::Xor => Some(MaskComposite::Exclude),
+      _ => None
+    }
+  }
+}
+
+/// The standard `mask-composite` property and the legacy `-webkit-mask-composite` property
+/// use different keywords, so the prefixed property cannot simply be generated with the same
+/// value. When targets are given, the missing syntax is translated from the other one where
+/// possible, and the prefixed property is removed if none of the targets need it.
+#[derive(Default)]
+pub struct MaskHandler {
+  targets: Option<Browsers>,
+  composite: Option<SmallVec<[MaskComposite; 1]>>,
+  webkit_composite: Option<SmallVec<[WebKitMaskComposite; 1]>>
+}
+
+impl MaskHandler {
+  pub fn new(targets: Option<Browsers>) -> MaskHandler {
+    MaskHandler {
+      targets,
+      ..MaskHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for MaskHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    match property {
+      Property::MaskComposite(val) => self.composite = Some(val.clone()),
+      Property::WebKitMaskComposite(val) => self.webkit_composite = Some(val.clone()),
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    let mut composite = std::mem::take(&mut self.composite);
+    let mut webkit_composite = std::mem::take(&mut self.webkit_composite);
+
+    if let Some(targets) = self.targets {
+      if composite.is_none() {
+        if let Some(webkit_composite) = &webkit_composite {
+          composite = webkit_composite.iter().map(MaskComposite::from_standard).collect();
+        }
+      }
+
+      // If there is no standard value, the prefixed property is kept as written.
+      if let Some(composite) = &composite {
+        if !Feature::MaskComposite.prefixes_for(targets).contains(VendorPrefix::WebKit) {
+          webkit_composite = None;
+        } else if webkit_composite.is_none() {
+          webkit_composite = composite.iter().map(WebKitMaskComposite::from_standard).collect();
+        }
+      }
+    }
+
+    let mut decls = vec![];
+    if let Some(webkit_composite) = webkit_composite {
+      decls.push(Property::WebKitMaskComposite(webkit_composite));
+    }
+
+    if let Some(composite) = composite {
+      decls.push(Property::MaskComposite(composite));
+    }
+
+    decls
+  }
+}
@@ -12,6 +12,22 @@ pub mod border_radius;
 pub mod transition;
 pub mod animation;
 pub mod transform;
+pub mod masking;
+pub mod position;
+pub mod text;
+pub mod overflow;
+pub mod list;
+pub mod content;
+pub mod will_change;
+pub mod ui;
+pub mod scroll_snap;
+pub mod columns;
+pub mod grid;
+pub mod inline;
+pub mod table;
+pub mod motion;
+pub mod css_modules;
+pub mod logical;
 pub mod prefixes;
 pub mod prefix_handler;
 
@@ -29,12 +45,29 @@ use border_radius::*;
 use transition::*;
 use animation::*;
 use transform::*;
+use masking::*;
+use text::*;
+use overflow::*;
+use list::*;
+use content::*;
+use will_change::*;
+use ui::*;
+use scroll_snap::*;
+use columns::*;
+use grid::*;
+use inline::*;
+use table::*;
+use motion::*;
+use css_modules::*;
 use crate::values::{image::*, length::*, position::*, alpha::*, size::*, rect::*, color::*, time::Time, ident::CustomIdent, easing::EasingFunction};
 use crate::traits::{Parse, ToCss};
 use crate::printer::Printer;
+use crate::warnings::{self, WarningKind};
+use crate::limits;
 use smallvec::{SmallVec, smallvec};
 use bitflags::bitflags;
 use std::fmt::Write;
+use crate::error::ParserError;
 
 bitflags! {
   pub struct VendorPrefix: u8 {
@@ -85,34 +118,148 @@ macro_rules! define_properties {
       $(
         $property($type, $($vp)?),
       )+
+      Unparsed(UnparsedProperty),
       Custom(CustomProperty),
     }
 
     impl Property {
-      pub fn parse<'i, 't>(name: CowRcStr<'i>, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+      pub fn parse<'i, 't>(name: CowRcStr<'i>, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
         let state = input.state();
-        match name.as_ref() {
+        let warning_count = warnings::len();
+        let exceeded_count = limits::exceeded_count();
+        let known = match name.as_ref() {
           $(
             $name => {
-              if let Ok(c) = <$type>::parse(input) {
+              if let Ok(c) = input.parse_entirely(<$type>::parse) {
                 return Ok(Property::$property(c, $(<$vp>::None)?))
               }
+              true
             }
           )+
           $(
             $(
               concat!("-", $prefix, "-", $name) => {
-                if let Ok(c) = <$type>::parse(input) {
+                if let Ok(c) = input.parse_entirely(<$type>::parse) {
                   return Ok(Property::$property(c, VendorPrefix::from_str($prefix)))
                 }
+                true
               }
             )*
           )?
-          _ => {}
+          _ => false
+        };
+
+        // Values that exceeded a limit, e.g. calc() nested too deeply, are dropped
+        // rather than kept as an unparsed fallback.
+        if limits::exceeded_count() != exceeded_count {
+          return Err(input.new_custom_error(ParserError::InvalidPropertyValue(name)))
         }
 
+        // Values containing environment variables cannot be parsed ahead of time,
+        // but are kept as tokens rather than a string so they can still be transformed.
         input.reset(&state);
-        return Ok(Property::Custom(CustomProperty::parse(name, input)?))
+        if !name.starts_with("--") {
+          if let Ok(unparsed) = UnparsedProperty::parse(name.clone(), input) {
+            if unparsed.value.has_env() {
+              return Ok(Property::Unparsed(unparsed))
+            }
+          }
+          input.reset(&state);
+        }
+
+        input.skip_whitespace();
+        let location = input.current_source_location();
+        let custom = CustomProperty::parse(name.clone(), input)?;
+        // Values containing var() or CSS-wide keywords can't be checked until computed time.
+        // Skip values that a more specific warning was already reported for.
+        if known && warnings::is_validating() && !is_unresolved_value(&custom.value) {
+          if warnings::len() == warning_count {
+            warnings::warn(location, WarningKind::InvalidValue);
+          }
+          if warnings::is_recovering() {
+            return Err(location.new_custom_error(ParserError::InvalidPropertyValue(name)))
+          }
+        }
+        return Ok(Property::Custom(custom))
+      }
+
+      /// Returns whether the given property name, including any vendor prefix, is a known property.
+      pub fn is_known(name: &str) -> bool {
+        match name {
+          $(
+            $name => true,
+            $(
+              concat!("-", $prefix, "-", $name) => true,
+            )*
+          )+
+          _ => false
+        }
+      }
+
+      /// Returns the name of the property, without any vendor prefix.
+      pub fn name(&self) -> &str {
+        use Property::*;
+
+        match self {
+          $(
+            $property(..) => $name,
+          )+
+          Unparsed(unparsed) => &unparsed.name,
+          Custom(custom) => &custom.name
+        }
+      }
+
+      /// Returns the vendor prefixes the property is declared with. Unparsed and custom
+      /// properties include the prefix in their name instead.
+      pub fn vendor_prefix(&self) -> VendorPrefix {
+        use Property::*;
+
+        match self {
+          $(
+            $property(_, $($vp)?) => {
+              macro_rules! prefix {
+                ($v: ident) => { *$v };
+                () => { VendorPrefix::None };
+              }
+
+              prefix!($($vp)?)
+            }
+          )+
+          _ => VendorPrefix::None
+        }
+      }
+
+      /// Returns the property as it should be declared within a rule that only applies to browsers
+      /// of the given vendor, e.g. `@-webkit-keyframes`. Unprefixed properties use the vendor's prefix
+      /// if there is one, and `None` is returned if the property only applies to other vendors.
+      pub fn for_vendor(&self, prefix: VendorPrefix) -> Option<Property> {
+        use Property::*;
+
+        match self {
+          $(
+            $property(val, $($vp)?) => {
+              macro_rules! with_prefix {
+                ($v: ident) => {{
+                  let supported = VendorPrefix::empty() $(| VendorPrefix::from_str($prefix))*;
+                  let vp = if supported.contains(prefix) && ($v.contains(prefix) || $v.contains(VendorPrefix::None)) {
+                    prefix
+                  } else if $v.contains(VendorPrefix::None) {
+                    VendorPrefix::None
+                  } else {
+                    return None
+                  };
+                  Some($property(val.clone(), vp))
+                }};
+                () => {
+                  Some($property(val.clone()))
+                };
+              }
+
+              with_prefix!($($vp)?)
+            }
+          )+
+          _ => Some(self.clone())
+        }
       }
 
       pub fn to_css<W>(&self, dest: &mut Printer<W>, important: bool) -> std::fmt::Result where W: std::fmt::Write {
@@ -169,6 +316,15 @@ macro_rules! define_properties {
               write!($($vp,)? VendorPrefix::None);
             }
           )+
+          Unparsed(unparsed) => {
+            dest.write_str(unparsed.name.as_ref())?;
+            dest.delim(':', false)?;
+            unparsed.value.to_css(dest)?;
+            if important {
+              dest.whitespace()?;
+              dest.write_str("!important")?;
+            }
+          }
           Custom(custom) => {
             dest.write_str(custom.name.as_ref())?;
             dest.delim(':', false)?;
@@ -216,7 +372,16 @@ define_properties! {
   "max-block-size": MaxBlockSize(MinMaxSize),
   "max-inline-size": MaxInlineSize(MinMaxSize),
   "box-sizing": BoxSizing(BoxSizing, VendorPrefix) / "webkit" / "moz",
+  "aspect-ratio": AspectRatio(AspectRatio),
+
+  // https://www.w3.org/TR/css-overflow-3/
+  "overflow": Overflow(Overflow),
+  "overflow-x": OverflowX(OverflowKeyword),
+  "overflow-y": OverflowY(OverflowKeyword),
+  "text-overflow": TextOverflow(TextOverflow),
 
+  // https://www.w3.org/TR/css-position-3/
+  "position": Position(position::Position),
   "top": Top(LengthPercentageOrAuto),
   "bottom": Bottom(LengthPercentageOrAuto),
   "left": Left(LengthPercentageOrAuto),
@@ -225,6 +390,13 @@ define_properties! {
   "inset-block-end": InsetBlockEnd(LengthPercentageOrAuto),
   "inset-inline-start": InsetInlineStart(LengthPercentageOrAuto),
   "inset-inline-end": InsetInlineEnd(LengthPercentageOrAuto),
+  "inset-block": InsetBlock(Size2D<LengthPercentageOrAuto>),
+  "inset-inline": InsetInline(Size2D<LengthPercentageOrAuto>),
+  "inset": Inset(Rect<LengthPercentageOrAuto>),
+
+  // https://www.w3.org/TR/CSS2/visuren.html#floats
+  "float": Float(position::Float),
+  "clear": Clear(position::Clear),
 
   "border-top-color": BorderTopColor(CssColor),
   "border-bottom-color": BorderBottomColor(CssColor),
@@ -253,14 +425,14 @@ define_properties! {
   "border-inline-start-width": BorderInlineStartWidth(BorderSideWidth),
   "border-inline-end-width": BorderInlineEndWidth(BorderSideWidth),
 
-  "border-top-left-radius": BorderTopLeftRadius(Size2D<LengthPercentage>, VendorPrefix) / "webkit" / "moz",
-  "border-top-right-radius": BorderTopRightRadius(Size2D<LengthPercentage>, VendorPrefix) / "webkit" / "moz",
-  "border-bottom-left-radius": BorderBottomLeftRadius(Size2D<LengthPercentage>, VendorPrefix) / "webkit" / "moz",
-  "border-bottom-right-radius": BorderBottomRightRadius(Size2D<LengthPercentage>, VendorPrefix) / "webkit" / "moz",
-  "border-start-start-radius": BorderStartStartRadius(Size2D<LengthPercentage>),
-  "border-start-end-radius": BorderStartEndRadius(Size2D<LengthPercentage>),
-  "border-end-start-radius": BorderEndStartRadius(Size2D<LengthPercentage>),
-  "border-end-end-radius": BorderEndEndRadius(Size2D<LengthPercentage>),
+  "border-top-left-radius": BorderTopLeftRadius(BorderCornerRadius, VendorPrefix) / "webkit" / "moz",
+  "border-top-right-radius": BorderTopRightRadius(BorderCornerRadius, VendorPrefix) / "webkit" / "moz",
+  "border-bottom-left-radius": BorderBottomLeftRadius(BorderCornerRadius, VendorPrefix) / "webkit" / "moz",
+  "border-bottom-right-radius": BorderBottomRightRadius(BorderCornerRadius, VendorPrefix) / "webkit" / "moz",
+  "border-start-start-radius": BorderStartStartRadius(BorderCornerRadius),
+  "border-start-end-radius": BorderStartEndRadius(BorderCornerRadius),
+  "border-end-start-radius": BorderEndStartRadius(BorderCornerRadius),
+  "border-end-end-radius": BorderEndEndRadius(BorderCornerRadius),
   "border-radius": BorderRadius(BorderRadius, VendorPrefix) / "webkit" / "moz",
 
   "border-image-source": BorderImageSource(Image),
@@ -320,8 +492,11 @@ define_properties! {
   "justify-items": JustifyItems(JustifyItems),
   "place-items": PlaceItems(PlaceItems),
   "row-gap": RowGap(GapValue),
-  "column-gap": ColumnGap(GapValue),
+  "column-gap": ColumnGap(GapValue, VendorPrefix) / "webkit" / "moz",
   "gap": Gap(Gap),
+  "grid-row-gap": GridRowGap(GapValue),
+  "grid-column-gap": GridColumnGap(GapValue),
+  "grid-gap": GridGap(Gap),
 
   // Old flex (2009): https://www.w3.org/TR/2009/WD-css3-flexbox-20090723/
   "box-orient": BoxOrient(BoxOrient, VendorPrefix) / "webkit" / "moz",
@@ -393,8 +568,60 @@ define_properties! {
   "scroll-padding-inline": ScrollPaddingInline(Size2D<LengthPercentageOrAuto>),
   "scroll-padding": ScrollPadding(Rect<LengthPercentageOrAuto>),
 
-  // shorthands: columns, list-style
-  // grid, inset
+  // https://www.w3.org/TR/css-scroll-snap-1/
+  "scroll-snap-type": ScrollSnapType(ScrollSnapType),
+  // The legacy syntax in Edge and IE uses different values, so it is a separate property.
+  "-ms-scroll-snap-type": MsScrollSnapType(MsScrollSnapType),
+  "scroll-snap-align": ScrollSnapAlign(ScrollSnapAlign),
+  "scroll-snap-stop": ScrollSnapStop(ScrollSnapStop),
+  "scroll-behavior": ScrollBehavior(ScrollBehavior),
+
+  // https://www.w3.org/TR/css-lists-3/
+  "list-style-type": ListStyleType(ListStyleType),
+  "list-style-image": ListStyleImage(Image),
+  "list-style-position": ListStylePosition(ListStylePosition),
+  "list-style": ListStyle(ListStyle),
+
+  // https://www.w3.org/TR/css-content-3/
+  "content": Content(Content),
+
+  // shorthands: columns
+
+  // https://www.w3.org/TR/css-grid-1/
+  "grid-template-columns": GridTemplateColumns(TrackSizing),
+  "grid-template-rows": GridTemplateRows(TrackSizing),
+  "grid-auto-flow": GridAutoFlow(GridAutoFlow),
+  "grid-row-start": GridRowStart(GridLine),
+  "grid-row-end": GridRowEnd(GridLine),
+  "grid-column-start": GridColumnStart(GridLine),
+  "grid-column-end": GridColumnEnd(GridLine),
+  "grid-row": GridRow(GridPlacement),
+  "grid-column": GridColumn(GridPlacement),
+  "grid-area": GridArea(GridArea),
+  // The legacy syntax in Edge and IE uses different properties, so they are separate properties.
+  "-ms-grid-columns": MsGridColumns(MsTrackList),
+  "-ms-grid-rows": MsGridRows(MsTrackList),
+  "-ms-grid-row": MsGridRow(i32),
+  "-ms-grid-row-span": MsGridRowSpan(i32),
+  "-ms-grid-column": MsGridColumn(i32),
+  "-ms-grid-column-span": MsGridColumnSpan(i32),
+
+  // https://www.w3.org/TR/css-text-3/
+  "text-align": TextAlign(TextAlign),
+
+  // https://www.w3.org/TR/css-text-decor-4/
+  "text-decoration-line": TextDecorationLine(TextDecorationLine, VendorPrefix) / "webkit" / "moz",
+  "text-decoration-style": TextDecorationStyle(TextDecorationStyle, VendorPrefix) / "webkit" / "moz",
+  "text-decoration-color": TextDecorationColor(CssColor, VendorPrefix) / "webkit" / "moz",
+  "text-decoration-thickness": TextDecorationThickness(TextDecorationThickness),
+  "text-decoration": TextDecoration(TextDecoration, VendorPrefix) / "webkit" / "moz",
+  "text-underline-offset": TextUnderlineOffset(LengthPercentageOrAuto),
+  "text-underline-position": TextUnderlinePosition(TextUnderlinePosition),
+  "text-shadow": TextShadow(SmallVec<[TextShadow; 1]>),
+  "text-emphasis-style": TextEmphasisStyle(TextEmphasisStyle, VendorPrefix) / "webkit",
+  "text-emphasis-color": TextEmphasisColor(CssColor, VendorPrefix) / "webkit",
+  "text-emphasis": TextEmphasis(TextEmphasis, VendorPrefix) / "webkit",
+  "text-emphasis-position": TextEmphasisPosition(TextEmphasisPosition, VendorPrefix) / "webkit",
 
   "font-weight": FontWeight(FontWeight),
   "font-size": FontSize(FontSize),
@@ -404,6 +631,8 @@ define_properties! {
   "font-variant-caps": FontVariantCaps(FontVariantCaps),
   "line-height": LineHeight(LineHeight),
   "font": Font(Font),
+  "font-size-adjust": FontSizeAdjust(FontSizeAdjust),
+  "font-optical-sizing": FontOpticalSizing(FontOpticalSizing),
 
   "transition-property": TransitionProperty(SmallVec<[CustomIdent; 1]>, VendorPrefix) / "webkit" / "moz" / "ms",
   "transition-duration": TransitionDuration(SmallVec<[Time; 1]>, VendorPrefix) / "webkit" / "moz" / "ms",
@@ -423,16 +652,70 @@ define_properties! {
 
   // https://drafts.csswg.org/css-transforms-2/
   "transform": Transform(TransformList, VendorPrefix) / "webkit" / "moz" / "ms" / "o",
-  "transform-origin": TransformOrigin(Position, VendorPrefix) / "webkit" / "moz" / "ms" / "o", // TODO: handle z offset syntax
+  "transform-origin": TransformOrigin(TransformOrigin, VendorPrefix) / "webkit" / "moz" / "ms" / "o",
   "transform-style": TransformStyle(TransformStyle, VendorPrefix) / "webkit" / "moz",
   "transform-box": TransformBox(TransformBox),
+  "translate": Translate(Translate),
+  "rotate": Rotate(Rotate),
+  "scale": Scale(Scale),
   "backface-visibility": BackfaceVisibility(BackfaceVisibility, VendorPrefix) / "webkit" / "moz",
   "perspective": Perspective(Perspective, VendorPrefix) / "webkit" / "moz",
-  "perspective-origin": PerspectiveOrigin(Position, VendorPrefix) / "webkit" / "moz",
+  "perspective-origin": PerspectiveOrigin(PerspectiveOrigin, VendorPrefix) / "webkit" / "moz",
+
+  // https://www.w3.org/TR/css-multicol-1/
+  "column-width": ColumnWidth(ColumnWidth, VendorPrefix) / "webkit" / "moz",
+  "column-count": ColumnCount(ColumnCount, VendorPrefix) / "webkit" / "moz",
+  "columns": Columns(Columns, VendorPrefix) / "webkit" / "moz",
+  "column-rule-width": ColumnRuleWidth(BorderSideWidth, VendorPrefix) / "webkit" / "moz",
+  "column-rule-style": ColumnRuleStyle(BorderStyle, VendorPrefix) / "webkit" / "moz",
+  "column-rule-color": ColumnRuleColor(CssColor, VendorPrefix) / "webkit" / "moz",
+  "column-rule": ColumnRule(ColumnRule, VendorPrefix) / "webkit" / "moz",
+  "column-span": ColumnSpan(ColumnSpan, VendorPrefix) / "webkit" / "moz",
+  "column-fill": ColumnFill(ColumnFill, VendorPrefix) / "webkit" / "moz",
+
+  // https://www.w3.org/TR/css-tables-3/
+  "caption-side": CaptionSide(CaptionSide),
+
+  // https://www.w3.org/TR/css-inline-3/
+  "initial-letter": InitialLetter(InitialLetter, VendorPrefix) / "webkit",
+
+  // https://www.w3.org/TR/css-ui-4/
+  "appearance": Appearance(Appearance, VendorPrefix) / "webkit" / "moz",
+  "resize": Resize(Resize),
+
+  // https://www.w3.org/TR/css-will-change-1/
+  "will-change": WillChange(WillChange),
+
+  // https://github.com/css-modules/css-modules#composition
+  "composes": Composes(Composes),
+
+  // https://www.w3.org/TR/css-masking-1/
+  "mask-composite": MaskComposite(SmallVec<[MaskComposite; 1]>),
+  // The legacy WebKit syntax uses different keywords, so it is a separate property.
+  "-webkit-mask-composite": WebKitMaskComposite(SmallVec<[WebKitMaskComposite; 1]>),
+
+  // https://drafts.fxtf.org/motion-1/
+  "offset-path": OffsetPath(OffsetPath),
+  "offset-distance": OffsetDistance(LengthPercentage),
+  "offset-rotate": OffsetRotate(OffsetRotate),
+  "offset-anchor": OffsetAnchor(OffsetAnchor),
+  "offset-position": OffsetPosition(OffsetPosition),
+  "offset": Offset(Offset),
+  // Chrome 46 to 55 implemented an earlier draft of the spec, with different property names.
+  "motion-path": MotionPath(OffsetPath),
+  "motion-offset": MotionOffset(LengthPercentage),
+  "motion-rotation": MotionRotation(OffsetRotate),
+}
+
+/// Returns whether a value can only be checked once it is computed, i.e. it contains
+/// a var() reference or is a CSS-wide keyword.
+fn is_unresolved_value(value: &str) -> bool {
+  let value = value.trim().to_ascii_lowercase();
+  value.contains("var(") || matches!(value.as_str(), "inherit" | "initial" | "unset" | "revert")
 }
 
 impl<T: smallvec::Array<Item = V>, V: Parse> Parse for SmallVec<T> {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     // Copied from cssparser `parse_comma_separated` but using SmallVec instead of Vec.
     let mut values = smallvec![];
     loop {
@@ -464,7 +747,7 @@ impl<T: smallvec::Array<Item = V>, V: ToCss> ToCss for SmallVec<T> {
 }
 
 impl<T: Parse> Parse for Vec<T> {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     input.parse_comma_separated(|input| T::parse(input))
   }
 }
@@ -0,0 +1,494 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss, PropertyHandler};
+use crate::printer::Printer;
+use crate::macros::enum_property;
+use crate::compat;
+use crate::values::{
+  angle::Angle,
+  length::LengthPercentage,
+  position::Position,
+  shape::BasicShape,
+  url::Url
+};
+use super::Property;
+use super::prefixes::Browsers;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// https://drafts.fxtf.org/motion-1/#offset-path-property
+#[derive(Debug, Clone, PartialEq)]
+pub enum OffsetPath {
+  None,
+  Ray(Ray),
+  /// An SVG path, in the syntax of the `d` attribute.
+  Path(String),
+  Shape(BasicShape),
+  Url(Url)
+}
+
+impl Parse for OffsetPath {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(OffsetPath::None)
+    }
+
+    if let Ok(url) = input.try_parse(Url::parse) {
+      return Ok(OffsetPath::Url(url))
+    }
+
+    if let Ok(ray) = input.try_parse(Ray::parse) {
+      return Ok(OffsetPath::Ray(ray))
+    }
+
+    if let Ok(path) = input.try_parse(|input| {
+      input.expect_function_matching("path")?;
+      input.parse_nested_block(|input| Ok::<_, ParseError<'i, ParserError<'i>>>(input.expect_string()?.as_ref().to_owned()))
+    }) {
+      return Ok(OffsetPath::Path(path))
+    }
+
+    Ok(OffsetPath::Shape(BasicShape::parse(input)?))
+  }
+}
+
+impl ToCss for OffsetPath {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      OffsetPath::None => dest.write_str("none"),
+      OffsetPath::Ray(ray) => ray.to_css(dest),
+      OffsetPath::Path(path) => {
+        dest.write_str("path(")?;
+        serialize_string(path, dest)?;
+        dest.write_char(')')
+      }
+      OffsetPath::Shape(shape) => shape.to_css(dest),
+      OffsetPath::Url(url) => url.to_css(dest)
+    }
+  }
+}
+
+/// https://drafts.fxtf.org/motion-1/#ray-function
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ray {
+  pub angle: Angle,
+  pub size: RaySize,
+  pub contain: bool
+}
+
+impl Parse for Ray {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    input.expect_function_matching("ray")?;
+    input.parse_nested_block(|input| {
+      let mut angle = None;
+      let mut size = None;
+      let mut contain = false;
+      loop {
+        if angle.is_none() {
+          if let Ok(value) = input.try_parse(Angle::parse) {
+            angle = Some(value);
+            continue
+          }
+        }
+
+        if size.is_none() {
+          if let Ok(value) = input.try_parse(RaySize::parse) {
+            size = Some(value);
+            continue
+          }
+        }
+
+        if !contain && input.try_parse(|input| input.expect_ident_matching("contain")).is_ok() {
+          contain = true;
+          continue
+        }
+
+        break
+      }
+
+      match angle {
+        Some(angle) => Ok(Ray {
+          angle,
+          size: size.unwrap_or(RaySize::ClosestSide),
+          contain
+        }),
+        None => Err(input.new_custom_error(ParserError::InvalidValue))
+      }
+    })
+  }
+}
+
+impl ToCss for Ray {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    dest.write_str("ray(")?;
+    self.angle.to_css(dest)?;
+    // `closest-side` is the default.
+    if self.size != RaySize::ClosestSide {
+      dest.write_char(' ')?;
+      self.size.to_css(dest)?;
+    }
+    if self.contain {
+      dest.write_str(" contain")?;
+    }
+    dest.write_char(')')
+  }
+}
+
+// https://drafts.fxtf.org/motion-1/#valdef-ray-size
+enum_property!(RaySize,
+  ("closest-side", ClosestSide),
+  ("closest-corner", ClosestCorner),
+  ("farthest-side", FarthestSide),
+  ("farthest-corner", FarthestCorner),
+  ("sides", Sides)
+);
+
+/// https://drafts.fxtf.org/motion-1/#offset-rotate-property
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffsetRotate {
+  /// Whether the element is rotated in the direction of the path, or the opposite direction.
+  pub direction: Option<OffsetRotateDirection>,
+  pub angle: Angle
+}
+
+enum_property!(OffsetRotateDirection,
+  Auto,
+  Reverse
+);
+
+impl Default for OffsetRotate {
+  fn default() -> OffsetRotate {
+    OffsetRotate {
+      direction: Some(OffsetRotateDirection::Auto),
+      angle: Angle::Deg(0.0)
+    }
+  }
+}
+
+impl Parse for OffsetRotate {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut direction = None;
+    let mut angle = None;
+    loop {
+      if direction.is_none() {
+        if let Ok(value) = input.try_parse(OffsetRotateDirection::parse) {
+          direction = Some(value);
+          continue
+        }
+      }
+
+      if angle.is_none() {
+        if let Ok(value) = input.try_parse(Angle::parse) {
+          angle = Some(value);
+          continue
+        }
+      }
+
+      break
+    }
+
+    if direction.is_none() && angle.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+
+    Ok(OffsetRotate {
+      direction,
+      angle: angle.unwrap_or(Angle::Deg(0.0))
+    })
+  }
+}
+
+impl ToCss for OffsetRotate {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match &self.direction {
+      Some(direction) => {
+        direction.to_css(dest)?;
+        if !self.angle.is_zero() {
+          dest.write_char(' ')?;
+          self.angle.to_css(dest)?;
+        }
+        Ok(())
+      }
+      None => self.angle.to_css(dest)
+    }
+  }
+}
+
+/// https://drafts.fxtf.org/motion-1/#offset-position-property
+#[derive(Debug, Clone, PartialEq)]
+pub enum OffsetPosition {
+  Normal,
+  Auto,
+  Position(Position)
+}
+
+impl Parse for OffsetPosition {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("normal")).is_ok() {
+      return Ok(OffsetPosition::Normal)
+    }
+
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(OffsetPosition::Auto)
+    }
+
+    Ok(OffsetPosition::Position(Position::parse(input)?))
+  }
+}
+
+impl ToCss for OffsetPosition {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      OffsetPosition::Normal => dest.write_str("normal"),
+      OffsetPosition::Auto => dest.write_str("auto"),
+      OffsetPosition::Position(position) => position.to_css(dest)
+    }
+  }
+}
+
+/// https://drafts.fxtf.org/motion-1/#offset-anchor-property
+#[derive(Debug, Clone, PartialEq)]
+pub enum OffsetAnchor {
+  Auto,
+  Position(Position)
+}
+
+impl Parse for OffsetAnchor {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(OffsetAnchor::Auto)
+    }
+
+    Ok(OffsetAnchor::Position(Position::parse(input)?))
+  }
+}
+
+impl ToCss for OffsetAnchor {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      OffsetAnchor::Auto => dest.write_str("auto"),
+      OffsetAnchor::Position(position) => position.to_css(dest)
+    }
+  }
+}
+
+/// https://drafts.fxtf.org/motion-1/#offset-shorthand
+#[derive(Debug, Clone, PartialEq)]
+pub struct Offset {
+  pub position: OffsetPosition,
+  pub path: OffsetPath,
+  pub distance: LengthPercentage,
+  pub rotate: OffsetRotate,
+  pub anchor: OffsetAnchor
+}
+
+impl Parse for Offset {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let position = input.try_parse(OffsetPosition::parse).ok();
+    let path = input.try_parse(OffsetPath::parse).ok();
+
+    // The distance and rotation may only be given along with a path.
+    let mut distance = None;
+    let mut rotate = None;
+    if path.is_some() {
+      loop {
+        if distance.is_none() {
+          if let Ok(value) = input.try_parse(LengthPercentage::parse) {
+            distance = Some(value);
+            continue
+          }
+        }
+
+        if rotate.is_none() {
+          if let Ok(value) = input.try_parse(OffsetRotate::parse) {
+            rotate = Some(value);
+            continue
+          }
+        }
+
+        break
+      }
+    } else if position.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+
+    let anchor = if input.try_parse(|input| input.expect_delim('/')).is_ok() {
+      OffsetAnchor::parse(input)?
+    } else {
+      OffsetAnchor::Auto
+    };
+
+    Ok(Offset {
+      position: position.unwrap_or(OffsetPosition::Normal),
+      path: path.unwrap_or(OffsetPath::None),
+      distance: distance.unwrap_or_else(LengthPercentage::zero),
+      rotate: rotate.unwrap_or_default(),
+      anchor
+    })
+  }
+}
+
+impl ToCss for Offset {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    let has_position = self.position != OffsetPosition::Normal;
+    if has_position {
+      self.position.to_css(dest)?;
+    }
+
+    // The path must be given if the distance or rotation is, or if nothing else is.
+    let has_distance = self.distance != 0.0;
+    let has_rotate = self.rotate != OffsetRotate::default();
+    if self.path != OffsetPath::None || has_distance || has_rotate || !has_position {
+      if has_position {
+        dest.write_char(' ')?;
+      }
+      self.path.to_css(dest)?;
+
+      if has_distance {
+        dest.write_char(' ')?;
+        self.distance.to_css(dest)?;
+      }
+
+      if has_rotate {
+        dest.write_char(' ')?;
+        self.rotate.to_css(dest)?;
+      }
+    }
+
+    if self.anchor != OffsetAnchor::Auto {
+      dest.delim('/', true)?;
+      self.anchor.to_css(dest)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Combines the `offset` longhands into the shorthand, and adds the `motion-path`, `motion-offset`,
+/// and `motion-rotation` properties from an earlier draft of the spec for targets that only support
+/// those, i.e. Chrome 46 to 55. Legacy properties are removed if none of the targets need them.
+#[derive(Default)]
+pub struct OffsetHandler {
+  targets: Option<Browsers>,
+  position: Option<OffsetPosition>,
+  path: Option<OffsetPath>,
+  distance: Option<LengthPercentage>,
+  rotate: Option<OffsetRotate>,
+  anchor: Option<OffsetAnchor>,
+  legacy_path: Option<OffsetPath>,
+  legacy_distance: Option<LengthPercentage>,
+  legacy_rotate: Option<OffsetRotate>
+}
+
+impl OffsetHandler {
+  pub fn new(targets: Option<Browsers>) -> OffsetHandler {
+    OffsetHandler {
+      targets,
+      ..OffsetHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for OffsetHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    use Property::*;
+
+    match property {
+      OffsetPosition(val) => self.position = Some(val.clone()),
+      OffsetPath(val) => self.path = Some(val.clone()),
+      OffsetDistance(val) => self.distance = Some(val.clone()),
+      OffsetRotate(val) => self.rotate = Some(val.clone()),
+      OffsetAnchor(val) => self.anchor = Some(val.clone()),
+      Offset(val) => {
+        self.position = Some(val.position.clone());
+        self.path = Some(val.path.clone());
+        self.distance = Some(val.distance.clone());
+        self.rotate = Some(val.rotate.clone());
+        self.anchor = Some(val.anchor.clone());
+      }
+      // Without targets, the legacy properties are passed through unchanged.
+      MotionPath(val) if self.targets.is_some() => self.legacy_path = Some(val.clone()),
+      MotionOffset(val) if self.targets.is_some() => self.legacy_distance = Some(val.clone()),
+      MotionRotation(val) if self.targets.is_some() => self.legacy_rotate = Some(val.clone()),
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    let position = std::mem::take(&mut self.position);
+    let path = std::mem::take(&mut self.path);
+    let distance = std::mem::take(&mut self.distance);
+    let rotate = std::mem::take(&mut self.rotate);
+    let anchor = std::mem::take(&mut self.anchor);
+    let legacy_path = std::mem::take(&mut self.legacy_path);
+    let legacy_distance = std::mem::take(&mut self.legacy_distance);
+    let legacy_rotate = std::mem::take(&mut self.legacy_rotate);
+
+    let mut decls = vec![];
+    if self.needs_legacy_properties() {
+      // The earlier draft only supported `path()` and `none`.
+      match path.as_ref().or_else(|| legacy_path.as_ref()) {
+        Some(path @ OffsetPath::Path(_)) | Some(path @ OffsetPath::None) => decls.push(Property::MotionPath(path.clone())),
+        _ => {}
+      }
+
+      if let Some(distance) = distance.as_ref().or_else(|| legacy_distance.as_ref()) {
+        decls.push(Property::MotionOffset(distance.clone()));
+      }
+
+      if let Some(rotate) = rotate.as_ref().or_else(|| legacy_rotate.as_ref()) {
+        decls.push(Property::MotionRotation(rotate.clone()));
+      }
+    }
+
+    if let (Some(position), Some(path), Some(distance), Some(rotate), Some(anchor)) = (&position, &path, &distance, &rotate, &anchor) {
+      decls.push(Property::Offset(Offset {
+        position: position.clone(),
+        path: path.clone(),
+        distance: distance.clone(),
+        rotate: rotate.clone(),
+        anchor: anchor.clone()
+      }));
+      return decls
+    }
+
+    if let Some(position) = position {
+      decls.push(Property::OffsetPosition(position));
+    }
+
+    if let Some(path) = path {
+      decls.push(Property::OffsetPath(path));
+    }
+
+    if let Some(distance) = distance {
+      decls.push(Property::OffsetDistance(distance));
+    }
+
+    if let Some(rotate) = rotate {
+      decls.push(Property::OffsetRotate(rotate));
+    }
+
+    if let Some(anchor) = anchor {
+      decls.push(Property::OffsetAnchor(anchor));
+    }
+
+    decls
+  }
+}
+
+impl OffsetHandler {
+  /// Returns whether any of the Chromium based targets only support the legacy properties.
+  fn needs_legacy_properties(&self) -> bool {
+    match self.targets {
+      Some(targets) => !compat::Feature::OffsetProperties.is_compatible(Browsers {
+        android: targets.android,
+        chrome: targets.chrome,
+        opera: targets.opera,
+        samsung: targets.samsung,
+        ..Browsers::default()
+      }),
+      None => false
+    }
+  }
+}
@@ -0,0 +1,167 @@
+use cssparser::*;
+use crate::macros::enum_property;
+use crate::traits::{Parse, ToCss, PropertyHandler};
+use crate::printer::Printer;
+use crate::compat;
+use super::Property;
+use super::prefixes::Browsers;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+// https://www.w3.org/TR/css-overflow-3/#overflow-properties
+enum_property!(OverflowKeyword,
+  Visible,
+  Hidden,
+  Clip,
+  Scroll,
+  Auto
+);
+
+/// https://www.w3.org/TR/css-overflow-3/#propdef-overflow
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overflow {
+  pub x: OverflowKeyword,
+  pub y: OverflowKeyword
+}
+
+impl Parse for Overflow {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let x = OverflowKeyword::parse(input)?;
+    let y = input.try_parse(OverflowKeyword::parse).unwrap_or(x);
+    Ok(Overflow { x, y })
+  }
+}
+
+impl ToCss for Overflow {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    self.x.to_css(dest)?;
+    if self.y != self.x {
+      dest.write_char(' ')?;
+      self.y.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+/// https://www.w3.org/TR/css-overflow-3/#text-overflow
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextOverflowSide {
+  Clip,
+  Ellipsis,
+  String(String)
+}
+
+impl Parse for TextOverflowSide {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(s) = input.try_parse(|input| input.expect_string_cloned()) {
+      return Ok(TextOverflowSide::String(s.as_ref().into()))
+    }
+
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    match_ignore_ascii_case! { &*ident,
+      "clip" => Ok(TextOverflowSide::Clip),
+      "ellipsis" => Ok(TextOverflowSide::Ellipsis),
+      _ => Err(location.new_unexpected_token_error(
+        cssparser::Token::Ident(ident.clone())
+      ))
+    }
+  }
+}
+
+impl ToCss for TextOverflowSide {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      TextOverflowSide::Clip => dest.write_str("clip"),
+      TextOverflowSide::Ellipsis => dest.write_str("ellipsis"),
+      TextOverflowSide::String(s) => serialize_string(&s, dest)
+    }
+  }
+}
+
+/// With one value, `text-overflow` sets the end of the line, and the start is clipped.
+/// With two values, the first sets the start and the second sets the end.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextOverflow {
+  pub start: TextOverflowSide,
+  pub end: TextOverflowSide
+}
+
+impl Parse for TextOverflow {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let first = TextOverflowSide::parse(input)?;
+    if let Ok(end) = input.try_parse(TextOverflowSide::parse) {
+      return Ok(TextOverflow { start: first, end })
+    }
+
+    Ok(TextOverflow { start: TextOverflowSide::Clip, end: first })
+  }
+}
+
+impl ToCss for TextOverflow {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    if self.start != TextOverflowSide::Clip {
+      self.start.to_css(dest)?;
+      dest.write_char(' ')?;
+    }
+    self.end.to_css(dest)
+  }
+}
+
+/// Combines `overflow-x` and `overflow-y` into the `overflow` shorthand. When the targets
+/// don't support the two value syntax, it is only used if both values are equal.
+#[derive(Default)]
+pub struct OverflowHandler {
+  targets: Option<Browsers>,
+  x: Option<OverflowKeyword>,
+  y: Option<OverflowKeyword>
+}
+
+impl OverflowHandler {
+  pub fn new(targets: Option<Browsers>) -> OverflowHandler {
+    OverflowHandler {
+      targets,
+      ..OverflowHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for OverflowHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    match property {
+      Property::OverflowX(val) => self.x = Some(*val),
+      Property::OverflowY(val) => self.y = Some(*val),
+      Property::Overflow(val) => {
+        self.x = Some(val.x);
+        self.y = Some(val.y);
+      }
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    let x = std::mem::take(&mut self.x);
+    let y = std::mem::take(&mut self.y);
+    let two_values_supported = self.targets.map_or(true, |targets| compat::Feature::OverflowShorthand.is_compatible(targets));
+
+    let mut decls = vec![];
+    match (x, y) {
+      (Some(x), Some(y)) if x == y || two_values_supported => {
+        decls.push(Property::Overflow(Overflow { x, y }));
+      }
+      (x, y) => {
+        if let Some(x) = x {
+          decls.push(Property::OverflowX(x));
+        }
+
+        if let Some(y) = y {
+          decls.push(Property::OverflowY(y));
+        }
+      }
+    }
+
+    decls
+  }
+}
@@ -0,0 +1,128 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss, PropertyHandler};
+use super::{Property, VendorPrefix};
+use super::prefixes::{Browsers, Feature};
+use super::prefix_handler::expand_prefixes;
+use crate::printer::Printer;
+use crate::macros::enum_property;
+use crate::error::ParserError;
+
+/// https://www.w3.org/TR/css-position-3/#position-property
+#[derive(Debug, Clone, PartialEq)]
+pub enum Position {
+  Static,
+  Relative,
+  Absolute,
+  Sticky(VendorPrefix),
+  Fixed
+}
+
+impl Parse for Position {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    match_ignore_ascii_case! { &*ident,
+      "static" => Ok(Position::Static),
+      "relative" => Ok(Position::Relative),
+      "absolute" => Ok(Position::Absolute),
+      "fixed" => Ok(Position::Fixed),
+      "sticky" => Ok(Position::Sticky(VendorPrefix::None)),
+      "-webkit-sticky" => Ok(Position::Sticky(VendorPrefix::WebKit)),
+      _ => Err(location.new_unexpected_token_error(
+        cssparser::Token::Ident(ident.clone())
+      ))
+    }
+  }
+}
+
+impl ToCss for Position {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      Position::Static => dest.write_str("static"),
+      Position::Relative => dest.write_str("relative"),
+      Position::Absolute => dest.write_str("absolute"),
+      Position::Fixed => dest.write_str("fixed"),
+      Position::Sticky(prefix) => {
+        prefix.to_css(dest)?;
+        dest.write_str("sticky")
+      }
+    }
+  }
+}
+
+// https://drafts.csswg.org/css-logical/#float-clear
+enum_property!(Float,
+  ("left", Left),
+  ("right", Right),
+  ("none", None),
+  ("inline-start", InlineStart),
+  ("inline-end", InlineEnd)
+);
+
+// https://drafts.csswg.org/css-logical/#float-clear
+enum_property!(Clear,
+  ("none", None),
+  ("left", Left),
+  ("right", Right),
+  ("both", Both),
+  ("inline-start", InlineStart),
+  ("inline-end", InlineEnd)
+);
+
+/// Combines `position: sticky` with its `-webkit-` prefixed value, and adds the prefixed
+/// value when the targets need it. A value before `sticky`, e.g. `position: relative`, is
+/// kept as a fallback for browsers that don't support sticky positioning.
+#[derive(Default)]
+pub struct PositionHandler {
+  targets: Option<Browsers>,
+  position: Option<Position>,
+  fallback: Option<Position>
+}
+
+impl PositionHandler {
+  pub fn new(targets: Option<Browsers>) -> PositionHandler {
+    PositionHandler {
+      targets,
+      ..PositionHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for PositionHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    if let Property::Position(position) = property {
+      match (&mut self.position, position) {
+        (Some(Position::Sticky(cur)), Position::Sticky(prefix)) => *cur |= *prefix,
+        (cur, Position::Sticky(_)) => self.fallback = std::mem::replace(cur, Some(position.clone())),
+        (cur, _) => {
+          self.fallback = None;
+          *cur = Some(position.clone());
+        }
+      }
+      true
+    } else {
+      false
+    }
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    match std::mem::take(&mut self.position) {
+      Some(Position::Sticky(prefixes)) => {
+        let prefixes = expand_prefixes(prefixes, Feature::Sticky, self.targets);
+        let mut decls = vec![];
+        if let Some(fallback) = std::mem::take(&mut self.fallback) {
+          decls.push(Property::Position(fallback));
+        }
+        // Prefixed values come first, so the standard value takes precedence where supported.
+        for prefix in &[VendorPrefix::WebKit, VendorPrefix::None] {
+          if prefixes.contains(*prefix) {
+            decls.push(Property::Position(Position::Sticky(*prefix)));
+          }
+        }
+        decls
+      }
+      Some(position) => vec![Property::Position(position)],
+      None => vec![]
+    }
+  }
+}
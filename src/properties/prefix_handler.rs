@@ -1,17 +1,126 @@
 #![allow(non_snake_case)]
 use super::prefixes::{Browsers, Feature};
 use super::{Property, VendorPrefix};
+use super::transform::*;
+use super::ui::Appearance;
+use crate::values::size::BoxSizing;
 use crate::traits::{PropertyHandler};
+use crate::macros::trace;
+
+/// The value of a property within a handler, along with the vendor prefixes it was declared with.
+/// Prefixed variants of the property with the same value are combined into a single value with
+/// multiple prefixes. When a variant with a different value is declared for a prefix that was not
+/// seen yet, the handler must flush what it has first in order to preserve the order of the declarations.
+#[derive(Debug)]
+pub struct PrefixedPropertyState<T> {
+  value: Option<(T, VendorPrefix)>
+}
+
+impl<T> Default for PrefixedPropertyState<T> {
+  fn default() -> PrefixedPropertyState<T> {
+    PrefixedPropertyState {
+      value: None
+    }
+  }
+}
+
+impl<T: Clone + PartialEq> PrefixedPropertyState<T> {
+  /// Returns whether the handler must be flushed before the given value can be set with the given prefix.
+  /// If the prefix was already declared along with others, the new value only overrides it for that
+  /// prefix, so it is removed from the current value, and the other prefixes are flushed with the old value.
+  pub fn needs_flush(&mut self, val: &T, prefix: VendorPrefix) -> bool {
+    match &mut self.value {
+      Some((cur, prefixes)) if cur != val => {
+        if !prefixes.contains(prefix) {
+          return true
+        }
+        if *prefixes == prefix {
+          return false
+        }
+        prefixes.remove(prefix);
+        true
+      }
+      _ => false
+    }
+  }
+
+  /// Updates the value, and adds the given prefix.
+  pub fn set(&mut self, val: &T, prefix: VendorPrefix) {
+    match &mut self.value {
+      Some((cur, prefixes)) => {
+        *cur = val.clone();
+        *prefixes |= prefix;
+      }
+      None => self.value = Some((val.clone(), prefix))
+    }
+  }
+
+  pub fn value(&self) -> Option<&T> {
+    self.value.as_ref().map(|(val, _)| val)
+  }
+
+  /// Returns the prefixes the property was declared with, or an empty set if it was not declared.
+  pub fn prefixes(&self) -> VendorPrefix {
+    self.value.as_ref().map_or(VendorPrefix::empty(), |(_, prefixes)| *prefixes)
+  }
+
+  /// Removes the given prefixes, e.g. after outputting them as part of a shorthand.
+  pub fn remove_prefixes(&mut self, prefixes: VendorPrefix) {
+    if let Some((_, cur)) = &mut self.value {
+      cur.remove(prefixes);
+    }
+  }
+
+  /// Called before declaring the property with the given prefix. With targets, an unprefixed
+  /// declaration overrides the values declared before it: the prefixes that none of the targets
+  /// need are removed, and the others are output again with the new value once it is expanded.
+  /// Without targets, all prefixes are kept.
+  pub fn remove_overridden(&mut self, prefix: VendorPrefix, targets: Option<Browsers>) {
+    if targets.is_none() || !prefix.contains(VendorPrefix::None) {
+      return
+    }
+
+    let removed = self.prefixes();
+    if !removed.is_empty() {
+      trace!(?removed, "removed prefixed values overridden by the unprefixed property");
+    }
+    self.value = None;
+  }
+
+  /// Takes the value and the prefixes to output it with, if any remain. If the unprefixed
+  /// property was declared, these are expanded to the prefixes of the feature needed by the targets.
+  pub fn take(&mut self, feature: Feature, targets: Option<Browsers>) -> Option<(T, VendorPrefix)> {
+    match std::mem::take(&mut self.value) {
+      Some((val, prefixes)) if !prefixes.is_empty() => Some((val, expand_prefixes(prefixes, feature, targets))),
+      _ => None
+    }
+  }
+}
+
+/// Returns the prefixes to output a property with, given the prefixes it was declared with.
+/// If the unprefixed property was declared, and there are targets, the prefixes of the
+/// feature needed by the targets are used instead.
+pub fn expand_prefixes(prefixes: VendorPrefix, feature: Feature, targets: Option<Browsers>) -> VendorPrefix {
+  if prefixes.contains(VendorPrefix::None) {
+    if let Some(targets) = targets {
+      let expanded = feature.prefixes_for(targets);
+      trace!(declared = ?prefixes, output = ?expanded, "expanded vendor prefixes for targets");
+      return expanded
+    }
+  }
+
+  prefixes
+}
 
 macro_rules! define_prefixes {
   (
-    $( $name: ident, )+
+    $( $name: ident($type: ty), )+
   ) => {
     #[derive(Default)]
     pub struct PrefixHandler {
       targets: Option<Browsers>,
       $(
-        $name: Option<Property>,
+        $name: PrefixedPropertyState<$type>,
       )+
       decls: Vec<Property>
     }
@@ -30,35 +139,11 @@ macro_rules! define_prefixes {
         match property {
           $(
             Property::$name(val, prefix) => {
-              // If two vendor prefixes for the same property have different
-              // values, we need to flush what we have immediately to preserve order.
-              if let Some(Property::$name(cur, prefixes)) = &self.$name {
-                if val != cur && !prefixes.contains(*prefix) {
-                  self.flush();
-                }
-              }
-
-              // Otherwise, update the value and add the prefix.
-              if let Some(Property::$name(val, prefixes)) = &mut self.$name {
-                *val = val.clone();
-                *prefixes |= *prefix;
-                if prefixes.contains(VendorPrefix::None) {
-                  if let Some(targets) = self.targets {
-                    *prefixes = Feature::$name.prefixes_for(targets);
-                  }
-                }
-              } else {
-                let prefixes = if prefix.contains(VendorPrefix::None) {
-                  if let Some(targets) = self.targets {
-                    Feature::$name.prefixes_for(targets)
-                  } else {
-                    *prefix
-                  }
-                } else {
-                  *prefix
-                };
-                self.$name = Some(Property::$name(val.clone(), prefixes))
+              self.$name.remove_overridden(*prefix, self.targets);
+              if self.$name.needs_flush(val, *prefix) {
+                self.flush();
               }
+              self.$name.set(val, *prefix);
             }
           )+
           _ => return false
@@ -76,9 +161,8 @@ macro_rules! define_prefixes {
     impl PrefixHandler {
       fn flush(&mut self) {
         $(
-          let $name = std::mem::take(&mut self.$name);
-          if let Some(p) = $name {
-            self.decls.push(p)
+          if let Some((val, prefixes)) = self.$name.take(Feature::$name, self.targets) {
+            self.decls.push(Property::$name(val, prefixes))
           }
         )+
       }
@@ -87,12 +171,11 @@ macro_rules! define_prefixes {
 }
 
 define_prefixes! {
-  Transform,
-  TransformOrigin,
-  TransformStyle,
-  BackfaceVisibility,
-  Perspective,
-  PerspectiveOrigin,
-  BoxShadow,
-  BoxSizing,
+  TransformOrigin(TransformOrigin),
+  TransformStyle(TransformStyle),
+  BackfaceVisibility(BackfaceVisibility),
+  Perspective(Perspective),
+  PerspectiveOrigin(PerspectiveOrigin),
+  BoxSizing(BoxSizing),
+  Appearance(Appearance),
 }
@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 use super::VendorPrefix;
 
-#[derive(Serialize, Debug, Deserialize, Clone, Copy, Default)]
+#[derive(Serialize, Debug, Deserialize, Clone, Copy, Default, Hash)]
 pub struct Browsers {
   pub android: Option<u32>,
   pub chrome: Option<u32>,
@@ -1742,10 +1742,7 @@ impl Feature {
           }
         }
         if let Some(version) = browsers.edge {
-          if version >= 786432 && version <= 1179648 {
-            prefixes |= VendorPrefix::Ms;
-          }
-          if version >= 5177344 && version <= 5439488 {
+          if version >= 786432 && version <= 5439488 {
             prefixes |= VendorPrefix::WebKit;
           }
         }
@@ -1756,7 +1753,7 @@ impl Feature {
         }
         if let Some(version) = browsers.ie {
           if version >= 720896 && version <= 720896 {
-            prefixes |= VendorPrefix::Ms;
+            prefixes |= VendorPrefix::WebKit;
           }
         }
         if let Some(version) = browsers.ios_saf {
@@ -0,0 +1,178 @@
+use cssparser::*;
+use crate::macros::enum_property;
+use crate::traits::{Parse, ToCss, PropertyHandler, FromStandard};
+use super::{Property, VendorPrefix};
+use super::prefixes::{Browsers, Feature};
+use crate::printer::Printer;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+// https://www.w3.org/TR/css-scroll-snap-1/#scroll-snap-type
+enum_property!(ScrollSnapAxis,
+  X,
+  Y,
+  Block,
+  Inline,
+  Both
+);
+
+// https://www.w3.org/TR/css-scroll-snap-1/#scroll-snap-type
+enum_property!(ScrollSnapStrictness,
+  Mandatory,
+  Proximity
+);
+
+/// https://www.w3.org/TR/css-scroll-snap-1/#scroll-snap-type
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScrollSnapType {
+  None,
+  Axis(ScrollSnapAxis, ScrollSnapStrictness)
+}
+
+impl Parse for ScrollSnapType {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(ScrollSnapType::None)
+    }
+
+    let axis = ScrollSnapAxis::parse(input)?;
+    let strictness = input.try_parse(ScrollSnapStrictness::parse).unwrap_or(ScrollSnapStrictness::Proximity);
+    Ok(ScrollSnapType::Axis(axis, strictness))
+  }
+}
+
+impl ToCss for ScrollSnapType {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      ScrollSnapType::None => dest.write_str("none"),
+      ScrollSnapType::Axis(axis, strictness) => {
+        axis.to_css(dest)?;
+        // Proximity is the default.
+        if *strictness != ScrollSnapStrictness::Proximity {
+          dest.write_char(' ')?;
+          strictness.to_css(dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+// https://developer.mozilla.org/en-US/docs/Web/CSS/scroll-snap-type-x
+// The legacy syntax in Edge and IE only specifies the strictness.
+enum_property!(MsScrollSnapType,
+  None,
+  Proximity,
+  Mandatory
+);
+
+impl FromStandard<ScrollSnapType> for MsScrollSnapType {
+  fn from_standard(snap_type: &ScrollSnapType) -> Option<MsScrollSnapType> {
+    match snap_type {
+      ScrollSnapType::None => Some(MsScrollSnapType::None),
+      ScrollSnapType::Axis(_, ScrollSnapStrictness::Proximity) => Some(MsScrollSnapType::Proximity),
+      ScrollSnapType::Axis(_, ScrollSnapStrictness::Mandatory) => Some(MsScrollSnapType::Mandatory)
+    }
+  }
+}
+
+// https://www.w3.org/TR/css-scroll-snap-1/#scroll-snap-align
+enum_property!(ScrollSnapAlignKeyword,
+  None,
+  Start,
+  End,
+  Center
+);
+
+/// https://www.w3.org/TR/css-scroll-snap-1/#scroll-snap-align
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrollSnapAlign {
+  pub block: ScrollSnapAlignKeyword,
+  pub inline: ScrollSnapAlignKeyword
+}
+
+impl Parse for ScrollSnapAlign {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let block = ScrollSnapAlignKeyword::parse(input)?;
+    let inline = input.try_parse(ScrollSnapAlignKeyword::parse).unwrap_or(block);
+    Ok(ScrollSnapAlign { block, inline })
+  }
+}
+
+impl ToCss for ScrollSnapAlign {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    self.block.to_css(dest)?;
+    if self.inline != self.block {
+      dest.write_char(' ')?;
+      self.inline.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+// https://www.w3.org/TR/css-scroll-snap-1/#scroll-snap-stop
+enum_property!(ScrollSnapStop,
+  Normal,
+  Always
+);
+
+// https://www.w3.org/TR/cssom-view-1/#smooth-scrolling
+enum_property!(ScrollBehavior,
+  Auto,
+  Smooth
+);
+
+/// Adds the legacy `-ms-scroll-snap-type` property for targets that need it, translated from
+/// `scroll-snap-type`, and removes it if none of the targets do. The legacy property cannot
+/// express an axis, so it is only generated from the standard property and not the other way.
+#[derive(Default)]
+pub struct ScrollSnapHandler {
+  targets: Option<Browsers>,
+  snap_type: Option<ScrollSnapType>,
+  ms_snap_type: Option<MsScrollSnapType>
+}
+
+impl ScrollSnapHandler {
+  pub fn new(targets: Option<Browsers>) -> ScrollSnapHandler {
+    ScrollSnapHandler {
+      targets,
+      ..ScrollSnapHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for ScrollSnapHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    match property {
+      Property::ScrollSnapType(val) => self.snap_type = Some(val.clone()),
+      Property::MsScrollSnapType(val) => self.ms_snap_type = Some(val.clone()),
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    let snap_type = std::mem::take(&mut self.snap_type);
+    let mut ms_snap_type = std::mem::take(&mut self.ms_snap_type);
+
+    if let (Some(targets), Some(snap_type)) = (self.targets, &snap_type) {
+      if !Feature::ScrollSnapType.prefixes_for(targets).contains(VendorPrefix::Ms) {
+        ms_snap_type = None;
+      } else if ms_snap_type.is_none() {
+        ms_snap_type = MsScrollSnapType::from_standard(snap_type);
+      }
+    }
+
+    let mut decls = vec![];
+    if let Some(ms_snap_type) = ms_snap_type {
+      decls.push(Property::MsScrollSnapType(ms_snap_type));
+    }
+
+    if let Some(snap_type) = snap_type {
+      decls.push(Property::ScrollSnapType(snap_type));
+    }
+
+    decls
+  }
+}
@@ -0,0 +1,13 @@
+use cssparser::*;
+use crate::macros::enum_property;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+
+// https://www.w3.org/TR/css-tables-3/#propdef-caption-side
+// The block values are from https://drafts.csswg.org/css-logical/#caption-side
+enum_property!(CaptionSide,
+  ("top", Top),
+  ("bottom", Bottom),
+  ("block-start", BlockStart),
+  ("block-end", BlockEnd)
+);
@@ -0,0 +1,767 @@
+use cssparser::*;
+use crate::macros::enum_property;
+use crate::traits::{Parse, ToCss, PropertyHandler};
+use crate::printer::Printer;
+use crate::values::color::CssColor;
+use crate::values::length::{Length, LengthPercentage};
+use super::{Property, VendorPrefix};
+use super::prefixes::{Browsers, Feature};
+use super::prefix_handler::{PrefixedPropertyState, expand_prefixes};
+use bitflags::bitflags;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+// https://www.w3.org/TR/css-text-3/#text-align-property
+enum_property!(TextAlign,
+  ("start", Start),
+  ("end", End),
+  ("left", Left),
+  ("right", Right),
+  ("center", Center),
+  ("justify", Justify),
+  ("match-parent", MatchParent),
+  ("justify-all", JustifyAll)
+);
+
+bitflags! {
+  /// https://www.w3.org/TR/css-text-decor-4/#text-decoration-line-property
+  /// An empty set of lines is `none`.
+  pub struct TextDecorationLine: u8 {
+    const Underline   = 0b00000001;
+    const Overline    = 0b00000010;
+    const LineThrough = 0b00000100;
+    const Blink       = 0b00001000;
+  }
+}
+
+impl Default for TextDecorationLine {
+  fn default() -> TextDecorationLine {
+    TextDecorationLine::empty()
+  }
+}
+
+impl Parse for TextDecorationLine {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(TextDecorationLine::empty())
+    }
+
+    let mut value = TextDecorationLine::empty();
+    loop {
+      let flag = input.try_parse(|input| {
+        let location = input.current_source_location();
+        let ident = input.expect_ident()?;
+        match_ignore_ascii_case! { &*ident,
+          "underline" => Ok(TextDecorationLine::Underline),
+          "overline" => Ok(TextDecorationLine::Overline),
+          "line-through" => Ok(TextDecorationLine::LineThrough),
+          "blink" => Ok(TextDecorationLine::Blink),
+          _ => Err(location.new_unexpected_token_error::<ParserError<'i>>(
+            cssparser::Token::Ident(ident.clone())
+          ))
+        }
+      });
+
+      match flag {
+        Ok(flag) if !value.contains(flag) => value |= flag,
+        Ok(_) => return Err(input.new_custom_error(ParserError::InvalidValue)),
+        Err(_) => break
+      }
+    }
+
+    if value.is_empty() {
+      return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+
+    Ok(value)
+  }
+}
+
+impl ToCss for TextDecorationLine {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    if self.is_empty() {
+      return dest.write_str("none")
+    }
+
+    let mut first = true;
+    for (flag, name) in &[
+      (TextDecorationLine::Underline, "underline"),
+      (TextDecorationLine::Overline, "overline"),
+      (TextDecorationLine::LineThrough, "line-through"),
+      (TextDecorationLine::Blink, "blink")
+    ] {
+      if self.contains(*flag) {
+        if first {
+          first = false;
+        } else {
+          dest.write_char(' ')?;
+        }
+        dest.write_str(name)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+// https://www.w3.org/TR/css-text-decor-4/#text-decoration-style-property
+enum_property!(TextDecorationStyle,
+  Solid,
+  Double,
+  Dotted,
+  Dashed,
+  Wavy
+);
+
+impl Default for TextDecorationStyle {
+  fn default() -> TextDecorationStyle {
+    TextDecorationStyle::Solid
+  }
+}
+
+/// https://www.w3.org/TR/css-text-decor-4/#text-decoration-width-property
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextDecorationThickness {
+  Auto,
+  FromFont,
+  LengthPercentage(LengthPercentage)
+}
+
+impl Default for TextDecorationThickness {
+  fn default() -> TextDecorationThickness {
+    TextDecorationThickness::Auto
+  }
+}
+
+impl Parse for TextDecorationThickness {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(TextDecorationThickness::Auto)
+    }
+
+    if input.try_parse(|input| input.expect_ident_matching("from-font")).is_ok() {
+      return Ok(TextDecorationThickness::FromFont)
+    }
+
+    let lp = LengthPercentage::parse(input)?;
+    Ok(TextDecorationThickness::LengthPercentage(lp))
+  }
+}
+
+impl ToCss for TextDecorationThickness {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      TextDecorationThickness::Auto => dest.write_str("auto"),
+      TextDecorationThickness::FromFont => dest.write_str("from-font"),
+      TextDecorationThickness::LengthPercentage(lp) => lp.to_css(dest)
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-text-decor-4/#text-decoration-property
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextDecoration {
+  pub line: TextDecorationLine,
+  pub thickness: TextDecorationThickness,
+  pub style: TextDecorationStyle,
+  pub color: CssColor
+}
+
+impl Parse for TextDecoration {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut line = None;
+    let mut thickness = None;
+    let mut style = None;
+    let mut color = None;
+
+    loop {
+      macro_rules! prop {
+        ($key: ident, $type: ident) => {
+          if $key.is_none() {
+            if let Ok(val) = input.try_parse($type::parse) {
+              $key = Some(val);
+              continue
+            }
+          }
+        };
+      }
+
+      prop!(line, TextDecorationLine);
+      prop!(thickness, TextDecorationThickness);
+      prop!(style, TextDecorationStyle);
+      prop!(color, CssColor);
+      break
+    }
+
+    if line.is_none() && thickness.is_none() && style.is_none() && color.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+
+    Ok(TextDecoration {
+      line: line.unwrap_or_default(),
+      thickness: thickness.unwrap_or_default(),
+      style: style.unwrap_or_default(),
+      color: color.unwrap_or_else(|| CssColor::current_color())
+    })
+  }
+}
+
+impl ToCss for TextDecoration {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    self.line.to_css(dest)?;
+    if self.thickness != TextDecorationThickness::default() {
+      dest.write_char(' ')?;
+      self.thickness.to_css(dest)?;
+    }
+    if self.style != TextDecorationStyle::default() {
+      dest.write_char(' ')?;
+      self.style.to_css(dest)?;
+    }
+    if self.color != CssColor::current_color() {
+      dest.write_char(' ')?;
+      self.color.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+/// https://www.w3.org/TR/css-text-decor-4/#text-underline-position-property
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextUnderlinePosition {
+  Auto,
+  FromFont,
+  Under,
+  Left,
+  Right,
+  UnderLeft,
+  UnderRight
+}
+
+impl Parse for TextUnderlinePosition {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(TextUnderlinePosition::Auto)
+    }
+
+    if input.try_parse(|input| input.expect_ident_matching("from-font")).is_ok() {
+      return Ok(TextUnderlinePosition::FromFont)
+    }
+
+    // `under` and the side may appear in either order.
+    let mut under = false;
+    let mut side = None;
+    loop {
+      if !under && input.try_parse(|input| input.expect_ident_matching("under")).is_ok() {
+        under = true;
+        continue
+      }
+
+      if side.is_none() {
+        if input.try_parse(|input| input.expect_ident_matching("left")).is_ok() {
+          side = Some(TextUnderlinePosition::Left);
+          continue
+        }
+
+        if input.try_parse(|input| input.expect_ident_matching("right")).is_ok() {
+          side = Some(TextUnderlinePosition::Right);
+          continue
+        }
+      }
+
+      break
+    }
+
+    match (under, side) {
+      (true, None) => Ok(TextUnderlinePosition::Under),
+      (true, Some(TextUnderlinePosition::Left)) => Ok(TextUnderlinePosition::UnderLeft),
+      (true, Some(_)) => Ok(TextUnderlinePosition::UnderRight),
+      (false, Some(side)) => Ok(side),
+      (false, None) => Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+  }
+}
+
+impl ToCss for TextUnderlinePosition {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      TextUnderlinePosition::Auto => dest.write_str("auto"),
+      TextUnderlinePosition::FromFont => dest.write_str("from-font"),
+      TextUnderlinePosition::Under => dest.write_str("under"),
+      TextUnderlinePosition::Left => dest.write_str("left"),
+      TextUnderlinePosition::Right => dest.write_str("right"),
+      TextUnderlinePosition::UnderLeft => dest.write_str("under left"),
+      TextUnderlinePosition::UnderRight => dest.write_str("under right")
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-text-decor-3/#text-shadow-property
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextShadow {
+  pub color: CssColor,
+  pub x_offset: Length,
+  pub y_offset: Length,
+  pub blur: Length
+}
+
+impl Parse for TextShadow {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut color = None;
+    let mut lengths = None;
+
+    loop {
+      if lengths.is_none() {
+        let value = input.try_parse::<_, _, ParseError<ParserError>>(|input| {
+          let horizontal = Length::parse(input)?;
+          let vertical = Length::parse(input)?;
+          let blur = input.try_parse(Length::parse).unwrap_or(Length::zero());
+          Ok((horizontal, vertical, blur))
+        });
+
+        if let Ok(value) = value {
+          lengths = Some(value);
+          continue;
+        }
+      }
+
+      if color.is_none() {
+        if let Ok(value) = input.try_parse(CssColor::parse) {
+          color = Some(value);
+          continue;
+        }
+      }
+
+      break
+    }
+
+    let lengths = lengths.ok_or(input.new_error(BasicParseErrorKind::QualifiedRuleInvalid))?;
+    Ok(TextShadow {
+      color: color.unwrap_or(CssColor::current_color()),
+      x_offset: lengths.0,
+      y_offset: lengths.1,
+      blur: lengths.2
+    })
+  }
+}
+
+impl ToCss for TextShadow {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    self.x_offset.to_css(dest)?;
+    dest.write_char(' ')?;
+    self.y_offset.to_css(dest)?;
+
+    if !self.blur.is_zero() {
+      dest.write_char(' ')?;
+      self.blur.to_css(dest)?;
+    }
+
+    if self.color != CssColor::current_color() {
+      dest.write_char(' ')?;
+      self.color.to_css(dest)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Merges the `text-decoration` longhands into the shorthand, and adds the prefixed
+/// properties that the targets need. Prefixed versions of the shorthand don't support
+/// `text-decoration-thickness`, so it is omitted from them.
+#[derive(Default)]
+pub struct TextDecorationHandler {
+  targets: Option<Browsers>,
+  line: PrefixedPropertyState<TextDecorationLine>,
+  style: PrefixedPropertyState<TextDecorationStyle>,
+  color: PrefixedPropertyState<CssColor>,
+  thickness: Option<TextDecorationThickness>,
+  decls: Vec<Property>
+}
+
+impl TextDecorationHandler {
+  pub fn new(targets: Option<Browsers>) -> TextDecorationHandler {
+    TextDecorationHandler {
+      targets,
+      ..TextDecorationHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for TextDecorationHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    use Property::*;
+
+    macro_rules! property {
+      ($key: ident, $feature: ident, $val: expr, $vp: expr) => {{
+        self.$key.remove_overridden(*$vp, self.targets);
+        if self.$key.needs_flush($val, *$vp) {
+          self.flush();
+        }
+        self.$key.set($val, *$vp);
+      }};
+    }
+
+    match property {
+      TextDecorationLine(val, vp) => property!(line, TextDecorationLine, val, vp),
+      TextDecorationStyle(val, vp) => property!(style, TextDecorationStyle, val, vp),
+      TextDecorationColor(val, vp) => property!(color, TextDecorationColor, val, vp),
+      TextDecorationThickness(val) => self.thickness = Some(val.clone()),
+      TextDecoration(val, vp) => {
+        self.line.remove_overridden(*vp, self.targets);
+        self.style.remove_overridden(*vp, self.targets);
+        self.color.remove_overridden(*vp, self.targets);
+        if self.line.needs_flush(&val.line, *vp) || self.style.needs_flush(&val.style, *vp) || self.color.needs_flush(&val.color, *vp) {
+          self.flush();
+        }
+        self.line.set(&val.line, *vp);
+        self.style.set(&val.style, *vp);
+        self.color.set(&val.color, *vp);
+        if vp.contains(VendorPrefix::None) {
+          self.thickness = Some(val.thickness.clone());
+        }
+      }
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    self.flush();
+    std::mem::take(&mut self.decls)
+  }
+}
+
+impl TextDecorationHandler {
+  fn flush(&mut self) {
+    let mut intersection = self.line.prefixes() & self.style.prefixes() & self.color.prefixes();
+    // The unprefixed shorthand would reset the thickness, so it is only used if that was declared too.
+    if self.thickness.is_none() {
+      intersection.remove(VendorPrefix::None);
+    }
+
+    if !intersection.is_empty() {
+      let mut decoration = TextDecoration {
+        line: *self.line.value().unwrap(),
+        thickness: TextDecorationThickness::Auto,
+        style: *self.style.value().unwrap(),
+        color: self.color.value().unwrap().clone()
+      };
+
+      // The shorthand is output with every prefix the targets need, so the longhands
+      // declared with these prefixes must not also be output for the same values.
+      let expanded = expand_prefixes(intersection, Feature::TextDecoration, self.targets);
+      let mut prefixes = expanded;
+      if prefixes.contains(VendorPrefix::None) {
+        let thickness = std::mem::take(&mut self.thickness).unwrap();
+        if thickness != TextDecorationThickness::Auto {
+          prefixes.remove(VendorPrefix::None);
+          if !prefixes.is_empty() {
+            self.decls.push(Property::TextDecoration(decoration.clone(), prefixes));
+          }
+          decoration.thickness = thickness;
+          prefixes = VendorPrefix::None;
+        }
+      }
+
+      if !prefixes.is_empty() {
+        self.decls.push(Property::TextDecoration(decoration, prefixes));
+      }
+
+      self.line.remove_prefixes(intersection | expanded);
+      self.style.remove_prefixes(intersection | expanded);
+      self.color.remove_prefixes(intersection | expanded);
+    }
+
+    if let Some((line, prefixes)) = self.line.take(Feature::TextDecorationLine, self.targets) {
+      self.decls.push(Property::TextDecorationLine(line, prefixes));
+    }
+
+    if let Some((style, prefixes)) = self.style.take(Feature::TextDecorationStyle, self.targets) {
+      self.decls.push(Property::TextDecorationStyle(style, prefixes));
+    }
+
+    if let Some((color, prefixes)) = self.color.take(Feature::TextDecorationColor, self.targets) {
+      self.decls.push(Property::TextDecorationColor(color, prefixes));
+    }
+
+    if let Some(thickness) = std::mem::take(&mut self.thickness) {
+      self.decls.push(Property::TextDecorationThickness(thickness));
+    }
+  }
+}
+
+// https://www.w3.org/TR/css-text-decor-4/#text-emphasis-style-property
+enum_property!(TextEmphasisFillMode,
+  Filled,
+  Open
+);
+
+enum_property!(TextEmphasisShape,
+  ("dot", Dot),
+  ("circle", Circle),
+  ("double-circle", DoubleCircle),
+  ("triangle", Triangle),
+  ("sesame", Sesame)
+);
+
+/// https://www.w3.org/TR/css-text-decor-4/#text-emphasis-style-property
+/// If the shape is omitted, it depends on the writing mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TextEmphasisStyle {
+  None,
+  Keyword {
+    fill: TextEmphasisFillMode,
+    shape: Option<TextEmphasisShape>
+  },
+  String(String)
+}
+
+impl Default for TextEmphasisStyle {
+  fn default() -> TextEmphasisStyle {
+    TextEmphasisStyle::None
+  }
+}
+
+impl Parse for TextEmphasisStyle {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(TextEmphasisStyle::None)
+    }
+
+    if let Ok(s) = input.try_parse(|input| input.expect_string_cloned()) {
+      return Ok(TextEmphasisStyle::String(s.as_ref().into()))
+    }
+
+    let mut fill = None;
+    let mut shape = None;
+    loop {
+      if fill.is_none() {
+        if let Ok(value) = input.try_parse(TextEmphasisFillMode::parse) {
+          fill = Some(value);
+          continue
+        }
+      }
+
+      if shape.is_none() {
+        if let Ok(value) = input.try_parse(TextEmphasisShape::parse) {
+          shape = Some(value);
+          continue
+        }
+      }
+
+      break
+    }
+
+    if fill.is_none() && shape.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+
+    Ok(TextEmphasisStyle::Keyword {
+      fill: fill.unwrap_or(TextEmphasisFillMode::Filled),
+      shape
+    })
+  }
+}
+
+impl ToCss for TextEmphasisStyle {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      TextEmphasisStyle::None => dest.write_str("none"),
+      TextEmphasisStyle::String(s) => serialize_string(&s, dest),
+      TextEmphasisStyle::Keyword { fill, shape } => {
+        // `filled` is the default, but is needed when there is no shape.
+        let has_fill = *fill != TextEmphasisFillMode::Filled || shape.is_none();
+        if has_fill {
+          fill.to_css(dest)?;
+        }
+
+        if let Some(shape) = shape {
+          if has_fill {
+            dest.write_char(' ')?;
+          }
+          shape.to_css(dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-text-decor-4/#text-emphasis-property
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEmphasis {
+  pub style: TextEmphasisStyle,
+  pub color: CssColor
+}
+
+impl Parse for TextEmphasis {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut style = None;
+    let mut color = None;
+
+    loop {
+      if style.is_none() {
+        if let Ok(value) = input.try_parse(TextEmphasisStyle::parse) {
+          style = Some(value);
+          continue
+        }
+      }
+
+      if color.is_none() {
+        if let Ok(value) = input.try_parse(CssColor::parse) {
+          color = Some(value);
+          continue
+        }
+      }
+
+      break
+    }
+
+    if style.is_none() && color.is_none() {
+      return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+
+    Ok(TextEmphasis {
+      style: style.unwrap_or_default(),
+      color: color.unwrap_or_else(|| CssColor::current_color())
+    })
+  }
+}
+
+impl ToCss for TextEmphasis {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    self.style.to_css(dest)?;
+    if self.color != CssColor::current_color() {
+      dest.write_char(' ')?;
+      self.color.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+// https://www.w3.org/TR/css-text-decor-4/#text-emphasis-position-property
+enum_property!(TextEmphasisPositionVertical,
+  Over,
+  Under
+);
+
+enum_property!(TextEmphasisPositionHorizontal,
+  Left,
+  Right
+);
+
+/// https://www.w3.org/TR/css-text-decor-4/#text-emphasis-position-property
+/// The horizontal position defaults to `right`, but is always printed since older
+/// browsers require it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEmphasisPosition {
+  pub vertical: TextEmphasisPositionVertical,
+  pub horizontal: TextEmphasisPositionHorizontal
+}
+
+impl Parse for TextEmphasisPosition {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(horizontal) = input.try_parse(TextEmphasisPositionHorizontal::parse) {
+      let vertical = TextEmphasisPositionVertical::parse(input)?;
+      return Ok(TextEmphasisPosition { vertical, horizontal })
+    }
+
+    let vertical = TextEmphasisPositionVertical::parse(input)?;
+    let horizontal = input.try_parse(TextEmphasisPositionHorizontal::parse).unwrap_or(TextEmphasisPositionHorizontal::Right);
+    Ok(TextEmphasisPosition { vertical, horizontal })
+  }
+}
+
+impl ToCss for TextEmphasisPosition {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    self.vertical.to_css(dest)?;
+    dest.write_char(' ')?;
+    self.horizontal.to_css(dest)
+  }
+}
+
+/// Merges the `text-emphasis` longhands into the shorthand, and adds the prefixed
+/// properties that the targets need.
+#[derive(Default)]
+pub struct TextEmphasisHandler {
+  targets: Option<Browsers>,
+  style: PrefixedPropertyState<TextEmphasisStyle>,
+  color: PrefixedPropertyState<CssColor>,
+  position: PrefixedPropertyState<TextEmphasisPosition>,
+  decls: Vec<Property>
+}
+
+impl TextEmphasisHandler {
+  pub fn new(targets: Option<Browsers>) -> TextEmphasisHandler {
+    TextEmphasisHandler {
+      targets,
+      ..TextEmphasisHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for TextEmphasisHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    use Property::*;
+
+    macro_rules! property {
+      ($key: ident, $feature: ident, $val: expr, $vp: expr) => {{
+        self.$key.remove_overridden(*$vp, self.targets);
+        if self.$key.needs_flush($val, *$vp) {
+          self.flush();
+        }
+        self.$key.set($val, *$vp);
+      }};
+    }
+
+    match property {
+      TextEmphasisStyle(val, vp) => property!(style, TextEmphasisStyle, val, vp),
+      TextEmphasisColor(val, vp) => property!(color, TextEmphasisColor, val, vp),
+      TextEmphasisPosition(val, vp) => property!(position, TextEmphasisPosition, val, vp),
+      TextEmphasis(val, vp) => {
+        self.style.remove_overridden(*vp, self.targets);
+        self.color.remove_overridden(*vp, self.targets);
+        if self.style.needs_flush(&val.style, *vp) || self.color.needs_flush(&val.color, *vp) {
+          self.flush();
+        }
+        self.style.set(&val.style, *vp);
+        self.color.set(&val.color, *vp);
+      }
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    self.flush();
+    std::mem::take(&mut self.decls)
+  }
+}
+
+impl TextEmphasisHandler {
+  fn flush(&mut self) {
+    let intersection = self.style.prefixes() & self.color.prefixes();
+    if !intersection.is_empty() {
+      let emphasis = TextEmphasis {
+        style: self.style.value().unwrap().clone(),
+        color: self.color.value().unwrap().clone()
+      };
+      let prefixes = expand_prefixes(intersection, Feature::TextEmphasis, self.targets);
+      self.decls.push(Property::TextEmphasis(emphasis, prefixes));
+      self.style.remove_prefixes(intersection);
+      self.color.remove_prefixes(intersection);
+    }
+
+    if let Some((style, prefixes)) = self.style.take(Feature::TextEmphasisStyle, self.targets) {
+      self.decls.push(Property::TextEmphasisStyle(style, prefixes));
+    }
+
+    if let Some((color, prefixes)) = self.color.take(Feature::TextEmphasisColor, self.targets) {
+      self.decls.push(Property::TextEmphasisColor(color, prefixes));
+    }
+
+    if let Some((position, prefixes)) = self.position.take(Feature::TextEmphasisPosition, self.targets) {
+      self.decls.push(Property::TextEmphasisPosition(position, prefixes));
+    }
+  }
+}
@@ -1,20 +1,27 @@
 use cssparser::*;
-use crate::traits::{Parse, ToCss};
+use crate::traits::{Parse, ToCss, PropertyHandler, TryAdd};
 use crate::values::{
   angle::Angle,
-  percentage::NumberOrPercentage,
-  length::{LengthPercentage, Length}
+  percentage::{NumberOrPercentage, Percentage},
+  length::{LengthPercentage, Length, LengthContext},
+  number::{approx_eq, round_to_precision, serialize_number},
+  position::{Position, PositionComponent, HorizontalPositionKeyword, VerticalPositionKeyword}
 };
 use crate::macros::enum_property;
 use crate::printer::Printer;
+use crate::compat;
+use super::{Property, VendorPrefix};
+use super::prefixes::{Browsers, Feature};
+use super::prefix_handler::{PrefixedPropertyState, expand_prefixes};
 use std::fmt::Write;
+use crate::error::ParserError;
 
 /// https://www.w3.org/TR/2019/CR-css-transforms-1-20190214/#propdef-transform
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Default)]
 pub struct TransformList(pub Vec<Transform>);
 
 impl Parse for TransformList {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
       return Ok(TransformList(vec![]))
     }
@@ -32,6 +39,73 @@ impl Parse for TransformList {
   }
 }
 
+/// Builder methods to construct transforms programmatically, e.g.
+/// `TransformList::new().then_translate(x, y).then_rotate(angle)`. Each method appends a
+/// transform function to the end of the list, as it would be written in CSS.
+impl TransformList {
+  /// Returns an empty list, i.e. `none`.
+  pub fn new() -> TransformList {
+    TransformList(vec![])
+  }
+
+  pub fn then(mut self, transform: Transform) -> TransformList {
+    self.0.push(transform);
+    self
+  }
+
+  pub fn then_translate(self, x: LengthPercentage, y: LengthPercentage) -> TransformList {
+    self.then(Transform::Translate(x, y))
+  }
+
+  pub fn then_translate_3d(self, x: LengthPercentage, y: LengthPercentage, z: Length) -> TransformList {
+    self.then(Transform::Translate3d(x, y, z))
+  }
+
+  pub fn then_scale(self, x: f32, y: f32) -> TransformList {
+    self.then(Transform::Scale(NumberOrPercentage::Number(x), NumberOrPercentage::Number(y)))
+  }
+
+  pub fn then_scale_3d(self, x: f32, y: f32, z: f32) -> TransformList {
+    self.then(Transform::Scale3d(NumberOrPercentage::Number(x), NumberOrPercentage::Number(y), NumberOrPercentage::Number(z)))
+  }
+
+  pub fn then_rotate(self, angle: Angle) -> TransformList {
+    self.then(Transform::Rotate(angle))
+  }
+
+  pub fn then_rotate_3d(self, x: f32, y: f32, z: f32, angle: Angle) -> TransformList {
+    self.then(Transform::Rotate3d(x, y, z, angle))
+  }
+
+  pub fn then_skew(self, x: Angle, y: Angle) -> TransformList {
+    self.then(Transform::Skew(x, y))
+  }
+
+  pub fn then_perspective(self, length: Length) -> TransformList {
+    self.then(Transform::Perspective(length))
+  }
+
+  pub fn then_matrix(self, matrix: Matrix3d<f32>) -> TransformList {
+    self.then(matrix.into())
+  }
+}
+
+/// Concatenates the lists, like `transform: a b`.
+impl std::ops::Mul<TransformList> for TransformList {
+  type Output = TransformList;
+
+  fn mul(mut self, other: TransformList) -> TransformList {
+    self.0.extend(other.0);
+    self
+  }
+}
+
+impl From<Matrix3d<f32>> for TransformList {
+  fn from(matrix: Matrix3d<f32>) -> TransformList {
+    TransformList(vec![matrix.into()])
+  }
+}
+
 impl ToCss for TransformList {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
     if self.0.is_empty() {
@@ -39,18 +113,27 @@ impl ToCss for TransformList {
       return Ok(())
     }
 
-    if dest.minify {
+    // Collapsing into a matrix changes how transforms are interpolated, so it is
+    // skipped within animations and transitions unless explicitly enabled.
+    if dest.minify && (!dest.animated || dest.collapse_animated_transforms) {
       // Combine transforms into a single matrix.
-      if let Some(matrix) = self.to_matrix() {
+      if let Some(matrix) = self.to_matrix_with_context(&dest.transform_context) {
+        fn printer(dest: &mut String, precision: u8) -> Printer<String> {
+          let mut printer = Printer::new(dest, true);
+          printer.precision = precision;
+          printer
+        }
+        let precision = dest.precision;
+
         // Generate based on the original transforms.
         let mut base = String::new();
-        self.to_css_base(&mut Printer::new(&mut base, true))?;
+        self.to_css_base(&mut printer(&mut base, precision))?;
 
         // Decompose the matrix into transform functions if possible.
         // If the resulting length is shorter than the original, use it.
-        if let Some(d) = matrix.decompose() {
+        if let Some(d) = matrix.decompose_with_precision(precision) {
           let mut decomposed = String::new();
-          d.to_css_base(&mut Printer::new(&mut decomposed, true))?;
+          d.to_css_base(&mut printer(&mut decomposed, precision))?;
           if decomposed.len() < base.len() {
             base = decomposed;
           }
@@ -59,9 +142,9 @@ impl ToCss for TransformList {
         // Also generate a matrix() or matrix3d() representation and compare that.
         let mut mat = String::new();
         if let Some(matrix) = matrix.to_matrix2d() {
-          Transform::Matrix(matrix).to_css(&mut Printer::new(&mut mat, true))?
+          Transform::Matrix(matrix).to_css(&mut printer(&mut mat, precision))?
         } else {
-          Transform::Matrix3d(matrix).to_css(&mut Printer::new(&mut mat, true))?
+          Transform::Matrix3d(matrix).to_css(&mut printer(&mut mat, precision))?
         }
 
         if mat.len() < base.len() {
@@ -72,6 +155,9 @@ impl ToCss for TransformList {
 
         return Ok(())
       }
+
+      // If that is not possible, e.g. due to percentages, fold adjacent transforms instead.
+      return self.fold().to_css_base(dest)
     }
 
     self.to_css_base(dest)
@@ -80,16 +166,64 @@ impl ToCss for TransformList {
 
 impl TransformList {
   fn to_css_base<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
-    for item in &self.0 {
+    for (i, item) in self.0.iter().enumerate() {
+      if i > 0 {
+        dest.whitespace()?;
+      }
       item.to_css(dest)?;
     }
     Ok(())
   }
 
+  /// Parses the value of the SVG `transform` attribute, where lengths and angles are unitless
+  /// numbers, arguments and transforms may be separated by whitespace and/or a comma,
+  /// and `rotate()` accepts an optional center point.
+  /// https://www.w3.org/TR/SVG11/coords.html#TransformAttribute
+  pub fn parse_svg<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut results = vec![];
+    while !input.is_exhausted() {
+      if !results.is_empty() {
+        input.try_parse(|input| input.expect_comma()).ok();
+      }
+      Transform::parse_svg(input, &mut results)?;
+    }
+    Ok(TransformList(results))
+  }
+
+  /// Serializes the transforms in the SVG `transform` attribute syntax. Returns `None` if
+  /// any of them cannot be represented, e.g. 3D transforms, percentages, or relative lengths.
+  /// https://www.w3.org/TR/SVG11/coords.html#TransformAttribute
+  pub fn to_svg_string(&self) -> Option<String> {
+    let mut s = String::new();
+    let mut printer = Printer::new(&mut s, false);
+    for (i, transform) in self.0.iter().enumerate() {
+      let (name, args) = transform.to_svg()?;
+      if i > 0 {
+        printer.write_char(' ').ok()?;
+      }
+      printer.write_str(name).ok()?;
+      printer.write_char('(').ok()?;
+      for (j, arg) in args.iter().enumerate() {
+        if j > 0 {
+          printer.write_char(' ').ok()?;
+        }
+        serialize_number(*arg, &mut printer).ok()?;
+      }
+      printer.write_char(')').ok()?;
+    }
+    Some(s)
+  }
+
   pub fn to_matrix(&self) -> Option<Matrix3d<f32>> {
+    self.to_matrix_with_context(&TransformContext::default())
+  }
+
+  /// Converts the transforms to a matrix like `to_matrix`, but also resolves relative lengths,
+  /// e.g. `translate(1em)` or `translate(50%)`, using the sizes in the given context.
+  pub fn to_matrix_with_context(&self, context: &TransformContext) -> Option<Matrix3d<f32>> {
     let mut matrix = Matrix3d::identity();
     for transform in &self.0 {
-      if let Some(m) = transform.to_matrix() {
+      if let Some(m) = transform.to_matrix_with_context(context) {
         matrix = m.multiply(&matrix);
       } else {
         return None
@@ -97,6 +231,76 @@ impl TransformList {
     }
     Some(matrix)
   }
+
+  /// Merges adjacent transforms of the same kind, e.g. `translate()`s with compatible units,
+  /// `scale()`s, or `rotate()`s around the same axis, and removes identity transforms such
+  /// as `translate(0)` or `scale(1)`.
+  fn fold(&self) -> TransformList {
+    let mut result: Vec<Transform> = vec![];
+    for transform in &self.0 {
+      if transform.is_identity() {
+        continue
+      }
+
+      if let Some(merged) = result.last().and_then(|last| last.merge(transform)) {
+        result.pop();
+        if !merged.is_identity() {
+          result.push(merged);
+        }
+        continue
+      }
+
+      result.push(transform.clone());
+    }
+
+    // An empty list would be `none`, which is not equivalent since
+    // any transform establishes a stacking context.
+    if result.is_empty() {
+      result.push(Transform::Translate(LengthPercentage::zero(), LengthPercentage::zero()));
+    }
+
+    TransformList(result)
+  }
+
+  /// Interpolates between this list and another at the given progress, where 0 is this
+  /// list and 1 is the other. Lists of the same length whose functions are of the same kind,
+  /// e.g. `translateX()` and `translate()`, are interpolated function by function. Otherwise,
+  /// both lists are converted to matrices, which are decomposed and interpolated. If that is
+  /// not possible, e.g. due to percentages, the lists are swapped halfway through.
+  /// https://drafts.csswg.org/css-transforms-2/#interpolation-of-transforms
+  pub fn interpolate(&self, other: &TransformList, t: f32) -> TransformList {
+    if self.0.is_empty() && other.0.is_empty() {
+      return TransformList(vec![])
+    }
+
+    // `none` is equivalent to a list of identity transforms matching the other list.
+    let from = if self.0.is_empty() { other.to_identity() } else { Some(self.clone()) };
+    let to = if other.0.is_empty() { self.to_identity() } else { Some(other.clone()) };
+    if let (Some(from), Some(to)) = (from, to) {
+      if from.0.len() == to.0.len() {
+        let list: Option<Vec<Transform>> = from.0.iter()
+          .zip(to.0.iter())
+          .map(|(a, b)| a.interpolate(b, t))
+          .collect();
+        if let Some(list) = list {
+          return TransformList(list)
+        }
+      }
+    }
+
+    if let (Some(from), Some(to)) = (self.to_matrix(), other.to_matrix()) {
+      if let Some(matrix) = from.interpolate(&to, t) {
+        return TransformList::from(matrix)
+      }
+    }
+
+    if t < 0.5 { self.clone() } else { other.clone() }
+  }
+
+  fn to_identity(&self) -> Option<TransformList> {
+    let list: Option<Vec<Transform>> = self.0.iter().map(|transform| transform.to_identity()).collect();
+    list.map(TransformList)
+  }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -266,12 +470,13 @@ impl Matrix3d<f32> {
     }
   }
 
+  /// Returns whether the matrix only has 2D components, ignoring rounding errors.
   pub fn is_2d(&self) -> bool {
-    self.m31 == 0.0 && self.m32 == 0.0 &&
-    self.m13 == 0.0 && self.m23 == 0.0 &&
-    self.m43 == 0.0 && self.m14 == 0.0 &&
-    self.m24 == 0.0 && self.m34 == 0.0 &&
-    self.m33 == 1.0 && self.m44 == 1.0
+    approx_eq(self.m31, 0.0) && approx_eq(self.m32, 0.0) &&
+    approx_eq(self.m13, 0.0) && approx_eq(self.m23, 0.0) &&
+    approx_eq(self.m43, 0.0) && approx_eq(self.m14, 0.0) &&
+    approx_eq(self.m24, 0.0) && approx_eq(self.m34, 0.0) &&
+    approx_eq(self.m33, 1.0) && approx_eq(self.m44, 1.0)
   }
 
   pub fn to_matrix2d(&self) -> Option<Matrix<f32>> {
@@ -426,6 +631,16 @@ impl Matrix3d<f32> {
 
   // https://drafts.csswg.org/css-transforms-2/#decomposing-a-3d-matrix
   pub fn decompose(&self) -> Option<TransformList> {
+    self.decompose_with_precision(6)
+  }
+
+  /// Decomposes the matrix like `decompose`, rounding the resulting values to the number of
+  /// decimal places that are printed with the given precision in significant digits.
+  pub fn decompose_with_precision(&self, precision: u8) -> Option<TransformList> {
+    if self.is_2d() {
+      return self.decompose_2d(precision)
+    }
+
     // Combine 2 point.
     let combine = |a: [f32; 3], b: [f32; 3], ascl: f32, bscl: f32| {
       [
@@ -447,7 +662,7 @@ impl Matrix3d<f32> {
       ]
     };
 
-    if self.m44 == 0.0 {
+    if approx_eq(self.m44, 0.0) {
       return None;
     }
 
@@ -465,19 +680,19 @@ impl Matrix3d<f32> {
     perspective_matrix.m34 = 0.0;
     perspective_matrix.m44 = 1.0;
 
-    if perspective_matrix.determinant() == 0.0 {
+    if approx_eq(perspective_matrix.determinant(), 0.0) {
       return None;
     }
 
     let mut transforms = vec![];
 
     // First, isolate perspective.
-    if matrix.m14 != 0.0 || matrix.m24 != 0.0 || matrix.m34 != 0.0 {
+    if !approx_eq(matrix.m14, 0.0) || !approx_eq(matrix.m24, 0.0) || !approx_eq(matrix.m34, 0.0) {
       let right_hand_side: [f32; 4] = [matrix.m14, matrix.m24, matrix.m34, matrix.m44];
 
       perspective_matrix = perspective_matrix.inverse().unwrap().transpose();
       let perspective = perspective_matrix.multiply_vector(&right_hand_side);
-      if perspective[0] == 0.0 && perspective[1] == 0.0 && perspective[3] == 0.0 {
+      if approx_eq(perspective[0], 0.0) && approx_eq(perspective[1], 0.0) && approx_eq(perspective[3], 0.0) {
         transforms.push(Transform::Perspective(
           Length::px(-1.0 / perspective[2])
         ))
@@ -488,7 +703,7 @@ impl Matrix3d<f32> {
 
     // Next take care of translation (easy).
     // let translate = Translate3D(matrix.m41, matrix.m42, matrix.m43);
-    if matrix.m41 != 0.0 || matrix.m42 != 0.0 || matrix.m43 != 0.0 {
+    if !approx_eq(matrix.m41, 0.0) || !approx_eq(matrix.m42, 0.0) || !approx_eq(matrix.m43, 0.0) {
       transforms.push(Transform::Translate3d(
         LengthPercentage::px(matrix.m41),
         LengthPercentage::px(matrix.m42),
@@ -543,14 +758,14 @@ impl Matrix3d<f32> {
     skew_y /= scale_z;
     skew_z /= scale_z;
 
-    if skew_z != 0.0 {
+    if !approx_eq(skew_z, 0.0) {
       return None // ???
     }
 
-    // Round to 5 digits of precision, which is what we print.
+    // Round to the precision that we print.
     macro_rules! round {
       ($var: ident) => {
-        $var = ($var * 100000.0).round() / 100000.0;
+        $var = round_to_precision($var, precision);
       };
     }
 
@@ -623,20 +838,324 @@ impl Matrix3d<f32> {
     rotate_y /= max;
     rotate_z /= max;
 
-    if a != 0.0 {
+    if !approx_eq(a, 0.0) {
       transforms.push(Transform::Rotate3d(rotate_x, rotate_y, rotate_z, Angle::Rad(a)))
     }
     
+    // An identity matrix is equivalent to `translate(0)`, like an empty list when folding.
     if transforms.is_empty() {
+      transforms.push(Transform::Translate(LengthPercentage::zero(), LengthPercentage::zero()));
+    }
+
+    Some(TransformList(transforms))
+  }
+
+  /// Decomposes a 2D matrix into a translation, rotation, skew along the x axis, and scale,
+  /// in that order. This avoids the 3D functions produced by `decompose`, e.g. `rotate3d()`.
+  // https://drafts.csswg.org/css-transforms-1/#decomposing-a-2d-matrix
+  fn decompose_2d(&self, precision: u8) -> Option<TransformList> {
+    let (a, b, c, d) = (self.m11, self.m12, self.m21, self.m22);
+    if approx_eq(a * d - b * c, 0.0) {
       return None
     }
 
+    // The first column is the x axis after rotating and scaling it.
+    let mut scale_x = (a * a + b * b).sqrt();
+    let angle = b.atan2(a);
+    let (cos, sin) = (a / scale_x, b / scale_x);
+
+    // Rotating the second column back leaves the skewed and scaled y axis. A negative
+    // determinant, i.e. a flipped coordinate system, results in a negative y scale.
+    let shear = c * cos + d * sin;
+    let mut scale_y = d * cos - c * sin;
+
+    // Round to the precision that we print. Angles within the epsilon of zero, e.g. due to
+    // rounding errors in the matrix, are dropped like translations.
+    let round = |v: f32| round_to_precision(v, precision);
+    let round_angle = |radians: f32| if approx_eq(radians, 0.0) { 0.0 } else { round(radians.to_degrees()) };
+    let rotate = round_angle(angle);
+    let skew = round_angle((shear / scale_y).atan());
+    scale_x = round(scale_x);
+    scale_y = round(scale_y);
+
+    let mut transforms = vec![];
+    if !approx_eq(self.m41, 0.0) || !approx_eq(self.m42, 0.0) {
+      transforms.push(Transform::Translate(
+        LengthPercentage::px(self.m41),
+        LengthPercentage::px(self.m42)
+      ));
+    }
+
+    if rotate != 0.0 {
+      transforms.push(Transform::Rotate(Angle::Deg(rotate)));
+    }
+
+    if skew != 0.0 {
+      transforms.push(Transform::SkewX(Angle::Deg(skew)));
+    }
+
+    if scale_x != 1.0 || scale_y != 1.0 {
+      transforms.push(Transform::Scale(
+        NumberOrPercentage::Number(scale_x),
+        NumberOrPercentage::Number(scale_y)
+      ));
+    }
+
+    // An identity matrix is equivalent to `translate(0)`, like an empty list when folding.
+    if transforms.is_empty() {
+      transforms.push(Transform::Translate(LengthPercentage::zero(), LengthPercentage::zero()));
+    }
+
     Some(TransformList(transforms))
   }
+
+  /// Interpolates between two matrices by decomposing them, interpolating the components,
+  /// and recomposing the result. Returns `None` if either matrix cannot be decomposed.
+  // https://drafts.csswg.org/css-transforms-2/#interpolation-of-3d-matrices
+  pub fn interpolate(&self, other: &Matrix3d<f32>, t: f32) -> Option<Matrix3d<f32>> {
+    let from = DecomposedMatrix3d::new(self)?;
+    let to = DecomposedMatrix3d::new(other)?;
+    Some(from.interpolate(&to, t).recompose())
+  }
+}
+
+/// Multiplies the matrices like `multiply`. Since points are row vectors that are multiplied
+/// by the matrix, `a * b` applies `a` before `b`, which is equivalent to `transform: b a`.
+impl std::ops::Mul<Matrix3d<f32>> for Matrix3d<f32> {
+  type Output = Matrix3d<f32>;
+
+  fn mul(self, other: Matrix3d<f32>) -> Matrix3d<f32> {
+    self.multiply(&other)
+  }
+}
+
+/// Converts the matrix to `matrix()` if it is 2D, and `matrix3d()` otherwise.
+impl From<Matrix3d<f32>> for Transform {
+  fn from(matrix: Matrix3d<f32>) -> Transform {
+    match matrix.to_matrix2d() {
+      Some(matrix) => Transform::Matrix(matrix),
+      None => Transform::Matrix3d(matrix)
+    }
+  }
+}
+
+/// The components of a 3D matrix, which can be interpolated individually.
+struct DecomposedMatrix3d {
+  perspective: [f32; 4],
+  translate: [f32; 3],
+  quaternion: [f32; 4],
+  skew: [f32; 3],
+  scale: [f32; 3]
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+  a + (b - a) * t
+}
+
+impl DecomposedMatrix3d {
+  // https://drafts.csswg.org/css-transforms-2/#decomposing-a-3d-matrix
+  fn new(matrix: &Matrix3d<f32>) -> Option<DecomposedMatrix3d> {
+    let dot = |a: [f32; 3], b: [f32; 3]| a[0] * b[0] + a[1] * b[1] + a[2] * b[2];
+    let length = |a: [f32; 3]| dot(a, a).sqrt();
+    let normalize = |a: [f32; 3], len: f32| [a[0] / len, a[1] / len, a[2] / len];
+    let combine = |a: [f32; 3], b: [f32; 3], bscl: f32| [a[0] + bscl * b[0], a[1] + bscl * b[1], a[2] + bscl * b[2]];
+    let cross = |a: [f32; 3], b: [f32; 3]| [
+      a[1] * b[2] - a[2] * b[1],
+      a[2] * b[0] - a[0] * b[2],
+      a[0] * b[1] - a[1] * b[0]
+    ];
+
+    if matrix.m44 == 0.0 {
+      return None
+    }
+
+    // Normalize the matrix.
+    let mut matrix = matrix.clone();
+    matrix.scale_by_factor(1.0 / matrix.m44);
+
+    let mut perspective_matrix = matrix.clone();
+    perspective_matrix.m14 = 0.0;
+    perspective_matrix.m24 = 0.0;
+    perspective_matrix.m34 = 0.0;
+    perspective_matrix.m44 = 1.0;
+    if perspective_matrix.determinant() == 0.0 {
+      return None
+    }
+
+    let perspective = if matrix.m14 != 0.0 || matrix.m24 != 0.0 || matrix.m34 != 0.0 {
+      let right_hand_side = [matrix.m14, matrix.m24, matrix.m34, matrix.m44];
+      perspective_matrix.inverse()?.transpose().multiply_vector(&right_hand_side)
+    } else {
+      [0.0, 0.0, 0.0, 1.0]
+    };
+
+    let translate = [matrix.m41, matrix.m42, matrix.m43];
+
+    let mut row = [
+      [matrix.m11, matrix.m12, matrix.m13],
+      [matrix.m21, matrix.m22, matrix.m23],
+      [matrix.m31, matrix.m32, matrix.m33]
+    ];
+    let mut scale = [0.0; 3];
+    let mut skew = [0.0; 3];
+
+    scale[0] = length(row[0]);
+    row[0] = normalize(row[0], scale[0]);
+
+    skew[0] = dot(row[0], row[1]);
+    row[1] = combine(row[1], row[0], -skew[0]);
+    scale[1] = length(row[1]);
+    row[1] = normalize(row[1], scale[1]);
+    skew[0] /= scale[1];
+
+    skew[1] = dot(row[0], row[2]);
+    row[2] = combine(row[2], row[0], -skew[1]);
+    skew[2] = dot(row[1], row[2]);
+    row[2] = combine(row[2], row[1], -skew[2]);
+    scale[2] = length(row[2]);
+    row[2] = normalize(row[2], scale[2]);
+    skew[1] /= scale[2];
+    skew[2] /= scale[2];
+
+    // Negate the matrix and the scale if the coordinate system is flipped.
+    if dot(row[0], cross(row[1], row[2])) < 0.0 {
+      for i in 0..3 {
+        scale[i] = -scale[i];
+        row[i] = [-row[i][0], -row[i][1], -row[i][2]];
+      }
+    }
+
+    let mut quaternion = [
+      0.5 * (1.0 + row[0][0] - row[1][1] - row[2][2]).max(0.0).sqrt(),
+      0.5 * (1.0 - row[0][0] + row[1][1] - row[2][2]).max(0.0).sqrt(),
+      0.5 * (1.0 - row[0][0] - row[1][1] + row[2][2]).max(0.0).sqrt(),
+      0.5 * (1.0 + row[0][0] + row[1][1] + row[2][2]).max(0.0).sqrt()
+    ];
+
+    if row[2][1] > row[1][2] {
+      quaternion[0] = -quaternion[0];
+    }
+
+    if row[0][2] > row[2][0] {
+      quaternion[1] = -quaternion[1];
+    }
+
+    if row[1][0] > row[0][1] {
+      quaternion[2] = -quaternion[2];
+    }
+
+    Some(DecomposedMatrix3d {
+      perspective,
+      translate,
+      quaternion,
+      skew,
+      scale
+    })
+  }
+
+  // https://drafts.csswg.org/css-transforms-2/#interpolation-of-decomposed-3d-matrix-values
+  fn interpolate(&self, other: &DecomposedMatrix3d, t: f32) -> DecomposedMatrix3d {
+    let lerp3 = |a: [f32; 3], b: [f32; 3]| [lerp(a[0], b[0], t), lerp(a[1], b[1], t), lerp(a[2], b[2], t)];
+
+    // The rotation is interpolated along the shortest arc between the quaternions.
+    let (a, b) = (self.quaternion, other.quaternion);
+    let product = (a[0] * b[0] + a[1] * b[1] + a[2] * b[2] + a[3] * b[3]).max(-1.0).min(1.0);
+    let quaternion = if product.abs() == 1.0 {
+      a
+    } else {
+      let theta = product.acos();
+      let w = (t * theta).sin() / (1.0 - product * product).sqrt();
+      let scale = (t * theta).cos() - product * w;
+      [
+        a[0] * scale + b[0] * w,
+        a[1] * scale + b[1] * w,
+        a[2] * scale + b[2] * w,
+        a[3] * scale + b[3] * w
+      ]
+    };
+
+    DecomposedMatrix3d {
+      perspective: [
+        lerp(self.perspective[0], other.perspective[0], t),
+        lerp(self.perspective[1], other.perspective[1], t),
+        lerp(self.perspective[2], other.perspective[2], t),
+        lerp(self.perspective[3], other.perspective[3], t)
+      ],
+      translate: lerp3(self.translate, other.translate),
+      quaternion,
+      skew: lerp3(self.skew, other.skew),
+      scale: lerp3(self.scale, other.scale)
+    }
+  }
+
+  // https://drafts.csswg.org/css-transforms-2/#recomposing-to-a-3d-matrix
+  fn recompose(&self) -> Matrix3d<f32> {
+    let mut matrix = Matrix3d::identity();
+
+    // Apply the perspective, and then the translation.
+    let [px, py, pz, pw] = self.perspective;
+    let [tx, ty, tz] = self.translate;
+    matrix.m14 = px;
+    matrix.m24 = py;
+    matrix.m34 = pz;
+    matrix.m41 = tx;
+    matrix.m42 = ty;
+    matrix.m43 = tz;
+    matrix.m44 = pw + tx * px + ty * py + tz * pz;
+
+    // Apply the rotation.
+    let [x, y, z, w] = self.quaternion;
+    let mut rotation = Matrix3d::identity();
+    rotation.m11 = 1.0 - 2.0 * (y * y + z * z);
+    rotation.m12 = 2.0 * (x * y + z * w);
+    rotation.m13 = 2.0 * (x * z - y * w);
+    rotation.m21 = 2.0 * (x * y - z * w);
+    rotation.m22 = 1.0 - 2.0 * (x * x + z * z);
+    rotation.m23 = 2.0 * (y * z + x * w);
+    rotation.m31 = 2.0 * (x * z + y * w);
+    rotation.m32 = 2.0 * (y * z - x * w);
+    rotation.m33 = 1.0 - 2.0 * (x * x + y * y);
+    matrix = rotation.multiply(&matrix);
+
+    // Apply the skew.
+    if self.skew[2] != 0.0 {
+      let mut skew = Matrix3d::identity();
+      skew.m32 = self.skew[2];
+      matrix = skew.multiply(&matrix);
+    }
+
+    if self.skew[1] != 0.0 {
+      let mut skew = Matrix3d::identity();
+      skew.m31 = self.skew[1];
+      matrix = skew.multiply(&matrix);
+    }
+
+    if self.skew[0] != 0.0 {
+      let mut skew = Matrix3d::identity();
+      skew.m21 = self.skew[0];
+      matrix = skew.multiply(&matrix);
+    }
+
+    // Apply the scale.
+    let [sx, sy, sz] = self.scale;
+    matrix.m11 *= sx;
+    matrix.m12 *= sx;
+    matrix.m13 *= sx;
+    matrix.m14 *= sx;
+    matrix.m21 *= sy;
+    matrix.m22 *= sy;
+    matrix.m23 *= sy;
+    matrix.m24 *= sy;
+    matrix.m31 *= sz;
+    matrix.m32 *= sz;
+    matrix.m33 *= sz;
+    matrix.m34 *= sz;
+    matrix
+  }
 }
 
 impl Parse for Transform {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let function = input.expect_function()?.clone();
     input.parse_nested_block(|input| {
       let location = input.current_source_location();
@@ -807,6 +1326,130 @@ impl Parse for Transform {
   }
 }
 
+impl Transform {
+  /// Parses a single transform function in the SVG `transform` attribute syntax. Lengths
+  /// are in user units, which map to `px`, and angles are in degrees. A rotation around
+  /// a center point is expanded into a translation, rotation, and inverse translation.
+  fn parse_svg<'i, 't>(input: &mut Parser<'i, 't>, dest: &mut Vec<Transform>) -> Result<(), ParseError<'i, ParserError<'i>>> {
+    let function = input.expect_function()?.clone();
+    input.parse_nested_block(|input| {
+      let location = input.current_source_location();
+      let mut args = vec![];
+      while !input.is_exhausted() {
+        if !args.is_empty() {
+          input.try_parse(|input| input.expect_comma()).ok();
+        }
+        args.push(input.expect_number()?);
+      }
+
+      match_ignore_ascii_case! { &function,
+        "matrix" => {
+          if let [a, b, c, d, e, f] = args[..] {
+            dest.push(Transform::Matrix(Matrix { a, b, c, d, e, f }));
+            return Ok(())
+          }
+        },
+        "translate" => {
+          match args[..] {
+            [x] => {
+              dest.push(Transform::Translate(LengthPercentage::px(x), LengthPercentage::zero()));
+              return Ok(())
+            }
+            [x, y] => {
+              dest.push(Transform::Translate(LengthPercentage::px(x), LengthPercentage::px(y)));
+              return Ok(())
+            }
+            _ => {}
+          }
+        },
+        "scale" => {
+          match args[..] {
+            [x] => {
+              dest.push(Transform::Scale(NumberOrPercentage::Number(x), NumberOrPercentage::Number(x)));
+              return Ok(())
+            }
+            [x, y] => {
+              dest.push(Transform::Scale(NumberOrPercentage::Number(x), NumberOrPercentage::Number(y)));
+              return Ok(())
+            }
+            _ => {}
+          }
+        },
+        "rotate" => {
+          match args[..] {
+            [angle] => {
+              dest.push(Transform::Rotate(Angle::Deg(angle)));
+              return Ok(())
+            }
+            [angle, cx, cy] => {
+              dest.push(Transform::Translate(LengthPercentage::px(cx), LengthPercentage::px(cy)));
+              dest.push(Transform::Rotate(Angle::Deg(angle)));
+              dest.push(Transform::Translate(LengthPercentage::px(-cx), LengthPercentage::px(-cy)));
+              return Ok(())
+            }
+            _ => {}
+          }
+        },
+        "skewx" => {
+          if let [angle] = args[..] {
+            dest.push(Transform::SkewX(Angle::Deg(angle)));
+            return Ok(())
+          }
+        },
+        "skewy" => {
+          if let [angle] = args[..] {
+            dest.push(Transform::SkewY(Angle::Deg(angle)));
+            return Ok(())
+          }
+        },
+        _ => {}
+      }
+
+      Err(location.new_unexpected_token_error(
+        cssparser::Token::Ident(function.clone())
+      ))
+    })
+  }
+
+  /// Returns the name and arguments of the equivalent SVG transform function. Functions
+  /// without an SVG equivalent, e.g. `skew()` or `rotateZ()`, are converted to a `matrix()`
+  /// if they are 2D.
+  fn to_svg(&self) -> Option<(&'static str, Vec<f32>)> {
+    fn px(length: &LengthPercentage) -> Option<f32> {
+      match length {
+        LengthPercentage::Dimension(length) => length.to_px(),
+        _ => None
+      }
+    }
+
+    use Transform::*;
+    match self {
+      Translate(x, y) => Some(("translate", vec![px(x)?, px(y)?])),
+      TranslateX(x) => Some(("translate", vec![px(x)?, 0.0])),
+      TranslateY(y) => Some(("translate", vec![0.0, px(y)?])),
+      Scale(x, y) => Some(("scale", vec![x.to_number(), y.to_number()])),
+      ScaleX(x) => Some(("scale", vec![x.to_number(), 1.0])),
+      ScaleY(y) => Some(("scale", vec![1.0, y.to_number()])),
+      Rotate(angle) => Some(("rotate", vec![angle.to_degrees()])),
+      SkewX(angle) => Some(("skewX", vec![angle.to_degrees()])),
+      SkewY(angle) => Some(("skewY", vec![angle.to_degrees()])),
+      _ => {
+        let self::Matrix { a, b, c, d, e, f } = self.to_matrix()?.to_matrix2d()?;
+        Some(("matrix", vec![a, b, c, d, e, f]))
+      }
+    }
+    .map(|(name, mut args)| {
+      // Omit the optional second argument when it is the default.
+      match name {
+        "translate" if args[1] == 0.0 => { args.pop(); },
+        "scale" if args[1] == args[0] => { args.pop(); },
+        _ => {}
+      }
+      (name, args)
+    })
+  }
+}
+
 impl ToCss for Transform {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
     use Transform::*;
@@ -868,65 +1511,65 @@ impl ToCss for Transform {
       Scale(x, y) => {
         if dest.minify && *x == 1.0 && *y != 1.0 {
           dest.write_str("scaleY(")?;
-          y.to_css(dest)?;
+          y.to_css_interchangeable(dest)?;
         } else if dest.minify && *x != 1.0 && *y == 1.0 {
           dest.write_str("scaleX(")?;
-          x.to_css(dest)?;
+          x.to_css_interchangeable(dest)?;
         } else {
           dest.write_str("scale(")?;
-          x.to_css(dest)?;
+          x.to_css_interchangeable(dest)?;
           if *y != *x {
             dest.delim(',', false)?;
-            y.to_css(dest)?;
+            y.to_css_interchangeable(dest)?;
           }
         }
         dest.write_char(')')
       }
       ScaleX(x) => {
         dest.write_str("scaleX(")?;
-        x.to_css(dest)?;
+        x.to_css_interchangeable(dest)?;
         dest.write_char(')')
       }
       ScaleY(y) => {
         dest.write_str("scaleY(")?;
-        y.to_css(dest)?;
+        y.to_css_interchangeable(dest)?;
         dest.write_char(')')
       }
       ScaleZ(z) => {
         dest.write_str("scaleZ(")?;
-        z.to_css(dest)?;
+        z.to_css_interchangeable(dest)?;
         dest.write_char(')')
       }
       Scale3d(x, y, z) => {
         if dest.minify && *z == 1.0 && *x == *y {
           // scale3d(x, x, 1) => scale(x)
           dest.write_str("scale(")?;
-          x.to_css(dest)?;
+          x.to_css_interchangeable(dest)?;
         } else if dest.minify && *x != 1.0 && *y == 1.0 && *z == 1.0 {
           // scale3d(x, 1, 1) => scaleX(x)
           dest.write_str("scaleX(")?;
-          x.to_css(dest)?;
+          x.to_css_interchangeable(dest)?;
         } else if dest.minify && *x == 1.0 && *y != 1.0 && *z == 1.0 {
            // scale3d(1, y, 1) => scaleY(y)
           dest.write_str("scaleY(")?;
-          y.to_css(dest)?;
+          y.to_css_interchangeable(dest)?;
         } else if dest.minify && *x == 1.0 && *y == 1.0 && *z != 1.0 {
           // scale3d(1, 1, z) => scaleZ(z)
           dest.write_str("scaleZ(")?;
-          z.to_css(dest)?;
+          z.to_css_interchangeable(dest)?;
         } else if dest.minify && *z == 1.0 {
           // scale3d(x, y, 1) => scale(x, y)
           dest.write_str("scale(")?;
-          x.to_css(dest)?;
+          x.to_css_interchangeable(dest)?;
           dest.delim(',', false)?;
-          y.to_css(dest)?;
+          y.to_css_interchangeable(dest)?;
         } else {
           dest.write_str("scale3d(")?;
-          x.to_css(dest)?;
+          x.to_css_interchangeable(dest)?;
           dest.delim(',', false)?;
-          y.to_css(dest)?;
+          y.to_css_interchangeable(dest)?;
           dest.delim(',', false)?;
-          z.to_css(dest)?;
+          z.to_css_interchangeable(dest)?;
         }
         dest.write_char(')')
       }
@@ -1063,31 +1706,86 @@ impl ToCss for Transform {
   }
 }
 
+/// The layout context that relative lengths and percentages in transforms are resolved against
+/// when converting them to a matrix, in pixels. This can be provided when the layout is known
+/// ahead of time, e.g. for static content, so that more transforms can be collapsed when minifying.
+/// Values whose context is not provided are not resolved.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TransformContext {
+  /// The font size of the element, for `em`.
+  pub font_size: Option<f32>,
+  /// The font size of the root element, for `rem`.
+  pub root_font_size: Option<f32>,
+  /// The width and height of the viewport, for `vw`, `vh`, `vmin`, and `vmax`.
+  pub viewport: Option<(f32, f32)>,
+  /// The width and height of the element's reference box, for percentages.
+  pub bounding_box: Option<(f32, f32)>
+}
+
+impl std::hash::Hash for TransformContext {
+  fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+    let bits = |value: Option<f32>| value.map(f32::to_bits);
+    bits(self.font_size).hash(state);
+    bits(self.root_font_size).hash(state);
+    self.viewport.map(|(w, h)| (w.to_bits(), h.to_bits())).hash(state);
+    self.bounding_box.map(|(w, h)| (w.to_bits(), h.to_bits())).hash(state);
+  }
+}
+
+impl TransformContext {
+  fn length_context(&self) -> LengthContext {
+    LengthContext {
+      font_size: self.font_size,
+      root_font_size: self.root_font_size,
+      viewport_width: self.viewport.map(|(width, _)| width),
+      viewport_height: self.viewport.map(|(_, height)| height)
+    }
+  }
+
+  /// Resolves a length or a percentage of the given size to pixels.
+  fn resolve(&self, value: &LengthPercentage, size: Option<f32>) -> Option<f32> {
+    match value {
+      LengthPercentage::Dimension(value) => value.to_px_with_context(&self.length_context()),
+      LengthPercentage::Percentage(percentage) => size.map(|size| percentage.0 * size),
+      LengthPercentage::Calc(_) => None
+    }
+  }
+}
+
 impl Transform {
   pub fn to_matrix(&self) -> Option<Matrix3d<f32>> {
+    self.to_matrix_with_context(&TransformContext::default())
+  }
+
+  /// Converts the transform to a matrix like `to_matrix`, but also resolves relative
+  /// lengths and percentages using the sizes in the given context.
+  pub fn to_matrix_with_context(&self, context: &TransformContext) -> Option<Matrix3d<f32>> {
+    let length_context = context.length_context();
+    let width = context.bounding_box.map(|(width, _)| width);
+    let height = context.bounding_box.map(|(_, height)| height);
     match &self {
-      Transform::Translate(LengthPercentage::Dimension(x), LengthPercentage::Dimension(y)) => {
-        if let (Some(x), Some(y)) = (x.to_px(), y.to_px()) {
+      Transform::Translate(x, y) => {
+        if let (Some(x), Some(y)) = (context.resolve(x, width), context.resolve(y, height)) {
           return Some(Matrix3d::translate(x, y, 0.0))
         }
       }
-      Transform::TranslateX(LengthPercentage::Dimension(x)) => {
-        if let Some(x) = x.to_px() {
+      Transform::TranslateX(x) => {
+        if let Some(x) = context.resolve(x, width) {
           return Some(Matrix3d::translate(x, 0.0, 0.0))
         }
       }
-      Transform::TranslateY(LengthPercentage::Dimension(y)) => {
-        if let Some(y) = y.to_px() {
+      Transform::TranslateY(y) => {
+        if let Some(y) = context.resolve(y, height) {
           return Some(Matrix3d::translate(0.0, y, 0.0))
         }
       }
       Transform::TranslateZ(z) => {
-        if let Some(z) = z.to_px() {
+        if let Some(z) = z.to_px_with_context(&length_context) {
           return Some(Matrix3d::translate(0.0, 0.0, z))
         }
       }
-      Transform::Translate3d(LengthPercentage::Dimension(x), LengthPercentage::Dimension(y), z) => {
-        if let (Some(x), Some(y), Some(z)) = (x.to_px(), y.to_px(), z.to_px()) {
+      Transform::Translate3d(x, y, z) => {
+        if let (Some(x), Some(y), Some(z)) = (context.resolve(x, width), context.resolve(y, height), z.to_px_with_context(&length_context)) {
           return Some(Matrix3d::translate(x, y, z))
         }
       }
@@ -1128,7 +1826,7 @@ impl Transform {
         return Some(Matrix3d::skew(0.0, y.to_radians()))
       }
       Transform::Perspective(len) => {
-        if let Some(len) = len.to_px() {
+        if let Some(len) = len.to_px_with_context(&length_context) {
           return Some(Matrix3d::perspective(len))
         }
       }
@@ -1142,6 +1840,179 @@ impl Transform {
   }
 }
 
+impl Transform {
+  fn is_identity(&self) -> bool {
+    use Transform::*;
+    match self {
+      Translate(x, y) => *x == 0.0 && *y == 0.0,
+      TranslateX(x) | TranslateY(x) => *x == 0.0,
+      TranslateZ(z) => *z == 0.0,
+      Translate3d(x, y, z) => *x == 0.0 && *y == 0.0 && *z == 0.0,
+      Scale(x, y) => *x == 1.0 && *y == 1.0,
+      ScaleX(x) | ScaleY(x) | ScaleZ(x) => *x == 1.0,
+      Scale3d(x, y, z) => *x == 1.0 && *y == 1.0 && *z == 1.0,
+      Rotate(angle) | RotateX(angle) | RotateY(angle) | RotateZ(angle) | Rotate3d(_, _, _, angle) => angle.is_zero(),
+      Skew(x, y) => x.is_zero() && y.is_zero(),
+      SkewX(angle) | SkewY(angle) => angle.is_zero(),
+      Perspective(_) | Matrix(_) | Matrix3d(_) => false
+    }
+  }
+
+  /// Combines this transform with the following one into a single transform, if possible.
+  fn merge(&self, next: &Transform) -> Option<Transform> {
+    use Transform::*;
+    match (self, next) {
+      (Rotate(a), Rotate(b)) | (Rotate(a), RotateZ(b)) |
+      (RotateZ(a), Rotate(b)) | (RotateZ(a), RotateZ(b)) => Some(Rotate(a.clone() + b.clone())),
+      (RotateX(a), RotateX(b)) => Some(RotateX(a.clone() + b.clone())),
+      (RotateY(a), RotateY(b)) => Some(RotateY(a.clone() + b.clone())),
+      _ => {
+        if let (Some((x1, y1, z1)), Some((x2, y2, z2))) = (self.to_translate3d(), next.to_translate3d()) {
+          let x = try_add_lengths(&x1, &x2)?;
+          let y = try_add_lengths(&y1, &y2)?;
+          let z = try_add_lengths(&z1, &z2)?;
+          return Some(if z == 0.0 { Translate(x, y) } else { Translate3d(x, y, z) })
+        }
+
+        if let (Some((x1, y1, z1)), Some((x2, y2, z2))) = (self.to_scale3d(), next.to_scale3d()) {
+          let (x, y, z) = (x1 * x2, y1 * y2, z1 * z2);
+          return Some(if z == 1.0 {
+            Scale(NumberOrPercentage::Number(x), NumberOrPercentage::Number(y))
+          } else {
+            Scale3d(NumberOrPercentage::Number(x), NumberOrPercentage::Number(y), NumberOrPercentage::Number(z))
+          })
+        }
+
+        None
+      }
+    }
+  }
+
+  /// Interpolates between two transforms of the same kind, e.g. `translateX()` and `translate()`.
+  /// Returns `None` for transforms of different kinds, which must be interpolated as matrices.
+  fn interpolate(&self, other: &Transform, t: f32) -> Option<Transform> {
+    use Transform::*;
+    let angle = |a: &Angle, b: &Angle| Angle::Deg(lerp(a.to_degrees(), b.to_degrees(), t));
+    match (self, other) {
+      (Rotate(a), Rotate(b)) | (Rotate(a), RotateZ(b)) |
+      (RotateZ(a), Rotate(b)) | (RotateZ(a), RotateZ(b)) => Some(Rotate(angle(a, b))),
+      (RotateX(a), RotateX(b)) => Some(RotateX(angle(a, b))),
+      (RotateY(a), RotateY(b)) => Some(RotateY(angle(a, b))),
+      (Rotate3d(x1, y1, z1, a), Rotate3d(x2, y2, z2, b)) => {
+        // Rotations around different axes are interpolated as matrices.
+        let normalize = |x: f32, y: f32, z: f32| {
+          let len = (x * x + y * y + z * z).sqrt();
+          (x / len, y / len, z / len)
+        };
+        if normalize(*x1, *y1, *z1) != normalize(*x2, *y2, *z2) {
+          return None
+        }
+        Some(Rotate3d(*x1, *y1, *z1, angle(a, b)))
+      }
+      (Perspective(_), _) | (_, Perspective(_)) | (Matrix(_), _) | (_, Matrix(_)) | (Matrix3d(_), _) | (_, Matrix3d(_)) => None,
+      _ => {
+        if let (Some((x1, y1, z1)), Some((x2, y2, z2))) = (self.to_translate3d(), other.to_translate3d()) {
+          let x = x1 * (1.0 - t) + x2 * t;
+          let y = y1 * (1.0 - t) + y2 * t;
+          let z = z1 * (1.0 - t) + z2 * t;
+          return Some(if z == 0.0 { Translate(x, y) } else { Translate3d(x, y, z) })
+        }
+
+        if let (Some((x1, y1, z1)), Some((x2, y2, z2))) = (self.to_scale3d(), other.to_scale3d()) {
+          let (x, y, z) = (lerp(x1, x2, t), lerp(y1, y2, t), lerp(z1, z2, t));
+          return Some(if z == 1.0 {
+            Scale(NumberOrPercentage::Number(x), NumberOrPercentage::Number(y))
+          } else {
+            Scale3d(NumberOrPercentage::Number(x), NumberOrPercentage::Number(y), NumberOrPercentage::Number(z))
+          })
+        }
+
+        if let (Some((x1, y1)), Some((x2, y2))) = (self.to_skew(), other.to_skew()) {
+          return Some(Skew(angle(&x1, &x2), angle(&y1, &y2)))
+        }
+
+        None
+      }
+    }
+  }
+
+  /// Returns the identity transform of the same kind, e.g. `scale(1)` for `scale(2)`.
+  fn to_identity(&self) -> Option<Transform> {
+    use Transform::*;
+    let zero = LengthPercentage::zero;
+    let one = || NumberOrPercentage::Number(1.0);
+    Some(match self {
+      Translate(..) => Translate(zero(), zero()),
+      TranslateX(_) => TranslateX(zero()),
+      TranslateY(_) => TranslateY(zero()),
+      TranslateZ(_) => TranslateZ(Length::zero()),
+      Translate3d(..) => Translate3d(zero(), zero(), Length::zero()),
+      Scale(..) => Scale(one(), one()),
+      ScaleX(_) => ScaleX(one()),
+      ScaleY(_) => ScaleY(one()),
+      ScaleZ(_) => ScaleZ(one()),
+      Scale3d(..) => Scale3d(one(), one(), one()),
+      Rotate(_) => Rotate(Angle::Deg(0.0)),
+      RotateX(_) => RotateX(Angle::Deg(0.0)),
+      RotateY(_) => RotateY(Angle::Deg(0.0)),
+      RotateZ(_) => RotateZ(Angle::Deg(0.0)),
+      Rotate3d(x, y, z, _) => Rotate3d(*x, *y, *z, Angle::Deg(0.0)),
+      Skew(..) => Skew(Angle::Deg(0.0), Angle::Deg(0.0)),
+      SkewX(_) => SkewX(Angle::Deg(0.0)),
+      SkewY(_) => SkewY(Angle::Deg(0.0)),
+      Perspective(_) => return None,
+      Matrix(_) => Matrix(super::transform::Matrix3d::identity().to_matrix2d()?),
+      Matrix3d(_) => Matrix3d(super::transform::Matrix3d::identity())
+    })
+  }
+
+  fn to_skew(&self) -> Option<(Angle, Angle)> {
+    use Transform::*;
+    match self {
+      Skew(x, y) => Some((x.clone(), y.clone())),
+      SkewX(x) => Some((x.clone(), Angle::Deg(0.0))),
+      SkewY(y) => Some((Angle::Deg(0.0), y.clone())),
+      _ => None
+    }
+  }
+
+  fn to_translate3d(&self) -> Option<(LengthPercentage, LengthPercentage, Length)> {
+    use Transform::*;
+    match self {
+      Translate(x, y) => Some((x.clone(), y.clone(), Length::zero())),
+      TranslateX(x) => Some((x.clone(), LengthPercentage::zero(), Length::zero())),
+      TranslateY(y) => Some((LengthPercentage::zero(), y.clone(), Length::zero())),
+      TranslateZ(z) => Some((LengthPercentage::zero(), LengthPercentage::zero(), z.clone())),
+      Translate3d(x, y, z) => Some((x.clone(), y.clone(), z.clone())),
+      _ => None
+    }
+  }
+
+  fn to_scale3d(&self) -> Option<(f32, f32, f32)> {
+    use Transform::*;
+    match self {
+      Scale(x, y) => Some((x.into(), y.into(), 1.0)),
+      ScaleX(x) => Some((x.into(), 1.0, 1.0)),
+      ScaleY(y) => Some((1.0, y.into(), 1.0)),
+      ScaleZ(z) => Some((1.0, 1.0, z.into())),
+      Scale3d(x, y, z) => Some((x.into(), y.into(), z.into())),
+      _ => None
+    }
+  }
+}
+
+/// Adds two lengths if they have compatible units, e.g. not `10px` and `10%`.
+/// Zero can be added to anything.
+fn try_add_lengths<T: TryAdd<T> + Clone + std::cmp::PartialEq<f32>>(a: &T, b: &T) -> Option<T> {
+  if *b == 0.0 {
+    Some(a.clone())
+  } else if *a == 0.0 {
+    Some(b.clone())
+  } else {
+    a.try_add(b)
+  }
+}
+
 // https://drafts.csswg.org/css-transforms-2/#transform-style-property
 enum_property!(TransformStyle,
   ("flat", Flat),
@@ -1171,7 +2042,7 @@ pub enum Perspective {
 }
 
 impl Parse for Perspective {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
       return Ok(Perspective::None)
     }
@@ -1188,3 +2059,420 @@ impl ToCss for Perspective {
     }
   }
 }
+
+/// https://drafts.csswg.org/css-transforms-1/#transform-origin-property
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransformOrigin {
+  pub position: Position,
+  pub z: Length
+}
+
+impl Parse for TransformOrigin {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let position = parse_origin_position(input)?;
+    let z = input.try_parse(Length::parse).unwrap_or(Length::zero());
+    Ok(TransformOrigin { position, z })
+  }
+}
+
+/// Unlike `<position>`, the keywords of an origin cannot have offsets, so that
+/// a length following them is parsed as the z offset, e.g. `right bottom 10px`.
+fn parse_origin_position<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Position, ParseError<'i, ParserError<'i>>> {
+  if let Ok(x) = input.try_parse(|input| parse_origin_component::<HorizontalPositionKeyword>(input, true)) {
+    if let Ok(y) = input.try_parse(|input| parse_origin_component::<VerticalPositionKeyword>(input, true)) {
+      return Ok(Position { x, y })
+    }
+
+    // Keywords may be in either order, e.g. `center left`.
+    if x == PositionComponent::Center {
+      if let Ok(x) = input.try_parse(|input| parse_origin_component::<HorizontalPositionKeyword>(input, false)) {
+        return Ok(Position { x, y: PositionComponent::Center })
+      }
+    }
+
+    return Ok(Position { x, y: PositionComponent::Center })
+  }
+
+  let y = parse_origin_component::<VerticalPositionKeyword>(input, false)?;
+  let x = input.try_parse(|input| parse_origin_component::<HorizontalPositionKeyword>(input, false)).unwrap_or(PositionComponent::Center);
+  Ok(Position { x, y })
+}
+
+fn parse_origin_component<'i, 't, S: Parse>(input: &mut Parser<'i, 't>, allow_length: bool) -> Result<PositionComponent<S>, ParseError<'i, ParserError<'i>>> {
+  if input.try_parse(|input| input.expect_ident_matching("center")).is_ok() {
+    return Ok(PositionComponent::Center)
+  }
+
+  if allow_length {
+    if let Ok(lp) = input.try_parse(LengthPercentage::parse) {
+      return Ok(PositionComponent::Length(lp))
+    }
+  }
+
+  Ok(PositionComponent::Side(S::parse(input)?, None))
+}
+
+impl ToCss for TransformOrigin {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    serialize_origin(&self.position, &self.z, dest)
+  }
+}
+
+/// https://drafts.csswg.org/css-transforms-2/#perspective-origin-property
+#[derive(Debug, Clone, PartialEq)]
+pub struct PerspectiveOrigin(pub Position);
+
+impl Parse for PerspectiveOrigin {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    Ok(PerspectiveOrigin(Position::parse(input)?))
+  }
+}
+
+impl ToCss for PerspectiveOrigin {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    serialize_origin(&self.0, &Length::zero(), dest)
+  }
+}
+
+/// Serializes an origin. When minifying, keywords are converted to percentages, e.g. `left top`
+/// becomes `0 0`, unless the keywords are shorter, e.g. `top` rather than `50% 0`.
+fn serialize_origin<W>(position: &Position, z: &Length, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+  if dest.minify {
+    let x = match &position.x {
+      PositionComponent::Center => Some(LengthPercentage::Percentage(Percentage(0.5))),
+      PositionComponent::Length(lp) => Some(lp.clone()),
+      PositionComponent::Side(HorizontalPositionKeyword::Left, None) => Some(LengthPercentage::Percentage(Percentage(0.0))),
+      PositionComponent::Side(HorizontalPositionKeyword::Right, None) => Some(LengthPercentage::Percentage(Percentage(1.0))),
+      PositionComponent::Side(_, Some(_)) => None
+    };
+    let y = match &position.y {
+      PositionComponent::Center => Some(LengthPercentage::Percentage(Percentage(0.5))),
+      PositionComponent::Length(lp) => Some(lp.clone()),
+      PositionComponent::Side(VerticalPositionKeyword::Top, None) => Some(LengthPercentage::Percentage(Percentage(0.0))),
+      PositionComponent::Side(VerticalPositionKeyword::Bottom, None) => Some(LengthPercentage::Percentage(Percentage(1.0))),
+      PositionComponent::Side(_, Some(_)) => None
+    };
+
+    if let (Some(x), Some(y)) = (x, y) {
+      // A zero percentage is equivalent to a zero length, which is shorter.
+      let canonicalize = |lp: LengthPercentage| match lp {
+        LengthPercentage::Percentage(Percentage(p)) if p == 0.0 => LengthPercentage::zero(),
+        lp => lp
+      };
+      let x = canonicalize(x);
+      let y = canonicalize(y);
+
+      let mut canonical = String::new();
+      let mut printer = Printer::new(&mut canonical, true);
+      x.to_css(&mut printer)?;
+      // The y position defaults to `center`, and z defaults to zero.
+      if y != LengthPercentage::Percentage(Percentage(0.5)) || *z != 0.0 {
+        printer.write_char(' ')?;
+        y.to_css(&mut printer)?;
+      }
+      if *z != 0.0 {
+        printer.write_char(' ')?;
+        z.to_css(&mut printer)?;
+      }
+
+      let mut keywords = String::new();
+      position.to_css(&mut Printer::new(&mut keywords, true))?;
+      if *z != 0.0 || canonical.len() <= keywords.len() {
+        return dest.write_str(&canonical)
+      }
+    }
+  }
+
+  position.to_css(dest)?;
+  if *z != 0.0 {
+    dest.write_char(' ')?;
+    z.to_css(dest)?;
+  }
+  Ok(())
+}
+
+/// https://drafts.csswg.org/css-transforms-2/#propdef-translate
+#[derive(Debug, Clone, PartialEq)]
+pub enum Translate {
+  None,
+  XYZ(LengthPercentage, LengthPercentage, Length)
+}
+
+impl Parse for Translate {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(Translate::None)
+    }
+
+    let x = LengthPercentage::parse(input)?;
+    let y = input.try_parse(LengthPercentage::parse);
+    let z = if y.is_ok() {
+      input.try_parse(Length::parse).ok()
+    } else {
+      None
+    };
+
+    Ok(Translate::XYZ(x, y.unwrap_or(LengthPercentage::zero()), z.unwrap_or(Length::zero())))
+  }
+}
+
+impl ToCss for Translate {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      Translate::None => dest.write_str("none"),
+      Translate::XYZ(x, y, z) => {
+        x.to_css(dest)?;
+        if *y != 0.0 || *z != 0.0 {
+          dest.write_char(' ')?;
+          y.to_css(dest)?;
+          if *z != 0.0 {
+            dest.write_char(' ')?;
+            z.to_css(dest)?;
+          }
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+impl Translate {
+  /// Returns the equivalent transform function, or `None` if there is no translation.
+  pub fn to_transform(&self) -> Option<Transform> {
+    match self {
+      Translate::None => None,
+      Translate::XYZ(x, y, z) if *z == 0.0 => Some(Transform::Translate(x.clone(), y.clone())),
+      Translate::XYZ(x, y, z) => Some(Transform::Translate3d(x.clone(), y.clone(), z.clone()))
+    }
+  }
+}
+
+/// https://drafts.csswg.org/css-transforms-2/#propdef-rotate
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rotate {
+  None,
+  /// A rotation around the axis given by the x, y, and z components.
+  Axis(f32, f32, f32, Angle)
+}
+
+impl Parse for Rotate {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(Rotate::None)
+    }
+
+    // The angle may come before or after the axis, which defaults to the z axis.
+    let axis = input.try_parse(parse_rotate_axis);
+    let angle = Angle::parse(input)?;
+    let (x, y, z) = match axis {
+      Ok(axis) => axis,
+      Err(_) => input.try_parse(parse_rotate_axis).unwrap_or((0.0, 0.0, 1.0))
+    };
+
+    Ok(Rotate::Axis(x, y, z, angle))
+  }
+}
+
+fn parse_rotate_axis<'i, 't>(input: &mut Parser<'i, 't>) -> Result<(f32, f32, f32), ParseError<'i, ParserError<'i>>> {
+  let location = input.current_source_location();
+  if let Ok(ident) = input.try_parse(|input| input.expect_ident_cloned()) {
+    return match_ignore_ascii_case! { &*ident,
+      "x" => Ok((1.0, 0.0, 0.0)),
+      "y" => Ok((0.0, 1.0, 0.0)),
+      "z" => Ok((0.0, 0.0, 1.0)),
+      _ => Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+    }
+  }
+
+  let x = f32::parse(input)?;
+  let y = f32::parse(input)?;
+  let z = f32::parse(input)?;
+  Ok((x, y, z))
+}
+
+impl ToCss for Rotate {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      Rotate::None => dest.write_str("none"),
+      Rotate::Axis(x, y, z, angle) => {
+        if *x == 1.0 && *y == 0.0 && *z == 0.0 {
+          dest.write_str("x ")?;
+        } else if *x == 0.0 && *y == 1.0 && *z == 0.0 {
+          dest.write_str("y ")?;
+        } else if !(*x == 0.0 && *y == 0.0 && *z == 1.0) {
+          x.to_css(dest)?;
+          dest.write_char(' ')?;
+          y.to_css(dest)?;
+          dest.write_char(' ')?;
+          z.to_css(dest)?;
+          dest.write_char(' ')?;
+        }
+        angle.to_css(dest)
+      }
+    }
+  }
+}
+
+impl Rotate {
+  /// Returns the equivalent transform function, or `None` if there is no rotation.
+  pub fn to_transform(&self) -> Option<Transform> {
+    match self {
+      Rotate::None => None,
+      Rotate::Axis(x, y, z, angle) => Some(match (*x, *y, *z) {
+        (x, y, z) if x == 0.0 && y == 0.0 && z == 1.0 => Transform::Rotate(angle.clone()),
+        (x, y, z) if x == 1.0 && y == 0.0 && z == 0.0 => Transform::RotateX(angle.clone()),
+        (x, y, z) if x == 0.0 && y == 1.0 && z == 0.0 => Transform::RotateY(angle.clone()),
+        (x, y, z) => Transform::Rotate3d(x, y, z, angle.clone())
+      })
+    }
+  }
+}
+
+/// https://drafts.csswg.org/css-transforms-2/#propdef-scale
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scale {
+  None,
+  XYZ(NumberOrPercentage, NumberOrPercentage, NumberOrPercentage)
+}
+
+impl Parse for Scale {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("none")).is_ok() {
+      return Ok(Scale::None)
+    }
+
+    let x = NumberOrPercentage::parse(input)?;
+    let y = input.try_parse(NumberOrPercentage::parse);
+    let z = if y.is_ok() {
+      input.try_parse(NumberOrPercentage::parse).ok()
+    } else {
+      None
+    };
+
+    Ok(Scale::XYZ(x.clone(), y.unwrap_or(x), z.unwrap_or(NumberOrPercentage::Number(1.0))))
+  }
+}
+
+impl ToCss for Scale {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      Scale::None => dest.write_str("none"),
+      Scale::XYZ(x, y, z) => {
+        x.to_css_interchangeable(dest)?;
+        if *y != *x || *z != 1.0 {
+          dest.write_char(' ')?;
+          y.to_css_interchangeable(dest)?;
+          if *z != 1.0 {
+            dest.write_char(' ')?;
+            z.to_css_interchangeable(dest)?;
+          }
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+impl Scale {
+  /// Returns the equivalent transform function, or `None` if there is no scale.
+  pub fn to_transform(&self) -> Option<Transform> {
+    match self {
+      Scale::None => None,
+      Scale::XYZ(x, y, z) if *z == 1.0 => Some(Transform::Scale(x.clone(), y.clone())),
+      Scale::XYZ(x, y, z) => Some(Transform::Scale3d(x.clone(), y.clone(), z.clone()))
+    }
+  }
+}
+
+/// Handles the `transform` property along with its vendor prefixes, and compiles the individual
+/// transform properties into it for targets that don't support them. The individual properties
+/// are applied before `transform`, in the order translate, rotate, and scale, so they are prepended
+/// to the transform list. Since the generated `transform` overrides any from other rules, this is
+/// only equivalent when the individual properties and `transform` are declared in the same rule.
+#[derive(Default)]
+pub struct TransformHandler {
+  targets: Option<Browsers>,
+  transform: PrefixedPropertyState<TransformList>,
+  translate: Option<Translate>,
+  rotate: Option<Rotate>,
+  scale: Option<Scale>,
+  decls: Vec<Property>
+}
+
+impl TransformHandler {
+  pub fn new(targets: Option<Browsers>) -> TransformHandler {
+    TransformHandler {
+      targets,
+      ..TransformHandler::default()
+    }
+  }
+}
+
+impl PropertyHandler for TransformHandler {
+  fn handle_property(&mut self, property: &Property) -> bool {
+    match property {
+      Property::Transform(val, vp) => {
+        self.transform.remove_overridden(*vp, self.targets);
+        if self.transform.needs_flush(val, *vp) {
+          self.flush();
+        }
+        self.transform.set(val, *vp);
+      }
+      Property::Translate(val) => self.translate = Some(val.clone()),
+      Property::Rotate(val) => self.rotate = Some(val.clone()),
+      Property::Scale(val) => self.scale = Some(val.clone()),
+      _ => return false
+    }
+
+    true
+  }
+
+  fn finalize(&mut self) -> Vec<Property> {
+    self.flush();
+    std::mem::take(&mut self.decls)
+  }
+}
+
+impl TransformHandler {
+  fn flush(&mut self) {
+    let translate = std::mem::take(&mut self.translate);
+    let rotate = std::mem::take(&mut self.rotate);
+    let scale = std::mem::take(&mut self.scale);
+    let transform = self.transform.take(Feature::Transform, self.targets);
+
+    let has_individual = translate.is_some() || rotate.is_some() || scale.is_some();
+    if has_individual && self.targets.map_or(false, |targets| !compat::Feature::IndividualTransforms.is_compatible(targets)) {
+      let mut list = vec![];
+      list.extend(translate.and_then(|translate| translate.to_transform()));
+      list.extend(rotate.and_then(|rotate| rotate.to_transform()));
+      list.extend(scale.and_then(|scale| scale.to_transform()));
+
+      let prefixes = match transform {
+        Some((transform, prefixes)) => {
+          list.extend(transform.0);
+          prefixes
+        }
+        None => expand_prefixes(VendorPrefix::None, Feature::Transform, self.targets)
+      };
+
+      self.decls.push(Property::Transform(TransformList(list), prefixes));
+      return
+    }
+
+    if let Some((transform, prefixes)) = transform {
+      self.decls.push(Property::Transform(transform, prefixes));
+    }
+
+    if let Some(translate) = translate {
+      self.decls.push(Property::Translate(translate));
+    }
+
+    if let Some(rotate) = rotate {
+      self.decls.push(Property::Rotate(rotate));
+    }
+
+    if let Some(scale) = scale {
+      self.decls.push(Property::Scale(scale));
+    }
+  }
+}
@@ -1,24 +1,26 @@
 use cssparser::*;
 use crate::traits::{Parse, ToCss, PropertyHandler};
 use crate::values::{ident::CustomIdent, time::Time, easing::EasingFunction};
-use super::{Property, VendorPrefix};
+use super::Property;
 use crate::printer::Printer;
 use std::fmt::Write;
 use itertools::izip;
 use smallvec::SmallVec;
 use super::prefixes::{Browsers, Feature};
+use super::prefix_handler::{PrefixedPropertyState, expand_prefixes};
+use crate::error::ParserError;
 
 /// https://www.w3.org/TR/2018/WD-css-transitions-1-20181011/#transition-shorthand-property
 #[derive(Debug, Clone, PartialEq)]
 pub struct Transition {
-  property: CustomIdent,
-  duration: Time,
-  delay: Time,
-  timing_function: EasingFunction 
+  pub property: CustomIdent,
+  pub duration: Time,
+  pub delay: Time,
+  pub timing_function: EasingFunction
 }
 
 impl Parse for Transition {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut property = None;
     let mut duration = None;
     let mut delay = None;
@@ -90,10 +92,10 @@ impl ToCss for Transition {
 #[derive(Default)]
 pub struct TransitionHandler {
   targets: Option<Browsers>,
-  properties: Option<(SmallVec<[CustomIdent; 1]>, VendorPrefix)>,
-  durations: Option<(SmallVec<[Time; 1]>, VendorPrefix)>,
-  delays: Option<(SmallVec<[Time; 1]>, VendorPrefix)>,
-  timing_functions: Option<(SmallVec<[EasingFunction; 1]>, VendorPrefix)>,
+  properties: PrefixedPropertyState<SmallVec<[CustomIdent; 1]>>,
+  durations: PrefixedPropertyState<SmallVec<[Time; 1]>>,
+  delays: PrefixedPropertyState<SmallVec<[Time; 1]>>,
+  timing_functions: PrefixedPropertyState<SmallVec<[EasingFunction; 1]>>,
   decls: Vec<Property>
 }
 
@@ -111,56 +113,41 @@ impl PropertyHandler for TransitionHandler {
     use Property::*;
 
     macro_rules! property {
-      ($feature: ident, $prop: ident, $val: expr, $vp: ident) => {{
-        // If two vendor prefixes for the same property have different
-        // values, we need to flush what we have immediately to preserve order.
-        if let Some((val, prefixes)) = &self.$prop {
-          if val != $val && !prefixes.contains(*$vp) {
-            self.flush();
-          }
-        }
-
-        // Otherwise, update the value and add the prefix.
-        if let Some((val, prefixes)) = &mut self.$prop {
-          *val = $val.clone();
-          *prefixes |= *$vp;
-          if prefixes.contains(VendorPrefix::None) {
-            if let Some(targets) = self.targets {
-              *prefixes = Feature::$feature.prefixes_for(targets);
-            }
-          }
-        } else {
-          let prefixes = if $vp.contains(VendorPrefix::None) {
-            if let Some(targets) = self.targets {
-              Feature::$feature.prefixes_for(targets)
-            } else {
-              *$vp
-            }
-          } else {
-            *$vp
-          };
-          self.$prop = Some(($val.clone(), prefixes))
+      ($prop: ident, $feature: ident, $val: expr, $vp: ident) => {{
+        self.$prop.remove_overridden(*$vp, self.targets);
+        if self.$prop.needs_flush($val, *$vp) {
+          self.flush();
         }
+        self.$prop.set($val, *$vp);
       }};
     }
 
     match property {
-      TransitionProperty(val, vp) => property!(TransitionProperty, properties, val, vp),
-      TransitionDuration(val, vp) => property!(TransitionDuration, durations, val, vp),
-      TransitionDelay(val, vp) => property!(TransitionDelay, delays, val, vp),
-      TransitionTimingFunction(val, vp) => property!(TransitionTimingFunction, timing_functions, val, vp),
+      TransitionProperty(val, vp) => property!(properties, TransitionProperty, val, vp),
+      TransitionDuration(val, vp) => property!(durations, TransitionDuration, val, vp),
+      TransitionDelay(val, vp) => property!(delays, TransitionDelay, val, vp),
+      TransitionTimingFunction(val, vp) => property!(timing_functions, TransitionTimingFunction, val, vp),
       Transition(val, vp) => {
         let properties: SmallVec<[CustomIdent; 1]> = val.iter().map(|b| b.property.clone()).collect();
-        property!(TransitionProperty, properties, &properties, vp);
-
         let durations: SmallVec<[Time; 1]> = val.iter().map(|b| b.duration.clone()).collect();
-        property!(TransitionDuration, durations, &durations, vp);
-
         let delays: SmallVec<[Time; 1]> = val.iter().map(|b| b.delay.clone()).collect();
-        property!(TransitionDelay, delays, &delays, vp);
-
         let timing_functions: SmallVec<[EasingFunction; 1]> = val.iter().map(|b| b.timing_function.clone()).collect();
-        property!(TransitionTimingFunction, timing_functions, &timing_functions, vp);
+
+        self.properties.remove_overridden(*vp, self.targets);
+        self.durations.remove_overridden(*vp, self.targets);
+        self.delays.remove_overridden(*vp, self.targets);
+        self.timing_functions.remove_overridden(*vp, self.targets);
+
+        // Flush before setting any of the longhands, so the shorthand isn't split.
+        if self.properties.needs_flush(&properties, *vp) || self.durations.needs_flush(&durations, *vp) ||
+          self.delays.needs_flush(&delays, *vp) || self.timing_functions.needs_flush(&timing_functions, *vp) {
+          self.flush();
+        }
+
+        self.properties.set(&properties, *vp);
+        self.durations.set(&durations, *vp);
+        self.delays.set(&delays, *vp);
+        self.timing_functions.set(&timing_functions, *vp);
       }
       _ => return false
     }
@@ -176,69 +163,44 @@ impl PropertyHandler for TransitionHandler {
 
 impl TransitionHandler {
   fn flush(&mut self) {
-    let mut properties = std::mem::take(&mut self.properties);
-    let mut durations = std::mem::take(&mut self.durations);
-    let mut delays = std::mem::take(&mut self.delays);
-    let mut timing_functions = std::mem::take(&mut self.timing_functions);
-
-    if let (Some((properties, property_prefixes)), Some((durations, duration_prefixes)), Some((delays, delay_prefixes)), Some((timing_functions, timing_prefixes))) = (&mut properties, &mut durations, &mut delays, &mut timing_functions) {
+    if let (Some(properties), Some(durations), Some(delays), Some(timing_functions)) = (self.properties.value(), self.durations.value(), self.delays.value(), self.timing_functions.value()) {
       // Only use shorthand syntax if the number of transitions matches on all properties.
       let len = properties.len();
       if durations.len() == len && delays.len() == len && timing_functions.len() == len {
-        let transitions: SmallVec<[Transition; 1]> = izip!(properties, durations, delays, timing_functions).map(|(property, duration, delay, timing_function)| {
-          Transition {
-            property: property.clone(),
-            duration: duration.clone(),
-            delay: delay.clone(),
-            timing_function: timing_function.clone()
-          }
-        }).collect();
-
         // Find the intersection of prefixes with the same value.
         // Remove that from the prefixes of each of the properties. The remaining
         // prefixes will be handled by outputing individual properties below.
-        let intersection = *property_prefixes & *duration_prefixes & *delay_prefixes & *timing_prefixes;
+        let intersection = self.properties.prefixes() & self.durations.prefixes() & self.delays.prefixes() & self.timing_functions.prefixes();
         if !intersection.is_empty() {
-          self.decls.push(Property::Transition(transitions.clone(), intersection));
-          property_prefixes.remove(intersection);
-          duration_prefixes.remove(intersection);
-          delay_prefixes.remove(intersection);
-          timing_prefixes.remove(intersection);
-        }
-      }
-    }
-
-    if let Some((properties, prefix)) = properties {
-      if !prefix.is_empty() {
-        self.decls.push(Property::TransitionProperty(properties, prefix));
-      }
-    }
-
-    if let Some((durations, prefix)) = durations {
-      if !prefix.is_empty() {
-        self.decls.push(Property::TransitionDuration(durations, prefix));
-      }
-    }
+          let transitions: SmallVec<[Transition; 1]> = izip!(properties, durations, delays, timing_functions).map(|(property, duration, delay, timing_function)| {
+            Transition {
+              property: property.clone(),
+              duration: duration.clone(),
+              delay: delay.clone(),
+              timing_function: timing_function.clone()
+            }
+          }).collect();
 
-    if let Some((delays, prefix)) = delays {
-      if !prefix.is_empty() {
-        self.decls.push(Property::TransitionDelay(delays, prefix));
+          self.decls.push(Property::Transition(transitions, expand_prefixes(intersection, Feature::Transition, self.targets)));
+          self.properties.remove_prefixes(intersection);
+          self.durations.remove_prefixes(intersection);
+          self.delays.remove_prefixes(intersection);
+          self.timing_functions.remove_prefixes(intersection);
+        }
       }
     }
 
-    if let Some((timing_functions, prefix)) = timing_functions {
-      if !prefix.is_empty() {
-        self.decls.push(Property::TransitionTimingFunction(timing_functions, prefix));
-      }
+    macro_rules! single_property {
+      ($prop: ident, $key: ident) => {
+        if let Some((val, vp)) = self.$key.take(Feature::$prop, self.targets) {
+          self.decls.push(Property::$prop(val, vp))
+        }
+      };
     }
 
-    self.reset();
-  }
-
-  fn reset(&mut self) {
-    self.properties = None;
-    self.durations = None;
-    self.delays = None;
-    self.timing_functions = None;
+    single_property!(TransitionProperty, properties);
+    single_property!(TransitionDuration, durations);
+    single_property!(TransitionDelay, delays);
+    single_property!(TransitionTimingFunction, timing_functions);
   }
 }
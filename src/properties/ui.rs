@@ -0,0 +1,88 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::macros::enum_property;
+use crate::error::ParserError;
+
+/// https://www.w3.org/TR/css-ui-4/#appearance-switching
+#[derive(Debug, Clone, PartialEq)]
+pub enum Appearance {
+  None,
+  Auto,
+  Textfield,
+  MenulistButton,
+  Button,
+  Checkbox,
+  Listbox,
+  Menulist,
+  Meter,
+  ProgressBar,
+  PushButton,
+  Radio,
+  Searchfield,
+  SliderHorizontal,
+  SquareButton,
+  Textarea,
+  /// A non-standard value supported by some browsers, e.g. `caret` or `-webkit-inner-spin-button`.
+  NonStandard(String)
+}
+
+impl Parse for Appearance {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let ident = input.expect_ident()?;
+    Ok(match_ignore_ascii_case! { &*ident,
+      "none" => Appearance::None,
+      "auto" => Appearance::Auto,
+      "textfield" => Appearance::Textfield,
+      "menulist-button" => Appearance::MenulistButton,
+      "button" => Appearance::Button,
+      "checkbox" => Appearance::Checkbox,
+      "listbox" => Appearance::Listbox,
+      "menulist" => Appearance::Menulist,
+      "meter" => Appearance::Meter,
+      "progress-bar" => Appearance::ProgressBar,
+      "push-button" => Appearance::PushButton,
+      "radio" => Appearance::Radio,
+      "searchfield" => Appearance::Searchfield,
+      "slider-horizontal" => Appearance::SliderHorizontal,
+      "square-button" => Appearance::SquareButton,
+      "textarea" => Appearance::Textarea,
+      _ => Appearance::NonStandard(ident.as_ref().to_ascii_lowercase())
+    })
+  }
+}
+
+impl ToCss for Appearance {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      Appearance::None => dest.write_str("none"),
+      Appearance::Auto => dest.write_str("auto"),
+      Appearance::Textfield => dest.write_str("textfield"),
+      Appearance::MenulistButton => dest.write_str("menulist-button"),
+      Appearance::Button => dest.write_str("button"),
+      Appearance::Checkbox => dest.write_str("checkbox"),
+      Appearance::Listbox => dest.write_str("listbox"),
+      Appearance::Menulist => dest.write_str("menulist"),
+      Appearance::Meter => dest.write_str("meter"),
+      Appearance::ProgressBar => dest.write_str("progress-bar"),
+      Appearance::PushButton => dest.write_str("push-button"),
+      Appearance::Radio => dest.write_str("radio"),
+      Appearance::Searchfield => dest.write_str("searchfield"),
+      Appearance::SliderHorizontal => dest.write_str("slider-horizontal"),
+      Appearance::SquareButton => dest.write_str("square-button"),
+      Appearance::Textarea => dest.write_str("textarea"),
+      Appearance::NonStandard(s) => serialize_identifier(&s, dest)
+    }
+  }
+}
+
+// https://www.w3.org/TR/css-ui-4/#resize
+// The block and inline values are from https://drafts.csswg.org/css-logical/#resize
+enum_property!(Resize,
+  None,
+  Both,
+  Horizontal,
+  Vertical,
+  Block,
+  Inline
+);
@@ -0,0 +1,96 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::warnings::{self, WarningKind};
+use super::Property;
+use crate::error::ParserError;
+
+/// https://www.w3.org/TR/css-will-change-1/#typedef-animateable-feature
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnimateableFeature {
+  ScrollPosition,
+  Contents,
+  /// A property name. Known properties are stored in lowercase.
+  Property(String)
+}
+
+impl Parse for AnimateableFeature {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    match_ignore_ascii_case! { &*ident,
+      "scroll-position" => return Ok(AnimateableFeature::ScrollPosition),
+      "contents" => return Ok(AnimateableFeature::Contents),
+      // These are excluded from the custom identifiers that are allowed.
+      "will-change" | "none" | "all" | "auto" | "initial" | "inherit" | "unset" | "default" => {
+        return Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+      },
+      _ => {}
+    }
+
+    // Custom properties are case sensitive, but other property names are not.
+    if ident.starts_with("--") {
+      return Ok(AnimateableFeature::Property(ident.as_ref().into()))
+    }
+
+    let name = ident.to_ascii_lowercase();
+    if !Property::is_known(&name) && warnings::is_validating() {
+      warnings::warn(location, WarningKind::UnknownProperty);
+    }
+    Ok(AnimateableFeature::Property(name))
+  }
+}
+
+impl ToCss for AnimateableFeature {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      AnimateableFeature::ScrollPosition => dest.write_str("scroll-position"),
+      AnimateableFeature::Contents => dest.write_str("contents"),
+      AnimateableFeature::Property(name) => serialize_identifier(&name, dest)
+    }
+  }
+}
+
+/// https://www.w3.org/TR/css-will-change-1/#will-change
+#[derive(Debug, Clone, PartialEq)]
+pub enum WillChange {
+  Auto,
+  /// The features that are expected to change, without duplicates.
+  Features(Vec<AnimateableFeature>)
+}
+
+impl Parse for WillChange {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(WillChange::Auto)
+    }
+
+    let mut features = vec![];
+    for feature in input.parse_comma_separated(AnimateableFeature::parse)? {
+      if !features.contains(&feature) {
+        features.push(feature);
+      }
+    }
+    Ok(WillChange::Features(features))
+  }
+}
+
+impl ToCss for WillChange {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      WillChange::Auto => dest.write_str("auto"),
+      WillChange::Features(features) => {
+        let mut first = true;
+        for feature in features {
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          feature.to_css(dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
@@ -0,0 +1,217 @@
+use cssparser::*;
+use selectors::SelectorList;
+use crate::parser::CssRule;
+use crate::selector::{Selectors, Namespaces, parse_selector_list};
+use crate::nesting;
+use crate::traits::ToCss;
+use crate::error::ParserError;
+
+/// The kind of simple selector to rename.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenameKind {
+  Class,
+  Id
+}
+
+/// Renames a class or id in the selectors of all style rules, including those within
+/// `@media` and `@supports` rules and nested style rules, and in `@supports selector()` tests.
+/// Returns the number of occurrences renamed.
+pub fn rename(rules: &mut Vec<CssRule>, kind: RenameKind, from: &str, to: &str, namespaces: &Namespaces) -> usize {
+  rename_with(rules, namespaces, &mut |k, name| {
+    if k == kind && name == from {
+      Some(to.to_owned())
+    } else {
+      None
+    }
+  })
+}
+
+/// Renames the classes and ids in the selectors of all style rules, like `rename`, to the names
+/// returned by the given function. Names for which it returns `None` are left as is.
+pub fn rename_with(rules: &mut Vec<CssRule>, namespaces: &Namespaces, f: &mut dyn FnMut(RenameKind, &str) -> Option<String>) -> usize {
+  rename_rules(rules, namespaces, false, false, f)
+}
+
+/// Scopes the classes and ids in a CSS module to the names returned by the given function, like
+/// `rename_with`. Names within `:global()`, or after `:global` up to the end of the selector, are
+/// left as is, and the `:global` and `:local` pseudo classes are removed.
+pub(crate) fn scope_with(rules: &mut Vec<CssRule>, namespaces: &Namespaces, f: &mut dyn FnMut(RenameKind, &str) -> Option<String>) -> usize {
+  rename_rules(rules, namespaces, false, true, f)
+}
+
+fn rename_rules(rules: &mut Vec<CssRule>, namespaces: &Namespaces, nested: bool, modules: bool, f: &mut dyn FnMut(RenameKind, &str) -> Option<String>) -> usize {
+  let mut count = 0;
+  for rule in rules.iter_mut() {
+    match rule {
+      CssRule::Style(style) => {
+        if let Some((selectors, renamed)) = rename_selectors(&style.selectors, namespaces, nested, modules, f) {
+          style.selectors = selectors;
+          count += renamed;
+        }
+        count += rename_rules(&mut style.rules, namespaces, true, modules, f);
+      }
+      CssRule::Media(media) => count += rename_rules(&mut media.rules, namespaces, nested, modules, f),
+      CssRule::Supports(supports) => {
+        if let Some((condition, renamed)) = rename_condition(&supports.condition, modules, f) {
+          supports.condition = condition;
+          count += renamed;
+        }
+        count += rename_rules(&mut supports.rules, namespaces, nested, modules, f);
+      }
+      _ => {}
+    }
+  }
+  count
+}
+
+/// Selectors cannot be modified in place, so they are serialized, renamed, and reparsed.
+/// Returns `None` if nothing changed.
+fn rename_selectors(selectors: &SelectorList<Selectors>, namespaces: &Namespaces, nested: bool, modules: bool, f: &mut dyn FnMut(RenameKind, &str) -> Option<String>) -> Option<(SelectorList<Selectors>, usize)> {
+  let source = selectors.to_css_string();
+  let mut input = ParserInput::new(&source);
+  let mut parser = Parser::new(&mut input);
+  let mut renamed = String::new();
+  let count = rename_tokens(&mut parser, &mut renamed, modules, f).ok()?;
+  // Removing `:global` or `:local` changes the selector without renaming anything.
+  if count == 0 && renamed == source {
+    return None
+  }
+
+  let selectors = if nested {
+    let mut input = ParserInput::new(&renamed);
+    let mut parser = Parser::new(&mut input);
+    nesting::parse_nested_selectors(&mut parser, namespaces).ok()?
+  } else {
+    parse_selector_list(&renamed, namespaces)?
+  };
+
+  Some((selectors, count))
+}
+
+/// Renames the classes and ids within the `selector()` functions of a `@supports` condition,
+/// e.g. `selector(.foo:has(> img))`. Returns `None` if nothing was renamed.
+fn rename_condition(condition: &str, modules: bool, f: &mut dyn FnMut(RenameKind, &str) -> Option<String>) -> Option<(String, usize)> {
+  let mut input = ParserInput::new(condition);
+  let mut parser = Parser::new(&mut input);
+  let mut renamed = String::new();
+  let count = rename_condition_tokens(&mut parser, &mut renamed, modules, f).ok()?;
+  if count == 0 {
+    return None
+  }
+  Some((renamed, count))
+}
+
+fn rename_condition_tokens<'i, 't>(input: &mut Parser<'i, 't>, dest: &mut String, modules: bool, f: &mut dyn FnMut(RenameKind, &str) -> Option<String>) -> Result<usize, ParseError<'i, ParserError<'i>>> {
+  let mut count = 0;
+  loop {
+    let start = input.position();
+    let token = match input.next_including_whitespace_and_comments() {
+      Ok(token) => token.clone(),
+      Err(_) => break
+    };
+
+    match &token {
+      Token::Function(name) if name.eq_ignore_ascii_case("selector") => {
+        cssparser::ToCss::to_css(&token, dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?;
+        count += input.parse_nested_block(|input| rename_tokens(input, dest, modules, f))?;
+        dest.push(')');
+      }
+      Token::Function(_) | Token::ParenthesisBlock => {
+        cssparser::ToCss::to_css(&token, dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?;
+        count += input.parse_nested_block(|input| rename_condition_tokens(input, dest, modules, f))?;
+        dest.push(')');
+      }
+      // Other tokens, e.g. in a declaration test such as `(display: grid)`, are kept as written.
+      _ => dest.push_str(input.slice_from(start))
+    }
+  }
+
+  Ok(count)
+}
+
+/// Serializes the tokens in the input, renaming each class or id for which the function
+/// returns a new name. Returns the number of occurrences renamed. In a CSS module, the
+/// `:global` and `:local` pseudo classes are removed, and the names they make global are
+/// left as is.
+fn rename_tokens<'i, 't>(input: &mut Parser<'i, 't>, dest: &mut String, modules: bool, f: &mut dyn FnMut(RenameKind, &str) -> Option<String>) -> Result<usize, ParseError<'i, ParserError<'i>>> {
+  let mut count = 0;
+  let mut after_dot = false;
+  // Whether the names are global after a `:global` without arguments, up to the next selector.
+  let mut global = false;
+  let mut keep = |_: RenameKind, _: &str| None;
+  loop {
+    let token = match input.next_including_whitespace() {
+      Ok(token) => token.clone(),
+      Err(_) => break
+    };
+
+    if modules && token == Token::Colon {
+      let state = input.state();
+      match input.next_including_whitespace().map(|token| token.clone()) {
+        Ok(Token::Function(name)) if is_scope_pseudo_class(&name) => {
+          let f: &mut dyn FnMut(RenameKind, &str) -> Option<String> = if name.eq_ignore_ascii_case("global") { &mut keep } else { &mut *f };
+          count += input.parse_nested_block(|input| rename_tokens(input, dest, true, f))?;
+          after_dot = false;
+          continue
+        }
+        Ok(Token::Ident(name)) if is_scope_pseudo_class(&name) => {
+          global = name.eq_ignore_ascii_case("global");
+          // Whitespace after the pseudo class is a descendant combinator, unless it starts the selector.
+          let start = input.position();
+          input.skip_whitespace();
+          if input.position() != start && !dest.is_empty() && !dest.ends_with(' ') {
+            dest.push(' ');
+          }
+          after_dot = false;
+          continue
+        }
+        _ => input.reset(&state)
+      }
+    }
+
+    if token == Token::Comma {
+      global = false;
+    }
+
+    let f: &mut dyn FnMut(RenameKind, &str) -> Option<String> = if global { &mut keep } else { &mut *f };
+    let renamed = match &token {
+      Token::Ident(name) if after_dot => f(RenameKind::Class, name.as_ref()),
+      Token::IDHash(name) => f(RenameKind::Id, name.as_ref()),
+      _ => None
+    };
+
+    match (&token, renamed) {
+      (_, Some(to)) => {
+        if let Token::IDHash(_) = token {
+          dest.push('#');
+        }
+        serialize_identifier(&to, dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?;
+        count += 1;
+      }
+      // Attribute selectors are left as is, e.g. `[class~=foo]`.
+      (Token::SquareBracketBlock, _) => {
+        let start = input.position();
+        input.parse_nested_block(|input| {
+          while input.next_including_whitespace_and_comments().is_ok() {}
+          Ok::<_, ParseError<'i, ParserError<'i>>>(())
+        })?;
+        dest.push('[');
+        dest.push_str(input.slice_from(start));
+      }
+      (Token::Function(_), _) | (Token::ParenthesisBlock, _) => {
+        cssparser::ToCss::to_css(&token, dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?;
+        count += input.parse_nested_block(|input| rename_tokens(input, dest, modules, f))?;
+        dest.push(')');
+      }
+      _ => cssparser::ToCss::to_css(&token, dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?
+    }
+
+    after_dot = token == Token::Delim('.');
+  }
+
+  Ok(count)
+}
+
+fn is_scope_pseudo_class(name: &str) -> bool {
+  name.eq_ignore_ascii_case("global") || name.eq_ignore_ascii_case("local")
+}
@@ -0,0 +1,531 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::values::{ident::CustomIdent, image::Image};
+use crate::macros::enum_property;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// https://drafts.csswg.org/css-counter-styles-3/#the-counter-style-rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct CounterStyleRule {
+  pub name: CustomIdent,
+  pub properties: Vec<CounterStyleProperty>
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CounterStyleProperty {
+  System(System),
+  Symbols(Vec<Symbol>),
+  AdditiveSymbols(Vec<AdditiveSymbol>),
+  Negative(Symbol, Option<Symbol>),
+  Prefix(Symbol),
+  Suffix(Symbol),
+  Range(CounterRange),
+  Pad(u32, Symbol),
+  Fallback(CustomIdent),
+  SpeakAs(SpeakAs)
+}
+
+impl CounterStyleRule {
+  /// https://drafts.csswg.org/css-counter-styles-3/#typedef-counter-style-name
+  pub fn parse_name<'i, 't>(input: &mut Parser<'i, 't>) -> Result<CustomIdent, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let name = CustomIdent::parse(input)?;
+    let valid = match_ignore_ascii_case! { &name.0,
+      "none" | "decimal" | "disc" | "square" | "circle" | "disclosure-open" | "disclosure-closed" => false,
+      _ => true
+    };
+
+    if !valid {
+      return Err(location.new_custom_error(ParserError::AtRulePreludeInvalid))
+    }
+
+    Ok(name)
+  }
+
+  pub fn parse<'i, 't>(name: CustomIdent, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut parser = DeclarationListParser::new(input, CounterStyleDeclarationParser);
+    let mut properties: Vec<CounterStyleProperty> = vec![];
+    while let Some(decl) = parser.next() {
+      if let Ok(decl) = decl {
+        // Later descriptors override earlier ones with the same name.
+        let discriminant = std::mem::discriminant(&decl);
+        properties.retain(|p| std::mem::discriminant(p) != discriminant);
+        properties.push(decl);
+      }
+    }
+
+    let rule = CounterStyleRule { name, properties };
+    if !rule.is_valid() {
+      return Err(input.new_custom_error(ParserError::AtRuleBodyInvalid))
+    }
+
+    Ok(rule)
+  }
+
+  /// Checks that the symbols required by the counter system are present.
+  /// https://drafts.csswg.org/css-counter-styles-3/#counter-style-symbols
+  fn is_valid(&self) -> bool {
+    let mut system = &System::Symbolic;
+    let mut symbols = None;
+    let mut additive_symbols = None;
+    for property in &self.properties {
+      match property {
+        CounterStyleProperty::System(s) => system = s,
+        CounterStyleProperty::Symbols(s) => symbols = Some(s.len()),
+        CounterStyleProperty::AdditiveSymbols(s) => additive_symbols = Some(s),
+        _ => {}
+      }
+    }
+
+    match system {
+      System::Cyclic | System::Fixed(_) | System::Symbolic => symbols.unwrap_or(0) >= 1,
+      System::Alphabetic | System::Numeric => symbols.unwrap_or(0) >= 2,
+      System::Additive => {
+        match additive_symbols {
+          // Weights must be in strictly descending order.
+          Some(additive_symbols) if !additive_symbols.is_empty() => {
+            additive_symbols.windows(2).all(|w| w[0].weight > w[1].weight)
+          }
+          _ => false
+        }
+      }
+      System::Extends(_) => symbols.is_none() && additive_symbols.is_none()
+    }
+  }
+}
+
+impl ToCss for CounterStyleRule {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    dest.write_str("@counter-style ")?;
+    self.name.to_css(dest)?;
+    dest.whitespace()?;
+    dest.write_char('{')?;
+    dest.indent();
+    let len = self.properties.len();
+    for (i, prop) in self.properties.iter().enumerate() {
+      dest.newline()?;
+      prop.to_css(dest)?;
+      if i != len - 1 || !dest.minify {
+        dest.write_char(';')?;
+      }
+    }
+    dest.dedent();
+    dest.newline()?;
+    dest.write_char('}')
+  }
+}
+
+/// https://drafts.csswg.org/css-counter-styles-3/#counter-style-system
+#[derive(Debug, Clone, PartialEq)]
+pub enum System {
+  Cyclic,
+  Numeric,
+  Alphabetic,
+  Symbolic,
+  Additive,
+  Fixed(Option<i32>),
+  Extends(CustomIdent)
+}
+
+impl Parse for System {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let ident = input.expect_ident_cloned()?;
+    match_ignore_ascii_case! { &*ident,
+      "cyclic" => Ok(System::Cyclic),
+      "numeric" => Ok(System::Numeric),
+      "alphabetic" => Ok(System::Alphabetic),
+      "symbolic" => Ok(System::Symbolic),
+      "additive" => Ok(System::Additive),
+      "fixed" => {
+        let first = input.try_parse(|input| input.expect_integer()).ok();
+        Ok(System::Fixed(first))
+      },
+      "extends" => {
+        // Any counter style except `none` may be extended, including the predefined ones.
+        let location = input.current_source_location();
+        let name = CustomIdent::parse(input)?;
+        if name.0.eq_ignore_ascii_case("none") {
+          return Err(location.new_custom_error(ParserError::InvalidValue))
+        }
+        Ok(System::Extends(name))
+      },
+      _ => Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+    }
+  }
+}
+
+impl ToCss for System {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      System::Cyclic => dest.write_str("cyclic"),
+      System::Numeric => dest.write_str("numeric"),
+      System::Alphabetic => dest.write_str("alphabetic"),
+      System::Symbolic => dest.write_str("symbolic"),
+      System::Additive => dest.write_str("additive"),
+      System::Fixed(first) => {
+        dest.write_str("fixed")?;
+        // The first symbol value defaults to 1.
+        match first {
+          Some(first) if *first != 1 => write!(dest, " {}", first),
+          _ => Ok(())
+        }
+      }
+      System::Extends(name) => {
+        dest.write_str("extends ")?;
+        name.to_css(dest)
+      }
+    }
+  }
+}
+
+/// https://drafts.csswg.org/css-counter-styles-3/#typedef-symbol
+#[derive(Debug, Clone, PartialEq)]
+pub enum Symbol {
+  String(String),
+  Image(Image),
+  Ident(CustomIdent)
+}
+
+impl Parse for Symbol {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(s) = input.try_parse(|input| input.expect_string_cloned()) {
+      return Ok(Symbol::String(s.as_ref().into()))
+    }
+
+    if let Ok(ident) = input.try_parse(CustomIdent::parse) {
+      return Ok(Symbol::Ident(ident))
+    }
+
+    let image = Image::parse(input)?;
+    Ok(Symbol::Image(image))
+  }
+}
+
+impl ToCss for Symbol {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      Symbol::String(s) => serialize_string(&s, dest),
+      Symbol::Image(image) => image.to_css(dest),
+      Symbol::Ident(ident) => ident.to_css(dest)
+    }
+  }
+}
+
+fn parse_symbols<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Vec<Symbol>, ParseError<'i, ParserError<'i>>> {
+  let mut symbols = vec![Symbol::parse(input)?];
+  while let Ok(symbol) = input.try_parse(Symbol::parse) {
+    symbols.push(symbol);
+  }
+  Ok(symbols)
+}
+
+pub(crate) fn serialize_symbols<W>(symbols: &Vec<Symbol>, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+  let mut prev: Option<&Symbol> = None;
+  for symbol in symbols {
+    if let Some(prev) = prev {
+      // Strings are self delimiting, so whitespace is not needed next to them when minifying.
+      let needs_space = !dest.minify ||
+        !(matches!(prev, Symbol::String(_)) || matches!(symbol, Symbol::String(_)));
+      if needs_space {
+        dest.write_char(' ')?;
+      }
+    }
+    symbol.to_css(dest)?;
+    prev = Some(symbol);
+  }
+  Ok(())
+}
+
+/// https://drafts.csswg.org/css-counter-styles-3/#descdef-counter-style-additive-symbols
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdditiveSymbol {
+  pub weight: u32,
+  pub symbol: Symbol
+}
+
+impl Parse for AdditiveSymbol {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let weight = input.try_parse(parse_non_negative_integer);
+    let symbol = Symbol::parse(input)?;
+    let weight = match weight {
+      Ok(weight) => weight,
+      Err(_) => parse_non_negative_integer(input)?
+    };
+    Ok(AdditiveSymbol { weight, symbol })
+  }
+}
+
+impl ToCss for AdditiveSymbol {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    write!(dest, "{}", self.weight)?;
+    if !dest.minify || !matches!(self.symbol, Symbol::String(_)) {
+      dest.write_char(' ')?;
+    }
+    self.symbol.to_css(dest)
+  }
+}
+
+fn parse_non_negative_integer<'i, 't>(input: &mut Parser<'i, 't>) -> Result<u32, ParseError<'i, ParserError<'i>>> {
+  let location = input.current_source_location();
+  let value = input.expect_integer()?;
+  if value < 0 {
+    return Err(location.new_custom_error(ParserError::InvalidValue))
+  }
+  Ok(value as u32)
+}
+
+/// https://drafts.csswg.org/css-counter-styles-3/#counter-style-range
+#[derive(Debug, Clone, PartialEq)]
+pub enum CounterRange {
+  Auto,
+  Ranges(Vec<(CounterRangeBound, CounterRangeBound)>)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CounterRangeBound {
+  Integer(i32),
+  Infinite
+}
+
+impl Parse for CounterRangeBound {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("infinite")).is_ok() {
+      return Ok(CounterRangeBound::Infinite)
+    }
+
+    Ok(CounterRangeBound::Integer(input.expect_integer()?))
+  }
+}
+
+impl ToCss for CounterRangeBound {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      CounterRangeBound::Integer(v) => write!(dest, "{}", v),
+      CounterRangeBound::Infinite => dest.write_str("infinite")
+    }
+  }
+}
+
+impl Parse for CounterRange {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if input.try_parse(|input| input.expect_ident_matching("auto")).is_ok() {
+      return Ok(CounterRange::Auto)
+    }
+
+    let ranges = input.parse_comma_separated(|input| {
+      let location = input.current_source_location();
+      let start = CounterRangeBound::parse(input)?;
+      let end = CounterRangeBound::parse(input)?;
+      // The lower bound must not be greater than the upper bound.
+      if let (CounterRangeBound::Integer(start), CounterRangeBound::Integer(end)) = (&start, &end) {
+        if start > end {
+          return Err(location.new_custom_error(ParserError::InvalidValue))
+        }
+      }
+      Ok((start, end))
+    })?;
+    Ok(CounterRange::Ranges(ranges))
+  }
+}
+
+impl ToCss for CounterRange {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      CounterRange::Auto => dest.write_str("auto"),
+      CounterRange::Ranges(ranges) => {
+        let mut first = true;
+        for (start, end) in ranges {
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          start.to_css(dest)?;
+          dest.write_char(' ')?;
+          end.to_css(dest)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+enum_property!(SpeakAsKeyword,
+  ("auto", Auto),
+  ("bullets", Bullets),
+  ("numbers", Numbers),
+  ("words", Words),
+  ("spell-out", SpellOut)
+);
+
+/// https://drafts.csswg.org/css-counter-styles-3/#counter-style-speak-as
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpeakAs {
+  Keyword(SpeakAsKeyword),
+  CounterStyle(CustomIdent)
+}
+
+impl Parse for SpeakAs {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(keyword) = input.try_parse(SpeakAsKeyword::parse) {
+      return Ok(SpeakAs::Keyword(keyword))
+    }
+
+    Ok(SpeakAs::CounterStyle(CustomIdent::parse(input)?))
+  }
+}
+
+impl ToCss for SpeakAs {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      SpeakAs::Keyword(keyword) => keyword.to_css(dest),
+      SpeakAs::CounterStyle(name) => name.to_css(dest)
+    }
+  }
+}
+
+pub struct CounterStyleDeclarationParser;
+
+/// Parse a declaration within {} block: `system: cyclic`
+impl<'i> cssparser::DeclarationParser<'i> for CounterStyleDeclarationParser {
+  type Declaration = CounterStyleProperty;
+  type Error = ParserError<'i>;
+
+  fn parse_value<'t>(
+      &mut self,
+      name: CowRcStr<'i>,
+      input: &mut cssparser::Parser<'i, 't>,
+  ) -> Result<Self::Declaration, cssparser::ParseError<'i, Self::Error>> {
+    macro_rules! property {
+      ($property: ident, $type: ident) => {
+        if let Ok(c) = input.try_parse(|input| input.parse_entirely($type::parse)) {
+          return Ok(CounterStyleProperty::$property(c))
+        }
+      };
+    }
+
+    let state = input.state();
+    match_ignore_ascii_case! { &name,
+      "system" => property!(System, System),
+      "symbols" => {
+        if let Ok(symbols) = input.try_parse(|input| input.parse_entirely(parse_symbols)) {
+          return Ok(CounterStyleProperty::Symbols(symbols))
+        }
+      },
+      "additive-symbols" => {
+        if let Ok(symbols) = input.try_parse(|input| input.parse_entirely(|input| input.parse_comma_separated(AdditiveSymbol::parse))) {
+          return Ok(CounterStyleProperty::AdditiveSymbols(symbols))
+        }
+      },
+      "negative" => {
+        if let Ok((start, end)) = input.try_parse(|input| input.parse_entirely(|input| {
+          let start = Symbol::parse(input)?;
+          let end = input.try_parse(Symbol::parse).ok();
+          Ok((start, end))
+        })) {
+          return Ok(CounterStyleProperty::Negative(start, end))
+        }
+      },
+      "prefix" => property!(Prefix, Symbol),
+      "suffix" => property!(Suffix, Symbol),
+      "range" => property!(Range, CounterRange),
+      "pad" => {
+        if let Ok((pad, symbol)) = input.try_parse(|input| input.parse_entirely(|input| {
+          let pad = input.try_parse(parse_non_negative_integer);
+          let symbol = Symbol::parse(input)?;
+          let pad = match pad {
+            Ok(pad) => pad,
+            Err(_) => parse_non_negative_integer(input)?
+          };
+          Ok((pad, symbol))
+        })) {
+          return Ok(CounterStyleProperty::Pad(pad, symbol))
+        }
+      },
+      "fallback" => {
+        if let Ok(fallback) = input.try_parse(|input| input.parse_entirely(CustomIdent::parse)) {
+          return Ok(CounterStyleProperty::Fallback(fallback))
+        }
+      },
+      "speak-as" => property!(SpeakAs, SpeakAs),
+      _ => {}
+    }
+
+    // Unknown descriptors, and known descriptors with invalid values, are dropped.
+    input.reset(&state);
+    Err(input.new_custom_error(ParserError::InvalidValue))
+  }
+}
+
+/// Default methods reject all at rules.
+impl<'i> AtRuleParser<'i> for CounterStyleDeclarationParser {
+  type PreludeNoBlock = ();
+  type PreludeBlock = ();
+  type AtRule = CounterStyleProperty;
+  type Error = ParserError<'i>;
+}
+
+impl ToCss for CounterStyleProperty {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    use CounterStyleProperty::*;
+    macro_rules! property {
+      ($prop: literal, $value: expr) => {{
+        dest.write_str($prop)?;
+        dest.delim(':', false)?;
+        $value.to_css(dest)
+      }};
+    }
+
+    match self {
+      System(value) => property!("system", value),
+      Symbols(symbols) => {
+        dest.write_str("symbols")?;
+        dest.delim(':', false)?;
+        serialize_symbols(symbols, dest)
+      }
+      AdditiveSymbols(symbols) => {
+        dest.write_str("additive-symbols")?;
+        dest.delim(':', false)?;
+        let mut first = true;
+        for symbol in symbols {
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          symbol.to_css(dest)?;
+        }
+        Ok(())
+      }
+      Negative(start, end) => {
+        dest.write_str("negative")?;
+        dest.delim(':', false)?;
+        start.to_css(dest)?;
+        if let Some(end) = end {
+          if !dest.minify || !(matches!(start, Symbol::String(_)) || matches!(end, Symbol::String(_))) {
+            dest.write_char(' ')?;
+          }
+          end.to_css(dest)?;
+        }
+        Ok(())
+      }
+      Prefix(value) => property!("prefix", value),
+      Suffix(value) => property!("suffix", value),
+      Range(value) => property!("range", value),
+      Pad(pad, symbol) => {
+        dest.write_str("pad")?;
+        dest.delim(':', false)?;
+        write!(dest, "{}", pad)?;
+        if !dest.minify || !matches!(symbol, Symbol::String(_)) {
+          dest.write_char(' ')?;
+        }
+        symbol.to_css(dest)
+      }
+      Fallback(value) => property!("fallback", value),
+      SpeakAs(value) => property!("speak-as", value),
+    }
+  }
+}
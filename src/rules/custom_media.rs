@@ -0,0 +1,39 @@
+use cssparser::*;
+use crate::media_query::MediaList;
+use crate::traits::ToCss;
+use crate::printer::Printer;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// https://drafts.csswg.org/mediaqueries-5/#custom-mq
+#[derive(Debug, Clone, PartialEq)]
+pub struct CustomMediaRule {
+  pub name: String,
+  pub query: MediaList
+}
+
+impl CustomMediaRule {
+  pub fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let name = input.expect_ident()?.as_ref().to_owned();
+    if !name.starts_with("--") {
+      return Err(input.new_custom_error(ParserError::AtRulePreludeInvalid))
+    }
+
+    let query = MediaList::parse(input);
+    if query.media_queries.is_empty() {
+      return Err(input.new_custom_error(ParserError::AtRulePreludeInvalid))
+    }
+
+    Ok(CustomMediaRule { name, query })
+  }
+}
+
+impl ToCss for CustomMediaRule {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    dest.write_str("@custom-media ")?;
+    serialize_identifier(&self.name, dest)?;
+    dest.write_char(' ')?;
+    self.query.to_css(dest)?;
+    dest.write_char(';')
+  }
+}
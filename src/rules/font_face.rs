@@ -6,9 +6,11 @@ use crate::printer::Printer;
 use crate::properties::font::{FontFamily, FontStyle, FontWeight, FontStretch};
 use crate::properties::custom::CustomProperty;
 use crate::macros::enum_property;
+use crate::values::url::Url;
 use std::fmt::Write;
+use crate::error::ParserError;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct FontFaceRule {
   pub properties: Vec<FontFaceProperty>
 }
@@ -31,7 +33,7 @@ pub enum Source {
 }
 
 impl Parse for Source {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(url) = input.try_parse(UrlSource::parse) {
       return Ok(Source::Url(url))
     }
@@ -57,13 +59,13 @@ impl ToCss for Source {
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct UrlSource {
-  pub url: String,
+  pub url: Url,
   pub format: Option<Format>
 }
 
 impl Parse for UrlSource {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
-    let url = input.expect_url()?.as_ref().to_owned();
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let url = Url::parse(input)?;
 
     let format = if input.try_parse(|input| input.expect_function_matching("format")).is_ok() {
       Some(input.parse_nested_block(Format::parse)?)
@@ -77,8 +79,7 @@ impl Parse for UrlSource {
 
 impl ToCss for UrlSource {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
-    use cssparser::ToCss;
-    Token::UnquotedUrl(CowRcStr::from(self.url.as_ref())).to_css(dest)?;
+    self.url.to_css(dest)?;
     if let Some(format) = &self.format {
       dest.whitespace()?;
       dest.write_str("format(")?;
@@ -96,7 +97,7 @@ pub struct Format {
 }
 
 impl Parse for Format {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let format = FontFormat::parse(input)?;
     let mut supports = vec![];
     if input.try_parse(|input| input.expect_ident_matching("supports")).is_ok() {
@@ -145,7 +146,7 @@ pub enum FontFormat {
 }
 
 impl Parse for FontFormat {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let s = input.expect_ident_or_string()?;
     match_ignore_ascii_case! { &s,
       "woff" => Ok(FontFormat::WOFF),
@@ -200,7 +201,7 @@ pub enum FontTechnology {
 }
 
 impl Parse for FontTechnology {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let location = input.current_source_location();
     match input.next()? {
       Token::Function(f) => {
@@ -250,7 +251,7 @@ pub struct FontFaceDeclarationParser;
 /// Parse a declaration within {} block: `color: blue`
 impl<'i> cssparser::DeclarationParser<'i> for FontFaceDeclarationParser {
   type Declaration = FontFaceProperty;
-  type Error = ();
+  type Error = ParserError<'i>;
 
   fn parse_value<'t>(
       &mut self,
@@ -289,7 +290,7 @@ impl<'i> AtRuleParser<'i> for FontFaceDeclarationParser {
   type PreludeNoBlock = ();
   type PreludeBlock = ();
   type AtRule = FontFaceProperty;
-  type Error = ();
+  type Error = ParserError<'i>;
 }
 
 impl ToCss for FontFaceRule {
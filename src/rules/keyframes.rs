@@ -2,17 +2,60 @@ use cssparser::*;
 use crate::values::percentage::Percentage;
 use crate::traits::{Parse, ToCss};
 use crate::parser::{PropertyDeclarationParser, DeclarationBlock};
+use crate::declaration::Declaration;
 use crate::properties::VendorPrefix;
 use crate::printer::Printer;
 use std::fmt::Write;
+use crate::error::ParserError;
 
-#[derive(Debug, PartialEq)]
+/// How to handle `@keyframes` rules that share a name, e.g. in concatenated files.
+/// Rules with different vendor prefixes are not considered duplicates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DuplicateKeyframes {
+  /// Keep all of the rules. Browsers only use the last one.
+  Keep,
+  /// Remove all but the last rule, which browsers use.
+  RemoveEarlier,
+  /// Give earlier rules unique names, and rename the animations that refer to them,
+  /// i.e. those that are declared before the next rule with the same name.
+  Rename
+}
+
+impl Default for DuplicateKeyframes {
+  fn default() -> DuplicateKeyframes {
+    DuplicateKeyframes::Keep
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct KeyframesRule {
   pub name: String,
   pub keyframes: Vec<Keyframe>,
   pub vendor_prefix: VendorPrefix
 }
 
+impl KeyframesRule {
+  /// Removes duplicate selectors within each keyframe, and merges adjacent keyframes
+  /// with identical declarations into a single keyframe with both selectors.
+  pub fn merge_keyframes(&mut self) {
+    let mut keyframes: Vec<Keyframe> = Vec::with_capacity(self.keyframes.len());
+    for mut keyframe in self.keyframes.drain(..) {
+      if let Some(last) = keyframes.last_mut() {
+        if last.declarations == keyframe.declarations {
+          last.selectors.extend(keyframe.selectors);
+          last.dedup_selectors();
+          continue
+        }
+      }
+
+      keyframe.dedup_selectors();
+      keyframes.push(keyframe);
+    }
+
+    self.keyframes = keyframes;
+  }
+}
+
 impl ToCss for KeyframesRule {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
     let mut first_rule = true;
@@ -34,6 +77,8 @@ impl ToCss for KeyframesRule {
           dest.whitespace()?;
           dest.write_char('{')?;
           dest.indent();
+          // Old versions of WebKit ignore unprefixed properties within prefixed keyframes.
+          let webkit = VendorPrefix::$prefix == VendorPrefix::WebKit && dest.targets.is_some();
           let mut first = true;
           for keyframe in &self.keyframes {
             if first {
@@ -42,7 +87,11 @@ impl ToCss for KeyframesRule {
               dest.write_char('\n')?; // no indent
             }
             dest.newline()?;
-            keyframe.to_css(dest)?;
+            if webkit {
+              keyframe.for_vendor(VendorPrefix::WebKit).to_css(dest)?;
+            } else {
+              keyframe.to_css(dest)?;
+            }
           }
           dest.dedent();
           dest.newline()?;
@@ -60,15 +109,33 @@ impl ToCss for KeyframesRule {
 }
 
 /// https://drafts.csswg.org/css-animations/#typedef-keyframe-selector
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone)]
 pub enum KeyframeSelector {
   Percentage(Percentage),
   From,
   To
 }
 
+impl KeyframeSelector {
+  /// Returns the offset of the keyframe, which is used to compare selectors.
+  /// `from` is equivalent to `0%`, and `to` is equivalent to `100%`.
+  pub fn key(&self) -> Percentage {
+    match self {
+      KeyframeSelector::Percentage(p) => p.clone(),
+      KeyframeSelector::From => Percentage(0.0),
+      KeyframeSelector::To => Percentage(1.0)
+    }
+  }
+}
+
+impl PartialEq for KeyframeSelector {
+  fn eq(&self, other: &KeyframeSelector) -> bool {
+    self.key() == other.key()
+  }
+}
+
 impl Parse for KeyframeSelector {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(val) = input.try_parse(Percentage::parse) {
       return Ok(KeyframeSelector::Percentage(val))
     }
@@ -107,12 +174,40 @@ impl ToCss for KeyframeSelector {
   }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Keyframe {
   pub selectors: Vec<KeyframeSelector>,
   pub declarations: DeclarationBlock
 }
 
+impl Keyframe {
+  fn dedup_selectors(&mut self) {
+    let mut selectors: Vec<KeyframeSelector> = Vec::with_capacity(self.selectors.len());
+    for selector in self.selectors.drain(..) {
+      if !selectors.contains(&selector) {
+        selectors.push(selector);
+      }
+    }
+    self.selectors = selectors;
+  }
+
+  /// Returns a copy of the keyframe for use within keyframes prefixed for the given vendor,
+  /// where properties use the vendor's prefix.
+  fn for_vendor(&self, prefix: VendorPrefix) -> Keyframe {
+    Keyframe {
+      selectors: self.selectors.clone(),
+      declarations: DeclarationBlock {
+        declarations: self.declarations.declarations.iter().filter_map(|decl| {
+          Some(Declaration {
+            property: decl.property.for_vendor(prefix)?,
+            important: decl.important
+          })
+        }).collect()
+      }
+    }
+  }
+}
+
 impl ToCss for Keyframe {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
     let mut first = true;
@@ -124,7 +219,7 @@ impl ToCss for Keyframe {
       selector.to_css(dest)?;
     }
     
-    self.declarations.to_css(dest)
+    dest.with_animated(true, |dest| self.declarations.to_css(dest))
   }
 }
 
@@ -134,18 +229,18 @@ impl<'a, 'i> AtRuleParser<'i> for KeyframeListParser {
   type PreludeNoBlock = ();
   type PreludeBlock = ();
   type AtRule = Keyframe;
-  type Error = ();
+  type Error = ParserError<'i>;
 }
 
 impl<'a, 'i> QualifiedRuleParser<'i> for KeyframeListParser {
   type Prelude = Vec<KeyframeSelector>;
   type QualifiedRule = Keyframe;
-  type Error = ();
+  type Error = ParserError<'i>;
 
   fn parse_prelude<'t>(
     &mut self,
     input: &mut Parser<'i, 't>,
-  ) -> Result<Self::Prelude, ParseError<'i, ()>> {
+  ) -> Result<Self::Prelude, ParseError<'i, ParserError<'i>>> {
     input.parse_comma_separated(KeyframeSelector::parse)
   }
 
@@ -154,12 +249,16 @@ impl<'a, 'i> QualifiedRuleParser<'i> for KeyframeListParser {
     selectors: Self::Prelude,
     start: &ParserState,
     input: &mut Parser<'i, 't>,
-  ) -> Result<Self::QualifiedRule, ParseError<'i, ()>> {
+  ) -> Result<Self::QualifiedRule, ParseError<'i, ParserError<'i>>> {
     let mut parser = DeclarationListParser::new(input, PropertyDeclarationParser);
     let mut declarations = vec![];
     while let Some(decl) = parser.next() {
       if let Ok(decl) = decl {
-        declarations.push(decl);
+        // Declarations marked !important are ignored within keyframes.
+        // https://drafts.csswg.org/css-animations-1/#keyframes
+        if !decl.important {
+          declarations.push(decl);
+        }
       }
     }
     Ok(Keyframe {
@@ -1,3 +1,8 @@
 pub mod keyframes;
 pub mod font_face;
 pub mod page;
+pub mod property;
+pub mod counter_style;
+pub mod custom_media;
+pub mod unknown;
+pub mod supports;
@@ -5,9 +5,10 @@ use crate::parser::{PropertyDeclarationParser, DeclarationBlock};
 use crate::printer::Printer;
 use crate::macros::enum_property;
 use std::fmt::Write;
+use crate::error::ParserError;
 
 /// https://www.w3.org/TR/css-page-3/#typedef-page-selector
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PageSelector {
   name: Option<String>,
   pseudo_classes: Vec<PagePseudoClass>
@@ -22,7 +23,7 @@ enum_property!(PagePseudoClass,
 );
 
 impl Parse for PageSelector {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let name = input.try_parse(|input| input.expect_ident_cloned()).ok().map(|s| s.as_ref().to_owned());
     let mut pseudo_classes = vec![];
     
@@ -51,7 +52,7 @@ impl Parse for PageSelector {
   }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PageRule {
   pub selectors: Vec<PageSelector>,
   pub declarations: DeclarationBlock
@@ -0,0 +1,475 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::values::{
+  length::{Length, LengthPercentage},
+  percentage::Percentage,
+  color::CssColor,
+  image::Image,
+  url::Url,
+  angle::Angle,
+  time::Time,
+  resolution::Resolution,
+  ident::CustomIdent
+};
+use crate::properties::transform::{Transform, TransformList};
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// https://drafts.css-houdini.org/css-properties-values-api/#at-property-rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyRule {
+  pub name: String,
+  pub syntax: SyntaxString,
+  pub inherits: bool,
+  pub initial_value: Option<ParsedComponent>
+}
+
+impl PropertyRule {
+  pub fn parse<'i, 't>(name: String, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut parser = DeclarationListParser::new(input, PropertyRuleDeclarationParser::default());
+    // Unknown and invalid descriptors are ignored.
+    while let Some(_) = parser.next() {}
+
+    let descriptors = parser.parser;
+    let location = input.current_source_location();
+
+    // The syntax and inherits descriptors are required.
+    let syntax = match descriptors.syntax {
+      Some(syntax) => syntax,
+      None => return Err(location.new_custom_error(ParserError::AtRuleBodyInvalid))
+    };
+
+    let inherits = match descriptors.inherits {
+      Some(inherits) => inherits,
+      None => return Err(location.new_custom_error(ParserError::AtRuleBodyInvalid))
+    };
+
+    // The initial value is only optional for the universal syntax, and must
+    // match the declared syntax otherwise.
+    let initial_value = match descriptors.initial_value {
+      Some(initial_value) => {
+        let mut input = ParserInput::new(&initial_value);
+        let mut parser = Parser::new(&mut input);
+        match syntax.parse_value(&mut parser) {
+          Ok(value) if value.is_computationally_independent() => Some(value),
+          _ => return Err(location.new_custom_error(ParserError::AtRuleBodyInvalid))
+        }
+      }
+      None if syntax == SyntaxString::Universal => None,
+      None => return Err(location.new_custom_error(ParserError::AtRuleBodyInvalid))
+    };
+
+    Ok(PropertyRule {
+      name,
+      syntax,
+      inherits,
+      initial_value
+    })
+  }
+}
+
+impl ToCss for PropertyRule {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    dest.write_str("@property ")?;
+    serialize_identifier(&self.name, dest)?;
+    dest.whitespace()?;
+    dest.write_char('{')?;
+    dest.indent();
+    dest.newline()?;
+
+    dest.write_str("syntax")?;
+    dest.delim(':', false)?;
+    self.syntax.to_css(dest)?;
+    dest.write_char(';')?;
+    dest.newline()?;
+
+    dest.write_str("inherits")?;
+    dest.delim(':', false)?;
+    dest.write_str(if self.inherits { "true" } else { "false" })?;
+
+    if let Some(initial_value) = &self.initial_value {
+      dest.write_char(';')?;
+      dest.newline()?;
+      dest.write_str("initial-value")?;
+      dest.delim(':', false)?;
+      initial_value.to_css(dest)?;
+    }
+
+    if !dest.minify {
+      dest.write_char(';')?;
+    }
+
+    dest.dedent();
+    dest.newline()?;
+    dest.write_char('}')
+  }
+}
+
+#[derive(Default)]
+struct PropertyRuleDeclarationParser {
+  syntax: Option<SyntaxString>,
+  inherits: Option<bool>,
+  initial_value: Option<String>
+}
+
+impl<'i> cssparser::DeclarationParser<'i> for PropertyRuleDeclarationParser {
+  type Declaration = ();
+  type Error = ParserError<'i>;
+
+  fn parse_value<'t>(
+      &mut self,
+      name: CowRcStr<'i>,
+      input: &mut cssparser::Parser<'i, 't>,
+  ) -> Result<Self::Declaration, cssparser::ParseError<'i, Self::Error>> {
+    match_ignore_ascii_case! { &name,
+      "syntax" => {
+        self.syntax = Some(SyntaxString::parse(input)?);
+      },
+      "inherits" => {
+        let location = input.current_source_location();
+        let ident = input.expect_ident()?;
+        self.inherits = Some(match_ignore_ascii_case! { &*ident,
+          "true" => true,
+          "false" => false,
+          _ => return Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+        });
+      },
+      "initial-value" => {
+        // The syntax may not be known yet, so the value is parsed once the whole block has been read.
+        input.skip_whitespace();
+        let start = input.position();
+        while input.next().is_ok() {}
+        self.initial_value = Some(input.slice_from(start).trim_end().into());
+      },
+      _ => return Err(input.new_custom_error(ParserError::InvalidValue))
+    }
+
+    Ok(())
+  }
+}
+
+/// Default methods reject all at rules.
+impl<'i> AtRuleParser<'i> for PropertyRuleDeclarationParser {
+  type PreludeNoBlock = ();
+  type PreludeBlock = ();
+  type AtRule = ();
+  type Error = ParserError<'i>;
+}
+
+/// https://drafts.css-houdini.org/css-properties-values-api/#syntax-strings
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxString {
+  Components(Vec<SyntaxComponent>),
+  Universal
+}
+
+/// https://drafts.css-houdini.org/css-properties-values-api/#syntax-component
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxComponent {
+  pub kind: SyntaxComponentKind,
+  pub multiplier: Multiplier
+}
+
+/// https://drafts.css-houdini.org/css-properties-values-api/#supported-names
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyntaxComponentKind {
+  Length,
+  Number,
+  Percentage,
+  LengthPercentage,
+  Color,
+  Image,
+  Url,
+  Integer,
+  Angle,
+  Time,
+  Resolution,
+  TransformFunction,
+  TransformList,
+  CustomIdent,
+  Literal(String)
+}
+
+/// https://drafts.css-houdini.org/css-properties-values-api/#multipliers
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Multiplier {
+  None,
+  Space,
+  Comma
+}
+
+impl Parse for SyntaxString {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let string = input.expect_string_cloned()?;
+    SyntaxString::parse_string(&string).map_err(|_| location.new_custom_error(ParserError::InvalidValue))
+  }
+}
+
+impl SyntaxString {
+  pub fn parse_string(input: &str) -> Result<SyntaxString, ()> {
+    // https://drafts.css-houdini.org/css-properties-values-api/#parsing-syntax
+    let input = input.trim_matches(is_whitespace);
+    if input.is_empty() {
+      return Err(())
+    }
+
+    if input == "*" {
+      return Ok(SyntaxString::Universal)
+    }
+
+    let mut components = vec![];
+    for component in input.split('|') {
+      components.push(SyntaxComponent::parse_string(component.trim_matches(is_whitespace))?);
+    }
+
+    Ok(SyntaxString::Components(components))
+  }
+
+  pub fn parse_value<'i, 't>(&self, input: &mut Parser<'i, 't>) -> Result<ParsedComponent, ParseError<'i, ParserError<'i>>> {
+    match self {
+      SyntaxString::Universal => {
+        input.skip_whitespace();
+        let start = input.position();
+        while input.next_including_whitespace_and_comments().is_ok() {}
+        Ok(ParsedComponent::Token(input.slice_from(start).trim_end().into()))
+      }
+      SyntaxString::Components(components) => {
+        // The first component that matches the entire input wins.
+        for component in components {
+          let state = input.state();
+          if let Ok(value) = input.parse_entirely(|input| component.parse_value(input)) {
+            return Ok(value)
+          }
+          input.reset(&state);
+        }
+
+        Err(input.new_custom_error(ParserError::InvalidValue))
+      }
+    }
+  }
+}
+
+impl ToCss for SyntaxString {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    dest.write_char('"')?;
+    match self {
+      SyntaxString::Universal => dest.write_char('*')?,
+      SyntaxString::Components(components) => {
+        let mut first = true;
+        for component in components {
+          if first {
+            first = false;
+          } else {
+            dest.delim('|', true)?;
+          }
+          component.to_css(dest)?;
+        }
+      }
+    }
+    dest.write_char('"')
+  }
+}
+
+impl SyntaxComponent {
+  fn parse_string(input: &str) -> Result<SyntaxComponent, ()> {
+    let (input, multiplier) = if let Some(input) = input.strip_suffix('+') {
+      (input, Multiplier::Space)
+    } else if let Some(input) = input.strip_suffix('#') {
+      (input, Multiplier::Comma)
+    } else {
+      (input, Multiplier::None)
+    };
+
+    let kind = if let Some(name) = input.strip_prefix('<') {
+      let name = name.strip_suffix('>').ok_or(())?;
+      match_ignore_ascii_case! { name,
+        "length" => SyntaxComponentKind::Length,
+        "number" => SyntaxComponentKind::Number,
+        "percentage" => SyntaxComponentKind::Percentage,
+        "length-percentage" => SyntaxComponentKind::LengthPercentage,
+        "color" => SyntaxComponentKind::Color,
+        "image" => SyntaxComponentKind::Image,
+        "url" => SyntaxComponentKind::Url,
+        "integer" => SyntaxComponentKind::Integer,
+        "angle" => SyntaxComponentKind::Angle,
+        "time" => SyntaxComponentKind::Time,
+        "resolution" => SyntaxComponentKind::Resolution,
+        "transform-function" => SyntaxComponentKind::TransformFunction,
+        "transform-list" => SyntaxComponentKind::TransformList,
+        "custom-ident" => SyntaxComponentKind::CustomIdent,
+        _ => return Err(())
+      }
+    } else {
+      // Literal idents must be valid, non-CSS-wide custom idents.
+      let mut parser_input = ParserInput::new(input);
+      let mut parser = Parser::new(&mut parser_input);
+      let ident = parser.parse_entirely(CustomIdent::parse).map_err(|_| ())?;
+      SyntaxComponentKind::Literal(ident.0)
+    };
+
+    // <transform-list> is a pre-multiplied data type name.
+    if kind == SyntaxComponentKind::TransformList && multiplier != Multiplier::None {
+      return Err(())
+    }
+
+    Ok(SyntaxComponent { kind, multiplier })
+  }
+
+  fn parse_value<'i, 't>(&self, input: &mut Parser<'i, 't>) -> Result<ParsedComponent, ParseError<'i, ParserError<'i>>> {
+    match self.multiplier {
+      Multiplier::None => self.kind.parse_value(input),
+      Multiplier::Space => {
+        let mut values = vec![self.kind.parse_value(input)?];
+        loop {
+          input.skip_whitespace();
+          if input.is_exhausted() {
+            break
+          }
+          values.push(self.kind.parse_value(input)?);
+        }
+        Ok(ParsedComponent::Repeated(values, Multiplier::Space))
+      }
+      Multiplier::Comma => {
+        let values = input.parse_comma_separated(|input| self.kind.parse_value(input))?;
+        Ok(ParsedComponent::Repeated(values, Multiplier::Comma))
+      }
+    }
+  }
+}
+
+impl ToCss for SyntaxComponent {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    use SyntaxComponentKind::*;
+    let name = match &self.kind {
+      Length => "<length>",
+      Number => "<number>",
+      Percentage => "<percentage>",
+      LengthPercentage => "<length-percentage>",
+      Color => "<color>",
+      Image => "<image>",
+      Url => "<url>",
+      Integer => "<integer>",
+      Angle => "<angle>",
+      Time => "<time>",
+      Resolution => "<resolution>",
+      TransformFunction => "<transform-function>",
+      TransformList => "<transform-list>",
+      CustomIdent => "<custom-ident>",
+      Literal(ident) => ident
+    };
+    dest.write_str(name)?;
+
+    match self.multiplier {
+      Multiplier::None => Ok(()),
+      Multiplier::Space => dest.write_char('+'),
+      Multiplier::Comma => dest.write_char('#')
+    }
+  }
+}
+
+impl SyntaxComponentKind {
+  fn parse_value<'i, 't>(&self, input: &mut Parser<'i, 't>) -> Result<ParsedComponent, ParseError<'i, ParserError<'i>>> {
+    Ok(match self {
+      SyntaxComponentKind::Length => ParsedComponent::Length(Length::parse(input)?),
+      SyntaxComponentKind::Number => ParsedComponent::Number(f32::parse(input)?),
+      SyntaxComponentKind::Percentage => ParsedComponent::Percentage(Percentage::parse(input)?),
+      SyntaxComponentKind::LengthPercentage => ParsedComponent::LengthPercentage(LengthPercentage::parse(input)?),
+      SyntaxComponentKind::Color => ParsedComponent::Color(CssColor::parse(input)?),
+      SyntaxComponentKind::Image => ParsedComponent::Image(Image::parse(input)?),
+      SyntaxComponentKind::Url => ParsedComponent::Url(Url::parse(input)?),
+      SyntaxComponentKind::Integer => ParsedComponent::Integer(input.expect_integer()?),
+      SyntaxComponentKind::Angle => ParsedComponent::Angle(Angle::parse(input)?),
+      SyntaxComponentKind::Time => ParsedComponent::Time(Time::parse(input)?),
+      SyntaxComponentKind::Resolution => ParsedComponent::Resolution(Resolution::parse(input)?),
+      SyntaxComponentKind::TransformFunction => ParsedComponent::TransformFunction(Transform::parse(input)?),
+      SyntaxComponentKind::TransformList => ParsedComponent::TransformList(TransformList::parse(input)?),
+      SyntaxComponentKind::CustomIdent => ParsedComponent::CustomIdent(CustomIdent::parse(input)?),
+      SyntaxComponentKind::Literal(value) => {
+        let location = input.current_source_location();
+        let ident = input.expect_ident()?;
+        if ident.as_ref() != value.as_str() {
+          return Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+        }
+        ParsedComponent::Literal(value.clone())
+      }
+    })
+  }
+}
+
+/// A value matching a syntax string, e.g. the initial value of a registered property.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedComponent {
+  Length(Length),
+  Number(f32),
+  Percentage(Percentage),
+  LengthPercentage(LengthPercentage),
+  Color(CssColor),
+  Image(Image),
+  Url(Url),
+  Integer(i32),
+  Angle(Angle),
+  Time(Time),
+  Resolution(Resolution),
+  TransformFunction(Transform),
+  TransformList(TransformList),
+  CustomIdent(CustomIdent),
+  Literal(String),
+  Repeated(Vec<ParsedComponent>, Multiplier),
+  Token(String)
+}
+
+impl ParsedComponent {
+  /// https://drafts.css-houdini.org/css-properties-values-api/#computationally-independent
+  fn is_computationally_independent(&self) -> bool {
+    match self {
+      ParsedComponent::Length(length) => length.to_px().is_some(),
+      ParsedComponent::LengthPercentage(LengthPercentage::Dimension(length)) => length.to_px().is_some(),
+      ParsedComponent::Repeated(values, _) => values.iter().all(|v| v.is_computationally_independent()),
+      _ => true
+    }
+  }
+}
+
+impl ToCss for ParsedComponent {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    use ParsedComponent::*;
+    match self {
+      Length(v) => v.to_css(dest),
+      Number(v) => v.to_css(dest),
+      Percentage(v) => v.to_css(dest),
+      LengthPercentage(v) => v.to_css(dest),
+      Color(v) => v.to_css(dest),
+      Image(v) => v.to_css(dest),
+      Url(v) => v.to_css(dest),
+      Integer(v) => write!(dest, "{}", v),
+      Angle(v) => v.to_css(dest),
+      Time(v) => v.to_css(dest),
+      Resolution(v) => v.to_css(dest),
+      TransformFunction(v) => v.to_css(dest),
+      TransformList(v) => v.to_css(dest),
+      CustomIdent(v) => v.to_css(dest),
+      Literal(v) => serialize_identifier(v, dest),
+      Repeated(values, multiplier) => {
+        let mut first = true;
+        for value in values {
+          if first {
+            first = false;
+          } else if *multiplier == Multiplier::Comma {
+            dest.delim(',', false)?;
+          } else {
+            dest.write_char(' ')?;
+          }
+          value.to_css(dest)?;
+        }
+        Ok(())
+      }
+      Token(v) => dest.write_str(v)
+    }
+  }
+}
+
+fn is_whitespace(c: char) -> bool {
+  matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0C')
+}
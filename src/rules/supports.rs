@@ -0,0 +1,44 @@
+use cssparser::*;
+use crate::parser::CssRule;
+use crate::traits::ToCss;
+use crate::printer::Printer;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// https://drafts.csswg.org/css-conditional-3/#at-supports
+/// The condition is kept as source text, since it may test for features this crate does not know about.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SupportsRule {
+  pub condition: String,
+  pub rules: Vec<CssRule>
+}
+
+impl SupportsRule {
+  /// Parses the condition of a `@supports` rule, up to its block.
+  pub fn parse_condition<'i, 't>(input: &mut Parser<'i, 't>) -> Result<String, ParseError<'i, ParserError<'i>>> {
+    let start = input.position();
+    while input.next_including_whitespace_and_comments().is_ok() {}
+    let condition = input.slice_from(start).trim();
+    if condition.is_empty() {
+      return Err(input.new_custom_error(ParserError::AtRulePreludeInvalid))
+    }
+    Ok(condition.to_owned())
+  }
+}
+
+impl ToCss for SupportsRule {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    dest.write_str("@supports ")?;
+    dest.write_str(&self.condition)?;
+    dest.whitespace()?;
+    dest.write_char('{')?;
+    dest.indent();
+    for rule in self.rules.iter() {
+      dest.newline()?;
+      rule.to_css(dest)?;
+    }
+    dest.dedent();
+    dest.newline()?;
+    dest.write_char('}')
+  }
+}
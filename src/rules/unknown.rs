@@ -0,0 +1,78 @@
+use cssparser::*;
+use crate::traits::ToCss;
+use crate::printer::Printer;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// An at-rule that is not otherwise supported, e.g. `@tailwind base;`, `@apply` within a style
+/// rule, or a rule from a newer specification. The prelude and block are kept as source text,
+/// so that the rule is printed as it was written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnknownAtRule {
+  pub name: String,
+  pub prelude: String,
+  /// The contents of the `{}` block, or `None` if the rule ends with a semicolon.
+  pub block: Option<String>
+}
+
+impl UnknownAtRule {
+  /// Parses the rest of an at-rule with the given name, up to and including its block or semicolon.
+  pub fn parse<'i, 't>(name: &str, input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let prelude = input.parse_until_before(Delimiter::Semicolon | Delimiter::CurlyBracketBlock, |input| {
+      let start = input.position();
+      while input.next_including_whitespace_and_comments().is_ok() {}
+      Ok::<_, ParseError<'i, ParserError<'i>>>(input.slice_from(start).trim().to_owned())
+    })?;
+
+    let block = match input.next() {
+      Ok(Token::CurlyBracketBlock) => Some(input.parse_nested_block(|input| {
+        let start = input.position();
+        while input.next_including_whitespace_and_comments().is_ok() {}
+        Ok::<_, ParseError<'i, ParserError<'i>>>(input.slice_from(start).trim().to_owned())
+      })?),
+      _ => None
+    };
+
+    Ok(UnknownAtRule {
+      name: name.into(),
+      prelude,
+      block
+    })
+  }
+}
+
+impl ToCss for UnknownAtRule {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    self.to_css_terminated(dest, true)
+  }
+}
+
+impl UnknownAtRule {
+  /// Prints the rule. A rule without a block is only followed by a semicolon if `terminate`
+  /// is set, since it isn't needed before the end of the enclosing block.
+  pub(crate) fn to_css_terminated<W>(&self, dest: &mut Printer<W>, terminate: bool) -> std::fmt::Result where W: std::fmt::Write {
+    dest.write_char('@')?;
+    serialize_identifier(&self.name, dest)?;
+    if !self.prelude.is_empty() {
+      dest.write_char(' ')?;
+      dest.write_str(&self.prelude)?;
+    }
+
+    match &self.block {
+      Some(block) => {
+        dest.whitespace()?;
+        dest.write_char('{')?;
+        if !block.is_empty() {
+          dest.indent();
+          dest.newline()?;
+          dest.write_str(block)?;
+          dest.dedent();
+        }
+        dest.newline()?;
+        dest.write_char('}')
+      }
+      None if terminate => dest.write_char(';'),
+      None => Ok(())
+    }
+  }
+}
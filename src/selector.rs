@@ -5,6 +5,10 @@ use crate::printer::Printer;
 use crate::traits::ToCss;
 use std::fmt::Write;
 use super::parser::CssString;
+use crate::properties::VendorPrefix;
+use crate::properties::prefixes::{Browsers, Feature};
+use crate::compat;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Selectors;
@@ -24,11 +28,36 @@ impl SelectorImpl for Selectors {
   type ExtraMatchingData = ();
 }
 
-pub struct SelectorParser;
-impl<'i> selectors::parser::Parser<'i> for SelectorParser {
+/// The namespaces declared by @namespace rules, used to resolve prefixes in selectors.
+#[derive(Debug, Default)]
+pub struct Namespaces {
+  pub default: Option<String>,
+  pub prefixes: HashMap<String, String>
+}
+
+pub struct SelectorParser<'a> {
+  pub namespaces: &'a Namespaces
+}
+
+impl<'a, 'i> selectors::parser::Parser<'i> for SelectorParser<'a> {
   type Impl = Selectors;
   type Error = selectors::parser::SelectorParseErrorKind<'i>;
 
+  fn default_namespace(&self) -> Option<String> {
+    self.namespaces.default.clone()
+  }
+
+  /// Selectors using an undeclared prefix are invalid.
+  /// https://drafts.csswg.org/css-namespaces/#css-qnames
+  fn namespace_for_prefix(&self, prefix: &CssString) -> Option<String> {
+    let prefix: &String = std::borrow::Borrow::borrow(prefix);
+    self.namespaces.prefixes.get(prefix).cloned()
+  }
+
+  fn parse_is_and_where(&self) -> bool {
+    true
+  }
+
   fn parse_non_ts_pseudo_class(
     &self,
     location: SourceLocation,
@@ -68,6 +97,26 @@ impl<'i> selectors::parser::Parser<'i> for SelectorParser {
         "lang" => {
           Lang(parser.expect_ident_or_string()?.as_ref().into())
         },
+        "dir" => {
+          let location = parser.current_source_location();
+          let ident = parser.expect_ident()?;
+          match_ignore_ascii_case! { &*ident,
+            "ltr" => Dir(Direction::Ltr),
+            "rtl" => Dir(Direction::Rtl),
+            _ => return Err(location.new_custom_error(selectors::parser::SelectorParseErrorKind::UnexpectedIdent(ident.clone())))
+          }
+        },
+        "-internal-nesting" => Nesting,
+        "global" | "local" => {
+          let start = parser.position();
+          while parser.next_including_whitespace_and_comments().is_ok() {}
+          let selector = parser.slice_from(start).trim().to_owned();
+          if name.eq_ignore_ascii_case("global") {
+            Global(selector)
+          } else {
+            Local(selector)
+          }
+        },
         _ => return Err(parser.new_custom_error(selectors::parser::SelectorParseErrorKind::UnexpectedIdent(name.clone()))),
       };
 
@@ -86,6 +135,17 @@ impl<'i> selectors::parser::Parser<'i> for SelectorParser {
       "first-line" => FirstLine,
       "first-letter" => FirstLetter,
       "selection" => Selection,
+      "marker" => Marker,
+      "backdrop" => Backdrop(VendorPrefix::None),
+      "-webkit-backdrop" => Backdrop(VendorPrefix::WebKit),
+      "-ms-backdrop" => Backdrop(VendorPrefix::Ms),
+      "file-selector-button" => FileSelectorButton(VendorPrefix::None),
+      "-webkit-file-upload-button" => FileSelectorButton(VendorPrefix::WebKit),
+      "-ms-browse" => FileSelectorButton(VendorPrefix::Ms),
+      "placeholder" => Placeholder(VendorPrefix::None),
+      "-webkit-input-placeholder" => Placeholder(VendorPrefix::WebKit),
+      "-moz-placeholder" => Placeholder(VendorPrefix::Moz),
+      "-ms-input-placeholder" => Placeholder(VendorPrefix::Ms),
       _ => Custom(name.as_ref().into())
     };
 
@@ -107,12 +167,20 @@ pub enum PseudoClass {
   Hover,
   Indeterminate,
   Lang(Box<str>),
+  Dir(Direction),
   Link,
   PlaceholderShown,
   ReadWrite,
   ReadOnly,
   Target,
   Visited,
+  /// The nesting selector (`&`). https://drafts.csswg.org/css-nesting-1/#nest-selector
+  Nesting,
+  /// `:global()` in a CSS module, with the selector within it as written. The names in it
+  /// are not scoped to the file, and the pseudo class is removed when compiling the module.
+  Global(String),
+  /// `:local()` in a CSS module, which scopes the names in it like those outside of it.
+  Local(String),
   Custom(String)
 }
 
@@ -140,6 +208,19 @@ impl cssparser::ToCss for PseudoClass {
         return dest.write_str(")");
       }
 
+      if let Global(selector) | Local(selector) = self {
+        dest.write_str(if let Global(_) = self { ":global(" } else { ":local(" })?;
+        dest.write_str(selector)?;
+        return dest.write_char(')')
+      }
+
+      if let Dir(dir) = *self {
+        return dest.write_str(match dir {
+          Direction::Ltr => ":dir(ltr)",
+          Direction::Rtl => ":dir(rtl)"
+        })
+      }
+
       dest.write_str(match &self {
         Active => ":active",
         AnyLink => ":any-link",
@@ -157,7 +238,8 @@ impl cssparser::ToCss for PseudoClass {
         ReadOnly => ":read-only",
         Target => ":target",
         Visited => ":visited",
-        Lang(_) => unreachable!(),
+        Nesting => "&",
+        Lang(_) | Dir(_) | Global(_) | Local(_) => unreachable!(),
         Custom(val) => {
           dest.write_char(':')?;
           return dest.write_str(&val)
@@ -166,6 +248,12 @@ impl cssparser::ToCss for PseudoClass {
   }
 }
 
+/// https://drafts.csswg.org/selectors-4/#the-dir-pseudo
+#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+pub enum Direction {
+  Ltr,
+  Rtl
+}
 
 #[derive(PartialEq, Eq, Clone, Debug, Hash)]
 pub enum PseudoElement {
@@ -174,9 +262,58 @@ pub enum PseudoElement {
   FirstLine,
   FirstLetter,
   Selection,
+  Marker,
+  /// The prefix is that of the legacy alias, e.g. `::-ms-backdrop`.
+  Backdrop(VendorPrefix),
+  /// The prefix is that of the legacy alias: `::-webkit-file-upload-button` or `::-ms-browse`.
+  FileSelectorButton(VendorPrefix),
+  /// The prefix is that of the legacy alias: `::-webkit-input-placeholder`, `::-moz-placeholder`
+  /// or `::-ms-input-placeholder`.
+  Placeholder(VendorPrefix),
   Custom(String)
 }
 
+impl PseudoElement {
+  /// Returns the prefixes of the legacy aliases for this pseudo element that the targets need.
+  pub fn legacy_prefixes(&self, targets: Browsers) -> VendorPrefix {
+    let mut prefixes = match self {
+      PseudoElement::Backdrop(VendorPrefix::None) => {
+        let mut prefixes = Feature::PseudoElementBackdrop.prefixes_for(targets);
+        if targets.ie.is_some() {
+          prefixes |= VendorPrefix::Ms;
+        }
+        prefixes
+      }
+      PseudoElement::FileSelectorButton(VendorPrefix::None) => {
+        if compat::Feature::FileSelectorButton.is_compatible(targets) {
+          return VendorPrefix::empty()
+        }
+
+        let mut prefixes = VendorPrefix::WebKit;
+        if targets.ie.is_some() || targets.edge.map_or(false, |version| version < 79 << 16) {
+          prefixes |= VendorPrefix::Ms;
+        }
+        prefixes
+      }
+      PseudoElement::Placeholder(VendorPrefix::None) => Feature::PseudoElementPlaceholder.prefixes_for(targets),
+      _ => VendorPrefix::empty()
+    };
+
+    prefixes.remove(VendorPrefix::None);
+    prefixes
+  }
+
+  /// Returns whether this is a legacy alias, which is only understood by some browsers.
+  fn is_legacy_alias(&self) -> bool {
+    match self {
+      PseudoElement::Backdrop(prefix) |
+      PseudoElement::FileSelectorButton(prefix) |
+      PseudoElement::Placeholder(prefix) => *prefix != VendorPrefix::None,
+      _ => false
+    }
+  }
+}
+
 impl cssparser::ToCss for PseudoElement {
   fn to_css<W>(&self, dest: &mut W) -> fmt::Result
   where
@@ -192,6 +329,23 @@ impl cssparser::ToCss for PseudoElement {
       FirstLine => ":first-line",
       FirstLetter => ":first-letter",
       Selection => "::selection",
+      Marker => "::marker",
+      Backdrop(prefix) => match *prefix {
+        VendorPrefix::WebKit => "::-webkit-backdrop",
+        VendorPrefix::Ms => "::-ms-backdrop",
+        _ => "::backdrop"
+      },
+      FileSelectorButton(prefix) => match *prefix {
+        VendorPrefix::WebKit => "::-webkit-file-upload-button",
+        VendorPrefix::Ms => "::-ms-browse",
+        _ => "::file-selector-button"
+      },
+      Placeholder(prefix) => match *prefix {
+        VendorPrefix::WebKit => "::-webkit-input-placeholder",
+        VendorPrefix::Moz => "::-moz-placeholder",
+        VendorPrefix::Ms => "::-ms-input-placeholder",
+        _ => "::placeholder"
+      },
       Custom(val) => {
         dest.write_str("::")?;
         return dest.write_str(val)
@@ -396,6 +550,16 @@ impl ToCss for Component<Selectors> {
         }
         dest.write_char(']')
       },
+      // Level 3 only supports a single selector within :not(), so expand the list into
+      // a chain of negations if the targets don't support level 4, e.g. :not(.a):not(.b).
+      Negation(ref list) if list.len() > 1 && dest.targets.map_or(false, |targets| !compat::Feature::NotSelectorList.is_compatible(targets)) => {
+        for selector in list.iter() {
+          dest.write_str(":not(")?;
+          selector.to_css(dest)?;
+          dest.write_str(")")?;
+        }
+        Ok(())
+      },
       Is(ref list) | Where(ref list) | Negation(ref list) => {
         match *self {
           Where(..) => dest.write_str(":where(")?,
@@ -427,3 +591,149 @@ where
   }
   Ok(())
 }
+
+/// Returns whether a selector list only contains pseudo classes and pseudo elements that
+/// are known to be valid. Browsers drop an entire rule if any selector in it is invalid,
+/// so it is unsafe to combine unknown or vendor prefixed selectors with other selectors.
+pub fn is_safe_to_merge(selectors: &SelectorList<Selectors>) -> bool {
+  selectors.0.iter().all(is_safe_selector)
+}
+
+fn is_safe_selector(selector: &Selector<Selectors>) -> bool {
+  selector.iter_raw_match_order().all(|component| {
+    match component {
+      Component::NonTSPseudoClass(PseudoClass::Custom(_)) |
+      Component::NonTSPseudoClass(PseudoClass::Global(_)) |
+      Component::NonTSPseudoClass(PseudoClass::Local(_)) |
+      Component::PseudoElement(PseudoElement::Custom(_)) => false,
+      Component::PseudoElement(pseudo_element) if pseudo_element.is_legacy_alias() => false,
+      Component::Negation(list) | Component::Is(list) | Component::Where(list) => {
+        list.iter().all(is_safe_selector)
+      }
+      _ => true
+    }
+  })
+}
+
+/// Parses a single compound selector, e.g. `.btn` or `a.btn:hover`, for use with `contains_compound`.
+pub fn parse_compound_selector(source: &str, namespaces: &Namespaces) -> Option<Selector<Selectors>> {
+  let mut selectors = parse_selector_list(source, namespaces)?;
+  if selectors.0.len() != 1 {
+    return None
+  }
+
+  let selector = selectors.0.remove(0);
+  if compound_selectors(&selector).len() != 1 {
+    return None
+  }
+
+  Some(selector)
+}
+
+/// Returns whether any compound selector within the selector contains all of the simple
+/// selectors in the given compound selector, e.g. `.btn` is contained in `.nav > .btn.primary`.
+pub fn contains_compound(selector: &Selector<Selectors>, compound: &Selector<Selectors>) -> bool {
+  let query = &compound_selectors(compound)[0];
+  compound_selectors(selector).iter().any(|components| {
+    query.iter().all(|component| components.contains(component))
+  })
+}
+
+/// Splits a selector into its compound selectors. Pseudo elements are part of the
+/// compound selector they follow.
+fn compound_selectors(selector: &Selector<Selectors>) -> Vec<Vec<&Component<Selectors>>> {
+  let mut compounds = vec![vec![]];
+  for component in selector.iter_raw_match_order() {
+    match component {
+      Component::Combinator(combinator) if !combinator.is_pseudo_element() => compounds.push(vec![]),
+      Component::Combinator(_) => {}
+      component => compounds.last_mut().unwrap().push(component)
+    }
+  }
+  compounds
+}
+
+pub fn parse_selector_list(source: &str, namespaces: &Namespaces) -> Option<SelectorList<Selectors>> {
+  let mut input = ParserInput::new(source);
+  let mut parser = Parser::new(&mut input);
+  SelectorList::parse(&SelectorParser { namespaces }, &mut parser).ok()
+}
+
+/// Returns the selectors restricted to elements with the given direction. The `:dir()` pseudo
+/// class is used if the targets support it. Otherwise, or if a selector ends with a pseudo element,
+/// which pseudo classes cannot follow, a `[dir]` attribute selector for an ancestor is used instead.
+/// This only matches if the direction is set with the `dir` attribute, e.g. on the root element.
+pub fn direction_selectors(selectors: &SelectorList<Selectors>, direction: Direction, use_dir_pseudo: bool, namespaces: &Namespaces) -> Option<SelectorList<Selectors>> {
+  let dir = match direction {
+    Direction::Ltr => "ltr",
+    Direction::Rtl => "rtl"
+  };
+
+  let result: Vec<String> = selectors.0.iter().map(|selector| {
+    let source = selector.to_css_string();
+    if use_dir_pseudo && !selector.has_pseudo_element() {
+      format!("{}:dir({})", source, dir)
+    } else {
+      format!("[dir=\"{}\"] {}", dir, source)
+    }
+  }).collect();
+
+  parse_selector_list(&result.join(","), namespaces)
+}
+
+/// Returns whether any of the selectors contain a legacy alias of a pseudo element, e.g.
+/// `::-ms-browse`. These are only understood by the browsers that need them, so rules
+/// containing them are never merged with other rules.
+pub fn has_legacy_pseudo_element(selectors: &SelectorList<Selectors>) -> bool {
+  selectors.0.iter().any(|selector| {
+    selector.iter_raw_match_order().any(|component| {
+      matches!(component, Component::PseudoElement(pseudo_element) if pseudo_element.is_legacy_alias())
+    })
+  })
+}
+
+/// Returns the prefixes of the legacy pseudo element aliases that the targets need for the selectors.
+pub fn legacy_pseudo_element_prefixes(selectors: &SelectorList<Selectors>, targets: Browsers) -> VendorPrefix {
+  let mut prefixes = VendorPrefix::empty();
+  for selector in &selectors.0 {
+    for component in selector.iter_raw_match_order() {
+      if let Component::PseudoElement(pseudo_element) = component {
+        prefixes |= pseudo_element.legacy_prefixes(targets);
+      }
+    }
+  }
+  prefixes
+}
+
+/// Returns the selectors that contain a pseudo element with a legacy alias for the given prefix,
+/// with the pseudo element replaced by the alias. Returns `None` if there are none.
+pub fn legacy_pseudo_element_selectors(selectors: &SelectorList<Selectors>, prefix: VendorPrefix, namespaces: &Namespaces) -> Option<SelectorList<Selectors>> {
+  let result: Vec<String> = selectors.0.iter().filter_map(|selector| {
+    let pseudo_element = selector.iter_raw_match_order().find_map(|component| match component {
+      Component::PseudoElement(pseudo_element @ PseudoElement::Backdrop(VendorPrefix::None)) |
+      Component::PseudoElement(pseudo_element @ PseudoElement::FileSelectorButton(VendorPrefix::None)) |
+      Component::PseudoElement(pseudo_element @ PseudoElement::Placeholder(VendorPrefix::None)) => Some(pseudo_element),
+      _ => None
+    })?;
+
+    let alias = match pseudo_element {
+      PseudoElement::Backdrop(_) => PseudoElement::Backdrop(prefix),
+      PseudoElement::Placeholder(_) => PseudoElement::Placeholder(prefix),
+      _ => PseudoElement::FileSelectorButton(prefix)
+    };
+
+    // The pseudo element is in the last compound selector, so it is the last occurrence
+    // of its serialization, even if an attribute selector contains the same text.
+    let mut source = selector.to_css_string();
+    let name = cssparser::ToCss::to_css_string(pseudo_element);
+    let index = source.rfind(&name)?;
+    source.replace_range(index..index + name.len(), &cssparser::ToCss::to_css_string(&alias));
+    Some(source)
+  }).collect();
+
+  if result.is_empty() {
+    return None
+  }
+
+  parse_selector_list(&result.join(","), namespaces)
+}
@@ -0,0 +1,261 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A source map, following the v3 format.
+/// https://sourcemaps.info/spec.html
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SourceMap {
+  pub sources: Vec<String>,
+  /// The contents of each source, if included in the map.
+  pub sources_content: Vec<Option<String>>,
+  /// The mappings, sorted by their generated position.
+  pub mappings: Vec<Mapping>
+}
+
+/// A position in the generated output, and the position in a source it was generated from.
+/// Lines and columns start at 0.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mapping {
+  pub generated_line: u32,
+  pub generated_column: u32,
+  pub original: Option<OriginalLocation>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OriginalLocation {
+  /// The index of the source in `sources`.
+  pub source: u32,
+  pub line: u32,
+  pub column: u32
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SourceMapError {
+  /// The source map is not valid JSON, or is missing required fields.
+  Json(String),
+  /// The source map is not a v3 source map. Index maps with `sections` are not supported.
+  UnsupportedVersion,
+  /// The `mappings` field contains an invalid VLQ value, or refers to a source that does not exist.
+  InvalidMappings
+}
+
+impl fmt::Display for SourceMapError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      SourceMapError::Json(message) => write!(f, "Invalid source map: {}", message),
+      SourceMapError::UnsupportedVersion => f.write_str("Unsupported source map version"),
+      SourceMapError::InvalidMappings => f.write_str("Invalid source map mappings")
+    }
+  }
+}
+
+impl std::error::Error for SourceMapError {}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawSourceMap {
+  version: u8,
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  file: Option<String>,
+  #[serde(default, skip_serializing)]
+  source_root: Option<String>,
+  sources: Vec<Option<String>>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  sources_content: Vec<Option<String>>,
+  #[serde(default)]
+  names: Vec<String>,
+  mappings: String
+}
+
+impl SourceMap {
+  /// Parses a v3 source map, e.g. one emitted by a preprocessor such as Sass.
+  pub fn from_json(json: &str) -> Result<SourceMap, SourceMapError> {
+    let raw: RawSourceMap = serde_json::from_str(json).map_err(|err| SourceMapError::Json(err.to_string()))?;
+    if raw.version != 3 {
+      return Err(SourceMapError::UnsupportedVersion)
+    }
+
+    let source_root = raw.source_root.unwrap_or_default();
+    let sources: Vec<String> = raw.sources.into_iter().map(|source| {
+      let source = source.unwrap_or_default();
+      if source_root.is_empty() {
+        source
+      } else if source_root.ends_with('/') {
+        format!("{}{}", source_root, source)
+      } else {
+        format!("{}/{}", source_root, source)
+      }
+    }).collect();
+
+    let mappings = decode_mappings(&raw.mappings, sources.len())?;
+    Ok(SourceMap {
+      sources,
+      sources_content: raw.sources_content,
+      mappings
+    })
+  }
+
+  /// Serializes the source map to JSON, with the given name of the generated file.
+  pub fn to_json(&self, file: Option<&str>) -> String {
+    let raw = RawSourceMap {
+      version: 3,
+      file: file.map(|file| file.into()),
+      source_root: None,
+      sources: self.sources.iter().map(|source| Some(source.clone())).collect(),
+      sources_content: self.sources_content.clone(),
+      names: vec![],
+      mappings: encode_mappings(&self.mappings)
+    };
+    serde_json::to_string(&raw).unwrap()
+  }
+
+  /// Returns the mapping that covers the given generated position, i.e. the last one on the
+  /// same line that starts at or before the column.
+  pub fn find_closest_mapping(&self, line: u32, column: u32) -> Option<&Mapping> {
+    let index = self.mappings.partition_point(|mapping| {
+      (mapping.generated_line, mapping.generated_column) <= (line, column)
+    });
+    if index == 0 {
+      return None
+    }
+
+    let mapping = &self.mappings[index - 1];
+    if mapping.generated_line != line {
+      return None
+    }
+
+    Some(mapping)
+  }
+
+  /// Remaps the original positions of this map, which refer to the output of a previous build
+  /// step, through that step's source map, so that they refer to the authored sources instead.
+  /// Positions that the input map does not cover are left unmapped.
+  pub fn chain(&mut self, input: &SourceMap) {
+    for mapping in self.mappings.iter_mut() {
+      if let Some(original) = mapping.original {
+        mapping.original = input.find_closest_mapping(original.line, original.column).and_then(|m| m.original);
+      }
+    }
+
+    self.sources = input.sources.clone();
+    self.sources_content = input.sources_content.clone();
+  }
+}
+
+const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn encode_vlq(value: i64, dest: &mut String) {
+  let mut vlq = if value < 0 { ((-value) << 1) | 1 } else { value << 1 };
+  loop {
+    let mut digit = vlq & 0b11111;
+    vlq >>= 5;
+    if vlq > 0 {
+      digit |= 0b100000;
+    }
+    dest.push(BASE64[digit as usize] as char);
+    if vlq == 0 {
+      break
+    }
+  }
+}
+
+fn decode_vlq(chars: &mut std::iter::Peekable<std::str::Bytes>) -> Result<i64, SourceMapError> {
+  let mut value = 0i64;
+  let mut shift = 0;
+  loop {
+    let c = chars.next().ok_or(SourceMapError::InvalidMappings)?;
+    let digit = BASE64.iter().position(|b| *b == c).ok_or(SourceMapError::InvalidMappings)? as i64;
+    if shift > 60 {
+      return Err(SourceMapError::InvalidMappings)
+    }
+    value |= (digit & 0b11111) << shift;
+    shift += 5;
+    if digit & 0b100000 == 0 {
+      break
+    }
+  }
+
+  Ok(if value & 1 == 1 { -(value >> 1) } else { value >> 1 })
+}
+
+fn encode_mappings(mappings: &[Mapping]) -> String {
+  let mut dest = String::new();
+  let mut line = 0;
+  let mut column = 0;
+  let mut source = 0;
+  let mut original_line = 0;
+  let mut original_column = 0;
+  let mut first = true;
+  for mapping in mappings {
+    if mapping.generated_line != line {
+      while line < mapping.generated_line {
+        dest.push(';');
+        line += 1;
+      }
+      column = 0;
+    } else if !first {
+      dest.push(',');
+    }
+    first = false;
+
+    encode_vlq(mapping.generated_column as i64 - column as i64, &mut dest);
+    column = mapping.generated_column;
+    if let Some(original) = mapping.original {
+      encode_vlq(original.source as i64 - source as i64, &mut dest);
+      encode_vlq(original.line as i64 - original_line as i64, &mut dest);
+      encode_vlq(original.column as i64 - original_column as i64, &mut dest);
+      source = original.source;
+      original_line = original.line;
+      original_column = original.column;
+    }
+  }
+
+  dest
+}
+
+fn decode_mappings(mappings: &str, source_count: usize) -> Result<Vec<Mapping>, SourceMapError> {
+  let mut res = vec![];
+  let mut source = 0i64;
+  let mut original_line = 0i64;
+  let mut original_column = 0i64;
+  for (line, segments) in mappings.split(';').enumerate() {
+    let mut column = 0i64;
+    for segment in segments.split(',').filter(|segment| !segment.is_empty()) {
+      let mut chars = segment.bytes().peekable();
+      column += decode_vlq(&mut chars)?;
+      let original = if chars.peek().is_some() {
+        source += decode_vlq(&mut chars)?;
+        original_line += decode_vlq(&mut chars)?;
+        original_column += decode_vlq(&mut chars)?;
+        // Names are not used, since CSS has no identifiers that are renamed.
+        if chars.peek().is_some() {
+          decode_vlq(&mut chars)?;
+        }
+        if source < 0 || source as usize >= source_count || original_line < 0 || original_column < 0 {
+          return Err(SourceMapError::InvalidMappings)
+        }
+        Some(OriginalLocation {
+          source: source as u32,
+          line: original_line as u32,
+          column: original_column as u32
+        })
+      } else {
+        None
+      };
+
+      if column < 0 || chars.peek().is_some() {
+        return Err(SourceMapError::InvalidMappings)
+      }
+
+      res.push(Mapping {
+        generated_line: line as u32,
+        generated_column: column as u32,
+        original
+      });
+    }
+  }
+
+  // Segments within a line are not required to be sorted.
+  res.sort_by_key(|mapping| (mapping.generated_line, mapping.generated_column));
+  Ok(res)
+}
@@ -0,0 +1,155 @@
+use selectors::parser::{Selector, Component};
+use crate::parser::CssRule;
+use crate::declaration::Declaration;
+use crate::selector::Selectors;
+use crate::traits::ToCss;
+use std::collections::HashMap;
+
+/// Statistics about a stylesheet, e.g. for reporting tools.
+#[derive(Debug, Default, PartialEq)]
+pub struct StyleSheetStats {
+  /// The number of style rules, including nested rules.
+  pub style_rules: usize,
+  /// The number of declarations, including those in `@keyframes` and `@page` rules.
+  pub declarations: usize,
+  pub selectors: SelectorCounts,
+  /// The number of declarations of each property, most used first. Vendor prefixed
+  /// properties are counted with their unprefixed name.
+  pub properties: Vec<(String, usize)>,
+  /// The number of selectors with each specificity, from lowest to highest.
+  pub specificity: Vec<(Specificity, usize)>,
+  /// Each distinct media query, along with the number of `@media` rules using it,
+  /// in order of first appearance.
+  pub media_queries: Vec<(String, usize)>
+}
+
+/// The number of selectors in style rules, and of each type of simple selector within them.
+/// Simple selectors within `:not()`, `:is()`, and `:where()` are included.
+#[derive(Debug, Default, PartialEq)]
+pub struct SelectorCounts {
+  pub selectors: usize,
+  pub type_selectors: usize,
+  pub universal: usize,
+  pub id: usize,
+  pub class: usize,
+  pub attribute: usize,
+  pub pseudo_class: usize,
+  pub pseudo_element: usize
+}
+
+/// https://drafts.csswg.org/selectors-4/#specificity-rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Specificity {
+  pub ids: u32,
+  pub classes: u32,
+  pub types: u32
+}
+
+impl Specificity {
+  fn from_selector(selector: &Selector<Selectors>) -> Specificity {
+    // The selectors crate packs each component into 10 bits.
+    let specificity = selector.specificity();
+    Specificity {
+      ids: specificity >> 20,
+      classes: (specificity >> 10) & 0x3ff,
+      types: specificity & 0x3ff
+    }
+  }
+}
+
+#[derive(Default)]
+struct StatsCollector {
+  stats: StyleSheetStats,
+  properties: HashMap<String, usize>,
+  specificity: HashMap<Specificity, usize>,
+  media_queries: Vec<(String, usize)>
+}
+
+/// Computes statistics about the given rules.
+pub fn collect_stats(rules: &[CssRule]) -> StyleSheetStats {
+  let mut collector = StatsCollector::default();
+  collector.visit_rules(rules);
+
+  let mut stats = collector.stats;
+  stats.properties = collector.properties.into_iter().collect();
+  stats.properties.sort_by(|(a_name, a_count), (b_name, b_count)| b_count.cmp(a_count).then_with(|| a_name.cmp(b_name)));
+  stats.specificity = collector.specificity.into_iter().collect();
+  stats.specificity.sort();
+  stats.media_queries = collector.media_queries;
+  stats
+}
+
+impl StatsCollector {
+  fn visit_rules(&mut self, rules: &[CssRule]) {
+    for rule in rules {
+      match rule {
+        CssRule::Style(style) => {
+          self.stats.style_rules += 1;
+          for selector in &style.selectors.0 {
+            self.stats.selectors.selectors += 1;
+            *self.specificity.entry(Specificity::from_selector(selector)).or_default() += 1;
+            self.visit_selector(selector);
+          }
+          self.visit_declarations(&style.declarations.declarations);
+          self.visit_rules(&style.rules);
+        }
+        CssRule::Media(media) => {
+          let query = media.query.to_css_string();
+          match self.media_queries.iter_mut().find(|(q, _)| *q == query) {
+            Some((_, count)) => *count += 1,
+            None => self.media_queries.push((query, 1))
+          }
+          self.visit_rules(&media.rules);
+        }
+        CssRule::Supports(supports) => self.visit_rules(&supports.rules),
+        CssRule::Keyframes(keyframes) => {
+          for keyframe in &keyframes.keyframes {
+            self.visit_declarations(&keyframe.declarations.declarations);
+          }
+        }
+        CssRule::Page(page) => self.visit_declarations(&page.declarations.declarations),
+        _ => {}
+      }
+    }
+  }
+
+  fn visit_selector(&mut self, selector: &Selector<Selectors>) {
+    let counts = &mut self.stats.selectors;
+    let mut nested = vec![];
+    for component in selector.iter_raw_match_order() {
+      match component {
+        Component::Combinator(_) |
+        Component::ExplicitAnyNamespace |
+        Component::ExplicitNoNamespace |
+        Component::DefaultNamespace(..) |
+        Component::Namespace(..) => {}
+        Component::ExplicitUniversalType => counts.universal += 1,
+        Component::LocalName(_) => counts.type_selectors += 1,
+        Component::ID(_) => counts.id += 1,
+        Component::Class(_) => counts.class += 1,
+        Component::AttributeInNoNamespaceExists { .. } |
+        Component::AttributeInNoNamespace { .. } |
+        Component::AttributeOther(_) => counts.attribute += 1,
+        Component::PseudoElement(_) |
+        Component::Slotted(_) |
+        Component::Part(_) => counts.pseudo_element += 1,
+        Component::Negation(list) | Component::Is(list) | Component::Where(list) => {
+          counts.pseudo_class += 1;
+          nested.extend(list.iter());
+        }
+        _ => counts.pseudo_class += 1
+      }
+    }
+
+    for selector in nested {
+      self.visit_selector(selector);
+    }
+  }
+
+  fn visit_declarations(&mut self, declarations: &[Declaration]) {
+    self.stats.declarations += declarations.len();
+    for declaration in declarations {
+      *self.properties.entry(declaration.property.name().to_owned()).or_default() += 1;
+    }
+  }
+}
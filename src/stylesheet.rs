@@ -0,0 +1,1060 @@
+use cssparser::{Parser, ParserInput, RuleListParser, DeclarationListParser, Token};
+use crate::parser::{TopLevelRuleParser, CssRule, parse_unknown_at_rule, StyleRule, MediaRule, DeclarationBlock, PropertyDeclarationParser};
+use crate::rules::keyframes::DuplicateKeyframes;
+use crate::selector::{Selectors, Namespaces, Direction, is_safe_to_merge, direction_selectors, parse_compound_selector, contains_compound, has_legacy_pseudo_element, legacy_pseudo_element_prefixes, legacy_pseudo_element_selectors};
+use selectors::parser::Selector;
+use crate::media_query::MediaList;
+use crate::traits::ToCss;
+use crate::printer::{Printer, PrinterOptions, PreserveComments};
+use crate::properties::{Property, VendorPrefix};
+use crate::properties::animation::rename_animations;
+use crate::properties::font::GenericFontFamily;
+use crate::properties::prefixes::{Browsers, Feature};
+use crate::declaration::{Declaration, DeclarationHandler, HandlerFactory};
+use crate::values::alpha::AlphaValue;
+use crate::compat;
+use crate::nesting;
+use crate::limits::{self, Limits, LimitError};
+use crate::warnings::{self, Warning, WarningKind};
+use crate::dependencies::{self, Dependency};
+use crate::stats::{self, StyleSheetStats};
+use crate::rename::{self, RenameKind};
+use crate::css_modules::{self, CssModuleExports};
+use crate::unused_symbols;
+use crate::cache::SerializationCache;
+use crate::value_hooks::{self, ValueHooks};
+use crate::custom_properties;
+use crate::source_map::{SourceMap, Mapping};
+use crate::macros::trace;
+use std::collections::{HashMap, HashSet};
+
+/// Options for parsing a stylesheet.
+#[derive(Default)]
+pub struct ParserOptions {
+  /// Limits on the size and nesting of the stylesheet. Constructs that exceed
+  /// the limits are dropped as invalid.
+  pub limits: Limits,
+  /// Custom units and functions to convert into standard values while parsing.
+  pub hooks: Option<ValueHooks>,
+  /// Whether to substitute references to custom media queries within `@media` rules,
+  /// and remove the `@custom-media` rules.
+  pub custom_media: bool,
+  /// The name of the file being parsed, which is used as the source in source maps.
+  pub filename: String,
+  /// The source map of the code being parsed, if it was generated by a previous build step,
+  /// e.g. Sass. Source maps generated when printing then refer to the authored sources.
+  pub input_source_map: Option<SourceMap>,
+  /// Whether to drop declarations with invalid values, as browsers do, rather than keeping them
+  /// as is. Invalid declarations and rules are reported in the stylesheet's `warnings`. Note
+  /// that values using syntax not yet supported by the parser are also considered invalid.
+  pub error_recovery: bool,
+  /// Whether to compile the stylesheet as a CSS module, scoping the classes, ids, `@keyframes`
+  /// and `@counter-style` names to the file. The generated names are returned in `exports`.
+  pub css_modules: bool
+}
+
+#[derive(Default)]
+pub struct MinifyOptions {
+  pub targets: Option<Browsers>,
+  /// Also merge adjacent rules with identical declarations if some of their selectors could be
+  /// invalid in some browsers, e.g. vendor prefixed pseudo elements. Such merges reduce output
+  /// size but can cause browsers to drop the merged rule, so they are off by default.
+  pub unsafe_merges: bool,
+  /// Custom property handlers, which take precedence over the built-in ones.
+  pub custom_handlers: Vec<HandlerFactory>,
+  /// How to handle `@keyframes` rules with the same name.
+  pub duplicate_keyframes: DuplicateKeyframes,
+  /// Generic font families to append to `font-family` lists that do not contain one, e.g.
+  /// `sans-serif`, so that text is never rendered in the browser's default font. Families
+  /// that are already in a list are not added again.
+  pub font_fallbacks: Vec<GenericFontFamily>,
+  /// Removes declarations of custom properties that are never referenced by a `var()` in the
+  /// stylesheet. This is unsafe if custom properties are read or set at runtime, e.g. by
+  /// JavaScript via `getPropertyValue()`, so it must be enabled explicitly.
+  pub remove_unused_custom_properties: bool,
+  /// Custom properties that are known to be referenced outside the stylesheet, e.g. by inline
+  /// styles or other stylesheets, and are kept when removing unused custom properties.
+  pub used_custom_properties: HashSet<String>,
+  /// Adds a declaration with opaque colors before each declaration that uses semi-transparent
+  /// colors, for targets that don't support alpha in colors at all, i.e. IE 8 and older.
+  /// The alpha is dropped, since the color cannot be blended with an unknown backdrop.
+  pub opaque_color_fallbacks: bool,
+  /// Adds `opacity: 1` to the `::-moz-placeholder` rules generated for older versions of Firefox,
+  /// which render placeholders with a reduced opacity by default, so that placeholders look the
+  /// same as in other browsers. Rules that already set the opacity are left as is.
+  pub placeholder_opacity: bool,
+  /// Classes and ids that are not used by the application, e.g. according to an analysis of its
+  /// templates. Selectors that require any of them to match are removed, along with rules that
+  /// become empty, and the `@keyframes` and `@font-face` rules that are then no longer referenced.
+  /// Names of `@keyframes` rules and font families in the set are removed as well.
+  pub unused_symbols: HashSet<String>
+}
+
+/// The result of printing a stylesheet.
+#[derive(Debug, PartialEq)]
+pub struct ToCssResult {
+  pub code: String,
+  /// The files referenced by the stylesheet via `@import` rules and `url()`s, in source order.
+  pub dependencies: Option<Vec<Dependency>>,
+  /// The source map, if `source_map` is set.
+  pub map: Option<SourceMap>,
+  /// The names generated for the classes, ids, `@keyframes` and `@counter-style` rules,
+  /// if the stylesheet was parsed with `css_modules`.
+  pub exports: Option<CssModuleExports>
+}
+
+#[derive(Debug, PartialEq, Default)]
+pub struct StyleSheet {
+  pub rules: Vec<CssRule>,
+  pub filename: String,
+  pub input_source_map: Option<SourceMap>,
+  /// The constructs that were skipped while parsing with `error_recovery`.
+  pub warnings: Vec<Warning>,
+  /// The names generated when parsing with `css_modules`.
+  pub exports: Option<CssModuleExports>
+}
+
+impl StyleSheet {
+  /// Parses a stylesheet with the given options.
+  pub fn parse(code: &str, options: ParserOptions) -> StyleSheet {
+    let limits = options.limits;
+    let hooks = options.hooks;
+    let filename = &options.filename;
+    let (custom_media, css_modules) = (options.custom_media, options.css_modules);
+    let parse = || {
+      let parse = || limits::with_limits(limits, || StyleSheet::parse_rules(code)).0;
+      let mut stylesheet = match hooks {
+        Some(hooks) => value_hooks::with_hooks(hooks, parse),
+        None => parse()
+      };
+
+      if custom_media {
+        stylesheet.substitute_custom_media();
+      }
+
+      // Within the closure, so that invalid `composes` declarations are reported as warnings.
+      if css_modules {
+        let namespaces = stylesheet.namespaces();
+        stylesheet.exports = Some(css_modules::scope_names(&mut stylesheet.rules, filename, &namespaces));
+      }
+      stylesheet
+    };
+    let (mut stylesheet, warnings) = if options.error_recovery {
+      warnings::recover(None, parse)
+    } else {
+      (parse(), vec![])
+    };
+
+    stylesheet.filename = options.filename;
+    stylesheet.input_source_map = options.input_source_map;
+    stylesheet.warnings = warnings;
+    stylesheet
+  }
+
+  /// Parses a stylesheet, returning an error if it exceeds any of the given limits.
+  pub fn parse_with_limits(code: &str, limits: Limits) -> Result<StyleSheet, LimitError> {
+    match limits::with_limits(limits, || StyleSheet::parse_rules(code)) {
+      (stylesheet, None) => Ok(stylesheet),
+      (_, Some(err)) => Err(err)
+    }
+  }
+
+  /// Parses a stylesheet using the default limits, and returns it along with any warnings
+  /// about likely mistakes, e.g. invalid values that browsers will ignore.
+  pub fn parse_with_warnings(code: &str) -> (StyleSheet, Vec<Warning>) {
+    let ((stylesheet, _), warnings) = warnings::collect(|| {
+      limits::with_limits(Limits::default(), || StyleSheet::parse_rules(code))
+    });
+    (stylesheet, warnings)
+  }
+
+  /// Parses a stylesheet using the default limits, converting the custom units and functions
+  /// registered with the hooks into standard values first.
+  pub fn parse_with_hooks(code: &str, hooks: &ValueHooks) -> StyleSheet {
+    StyleSheet::parse(code, ParserOptions {
+      hooks: Some(hooks.clone()),
+      ..ParserOptions::default()
+    })
+  }
+
+  /// Checks a stylesheet for problems, without minifying or printing it. Along with the
+  /// warnings reported by `parse_with_warnings`, this reports rules and values that could
+  /// not be parsed, and features that the targets don't support and can't be compiled for them.
+  pub fn validate(code: &str, targets: Option<Browsers>) -> Vec<Warning> {
+    let (_, warnings) = warnings::validate(targets, || {
+      limits::with_limits(Limits::default(), || StyleSheet::parse_rules(code))
+    });
+    warnings
+  }
+
+  pub(crate) fn parse_rules(code: &str) -> StyleSheet {
+    let code = code.strip_prefix('\u{feff}').unwrap_or(code);
+    let mut input = ParserInput::new(&code);
+    let mut parser = Parser::new(&mut input);
+    parse_charset(&mut parser);
+    let mut rule_list = RuleListParser::new_for_stylesheet(&mut parser, TopLevelRuleParser::new());
+
+    let mut rules = vec![];
+    loop {
+      // Comments are skipped by the rule list parser, so they are collected before each rule.
+      rules.extend(collect_comments(rule_list.input).into_iter().map(CssRule::Comment));
+      if let Some(rule) = parse_unknown_at_rule(rule_list.input) {
+        if limits::count_rule(rule_list.input).is_err() {
+          break
+        }
+        rules.push(rule);
+        continue
+      }
+
+      let exceeded_count = limits::exceeded_count();
+      let rule = match rule_list.next() {
+        Some(rule) => rule,
+        None => break
+      };
+
+      match rule {
+        Ok((_, rule)) => {
+          if limits::count_rule(rule_list.input).is_err() {
+            break
+          }
+          if rule.is_emptied_since(exceeded_count) {
+            continue
+          }
+          rules.push(rule);
+        }
+        Err((error, _)) => {
+          if warnings::is_validating() {
+            warnings::warn(error.location, WarningKind::InvalidRule);
+          }
+        }
+      }
+    }
+
+    StyleSheet { rules, ..StyleSheet::default() }
+  }
+
+  /// Substitutes references to custom media queries within `@media` rules, and removes
+  /// the `@custom-media` rules, which are not supported by any browsers. Definitions that
+  /// are still referenced because a reference could not be inlined are kept.
+  pub fn substitute_custom_media(&mut self) {
+    let mut custom_media = HashMap::new();
+    for rule in &self.rules {
+      if let CssRule::CustomMedia(rule) = rule {
+        custom_media.insert(rule.name.clone(), rule.query.clone());
+      }
+    }
+
+    let mut referenced = HashSet::new();
+    substitute_custom_media(&mut self.rules, &custom_media, &mut referenced);
+
+    // Definitions referenced by a kept definition must be kept as well.
+    let mut pending: Vec<String> = referenced.iter().cloned().collect();
+    while let Some(name) = pending.pop() {
+      if let Some(query) = custom_media.get(&name) {
+        let mut names = HashSet::new();
+        query.custom_media_references(&mut names);
+        for name in names {
+          if referenced.insert(name.clone()) {
+            pending.push(name);
+          }
+        }
+      }
+    }
+
+    self.rules.retain(|rule| match rule {
+      CssRule::CustomMedia(rule) => referenced.contains(&rule.name),
+      _ => true
+    });
+  }
+
+  pub fn minify(&mut self, options: MinifyOptions) {
+    let targets = options.targets;
+    let (mut handler, mut important_handler) = declaration_handlers(&options);
+    let mut keyframe_rules = HashMap::new();
+    let mut rules = vec![];
+
+    // Selectors are reparsed when flattening nested rules and compiling logical properties.
+    let namespaces = self.namespaces();
+
+    // Nested style rules are flattened for targets that don't support nesting.
+    if let Some(targets) = targets {
+      if !compat::Feature::CssNesting.is_compatible(targets) {
+        self.rules = nesting::flatten(std::mem::take(&mut self.rules), &namespaces);
+      }
+    }
+
+    // Nested @media rules, e.g. from preprocessor output, are combined into a single rule where possible.
+    self.rules = flatten_media(std::mem::take(&mut self.rules));
+
+    // Comments would prevent adjacent rules from being merged, so only license comments are kept.
+    self.rules.retain(|rule| !rule.is_omitted_comment(PreserveComments::License));
+
+    if !options.unused_symbols.is_empty() {
+      unused_symbols::remove_unused_symbols(&mut self.rules, &options.unused_symbols);
+    }
+
+    resolve_duplicate_keyframes(&mut self.rules, options.duplicate_keyframes);
+
+    // Only the last @namespace rule for each prefix (or the default namespace) has an effect.
+    let mut last_namespace_rules = HashMap::new();
+    for (i, rule) in self.rules.iter().enumerate() {
+      if let CssRule::Namespace(namespace) = rule {
+        last_namespace_rules.insert(namespace.prefix.clone(), i);
+      }
+    }
+
+    for (i, rule) in std::mem::take(&mut self.rules).into_iter().enumerate() {
+      let rule = match rule {
+        CssRule::Namespace(namespace) => {
+          if last_namespace_rules.get(&namespace.prefix) != Some(&i) {
+            continue
+          }
+          CssRule::Namespace(namespace)
+        }
+        CssRule::Keyframes(mut keyframes) => {
+          for keyframe in keyframes.keyframes.iter_mut() {
+            keyframe.declarations.minify(&mut handler, &mut important_handler);
+          }
+          keyframes.merge_keyframes();
+
+          macro_rules! set_prefix {
+            ($keyframes: ident) => {
+              if $keyframes.vendor_prefix.contains(VendorPrefix::None) {
+                if let Some(targets) = targets {
+                  $keyframes.vendor_prefix = Feature::AtKeyframes.prefixes_for(targets)
+                }
+              }
+            };
+          }
+
+          // If there is an existing rule with the same name and identical keyframes,
+          // merge the vendor prefixes from this rule into it.
+          if let Some(existing_idx) = keyframe_rules.get(&keyframes.name) {
+            if let Some(CssRule::Keyframes(existing)) = &mut rules.get_mut(*existing_idx) {
+              if existing.keyframes == keyframes.keyframes {
+                existing.vendor_prefix |= keyframes.vendor_prefix;
+                set_prefix!(existing);
+                continue;
+              }
+            }
+          }
+
+          set_prefix!(keyframes);
+          keyframe_rules.insert(keyframes.name.clone(), rules.len());
+          CssRule::Keyframes(keyframes)
+        }
+        CssRule::Media(mut media) => {
+          let was_empty = media.rules.is_empty();
+          media.rules = minify_style_rules(std::mem::take(&mut media.rules), &mut handler, &mut important_handler, &namespaces, options.unsafe_merges);
+          if media.rules.is_empty() && !was_empty {
+            continue
+          }
+
+          if let Some(targets) = targets {
+            media.query.transform_range_syntax(compat::Feature::MediaRangeSyntax.is_compatible(targets));
+          }
+
+          // Merge adjacent media rules with the same query.
+          if let Some(CssRule::Media(last_media_rule)) = rules.last_mut() {
+            if last_media_rule.query == media.query {
+              last_media_rule.rules.extend(media.rules);
+              continue
+            }
+          }
+
+          CssRule::Media(media)
+        }
+        CssRule::Supports(mut supports) => {
+          let was_empty = supports.rules.is_empty();
+          supports.rules = minify_style_rules(std::mem::take(&mut supports.rules), &mut handler, &mut important_handler, &namespaces, options.unsafe_merges);
+          if supports.rules.is_empty() && !was_empty {
+            continue
+          }
+          CssRule::Supports(supports)
+        }
+        r => r
+      };
+      rules.push(rule);
+    }
+
+    self.rules = minify_style_rules(rules, &mut handler, &mut important_handler, &namespaces, options.unsafe_merges);
+
+    if options.remove_unused_custom_properties {
+      custom_properties::remove_unused_custom_properties(&mut self.rules, &options.used_custom_properties);
+    }
+  }
+
+  /// Prints the stylesheet, along with the files it references if `analyze_dependencies` is set,
+  /// and a source map if `source_map` is set. Returns an error if a rule cannot be printed.
+  pub fn to_css(&self, options: PrinterOptions) -> Result<ToCssResult, std::fmt::Error> {
+    let mut dest = String::new();
+    write_banner(&options, &mut dest);
+    let mappings = self.print_rules(&options, &mut dest)?;
+    write_footer(&options, &mut dest);
+    Ok(ToCssResult {
+      code: dest,
+      dependencies: if options.analyze_dependencies {
+        Some(self.dependencies())
+      } else {
+        None
+      },
+      map: mappings.map(|mappings| {
+        let mut map = SourceMap {
+          sources: vec![self.filename.clone()],
+          sources_content: vec![],
+          mappings
+        };
+        if let Some(input_source_map) = &self.input_source_map {
+          map.chain(input_source_map);
+        }
+        map
+      }),
+      exports: self.exports.clone()
+    })
+  }
+
+  fn print_rules(&self, options: &PrinterOptions, dest: &mut String) -> Result<Option<Vec<Mapping>>, std::fmt::Error> {
+    // The output may already contain a banner.
+    let line = dest.matches('\n').count() as u32;
+    let column = dest[dest.rfind('\n').map_or(0, |pos| pos + 1)..].encode_utf16().count() as u32;
+    let mut printer = Printer::with_options(dest, options);
+    printer.line = line;
+    printer.column = column;
+
+    // The nested output style is only used when the targets support nesting, since
+    // otherwise the output would not be understood by the browser.
+    if options.nested && !options.minify && options.targets.map_or(true, |targets| compat::Feature::CssNesting.is_compatible(targets)) {
+      nesting::print_nested(&self.rules, &mut printer)?;
+      return Ok(printer.mappings)
+    }
+
+    let mut first = true;
+    for rule in &self.rules {
+      if rule.is_omitted_comment(options.preserve_comments) {
+        continue
+      }
+
+      if first {
+        first = false;
+      } else {
+        printer.newline()?;
+      }
+
+      rule.to_css(&mut printer)?;
+      printer.newline()?;
+    }
+
+    Ok(printer.mappings)
+  }
+
+  /// Prints the stylesheet like `to_css`, but reuses the output of rules that are unchanged
+  /// since the previous build with the same cache. The nested output style is not cached,
+  /// since rules are grouped with their neighbours.
+  pub fn to_css_with_cache(&self, options: PrinterOptions, cache: &mut SerializationCache) -> Result<String, std::fmt::Error> {
+    if options.nested && !options.minify {
+      return Ok(self.to_css(options)?.code)
+    }
+
+    let mut dest = String::new();
+    write_banner(&options, &mut dest);
+    cache.print_rules(&self.rules, &options, &mut dest)?;
+    write_footer(&options, &mut dest);
+    Ok(dest)
+  }
+
+  /// Returns the files referenced by the stylesheet via `@import` rules and `url()`s,
+  /// in source order.
+  pub fn dependencies(&self) -> Vec<Dependency> {
+    dependencies::collect_dependencies(&self.rules)
+  }
+
+  /// Returns the style rules with a selector containing the given compound selector, e.g.
+  /// `.btn` matches rules for `.btn:hover` and `.nav .btn`, in source order. This includes
+  /// style rules within `@media` and `@supports` rules and nested style rules. Returns `None` if the query
+  /// is not a valid compound selector.
+  pub fn rules_matching(&self, query: &str) -> Option<Vec<&StyleRule>> {
+    let compound = parse_compound_selector(query, &self.namespaces())?;
+    let mut result = vec![];
+    collect_matching_rules(&self.rules, &compound, &mut result);
+    Some(result)
+  }
+
+  /// Renames a class in all selectors in the stylesheet, including `@supports selector()` tests,
+  /// e.g. to migrate to a new naming scheme along with the HTML. Returns the number of occurrences renamed.
+  pub fn rename_class(&mut self, from: &str, to: &str) -> usize {
+    let namespaces = self.namespaces();
+    rename::rename(&mut self.rules, RenameKind::Class, from, to, &namespaces)
+  }
+
+  /// Renames an id in all selectors in the stylesheet. Returns the number of occurrences renamed.
+  pub fn rename_id(&mut self, from: &str, to: &str) -> usize {
+    let namespaces = self.namespaces();
+    rename::rename(&mut self.rules, RenameKind::Id, from, to, &namespaces)
+  }
+
+  /// Returns the namespaces declared by the `@namespace` rules in the stylesheet.
+  fn namespaces(&self) -> Namespaces {
+    let mut namespaces = Namespaces::default();
+    for rule in &self.rules {
+      if let CssRule::Namespace(namespace) = rule {
+        match &namespace.prefix {
+          Some(prefix) => { namespaces.prefixes.insert(prefix.clone(), namespace.url.clone()); }
+          None => namespaces.default = Some(namespace.url.clone())
+        }
+      }
+    }
+    namespaces
+  }
+
+  /// Returns statistics about the stylesheet, such as the number of selectors of each type,
+  /// the most used properties, and the media queries it contains.
+  pub fn stats(&self) -> StyleSheetStats {
+    stats::collect_stats(&self.rules)
+  }
+
+  /// Returns a hash of the stylesheet that is stable across builds. It is computed
+  /// over the minified serialization of the rules rather than the source text, so
+  /// whitespace and comment only changes, or changes that minify to the same output
+  /// after calling `minify`, result in the same hash.
+  pub fn content_hash(&self) -> Result<u64, std::fmt::Error> {
+    let mut hasher = ContentHasher::new();
+    let mut printer = Printer::new(&mut hasher, true);
+    for rule in &self.rules {
+      rule.to_css(&mut printer)?;
+      // Separate rules so that e.g. moving a declaration between two rules changes the hash.
+      printer.write_str("\0")?;
+    }
+    Ok(hasher.finish())
+  }
+}
+
+/// The declarations of an HTML `style` attribute, e.g. `color: red; transform: translate(10px)`.
+/// These are minified in the same way as the body of a style rule, and printed without braces.
+#[derive(Debug, PartialEq)]
+pub struct StyleAttribute {
+  pub declarations: DeclarationBlock
+}
+
+impl StyleAttribute {
+  /// Parses a declaration list using the default limits. Invalid declarations are dropped.
+  pub fn parse(code: &str) -> StyleAttribute {
+    let (declarations, _) = limits::with_limits(Limits::default(), || {
+      let mut input = ParserInput::new(&code);
+      let mut parser = Parser::new(&mut input);
+      let mut parser = DeclarationListParser::new(&mut parser, PropertyDeclarationParser);
+      let mut declarations = vec![];
+      while let Some(decl) = parser.next() {
+        if let Ok(decl) = decl {
+          declarations.push(decl);
+        }
+      }
+      declarations
+    });
+
+    StyleAttribute {
+      declarations: DeclarationBlock {
+        declarations
+      }
+    }
+  }
+
+  pub fn minify(&mut self, options: MinifyOptions) {
+    let (mut handler, mut important_handler) = declaration_handlers(&options);
+    self.declarations.minify(&mut handler, &mut important_handler);
+  }
+
+  /// Prints the declarations separated by semicolons, along with the files they reference
+  /// if `analyze_dependencies` is set. The banner and footer are not included, and no source
+  /// map is generated. Returns an error if a declaration cannot be printed.
+  pub fn to_css(&self, options: PrinterOptions) -> Result<ToCssResult, std::fmt::Error> {
+    let mut dest = String::new();
+    let mut printer = Printer::with_options(&mut dest, &options);
+    let mut first = true;
+    for decl in &self.declarations.declarations {
+      if first {
+        first = false;
+      } else {
+        printer.delim(';', false)?;
+      }
+      decl.to_css(&mut printer)?;
+    }
+
+    Ok(ToCssResult {
+      code: dest,
+      dependencies: if options.analyze_dependencies {
+        Some(dependencies::collect_declaration_dependencies(&self.declarations.declarations))
+      } else {
+        None
+      },
+      map: None,
+      exports: None
+    })
+  }
+}
+
+fn declaration_handlers(options: &MinifyOptions) -> (DeclarationHandler, DeclarationHandler) {
+  let mut handler = DeclarationHandler::new(false, options.targets);
+  let mut important_handler = DeclarationHandler::new(true, options.targets);
+  for factory in &options.custom_handlers {
+    handler.register(factory());
+    important_handler.register(factory());
+  }
+  if !options.font_fallbacks.is_empty() {
+    handler.set_font_fallbacks(options.font_fallbacks.clone());
+    important_handler.set_font_fallbacks(options.font_fallbacks.clone());
+  }
+  handler.opaque_color_fallbacks = options.opaque_color_fallbacks;
+  important_handler.opaque_color_fallbacks = options.opaque_color_fallbacks;
+  handler.placeholder_opacity = options.placeholder_opacity;
+  (handler, important_handler)
+}
+
+/// Removes or renames `@keyframes` rules that are overridden by a later rule with the same
+/// name and vendor prefix. Each rule starts a new definition of the name if one of its prefixes
+/// was already defined, so that the prefixed and unprefixed rules from a file stay together.
+fn resolve_duplicate_keyframes(rules: &mut Vec<CssRule>, mode: DuplicateKeyframes) {
+  if mode == DuplicateKeyframes::Keep {
+    return
+  }
+
+  // Assign each rule the index of the definition of its name that it belongs to.
+  let mut definitions: HashMap<String, (usize, VendorPrefix)> = HashMap::new();
+  let mut indices = vec![0; rules.len()];
+  for (i, rule) in rules.iter().enumerate() {
+    if let CssRule::Keyframes(keyframes) = rule {
+      let (index, prefixes) = definitions.entry(keyframes.name.clone()).or_insert((0, VendorPrefix::empty()));
+      if prefixes.intersects(keyframes.vendor_prefix) {
+        *index += 1;
+        *prefixes = VendorPrefix::empty();
+      }
+      *prefixes |= keyframes.vendor_prefix;
+      indices[i] = *index;
+    }
+  }
+
+  let is_last = |name: &str, index: usize| definitions.get(name).map_or(true, |(last, _)| *last == index);
+
+  if mode == DuplicateKeyframes::RemoveEarlier {
+    let mut i = 0;
+    rules.retain(|rule| {
+      let keep = match rule {
+        CssRule::Keyframes(keyframes) => is_last(&keyframes.name, indices[i]),
+        _ => true
+      };
+      i += 1;
+      keep
+    });
+    return
+  }
+
+  // Earlier definitions are renamed to the name with the lowest unused number appended.
+  let mut used_names: HashSet<String> = definitions.keys().cloned().collect();
+  let mut new_names: HashMap<(String, usize), String> = HashMap::new();
+  let mut new_name = |name: &str, index: usize| {
+    new_names.entry((name.to_owned(), index)).or_insert_with(|| {
+      let mut n = index + 1;
+      while used_names.contains(&format!("{}-{}", name, n)) {
+        n += 1;
+      }
+      let new_name = format!("{}-{}", name, n);
+      used_names.insert(new_name.clone());
+      new_name
+    }).clone()
+  };
+
+  // Animations refer to the most recent definition of a name, or the first
+  // one if they are declared before it.
+  let mut current_names = HashMap::new();
+  for name in definitions.keys() {
+    if !is_last(name, 0) {
+      current_names.insert(name.clone(), new_name(name, 0));
+    }
+  }
+
+  for (i, rule) in rules.iter_mut().enumerate() {
+    match rule {
+      CssRule::Keyframes(keyframes) => {
+        if is_last(&keyframes.name, indices[i]) {
+          current_names.remove(&keyframes.name);
+        } else {
+          let renamed = new_name(&keyframes.name, indices[i]);
+          current_names.insert(keyframes.name.clone(), renamed.clone());
+          keyframes.name = renamed;
+        }
+      }
+      rule => rename_keyframes_references(rule, &current_names)
+    }
+  }
+}
+
+fn rename_keyframes_references(rule: &mut CssRule, names: &HashMap<String, String>) {
+  match rule {
+    CssRule::Style(style) => {
+      for decl in style.declarations.declarations.iter_mut() {
+        rename_animations(&mut decl.property, names);
+      }
+      for rule in style.rules.iter_mut() {
+        rename_keyframes_references(rule, names);
+      }
+    }
+    CssRule::Media(media) => {
+      for rule in media.rules.iter_mut() {
+        rename_keyframes_references(rule, names);
+      }
+    }
+    CssRule::Supports(supports) => {
+      for rule in supports.rules.iter_mut() {
+        rename_keyframes_references(rule, names);
+      }
+    }
+    _ => {}
+  }
+}
+
+/// Parses the `@charset` rule, if any, at the start of a stylesheet, and reports a warning if it declares an
+/// encoding other than UTF-8. The rule is not kept, since the output is always UTF-8, but can be
+/// emitted again with the `emit_charset` printer option.
+/// https://www.w3.org/TR/css-syntax-3/#charset-rule
+fn parse_charset(input: &mut Parser) {
+  let location = input.current_source_location();
+  let encoding = input.try_parse(|input| {
+    let location = input.current_source_location();
+    match input.next()? {
+      Token::AtKeyword(name) if name.eq_ignore_ascii_case("charset") => {}
+      token => return Err(location.new_basic_unexpected_token_error(token.clone()))
+    }
+    let encoding = input.expect_string_cloned()?;
+    input.expect_semicolon()?;
+    Ok::<_, cssparser::BasicParseError>(encoding)
+  });
+
+  if let Ok(encoding) = encoding {
+    if !encoding.eq_ignore_ascii_case("utf-8") && !encoding.eq_ignore_ascii_case("utf8") && warnings::is_validating() {
+      warnings::warn(location, WarningKind::UnsupportedCharset);
+    }
+  }
+}
+
+/// Returns the comments before the next token, leaving the position of the parser unchanged.
+fn collect_comments(input: &mut Parser) -> Vec<String> {
+  let state = input.state();
+  let mut comments = vec![];
+  loop {
+    match input.next_including_whitespace_and_comments() {
+      Ok(Token::Comment(comment)) => comments.push((*comment).to_owned()),
+      Ok(Token::WhiteSpace(_)) => {}
+      _ => break
+    }
+  }
+  input.reset(&state);
+  comments
+}
+
+/// The banner is placed on its own line, even when minifying, so that it is easy to find.
+/// The `@charset` rule must be at the very start of the file, so it is placed before it.
+fn write_banner(options: &PrinterOptions, dest: &mut String) {
+  if options.emit_charset {
+    dest.push_str("@charset \"UTF-8\";");
+    if !options.minify {
+      dest.push('\n');
+    }
+  }
+
+  if let Some(banner) = &options.banner {
+    dest.push_str(banner);
+    dest.push('\n');
+  }
+}
+
+fn write_footer(options: &PrinterOptions, dest: &mut String) {
+  if let Some(footer) = &options.footer {
+    if !dest.is_empty() && !dest.ends_with('\n') {
+      dest.push('\n');
+    }
+    dest.push_str(footer);
+    if !options.minify {
+      dest.push('\n');
+    }
+  }
+}
+
+/// Substitutes the custom media queries within the given rules, and adds the names
+/// of the references that could not be inlined to `referenced`.
+fn substitute_custom_media(rules: &mut Vec<CssRule>, custom_media: &HashMap<String, MediaList>, referenced: &mut HashSet<String>) {
+  for rule in rules.iter_mut() {
+    match rule {
+      CssRule::Media(media) => {
+        media.query.substitute_custom_media(custom_media);
+        media.query.custom_media_references(referenced);
+        substitute_custom_media(&mut media.rules, custom_media, referenced);
+      }
+      CssRule::Supports(supports) => substitute_custom_media(&mut supports.rules, custom_media, referenced),
+      _ => {}
+    }
+  }
+}
+
+/// Replaces `@media` rules nested directly within another `@media` rule with a rule that
+/// combines both queries, e.g. `@media screen { @media (min-width: 600px) {} }` becomes
+/// `@media screen and (min-width: 600px) {}`. The outer rule is split around the nested rules
+/// to preserve the order of the rules. Queries that cannot be combined are left nested.
+fn flatten_media(rules: Vec<CssRule>) -> Vec<CssRule> {
+  let mut result = Vec::with_capacity(rules.len());
+  for rule in rules {
+    let MediaRule { query, rules } = match rule {
+      CssRule::Media(media) => media,
+      rule => {
+        result.push(rule);
+        continue
+      }
+    };
+
+    let mut flattened = false;
+    let mut pending = vec![];
+    for rule in flatten_media(rules) {
+      let inner = match rule {
+        CssRule::Media(inner) => inner,
+        rule => {
+          pending.push(rule);
+          continue
+        }
+      };
+
+      match query.and(&inner.query) {
+        Some(combined) => {
+          if !pending.is_empty() {
+            result.push(CssRule::Media(MediaRule {
+              query: query.clone(),
+              rules: std::mem::take(&mut pending)
+            }));
+          }
+          result.push(CssRule::Media(MediaRule {
+            query: combined,
+            rules: inner.rules
+          }));
+          flattened = true;
+        }
+        None => pending.push(CssRule::Media(inner))
+      }
+    }
+
+    if !pending.is_empty() || !flattened {
+      result.push(CssRule::Media(MediaRule {
+        query,
+        rules: pending
+      }));
+    }
+  }
+  result
+}
+
+/// Minifies the style rules in a list of rules, merging adjacent rules with the same selectors
+/// or declarations, and removing rules that become empty or are duplicated later in the list.
+fn minify_style_rules(rules: Vec<CssRule>, handler: &mut DeclarationHandler, important_handler: &mut DeclarationHandler, namespaces: &Namespaces, unsafe_merges: bool) -> Vec<CssRule> {
+  let mut result: Vec<CssRule> = Vec::with_capacity(rules.len());
+  for rule in rules {
+    let mut style = match rule {
+      CssRule::Style(style) => style,
+      rule => {
+        result.push(rule);
+        continue
+      }
+    };
+
+    let was_empty = style.declarations.declarations.is_empty() && style.rules.is_empty();
+    let (ltr, rtl) = minify_style_rule(&mut style, handler, important_handler);
+    let direction_rules = direction_rules(&style, ltr, rtl, handler.targets, namespaces);
+    if was_empty || !style.declarations.declarations.is_empty() || !style.rules.is_empty() {
+      result.extend(legacy_pseudo_element_rules(&style, handler, namespaces));
+      push_style_rule(&mut result, style, handler, important_handler, unsafe_merges);
+    }
+
+    result.extend(direction_rules);
+  }
+
+  // A rule is redundant if an identical rule appears later in the same list, since the
+  // later rule takes precedence over the earlier one, and anything in between.
+  let keys: Vec<Option<String>> = result.iter().map(|rule| match rule {
+    CssRule::Style(style) if style.rules.is_empty() => Some(style.to_css_string()),
+    _ => None
+  }).collect();
+  let mut seen = HashSet::new();
+  let mut keep = vec![true; result.len()];
+  for (i, key) in keys.iter().enumerate().rev() {
+    if let Some(key) = key {
+      keep[i] = seen.insert(key);
+      #[cfg(feature = "trace")]
+      if let (false, CssRule::Style(style)) = (keep[i], &result[i]) {
+        tracing::trace!(line = style.loc.line + 1, "removed rule identical to a later rule");
+      }
+    }
+  }
+
+  let mut keep = keep.into_iter();
+  result.retain(|_| keep.next().unwrap());
+  result
+}
+
+fn collect_matching_rules<'a>(rules: &'a [CssRule], compound: &Selector<Selectors>, result: &mut Vec<&'a StyleRule>) {
+  for rule in rules {
+    match rule {
+      CssRule::Style(style) => {
+        if style.selectors.0.iter().any(|selector| contains_compound(selector, compound)) {
+          result.push(style);
+        }
+        collect_matching_rules(&style.rules, compound, result);
+      }
+      CssRule::Media(media) => collect_matching_rules(&media.rules, compound, result),
+      CssRule::Supports(supports) => collect_matching_rules(&supports.rules, compound, result),
+      _ => {}
+    }
+  }
+}
+
+/// Adds a style rule to the list, merging it with the previous rule if possible.
+fn push_style_rule(result: &mut Vec<CssRule>, style: StyleRule, handler: &mut DeclarationHandler, important_handler: &mut DeclarationHandler, unsafe_merges: bool) {
+  // Rules containing nested rules are not merged, since that could change the
+  // order of the nested rules relative to other rules.
+  if let Some(CssRule::Style(last_style_rule)) = result.last_mut() {
+    let has_legacy_alias = has_legacy_pseudo_element(&style.selectors) || has_legacy_pseudo_element(&last_style_rule.selectors);
+    if style.rules.is_empty() && last_style_rule.rules.is_empty() && !has_legacy_alias {
+      if style.selectors == last_style_rule.selectors {
+        trace!(line = style.loc.line + 1, into = last_style_rule.loc.line + 1, "merged rule into the previous rule with the same selectors");
+        last_style_rule.declarations.declarations.extend(style.declarations.declarations);
+        last_style_rule.declarations.minify(handler, important_handler);
+        return
+      } else if style.declarations == last_style_rule.declarations && (unsafe_merges || (is_safe_to_merge(&style.selectors) && is_safe_to_merge(&last_style_rule.selectors))) {
+        trace!(line = style.loc.line + 1, into = last_style_rule.loc.line + 1, "merged rule into the previous rule with the same declarations");
+        last_style_rule.selectors.0.extend(style.selectors.0);
+        return
+      }
+    }
+  }
+
+  result.push(CssRule::Style(style));
+}
+
+/// Minifies a style rule, and returns the declarations compiled from logical properties
+/// for left-to-right and right-to-left content.
+fn minify_style_rule(style: &mut StyleRule, handler: &mut DeclarationHandler, important_handler: &mut DeclarationHandler) -> (DeclarationBlock, DeclarationBlock) {
+  #[cfg(feature = "trace")]
+  let _span = tracing::trace_span!("rule", line = style.loc.line + 1, column = style.loc.column).entered();
+  let directional = style.declarations.minify_with_direction(handler, important_handler);
+  minify_nested_rules(&mut style.rules, handler, important_handler);
+  directional
+}
+
+// Nested rules are only kept for targets that support nesting, which all support
+// logical properties, so they never need rules for each direction.
+fn minify_nested_rules(rules: &mut Vec<CssRule>, handler: &mut DeclarationHandler, important_handler: &mut DeclarationHandler) {
+  for rule in rules.iter_mut() {
+    match rule {
+      CssRule::Style(nested) => {
+        #[cfg(feature = "trace")]
+        let _span = tracing::trace_span!("rule", line = nested.loc.line + 1, column = nested.loc.column).entered();
+        nested.declarations.minify(handler, important_handler);
+        minify_nested_rules(&mut nested.rules, handler, important_handler);
+      }
+      CssRule::Media(media) => minify_nested_rules(&mut media.rules, handler, important_handler),
+      CssRule::Supports(supports) => minify_nested_rules(&mut supports.rules, handler, important_handler),
+      _ => {}
+    }
+  }
+}
+
+/// Creates a copy of the rule for each legacy pseudo element alias that the targets need, e.g.
+/// `::-webkit-file-upload-button` for `::file-selector-button`. These are separate rules, since
+/// browsers drop rules containing selectors they don't understand.
+fn legacy_pseudo_element_rules(style: &StyleRule, handler: &DeclarationHandler, namespaces: &Namespaces) -> Vec<CssRule> {
+  let prefixes = match handler.targets {
+    Some(targets) => legacy_pseudo_element_prefixes(&style.selectors, targets),
+    None => return vec![]
+  };
+
+  let mut rules = vec![];
+  for prefix in &[VendorPrefix::WebKit, VendorPrefix::Moz, VendorPrefix::Ms] {
+    if !prefixes.contains(*prefix) {
+      continue
+    }
+
+    if let Some(selectors) = legacy_pseudo_element_selectors(&style.selectors, *prefix, namespaces) {
+      let mut declarations = style.declarations.clone();
+      // `::-moz-placeholder` is the only alias with the Firefox prefix.
+      if *prefix == VendorPrefix::Moz && handler.placeholder_opacity {
+        add_placeholder_opacity(&mut declarations);
+      }
+
+      rules.push(CssRule::Style(StyleRule {
+        selectors,
+        declarations,
+        rules: vec![],
+        loc: style.loc
+      }));
+    }
+  }
+  rules
+}
+
+fn add_placeholder_opacity(declarations: &mut DeclarationBlock) {
+  if !declarations.declarations.iter().any(|decl| matches!(decl.property, Property::Opacity(_))) {
+    declarations.declarations.push(Declaration {
+      property: Property::Opacity(AlphaValue(1.0)),
+      important: false
+    });
+  }
+}
+
+/// Creates the rules for each direction containing the declarations compiled from logical properties.
+fn direction_rules(style: &StyleRule, ltr: DeclarationBlock, rtl: DeclarationBlock, targets: Option<Browsers>, namespaces: &Namespaces) -> Vec<CssRule> {
+  let use_dir_pseudo = targets.map_or(false, |targets| compat::Feature::DirSelector.is_compatible(targets));
+  let mut rules = vec![];
+  for (declarations, direction) in vec![(ltr, Direction::Ltr), (rtl, Direction::Rtl)] {
+    if declarations.declarations.is_empty() {
+      continue
+    }
+
+    if let Some(selectors) = direction_selectors(&style.selectors, direction, use_dir_pseudo, namespaces) {
+      rules.push(CssRule::Style(StyleRule {
+        selectors,
+        declarations,
+        rules: vec![],
+        loc: style.loc
+      }));
+    }
+  }
+  rules
+}
+
+/// A 64-bit FNV-1a hasher. The std `DefaultHasher` is not guaranteed to produce
+/// the same output between Rust releases, which makes it unsuitable for persistent cache keys.
+pub struct ContentHasher(u64);
+
+impl ContentHasher {
+  pub fn new() -> ContentHasher {
+    ContentHasher(0xcbf29ce484222325)
+  }
+
+  pub fn finish(&self) -> u64 {
+    self.0
+  }
+}
+
+impl std::hash::Hasher for ContentHasher {
+  fn write(&mut self, bytes: &[u8]) {
+    for byte in bytes {
+      self.0 ^= *byte as u64;
+      self.0 = self.0.wrapping_mul(0x100000001b3);
+    }
+  }
+
+  fn finish(&self) -> u64 {
+    self.0
+  }
+}
+
+impl std::fmt::Write for ContentHasher {
+  fn write_str(&mut self, s: &str) -> std::fmt::Result {
+    std::hash::Hasher::write(self, s.as_bytes());
+    Ok(())
+  }
+}
@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use crate::properties::prefixes::Browsers;
+
+#[derive(Debug)]
+pub enum BrowserslistError {
+  /// A query could not be resolved by browserslist.
+  Query(browserslist::Error),
+  /// A config file could not be read.
+  Io(std::io::Error),
+  /// The `browserslist` field of a package.json is not a string, an array of strings,
+  /// or an object with one of those for each environment.
+  InvalidConfig(String)
+}
+
+impl fmt::Display for BrowserslistError {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      BrowserslistError::Query(err) => write!(f, "Invalid browserslist query: {}", err),
+      BrowserslistError::Io(err) => write!(f, "Could not read browserslist config: {}", err),
+      BrowserslistError::InvalidConfig(message) => write!(f, "Invalid browserslist config: {}", message)
+    }
+  }
+}
+
+impl std::error::Error for BrowserslistError {}
+
+impl From<browserslist::Error> for BrowserslistError {
+  fn from(err: browserslist::Error) -> BrowserslistError {
+    BrowserslistError::Query(err)
+  }
+}
+
+impl From<std::io::Error> for BrowserslistError {
+  fn from(err: std::io::Error) -> BrowserslistError {
+    BrowserslistError::Io(err)
+  }
+}
+
+impl Browsers {
+  /// Resolves browserslist queries, e.g. `["last 2 versions", "not dead"]`, to the minimum
+  /// version of each browser. Returns `None` if no supported browser matches.
+  pub fn from_browserslist<S: AsRef<str>, I: IntoIterator<Item = S>>(queries: I) -> Result<Option<Browsers>, BrowserslistError> {
+    let distribs = browserslist::resolve(queries, &browserslist::Opts::new())?;
+    let mut browsers = Browsers::default();
+    let mut has_any = false;
+    for distrib in distribs {
+      macro_rules! browser {
+        ($browser: ident) => {{
+          if let Some(version) = parse_version(distrib.version()) {
+            if browsers.$browser.map_or(true, |v| version < v) {
+              browsers.$browser = Some(version);
+            }
+            has_any = true;
+          }
+        }};
+      }
+
+      // Mobile browsers share the data of their desktop counterparts, as in build-prefixes.js.
+      match distrib.name() {
+        "android" => browser!(android),
+        "chrome" | "and_chr" => browser!(chrome),
+        "edge" => browser!(edge),
+        "firefox" | "and_ff" => browser!(firefox),
+        "ie" | "ie_mob" => browser!(ie),
+        "ios_saf" => browser!(ios_saf),
+        "opera" | "op_mob" => browser!(opera),
+        "safari" => browser!(safari),
+        "samsung" => browser!(samsung),
+        _ => {}
+      }
+    }
+
+    Ok(if has_any { Some(browsers) } else { None })
+  }
+
+  /// Finds the browserslist config that applies to the given file or directory, i.e. a
+  /// `.browserslistrc` or `browserslist` file, or the `browserslist` field of a package.json,
+  /// in it or the closest parent directory, and resolves its queries. The environment is read
+  /// from `BROWSERSLIST_ENV` or `NODE_ENV`, and defaults to `production`.
+  /// Returns `None` if there is no config.
+  pub fn load_from_config<P: AsRef<Path>>(path: P) -> Result<Option<Browsers>, BrowserslistError> {
+    let env = std::env::var("BROWSERSLIST_ENV")
+      .or_else(|_| std::env::var("NODE_ENV"))
+      .unwrap_or_else(|_| "production".into());
+
+    let path = path.as_ref();
+    let mut dir = if path.is_dir() { Some(path) } else { path.parent() };
+    while let Some(d) = dir {
+      for name in &[".browserslistrc", "browserslist"] {
+        let file = d.join(name);
+        if file.is_file() {
+          let config = parse_config(&std::fs::read_to_string(file)?);
+          return resolve_config(config, &env)
+        }
+      }
+
+      let file = d.join("package.json");
+      if file.is_file() {
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(file)?)
+          .map_err(|err| BrowserslistError::InvalidConfig(err.to_string()))?;
+        if let Some(value) = json.get("browserslist") {
+          return resolve_config(parse_package_config(value)?, &env)
+        }
+      }
+
+      dir = d.parent();
+    }
+
+    Ok(None)
+  }
+}
+
+/// Parses a version such as `14.1` or `15.2-15.3` to the encoding used by `Browsers`,
+/// i.e. `major << 16 | minor << 8 | patch`. Ranges resolve to their lower bound.
+fn parse_version(version: &str) -> Option<u32> {
+  let mut parts = version.split('-').next()?.split('.');
+  let major: u32 = parts.next()?.parse().ok()?;
+  let minor: u32 = parts.next().map_or(Some(0), |v| v.parse().ok())?;
+  let patch: u32 = parts.next().map_or(Some(0), |v| v.parse().ok())?;
+  Some(major << 16 | minor << 8 | patch)
+}
+
+/// The queries of a config, by environment. Queries outside of an environment section are
+/// stored under `defaults`.
+type Config = HashMap<String, Vec<String>>;
+
+fn parse_config(source: &str) -> Config {
+  let mut config = Config::new();
+  let mut envs = vec!["defaults".to_owned()];
+  for line in source.lines() {
+    let line = line.split('#').next().unwrap().trim();
+    if line.is_empty() {
+      continue
+    }
+
+    if line.starts_with('[') && line.ends_with(']') {
+      envs = line[1..line.len() - 1].split_whitespace().map(|env| env.to_owned()).collect();
+      continue
+    }
+
+    for env in &envs {
+      config.entry(env.clone()).or_default().push(line.to_owned());
+    }
+  }
+
+  config
+}
+
+fn parse_package_config(value: &serde_json::Value) -> Result<Config, BrowserslistError> {
+  fn queries(value: &serde_json::Value) -> Result<Vec<String>, BrowserslistError> {
+    match value {
+      serde_json::Value::String(query) => Ok(vec![query.clone()]),
+      serde_json::Value::Array(items) => items.iter().map(|item| {
+        item.as_str()
+          .map(|query| query.to_owned())
+          .ok_or_else(|| BrowserslistError::InvalidConfig("queries must be strings".into()))
+      }).collect(),
+      _ => Err(BrowserslistError::InvalidConfig("expected a string or an array of queries".into()))
+    }
+  }
+
+  let mut config = Config::new();
+  if let serde_json::Value::Object(envs) = value {
+    for (env, value) in envs {
+      config.insert(env.clone(), queries(value)?);
+    }
+  } else {
+    config.insert("defaults".into(), queries(value)?);
+  }
+
+  Ok(config)
+}
+
+fn resolve_config(mut config: Config, env: &str) -> Result<Option<Browsers>, BrowserslistError> {
+  let queries = match config.remove(env).or_else(|| config.remove("defaults")) {
+    Some(queries) => queries,
+    None => return Ok(None)
+  };
+
+  Browsers::from_browserslist(queries)
+}
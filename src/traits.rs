@@ -1,6 +1,7 @@
 use cssparser::*;
 use crate::properties::Property;
 use crate::printer::Printer;
+use crate::error::ParserError;
 
 pub trait Parse: Sized {
   /// Parse a value of this type.
@@ -8,7 +9,7 @@ pub trait Parse: Sized {
   /// Returns an error on failure.
   fn parse<'i, 't>(
       input: &mut Parser<'i, 't>,
-  ) -> Result<Self, ParseError<'i, ()>>;
+  ) -> Result<Self, ParseError<'i, ParserError<'i>>>;
 }
 
 /// Trait for things the can serialize themselves in CSS syntax.
@@ -37,8 +38,14 @@ where
     }
 }
 
-pub trait PropertyHandler: Sized {
+/// Handles a group of related properties within a declaration block, e.g. to combine
+/// longhands into shorthands. See `Handlers` for how handlers interact.
+pub trait PropertyHandler {
+  /// Returns `true` if the property was handled, in which case it is removed from the
+  /// declaration block and no other handler sees it.
   fn handle_property(&mut self, property: &Property) -> bool;
+  /// Returns the declarations to output for the handled properties, and resets the handler
+  /// for the next declaration block.
   fn finalize(&mut self) -> Vec<Property>;
 }
 
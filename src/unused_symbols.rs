@@ -0,0 +1,122 @@
+use selectors::parser::{Selector, Component};
+use std::collections::HashSet;
+use crate::parser::CssRule;
+use crate::declaration::Declaration;
+use crate::properties::Property;
+use crate::properties::animation::collect_animation_names;
+use crate::properties::font::FontFamily;
+use crate::rules::font_face::FontFaceProperty;
+use crate::selector::Selectors;
+
+/// Removes the selectors that reference a class or id in the given set of unused symbols, e.g.
+/// `.unused > a`, along with style rules and `@media` rules that become empty. `@keyframes` and
+/// `@font-face` rules are removed if they are no longer referenced by the remaining rules, or
+/// if their name is in the set. Rules that were not referenced to begin with are kept, since
+/// they may be used outside the stylesheet.
+pub fn remove_unused_symbols(rules: &mut Vec<CssRule>, unused: &HashSet<String>) {
+  let mut referenced = References::default();
+  referenced.collect(rules);
+
+  remove_selectors(rules, unused);
+
+  let mut remaining = References::default();
+  remaining.collect(rules);
+
+  let is_unused = |name: &String, referenced: &HashSet<String>, remaining: &HashSet<String>| {
+    unused.contains(name) || (referenced.contains(name) && !remaining.contains(name))
+  };
+
+  rules.retain(|rule| {
+    match rule {
+      CssRule::Keyframes(keyframes) => !is_unused(&keyframes.name, &referenced.keyframes, &remaining.keyframes),
+      CssRule::FontFace(font_face) => !font_face.properties.iter().any(|property| {
+        match property {
+          FontFaceProperty::FontFamily(FontFamily::FamilyName(name)) => is_unused(name, &referenced.font_families, &remaining.font_families),
+          _ => false
+        }
+      }),
+      _ => true
+    }
+  });
+}
+
+fn remove_selectors(rules: &mut Vec<CssRule>, unused: &HashSet<String>) {
+  let mut i = 0;
+  while i < rules.len() {
+    let keep = match &mut rules[i] {
+      CssRule::Style(style) => {
+        style.selectors.0.retain(|selector| !references_unused(selector, unused));
+        remove_selectors(&mut style.rules, unused);
+        !style.selectors.0.is_empty()
+      }
+      CssRule::Media(media) => {
+        let was_empty = media.rules.is_empty();
+        remove_selectors(&mut media.rules, unused);
+        was_empty || !media.rules.is_empty()
+      }
+      CssRule::Supports(supports) => {
+        let was_empty = supports.rules.is_empty();
+        remove_selectors(&mut supports.rules, unused);
+        was_empty || !supports.rules.is_empty()
+      }
+      _ => true
+    };
+
+    if keep {
+      i += 1;
+    } else {
+      rules.remove(i);
+    }
+  }
+}
+
+/// Returns whether a selector requires a class or id that is unused in order to match.
+/// Classes within functional pseudo classes such as `:not()` are ignored.
+fn references_unused(selector: &Selector<Selectors>, unused: &HashSet<String>) -> bool {
+  selector.iter_raw_match_order().any(|component| {
+    match component {
+      Component::Class(name) | Component::ID(name) => unused.contains(&cssparser::ToCss::to_css_string(name)),
+      _ => false
+    }
+  })
+}
+
+/// The names of the keyframes and font families referenced by declarations.
+#[derive(Default)]
+struct References {
+  keyframes: HashSet<String>,
+  font_families: HashSet<String>
+}
+
+impl References {
+  fn collect(&mut self, rules: &[CssRule]) {
+    for rule in rules {
+      match rule {
+        CssRule::Style(style) => {
+          self.collect_declarations(&style.declarations.declarations);
+          self.collect(&style.rules);
+        }
+        CssRule::Media(media) => self.collect(&media.rules),
+        CssRule::Supports(supports) => self.collect(&supports.rules),
+        CssRule::Page(page) => self.collect_declarations(&page.declarations.declarations),
+        _ => {}
+      }
+    }
+  }
+
+  fn collect_declarations(&mut self, declarations: &[Declaration]) {
+    for declaration in declarations {
+      collect_animation_names(&declaration.property, &mut self.keyframes);
+      let families = match &declaration.property {
+        Property::FontFamily(families) => families,
+        Property::Font(font) => &font.family,
+        _ => continue
+      };
+      for family in families {
+        if let FontFamily::FamilyName(name) = family {
+          self.font_families.insert(name.clone());
+        }
+      }
+    }
+  }
+}
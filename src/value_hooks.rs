@@ -0,0 +1,117 @@
+use cssparser::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::error::ParserError;
+
+/// Converts custom units and functions that are not part of CSS, e.g. `rpx` or `theme(colors.red)`,
+/// into standard values before declarations are parsed. This allows simple preprocessing without
+/// a separate build step.
+#[derive(Clone, Default)]
+pub struct ValueHooks {
+  units: HashMap<String, Rc<dyn Fn(f32) -> Option<String>>>,
+  functions: HashMap<String, Rc<dyn Fn(&str) -> Option<String>>>
+}
+
+impl ValueHooks {
+  pub fn new() -> ValueHooks {
+    ValueHooks::default()
+  }
+
+  /// Registers a custom unit, e.g. `rpx`. The callback receives the number before the unit,
+  /// and returns the CSS to replace the dimension with, or `None` to leave it unchanged.
+  pub fn register_unit<F: Fn(f32) -> Option<String> + 'static>(&mut self, unit: &str, f: F) {
+    self.units.insert(unit.to_ascii_lowercase(), Rc::new(f));
+  }
+
+  /// Registers a custom function, e.g. `theme`. The callback receives the source of the arguments,
+  /// and returns the CSS to replace the function call with, or `None` to leave it unchanged.
+  pub fn register_function<F: Fn(&str) -> Option<String> + 'static>(&mut self, name: &str, f: F) {
+    self.functions.insert(name.to_ascii_lowercase(), Rc::new(f));
+  }
+}
+
+// Like the limits, the hooks are set per thread since values are parsed
+// through the `Parse` trait, which has no context parameter.
+thread_local! {
+  static HOOKS: RefCell<Option<ValueHooks>> = RefCell::new(None);
+}
+
+/// Runs the given function with the given hooks applied to declaration values.
+pub fn with_hooks<T, F: FnOnce() -> T>(hooks: ValueHooks, f: F) -> T {
+  let prev = HOOKS.with(|h| h.replace(Some(hooks)));
+  let res = f();
+  HOOKS.with(|h| h.replace(prev));
+  res
+}
+
+/// Returns the value with custom units and functions replaced, if the input contains any
+/// that the hooks converted. Otherwise, returns `None` and leaves the input as is.
+pub fn rewrite<'i, 't>(input: &mut Parser<'i, 't>) -> Option<String> {
+  // Cloning only copies the `Rc`s, and allows the callbacks to parse values themselves.
+  let hooks = HOOKS.with(|h| h.borrow().clone())?;
+  let state = input.state();
+  let mut dest = String::new();
+  match rewrite_tokens(input, &mut dest, &hooks) {
+    Ok(true) => Some(dest),
+    _ => {
+      input.reset(&state);
+      None
+    }
+  }
+}
+
+/// Serializes the tokens in the input, replacing custom units and functions.
+/// Returns whether anything was replaced.
+fn rewrite_tokens<'i, 't>(input: &mut Parser<'i, 't>, dest: &mut String, hooks: &ValueHooks) -> Result<bool, ParseError<'i, ParserError<'i>>> {
+  let mut replaced = false;
+  loop {
+    let token = match input.next_including_whitespace() {
+      Ok(token) => token.clone(),
+      Err(_) => break
+    };
+
+    match &token {
+      Token::Dimension { value, unit, .. } => {
+        match hooks.units.get(&unit.to_ascii_lowercase()).and_then(|f| f(*value)) {
+          Some(replacement) => {
+            dest.push_str(&replacement);
+            replaced = true;
+          }
+          None => token.to_css(dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?
+        }
+      }
+      Token::Function(name) if hooks.functions.contains_key(&name.to_ascii_lowercase()) => {
+        let args = input.parse_nested_block(|input| {
+          let start = input.position();
+          while input.next_including_whitespace_and_comments().is_ok() {}
+          Ok::<_, ParseError<'i, ParserError<'i>>>(input.slice_from(start))
+        })?;
+
+        match hooks.functions[&name.to_ascii_lowercase()](args.trim()) {
+          Some(replacement) => {
+            dest.push_str(&replacement);
+            replaced = true;
+          }
+          None => {
+            token.to_css(dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?;
+            dest.push_str(args);
+            dest.push(')');
+          }
+        }
+      }
+      Token::Function(_) | Token::ParenthesisBlock | Token::SquareBracketBlock | Token::CurlyBracketBlock => {
+        token.to_css(dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?;
+        replaced |= input.parse_nested_block(|input| rewrite_tokens(input, dest, hooks))?;
+        dest.push(match token {
+          Token::SquareBracketBlock => ']',
+          Token::CurlyBracketBlock => '}',
+          _ => ')'
+        });
+      }
+      _ => token.to_css(dest).map_err(|_| input.new_custom_error(ParserError::InvalidValue))?
+    }
+  }
+
+  Ok(replaced)
+}
@@ -2,17 +2,16 @@ use cssparser::*;
 use crate::traits::{Parse, ToCss};
 use crate::printer::Printer;
 use super::percentage::NumberOrPercentage;
+use crate::error::ParserError;
 
 /// https://www.w3.org/TR/2021/WD-css-color-4-20210601/#typedef-alpha-value
 #[derive(Debug, Clone, PartialEq)]
-pub struct AlphaValue(f32);
+pub struct AlphaValue(pub f32);
 
 impl Parse for AlphaValue {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
-    match NumberOrPercentage::parse(input)? {
-      NumberOrPercentage::Percentage(percent) => Ok(AlphaValue(percent.0)),
-      NumberOrPercentage::Number(number) => Ok(AlphaValue(number))
-    }
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    // Values outside the range are valid, but clamped.
+    Ok(AlphaValue(NumberOrPercentage::parse(input)?.clamp(0.0, 1.0).to_number()))
   }
 }
 
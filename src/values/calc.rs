@@ -3,6 +3,9 @@ use crate::traits::{Parse, ToCss};
 use crate::printer::Printer;
 use std::fmt::Write;
 use super::number::serialize_number;
+use crate::limits::{self, LimitKind};
+use crate::compat::Feature;
+use crate::error::ParserError;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum MathFunction<V> {
@@ -47,6 +50,21 @@ impl<V: ToCss + std::cmp::PartialOrd<f32> + std::ops::Mul<f32, Output = V> + Clo
         dest.write_char(')')
       }
       MathFunction::Clamp(a, b, c) => {
+        // clamp() is supported by fewer browsers than min() and max(), so it is
+        // written in terms of them if any of the targets don't support it.
+        if let Some(targets) = dest.targets {
+          if !Feature::ClampFunction.is_compatible(targets) {
+            dest.write_str("max(")?;
+            a.to_css(dest)?;
+            dest.delim(',', false)?;
+            dest.write_str("min(")?;
+            b.to_css(dest)?;
+            dest.delim(',', false)?;
+            c.to_css(dest)?;
+            return dest.write_str("))")
+          }
+        }
+
         dest.write_str("clamp(")?;
         a.to_css(dest)?;
         dest.delim(',', false)?;
@@ -68,13 +86,15 @@ pub enum Calc<V> {
 }
 
 impl<V: Parse + std::ops::Mul<f32, Output = V> + std::ops::Add<V, Output = V> + std::cmp::PartialOrd<V> + std::convert::Into<Calc<V>> + std::convert::From<Calc<V>> + std::fmt::Debug> Parse for Calc<V> {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
-    let f = input.expect_function()?;
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let f = input.expect_function()?.clone();
+    let _guard = limits::enter(input, LimitKind::CalcDepth)?;
     match_ignore_ascii_case! { &f,
       "calc" => {
         let calc = input.parse_nested_block(Calc::parse_sum)?;
+        // A calc() containing only a single value or a nested math function is unnecessary.
         match calc {
-          Calc::Value(_) | Calc::Number(_) => Ok(calc),
+          Calc::Value(_) | Calc::Number(_) | Calc::Function(_) => Ok(calc),
           _ => Ok(Calc::Function(Box::new(MathFunction::Calc(calc))))
         }
       },
@@ -155,7 +175,7 @@ impl<V: Parse + std::ops::Mul<f32, Output = V> + std::ops::Add<V, Output = V> +
 }
 
 impl<V: Parse + std::ops::Mul<f32, Output = V> + std::ops::Add<V, Output = V> + std::cmp::PartialOrd<V> + std::convert::Into<Calc<V>> + std::convert::From<Calc<V>> + std::fmt::Debug> Calc<V> {
-  fn parse_sum<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse_sum<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut cur: Calc<V> = Calc::parse_product(input)?;
     loop {
       let start = input.state();
@@ -189,7 +209,7 @@ impl<V: Parse + std::ops::Mul<f32, Output = V> + std::ops::Add<V, Output = V> +
     Ok(cur)
   }
 
-  fn parse_product<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse_product<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let mut node = Calc::parse_value(input)?;
     loop {
       let start = input.state();
@@ -225,7 +245,7 @@ impl<V: Parse + std::ops::Mul<f32, Output = V> + std::ops::Add<V, Output = V> +
     Ok(node)
   }
 
-  fn parse_value<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse_value<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     // Parse nested calc() and other math functions.
     if let Ok(calc) = input.try_parse(Self::parse) {
       match calc {
@@ -240,6 +260,7 @@ impl<V: Parse + std::ops::Mul<f32, Output = V> + std::ops::Add<V, Output = V> +
     }
 
     if input.try_parse(|input| input.expect_parenthesis_block()).is_ok() {
+      let _guard = limits::enter(input, LimitKind::CalcDepth)?;
       return input.parse_nested_block(Calc::parse_sum)
     }
 
@@ -303,7 +324,33 @@ impl<V: std::ops::Mul<f32, Output = V>> std::ops::Mul<f32> for Calc<V> {
       Calc::Function(f) => {
         match *f {
           MathFunction::Calc(c) => Calc::Function(Box::new(MathFunction::Calc(c * other))),
-          _ => todo!()
+          // Multiplying by a negative number reverses the order of the arguments,
+          // so min() becomes max() and vice versa.
+          MathFunction::Min(args) => {
+            let args = args.into_iter().map(|arg| arg * other).collect();
+            if other < 0.0 {
+              Calc::Function(Box::new(MathFunction::Max(args)))
+            } else {
+              Calc::Function(Box::new(MathFunction::Min(args)))
+            }
+          }
+          MathFunction::Max(args) => {
+            let args = args.into_iter().map(|arg| arg * other).collect();
+            if other < 0.0 {
+              Calc::Function(Box::new(MathFunction::Min(args)))
+            } else {
+              Calc::Function(Box::new(MathFunction::Max(args)))
+            }
+          }
+          MathFunction::Clamp(a, b, c) => {
+            if other < 0.0 {
+              // -clamp(a, b, c) = -max(a, min(b, c)) = min(-a, max(-b, -c))
+              let max = Calc::Function(Box::new(MathFunction::Max(vec![b * other, c * other])));
+              Calc::Function(Box::new(MathFunction::Min(vec![a * other, max])))
+            } else {
+              Calc::Function(Box::new(MathFunction::Clamp(a * other, b * other, c * other)))
+            }
+          }
         }
       }
     }
@@ -1,18 +1,86 @@
 use cssparser::*;
 use crate::traits::{Parse, ToCss};
 use crate::printer::Printer;
+use crate::compat;
+use crate::properties::prefixes::Browsers;
+use super::angle::Angle;
+use super::number::{serialize_number, serialize_dimension};
 use std::fmt::Write;
+use crate::error::ParserError;
 
+/// A color value. Colors in the color spaces of CSS Color Level 4 are not supported by cssparser,
+/// so they are parsed here, and can be converted to sRGB for browsers that don't support them.
+/// https://www.w3.org/TR/css-color-4/
 #[derive(Debug, Clone, PartialEq)]
-pub struct CssColor(Color);
+pub enum CssColor {
+  CurrentColor,
+  RGBA(RGBA),
+  LAB(Box<LABColor>),
+  Predefined(Box<PredefinedColor>)
+}
 
 impl CssColor {
   pub fn current_color() -> CssColor {
-    CssColor(Color::CurrentColor)
+    CssColor::CurrentColor
   }
 
   pub fn transparent() -> CssColor {
-    CssColor(Color::RGBA(RGBA::transparent()))
+    CssColor::RGBA(RGBA::transparent())
+  }
+
+  /// Returns whether the color is known to be fully opaque. `currentColor` may not be.
+  pub fn is_opaque(&self) -> bool {
+    match self {
+      CssColor::RGBA(color) => color.alpha == 255,
+      CssColor::LAB(color) => color.alpha() >= 1.0,
+      CssColor::Predefined(color) => color.alpha >= 1.0,
+      CssColor::CurrentColor => false
+    }
+  }
+
+  /// Returns the feature that browsers must support in order to use the color,
+  /// or `None` for colors that are supported everywhere.
+  pub fn feature(&self) -> Option<compat::Feature> {
+    match self {
+      CssColor::LAB(color) => match **color {
+        LABColor::LAB { .. } | LABColor::LCH { .. } => Some(compat::Feature::LabColors),
+        LABColor::OKLAB { .. } | LABColor::OKLCH { .. } => Some(compat::Feature::OklabColors)
+      },
+      CssColor::Predefined(color) => match color.space {
+        PredefinedColorSpace::DisplayP3 => Some(compat::Feature::P3Colors),
+        _ => Some(compat::Feature::ColorFunction)
+      },
+      CssColor::CurrentColor | CssColor::RGBA(_) => None
+    }
+  }
+
+  /// Converts the color to sRGB. Colors outside of the sRGB gamut are clipped.
+  pub fn to_rgb(&self) -> CssColor {
+    match self {
+      CssColor::LAB(color) => CssColor::RGBA(color.to_rgba()),
+      CssColor::Predefined(color) => CssColor::RGBA(color.to_rgba()),
+      _ => self.clone()
+    }
+  }
+
+  /// Returns an sRGB fallback for the color if some of the targets don't support it.
+  pub fn get_fallback(&self, targets: Browsers) -> Option<CssColor> {
+    match self.feature() {
+      Some(feature) if !feature.is_compatible(targets) => Some(self.to_rgb()),
+      _ => None
+    }
+  }
+
+  /// Returns an opaque version of a semi-transparent sRGB color, for browsers that don't support
+  /// alpha in colors at all. The alpha is dropped rather than blended, since the backdrop is not
+  /// known. Fully transparent colors are kept, since they are printed as `transparent` for these.
+  pub fn get_opaque_fallback(&self) -> Option<CssColor> {
+    match self {
+      CssColor::RGBA(color) if color.alpha > 0 && color.alpha < 255 => {
+        Some(CssColor::RGBA(RGBA::new(color.red, color.green, color.blue, 255)))
+      }
+      _ => None
+    }
   }
 }
 
@@ -23,18 +91,98 @@ impl Default for CssColor {
 }
 
 impl Parse for CssColor {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
-    Color::parse(input)
-      .map(CssColor)
-      .map_err(|_| input.new_error(BasicParseErrorKind::QualifiedRuleInvalid))
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(color) = input.try_parse(Color::parse) {
+      return Ok(match color {
+        Color::CurrentColor => CssColor::CurrentColor,
+        Color::RGBA(rgba) => CssColor::RGBA(rgba)
+      })
+    }
+
+    let location = input.current_source_location();
+    let function = input.expect_function()
+      .map_err(|_| location.new_custom_error(ParserError::InvalidValue))?
+      .clone();
+    input.parse_nested_block(|input| {
+      match_ignore_ascii_case! { &*function,
+        "lab" => {
+          let l = parse_component(input, 100.0)?;
+          let a = parse_component(input, 125.0)?;
+          let b = parse_component(input, 125.0)?;
+          let alpha = parse_alpha(input)?;
+          Ok(CssColor::LAB(Box::new(LABColor::LAB { l, a, b, alpha })))
+        },
+        "lch" => {
+          let l = parse_component(input, 100.0)?;
+          let c = parse_component(input, 150.0)?;
+          let h = parse_hue(input)?;
+          let alpha = parse_alpha(input)?;
+          Ok(CssColor::LAB(Box::new(LABColor::LCH { l, c, h, alpha })))
+        },
+        "oklab" => {
+          let l = parse_component(input, 1.0)?;
+          let a = parse_component(input, 0.4)?;
+          let b = parse_component(input, 0.4)?;
+          let alpha = parse_alpha(input)?;
+          Ok(CssColor::LAB(Box::new(LABColor::OKLAB { l, a, b, alpha })))
+        },
+        "oklch" => {
+          let l = parse_component(input, 1.0)?;
+          let c = parse_component(input, 0.4)?;
+          let h = parse_hue(input)?;
+          let alpha = parse_alpha(input)?;
+          Ok(CssColor::LAB(Box::new(LABColor::OKLCH { l, c, h, alpha })))
+        },
+        "color" => {
+          let space = PredefinedColorSpace::parse(input)?;
+          let a = parse_component(input, 1.0)?;
+          let b = parse_component(input, 1.0)?;
+          let c = parse_component(input, 1.0)?;
+          let alpha = parse_alpha(input)?;
+          Ok(CssColor::Predefined(Box::new(PredefinedColor { space, components: [a, b, c], alpha })))
+        },
+        _ => Err(location.new_custom_error(ParserError::InvalidValue))
+      }
+    })
+  }
+}
+
+/// Parses a number, or a percentage of the given reference value. The `none` keyword,
+/// for a missing component, is treated as zero.
+fn parse_component<'i, 't>(input: &mut Parser<'i, 't>, percentage_basis: f32) -> Result<f32, ParseError<'i, ParserError<'i>>> {
+  let location = input.current_source_location();
+  match *input.next()? {
+    Token::Number { value, .. } => Ok(value),
+    Token::Percentage { unit_value, .. } => Ok(unit_value * percentage_basis),
+    Token::Ident(ref ident) if ident.eq_ignore_ascii_case("none") => Ok(0.0),
+    ref t => Err(location.new_unexpected_token_error(t.clone()))
   }
 }
 
+/// Parses a hue in degrees, which may also be an angle.
+fn parse_hue<'i, 't>(input: &mut Parser<'i, 't>) -> Result<f32, ParseError<'i, ParserError<'i>>> {
+  if let Ok(angle) = input.try_parse(Angle::parse) {
+    return Ok(angle.to_degrees())
+  }
+
+  parse_component(input, 0.0)
+}
+
+/// Parses an optional alpha value after a `/`, which defaults to 1.
+fn parse_alpha<'i, 't>(input: &mut Parser<'i, 't>) -> Result<f32, ParseError<'i, ParserError<'i>>> {
+  if input.try_parse(|input| input.expect_delim('/')).is_ok() {
+    return Ok(parse_component(input, 1.0)?.max(0.0).min(1.0))
+  }
+
+  input.expect_exhausted()?;
+  Ok(1.0)
+}
+
 impl ToCss for CssColor {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
-    match self.0 {
-      Color::CurrentColor => dest.write_str("currentColor"),
-      Color::RGBA(color) => {
+    match self {
+      CssColor::CurrentColor => dest.write_str("currentColor"),
+      CssColor::RGBA(color) => {
         if color.alpha == 255 {
           let hex: u32 = ((color.red as u32) << 16) | ((color.green as u32) << 8) | (color.blue as u32);
           if let Some(name) = short_color_name(hex) {
@@ -47,6 +195,8 @@ impl ToCss for CssColor {
           } else {
             write!(dest, "#{:06x}", hex);
           }
+        } else if dest.targets.map_or(false, |targets| !compat::Feature::HexAlphaColors.is_compatible(targets)) {
+          return write_rgba(color, dest)
         } else {
           let hex: u32 = ((color.red as u32) << 24) | ((color.green as u32) << 16) | ((color.blue as u32) << 8) | (color.alpha as u32);
           let compact = compact_hex(hex);
@@ -58,8 +208,327 @@ impl ToCss for CssColor {
         }
         Ok(())
       }
+      CssColor::LAB(color) => color.to_css(dest),
+      CssColor::Predefined(color) => color.to_css(dest)
+    }
+  }
+}
+
+/// Serializes a semi-transparent color using `rgba()`, for browsers that don't support
+/// hex colors with an alpha channel.
+fn write_rgba<W>(color: &RGBA, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+  if *color == RGBA::transparent() {
+    return dest.write_str("transparent")
+  }
+
+  write!(dest, "rgba({}", color.red)?;
+  dest.delim(',', false)?;
+  write!(dest, "{}", color.green)?;
+  dest.delim(',', false)?;
+  write!(dest, "{}", color.blue)?;
+  dest.delim(',', false)?;
+  // Like cssparser, use the shortest number that maps back to the same alpha.
+  let alpha = color.alpha as f32 / 255.0;
+  let rounded = (alpha * 100.0).round() / 100.0;
+  if (rounded * 255.0).round() as u8 == color.alpha {
+    serialize_number(rounded, dest)?;
+  } else {
+    serialize_number((alpha * 1000.0).round() / 1000.0, dest)?;
+  }
+  dest.write_char(')')
+}
+
+/// A color in the CIE Lab or Oklab color space, using either rectangular or polar coordinates.
+/// Lightness is a number between 0 and 100 for `lab()` and `lch()`, and between 0 and 1 for
+/// `oklab()` and `oklch()`. Hues are in degrees.
+/// https://www.w3.org/TR/css-color-4/#specifying-lab-lch
+#[derive(Debug, Clone, PartialEq)]
+pub enum LABColor {
+  LAB { l: f32, a: f32, b: f32, alpha: f32 },
+  LCH { l: f32, c: f32, h: f32, alpha: f32 },
+  OKLAB { l: f32, a: f32, b: f32, alpha: f32 },
+  OKLCH { l: f32, c: f32, h: f32, alpha: f32 }
+}
+
+impl LABColor {
+  pub fn alpha(&self) -> f32 {
+    match *self {
+      LABColor::LAB { alpha, .. } |
+      LABColor::LCH { alpha, .. } |
+      LABColor::OKLAB { alpha, .. } |
+      LABColor::OKLCH { alpha, .. } => alpha
     }
   }
+
+  /// Converts the color to sRGB, clipping components that are out of gamut.
+  pub fn to_rgba(&self) -> RGBA {
+    let linear = match *self {
+      LABColor::LAB { l, a, b, .. } => xyz_d65_to_linear_srgb(d50_to_d65(lab_to_xyz_d50(l, a, b))),
+      LABColor::LCH { l, c, h, .. } => {
+        let (a, b) = lch_to_lab(c, h);
+        xyz_d65_to_linear_srgb(d50_to_d65(lab_to_xyz_d50(l, a, b)))
+      }
+      LABColor::OKLAB { l, a, b, .. } => oklab_to_linear_srgb(l, a, b),
+      LABColor::OKLCH { l, c, h, .. } => {
+        let (a, b) = lch_to_lab(c, h);
+        oklab_to_linear_srgb(l, a, b)
+      }
+    };
+
+    to_rgba(linear.map(gamma_srgb), self.alpha())
+  }
+}
+
+impl ToCss for LABColor {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    // Lightness is serialized as a percentage, since early implementations require it.
+    let (name, l, x, y, alpha) = match *self {
+      LABColor::LAB { l, a, b, alpha } => ("lab(", l, a, b, alpha),
+      LABColor::LCH { l, c, h, alpha } => ("lch(", l, c, h, alpha),
+      LABColor::OKLAB { l, a, b, alpha } => ("oklab(", l * 100.0, a, b, alpha),
+      LABColor::OKLCH { l, c, h, alpha } => ("oklch(", l * 100.0, c, h, alpha)
+    };
+
+    dest.write_str(name)?;
+    serialize_dimension(l, "%", dest)?;
+    dest.write_char(' ')?;
+    serialize_number(x, dest)?;
+    dest.write_char(' ')?;
+    serialize_number(y, dest)?;
+    write_alpha(alpha, dest)?;
+    dest.write_char(')')
+  }
+}
+
+/// https://www.w3.org/TR/css-color-4/#predefined
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PredefinedColorSpace {
+  SRGB,
+  SRGBLinear,
+  DisplayP3,
+  A98RGB,
+  ProPhotoRGB,
+  Rec2020,
+  XYZD50,
+  XYZD65
+}
+
+impl Parse for PredefinedColorSpace {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    match_ignore_ascii_case! { &*ident,
+      "srgb" => Ok(PredefinedColorSpace::SRGB),
+      "srgb-linear" => Ok(PredefinedColorSpace::SRGBLinear),
+      "display-p3" => Ok(PredefinedColorSpace::DisplayP3),
+      "a98-rgb" => Ok(PredefinedColorSpace::A98RGB),
+      "prophoto-rgb" => Ok(PredefinedColorSpace::ProPhotoRGB),
+      "rec2020" => Ok(PredefinedColorSpace::Rec2020),
+      "xyz-d50" => Ok(PredefinedColorSpace::XYZD50),
+      "xyz" | "xyz-d65" => Ok(PredefinedColorSpace::XYZD65),
+      _ => Err(location.new_unexpected_token_error(Token::Ident(ident.clone())))
+    }
+  }
+}
+
+impl ToCss for PredefinedColorSpace {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    dest.write_str(match self {
+      PredefinedColorSpace::SRGB => "srgb",
+      PredefinedColorSpace::SRGBLinear => "srgb-linear",
+      PredefinedColorSpace::DisplayP3 => "display-p3",
+      PredefinedColorSpace::A98RGB => "a98-rgb",
+      PredefinedColorSpace::ProPhotoRGB => "prophoto-rgb",
+      PredefinedColorSpace::Rec2020 => "rec2020",
+      PredefinedColorSpace::XYZD50 => "xyz-d50",
+      PredefinedColorSpace::XYZD65 => "xyz"
+    })
+  }
+}
+
+/// A color in a predefined color space, using the `color()` function.
+/// https://www.w3.org/TR/css-color-4/#color-function
+#[derive(Debug, Clone, PartialEq)]
+pub struct PredefinedColor {
+  pub space: PredefinedColorSpace,
+  pub components: [f32; 3],
+  pub alpha: f32
+}
+
+impl PredefinedColor {
+  /// Converts the color to sRGB, clipping components that are out of gamut.
+  pub fn to_rgba(&self) -> RGBA {
+    use PredefinedColorSpace::*;
+    let c = self.components;
+    let srgb = match self.space {
+      SRGB => c,
+      SRGBLinear => c.map(gamma_srgb),
+      DisplayP3 => xyz_d65_to_linear_srgb(multiply(&P3_TO_XYZ, c.map(linear_srgb))).map(gamma_srgb),
+      A98RGB => xyz_d65_to_linear_srgb(multiply(&A98_TO_XYZ, c.map(linear_a98))).map(gamma_srgb),
+      ProPhotoRGB => xyz_d65_to_linear_srgb(d50_to_d65(multiply(&PROPHOTO_TO_XYZ_D50, c.map(linear_prophoto)))).map(gamma_srgb),
+      Rec2020 => xyz_d65_to_linear_srgb(multiply(&REC2020_TO_XYZ, c.map(linear_rec2020))).map(gamma_srgb),
+      XYZD50 => xyz_d65_to_linear_srgb(d50_to_d65(c)).map(gamma_srgb),
+      XYZD65 => xyz_d65_to_linear_srgb(c).map(gamma_srgb)
+    };
+
+    to_rgba(srgb, self.alpha)
+  }
+}
+
+impl ToCss for PredefinedColor {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    dest.write_str("color(")?;
+    self.space.to_css(dest)?;
+    for component in &self.components {
+      dest.write_char(' ')?;
+      serialize_number(*component, dest)?;
+    }
+    write_alpha(self.alpha, dest)?;
+    dest.write_char(')')
+  }
+}
+
+fn write_alpha<W>(alpha: f32, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+  if alpha < 1.0 {
+    dest.delim('/', true)?;
+    serialize_number(alpha, dest)?;
+  }
+  Ok(())
+}
+
+// The conversions below follow the sample code in the spec.
+// https://www.w3.org/TR/css-color-4/#color-conversion-code
+
+type Matrix = [[f32; 3]; 3];
+
+fn multiply(m: &Matrix, v: [f32; 3]) -> [f32; 3] {
+  [
+    m[0][0] * v[0] + m[0][1] * v[1] + m[0][2] * v[2],
+    m[1][0] * v[0] + m[1][1] * v[1] + m[1][2] * v[2],
+    m[2][0] * v[0] + m[2][1] * v[1] + m[2][2] * v[2]
+  ]
+}
+
+fn to_rgba(srgb: [f32; 3], alpha: f32) -> RGBA {
+  let clamp = |v: f32| (v.max(0.0).min(1.0) * 255.0).round() as u8;
+  RGBA::new(clamp(srgb[0]), clamp(srgb[1]), clamp(srgb[2]), clamp(alpha))
+}
+
+fn lch_to_lab(c: f32, h: f32) -> (f32, f32) {
+  let h = h.to_radians();
+  (c * h.cos(), c * h.sin())
+}
+
+fn lab_to_xyz_d50(l: f32, a: f32, b: f32) -> [f32; 3] {
+  const K: f32 = 24389.0 / 27.0;
+  const E: f32 = 216.0 / 24389.0;
+  const WHITE: [f32; 3] = [0.3457 / 0.3585, 1.0, (1.0 - 0.3457 - 0.3585) / 0.3585];
+
+  let f1 = (l + 16.0) / 116.0;
+  let f0 = a / 500.0 + f1;
+  let f2 = f1 - b / 200.0;
+  let x = if f0.powi(3) > E { f0.powi(3) } else { (116.0 * f0 - 16.0) / K };
+  let y = if l > K * E { f1.powi(3) } else { l / K };
+  let z = if f2.powi(3) > E { f2.powi(3) } else { (116.0 * f2 - 16.0) / K };
+  [x * WHITE[0], y * WHITE[1], z * WHITE[2]]
+}
+
+/// Adapts XYZ coordinates from the D50 to the D65 white point, using the Bradford transform.
+fn d50_to_d65(xyz: [f32; 3]) -> [f32; 3] {
+  const M: Matrix = [
+    [0.9554734527042182, -0.023098536874261423, 0.0632593086610217],
+    [-0.028369706963208136, 1.0099954580058226, 0.021041398966943008],
+    [0.012314001688319899, -0.020507696433477912, 1.3303659366080753]
+  ];
+  multiply(&M, xyz)
+}
+
+fn xyz_d65_to_linear_srgb(xyz: [f32; 3]) -> [f32; 3] {
+  const M: Matrix = [
+    [3.2409699419045226, -1.537383177570094, -0.4986107602930034],
+    [-0.9692436362808796, 1.8759675015077202, 0.04155505740717559],
+    [0.05563007969699366, -0.20397695888897652, 1.0569715142428786]
+  ];
+  multiply(&M, xyz)
+}
+
+// https://bottosson.github.io/posts/oklab/
+fn oklab_to_linear_srgb(l: f32, a: f32, b: f32) -> [f32; 3] {
+  let l_ = (l + 0.3963377774 * a + 0.2158037573 * b).powi(3);
+  let m_ = (l - 0.1055613458 * a - 0.0638541728 * b).powi(3);
+  let s_ = (l - 0.0894841775 * a - 1.2914855480 * b).powi(3);
+  [
+    4.0767416621 * l_ - 3.3077115913 * m_ + 0.2309699292 * s_,
+    -1.2684380046 * l_ + 2.6097574011 * m_ - 0.3413193965 * s_,
+    -0.0041960863 * l_ - 0.7034186147 * m_ + 1.7076147010 * s_
+  ]
+}
+
+const P3_TO_XYZ: Matrix = [
+  [0.4865709486482162, 0.26566769316909306, 0.1982172852343625],
+  [0.2289745640697488, 0.6917385218365064, 0.079286914093745],
+  [0.0, 0.04511338185890264, 1.043944368900976]
+];
+
+const A98_TO_XYZ: Matrix = [
+  [0.5766690429101305, 0.1855582379065463, 0.1882286462349947],
+  [0.29734497525053605, 0.6273635662554661, 0.07529145849399788],
+  [0.02703136138641234, 0.07068885253582723, 0.9913375368376388]
+];
+
+const PROPHOTO_TO_XYZ_D50: Matrix = [
+  [0.7977604896723027, 0.13518583717574031, 0.0313493495815248],
+  [0.2880711282292934, 0.7118432178101014, 0.00008565396060525902],
+  [0.0, 0.0, 0.8251046025104601]
+];
+
+const REC2020_TO_XYZ: Matrix = [
+  [0.6369580483012914, 0.14461690358620832, 0.1688809751641721],
+  [0.2627002120112671, 0.6779980715188708, 0.05930171646986196],
+  [0.0, 0.028072693049087428, 1.060985057710791]
+];
+
+/// Applies the sRGB transfer function to a linear component. Display P3 uses the same one.
+fn gamma_srgb(v: f32) -> f32 {
+  let abs = v.abs();
+  if abs > 0.0031308 {
+    v.signum() * (1.055 * abs.powf(1.0 / 2.4) - 0.055)
+  } else {
+    12.92 * v
+  }
+}
+
+fn linear_srgb(v: f32) -> f32 {
+  let abs = v.abs();
+  if abs < 0.04045 {
+    v / 12.92
+  } else {
+    v.signum() * ((abs + 0.055) / 1.055).powf(2.4)
+  }
+}
+
+fn linear_a98(v: f32) -> f32 {
+  v.signum() * v.abs().powf(563.0 / 256.0)
+}
+
+fn linear_prophoto(v: f32) -> f32 {
+  let abs = v.abs();
+  if abs <= 16.0 / 512.0 {
+    v / 16.0
+  } else {
+    v.signum() * abs.powf(1.8)
+  }
+}
+
+fn linear_rec2020(v: f32) -> f32 {
+  const A: f32 = 1.09929682680944;
+  const B: f32 = 0.018053968510807;
+  let abs = v.abs();
+  if abs < B * 4.5 {
+    v / 4.5
+  } else {
+    v.signum() * ((abs + A - 1.0) / A).powf(1.0 / 0.45)
+  }
 }
 
 // From esbuild: https://github.com/evanw/esbuild/blob/18e13bdfdca5cd3c7a2fae1a8bd739f8f891572c/internal/css_parser/css_decls_color.go#L218
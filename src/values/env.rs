@@ -0,0 +1,66 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::properties::custom::TokenList;
+use crate::warnings;
+use crate::compat;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// The function used to reference an environment variable. `constant()` is the legacy
+/// syntax, which is the only one supported by Safari 11.0 and iOS 11.0-11.1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EnvironmentFunction {
+  Env,
+  Constant
+}
+
+/// https://drafts.csswg.org/css-env-1/#env-function
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnvironmentVariable {
+  pub function: EnvironmentFunction,
+  pub name: String,
+  pub fallback: Option<TokenList>
+}
+
+impl Parse for EnvironmentVariable {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let f = input.expect_function()?.clone();
+    let function = match_ignore_ascii_case! { &f,
+      "env" => EnvironmentFunction::Env,
+      "constant" => EnvironmentFunction::Constant,
+      _ => return Err(location.new_unexpected_token_error(Token::Function(f.clone())))
+    };
+
+    if function == EnvironmentFunction::Env {
+      warnings::check_feature(location, compat::Feature::EnvFunction);
+    }
+
+    input.parse_nested_block(|input| {
+      let name = input.expect_ident()?.as_ref().into();
+      let fallback = if input.try_parse(|input| input.expect_comma()).is_ok() {
+        Some(TokenList::parse(input)?)
+      } else {
+        None
+      };
+
+      Ok(EnvironmentVariable { function, name, fallback })
+    })
+  }
+}
+
+impl ToCss for EnvironmentVariable {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self.function {
+      EnvironmentFunction::Env => dest.write_str("env(")?,
+      EnvironmentFunction::Constant => dest.write_str("constant(")?
+    }
+    serialize_identifier(&self.name, dest)?;
+    if let Some(fallback) = &self.fallback {
+      dest.delim(',', false)?;
+      fallback.to_css(dest)?;
+    }
+    dest.write_char(')')
+  }
+}
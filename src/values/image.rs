@@ -5,17 +5,23 @@ use super::color::CssColor;
 use super::length::{Length, LengthPercentage};
 use super::percentage::{Percentage, DimensionPercentage};
 use super::position::Position;
+use super::resolution::Resolution;
+use super::url::Url;
 use crate::traits::{Parse, ToCss};
+use crate::properties::VendorPrefix;
+use crate::properties::prefixes::{Browsers, Feature};
 use crate::macros::enum_property;
 use crate::printer::Printer;
 use std::fmt::Write;
+use crate::error::ParserError;
 
 /// https://www.w3.org/TR/css-images-3/#typedef-image
 #[derive(Debug, Clone, PartialEq)]
 pub enum Image {
   None,
-  Url(String),
-  Gradient(Gradient)
+  Url(Url),
+  Gradient(Gradient),
+  ImageSet(ImageSet)
 }
 
 impl Default for Image {
@@ -25,37 +31,212 @@ impl Default for Image {
 }
 
 impl Parse for Image {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|i| i.expect_ident_matching("none")).is_ok() {
       return Ok(Image::None)
     }
     
-    if let Ok(url) = input.try_parse(|input| input.expect_url()) {
-      return Ok(Image::Url(url.as_ref().into()))
+    if let Ok(url) = input.try_parse(Url::parse) {
+      return Ok(Image::Url(url))
     }
 
     if let Ok(grad) = input.try_parse(Gradient::parse) {
       return Ok(Image::Gradient(grad))
     }
 
+    if let Ok(image_set) = input.try_parse(ImageSet::parse) {
+      return Ok(Image::ImageSet(image_set))
+    }
+
     Err(input.new_error_for_next_token())
   }
 }
 
+impl Image {
+  /// Returns the vendor prefixes that are needed for this image in the given targets,
+  /// in addition to the unprefixed version.
+  pub fn get_necessary_prefixes(&self, targets: Browsers) -> VendorPrefix {
+    match self {
+      Image::ImageSet(image_set) if image_set.vendor_prefix == VendorPrefix::None => {
+        Feature::ImageSet.prefixes_for(targets) - VendorPrefix::None
+      }
+      _ => VendorPrefix::empty()
+    }
+  }
+
+  /// Appends the URLs referenced by the image to the given list.
+  pub fn collect_urls<'a>(&'a self, urls: &mut Vec<&'a Url>) {
+    match self {
+      Image::Url(url) => urls.push(url),
+      Image::ImageSet(image_set) => {
+        for option in &image_set.options {
+          option.image.collect_urls(urls);
+        }
+      }
+      Image::None | Image::Gradient(_) => {}
+    }
+  }
+
+  /// Returns a copy of the image with the given vendor prefix, or `None` if the
+  /// image cannot be represented with the prefixed syntax.
+  pub fn get_prefixed(&self, prefix: VendorPrefix) -> Option<Image> {
+    match self {
+      Image::ImageSet(image_set) => image_set.get_prefixed(prefix).map(Image::ImageSet),
+      _ => Some(self.clone())
+    }
+  }
+}
+
 impl ToCss for Image {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
     use Image::*;
-    use cssparser::ToCss;
     match self {
       None => dest.write_str("none"),
-      Url(url) => {
-        Token::UnquotedUrl(CowRcStr::from(url.as_ref())).to_css(dest)
+      Url(url) => url.to_css(dest),
+      Gradient(grad) => grad.to_css(dest),
+      ImageSet(image_set) => image_set.to_css(dest)
+    }
+  }
+}
+
+/// https://drafts.csswg.org/css-images-4/#image-set-notation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageSet {
+  pub options: Vec<ImageSetOption>,
+  pub vendor_prefix: VendorPrefix
+}
+
+impl Parse for ImageSet {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let f = input.expect_function()?.clone();
+    let vendor_prefix = match_ignore_ascii_case! { &f,
+      "image-set" => VendorPrefix::None,
+      "-webkit-image-set" => VendorPrefix::WebKit,
+      _ => return Err(location.new_unexpected_token_error(
+        cssparser::Token::Ident(f.clone())
+      ))
+    };
+
+    let options = input.parse_nested_block(|input| {
+      input.parse_comma_separated(ImageSetOption::parse)
+    })?;
+
+    Ok(ImageSet {
+      options,
+      vendor_prefix
+    })
+  }
+}
+
+impl ImageSet {
+  fn get_prefixed(&self, prefix: VendorPrefix) -> Option<ImageSet> {
+    // The legacy syntax does not support the type() function, so options with
+    // a type are omitted. If nothing is left, there is no prefixed equivalent.
+    let options: Vec<ImageSetOption> = self.options.iter()
+      .filter(|option| option.file_type.is_none())
+      .map(|option| ImageSetOption {
+        image: option.image.clone(),
+        resolution: Resolution::Dppx(option.resolution.to_dppx()),
+        file_type: None
+      })
+      .collect();
+
+    if options.is_empty() {
+      return None
+    }
+
+    Some(ImageSet {
+      options,
+      vendor_prefix: prefix
+    })
+  }
+}
+
+impl ToCss for ImageSet {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    self.vendor_prefix.to_css(dest)?;
+    dest.write_str("image-set(")?;
+    let mut first = true;
+    for option in &self.options {
+      if first {
+        first = false;
+      } else {
+        dest.delim(',', false)?;
       }
-      Gradient(grad) => grad.to_css(dest)
+      option.to_css(dest, self.vendor_prefix != VendorPrefix::None)?;
     }
+    dest.write_char(')')
   }
 }
 
+/// https://drafts.csswg.org/css-images-4/#typedef-image-set-option
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageSetOption {
+  pub image: Image,
+  pub resolution: Resolution,
+  pub file_type: Option<String>
+}
+
+impl Parse for ImageSetOption {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    // A bare string is equivalent to a url().
+    let image = if let Ok(url) = input.try_parse(|input| input.expect_string().map(|s| s.as_ref().to_owned())) {
+      Image::Url(Url { url })
+    } else {
+      match Image::parse(input)? {
+        // Nested image-set() functions are not allowed.
+        Image::ImageSet(_) => return Err(input.new_error(BasicParseErrorKind::QualifiedRuleInvalid)),
+        image => image
+      }
+    };
+
+    // The resolution and type may appear in either order, and the resolution defaults to 1x.
+    let (resolution, file_type) = if let Ok(resolution) = input.try_parse(Resolution::parse) {
+      (resolution, input.try_parse(parse_file_type).ok())
+    } else {
+      let file_type = input.try_parse(parse_file_type).ok();
+      (input.try_parse(Resolution::parse).unwrap_or(Resolution::Dppx(1.0)), file_type)
+    };
+
+    Ok(ImageSetOption {
+      image,
+      resolution,
+      file_type
+    })
+  }
+}
+
+impl ImageSetOption {
+  fn to_css<W>(&self, dest: &mut Printer<W>, is_prefixed: bool) -> std::fmt::Result where W: std::fmt::Write {
+    self.image.to_css(dest)?;
+
+    // The legacy syntax only supports the x unit.
+    dest.write_char(' ')?;
+    if is_prefixed {
+      self.resolution.to_dppx().to_css(dest)?;
+      dest.write_char('x')?;
+    } else {
+      self.resolution.to_css(dest)?;
+    }
+
+    if let Some(file_type) = &self.file_type {
+      dest.write_str(" type(")?;
+      serialize_string(file_type, dest)?;
+      dest.write_char(')')?;
+    }
+
+    Ok(())
+  }
+}
+
+fn parse_file_type<'i, 't>(input: &mut Parser<'i, 't>) -> Result<String, ParseError<'i, ParserError<'i>>> {
+  input.expect_function_matching("type")?;
+  input.parse_nested_block(|input| {
+    Ok(input.expect_string()?.as_ref().to_owned())
+  })
+}
+
 /// https://www.w3.org/TR/css-images-3/#gradients
 #[derive(Debug, Clone, PartialEq)]
 pub enum Gradient {
@@ -68,7 +249,7 @@ pub enum Gradient {
 }
 
 impl Parse for Gradient {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let location = input.current_source_location();
     let func = input.expect_function()?.clone();
     input.parse_nested_block(|input| {
@@ -111,12 +292,12 @@ impl ToCss for Gradient {
 /// https://www.w3.org/TR/css-images-3/#linear-gradients
 #[derive(Debug, Clone, PartialEq)]
 pub struct LinearGradient {
-  direction: LineDirection,
-  items: Vec<GradientItem<LengthPercentage>>,
+  pub direction: LineDirection,
+  pub items: Vec<GradientItem<LengthPercentage>>,
 }
 
 impl Parse for LinearGradient {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<LinearGradient, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<LinearGradient, ParseError<'i, ParserError<'i>>> {
     let direction = if let Ok(direction) = input.try_parse(LineDirection::parse) {
       input.expect_comma()?;
       direction
@@ -174,13 +355,13 @@ impl ToCss for LinearGradient {
 /// https://www.w3.org/TR/css-images-3/#radial-gradients
 #[derive(Debug, Clone, PartialEq)]
 pub struct RadialGradient {
-  shape: EndingShape,
-  position: Position,
-  items: Vec<GradientItem<LengthPercentage>>,
+  pub shape: EndingShape,
+  pub position: Position,
+  pub items: Vec<GradientItem<LengthPercentage>>,
 }
 
 impl Parse for RadialGradient {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<RadialGradient, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<RadialGradient, ParseError<'i, ParserError<'i>>> {
     let shape = input.try_parse(EndingShape::parse).ok();
     let position = input.try_parse(|input| {
       input.expect_ident_matching("at")?;
@@ -230,7 +411,7 @@ pub enum LineDirection {
 }
 
 impl Parse for LineDirection {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(angle) = input.try_parse(Angle::parse) {
       return Ok(LineDirection::Angle(angle))
     }
@@ -302,7 +483,7 @@ impl Default for EndingShape {
 }
 
 impl Parse for EndingShape {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     // Note: Ellipse::parse MUST run before Circle::parse for this to be correct. 
     if let Ok(ellipse) = input.try_parse(Ellipse::parse) {
       return Ok(EndingShape::Ellipse(ellipse))
@@ -332,7 +513,7 @@ pub enum Circle {
 }
 
 impl Parse for Circle {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(extent) = input.try_parse(ShapeExtent::parse) {
       // The `circle` keyword is required. If it's not there, then it's an ellipse.
       input.expect_ident_matching("circle")?;
@@ -386,7 +567,7 @@ pub enum Ellipse {
 }
 
 impl Parse for Ellipse {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(extent) = input.try_parse(ShapeExtent::parse) {
       // The `ellipse` keyword is optional, but only if the `circle` keyword is not present.
       // If it is, then we'll re-parse as a circle.
@@ -446,13 +627,13 @@ enum_property!(ShapeExtent,
 /// https://www.w3.org/TR/css-images-4/#conic-gradients
 #[derive(Debug, Clone, PartialEq)]
 pub struct ConicGradient {
-  angle: Angle,
-  position: Position,
-  items: Vec<GradientItem<AnglePercentage>>,
+  pub angle: Angle,
+  pub position: Position,
+  pub items: Vec<GradientItem<AnglePercentage>>,
 }
 
 impl ConicGradient {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let angle = input.try_parse(|input| {
       input.expect_ident_matching("from")?;
       Angle::parse(input)
@@ -502,12 +683,12 @@ impl ToCss for ConicGradient {
 /// https://www.w3.org/TR/css-images-4/#color-stop-syntax
 #[derive(Debug, Clone, PartialEq)]
 pub struct ColorStop<D> {
-  color: CssColor,
-  position: Option<D>
+  pub color: CssColor,
+  pub position: Option<D>
 }
 
 impl<D: Parse> Parse for ColorStop<D> {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let color = CssColor::parse(input)?;
     let position = input.try_parse(D::parse).ok();
     Ok(ColorStop {color, position })
@@ -540,7 +721,7 @@ impl<D: ToCss> ToCss for GradientItem<D> {
   }
 }
 
-fn parse_items<'i, 't, D: Parse>(input: &mut Parser<'i, 't>) -> Result<Vec<GradientItem<D>>, ParseError<'i, ()>> {
+fn parse_items<'i, 't, D: Parse>(input: &mut Parser<'i, 't>) -> Result<Vec<GradientItem<D>>, ParseError<'i, ParserError<'i>>> {
   let mut items = Vec::new();
   let mut seen_stop = false;
 
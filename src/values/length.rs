@@ -4,7 +4,8 @@ use crate::printer::Printer;
 use std::fmt::Write;
 use super::calc::Calc;
 use super::percentage::DimensionPercentage;
-use super::number::serialize_number;
+use super::number::{serialize_number, serialize_dimension};
+use crate::error::ParserError;
 
 /// https://drafts.csswg.org/css-values-4/#typedef-length-percentage
 pub type LengthPercentage = DimensionPercentage<LengthValue>;
@@ -27,7 +28,7 @@ pub enum LengthPercentageOrAuto {
 }
 
 impl Parse for LengthPercentageOrAuto {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|i| i.expect_ident_matching("auto")).is_ok() {
       return Ok(LengthPercentageOrAuto::Auto);
     }
@@ -57,6 +58,48 @@ const PX_PER_Q: f32 = PX_PER_CM / 40.0;
 const PX_PER_PT: f32 = PX_PER_IN / 72.0;
 const PX_PER_PC: f32 = PX_PER_IN / 6.0;
 
+/// An absolute length unit. Lengths in absolute units can be converted between each other.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LengthUnit {
+  Px,
+  In,
+  Cm,
+  Mm,
+  Q,
+  Pt,
+  Pc
+}
+
+impl LengthUnit {
+  fn px_per_unit(&self) -> f64 {
+    use LengthUnit::*;
+    match self {
+      Px => 1.0,
+      In => 96.0,
+      Cm => 96.0 / 2.54,
+      Mm => 96.0 / 25.4,
+      Q => 96.0 / 101.6,
+      Pt => 96.0 / 72.0,
+      Pc => 96.0 / 6.0
+    }
+  }
+}
+
+/// The sizes that font and viewport relative lengths are resolved against, in pixels,
+/// when they are known ahead of time, e.g. when rendering static content such as emails.
+/// Lengths in units whose size is not provided are not resolved.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LengthContext {
+  /// The font size of the element, for `em`.
+  pub font_size: Option<f32>,
+  /// The font size of the root element, for `rem`.
+  pub root_font_size: Option<f32>,
+  /// The width of the viewport, for `vw`, `vmin`, and `vmax`.
+  pub viewport_width: Option<f32>,
+  /// The height of the viewport, for `vh`, `vmin`, and `vmax`.
+  pub viewport_height: Option<f32>
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum LengthValue {
   Px(f32),
@@ -77,7 +120,7 @@ pub enum LengthValue {
 }
 
 impl Parse for LengthValue {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let location = input.current_source_location();
     let token = input.next()?;
     match *token {
@@ -112,44 +155,47 @@ impl Parse for LengthValue {
 
 impl ToCss for LengthValue {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
-    use cssparser::ToCss;
+    // Lengths in absolute units are converted to the unit with the shortest serialization
+    // when minifying, e.g. `96px` to `1in`.
+    if dest.minify {
+      if let Some(shortest) = self.to_shortest_unit() {
+        return shortest.serialize(dest)
+      }
+    }
+
+    self.serialize(dest)
+  }
+}
+
+/// Counts the bytes written to it, to compare the lengths of serializations without allocating.
+struct LengthCounter(usize);
+
+impl std::fmt::Write for LengthCounter {
+  fn write_str(&mut self, s: &str) -> std::fmt::Result {
+    self.0 += s.len();
+    Ok(())
+  }
+}
 
+impl LengthValue {
+  fn serialize<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
     let (value, unit) = self.to_unit_value();
     if value == 0.0 {
       return dest.write_char('0')
     }
 
-    let int_value = if value.fract() == 0.0 {
-      Some(value as i32)
-    } else {
-      None
-    };
-    let token = Token::Dimension {
-      has_sign: value < 0.0,
-      value: value,
-      int_value,
-      unit: CowRcStr::from(unit)
-    };
-    if value.abs() < 1.0 {
-      let mut s = String::new();
-      token.to_css(&mut s)?;
-      if value < 0.0 {
-        dest.write_char('-')?;
-        dest.write_str(s.trim_start_matches("-0"))
-      } else {
-        dest.write_str(s.trim_start_matches('0'))
-      }
-    } else {
-      token.to_css(dest)
-    }
+    serialize_dimension(value, unit, dest)
   }
-}
 
-impl LengthValue {
   pub fn zero() -> LengthValue {
     LengthValue::Px(0.0)
   }
 
+  /// Returns whether the length is zero, in any unit.
+  pub fn is_zero(&self) -> bool {
+    self.to_unit_value().0 == 0.0
+  }
+
   pub fn to_px(&self) -> Option<f32> {
     use LengthValue::*;
     match self {
@@ -164,6 +210,95 @@ impl LengthValue {
     }
   }
 
+  /// Converts the length to pixels like `to_px`, but also resolves font and
+  /// viewport relative lengths using the sizes in the given context.
+  pub fn to_px_with_context(&self, context: &LengthContext) -> Option<f32> {
+    use LengthValue::*;
+    let viewport = |f: fn(f32, f32) -> f32| match (context.viewport_width, context.viewport_height) {
+      (Some(width), Some(height)) => Some(f(width, height)),
+      _ => None
+    };
+
+    match self {
+      Em(value) => context.font_size.map(|size| value * size),
+      Rem(value) => context.root_font_size.map(|size| value * size),
+      Vw(value) => context.viewport_width.map(|width| value * width / 100.0),
+      Vh(value) => context.viewport_height.map(|height| value * height / 100.0),
+      Vmin(value) => viewport(f32::min).map(|size| value * size / 100.0),
+      Vmax(value) => viewport(f32::max).map(|size| value * size / 100.0),
+      _ => self.to_px()
+    }
+  }
+
+  fn to_absolute(&self) -> Option<(f32, LengthUnit)> {
+    use LengthValue::*;
+    match self {
+      Px(value) => Some((*value, LengthUnit::Px)),
+      In(value) => Some((*value, LengthUnit::In)),
+      Cm(value) => Some((*value, LengthUnit::Cm)),
+      Mm(value) => Some((*value, LengthUnit::Mm)),
+      Q(value) => Some((*value, LengthUnit::Q)),
+      Pt(value) => Some((*value, LengthUnit::Pt)),
+      Pc(value) => Some((*value, LengthUnit::Pc)),
+      _ => None
+    }
+  }
+
+  fn from_absolute(value: f32, unit: LengthUnit) -> LengthValue {
+    match unit {
+      LengthUnit::Px => LengthValue::Px(value),
+      LengthUnit::In => LengthValue::In(value),
+      LengthUnit::Cm => LengthValue::Cm(value),
+      LengthUnit::Mm => LengthValue::Mm(value),
+      LengthUnit::Q => LengthValue::Q(value),
+      LengthUnit::Pt => LengthValue::Pt(value),
+      LengthUnit::Pc => LengthValue::Pc(value)
+    }
+  }
+
+  /// Converts a length in an absolute unit to the given unit.
+  /// Returns `None` for relative lengths, which cannot be converted.
+  pub fn to_unit(&self, unit: LengthUnit) -> Option<LengthValue> {
+    let (value, from) = self.to_absolute()?;
+    let converted = value as f64 * from.px_per_unit() / unit.px_per_unit();
+    Some(LengthValue::from_absolute(converted as f32, unit))
+  }
+
+  /// Returns the equivalent length in the absolute unit with the shortest serialization,
+  /// if it is shorter than the current one. Only conversions that are exact to three
+  /// decimal places are considered. `Q` is never chosen since older browsers don't support it.
+  fn to_shortest_unit(&self) -> Option<LengthValue> {
+    let (value, unit) = self.to_absolute()?;
+    if value == 0.0 {
+      return None
+    }
+
+    let len = |value: &LengthValue| {
+      let mut counter = LengthCounter(0);
+      let _ = value.serialize(&mut Printer::new(&mut counter, true));
+      counter.0
+    };
+
+    let px = value as f64 * unit.px_per_unit();
+    let mut shortest = None;
+    let mut shortest_len = len(self);
+    for unit in [LengthUnit::Px, LengthUnit::In, LengthUnit::Cm, LengthUnit::Mm, LengthUnit::Pt, LengthUnit::Pc].iter() {
+      let thousandths = px / unit.px_per_unit() * 1000.0;
+      if (thousandths - thousandths.round()).abs() > 0.01 {
+        continue
+      }
+
+      let candidate = LengthValue::from_absolute((thousandths.round() / 1000.0) as f32, *unit);
+      let candidate_len = len(&candidate);
+      if candidate_len < shortest_len {
+        shortest = Some(candidate);
+        shortest_len = candidate_len;
+      }
+    }
+
+    shortest
+  }
+
   pub fn to_unit_value(&self) -> (f32, &str) {
     use LengthValue::*;
     match self {
@@ -317,7 +452,7 @@ pub enum Length {
 }
 
 impl Parse for Length {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     match input.try_parse(Calc::parse) {
       Ok(Calc::Value(v)) => return Ok(*v),
       Ok(calc) => return Ok(Length::Calc(Box::new(calc))),
@@ -369,6 +504,13 @@ impl Length {
     Length::Value(LengthValue::Px(px))
   }
 
+  pub fn is_zero(&self) -> bool {
+    match self {
+      Length::Value(value) => value.is_zero(),
+      _ => false
+    }
+  }
+
   pub fn to_px(&self) -> Option<f32> {
     match self {
       Length::Value(a) => a.to_px(),
@@ -376,6 +518,22 @@ impl Length {
     }
   }
 
+  pub fn to_px_with_context(&self, context: &LengthContext) -> Option<f32> {
+    match self {
+      Length::Value(a) => a.to_px_with_context(context),
+      _ => None
+    }
+  }
+
+  /// Converts a length in an absolute unit to the given unit.
+  /// Returns `None` for relative lengths and `calc()` expressions.
+  pub fn to_unit(&self, unit: LengthUnit) -> Option<Length> {
+    match self {
+      Length::Value(a) => a.to_unit(unit).map(Length::Value),
+      _ => None
+    }
+  }
+
   fn add(self, other: Length) -> Length {
     let mut a = self;
     let mut b = other;
@@ -511,7 +669,7 @@ pub enum LengthOrNumber {
 }
 
 impl Parse for LengthOrNumber {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     // Parse number first so unitless numbers are not parsed as lengths.
     if let Ok(number) = input.try_parse(f32::parse) {
       return Ok(LengthOrNumber::Number(number))
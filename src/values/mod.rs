@@ -12,3 +12,8 @@ pub mod percentage;
 pub mod alpha;
 pub mod position;
 pub mod number;
+pub mod env;
+pub mod resolution;
+pub mod url;
+pub mod ratio;
+pub mod shape;
@@ -3,9 +3,10 @@ use crate::traits::{Parse, ToCss};
 use crate::printer::Printer;
 use std::fmt::Write;
 use super::calc::Calc;
+use crate::error::ParserError;
 
 impl Parse for f32 {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     match input.try_parse(Calc::parse) {
       Ok(Calc::Value(v)) => return Ok(*v),
       Ok(Calc::Number(n)) => return Ok(n),
@@ -43,28 +44,88 @@ impl std::convert::From<Calc<f32>> for f32 {
   }
 }
 
+impl Parse for i32 {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    Ok(input.expect_integer()?)
+  }
+}
+
+impl ToCss for i32 {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    write!(dest, "{}", self)
+  }
+}
+
+/// Serializes a number with up to the printer's precision in significant digits, 6 by default,
+/// omitting the leading zero before the decimal point, e.g. `.5`. This only relies on the exact
+/// decimal formatting in the standard library rather than a shortest round-trip algorithm,
+/// so the output is the same on every platform. Negative zero is serialized as `0`.
 pub fn serialize_number<W>(number: f32, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
-  use cssparser::ToCss;
-  let int_value = if number.fract() == 0.0 {
-    Some(number as i32)
-  } else {
-    None
-  };
-  let tok = Token::Number {
-    has_sign: number < 0.0,
-    value: number,
-    int_value
-  };
-  if number != 0.0 && number.abs() < 1.0 {
-    let mut s = String::new();
-    tok.to_css(&mut s)?;
-    if number < 0.0 {
-      dest.write_char('-')?;
-      dest.write_str(s.trim_start_matches("-0"))
-    } else {
-      dest.write_str(s.trim_start_matches('0'))
+  if number == 0.0 {
+    return dest.write_char('0')
+  }
+
+  if !number.is_finite() {
+    return write!(dest, "{}", number)
+  }
+
+  if number.fract() == 0.0 && number.abs() < i32::MAX as f32 {
+    return write!(dest, "{}", number as i32)
+  }
+
+  // The exponential format rounds the mantissa to the given number of digits,
+  // from which the digits are placed around the decimal point.
+  let formatted = format!("{:.*e}", dest.precision.max(1) as usize - 1, number.abs());
+  let (mantissa, exponent) = formatted.split_once('e').unwrap();
+  let exponent: i32 = exponent.parse().unwrap();
+  let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+  let digits = digits.trim_end_matches('0');
+
+  if number < 0.0 {
+    dest.write_char('-')?;
+  }
+
+  if exponent < 0 {
+    dest.write_char('.')?;
+    for _ in 1..-exponent {
+      dest.write_char('0')?;
     }
+    return dest.write_str(digits)
+  }
+
+  let int_len = exponent as usize + 1;
+  if digits.len() <= int_len {
+    dest.write_str(digits)?;
+    for _ in digits.len()..int_len {
+      dest.write_char('0')?;
+    }
+    Ok(())
   } else {
-    tok.to_css(dest)
+    dest.write_str(&digits[..int_len])?;
+    dest.write_char('.')?;
+    dest.write_str(&digits[int_len..])
   }
 }
+
+/// Serializes a number followed by a unit, e.g. `.5em`.
+pub fn serialize_dimension<W>(value: f32, unit: &str, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+  serialize_number(value, dest)?;
+  dest.write_str(unit)
+}
+
+/// The tolerance for comparing computed values, e.g. the components of a matrix, so that
+/// rounding errors such as `1.0000001` do not prevent them from being simplified.
+const EPSILON: f32 = 1e-6;
+
+/// Returns whether two numbers are equal within a small tolerance, which is absolute
+/// for numbers close to zero, and relative to the size of the numbers otherwise.
+pub fn approx_eq(a: f32, b: f32) -> bool {
+  (a - b).abs() <= EPSILON * a.abs().max(b.abs()).max(1.0)
+}
+
+/// Rounds a number to the number of decimal places that fit in the given number of significant
+/// digits for values between 1 and 10, e.g. 5 digits for a precision of 6, which are printed.
+pub fn round_to_precision(value: f32, precision: u8) -> f32 {
+  let factor = 10f32.powi(precision.max(1) as i32 - 1);
+  (value * factor).round() / factor
+}
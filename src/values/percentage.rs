@@ -1,16 +1,16 @@
 use cssparser::*;
 use crate::traits::{Parse, ToCss, TryAdd};
 use crate::printer::Printer;
-use std::fmt::Write;
 use super::calc::Calc;
-use super::number::serialize_number;
+use super::number::{serialize_number, serialize_dimension};
+use crate::error::ParserError;
 
 /// https://drafts.csswg.org/css-values-4/#percentages
 #[derive(Debug, Clone, PartialEq)]
 pub struct Percentage(pub f32);
 
 impl Parse for Percentage {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     match input.try_parse(Calc::parse) {
       Ok(Calc::Value(v)) => return Ok(*v),
       // Percentages are always compatible, so they will always compute to a value.
@@ -25,29 +25,7 @@ impl Parse for Percentage {
 
 impl ToCss for Percentage {
   fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
-    use cssparser::ToCss;
-    let int_value = if (self.0 * 100.0).fract() == 0.0 {
-      Some(self.0 as i32)
-    } else {
-      None
-    };
-    let percent = Token::Percentage {
-      has_sign: self.0 < 0.0,
-      unit_value: self.0,
-      int_value
-    };
-    if self.0 != 0.0 && self.0.abs() < 0.01 {
-      let mut s = String::new();
-      percent.to_css(&mut s)?;
-      if self.0 < 0.0 {
-        dest.write_char('-')?;
-        dest.write_str(s.trim_start_matches("-0"))
-      } else {
-        dest.write_str(s.trim_start_matches('0'))
-      }
-    } else {
-      percent.to_css(dest)
-    }
+    serialize_dimension(self.0 * 100.0, "%", dest)
   }
 }
 
@@ -107,7 +85,7 @@ pub enum NumberOrPercentage {
 }
 
 impl Parse for NumberOrPercentage {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if let Ok(number) = input.try_parse(f32::parse) {
       return Ok(NumberOrPercentage::Number(number))
     }
@@ -129,6 +107,42 @@ impl ToCss for NumberOrPercentage {
   }
 }
 
+impl NumberOrPercentage {
+  /// Returns the value as a number, where 100% is 1.
+  pub fn to_number(&self) -> f32 {
+    match self {
+      NumberOrPercentage::Number(number) => *number,
+      NumberOrPercentage::Percentage(percent) => percent.0
+    }
+  }
+
+  /// Clamps the value to the given range, where 100% is 1, preserving its form.
+  pub fn clamp(&self, min: f32, max: f32) -> NumberOrPercentage {
+    match self {
+      NumberOrPercentage::Number(number) => NumberOrPercentage::Number(number.max(min).min(max)),
+      NumberOrPercentage::Percentage(percent) => NumberOrPercentage::Percentage(Percentage(percent.0.max(min).min(max)))
+    }
+  }
+
+  /// Serializes the value, converting a percentage to the equivalent number when minifying
+  /// unless that is longer. Numbers are never converted to percentages, since older browsers
+  /// don't accept percentages everywhere numbers are allowed. Only valid where both forms
+  /// are equivalent, e.g. `opacity` and `scale()`, but not `border-image-slice`.
+  pub fn to_css_interchangeable<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    if let NumberOrPercentage::Percentage(percent) = self {
+      if dest.minify {
+        let mut number = String::new();
+        serialize_number(percent.0, &mut Printer::new(&mut number, true))?;
+        let mut percentage = String::new();
+        percent.to_css(&mut Printer::new(&mut percentage, true))?;
+        return dest.write_str(if number.len() <= percentage.len() { &number } else { &percentage })
+      }
+    }
+
+    self.to_css(dest)
+  }
+}
+
 impl std::cmp::PartialEq<f32> for NumberOrPercentage {
   fn eq(&self, other: &f32) -> bool {
     match self {
@@ -158,7 +172,7 @@ pub enum DimensionPercentage<D> {
 }
 
 impl<D: Parse + std::ops::Mul<f32, Output = D> + TryAdd<D> + Clone + std::cmp::PartialEq<f32> + std::cmp::PartialOrd<f32> + std::cmp::PartialOrd<D> + std::fmt::Debug> Parse for DimensionPercentage<D> {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     match input.try_parse(Calc::parse) {
       Ok(Calc::Value(v)) => return Ok(*v),
       Ok(calc) => return Ok(DimensionPercentage::Calc(Box::new(calc))),
@@ -286,6 +300,12 @@ impl<D: TryAdd<D> + Clone + std::cmp::PartialEq<f32> + std::cmp::PartialOrd<f32>
   }
 }
 
+impl<D: TryAdd<D> + Clone + std::cmp::PartialEq<f32> + std::cmp::PartialOrd<f32> + std::fmt::Debug> TryAdd<DimensionPercentage<D>> for DimensionPercentage<D> {
+  fn try_add(&self, other: &DimensionPercentage<D>) -> Option<DimensionPercentage<D>> {
+    self.add_recursive(other)
+  }
+}
+
 impl<D> std::convert::Into<Calc<DimensionPercentage<D>>> for DimensionPercentage<D> {
   fn into(self) -> Calc<DimensionPercentage<D>> {
     match self {
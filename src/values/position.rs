@@ -5,6 +5,7 @@ use crate::printer::Printer;
 use std::fmt::Write;
 use super::length::LengthPercentage;
 use super::percentage::Percentage;
+use crate::error::ParserError;
 
 /// https://www.w3.org/TR/css-backgrounds-3/#background-position
 #[derive(Debug, Clone, PartialEq)]
@@ -39,7 +40,7 @@ impl Default for Position {
 }
 
 impl Parse for Position {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     match input.try_parse(HorizontalPosition::parse) {
       Ok(HorizontalPosition::Center) => {
         // Try parsing a vertical position next.
@@ -100,7 +101,7 @@ impl Parse for Position {
 
     // If the horizontal position didn't parse, then it must be out of order. Try vertical position keyword.
     let y_keyword = VerticalPositionKeyword::parse(input)?;
-    let lp_and_x_pos: Result<_, ParseError<()>> = input.try_parse(|i| {
+    let lp_and_x_pos: Result<_, ParseError<ParserError>> = input.try_parse(|i| {
       let y_lp = i.try_parse(LengthPercentage::parse).ok();
       if let Ok(x_keyword) = i.try_parse(HorizontalPositionKeyword::parse) {
         let x_lp = i.try_parse(LengthPercentage::parse).ok();
@@ -201,7 +202,7 @@ pub enum PositionComponent<S> {
 }
 
 impl<S: Parse> Parse for PositionComponent<S> {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|i| i.expect_ident_matching("center")).is_ok() {
       return Ok(PositionComponent::Center);
     }
@@ -0,0 +1,54 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::error::ParserError;
+
+/// https://drafts.csswg.org/css-values-4/#ratios
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ratio(pub f32, pub f32);
+
+impl Parse for Ratio {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let first = f32::parse(input)?;
+    let second = if input.try_parse(|input| input.expect_delim('/')).is_ok() {
+      f32::parse(input)?
+    } else {
+      1.0
+    };
+
+    if first < 0.0 || second < 0.0 {
+      return Err(input.new_error(BasicParseErrorKind::QualifiedRuleInvalid))
+    }
+
+    Ok(Ratio(first, second))
+  }
+}
+
+impl ToCss for Ratio {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    let Ratio(mut first, mut second) = *self;
+
+    // Ratios of integers are reduced, e.g. 32 / 18 becomes 16 / 9.
+    if dest.minify && first > 0.0 && second > 0.0 && first.fract() == 0.0 && second.fract() == 0.0 {
+      let divisor = gcd(first as u64, second as u64) as f32;
+      first /= divisor;
+      second /= divisor;
+    }
+
+    first.to_css(dest)?;
+    // The second number defaults to 1.
+    if second != 1.0 {
+      dest.delim('/', true)?;
+      second.to_css(dest)?;
+    }
+    Ok(())
+  }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+  if b == 0 {
+    a
+  } else {
+    gcd(b, a % b)
+  }
+}
@@ -0,0 +1,91 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::error::ParserError;
+
+/// https://www.w3.org/TR/css3-values/#resolution-value
+#[derive(Debug, Clone, PartialEq)]
+pub enum Resolution {
+  Dpi(f32),
+  Dpcm(f32),
+  Dppx(f32)
+}
+
+impl Resolution {
+  pub fn to_dppx(&self) -> f32 {
+    match self {
+      Resolution::Dpi(dpi) => dpi / 96.0,
+      Resolution::Dpcm(dpcm) => dpcm * 2.54 / 96.0,
+      Resolution::Dppx(dppx) => *dppx
+    }
+  }
+
+  fn value_and_unit(&self) -> (f32, &'static str) {
+    match self {
+      Resolution::Dpi(dpi) => (*dpi, "dpi"),
+      Resolution::Dpcm(dpcm) => (*dpcm, "dpcm"),
+      // The `x` alias is shorter than `dppx` and supported everywhere resolutions are.
+      Resolution::Dppx(dppx) => (*dppx, "x")
+    }
+  }
+}
+
+impl Parse for Resolution {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    match *input.next()? {
+      Token::Dimension { value, ref unit, .. } => {
+        match_ignore_ascii_case! { unit,
+          "dpi" => Ok(Resolution::Dpi(value)),
+          "dpcm" => Ok(Resolution::Dpcm(value)),
+          "dppx" | "x" => Ok(Resolution::Dppx(value)),
+          _ => Err(input.new_error(BasicParseErrorKind::QualifiedRuleInvalid))
+        }
+      }
+      ref t => Err(location.new_unexpected_token_error(t.clone())),
+    }
+  }
+}
+
+impl ToCss for Resolution {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    if dest.minify {
+      // Use whichever unit serializes shortest, e.g. 2x rather than 192dpi. Units that
+      // can't represent the value exactly (to the precision we serialize) are skipped.
+      let dppx = self.to_dppx();
+      let candidates = [
+        Resolution::Dppx(dppx),
+        Resolution::Dpi(dppx * 96.0),
+        Resolution::Dpcm(dppx * 96.0 / 2.54)
+      ];
+
+      let mut shortest = serialize_resolution(self)?;
+      for candidate in &candidates {
+        let (value, _) = candidate.value_and_unit();
+        if ((value * 1000.0).round() - value * 1000.0).abs() > 0.001 {
+          continue
+        }
+
+        let s = serialize_resolution(candidate)?;
+        if s.len() < shortest.len() {
+          shortest = s;
+        }
+      }
+
+      return dest.write_str(&shortest)
+    }
+
+    let (value, unit) = self.value_and_unit();
+    value.to_css(dest)?;
+    dest.write_str(unit)
+  }
+}
+
+fn serialize_resolution(resolution: &Resolution) -> Result<String, std::fmt::Error> {
+  let mut s = String::new();
+  let mut printer = Printer::new(&mut s, true);
+  let (value, unit) = resolution.value_and_unit();
+  value.to_css(&mut printer)?;
+  printer.write_str(unit)?;
+  Ok(s)
+}
@@ -0,0 +1,202 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::macros::enum_property;
+use crate::properties::border_radius::BorderRadius;
+use super::length::LengthPercentage;
+use super::position::Position;
+use super::rect::Rect;
+use std::fmt::Write;
+use crate::error::ParserError;
+
+/// https://www.w3.org/TR/css-shapes-1/#basic-shape-functions
+#[derive(Debug, Clone, PartialEq)]
+pub enum BasicShape {
+  Inset {
+    rect: Rect<LengthPercentage>,
+    radius: Option<BorderRadius>
+  },
+  Circle {
+    radius: ShapeRadius,
+    position: Position
+  },
+  Ellipse {
+    x: ShapeRadius,
+    y: ShapeRadius,
+    position: Position
+  },
+  Polygon {
+    fill_rule: FillRule,
+    points: Vec<(LengthPercentage, LengthPercentage)>
+  }
+}
+
+impl Parse for BasicShape {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let location = input.current_source_location();
+    let function = input.expect_function()?.clone();
+    input.parse_nested_block(|input| {
+      match_ignore_ascii_case! { &function,
+        "inset" => {
+          let rect = Rect::parse(input)?;
+          let radius = if input.try_parse(|input| input.expect_ident_matching("round")).is_ok() {
+            Some(BorderRadius::parse(input)?)
+          } else {
+            None
+          };
+          Ok(BasicShape::Inset { rect, radius })
+        },
+        "circle" => {
+          let radius = input.try_parse(ShapeRadius::parse).unwrap_or_default();
+          let position = parse_shape_position(input)?;
+          Ok(BasicShape::Circle { radius, position })
+        },
+        "ellipse" => {
+          let (x, y) = if let Ok(x) = input.try_parse(ShapeRadius::parse) {
+            (x, ShapeRadius::parse(input)?)
+          } else {
+            (ShapeRadius::default(), ShapeRadius::default())
+          };
+          let position = parse_shape_position(input)?;
+          Ok(BasicShape::Ellipse { x, y, position })
+        },
+        "polygon" => {
+          let fill_rule = input.try_parse(|input| {
+            let fill_rule = FillRule::parse(input)?;
+            input.expect_comma()?;
+            Ok::<_, ParseError<'i, ParserError<'i>>>(fill_rule)
+          }).unwrap_or(FillRule::Nonzero);
+          let points = input.parse_comma_separated(|input| {
+            Ok::<_, ParseError<'i, ParserError<'i>>>((LengthPercentage::parse(input)?, LengthPercentage::parse(input)?))
+          })?;
+          Ok(BasicShape::Polygon { fill_rule, points })
+        },
+        _ => Err(location.new_unexpected_token_error(
+          cssparser::Token::Ident(function.clone())
+        ))
+      }
+    })
+  }
+}
+
+/// Parses the optional `at <position>` of a `circle()` or `ellipse()`, which defaults to the center.
+fn parse_shape_position<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Position, ParseError<'i, ParserError<'i>>> {
+  if input.try_parse(|input| input.expect_ident_matching("at")).is_ok() {
+    return Position::parse(input)
+  }
+
+  Ok(Position::center())
+}
+
+impl ToCss for BasicShape {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      BasicShape::Inset { rect, radius } => {
+        dest.write_str("inset(")?;
+        rect.to_css(dest)?;
+        if let Some(radius) = radius {
+          dest.write_str(" round ")?;
+          radius.to_css(dest)?;
+        }
+      }
+      BasicShape::Circle { radius, position } => {
+        dest.write_str("circle(")?;
+        let has_radius = *radius != ShapeRadius::default();
+        if has_radius {
+          radius.to_css(dest)?;
+        }
+        write_shape_position(position, has_radius, dest)?;
+      }
+      BasicShape::Ellipse { x, y, position } => {
+        dest.write_str("ellipse(")?;
+        let has_radius = *x != ShapeRadius::default() || *y != ShapeRadius::default();
+        if has_radius {
+          x.to_css(dest)?;
+          dest.write_char(' ')?;
+          y.to_css(dest)?;
+        }
+        write_shape_position(position, has_radius, dest)?;
+      }
+      BasicShape::Polygon { fill_rule, points } => {
+        dest.write_str("polygon(")?;
+        if *fill_rule != FillRule::Nonzero {
+          fill_rule.to_css(dest)?;
+          dest.delim(',', false)?;
+        }
+        let mut first = true;
+        for (x, y) in points {
+          if first {
+            first = false;
+          } else {
+            dest.delim(',', false)?;
+          }
+          x.to_css(dest)?;
+          dest.write_char(' ')?;
+          y.to_css(dest)?;
+        }
+      }
+    }
+    dest.write_char(')')
+  }
+}
+
+fn write_shape_position<W>(position: &Position, has_radius: bool, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+  // The center is the default, so it is omitted.
+  if position.is_center() {
+    return Ok(())
+  }
+
+  if has_radius {
+    dest.write_char(' ')?;
+  }
+  dest.write_str("at ")?;
+  position.to_css(dest)
+}
+
+/// https://www.w3.org/TR/css-shapes-1/#typedef-shape-radius
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeRadius {
+  LengthPercentage(LengthPercentage),
+  ClosestSide,
+  FarthestSide
+}
+
+impl Default for ShapeRadius {
+  fn default() -> ShapeRadius {
+    ShapeRadius::ClosestSide
+  }
+}
+
+impl Parse for ShapeRadius {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    if let Ok(length) = input.try_parse(LengthPercentage::parse) {
+      return Ok(ShapeRadius::LengthPercentage(length))
+    }
+
+    let location = input.current_source_location();
+    let ident = input.expect_ident()?;
+    match_ignore_ascii_case! { &*ident,
+      "closest-side" => Ok(ShapeRadius::ClosestSide),
+      "farthest-side" => Ok(ShapeRadius::FarthestSide),
+      _ => Err(location.new_unexpected_token_error(
+        cssparser::Token::Ident(ident.clone())
+      ))
+    }
+  }
+}
+
+impl ToCss for ShapeRadius {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    match self {
+      ShapeRadius::LengthPercentage(length) => length.to_css(dest),
+      ShapeRadius::ClosestSide => dest.write_str("closest-side"),
+      ShapeRadius::FarthestSide => dest.write_str("farthest-side")
+    }
+  }
+}
+
+// https://www.w3.org/TR/css-shapes-1/#funcdef-polygon
+enum_property!(FillRule,
+  Nonzero,
+  Evenodd
+);
@@ -2,7 +2,10 @@ use cssparser::*;
 use crate::traits::{Parse, ToCss};
 use crate::printer::Printer;
 use super::length::LengthPercentage;
+use super::ratio::Ratio;
+use std::fmt::Write;
 use crate::macros::enum_property;
+use crate::error::ParserError;
 
 /// https://drafts.csswg.org/css-sizing-3/#specifying-sizes
 
@@ -17,7 +20,7 @@ pub enum Size {
 }
 
 impl Parse for Size {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|i| i.expect_ident_matching("auto")).is_ok() {
       return Ok(Size::Auto);
     }
@@ -64,7 +67,7 @@ impl ToCss for Size {
 pub struct Size2D<T>(pub T, pub T);
 
 impl<T> Parse for Size2D<T> where T: Parse + Clone {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     let first = T::parse(input)?;
     let second = input.try_parse(T::parse).unwrap_or_else(|_| first.clone());
     Ok(Size2D(first, second))
@@ -94,7 +97,7 @@ pub enum MinMaxSize {
 }
 
 impl Parse for MinMaxSize {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     if input.try_parse(|i| i.expect_ident_matching("none")).is_ok() {
       return Ok(MinMaxSize::None);
     }
@@ -137,7 +140,7 @@ impl ToCss for MinMaxSize {
   }
 }
 
-fn parse_fit_content<'i, 't>(input: &mut Parser<'i, 't>) -> Result<LengthPercentage, ParseError<'i, ()>> {
+fn parse_fit_content<'i, 't>(input: &mut Parser<'i, 't>) -> Result<LengthPercentage, ParseError<'i, ParserError<'i>>> {
   input.expect_function_matching("fit-content")?;
   input.parse_nested_block(|input| LengthPercentage::parse(input))
 }
@@ -147,3 +150,43 @@ enum_property!(BoxSizing,
   ("content-box", ContentBox),
   ("border-box", BorderBox)
 );
+
+/// https://drafts.csswg.org/css-sizing-4/#aspect-ratio
+#[derive(Debug, Clone, PartialEq)]
+pub struct AspectRatio {
+  pub auto: bool,
+  pub ratio: Option<Ratio>
+}
+
+impl Parse for AspectRatio {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let mut auto = input.try_parse(|i| i.expect_ident_matching("auto")).is_ok();
+    let ratio = input.try_parse(Ratio::parse).ok();
+    if !auto {
+      auto = input.try_parse(|i| i.expect_ident_matching("auto")).is_ok();
+    }
+
+    if !auto && ratio.is_none() {
+      return Err(input.new_error_for_next_token())
+    }
+
+    Ok(AspectRatio { auto, ratio })
+  }
+}
+
+impl ToCss for AspectRatio {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    if self.auto {
+      dest.write_str("auto")?;
+    }
+
+    if let Some(ratio) = &self.ratio {
+      if self.auto {
+        dest.write_char(' ')?;
+      }
+      ratio.to_css(dest)?;
+    }
+
+    Ok(())
+  }
+}
@@ -2,6 +2,7 @@ use cssparser::*;
 use crate::traits::{Parse, ToCss};
 use crate::printer::Printer;
 use super::calc::Calc;
+use crate::error::ParserError;
 
 /// https://www.w3.org/TR/css3-values/#time-value
 #[derive(Debug, Clone, PartialEq)]
@@ -20,7 +21,7 @@ impl Time {
 }
 
 impl Parse for Time {
-  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ()>> {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
     match input.try_parse(Calc::parse) {
       Ok(Calc::Value(v)) => return Ok(*v),
       // Time is always compatible, so they will always compute to a value.
@@ -103,7 +104,7 @@ impl std::ops::Add<Time> for Time {
       (Time::Seconds(a), Time::Seconds(b)) => Time::Seconds(a + b),
       (Time::Milliseconds(a), Time::Milliseconds(b)) => Time::Milliseconds(a + b),
       (Time::Seconds(a), Time::Milliseconds(b)) => Time::Seconds(a + b / 1000.0),
-      (Time::Milliseconds(a), Time::Seconds(b)) => Time::Seconds(a + b * 1000.0),
+      (Time::Milliseconds(a), Time::Seconds(b)) => Time::Milliseconds(a + b * 1000.0),
     }
   }
 }
@@ -0,0 +1,37 @@
+use cssparser::*;
+use crate::traits::{Parse, ToCss};
+use crate::printer::Printer;
+use crate::error::ParserError;
+
+/// https://www.w3.org/TR/css-values-4/#urls
+#[derive(Debug, Clone, PartialEq)]
+pub struct Url {
+  /// The URL, with any escapes in the source resolved.
+  pub url: String
+}
+
+impl Parse for Url {
+  fn parse<'i, 't>(input: &mut Parser<'i, 't>) -> Result<Self, ParseError<'i, ParserError<'i>>> {
+    let url = input.expect_url()?.as_ref().to_owned();
+    Ok(Url { url })
+  }
+}
+
+impl ToCss for Url {
+  fn to_css<W>(&self, dest: &mut Printer<W>) -> std::fmt::Result where W: std::fmt::Write {
+    // Escapes are normalized on output. Characters such as spaces, quotes, and parentheses
+    // must be escaped in unquoted URLs, so the quoted form is used when that is shorter.
+    let mut unquoted = String::new();
+    cssparser::ToCss::to_css(&Token::UnquotedUrl(CowRcStr::from(self.url.as_ref())), &mut unquoted)?;
+
+    let mut quoted = String::from("url(");
+    serialize_string(&self.url, &mut quoted)?;
+    quoted.push(')');
+
+    if quoted.len() < unquoted.len() {
+      dest.write_str(&quoted)
+    } else {
+      dest.write_str(&unquoted)
+    }
+  }
+}
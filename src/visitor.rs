@@ -0,0 +1,917 @@
+use smallvec::SmallVec;
+use crate::parser::{CssRule, DeclarationBlock};
+use crate::declaration::Declaration;
+use crate::stylesheet::{StyleSheet, StyleAttribute};
+use crate::rules::font_face::{FontFaceProperty, Source};
+use crate::properties::Property;
+use crate::properties::custom::{TokenList, TokenOrValue};
+use crate::properties::border::{BorderSideWidth, GenericBorder};
+use crate::properties::border_radius::{BorderRadius, BorderCornerRadius};
+use crate::properties::border_image::{BorderImage, BorderImageSideWidth};
+use crate::properties::background::{Background, BackgroundSize};
+use crate::properties::box_shadow::BoxShadow;
+use crate::properties::text::{TextShadow, TextDecoration, TextDecorationThickness, TextEmphasis};
+use crate::properties::align::{Gap, GapValue};
+use crate::properties::columns::{Columns, ColumnWidth};
+use crate::properties::grid::{TrackBreadth, TrackSize, TrackRepeat, TrackListItem, TrackList, TrackSizing, MsTrackListItem, MsTrackList};
+use crate::properties::content::{Content, ContentItem};
+use crate::properties::flex::Flex;
+use crate::properties::font::{Font, FontSize, LineHeight};
+use crate::properties::list::ListStyle;
+use crate::properties::motion::{Offset, OffsetPath, OffsetPosition, OffsetAnchor};
+use crate::properties::transform::{TransformList, Transform, Translate, Perspective, PerspectiveOrigin, TransformOrigin};
+use crate::values::length::{LengthValue, Length, LengthPercentageOrAuto, LengthOrNumber};
+use crate::values::percentage::DimensionPercentage;
+use crate::values::calc::{Calc, MathFunction};
+use crate::values::angle::Angle;
+use crate::values::color::CssColor;
+use crate::values::url::Url;
+use crate::values::image::{Image, ImageSet, Gradient, LinearGradient, RadialGradient, ConicGradient, EndingShape, Circle, Ellipse, GradientItem, ColorStop};
+use crate::values::position::{Position, PositionComponent};
+use crate::values::size::{Size, MinMaxSize, Size2D};
+use crate::values::rect::Rect;
+use crate::values::shape::{BasicShape, ShapeRadius};
+
+/// Callbacks for the nodes of a stylesheet, which may modify them, e.g. to rewrite URLs or
+/// convert units. Each callback is called before the nodes within the node are visited, and
+/// does nothing by default.
+pub trait Visitor {
+  fn visit_rule(&mut self, _rule: &mut CssRule) {}
+  fn visit_declaration(&mut self, _declaration: &mut Declaration) {}
+  /// Called for each length, including those within `calc()` expressions.
+  fn visit_length(&mut self, _length: &mut LengthValue) {}
+  fn visit_color(&mut self, _color: &mut CssColor) {}
+  /// Called for each `url()`, e.g. in images and `@font-face` sources. The URLs of `@import`
+  /// rules are strings, and can be changed in `visit_rule`.
+  fn visit_url(&mut self, _url: &mut Url) {}
+  /// Called for each token of a value that could not be parsed ahead of time, e.g. because it
+  /// contains an `env()` function. The values of custom properties are not tokenized.
+  fn visit_token(&mut self, _token: &mut TokenOrValue) {}
+}
+
+/// A node that can be traversed by a `Visitor`.
+pub trait Visit {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V);
+}
+
+impl Visit for StyleSheet {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.rules.visit(visitor);
+  }
+}
+
+impl Visit for StyleAttribute {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.declarations.visit(visitor);
+  }
+}
+
+impl Visit for CssRule {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    visitor.visit_rule(self);
+    match self {
+      CssRule::Media(media) => media.rules.visit(visitor),
+      CssRule::Supports(supports) => supports.rules.visit(visitor),
+      CssRule::Style(style) => {
+        style.declarations.visit(visitor);
+        style.rules.visit(visitor);
+      }
+      CssRule::Keyframes(keyframes) => {
+        for keyframe in keyframes.keyframes.iter_mut() {
+          keyframe.declarations.visit(visitor);
+        }
+      }
+      CssRule::Page(page) => page.declarations.visit(visitor),
+      CssRule::FontFace(font_face) => {
+        for property in font_face.properties.iter_mut() {
+          if let FontFaceProperty::Source(sources) = property {
+            for source in sources.iter_mut() {
+              if let Source::Url(source) = source {
+                source.url.visit(visitor);
+              }
+            }
+          }
+        }
+      }
+      _ => {}
+    }
+  }
+}
+
+impl Visit for DeclarationBlock {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.declarations.visit(visitor);
+  }
+}
+
+impl Visit for Declaration {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    visitor.visit_declaration(self);
+    self.property.visit(visitor);
+  }
+}
+
+impl Visit for Property {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      Property::Color(color) |
+      Property::BackgroundColor(color) |
+      Property::BorderTopColor(color) |
+      Property::BorderBottomColor(color) |
+      Property::BorderLeftColor(color) |
+      Property::BorderRightColor(color) |
+      Property::BorderBlockColor(color) |
+      Property::BorderBlockStartColor(color) |
+      Property::BorderBlockEndColor(color) |
+      Property::BorderInlineColor(color) |
+      Property::BorderInlineStartColor(color) |
+      Property::BorderInlineEndColor(color) |
+      Property::OutlineColor(color) |
+      Property::ColumnRuleColor(color, _) |
+      Property::TextDecorationColor(color, _) |
+      Property::TextEmphasisColor(color, _) => color.visit(visitor),
+      Property::BorderColor(colors) => colors.visit(visitor),
+      Property::Background(backgrounds) => backgrounds.visit(visitor),
+      Property::BackgroundImage(images) => images.visit(visitor),
+      Property::BackgroundPosition(positions) => positions.visit(visitor),
+      Property::BackgroundPositionX(positions) => positions.visit(visitor),
+      Property::BackgroundPositionY(positions) => positions.visit(visitor),
+      Property::BackgroundSize(sizes) => sizes.visit(visitor),
+      Property::BorderImageSource(image) |
+      Property::ListStyleImage(image) => image.visit(visitor),
+      Property::BorderImage(border_image, _) => border_image.visit(visitor),
+      Property::BorderImageWidth(width) => width.visit(visitor),
+      Property::BorderImageOutset(outset) => outset.visit(visitor),
+      Property::Border(border) |
+      Property::BorderTop(border) |
+      Property::BorderBottom(border) |
+      Property::BorderLeft(border) |
+      Property::BorderRight(border) |
+      Property::BorderBlock(border) |
+      Property::BorderBlockStart(border) |
+      Property::BorderBlockEnd(border) |
+      Property::BorderInline(border) |
+      Property::BorderInlineStart(border) |
+      Property::BorderInlineEnd(border) |
+      Property::ColumnRule(border, _) => border.visit(visitor),
+      Property::Outline(outline) => outline.visit(visitor),
+      Property::BorderWidth(widths) => widths.visit(visitor),
+      Property::BorderTopWidth(width) |
+      Property::BorderBottomWidth(width) |
+      Property::BorderLeftWidth(width) |
+      Property::BorderRightWidth(width) |
+      Property::BorderBlockWidth(width) |
+      Property::BorderBlockStartWidth(width) |
+      Property::BorderBlockEndWidth(width) |
+      Property::BorderInlineWidth(width) |
+      Property::BorderInlineStartWidth(width) |
+      Property::BorderInlineEndWidth(width) |
+      Property::OutlineWidth(width) |
+      Property::ColumnRuleWidth(width, _) => width.visit(visitor),
+      Property::BorderRadius(radius, _) => radius.visit(visitor),
+      Property::BorderTopLeftRadius(radius, _) |
+      Property::BorderTopRightRadius(radius, _) |
+      Property::BorderBottomLeftRadius(radius, _) |
+      Property::BorderBottomRightRadius(radius, _) |
+      Property::BorderStartStartRadius(radius) |
+      Property::BorderStartEndRadius(radius) |
+      Property::BorderEndStartRadius(radius) |
+      Property::BorderEndEndRadius(radius) => radius.visit(visitor),
+      Property::BoxShadow(shadows, _) => shadows.visit(visitor),
+      Property::TextShadow(shadows) => shadows.visit(visitor),
+      Property::Width(size) |
+      Property::Height(size) |
+      Property::BlockSize(size) |
+      Property::InlineSize(size) => size.visit(visitor),
+      Property::MinWidth(size) |
+      Property::MinHeight(size) |
+      Property::MinBlockSize(size) |
+      Property::MinInlineSize(size) |
+      Property::MaxWidth(size) |
+      Property::MaxHeight(size) |
+      Property::MaxBlockSize(size) |
+      Property::MaxInlineSize(size) => size.visit(visitor),
+      Property::Top(value) |
+      Property::Bottom(value) |
+      Property::Left(value) |
+      Property::Right(value) |
+      Property::InsetBlockStart(value) |
+      Property::InsetBlockEnd(value) |
+      Property::InsetInlineStart(value) |
+      Property::InsetInlineEnd(value) |
+      Property::MarginTop(value) |
+      Property::MarginBottom(value) |
+      Property::MarginLeft(value) |
+      Property::MarginRight(value) |
+      Property::MarginBlockStart(value) |
+      Property::MarginBlockEnd(value) |
+      Property::MarginInlineStart(value) |
+      Property::MarginInlineEnd(value) |
+      Property::PaddingTop(value) |
+      Property::PaddingBottom(value) |
+      Property::PaddingLeft(value) |
+      Property::PaddingRight(value) |
+      Property::PaddingBlockStart(value) |
+      Property::PaddingBlockEnd(value) |
+      Property::PaddingInlineStart(value) |
+      Property::PaddingInlineEnd(value) |
+      Property::ScrollMarginTop(value) |
+      Property::ScrollMarginBottom(value) |
+      Property::ScrollMarginLeft(value) |
+      Property::ScrollMarginRight(value) |
+      Property::ScrollMarginBlockStart(value) |
+      Property::ScrollMarginBlockEnd(value) |
+      Property::ScrollMarginInlineStart(value) |
+      Property::ScrollMarginInlineEnd(value) |
+      Property::ScrollPaddingTop(value) |
+      Property::ScrollPaddingBottom(value) |
+      Property::ScrollPaddingLeft(value) |
+      Property::ScrollPaddingRight(value) |
+      Property::ScrollPaddingBlockStart(value) |
+      Property::ScrollPaddingBlockEnd(value) |
+      Property::ScrollPaddingInlineStart(value) |
+      Property::ScrollPaddingInlineEnd(value) |
+      Property::TextUnderlineOffset(value) |
+      Property::FlexBasis(value, _) |
+      Property::FlexPreferredSize(value, _) => value.visit(visitor),
+      Property::Inset(rect) |
+      Property::Margin(rect) |
+      Property::Padding(rect) |
+      Property::ScrollMargin(rect) |
+      Property::ScrollPadding(rect) => rect.visit(visitor),
+      Property::InsetBlock(size) |
+      Property::InsetInline(size) |
+      Property::MarginBlock(size) |
+      Property::MarginInline(size) |
+      Property::PaddingBlock(size) |
+      Property::PaddingInline(size) |
+      Property::ScrollMarginBlock(size) |
+      Property::ScrollMarginInline(size) |
+      Property::ScrollPaddingBlock(size) |
+      Property::ScrollPaddingInline(size) => size.visit(visitor),
+      Property::RowGap(gap) |
+      Property::GridRowGap(gap) |
+      Property::GridColumnGap(gap) |
+      Property::ColumnGap(gap, _) => gap.visit(visitor),
+      Property::Gap(gap) |
+      Property::GridGap(gap) => gap.visit(visitor),
+      Property::ColumnWidth(width, _) => width.visit(visitor),
+      Property::Columns(columns, _) => columns.visit(visitor),
+      Property::GridTemplateColumns(sizing) |
+      Property::GridTemplateRows(sizing) => sizing.visit(visitor),
+      Property::MsGridColumns(list) |
+      Property::MsGridRows(list) => list.visit(visitor),
+      Property::Content(content) => content.visit(visitor),
+      Property::Flex(flex, _) => flex.visit(visitor),
+      Property::Font(font) => font.visit(visitor),
+      Property::FontSize(size) => size.visit(visitor),
+      Property::LineHeight(line_height) => line_height.visit(visitor),
+      Property::ListStyle(list_style) => list_style.visit(visitor),
+      Property::OffsetPath(path) |
+      Property::MotionPath(path) => path.visit(visitor),
+      Property::OffsetDistance(distance) |
+      Property::MotionOffset(distance) => distance.visit(visitor),
+      Property::OffsetPosition(position) => position.visit(visitor),
+      Property::OffsetAnchor(anchor) => anchor.visit(visitor),
+      Property::Offset(offset) => offset.visit(visitor),
+      Property::TextDecoration(decoration, _) => decoration.visit(visitor),
+      Property::TextDecorationThickness(thickness) => thickness.visit(visitor),
+      Property::TextEmphasis(emphasis, _) => emphasis.visit(visitor),
+      Property::Transform(transforms, _) => transforms.visit(visitor),
+      Property::TransformOrigin(origin, _) => origin.visit(visitor),
+      Property::Translate(translate) => translate.visit(visitor),
+      Property::Perspective(perspective, _) => perspective.visit(visitor),
+      Property::PerspectiveOrigin(origin, _) => origin.visit(visitor),
+      Property::Unparsed(unparsed) => unparsed.value.visit(visitor),
+      _ => {}
+    }
+  }
+}
+
+impl<T: Visit> Visit for Vec<T> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    for item in self.iter_mut() {
+      item.visit(visitor);
+    }
+  }
+}
+
+impl<A: smallvec::Array> Visit for SmallVec<A> where A::Item: Visit {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    for item in self.iter_mut() {
+      item.visit(visitor);
+    }
+  }
+}
+
+impl<T: Visit> Visit for Option<T> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let Some(value) = self {
+      value.visit(visitor);
+    }
+  }
+}
+
+impl<T: Visit> Visit for Box<T> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    (**self).visit(visitor);
+  }
+}
+
+impl<T: Visit> Visit for Rect<T> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.0.visit(visitor);
+    self.1.visit(visitor);
+    self.2.visit(visitor);
+    self.3.visit(visitor);
+  }
+}
+
+impl<T: Visit> Visit for Size2D<T> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.0.visit(visitor);
+    self.1.visit(visitor);
+  }
+}
+
+impl Visit for TokenList {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    for token in self.0.iter_mut() {
+      visitor.visit_token(token);
+      if let TokenOrValue::Env(env) = token {
+        env.fallback.visit(visitor);
+      }
+    }
+  }
+}
+
+impl Visit for LengthValue {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    visitor.visit_length(self);
+  }
+}
+
+impl Visit for CssColor {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    visitor.visit_color(self);
+  }
+}
+
+impl Visit for Url {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    visitor.visit_url(self);
+  }
+}
+
+// Angles have no nodes to visit, but are used within percentages in conic gradients.
+impl Visit for Angle {
+  fn visit<V: Visitor>(&mut self, _visitor: &mut V) {}
+}
+
+impl Visit for Length {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      Length::Value(value) => value.visit(visitor),
+      Length::Calc(calc) => calc.visit(visitor)
+    }
+  }
+}
+
+impl<D: Visit> Visit for DimensionPercentage<D> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      DimensionPercentage::Dimension(dimension) => dimension.visit(visitor),
+      DimensionPercentage::Percentage(_) => {}
+      DimensionPercentage::Calc(calc) => calc.visit(visitor)
+    }
+  }
+}
+
+impl<T: Visit> Visit for Calc<T> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      Calc::Value(value) => value.visit(visitor),
+      Calc::Number(_) => {}
+      Calc::Sum(a, b) => {
+        a.visit(visitor);
+        b.visit(visitor);
+      }
+      Calc::Function(function) => function.visit(visitor)
+    }
+  }
+}
+
+impl<T: Visit> Visit for MathFunction<T> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      MathFunction::Calc(calc) => calc.visit(visitor),
+      MathFunction::Min(args) | MathFunction::Max(args) => args.visit(visitor),
+      MathFunction::Clamp(min, center, max) => {
+        min.visit(visitor);
+        center.visit(visitor);
+        max.visit(visitor);
+      }
+    }
+  }
+}
+
+impl Visit for LengthPercentageOrAuto {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let LengthPercentageOrAuto::LengthPercentage(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for LengthOrNumber {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let LengthOrNumber::Length(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for Size {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      Size::LengthPercentage(length) | Size::FitContent(length) => length.visit(visitor),
+      _ => {}
+    }
+  }
+}
+
+impl Visit for MinMaxSize {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      MinMaxSize::LengthPercentage(length) | MinMaxSize::FitContent(length) => length.visit(visitor),
+      _ => {}
+    }
+  }
+}
+
+impl Visit for Position {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.x.visit(visitor);
+    self.y.visit(visitor);
+  }
+}
+
+impl<S> Visit for PositionComponent<S> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      PositionComponent::Center => {}
+      PositionComponent::Length(length) => length.visit(visitor),
+      PositionComponent::Side(_, length) => length.visit(visitor)
+    }
+  }
+}
+
+impl Visit for Image {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      Image::None => {}
+      Image::Url(url) => url.visit(visitor),
+      Image::Gradient(gradient) => gradient.visit(visitor),
+      Image::ImageSet(image_set) => image_set.visit(visitor)
+    }
+  }
+}
+
+impl Visit for ImageSet {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    for option in self.options.iter_mut() {
+      option.image.visit(visitor);
+    }
+  }
+}
+
+impl Visit for Gradient {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      Gradient::Linear(gradient) | Gradient::RepeatingLinear(gradient) => gradient.visit(visitor),
+      Gradient::Radial(gradient) | Gradient::RepeatingRadial(gradient) => gradient.visit(visitor),
+      Gradient::Conic(gradient) | Gradient::RepeatingConic(gradient) => gradient.visit(visitor)
+    }
+  }
+}
+
+impl Visit for LinearGradient {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.items.visit(visitor);
+  }
+}
+
+impl Visit for RadialGradient {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match &mut self.shape {
+      EndingShape::Circle(Circle::Radius(radius)) => radius.visit(visitor),
+      EndingShape::Ellipse(Ellipse::Size(x, y)) => {
+        x.visit(visitor);
+        y.visit(visitor);
+      }
+      _ => {}
+    }
+    self.position.visit(visitor);
+    self.items.visit(visitor);
+  }
+}
+
+impl Visit for ConicGradient {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.position.visit(visitor);
+    self.items.visit(visitor);
+  }
+}
+
+impl<D: Visit> Visit for GradientItem<D> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      GradientItem::ColorStop(stop) => stop.visit(visitor),
+      GradientItem::Hint(hint) => hint.visit(visitor)
+    }
+  }
+}
+
+impl<D: Visit> Visit for ColorStop<D> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.color.visit(visitor);
+    self.position.visit(visitor);
+  }
+}
+
+impl Visit for BasicShape {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      BasicShape::Inset { rect, radius } => {
+        rect.visit(visitor);
+        radius.visit(visitor);
+      }
+      BasicShape::Circle { radius, position } => {
+        radius.visit(visitor);
+        position.visit(visitor);
+      }
+      BasicShape::Ellipse { x, y, position } => {
+        x.visit(visitor);
+        y.visit(visitor);
+        position.visit(visitor);
+      }
+      BasicShape::Polygon { points, .. } => {
+        for (x, y) in points.iter_mut() {
+          x.visit(visitor);
+          y.visit(visitor);
+        }
+      }
+    }
+  }
+}
+
+impl Visit for ShapeRadius {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let ShapeRadius::LengthPercentage(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for BorderSideWidth {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let BorderSideWidth::Length(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl<S> Visit for GenericBorder<S> {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.width.visit(visitor);
+    self.color.visit(visitor);
+  }
+}
+
+impl Visit for BorderRadius {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.top_left.visit(visitor);
+    self.top_right.visit(visitor);
+    self.bottom_right.visit(visitor);
+    self.bottom_left.visit(visitor);
+  }
+}
+
+impl Visit for BorderCornerRadius {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.0.visit(visitor);
+  }
+}
+
+impl Visit for BorderImage {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.source.visit(visitor);
+    self.width.visit(visitor);
+    self.outset.visit(visitor);
+  }
+}
+
+impl Visit for BorderImageSideWidth {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let BorderImageSideWidth::LengthPercentage(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for Background {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.image.visit(visitor);
+    self.color.visit(visitor);
+    self.position.visit(visitor);
+    self.size.visit(visitor);
+  }
+}
+
+impl Visit for BackgroundSize {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let BackgroundSize::Explicit { width, height } = self {
+      width.visit(visitor);
+      height.visit(visitor);
+    }
+  }
+}
+
+impl Visit for BoxShadow {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.color.visit(visitor);
+    self.x_offset.visit(visitor);
+    self.y_offset.visit(visitor);
+    self.blur.visit(visitor);
+    self.spread.visit(visitor);
+  }
+}
+
+impl Visit for TextShadow {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.color.visit(visitor);
+    self.x_offset.visit(visitor);
+    self.y_offset.visit(visitor);
+    self.blur.visit(visitor);
+  }
+}
+
+impl Visit for TextDecoration {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.thickness.visit(visitor);
+    self.color.visit(visitor);
+  }
+}
+
+impl Visit for TextDecorationThickness {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let TextDecorationThickness::LengthPercentage(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for TextEmphasis {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.color.visit(visitor);
+  }
+}
+
+impl Visit for GapValue {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let GapValue::LengthPercentage(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for Gap {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.row.visit(visitor);
+    self.column.visit(visitor);
+  }
+}
+
+impl Visit for ColumnWidth {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let ColumnWidth::Length(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for Columns {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.width.visit(visitor);
+  }
+}
+
+impl Visit for TrackBreadth {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let TrackBreadth::Length(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for TrackSize {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      TrackSize::TrackBreadth(breadth) => breadth.visit(visitor),
+      TrackSize::MinMax(min, max) => {
+        min.visit(visitor);
+        max.visit(visitor);
+      }
+      TrackSize::FitContent(length) => length.visit(visitor)
+    }
+  }
+}
+
+impl Visit for TrackRepeat {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.track_sizes.visit(visitor);
+  }
+}
+
+impl Visit for TrackListItem {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      TrackListItem::TrackSize(size) => size.visit(visitor),
+      TrackListItem::TrackRepeat(repeat) => repeat.visit(visitor)
+    }
+  }
+}
+
+impl Visit for TrackList {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.items.visit(visitor);
+  }
+}
+
+impl Visit for TrackSizing {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let TrackSizing::TrackList(list) = self {
+      list.visit(visitor);
+    }
+  }
+}
+
+impl Visit for MsTrackListItem {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      MsTrackListItem::TrackSize(size) => size.visit(visitor),
+      MsTrackListItem::Repeat(sizes, _) => sizes.visit(visitor)
+    }
+  }
+}
+
+impl Visit for MsTrackList {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.0.visit(visitor);
+  }
+}
+
+impl Visit for Content {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let Content::List(items, alt) = self {
+      items.visit(visitor);
+      alt.visit(visitor);
+    }
+  }
+}
+
+impl Visit for ContentItem {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let ContentItem::Image(image) = self {
+      image.visit(visitor);
+    }
+  }
+}
+
+impl Visit for Flex {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.basis.visit(visitor);
+  }
+}
+
+impl Visit for Font {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.size.visit(visitor);
+    self.line_height.visit(visitor);
+  }
+}
+
+impl Visit for FontSize {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let FontSize::Length(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for LineHeight {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let LineHeight::Length(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for ListStyle {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.image.visit(visitor);
+  }
+}
+
+impl Visit for OffsetPath {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      OffsetPath::Shape(shape) => shape.visit(visitor),
+      OffsetPath::Url(url) => url.visit(visitor),
+      _ => {}
+    }
+  }
+}
+
+impl Visit for OffsetPosition {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let OffsetPosition::Position(position) = self {
+      position.visit(visitor);
+    }
+  }
+}
+
+impl Visit for OffsetAnchor {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let OffsetAnchor::Position(position) = self {
+      position.visit(visitor);
+    }
+  }
+}
+
+impl Visit for Offset {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.position.visit(visitor);
+    self.path.visit(visitor);
+    self.distance.visit(visitor);
+    self.anchor.visit(visitor);
+  }
+}
+
+impl Visit for TransformList {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.0.visit(visitor);
+  }
+}
+
+impl Visit for Transform {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    match self {
+      Transform::Translate(x, y) => {
+        x.visit(visitor);
+        y.visit(visitor);
+      }
+      Transform::TranslateX(x) => x.visit(visitor),
+      Transform::TranslateY(y) => y.visit(visitor),
+      Transform::TranslateZ(z) => z.visit(visitor),
+      Transform::Translate3d(x, y, z) => {
+        x.visit(visitor);
+        y.visit(visitor);
+        z.visit(visitor);
+      }
+      Transform::Perspective(length) => length.visit(visitor),
+      _ => {}
+    }
+  }
+}
+
+impl Visit for Translate {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let Translate::XYZ(x, y, z) = self {
+      x.visit(visitor);
+      y.visit(visitor);
+      z.visit(visitor);
+    }
+  }
+}
+
+impl Visit for Perspective {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    if let Perspective::Length(length) = self {
+      length.visit(visitor);
+    }
+  }
+}
+
+impl Visit for PerspectiveOrigin {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.0.visit(visitor);
+  }
+}
+
+impl Visit for TransformOrigin {
+  fn visit<V: Visitor>(&mut self, visitor: &mut V) {
+    self.position.visit(visitor);
+    self.z.visit(visitor);
+  }
+}
@@ -0,0 +1,162 @@
+use cssparser::*;
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use crate::compat;
+use crate::properties::prefixes::Browsers;
+
+/// A problem found while parsing that does not prevent the stylesheet from being parsed,
+/// but likely indicates a mistake, e.g. a declaration that browsers will ignore.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WarningKind {
+  /// A `border-radius` property contains a negative radius, which is invalid.
+  NegativeBorderRadius,
+  /// A declaration of a known property has a value that could not be parsed.
+  /// Only reported by `validate`.
+  InvalidValue,
+  /// A declaration could not be parsed, e.g. because of an invalid `!important` flag,
+  /// and was dropped. Only reported by `validate`.
+  InvalidDeclaration,
+  /// A rule could not be parsed, and was dropped. Only reported by `validate`.
+  InvalidRule,
+  /// A media query could not be parsed, and was replaced with `not all`.
+  /// Only reported by `validate`.
+  InvalidMediaQuery,
+  /// A property name that is not known is referenced, e.g. in `will-change`.
+  /// Only reported by `validate`.
+  UnknownProperty,
+  /// A feature is used that is not supported by the targets, and cannot be compiled
+  /// into something that is. Only reported by `validate`.
+  UnsupportedFeature(compat::Feature),
+  /// A grid property cannot be translated to the legacy `-ms-` grid syntax needed by the
+  /// targets, e.g. because it relies on auto-placement or gaps. Only reported by `validate`.
+  UnsupportedMsGrid,
+  /// The stylesheet declares an encoding other than UTF-8 with `@charset`, which is
+  /// ignored since the code is always parsed as UTF-8. Only reported by `validate`.
+  UnsupportedCharset,
+  /// A `composes` declaration is used in a CSS module rule whose selectors are not each
+  /// a single class, e.g. `.a .b`, so it cannot be resolved. Only reported by `validate`.
+  InvalidComposes
+}
+
+impl fmt::Display for WarningKind {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      WarningKind::NegativeBorderRadius => f.write_str("Negative border radius"),
+      WarningKind::InvalidValue => f.write_str("Invalid property value"),
+      WarningKind::InvalidDeclaration => f.write_str("Invalid declaration"),
+      WarningKind::InvalidRule => f.write_str("Invalid rule"),
+      WarningKind::InvalidMediaQuery => f.write_str("Invalid media query"),
+      WarningKind::UnknownProperty => f.write_str("Unknown property"),
+      WarningKind::UnsupportedFeature(feature) => write!(f, "{:?} is not supported by the targets", feature),
+      WarningKind::UnsupportedMsGrid => f.write_str("Grid property cannot be translated to the -ms- grid syntax needed by the targets"),
+      WarningKind::UnsupportedCharset => f.write_str("Only UTF-8 is supported in @charset rules"),
+      WarningKind::InvalidComposes => f.write_str("composes is only allowed in rules whose selectors are each a single class")
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+  pub kind: WarningKind,
+  /// The line of the invalid value, starting at 1.
+  pub line: u32,
+  /// The column of the invalid value, starting at 1.
+  pub column: u32
+}
+
+impl Warning {
+  /// Returns a description of the problem, without the location.
+  pub fn message(&self) -> String {
+    self.kind.to_string()
+  }
+}
+
+impl fmt::Display for Warning {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    write!(f, "{} at {}:{}", self.kind, self.line, self.column)
+  }
+}
+
+// Like the limits, warnings are collected per thread since values are parsed
+// through the `Parse` trait, which has no context parameter.
+thread_local! {
+  static WARNINGS: RefCell<Option<Vec<Warning>>> = RefCell::new(None);
+  /// Set within `validate`, to the targets that compatibility is checked against.
+  static VALIDATION: Cell<Option<Option<Browsers>>> = Cell::new(None);
+  /// Set within `recover`.
+  static RECOVERY: Cell<bool> = Cell::new(false);
+}
+
+/// Runs the given function, and returns its result along with the warnings reported while it ran.
+pub fn collect<T, F: FnOnce() -> T>(f: F) -> (T, Vec<Warning>) {
+  let prev = WARNINGS.with(|warnings| std::mem::replace(&mut *warnings.borrow_mut(), Some(vec![])));
+  let res = f();
+  let warnings = WARNINGS.with(|warnings| std::mem::replace(&mut *warnings.borrow_mut(), prev));
+  (res, warnings.unwrap_or_default())
+}
+
+/// Like `collect`, but also enables the diagnostics that are only useful when validating,
+/// e.g. for invalid rules and features that are not supported by the targets.
+pub fn validate<T, F: FnOnce() -> T>(targets: Option<Browsers>, f: F) -> (T, Vec<Warning>) {
+  let prev = VALIDATION.with(|validation| validation.replace(Some(targets)));
+  let res = collect(f);
+  VALIDATION.with(|validation| validation.set(prev));
+  res
+}
+
+/// Like `validate`, but also drops declarations with invalid values rather than keeping
+/// them as is, as browsers do when they ignore them.
+/// https://www.w3.org/TR/css-syntax-3/#error-handling
+pub fn recover<T, F: FnOnce() -> T>(targets: Option<Browsers>, f: F) -> (T, Vec<Warning>) {
+  let prev = RECOVERY.with(|recovery| recovery.replace(true));
+  let res = validate(targets, f);
+  RECOVERY.with(|recovery| recovery.set(prev));
+  res
+}
+
+/// Returns whether warnings are being collected within `validate`.
+pub fn is_validating() -> bool {
+  VALIDATION.with(|validation| validation.get().is_some())
+}
+
+/// Returns whether invalid declarations are being dropped within `recover`.
+pub fn is_recovering() -> bool {
+  RECOVERY.with(|recovery| recovery.get())
+}
+
+/// Returns the number of warnings collected so far.
+pub(crate) fn len() -> usize {
+  WARNINGS.with(|warnings| warnings.borrow().as_ref().map_or(0, |warnings| warnings.len()))
+}
+
+/// Returns the targets that compatibility is checked against within `validate`, if any.
+pub fn validation_targets() -> Option<Browsers> {
+  VALIDATION.with(|validation| validation.get()).flatten()
+}
+
+/// Reports an `UnsupportedFeature` warning if validating against targets that don't support the feature.
+pub fn check_feature(location: SourceLocation, feature: compat::Feature) {
+  if let Some(targets) = validation_targets() {
+    if !feature.is_compatible(targets) {
+      warn(location, WarningKind::UnsupportedFeature(feature))
+    }
+  }
+}
+
+/// Reports a warning at the given location. This does nothing unless called within `collect`.
+pub fn warn(location: SourceLocation, kind: WarningKind) {
+  WARNINGS.with(|warnings| {
+    if let Some(warnings) = &mut *warnings.borrow_mut() {
+      let warning = Warning {
+        kind,
+        line: location.line + 1,
+        column: location.column
+      };
+
+      // Values may be parsed more than once, e.g. when trying alternative grammars.
+      if !warnings.contains(&warning) {
+        warnings.push(warning);
+      }
+    }
+  })
+}
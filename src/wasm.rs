@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+use crate::css_modules::CssModuleExports;
+use crate::{Browsers, StyleSheet, ParserOptions, MinifyOptions, PrinterOptions};
+
+#[derive(Deserialize)]
+struct Config {
+  code: String,
+  minify: Option<bool>,
+  targets: Option<Browsers>,
+  css_modules: Option<bool>
+}
+
+#[derive(Serialize)]
+struct TransformResult {
+  code: String,
+  exports: Option<CssModuleExports>
+}
+
+/// Transforms the code of a stylesheet, e.g. `transform({ code, minify: true, targets: { chrome: 95 << 16 } })`,
+/// and returns an object with the resulting `code`, and the `exports` when compiling a CSS module.
+/// Throws if the options are invalid.
+#[wasm_bindgen]
+pub fn transform(config: JsValue) -> Result<JsValue, JsValue> {
+  let config: Config = serde_wasm_bindgen::from_value(config)?;
+  let mut stylesheet = StyleSheet::parse(&config.code, ParserOptions {
+    css_modules: config.css_modules.unwrap_or(false),
+    ..ParserOptions::default()
+  });
+  stylesheet.minify(MinifyOptions {
+    targets: config.targets,
+    ..MinifyOptions::default()
+  });
+  let res = stylesheet.to_css(PrinterOptions {
+    minify: config.minify.unwrap_or(true),
+    targets: config.targets,
+    ..PrinterOptions::default()
+  }).map_err(|_| JsValue::from_str("the stylesheet could not be printed"))?;
+
+  Ok(serde_wasm_bindgen::to_value(&TransformResult { code: res.code, exports: res.exports })?)
+}
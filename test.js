@@ -12,7 +12,7 @@ if (process.argv[process.argv.length - 1] !== __filename) {
   console.time('optimize');
   let r = css.transform(opts);
   console.timeEnd('optimize')
-  console.log(r.toString());
+  console.log(r.code.toString());
   return;
 }
 
@@ -30,4 +30,4 @@ let res = css.transform({
 }
 `)});
 
-console.log(res.toString());
+console.log(res.code.toString());